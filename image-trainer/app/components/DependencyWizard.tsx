@@ -2,6 +2,7 @@
 
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../lib/errors";
 
 type DependencyStatus = {
   python: boolean;
@@ -24,14 +25,8 @@ export default function DependencyWizard({ onComplete }: { onComplete: () => voi
     console.log("DependencyWizard mounted, starting checkDeps()");
     async function checkDeps() {
         try {
-            const raw: string = await invoke("check_dependencies");
-            let parsed: DependencyStatus;
-            try {
-                parsed = JSON.parse(raw);
-            } catch (e) {
-                parsed = { python: false, version: null, pandas: false, sklearn: false, torch: false, timm: false, optuna: false, error: raw };
-            }
-            
+            const parsed = await invoke<DependencyStatus>("check_dependencies");
+
             console.log("check_dependencies result:", parsed);
             setStatus(parsed);
             
@@ -49,7 +44,7 @@ export default function DependencyWizard({ onComplete }: { onComplete: () => voi
             }
         } catch (e) {
             console.error("Failed to invoke check_dependencies", e);
-            setStatus({ python: false, version: null, pandas: false, sklearn: false, torch: false, timm: false, optuna: false, error: String(e) });
+            setStatus({ python: false, version: null, pandas: false, sklearn: false, torch: false, timm: false, optuna: false, error: errorMessage(e) });
             setChecking(false);
         }
     }
@@ -0,0 +1,17 @@
+/** Error object rejected by backend commands; see `errors` in the API descriptor. */
+export type BackendError =
+  | { kind: 'python_not_found'; message: string }
+  | { kind: 'script_not_found'; message: string; path: string }
+  | { kind: 'script_failed'; message: string; code: number | null; stderr: string }
+  | { kind: 'invalid_input'; message: string }
+  | { kind: 'io'; message: string }
+  | { kind: 'other'; message: string };
+
+export function isBackendError(err: unknown): err is BackendError {
+  return typeof err === 'object' && err !== null && 'kind' in err && 'message' in err;
+}
+
+/** Text to show for a rejected `invoke`, whether the backend sent an error object or a string. */
+export function errorMessage(err: unknown): string {
+  return isBackendError(err) ? err.message : String(err);
+}
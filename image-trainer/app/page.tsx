@@ -12,6 +12,7 @@ import { twMerge } from 'tailwind-merge';
 import { save } from '@tauri-apps/plugin-dialog';
 import { writeTextFile } from '@tauri-apps/plugin-fs';
 import DependencyWizard from './components/DependencyWizard';
+import { errorMessage } from './lib/errors';
 
 function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
@@ -77,7 +78,7 @@ type TabularResult = {
   shape?: [number, number];
   dtypes?: Record<string, string>;
   missing?: Record<string, number>;
-  loaded_path?: string;
+  file_path?: string;
 };
 type TabAction = 'load' | 'drop_missing' | 'fill_missing' | 'label_encode' | 'one_hot_encode';
 type FillMethod = 'mean' | 'median' | 'mode' | 'zero';
@@ -122,6 +123,8 @@ export default function Home() {
   const [tabOutPath, setTabOutPath] = useState('');
   const [tabLoading, setTabLoading] = useState(false);
   const [tabResult, setTabResult] = useState<TabularResult | null>(null);
+  const [tabSession] = useState(() => crypto.randomUUID());
+  const [tabPipeline, setTabPipeline] = useState<{ steps: unknown[]; undone: unknown[] } | null>(null);
   const [systemInfo, setSystemInfo] = useState<any | null>(null);
   const [systemLoading, setSystemLoading] = useState(false);
   const [systemError, setSystemError] = useState<string | null>(null);
@@ -164,7 +167,7 @@ export default function Home() {
   }, [selectedEnv]);
 
   const pickTabFile = useCallback(async () => {
-    const selected = await open({ multiple: false, filters: [{ name: 'Data Files', extensions: ['csv', 'xlsx', 'xls'] }] });
+    const selected = await open({ multiple: false, filters: [{ name: 'Data Files', extensions: ['csv', 'tsv', 'parquet', 'feather', 'arrow', 'xlsx', 'xls'] }] });
     if (typeof selected === 'string') setTabFile(selected);
   }, []);
 
@@ -173,26 +176,38 @@ export default function Home() {
     setTabLoading(true);
     setTabResult(null);
     try {
-      const isProcess = tabAction !== 'load';
-      let paramsJson: string | undefined;
-      if (isProcess) {
-        const p: Record<string, unknown> = { operation: tabAction };
-        if (tabAction === 'fill_missing') p.method = fillMethod;
+      let request: Record<string, unknown> = { action: 'load', file: tabFile };
+      if (tabAction !== 'load') {
+        const operation: Record<string, unknown> = { operation: tabAction };
+        if (tabAction === 'fill_missing') operation.method = fillMethod;
         if (tabAction === 'label_encode' || tabAction === 'one_hot_encode')
-          p.columns = encodeColumns.split(',').map(s => s.trim()).filter(Boolean);
-        paramsJson = JSON.stringify(p);
+          operation.columns = encodeColumns.split(',').map(s => s.trim()).filter(Boolean);
+        request = { action: 'process', file: tabFile, operation, out: tabOutPath || null };
       }
-      const raw: string = await invoke('run_tabular_processor', {
-        file: tabFile, action: isProcess ? 'process' : 'load',
-        params: paramsJson, out: tabOutPath || undefined,
-      });
-      setTabResult(JSON.parse(raw));
+      const result: Omit<TabularResult, 'status'> = await invoke('run_tabular_processor', { request, sessionId: tabSession });
+      setTabResult({ status: 'success', ...result });
+      setTabPipeline(await invoke('get_pipeline', { sessionId: tabSession }));
+    } catch (err) {
+      setTabResult({ status: 'error', message: errorMessage(err) });
+    } finally {
+      setTabLoading(false);
+    }
+  }, [tabFile, tabAction, fillMethod, encodeColumns, tabOutPath, tabSession]);
+
+  const stepTabular = useCallback(async (command: 'undo_tabular_step' | 'redo_tabular_step') => {
+    setTabLoading(true);
+    try {
+      setTabPipeline(await invoke(command, { sessionId: tabSession }));
+      // Previewed outside the session, so loading the output does not start it over.
+      const request = { action: 'load', file: tabOutPath || tabFile };
+      const result: Omit<TabularResult, 'status'> = await invoke('run_tabular_processor', { request });
+      setTabResult({ status: 'success', ...result });
     } catch (err) {
-      setTabResult({ status: 'error', message: String(err) });
+      setTabResult({ status: 'error', message: errorMessage(err) });
     } finally {
       setTabLoading(false);
     }
-  }, [tabFile, tabAction, fillMethod, encodeColumns, tabOutPath]);
+  }, [tabFile, tabOutPath, tabSession]);
 
   const scanCondaEnvs = async () => {
     setScanningEnvs(true);
@@ -712,10 +727,9 @@ const fetchSystemInfo = async (initial = false) => {
   try {
     if (initial) setSystemLoading(true);
 
-    const raw = await invoke("get_system_info");
-    const parsed = JSON.parse(raw as string);
+    const info = await invoke("get_system_info");
 
-    setSystemInfo(parsed);
+    setSystemInfo(info);
     setSystemError(null);
   } catch (err) {
     setSystemError("Failed to load system information");
@@ -1632,7 +1646,7 @@ const InsightCard = ({ title, children }: any) => (
                    <div className="p-6 overflow-y-auto space-y-4">
                      <div>
                        <h3 className="text-sm font-medium text-zinc-400 uppercase tracking-widest mb-2">Tabular Data Processor</h3>
-                       <p className="text-xs text-zinc-500 mb-4">Load or transform CSV / Excel files via <code className="bg-zinc-900 px-1.5 py-0.5 rounded text-zinc-300">tabular_processor.py</code></p>
+                       <p className="text-xs text-zinc-500 mb-4">Load or transform CSV, Parquet, Feather or Excel files via <code className="bg-zinc-900 px-1.5 py-0.5 rounded text-zinc-300">tabular_processor.py</code></p>
                      </div>
                      <div className="flex gap-2">
                        <input id="data-file-path" type="text" value={tabFile} onChange={e => setTabFile(e.target.value)}
@@ -1685,6 +1699,16 @@ const InsightCard = ({ title, children }: any) => (
                      >
                        {tabLoading ? <><span className="inline-block w-4 h-4 border-2 border-current border-t-transparent rounded-full animate-spin" /> Processing…</> : 'Run'}
                      </button>
+                     {tabPipeline && (tabPipeline.steps.length > 0 || tabPipeline.undone.length > 0) && (
+                       <div className="flex gap-2">
+                         <button id="data-undo-btn" onClick={() => stepTabular('undo_tabular_step')} disabled={tabLoading || tabPipeline.steps.length === 0}
+                           className="flex-1 py-2 border border-zinc-800 hover:border-zinc-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-lg text-xs text-zinc-300 transition-all"
+                         >Undo ({tabPipeline.steps.length})</button>
+                         <button id="data-redo-btn" onClick={() => stepTabular('redo_tabular_step')} disabled={tabLoading || tabPipeline.undone.length === 0}
+                           className="flex-1 py-2 border border-zinc-800 hover:border-zinc-600 disabled:opacity-50 disabled:cursor-not-allowed rounded-lg text-xs text-zinc-300 transition-all"
+                         >Redo ({tabPipeline.undone.length})</button>
+                       </div>
+                     )}
                      {tabResult && (
                        <div>
                          {tabResult.status === 'error' ? (
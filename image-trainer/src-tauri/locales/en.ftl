@@ -0,0 +1,107 @@
+# English strings for messages produced by the Rust backend. This file is the
+# fallback for every other language: a message missing from a translation is
+# shown in English.
+
+## Job notifications (webhooks)
+
+job-started = EPOQ job { $job } started
+job-succeeded = EPOQ job { $job } finished successfully
+job-failed = EPOQ job { $job } failed (exit code { $code })
+job-cancelled = EPOQ job { $job } was cancelled
+job-event-detail = Event `{ $event }` for job `{ $job }`.
+
+## Batch prediction summary
+
+batch-title = Batch prediction summary
+batch-job = Job
+batch-model = Model
+batch-unknown-model = unknown
+batch-scored = Images scored
+batch-failed = Images failed
+batch-duration = Processing time
+batch-duration-value = { $seconds } s
+batch-rate = { $seconds } s ({ $rate } images/s)
+batch-low-confidence = Low confidence
+batch-low-confidence-value = { $count } below { $percent }%
+batch-class-counts = Class counts
+batch-class = Class
+batch-images = Images
+batch-share = Share
+batch-mean-confidence = Mean confidence
+batch-review-queue = Review queue
+batch-review-note = Least confident predictions first.
+batch-review-note-truncated = Least confident predictions first; showing { $shown } of { $total }.
+batch-file = File
+batch-predicted = Predicted
+batch-confidence = Confidence
+batch-runner-up = Runner-up
+batch-failures = Failures
+batch-threshold-range = The low-confidence threshold must be between 0 and 1.
+
+## Misclassified image gallery
+
+gallery-title = Misclassified images
+gallery-intro = Run: { $run } · { $count } misclassified test images, most confident first.
+gallery-actual = Actual
+gallery-predicted-as = Predicted as
+gallery-count = Count
+gallery-predicted = Predicted
+gallery-image-unavailable = (image unavailable)
+
+## Model cards
+
+card-title = Model card: { $name }
+card-model-id = Model id
+card-architecture = Architecture
+card-unknown = unknown
+card-generated = Generated
+card-generated-value = { $time } (unix time)
+card-intended-use = Intended use
+card-dataset = Dataset
+card-source = Source
+card-classes = Classes ({ $count })
+card-split-images = { $split } images
+card-training = Training
+card-parameter = Parameter
+card-value = Value
+card-evaluation = Evaluation
+card-no-evaluation = No test-set evaluation was recorded for this run.
+card-metric = Metric
+card-limitations = Limitations
+card-ethical-considerations = Ethical considerations
+
+## Training report
+
+report-title = Training report: { $name }
+report-run = Run
+report-generated = Generated
+report-configuration = Configuration
+report-classes = classes
+report-curves = Training curves
+report-no-curves = No per-epoch metrics were recorded for this run.
+report-loss = Loss
+report-accuracy = Accuracy
+report-train = train
+report-val = validation
+report-epoch = Epoch
+report-confusion-matrix = Confusion matrix
+report-matrix-note = Rows are the actual class, columns the predicted class.
+report-samples = Sample predictions
+report-samples-note = Misclassified test images, most confident first.
+report-pdf-browser = PDF export needs Google Chrome, Chromium or Microsoft Edge. Export as HTML and print it from a browser instead.
+
+## Errors
+# Only errors about the language setting, writing reports, and finding Python
+# and the backend scripts are localized. Errors from scripts, the OS and
+# libraries, and input validation, are shown as they are.
+
+error-unknown-language = No translation for "{ $language }" is available.
+error-write-file = Cannot write { $path }: { $error }
+error-script-missing = Backend script { $path } is missing; reinstall the app.
+error-python-not-found = No Python interpreter found.
+error-python-start = Failed to start { $python }: { $error }
+error-python-32-bit = { $python } is a 32-bit Python, which cannot load PyTorch. Install 64-bit Python.
+error-python-32-bit-pinned = { $python } is a 32-bit Python, which cannot load PyTorch. Install 64-bit Python { $version }.
+error-python-version = { $python } is Python { $found }, not the selected Python { $version }.
+error-py-launcher-version = The py launcher has no Python { $version } installed: { $error }
+error-xcode-stub = Only the macOS placeholder python3 was found, which asks to install the Xcode Command Line Tools instead of running Python. Install Python from python.org or Homebrew, or install the tools.
@@ -6,6 +6,7 @@ Outputs JSON status lines to stdout for the EPOQ frontend.
 import sys
 import json
 import os
+import time
 import argparse
 
 import optuna
@@ -17,12 +18,65 @@ import torch.optim as optim
 from torchvision import datasets, transforms
 from torch.utils.data import DataLoader, Subset
 
+from predict import load_image
+
+
+# Matches the transforms in build_dataloaders; stored with the best model.
+PREPROCESSING = {
+    "resize": 256,
+    "input_size": 224,
+    "mean": [0.485, 0.456, 0.406],
+    "std": [0.229, 0.224, 0.225],
+    "channel_order": "RGB",
+    "augmentation": None,
+}
+
 
 def emit(obj):
     """Print JSON to stdout for frontend consumption."""
     print(json.dumps(obj), flush=True)
 
 
+def save_best_model(output_dir, model, args, params, val_acc, trial_number, class_names):
+    """Writes the best trial's weights with a run manifest and preprocessing,
+    so the output folder is a model the app can register and predict with."""
+    os.makedirs(output_dir, exist_ok=True)
+    torch.save(model.state_dict(), os.path.join(output_dir, 'best_model.pth'))
+    with open(os.path.join(output_dir, 'preprocessing.json'), 'w') as f:
+        json.dump(PREPROCESSING, f, indent=2)
+    manifest = {
+        "experiment_id": args.study_name,
+        "config": {
+            "path": args.path,
+            "model": args.model,
+            "epochs": args.epochs_per_trial,
+            "learning_rate": params["learning_rate"],
+            "batch_size": params["batch_size"],
+            "optimizer": params["optimizer"],
+        },
+        "classes": class_names,
+        "dataset": {"path": args.path},
+        "hpo": {
+            "study_name": args.study_name,
+            "best_trial": trial_number,
+            "best_params": params,
+            "best_val_accuracy": val_acc,
+        },
+    }
+    with open(os.path.join(output_dir, 'run_manifest.json'), 'w') as f:
+        json.dump(manifest, f, indent=2)
+
+
+def wait_while_paused(args):
+    """Holds the next trial while the pause file exists. Returns False when
+    the study was asked to stop instead."""
+    while args.pause_file and os.path.exists(args.pause_file):
+        if args.stop_file and os.path.exists(args.stop_file):
+            return False
+        time.sleep(1)
+    return not (args.stop_file and os.path.exists(args.stop_file))
+
+
 def build_dataloaders(data_dir, batch_size, num_workers):
     """Build train/val DataLoaders reusing the same logic as script.py."""
     data_transforms = {
@@ -51,13 +105,13 @@ def build_dataloaders(data_dir, batch_size, num_workers):
     class_names = []
 
     if os.path.isdir(train_dir):
-        train_dataset = datasets.ImageFolder(train_dir, data_transforms['train'])
+        train_dataset = datasets.ImageFolder(train_dir, data_transforms['train'], loader=load_image)
         dataloaders['train'] = DataLoader(train_dataset, batch_size=batch_size, shuffle=True, num_workers=num_workers)
         dataset_sizes['train'] = len(train_dataset)
         class_names = train_dataset.classes
 
         if os.path.isdir(val_dir):
-            val_dataset = datasets.ImageFolder(val_dir, data_transforms['val'])
+            val_dataset = datasets.ImageFolder(val_dir, data_transforms['val'], loader=load_image)
             dataloaders['val'] = DataLoader(val_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers)
             dataset_sizes['val'] = len(val_dataset)
         else:
@@ -92,8 +146,8 @@ def build_dataloaders(data_dir, batch_size, num_workers):
             train_idx = subset_train.indices
             val_idx = subset_val.indices
 
-        dataset_train = datasets.ImageFolder(data_dir, data_transforms['train'])
-        dataset_val = datasets.ImageFolder(data_dir, data_transforms['val'])
+        dataset_train = datasets.ImageFolder(data_dir, data_transforms['train'], loader=load_image)
+        dataset_val = datasets.ImageFolder(data_dir, data_transforms['val'], loader=load_image)
 
         dataloaders['train'] = DataLoader(Subset(dataset_train, train_idx), batch_size=batch_size, shuffle=True, num_workers=num_workers)
         dataloaders['val'] = DataLoader(Subset(dataset_val, val_idx), batch_size=batch_size, shuffle=False, num_workers=num_workers)
@@ -103,11 +157,13 @@ def build_dataloaders(data_dir, batch_size, num_workers):
     return dataloaders, dataset_sizes, class_names, None
 
 
-def run_trial_training(model, dataloaders, dataset_sizes, device, optimizer, criterion, epochs):
-    """Run a short training and return best validation accuracy."""
+def run_trial_training(model, dataloaders, dataset_sizes, device, optimizer, criterion, epochs, on_epoch=None):
+    """Run a short training and return best validation accuracy. `on_epoch`
+    receives each epoch's number and per-phase loss and accuracy."""
     best_acc = 0.0
 
     for epoch in range(epochs):
+        epoch_metrics = {}
         for phase in ['train', 'val']:
             if dataset_sizes.get(phase, 0) == 0 or dataloaders.get(phase) is None:
                 continue
@@ -136,9 +192,12 @@ def run_trial_training(model, dataloaders, dataset_sizes, device, optimizer, cri
                 running_loss += loss.item() * inputs.size(0)
                 running_corrects += torch.sum(preds == labels.data)
 
+            epoch_metrics[f"{phase}_loss"] = running_loss / dataset_sizes[phase]
+            epoch_metrics[f"{phase}_accuracy"] = (running_corrects.double() / dataset_sizes[phase]).item()
             if phase == 'val':
-                epoch_acc = running_corrects.double() / dataset_sizes[phase]
-                best_acc = max(best_acc, epoch_acc.item())
+                best_acc = max(best_acc, epoch_metrics['val_accuracy'])
+        if on_epoch:
+            on_epoch(epoch + 1, epoch_metrics)
 
     return best_acc
 
@@ -153,6 +212,12 @@ def main():
     parser.add_argument('--n_trials', type=int, default=10, help='Number of Optuna trials')
     parser.add_argument('--epochs_per_trial', type=int, default=3, help='Epochs per trial')
     parser.add_argument('--num_workers', type=int, default=-1, help='DataLoader workers (-1=auto)')
+    parser.add_argument('--storage', type=str, default=None, help='Optuna storage URL shared by parallel sweep workers')
+    parser.add_argument('--study_name', type=str, default='epoq_automl', help='Study name within the shared storage')
+    parser.add_argument('--worker_id', type=int, default=0, help='Index of this worker when running in parallel')
+    parser.add_argument('--output_dir', type=str, default=None, help='Where the best trial is saved as a model, with hpo_study.json')
+    parser.add_argument('--pause_file', type=str, default=None, help='While this file exists, no new trial starts')
+    parser.add_argument('--stop_file', type=str, default=None, help='When this file appears, the study ends after the current trial')
     args = parser.parse_args()
 
     if not os.path.exists(args.path):
@@ -171,7 +236,7 @@ def main():
 
     device = torch.device("cuda:0" if torch.cuda.is_available() else "cpu")
 
-    emit({"status": "automl_started", "n_trials": args.n_trials, "device": str(device)})
+    emit({"status": "automl_started", "n_trials": args.n_trials, "device": str(device), "worker_id": args.worker_id})
 
     # Import model factory (same directory)
     import model_factory
@@ -191,11 +256,20 @@ def main():
 
     trial_results = []
 
+    best_saved = {"val_accuracy": -1.0}
+
     def objective(trial):
+        if not wait_while_paused(args):
+            trial.study.stop()
+            raise optuna.TrialPruned()
+
         # Suggest hyperparameters
         lr = trial.suggest_float("learning_rate", 1e-5, 1e-1, log=True)
         batch_size = trial.suggest_categorical("batch_size", [8, 16, 32, 64])
         optimizer_name = trial.suggest_categorical("optimizer", ["SGD", "Adam", "AdamW"])
+        params = {"learning_rate": lr, "batch_size": batch_size, "optimizer": optimizer_name}
+        emit({"status": "automl_trial_started", "trial": trial.number + 1, "n_trials": args.n_trials,
+              "params": params, "worker_id": args.worker_id})
 
         try:
             # Build dataloaders with this batch size
@@ -217,20 +291,24 @@ def main():
 
             criterion = nn.CrossEntropyLoss()
 
+            def on_epoch(epoch, metrics):
+                emit({"status": "automl_trial_epoch", "trial": trial.number + 1, "epoch": epoch,
+                      "total_epochs": args.epochs_per_trial, **metrics, "worker_id": args.worker_id})
+
             val_acc = run_trial_training(
-                model, dataloaders, dataset_sizes, device, opt, criterion, args.epochs_per_trial
+                model, dataloaders, dataset_sizes, device, opt, criterion, args.epochs_per_trial, on_epoch
             )
+            if args.output_dir and val_acc > best_saved["val_accuracy"]:
+                save_best_model(args.output_dir, model, args, params, val_acc, trial.number + 1, class_names)
+                best_saved["val_accuracy"] = val_acc
 
             trial_info = {
                 "status": "automl_trial",
                 "trial": trial.number + 1,
                 "n_trials": args.n_trials,
-                "params": {
-                    "learning_rate": lr,
-                    "batch_size": batch_size,
-                    "optimizer": optimizer_name
-                },
-                "val_accuracy": round(val_acc, 6)
+                "params": params,
+                "val_accuracy": round(val_acc, 6),
+                "worker_id": args.worker_id
             }
             emit(trial_info)
             trial_results.append(trial_info)
@@ -247,21 +325,40 @@ def main():
                 "status": "automl_trial_error",
                 "trial": trial.number + 1,
                 "n_trials": args.n_trials,
-                "message": str(e)
+                "message": str(e),
+                "worker_id": args.worker_id
             })
             return 0.0
 
     # Run the study
-    study = optuna.create_study(direction="maximize", study_name="epoq_automl")
+    callbacks = []
+    if args.storage:
+        # Parallel workers share one study; stop once the sweep as a whole hits n_trials
+        study = optuna.create_study(direction="maximize", study_name=args.study_name,
+                                    storage=args.storage, load_if_exists=True)
+        from optuna.study import MaxTrialsCallback
+        from optuna.trial import TrialState
+        callbacks.append(MaxTrialsCallback(args.n_trials, states=(TrialState.COMPLETE, TrialState.FAIL, TrialState.RUNNING)))
+    else:
+        study = optuna.create_study(direction="maximize", study_name=args.study_name)
 
     try:
-        study.optimize(objective, n_trials=args.n_trials, show_progress_bar=False)
+        study.optimize(objective, n_trials=args.n_trials, show_progress_bar=False, callbacks=callbacks)
     except Exception as e:
         emit({"status": "error", "message": f"Optuna study failed: {str(e)}"})
         return
 
     # Report best result
+    completed = [t for t in study.trials if t.state == optuna.trial.TrialState.COMPLETE]
+    if not completed:
+        emit({"status": "automl_complete", "best_params": None, "best_accuracy": None,
+              "total_trials": len(study.trials), "trials": trial_results, "worker_id": args.worker_id})
+        return
     best = study.best_trial
+    if args.output_dir:
+        os.makedirs(args.output_dir, exist_ok=True)
+        with open(os.path.join(args.output_dir, 'hpo_study.json'), 'w') as f:
+            json.dump({"best_params": best.params, "best_accuracy": best.value, "trials": trial_results}, f, indent=2)
     emit({
         "status": "automl_complete",
         "best_params": {
@@ -271,7 +368,8 @@ def main():
         },
         "best_accuracy": round(best.value, 6),
         "total_trials": len(study.trials),
-        "trials": trial_results
+        "trials": trial_results,
+        "worker_id": args.worker_id
     })
 
 
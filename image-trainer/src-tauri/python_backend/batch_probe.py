@@ -0,0 +1,63 @@
+import sys
+import json
+import argparse
+import torch
+import torch.nn as nn
+import torch.optim as optim
+
+import model_factory
+
+def main():
+    parser = argparse.ArgumentParser(description='Batch size probe')
+    parser.add_argument('--model', type=str, default='resnet18', help='Model type (same names as script.py)')
+    parser.add_argument('--architecture', type=str, default=None, help='JSON descriptor of a registered custom architecture')
+    parser.add_argument('--batch_size', type=int, required=True, help='Batch size to try')
+    parser.add_argument('--num_classes', type=int, default=2, help='Output classes for the head')
+    parser.add_argument('--freeze_mode', type=str, default='frozen', choices=['frozen', 'full', 'staged'], help='Freeze policy; staged is probed unfrozen since that is its peak')
+    parser.add_argument('--steps', type=int, default=2, help='Forward/backward passes to run')
+    args = parser.parse_args()
+
+    if not torch.cuda.is_available():
+        print(json.dumps({"status": "probe_skipped", "device": "cpu", "message": "CUDA is not available."}), flush=True)
+        return
+
+    device = torch.device("cuda:0")
+    architecture = json.loads(args.architecture) if args.architecture else None
+    if architecture is not None:
+        model, params = model_factory.create_custom_model(architecture, args.num_classes, device)
+        input_size = int(architecture.get('input_size', 224))
+    else:
+        model, params = model_factory.create_model(args.model, args.num_classes, device)
+        input_size = 224
+    if args.freeze_mode != 'frozen':
+        for param in model.parameters():
+            param.requires_grad = True
+        params = list(model.parameters())
+
+    optimizer = optim.SGD(params, lr=0.001, momentum=0.9)
+    criterion = nn.CrossEntropyLoss()
+    model.train()
+
+    # Synthetic batch at the training resolution; OOM surfaces as an exception on stderr
+    inputs = torch.randn(args.batch_size, 3, input_size, input_size, device=device)
+    labels = torch.randint(0, args.num_classes, (args.batch_size,), device=device)
+    for _ in range(args.steps):
+        optimizer.zero_grad()
+        loss = criterion(model(inputs), labels)
+        loss.backward()
+        optimizer.step()
+    torch.cuda.synchronize()
+
+    print(json.dumps({
+        "status": "probe_ok",
+        "device": torch.cuda.get_device_name(0),
+        "batch_size": args.batch_size,
+        "peak_memory_mb": torch.cuda.max_memory_allocated() // (1024 * 1024),
+    }), flush=True)
+
+if __name__ == "__main__":
+    try:
+        main()
+    except Exception as e:
+        sys.stderr.write(f"{type(e).__name__}: {e}\n")
+        sys.exit(1)
@@ -12,9 +12,37 @@ def get_gpu_info() -> dict:
         "cuda_version": torch.version.cuda if cuda_available else None,
         "device_count": torch.cuda.device_count() if cuda_available else 0,
         "device_name": torch.cuda.get_device_name(0) if cuda_available else None,
+        "vram_total_mb": torch.cuda.get_device_properties(0).total_memory // (1024 * 1024) if cuda_available else None,
     }
 
     return info
 
+def get_gpu_stats() -> dict:
+    """Live readings per device, used when the app cannot load NVML itself.
+    Utilization and temperature need pynvml and are null without it."""
+    devices = []
+    for i in range(torch.cuda.device_count() if torch.cuda.is_available() else 0):
+        free, total = torch.cuda.mem_get_info(i)
+        sample = {
+            "index": i,
+            "name": torch.cuda.get_device_name(i),
+            "utilization_percent": None,
+            "memory_used_mb": (total - free) // (1024 * 1024),
+            "memory_total_mb": total // (1024 * 1024),
+            "temperature_c": None,
+            "power_w": None,
+        }
+        try:
+            sample["utilization_percent"] = torch.cuda.utilization(i)
+            sample["temperature_c"] = torch.cuda.temperature(i)
+            sample["power_w"] = torch.cuda.power_draw(i) / 1000.0
+        except Exception:
+            pass
+        devices.append(sample)
+    return {"devices": devices}
+
 if __name__ == "__main__":
-    print(json.dumps(get_gpu_info()))
+    if "--stats" in sys.argv[1:]:
+        print(json.dumps(get_gpu_stats()))
+    else:
+        print(json.dumps(get_gpu_info()))
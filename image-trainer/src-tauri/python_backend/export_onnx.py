@@ -0,0 +1,82 @@
+import argparse
+import json
+import os
+import torch
+
+from predict import PreprocessingError, emit, load_model, load_preprocessing
+
+
+def progress(stage, fraction, message):
+    emit({"status": "progress", "stage": stage, "progress": fraction, "message": message})
+
+
+def add_metadata(path, manifest, pre):
+    """Stores the class names and preprocessing in the ONNX file, so the model
+    can be used without its run directory. Skipped when onnx is not installed."""
+    try:
+        import onnx
+    except ImportError:
+        return False
+    model = onnx.load(path)
+    for key, value in (("classes", manifest.get('classes') or []), ("preprocessing", pre)):
+        entry = model.metadata_props.add()
+        entry.key = key
+        entry.value = json.dumps(value)
+    onnx.checker.check_model(model)
+    onnx.save(model, path)
+    return True
+
+
+def main():
+    parser = argparse.ArgumentParser(description='Export a trained model to ONNX')
+    parser.add_argument('--model_dir', type=str, required=True, help='Run directory with best_model.pth, run_manifest.json and preprocessing.json')
+    parser.add_argument('--out', type=str, required=True, help='Path of the .onnx file to write')
+    parser.add_argument('--opset', type=int, default=17)
+    args = parser.parse_args()
+
+    try:
+        progress("loading", 0.0, "Loading the model")
+        with open(os.path.join(args.model_dir, 'run_manifest.json'), 'r') as f:
+            manifest = json.load(f)
+        classes = manifest.get('classes') or []
+        if not classes:
+            raise ValueError("run_manifest.json lists no classes.")
+        pre = load_preprocessing(args.model_dir, manifest)
+        # Exported on the CPU so the file does not depend on CUDA being present.
+        model, version = load_model(args.model_dir, manifest, len(classes), torch.device("cpu"))
+
+        progress("exporting", 0.3, f"Exporting with opset {args.opset}")
+        size = int(pre['input_size'])
+        dummy = torch.randn(1, 3, size, size)
+        out_dir = os.path.dirname(os.path.abspath(args.out))
+        os.makedirs(out_dir, exist_ok=True)
+        torch.onnx.export(
+            model,
+            dummy,
+            args.out,
+            input_names=["input"],
+            output_names=["logits"],
+            dynamic_axes={"input": {0: "batch"}, "logits": {0: "batch"}},
+            opset_version=args.opset,
+        )
+
+        progress("verifying", 0.8, "Checking the exported model")
+        verified = add_metadata(args.out, manifest, pre)
+
+        emit({
+            "status": "success",
+            "path": args.out,
+            "opset": args.opset,
+            "input_size": size,
+            "model_version": version,
+            "verified": verified,
+            "size_bytes": os.path.getsize(args.out),
+        })
+    except PreprocessingError as e:
+        emit({"status": "error", "kind": "preprocessing_mismatch", "message": str(e)})
+    except Exception as e:
+        emit({"status": "error", "message": f"ONNX export failed: {e}"})
+
+
+if __name__ == '__main__':
+    main()
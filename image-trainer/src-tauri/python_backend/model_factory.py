@@ -157,3 +157,40 @@ def create_model(model_name, num_classes, device):
     parameters_to_optimize = [p for p in model.parameters() if p.requires_grad]
     
     return model, parameters_to_optimize
+
+def create_custom_model(descriptor, num_classes, device):
+    """Builds a user-registered architecture from its descriptor (see architectures.rs)."""
+    name = descriptor.get('name', 'custom')
+    source = descriptor.get('source', {})
+    kind = source.get('kind')
+    print(f"[Model Factory] Initializing custom architecture {name} ({kind})...", flush=True)
+
+    if kind == 'timm':
+        import timm
+        model = timm.create_model(source['model_id'], pretrained=source.get('pretrained', True), num_classes=num_classes)
+        if source.get('pretrained', True):
+            # Same transfer-learning default as the built-ins: train the head only
+            for param in model.parameters():
+                param.requires_grad = False
+            for param in model.get_classifier().parameters():
+                param.requires_grad = True
+    elif kind == 'script':
+        import importlib.util
+        path = source['path']
+        spec = importlib.util.spec_from_file_location(f"epoq_arch_{name.replace('-', '_')}", path)
+        if spec is None or spec.loader is None:
+            raise ValueError(f"Cannot load architecture script: {path}")
+        module = importlib.util.module_from_spec(spec)
+        spec.loader.exec_module(module)
+        factory = getattr(module, source['factory'], None)
+        if factory is None:
+            raise ValueError(f"{path} does not define {source['factory']}()")
+        model = factory(num_classes)
+        if not isinstance(model, nn.Module):
+            raise ValueError(f"{source['factory']}() must return a torch.nn.Module")
+    else:
+        raise ValueError(f"Unknown architecture source: {kind}")
+
+    model = model.to(device)
+    parameters_to_optimize = [p for p in model.parameters() if p.requires_grad]
+    return model, parameters_to_optimize
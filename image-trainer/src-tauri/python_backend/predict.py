@@ -0,0 +1,184 @@
+import json
+import argparse
+import hashlib
+import os
+import sys
+import torch
+from PIL import Image, ImageOps
+from torchvision import transforms
+
+# Keys every preprocessing.json written by script.py carries
+REQUIRED_PREPROCESSING = ("resize", "input_size", "mean", "std", "channel_order")
+
+
+class PreprocessingError(Exception):
+    pass
+
+
+def load_image(path):
+    """Opens an image upright as RGB. Phone photos are often stored sideways
+    with an EXIF orientation tag, which PIL and ImageFolder's loader ignore."""
+    with open(path, 'rb') as f:
+        return ImageOps.exif_transpose(Image.open(f)).convert('RGB')
+
+
+def emit(payload):
+    print(json.dumps(payload), flush=True)
+
+
+def load_preprocessing(model_dir, manifest):
+    """Reads the preprocessing stored with the model and checks it is usable and
+    consistent with the model. Predicting with different preprocessing than the
+    model was trained with gives quietly wrong results, so nothing is defaulted."""
+    path = os.path.join(model_dir, 'preprocessing.json')
+    if not os.path.isfile(path):
+        raise PreprocessingError(f"{path} is missing; the model cannot be used without its training preprocessing.")
+    with open(path, 'r') as f:
+        pre = json.load(f)
+
+    missing = [k for k in REQUIRED_PREPROCESSING if k not in pre]
+    if missing:
+        raise PreprocessingError(f"preprocessing.json lacks {', '.join(missing)}.")
+    if len(pre['mean']) != 3 or len(pre['std']) != 3 or any(s <= 0 for s in pre['std']):
+        raise PreprocessingError("preprocessing.json needs three mean values and three positive std values.")
+    if pre['channel_order'] not in ('RGB', 'BGR'):
+        raise PreprocessingError(f"Unsupported channel order: {pre['channel_order']}")
+    if pre['resize'] < pre['input_size']:
+        raise PreprocessingError("preprocessing.json resizes below the crop size.")
+
+    architecture = manifest.get('architecture')
+    if architecture and int(architecture.get('input_size', 224)) != int(pre['input_size']):
+        raise PreprocessingError(
+            f"Preprocessing input size {pre['input_size']} does not match the architecture's "
+            f"{architecture.get('input_size')}."
+        )
+    return pre
+
+
+def build_transform(pre):
+    steps = [
+        transforms.Resize(pre['resize']),
+        transforms.CenterCrop(pre['input_size']),
+        transforms.ToTensor(),
+    ]
+    if pre['channel_order'] == 'BGR':
+        steps.append(transforms.Lambda(lambda t: t[[2, 1, 0]]))
+    steps.append(transforms.Normalize(pre['mean'], pre['std']))
+    return transforms.Compose(steps)
+
+
+def weights_version(path):
+    """Short content hash of the weights, so logged predictions name the exact model."""
+    digest = hashlib.sha256()
+    with open(path, 'rb') as f:
+        for chunk in iter(lambda: f.read(1 << 20), b''):
+            digest.update(chunk)
+    return digest.hexdigest()[:12]
+
+
+def load_model(model_dir, manifest, num_classes, device):
+    import model_factory
+    architecture = manifest.get('architecture')
+    if architecture:
+        model, _ = model_factory.create_custom_model(architecture, num_classes, device)
+    else:
+        model, _ = model_factory.create_model(manifest['config']['model'], num_classes, device)
+    weights_path = os.path.join(model_dir, 'best_model.pth')
+    # Weights can come from imported archives or the Hub; never unpickle code.
+    state = torch.load(weights_path, map_location=device, weights_only=True)
+    # Weights promoted from a training checkpoint keep the optimizer state alongside.
+    if isinstance(state, dict) and 'model_state_dict' in state:
+        state = state['model_state_dict']
+    model.load_state_dict(state)
+    model.eval()
+    return model, weights_version(weights_path)
+
+
+def capture_embeddings(model):
+    """Hooks the input of the last linear layer (the classifier head), which is
+    the image embedding for torchvision and custom architectures alike."""
+    head = None
+    for module in model.modules():
+        if isinstance(module, torch.nn.Linear):
+            head = module
+    if head is None:
+        raise ValueError("The model has no linear classifier layer to take embeddings from.")
+    captured = {}
+    head.register_forward_hook(lambda _m, inputs, _out: captured.__setitem__('features', inputs[0].detach()))
+    return captured
+
+
+def main():
+    parser = argparse.ArgumentParser(description='Classify images with a trained model')
+    parser.add_argument('--model_dir', type=str, required=True, help='Run directory with best_model.pth, run_manifest.json and preprocessing.json')
+    parser.add_argument('--images_file', type=str, default=None, help='JSON file listing the image paths')
+    parser.add_argument('--stdin', action='store_true', help='Read image paths from stdin, one per line, until it closes')
+    parser.add_argument('--classes', type=str, default=None, help='JSON list of labels in output order, validated by the app against class_map.json')
+    parser.add_argument('--top_k', type=int, default=5)
+    parser.add_argument('--embeddings', action='store_true', help='Also emit the classifier-head input of each image')
+    args = parser.parse_args()
+
+    try:
+        with open(os.path.join(args.model_dir, 'run_manifest.json'), 'r') as f:
+            manifest = json.load(f)
+        trained_classes = manifest.get('classes') or []
+        if not trained_classes:
+            raise ValueError("run_manifest.json lists no classes.")
+        class_names = json.loads(args.classes) if args.classes else trained_classes
+        if len(class_names) != len(trained_classes):
+            raise ValueError(f"The model has {len(trained_classes)} outputs but {len(class_names)} classes were given.")
+        pre = load_preprocessing(args.model_dir, manifest)
+        transform = build_transform(pre)
+        device = torch.device("cuda" if torch.cuda.is_available() else "cpu")
+        model, version = load_model(args.model_dir, manifest, len(class_names), device)
+        captured = capture_embeddings(model) if args.embeddings else None
+    except PreprocessingError as e:
+        emit({"status": "error", "kind": "preprocessing_mismatch", "message": str(e)})
+        return
+    except Exception as e:
+        emit({"status": "error", "message": f"Failed to load model: {e}"})
+        return
+
+    top_k = max(1, min(args.top_k, len(class_names)))
+
+    def classify(path):
+        try:
+            image = load_image(path)
+            batch = transform(image).unsqueeze(0).to(device)
+            with torch.no_grad():
+                probs = torch.softmax(model(batch), dim=1)[0].cpu()
+            values, indices = torch.topk(probs, top_k)
+            scores = [{"label": class_names[i], "probability": float(v)} for v, i in zip(values.tolist(), indices.tolist())]
+            record = {
+                "status": "prediction",
+                "file": path,
+                "label": scores[0]["label"],
+                "confidence": scores[0]["probability"],
+                "top_k": scores,
+                "model_id": args.model_dir,
+                "model_version": version,
+            }
+            if captured is not None:
+                record["embedding"] = [round(x, 5) for x in captured['features'].flatten(1)[0].cpu().tolist()]
+            emit(record)
+        except Exception as e:
+            emit({"status": "prediction_error", "file": path, "message": str(e)})
+
+    if args.stdin:
+        # The model stays loaded between images, e.g. for camera frames.
+        emit({"status": "ready", "model_version": version})
+        for line in sys.stdin:
+            if line.strip():
+                classify(line.strip())
+        return
+    if not args.images_file:
+        emit({"status": "error", "message": "Pass --images_file or --stdin."})
+        return
+    with open(args.images_file, 'r') as f:
+        images = json.load(f)
+    for path in images:
+        classify(path)
+
+
+if __name__ == '__main__':
+    main()
@@ -9,6 +9,8 @@ import torch.optim as optim
 import torchvision
 from torchvision import datasets, models, transforms
 
+from predict import load_image
+
 import numpy as np
 from sklearn.metrics import classification_report, confusion_matrix
 from torch.utils.data import DataLoader, Subset
@@ -18,20 +20,259 @@ matplotlib.use('Agg')
 import matplotlib.pyplot as plt
 import seaborn as sns
 
+# Exit code when training stopped on request after saving a checkpoint.
+INTERRUPTED_EXIT_CODE = 75
+# Seconds between "progress" lines; the app derives batch timing and the ETA from them
+PROGRESS_INTERVAL = 2.0
+
+# Written by the app's materialize_augmentations next to the class folders
+SYNTHETIC_MANIFEST = "synthetic_manifest.json"
+
+
+def load_synthetic_manifest(data_dir):
+    """Maps absolute paths of synthetic images to the original each was made from."""
+    path = os.path.join(data_dir, SYNTHETIC_MANIFEST)
+    if not os.path.isfile(path):
+        return {}
+    with open(path, 'r', encoding='utf-8') as f:
+        manifest = json.load(f)
+    root = os.path.dirname(os.path.abspath(path))
+    return {
+        os.path.abspath(os.path.join(root, item["file"])): os.path.abspath(os.path.join(root, item["source"]))
+        for item in manifest.get("synthetic", [])
+    }
+
+
+# Deterministic preprocessing shared by training (val/test) and inference
+PREPROCESSING = {
+    "resize": 256,
+    "input_size": 224,
+    "mean": [0.485, 0.456, 0.406],
+    "std": [0.229, 0.224, 0.225],
+    "channel_order": "RGB",
+}
+
+DEFAULT_AUGMENTATION = {"horizontal_flip": True}
+
+def build_train_transforms(policy):
+    """Builds the training transform pipeline from an augmentation policy dict."""
+    steps = [
+        transforms.Resize(PREPROCESSING['resize']),
+        transforms.CenterCrop(PREPROCESSING['input_size']),
+    ]
+    randaugment = policy.get('randaugment')
+    if randaugment:
+        steps.append(transforms.RandAugment(num_ops=randaugment['num_ops'], magnitude=randaugment['magnitude']))
+    if policy.get('horizontal_flip', False):
+        steps.append(transforms.RandomHorizontalFlip())
+    if policy.get('vertical_flip', False):
+        steps.append(transforms.RandomVerticalFlip())
+    if policy.get('rotation_degrees', 0) > 0:
+        steps.append(transforms.RandomRotation(policy['rotation_degrees']))
+    jitter = policy.get('color_jitter')
+    if jitter:
+        steps.append(transforms.ColorJitter(
+            brightness=jitter.get('brightness', 0),
+            contrast=jitter.get('contrast', 0),
+            saturation=jitter.get('saturation', 0),
+            hue=jitter.get('hue', 0),
+        ))
+    steps.append(transforms.ToTensor())
+    steps.append(transforms.Normalize(PREPROCESSING['mean'], PREPROCESSING['std']))
+    cutout = policy.get('cutout')
+    if cutout:
+        # RandomErasing works on tensors, so it must come after ToTensor
+        steps.append(transforms.RandomErasing(p=cutout['probability'], scale=(0.02, cutout['max_area'])))
+    return transforms.Compose(steps)
+
+def write_preprocessing(save_dir, augmentation):
+    """Stores the preprocessing next to the model weights so inference can match it."""
+    path = os.path.join(save_dir, 'preprocessing.json')
+    try:
+        with open(path, 'w') as f:
+            json.dump({**PREPROCESSING, "augmentation": augmentation}, f, indent=2)
+    except OSError as e:
+        print(f"Warning: Failed to write preprocessing config: {e}", flush=True)
+
+def lr_multiplier(schedule, epoch, step_in_epoch, steps_per_epoch, num_epochs):
+    """Returns the factor applied to the base LR at the given optimizer step."""
+    import math
+    kind = schedule.get('kind', 'constant')
+    steps_per_epoch = max(1, steps_per_epoch)
+    progress = epoch + step_in_epoch / steps_per_epoch  # fractional epochs elapsed
+
+    if kind in ('constant', 'cosine'):
+        warmup = schedule.get('warmup_epochs', 0)
+        if warmup > 0 and progress < warmup:
+            return (progress + 1.0 / steps_per_epoch) / warmup
+        if kind == 'constant':
+            return 1.0
+        min_ratio = schedule.get('min_lr_ratio', 0.0)
+        t = (progress - warmup) / max(1e-8, num_epochs - warmup)
+        return min_ratio + (1.0 - min_ratio) * 0.5 * (1.0 + math.cos(math.pi * min(1.0, t)))
+
+    if kind == 'step':
+        return schedule.get('gamma', 0.1) ** (epoch // max(1, schedule.get('step_size', 1)))
+
+    if kind == 'one_cycle':
+        pct_start = schedule.get('pct_start', 0.3)
+        start = 1.0 / schedule.get('div_factor', 25.0)
+        end = start / schedule.get('final_div_factor', 1e4)
+        t = min(1.0, progress / num_epochs)
+        if t < pct_start:
+            return start + (1.0 - start) * 0.5 * (1.0 - math.cos(math.pi * t / pct_start))
+        t = (t - pct_start) / (1.0 - pct_start)
+        return end + (1.0 - end) * 0.5 * (1.0 + math.cos(math.pi * t))
+
+    raise ValueError(f"Unknown lr schedule: {kind}")
+
+def seed_everything(seed, deterministic):
+    """Seeds python, numpy and torch; optionally forces deterministic kernels."""
+    import random
+    random.seed(seed)
+    np.random.seed(seed)
+    torch.manual_seed(seed)
+    if torch.cuda.is_available():
+        torch.cuda.manual_seed_all(seed)
+    if deterministic:
+        # Required by cuBLAS for deterministic matmuls on CUDA >= 10.2
+        os.environ.setdefault('CUBLAS_WORKSPACE_CONFIG', ':4096:8')
+        torch.backends.cudnn.deterministic = True
+        torch.backends.cudnn.benchmark = False
+        torch.use_deterministic_algorithms(True, warn_only=True)
+
+def seed_worker(worker_id):
+    # Each worker derives its seed from the main process generator
+    import random
+    worker_seed = torch.initial_seed() % 2**32
+    np.random.seed(worker_seed)
+    random.seed(worker_seed)
+
+def save_epoch_checkpoint(save_dir, state, epoch, val_accuracy, val_loss):
+    """Saves checkpoints/epoch_NNNN.pth and records it with its validation
+    metrics in checkpoints/index.json, which the app prunes by."""
+    checkpoint_dir = os.path.join(save_dir, 'checkpoints')
+    os.makedirs(checkpoint_dir, exist_ok=True)
+    name = f'epoch_{epoch:04d}.pth'
+    path = os.path.join(checkpoint_dir, name)
+    torch.save(state, path)
+    index_path = os.path.join(checkpoint_dir, 'index.json')
+    index = []
+    if os.path.exists(index_path):
+        try:
+            with open(index_path, 'r') as f:
+                index = json.load(f)
+        except (OSError, ValueError):
+            index = []
+    # A resumed run repeats epochs; the newer checkpoint replaces the entry.
+    index = [entry for entry in index if entry.get('file') != name]
+    index.append({
+        "file": name,
+        "epoch": epoch,
+        "val_accuracy": val_accuracy,
+        "val_loss": val_loss,
+        "saved_at": int(time.time()),
+    })
+    with open(index_path, 'w') as f:
+        json.dump(index, f, indent=2)
+    print(json.dumps({"status": "epoch_checkpoint", "epoch": epoch, "path": path}), flush=True)
+
+
+class ControlReader:
+    """Reads the JSON lines the app appends to the control file. The file
+    only grows, so each poll reads the complete lines added since the last."""
+
+    def __init__(self, path):
+        self.path = path
+        self.offset = 0
+
+    def poll(self):
+        if not self.path:
+            return []
+        try:
+            if os.path.getsize(self.path) <= self.offset:
+                return []
+            with open(self.path, 'rb') as f:
+                f.seek(self.offset)
+                data = f.read()
+        except OSError:
+            return []
+        # A line still being written is read on the next poll.
+        complete = data[:data.rfind(b'\n') + 1]
+        self.offset += len(complete)
+        controls = []
+        for line in complete.decode('utf-8', errors='replace').splitlines():
+            try:
+                controls.append(json.loads(line))
+            except ValueError:
+                print(f"Warning: Ignoring malformed control line: {line}", flush=True)
+        return controls
+
+
+def write_run_manifest(save_dir, updates):
+    """Merges `updates` into run_manifest.json in the output directory."""
+    manifest_path = os.path.join(save_dir, 'run_manifest.json')
+    manifest = {}
+    if os.path.exists(manifest_path):
+        try:
+            with open(manifest_path, 'r') as f:
+                manifest = json.load(f)
+        except (OSError, ValueError):
+            manifest = {}
+    manifest.update(updates)
+    try:
+        with open(manifest_path, 'w') as f:
+            json.dump(manifest, f, indent=2)
+    except OSError as e:
+        print(f"Warning: Failed to write run manifest: {e}", flush=True)
+
+def compute_custom_metrics(metric_refs, y_true, y_pred, y_prob, class_names):
+    """Runs plugin metric functions; a failing metric is recorded, not fatal."""
+    import importlib.util
+    results = {}
+    for ref in metric_refs:
+        entry = {"plugin": ref['plugin'], "higher_is_better": ref.get('higher_is_better', True)}
+        try:
+            spec = importlib.util.spec_from_file_location(f"epoq_metric_{ref['plugin']}_{ref['name']}".replace('-', '_'), ref['path'])
+            module = importlib.util.module_from_spec(spec)
+            spec.loader.exec_module(module)
+            value = getattr(module, ref['function'])(y_true, y_pred, y_prob, class_names)
+            entry["value"] = float(value)
+        except Exception as e:
+            entry["error"] = f"{type(e).__name__}: {e}"
+            print(f"Warning: Custom metric {ref['name']} failed: {entry['error']}", flush=True)
+        results[ref['name']] = entry
+    return results
+
 def main():
     parser = argparse.ArgumentParser(description='PyTorch Trainer')
     parser.add_argument('--path', type=str, required=True, help='Path to dataset')
     parser.add_argument('--epochs', type=int, default=5, help='Number of epochs')
     parser.add_argument('--save_path', type=str, required=False, help='Path to save models')
-    parser.add_argument('--model', type=str, default='resnet18', choices=['resnet18', 'resnet50', 'efficientnet_b0', 'dcn', 'eva02', 'mobilenet_v3', 'vit_b_16', 'convnext'], help='Model type: resnet18, resnet50, efficientnet_b0, dcn, eva02, mobilenet_v3, vit_b_16, or convnext')
+    parser.add_argument('--model', type=str, default='resnet18', help='Model type: resnet18, resnet50, efficientnet_b0, dcn, eva02, mobilenet_v3, vit_b_16, convnext, or a registered custom architecture')
+    parser.add_argument('--architecture', type=str, default=None, help='JSON descriptor of a registered custom architecture')
     parser.add_argument('--batch_size', type=int, default=32, help='Batch size for training')
     parser.add_argument('--learning_rate', type=float, default=0.001, help='Learning rate for optimizer')
+    parser.add_argument('--accumulation_steps', type=int, default=1, help='Number of batches to accumulate gradients over before each optimizer step')
     parser.add_argument('--zip_dataset', action='store_true', help='Create a zip archive of the dataset')
     parser.add_argument('--only_zip', action='store_true', help='Exit after creating dataset zip')
     parser.add_argument('--num_workers', type=int, default=-1, help='Number of data loading workers (default: dynamic, set to 0 to disable multiprocessing)')
     parser.add_argument('--experiment_id', type=str, default=None, help='Unique experiment identifier (auto-generated by UI)')
     parser.add_argument('--patience', type=int, default=5, help='Early stopping patience (epochs without val loss improvement)')
+    parser.add_argument('--freeze_mode', type=str, default='frozen', choices=['frozen', 'full', 'staged'], help='Backbone freezing: frozen (head only), full (all layers), or staged (frozen, then unfrozen)')
+    parser.add_argument('--freeze_epochs', type=int, default=0, help='Epochs to keep the backbone frozen when --freeze_mode=staged')
+    parser.add_argument('--unfreeze_lr_factor', type=float, default=0.1, help='Backbone learning rate multiplier after unfreezing in staged mode')
+    parser.add_argument('--lr_schedule', type=str, default=None, help='JSON learning-rate schedule, e.g. {"kind": "cosine", "warmup_epochs": 1}')
+    parser.add_argument('--augmentation', type=str, default=None, help='JSON augmentation policy for the training split')
+    parser.add_argument('--class_weights', type=str, default=None, help='JSON map of class name to loss weight')
+    parser.add_argument('--custom_metrics', type=str, default=None, help='JSON list of plugin metrics to compute on the test split')
+    parser.add_argument('--seed', type=int, default=None, help='Seed for python, numpy and torch RNGs')
+    parser.add_argument('--deterministic', action='store_true', help='Seed all RNGs and force deterministic cuDNN kernels')
     parser.add_argument('--resume', type=str, required=False, default=None, help='Path to a checkpoint .pth file to resume training from')
+    parser.add_argument('--stop_file', type=str, default=None, help='When this file appears, save a checkpoint and exit')
+    parser.add_argument('--control_file', type=str, default=None, help='File the app appends JSON control lines to: stop_after_epoch, save_checkpoint, extend')
+    parser.add_argument('--epoch_checkpoints', action='store_true', help='Also keep a checkpoint of every epoch under checkpoints/, pruned by the app')
+    parser.add_argument('--job_env', type=str, default=None, help='JSON of the environment variables set for this run (secrets redacted), for the run manifest')
     args = parser.parse_args()
     
     data_dir = args.path
@@ -43,27 +284,43 @@ def main():
 
     print("Initializing training...", flush=True)
 
+    architecture = json.loads(args.architecture) if args.architecture else None
+    import model_factory
+    if architecture is None and args.model not in model_factory.get_available_models():
+        print(json.dumps({"status": "error", "message": f"Unknown model type: {args.model}"}), flush=True)
+        return
+    if architecture is not None:
+        # Keep the 256/224 resize-to-crop ratio at the architecture's resolution
+        input_size = int(architecture.get('input_size', 224))
+        PREPROCESSING['input_size'] = input_size
+        PREPROCESSING['resize'] = round(input_size * 256 / 224)
+
+    seed = args.seed if args.seed is not None else (42 if args.deterministic else None)
+    loader_generator = None
+    worker_init_fn = None
+    if seed is not None:
+        seed_everything(seed, args.deterministic)
+        loader_generator = torch.Generator()
+        loader_generator.manual_seed(seed)
+        worker_init_fn = seed_worker
+        print(f"Seed: {seed} (deterministic={args.deterministic})", flush=True)
+
     # Data Augmentation & Normalization
+    augmentation = json.loads(args.augmentation) if args.augmentation else DEFAULT_AUGMENTATION
     data_transforms = {
-        'train': transforms.Compose([
-            transforms.Resize(256),
-            # transforms.RandomResizedCrop(224), # Optional: Add more config for this later
-            transforms.CenterCrop(224),
-            transforms.RandomHorizontalFlip(), 
-            transforms.ToTensor(),
-            transforms.Normalize([0.485, 0.456, 0.406], [0.229, 0.224, 0.225])
-        ]),
+        'train': build_train_transforms(augmentation),
         'val': transforms.Compose([
-            transforms.Resize(256),
-            transforms.CenterCrop(224),
+            transforms.Resize(PREPROCESSING['resize']),
+            transforms.CenterCrop(PREPROCESSING['input_size']),
             transforms.ToTensor(),
-            transforms.Normalize([0.485, 0.456, 0.406], [0.229, 0.224, 0.225])
+            transforms.Normalize(PREPROCESSING['mean'], PREPROCESSING['std'])
         ]),
     }
 
     dataloaders = {}
     dataset_sizes = {}
     class_names = []
+    train_targets = []
     
     # Check for existing split structure
     train_dir = os.path.join(data_dir, 'train')
@@ -92,15 +349,16 @@ def main():
         print("Detected structured dataset (train/val/test).", flush=True)
         
         # Train
-        train_dataset = datasets.ImageFolder(train_dir, data_transforms['train'])
-        dataloaders['train'] = DataLoader(train_dataset, batch_size=batch_size, shuffle=True, num_workers=num_workers)
+        train_dataset = datasets.ImageFolder(train_dir, data_transforms['train'], loader=load_image)
+        dataloaders['train'] = DataLoader(train_dataset, batch_size=batch_size, shuffle=True, num_workers=num_workers, worker_init_fn=worker_init_fn, generator=loader_generator)
         dataset_sizes['train'] = len(train_dataset)
         class_names = train_dataset.classes
+        train_targets = list(train_dataset.targets)
         
         # Val
         if os.path.isdir(val_dir):
-            val_dataset = datasets.ImageFolder(val_dir, data_transforms['val'])
-            dataloaders['val'] = DataLoader(val_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers)
+            val_dataset = datasets.ImageFolder(val_dir, data_transforms['val'], loader=load_image)
+            dataloaders['val'] = DataLoader(val_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers, worker_init_fn=worker_init_fn, generator=loader_generator)
             dataset_sizes['val'] = len(val_dataset)
         else:
             print("Warning: No validation folder found.", flush=True)
@@ -109,8 +367,8 @@ def main():
             
         # Test
         if os.path.isdir(test_dir):
-            test_dataset = datasets.ImageFolder(test_dir, data_transforms['val'])
-            dataloaders['test'] = DataLoader(test_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers)
+            test_dataset = datasets.ImageFolder(test_dir, data_transforms['val'], loader=load_image)
+            dataloaders['test'] = DataLoader(test_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers, worker_init_fn=worker_init_fn, generator=loader_generator)
             dataset_sizes['test'] = len(test_dataset)
         else:
             dataloaders['test'] = None
@@ -131,7 +389,13 @@ def main():
         # We load a dummy dataset just to get lengths and targets
         dummy_dataset = datasets.ImageFolder(data_dir)
         class_names = dummy_dataset.classes
-        total_images = len(dummy_dataset)
+
+        # Synthetic images from materialize_augmentations never go to val/test,
+        # and only join train together with their original.
+        synthetic_sources = load_synthetic_manifest(data_dir)
+        sample_paths = [os.path.abspath(p) for p, _ in dummy_dataset.samples]
+        original_idx = [i for i, p in enumerate(sample_paths) if p not in synthetic_sources]
+        total_images = len(original_idx)
         
         if total_images == 0:
             print(json.dumps({"status": "error", "message": "No images found."}), flush=True)
@@ -146,34 +410,42 @@ def main():
         from sklearn.model_selection import train_test_split
         
         targets = dummy_dataset.targets
-        indices = list(range(total_images))
+        indices = original_idx
+        original_targets = [targets[i] for i in original_idx]
         
         try:
             train_idx, temp_idx, _, temp_targets = train_test_split(
-                indices, targets, train_size=train_len, stratify=targets, random_state=42
+                indices, original_targets, train_size=train_len, stratify=original_targets, random_state=seed if seed is not None else 42
             )
             val_idx, test_idx = train_test_split(
-                temp_idx, train_size=val_len, stratify=temp_targets, random_state=42
+                temp_idx, train_size=val_len, stratify=temp_targets, random_state=seed if seed is not None else 42
             )
         except ValueError as e:
             print(f"Stratification failed ({e}), falling back to random split.", flush=True)
             from torch.utils.data import random_split
-            subset_train, subset_val, subset_test = random_split(dummy_dataset, [train_len, val_len, test_len])
-            train_idx = subset_train.indices
-            val_idx = subset_val.indices
-            test_idx = subset_test.indices
+            subset_train, subset_val, subset_test = random_split(Subset(dummy_dataset, original_idx), [train_len, val_len, test_len], generator=loader_generator or torch.default_generator)
+            train_idx = [original_idx[i] for i in subset_train.indices]
+            val_idx = [original_idx[i] for i in subset_val.indices]
+            test_idx = [original_idx[i] for i in subset_test.indices]
+
+        if synthetic_sources:
+            train_paths = {sample_paths[i] for i in train_idx}
+            extra = [i for i, p in enumerate(sample_paths) if synthetic_sources.get(p) in train_paths]
+            train_idx = list(train_idx) + extra
+            print(f"Added {len(extra)} synthetic images to the training split; {len(synthetic_sources) - len(extra)} were left out with their val/test originals.", flush=True)
         
         # True datasets
-        dataset_train_full = datasets.ImageFolder(data_dir, data_transforms['train'])
-        dataset_eval_full = datasets.ImageFolder(data_dir, data_transforms['val']) # No aug for val/test
+        dataset_train_full = datasets.ImageFolder(data_dir, data_transforms['train'], loader=load_image)
+        dataset_eval_full = datasets.ImageFolder(data_dir, data_transforms['val'], loader=load_image) # No aug for val/test
         
         train_dataset = Subset(dataset_train_full, train_idx)
+        train_targets = [targets[i] for i in train_idx]
         val_dataset = Subset(dataset_eval_full, val_idx)
         test_dataset = Subset(dataset_eval_full, test_idx)
         
-        dataloaders['train'] = DataLoader(train_dataset, batch_size=batch_size, shuffle=True, num_workers=num_workers)
-        dataloaders['val'] = DataLoader(val_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers)
-        dataloaders['test'] = DataLoader(test_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers)
+        dataloaders['train'] = DataLoader(train_dataset, batch_size=batch_size, shuffle=True, num_workers=num_workers, worker_init_fn=worker_init_fn, generator=loader_generator)
+        dataloaders['val'] = DataLoader(val_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers, worker_init_fn=worker_init_fn, generator=loader_generator)
+        dataloaders['test'] = DataLoader(test_dataset, batch_size=batch_size, shuffle=False, num_workers=num_workers, worker_init_fn=worker_init_fn, generator=loader_generator)
         
         dataset_sizes['train'] = len(train_dataset)
         dataset_sizes['val'] = len(val_dataset)
@@ -234,30 +506,121 @@ def main():
     # Setup Model
     device = torch.device("cuda:0" if torch.cuda.is_available() else "cpu")
     print(f"Using device: {device}", flush=True)
-    
-    import model_factory
+    # Sent with the first progress line so the app can spot an unintended CPU fallback
+    device_info = {
+        "type": device.type,
+        "torch_version": torch.__version__,
+        "torch_cuda": torch.version.cuda,
+        "cuda_available": torch.cuda.is_available(),
+        "device_name": torch.cuda.get_device_name(0) if device.type == 'cuda' else None,
+    }
     
     try:
-        model, parameters_to_optimize = model_factory.create_model(args.model, len(class_names), device)
-    except ValueError as e:
+        if architecture is not None:
+            model, parameters_to_optimize = model_factory.create_custom_model(architecture, len(class_names), device)
+        else:
+            model, parameters_to_optimize = model_factory.create_model(args.model, len(class_names), device)
+    except (ValueError, OSError, ImportError) as e:
         print(json.dumps({"status": "error", "message": str(e)}), flush=True)
         return
 
     try:
-        criterion = nn.CrossEntropyLoss()
+        class_weights = None
+        if args.class_weights:
+            weight_map = json.loads(args.class_weights)
+            missing = [c for c in class_names if c not in weight_map]
+            if missing:
+                print(f"Warning: No class weight for {missing}, using 1.0.", flush=True)
+            class_weights = [float(weight_map.get(c, 1.0)) for c in class_names]
+            print(f"Class weights: {dict(zip(class_names, class_weights))}", flush=True)
+            criterion = nn.CrossEntropyLoss(weight=torch.tensor(class_weights, dtype=torch.float32, device=device))
+        else:
+            criterion = nn.CrossEntropyLoss()
+
+        accumulation_steps = max(1, args.accumulation_steps)
+        if accumulation_steps > 1:
+            print(f"Gradient accumulation: {accumulation_steps} steps (effective batch size {batch_size * accumulation_steps})", flush=True)
+
+        # --- Freezing Policy ---
+        if args.freeze_mode == 'full':
+            for param in model.parameters():
+                param.requires_grad = True
+            parameters_to_optimize = list(model.parameters())
+            print("Freeze policy: training all layers.", flush=True)
+        elif args.freeze_mode == 'staged':
+            print(f"Freeze policy: backbone frozen for {args.freeze_epochs} epochs, then unfrozen at lr x{args.unfreeze_lr_factor}.", flush=True)
+
         optimizer = optim.SGD(parameters_to_optimize, lr=args.learning_rate, momentum=0.9)
-        
+        # Schedules scale each group's own base LR so the unfrozen backbone keeps its lower rate
+        for group in optimizer.param_groups:
+            group['base_lr'] = args.learning_rate
+        lr_schedule = json.loads(args.lr_schedule) if args.lr_schedule else {"kind": "constant"}
+        steps_per_epoch = -(-len(dataloaders['train']) // accumulation_steps) if dataset_sizes['train'] > 0 else 1
+        lr_curve = []
+
+        def apply_lr(epoch, step_in_epoch):
+            factor = lr_multiplier(lr_schedule, epoch, step_in_epoch, steps_per_epoch, num_epochs)
+            for group in optimizer.param_groups:
+                group['lr'] = group['base_lr'] * factor
+
+        backbone_unfrozen = args.freeze_mode == 'full'
+
+        def unfreeze_backbone():
+            frozen = [p for p in model.parameters() if not p.requires_grad]
+            for param in frozen:
+                param.requires_grad = True
+            if frozen:
+                backbone_lr = args.learning_rate * args.unfreeze_lr_factor
+                optimizer.add_param_group({'params': frozen, 'lr': backbone_lr, 'base_lr': backbone_lr})
+            print(json.dumps({
+                "status": "unfrozen",
+                "message": f"Backbone unfrozen ({len(frozen)} tensors) at lr {args.learning_rate * args.unfreeze_lr_factor:g}"
+            }), flush=True)
+
         num_epochs = args.epochs
         best_acc = 0.0
         start_epoch = 0
 
+        write_run_manifest(save_dir, {
+            "experiment_id": args.experiment_id,
+            "config": {k: v for k, v in vars(args).items() if k != 'job_env'},
+            "environment": json.loads(args.job_env) if args.job_env else None,
+            "classes": class_names,
+            "dataset": {
+                "path": data_dir,
+                "splits": dataset_sizes,
+                # Reference distribution for drift monitoring of logged predictions
+                "class_counts": {name: train_targets.count(i) for i, name in enumerate(class_names)},
+            },
+            "architecture": architecture,
+            "augmentation": augmentation,
+            "class_weights": dict(zip(class_names, class_weights)) if class_weights else None,
+            "reproducibility": {
+                "seed": seed,
+                "deterministic": args.deterministic,
+                "cudnn_deterministic": torch.backends.cudnn.deterministic,
+                "cudnn_benchmark": torch.backends.cudnn.benchmark,
+            },
+            "freeze_policy": {
+                "mode": args.freeze_mode,
+                "freeze_epochs": args.freeze_epochs if args.freeze_mode == 'staged' else None,
+                "unfreeze_lr_factor": args.unfreeze_lr_factor if args.freeze_mode == 'staged' else None,
+            },
+        })
+
+        write_preprocessing(save_dir, augmentation)
+
         # --- Checkpoint Resume ---
         if args.resume and os.path.isfile(args.resume):
             print(f"Resuming from checkpoint: {args.resume}", flush=True)
-            checkpoint = torch.load(args.resume, map_location=device)
+            checkpoint = torch.load(args.resume, map_location=device, weights_only=True)
             model.load_state_dict(checkpoint['model_state_dict'])
-            optimizer.load_state_dict(checkpoint['optimizer_state_dict'])
             start_epoch = checkpoint['epoch'] + 1
+            # The saved optimizer has the extra backbone param group once unfrozen
+            if args.freeze_mode == 'staged' and start_epoch >= args.freeze_epochs:
+                unfreeze_backbone()
+                backbone_unfrozen = True
+            optimizer.load_state_dict(checkpoint['optimizer_state_dict'])
             best_acc = checkpoint.get('best_acc', 0.0)
             print(json.dumps({
                 "status": "resumed",
@@ -267,14 +630,35 @@ def main():
         elif args.resume:
             print(f"Warning: Checkpoint file not found at '{args.resume}', starting from scratch.", flush=True)
 
+        # Per-epoch metrics for reports; a resumed run keeps the epochs before it.
+        metric_history = []
+        if start_epoch > 0:
+            try:
+                with open(os.path.join(save_dir, 'run_manifest.json'), 'r') as f:
+                    previous = json.load(f).get('metric_history') or []
+                metric_history = [entry for entry in previous if entry.get('epoch', 0) <= start_epoch]
+            except (OSError, ValueError):
+                metric_history = []
+
         # --- Early Stopping State ---
         best_val_loss = float('inf')
         epochs_no_improve = 0
         patience = args.patience
 
         print("Starting training loop...", flush=True)
+        last_progress = 0.0
+        device_reported = False
+        controls = ControlReader(args.control_file)
+        stop_after_epoch = False
+
+        # A while loop, as an extend control raises num_epochs mid-run.
+        epoch = start_epoch - 1
+        while epoch + 1 < num_epochs:
+            epoch += 1
+            if args.freeze_mode == 'staged' and not backbone_unfrozen and epoch >= args.freeze_epochs:
+                unfreeze_backbone()
+                backbone_unfrozen = True
 
-        for epoch in range(start_epoch, num_epochs):
             train_acc_epoch = 0.0
             train_loss_epoch = 0.0
             val_acc_epoch = 0.0
@@ -290,24 +674,92 @@ def main():
 
                 running_loss = 0.0
                 running_corrects = 0
+                num_batches = len(dataloaders[phase])
+                epoch_steps = sum(len(dataloaders[p]) for p in ('train', 'val') if dataset_sizes[p] > 0)
+                step_offset = len(dataloaders['train']) if phase == 'val' and dataset_sizes['train'] > 0 else 0
+
+                optimizer.zero_grad()
 
-                for inputs, labels in dataloaders[phase]:
+                for batch_idx, (inputs, labels) in enumerate(dataloaders[phase]):
                     inputs = inputs.to(device)
                     labels = labels.to(device)
 
-                    optimizer.zero_grad()
-
                     with torch.set_grad_enabled(phase == 'train'):
                         outputs = model(inputs)
                         _, preds = torch.max(outputs, 1)
                         loss = criterion(outputs, labels)
 
                         if phase == 'train':
-                            loss.backward()
-                            optimizer.step()
+                            # Scale so the accumulated gradient matches one large batch
+                            (loss / accumulation_steps).backward()
+                            if (batch_idx + 1) % accumulation_steps == 0 or (batch_idx + 1) == num_batches:
+                                apply_lr(epoch, batch_idx // accumulation_steps)
+                                optimizer.step()
+                                optimizer.zero_grad()
 
                     running_loss += loss.item() * inputs.size(0)
                     running_corrects += torch.sum(preds == labels.data)
+
+                    now = time.monotonic()
+                    if now - last_progress >= PROGRESS_INTERVAL:
+                        last_progress = now
+                        progress = {
+                            "status": "progress",
+                            "epoch": epoch + 1,
+                            "total_epochs": num_epochs,
+                            "phase": phase,
+                            "step": step_offset + batch_idx + 1,
+                            "steps": epoch_steps
+                        }
+                        if not device_reported:
+                            progress["device"] = device_info
+                            device_reported = True
+                        print(json.dumps(progress), flush=True)
+
+                    for control in controls.poll():
+                        action = control.get('action')
+                        if action == 'stop_after_epoch':
+                            stop_after_epoch = True
+                            message = f"Stopping after epoch {epoch + 1}."
+                        elif action == 'save_checkpoint':
+                            # Like the stop checkpoint: resuming repeats this epoch.
+                            checkpoint_path = os.path.join(save_dir, 'checkpoint.pth')
+                            torch.save({
+                                'epoch': epoch - 1,
+                                'model_state_dict': model.state_dict(),
+                                'optimizer_state_dict': optimizer.state_dict(),
+                                'best_acc': float(best_acc),
+                            }, checkpoint_path)
+                            message = f"Checkpoint saved to {checkpoint_path}."
+                        elif action == 'extend':
+                            num_epochs += int(control.get('epochs', 0))
+                            message = f"Extended to {num_epochs} epochs."
+                        else:
+                            message = f"Unknown control action: {action}"
+                        print(json.dumps({
+                            "status": "control",
+                            "action": action,
+                            "epoch": epoch + 1,
+                            "total_epochs": num_epochs,
+                            "message": message
+                        }), flush=True)
+
+                    if phase == 'train' and args.stop_file and os.path.exists(args.stop_file):
+                        # Resuming repeats the interrupted epoch from its start.
+                        checkpoint_path = os.path.join(save_dir, 'checkpoint.pth')
+                        torch.save({
+                            'epoch': epoch - 1,
+                            'model_state_dict': model.state_dict(),
+                            'optimizer_state_dict': optimizer.state_dict(),
+                            'best_acc': float(best_acc),
+                        }, checkpoint_path)
+                        print(json.dumps({
+                            "status": "interrupted",
+                            "epoch": epoch + 1,
+                            "message": "Stopped on request; checkpoint saved.",
+                            "path": checkpoint_path
+                        }), flush=True)
+                        sys.exit(INTERRUPTED_EXIT_CODE)
                 
                 epoch_loss = running_loss / dataset_sizes[phase]
                 epoch_acc = running_corrects.double() / dataset_sizes[phase]
@@ -333,12 +785,15 @@ def main():
 
                     # --- Full checkpoint (always, for resume support) ---
                     checkpoint_path = os.path.join(save_dir, 'checkpoint.pth')
-                    torch.save({
+                    checkpoint_state = {
                         'epoch': epoch,
                         'model_state_dict': model.state_dict(),
                         'optimizer_state_dict': optimizer.state_dict(),
                         'best_acc': float(best_acc),
-                    }, checkpoint_path)
+                    }
+                    torch.save(checkpoint_state, checkpoint_path)
+                    if args.epoch_checkpoints:
+                        save_epoch_checkpoint(save_dir, checkpoint_state, epoch + 1, float(epoch_acc), float(epoch_loss))
 
                     # --- Early Stopping: track best val loss ---
                     if epoch_loss < best_val_loss:
@@ -347,13 +802,27 @@ def main():
                     else:
                         epochs_no_improve += 1
 
+                    epoch_lr = optimizer.param_groups[0]['lr']
+                    lr_curve.append({"epoch": epoch + 1, "lr": epoch_lr})
+                    metric_history.append({
+                        "epoch": epoch + 1,
+                        "train_loss": round(float(train_loss_epoch), 4),
+                        "train_accuracy": round(float(train_acc_epoch), 4),
+                        "val_loss": round(float(val_loss_epoch), 4),
+                        "val_accuracy": round(float(val_acc_epoch), 4),
+                    })
+                    write_run_manifest(save_dir, {"lr_schedule": lr_schedule, "lr_curve": lr_curve, "metric_history": metric_history})
+
+                    # One line per epoch; the supervisor turns it into a
+                    # training://metrics event. Values are plain numbers.
                     status_update = {
                     "epoch": epoch + 1,
+                    "lr": float(f"{epoch_lr:.6g}"),
                     "total_epochs": num_epochs,
-                    "train_accuracy": f"{train_acc_epoch:.4f}",
-                    "train_loss": f"{train_loss_epoch:.4f}",
-                    "val_accuracy": f"{val_acc_epoch:.4f}",
-                    "val_loss": f"{val_loss_epoch:.4f}",
+                    "train_accuracy": round(float(train_acc_epoch), 4),
+                    "train_loss": round(float(train_loss_epoch), 4),
+                    "val_accuracy": round(float(val_acc_epoch), 4),
+                    "val_loss": round(float(val_loss_epoch), 4),
                     "status": "training"
                     }
                     print(json.dumps(status_update), flush=True)
@@ -367,6 +836,14 @@ def main():
                         }), flush=True)
                         break
 
+                    if stop_after_epoch:
+                        print(json.dumps({
+                            "status": "stopped_by_user",
+                            "epoch": epoch + 1,
+                            "message": f"Stopped on request after epoch {epoch + 1}."
+                        }), flush=True)
+                        break
+
             else:
                 continue
             break
@@ -380,7 +857,7 @@ def main():
             # Load best weights
             best_model_path = os.path.join(save_dir, 'best_model.pth')
             if os.path.exists(best_model_path):
-                model.load_state_dict(torch.load(best_model_path))
+                model.load_state_dict(torch.load(best_model_path, weights_only=True))
                 print("Loaded best model weights.", flush=True)
             else:
                 print("Warning: Best model not found, using last epoch weights.", flush=True)
@@ -389,6 +866,7 @@ def main():
             
             all_preds = []
             all_labels = []
+            all_probs = []
             
             with torch.no_grad():
                 for inputs, labels in dataloaders['test']:
@@ -398,6 +876,7 @@ def main():
                     outputs = model(inputs)
                     _, preds = torch.max(outputs, 1)
                     
+                    all_probs.extend(torch.softmax(outputs, dim=1).cpu().numpy())
                     all_preds.extend(preds.cpu().numpy())
                     all_labels.extend(labels.cpu().numpy())
             
@@ -412,6 +891,26 @@ def main():
             
             cr_dict = classification_report(all_labels, all_preds, target_names=class_names, labels=range(len(class_names)), zero_division=0, output_dict=True)
             
+            # Misclassified test images, for the misclassification browser and gallery export
+            if isinstance(test_dataset, Subset):
+                test_paths = [test_dataset.dataset.samples[i][0] for i in test_dataset.indices]
+            else:
+                test_paths = [path for path, _ in test_dataset.samples]
+            misclassified = [
+                {
+                    "file": test_paths[i],
+                    "actual": class_names[int(label)],
+                    "predicted": class_names[int(pred)],
+                    "confidence": float(np.max(all_probs[i])),
+                }
+                for i, (label, pred) in enumerate(zip(all_labels, all_preds)) if label != pred
+            ]
+            try:
+                with open(os.path.join(save_dir, 'misclassified.json'), 'w') as f:
+                    json.dump(misclassified, f, indent=2)
+            except OSError as e:
+                print(f"Warning: Failed to write misclassified samples: {e}", flush=True)
+
             # 2. Confusion Matrix & Heatmap
             cm = confusion_matrix(all_labels, all_preds)
             
@@ -428,10 +927,33 @@ def main():
             plt.close()
             print(f"Confusion Matrix saved to: {cm_save_path}", flush=True)
             
+            # 3. Summary metrics (built-in and plugin-provided)
+            metrics = {
+                "accuracy": cr_dict.get('accuracy', 0.0),
+                "macro_f1": cr_dict['macro avg']['f1-score'],
+                "weighted_f1": cr_dict['weighted avg']['f1-score'],
+                "macro_precision": cr_dict['macro avg']['precision'],
+                "macro_recall": cr_dict['macro avg']['recall'],
+            }
+            custom_metrics = {}
+            if args.custom_metrics:
+                custom_metrics = compute_custom_metrics(
+                    json.loads(args.custom_metrics),
+                    np.array(all_labels), np.array(all_preds), np.array(all_probs), class_names,
+                )
+            confidence = {"mean": float(np.max(np.array(all_probs), axis=1).mean())} if all_probs else None
+            write_run_manifest(save_dir, {"evaluation": {
+                "metrics": metrics,
+                "custom_metrics": custom_metrics,
+                "confidence": confidence,
+            }})
+
             # Send Data to Frontend
             eval_result = {
                 "status": "evaluation_complete",
                 "report": cr_dict,
+                "metrics": metrics,
+                "custom_metrics": custom_metrics,
                 "confusion_matrix_path": cm_save_path,
                 "total_epochs": num_epochs,
                 "test_size": dataset_sizes['test']
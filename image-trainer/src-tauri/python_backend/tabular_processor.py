@@ -2,7 +2,9 @@ import argparse
 import pandas as pd
 import json
 import os
+import sqlite3
 import sys
+from contextlib import closing
 
 def get_preview(df):
     """Returns a dictionary representation of the dataframe preview."""
@@ -10,20 +12,207 @@ def get_preview(df):
     df_preview = df.head(10).replace({float('nan'): None})
     
     return {
-        "columns": list(df.columns),
+        "columns": [str(c) for c in df.columns],
         "data": df_preview.values.tolist(),
         "shape": df.shape,
-        "dtypes": {col: str(dtype) for col, dtype in df.dtypes.items()},
-        "missing": df.isnull().sum().to_dict()
+        "dtypes": {str(col): str(dtype) for col, dtype in df.dtypes.items()},
+        "missing": {str(col): int(n) for col, n in df.isnull().sum().items()}
     }
 
+EXTENSIONS = {
+    '.csv': 'csv', '.txt': 'csv', '.tsv': 'tsv',
+    '.parquet': 'parquet', '.pq': 'parquet',
+    '.feather': 'feather', '.arrow': 'feather', '.ipc': 'feather',
+    '.xlsx': 'xlsx', '.xlsm': 'xlsx', '.xls': 'xls',
+}
+
+DB_SCHEME = 'sqlite://'
+
+def parse_db_source(source):
+    """Splits sqlite://<file>#<table> into the file and table."""
+    path, _, table = source[len(DB_SCHEME):].rpartition('#')
+    if not path or not table:
+        raise ValueError("Database sources are written sqlite://<file>#<table>.")
+    return path, table
+
+def quote_identifier(name):
+    return '"' + name.replace('"', '""') + '"'
+
+def detect_format(path):
+    """By magic bytes where the format has them, otherwise by extension."""
+    with open(path, 'rb') as f:
+        head = f.read(8)
+    if head.startswith(b'PAR1'):
+        return 'parquet'
+    if head.startswith(b'ARROW1') or head.startswith(b'FEA1'):
+        return 'feather'
+    if head.startswith(b'\xd0\xcf\x11\xe0'):
+        return 'xls'
+    if head.startswith(b'PK\x03\x04'):
+        return 'xlsx'
+    ext = os.path.splitext(path)[1].lower()
+    if ext not in EXTENSIONS:
+        raise ValueError("Unsupported file format. Please use CSV, TSV, Parquet, Feather or Excel.")
+    return EXTENSIONS[ext]
+
 def load_data(path):
-    if path.endswith('.csv'):
+    if path.startswith(DB_SCHEME):
+        db_path, table = parse_db_source(path)
+        with closing(sqlite3.connect(f"file:{db_path}?mode=ro", uri=True)) as conn:
+            return pd.read_sql_query(f"SELECT * FROM {quote_identifier(table)}", conn)
+    fmt = detect_format(path)
+    if fmt == 'csv':
         return pd.read_csv(path)
-    elif path.endswith('.xlsx') or path.endswith('.xls'):
+    if fmt == 'tsv':
+        return pd.read_csv(path, sep='\t')
+    if fmt == 'parquet':
+        return pd.read_parquet(path)
+    if fmt == 'feather':
+        return pd.read_feather(path)
+    if fmt == 'xlsx':
         return pd.read_excel(path, engine='openpyxl')
+    return pd.read_excel(path)
+
+def save_data(df, path):
+    """Writes in the format of the output's extension, converting from the input's."""
+    if path.startswith(DB_SCHEME):
+        db_path, table = parse_db_source(path)
+        with closing(sqlite3.connect(db_path)) as conn, conn:
+            df.to_sql(table, conn, if_exists='replace', index=False)
+        return
+    fmt = EXTENSIONS.get(os.path.splitext(path)[1].lower(), 'csv')
+    if fmt == 'tsv':
+        df.to_csv(path, index=False, sep='\t')
+    elif fmt == 'parquet':
+        df.to_parquet(path, index=False)
+    elif fmt == 'feather':
+        # Feather cannot store a non-default index.
+        df.reset_index(drop=True).to_feather(path)
+    elif fmt == 'xls':
+        raise ValueError("Saving as .xls is not supported; use .xlsx.")
+    elif fmt == 'xlsx':
+        df.to_excel(path, index=False)
     else:
-        raise ValueError("Unsupported file format. Please use CSV or Excel.")
+        df.to_csv(path, index=False)
+
+def chunk_progress(stage, chunk, rows_done, total_rows, passes, current):
+    """One line per chunk; the app forwards it as tabular://progress."""
+    progress = None
+    if total_rows:
+        progress = min(1.0, (current + rows_done / total_rows) / passes)
+    print(json.dumps({
+        "status": "progress",
+        "stage": stage,
+        "chunk": chunk,
+        "rows_done": rows_done,
+        "rows_total": total_rows,
+        "progress": progress,
+    }), flush=True)
+
+def collect_stats(read_chunks, params, total_rows):
+    """First pass for operations that need every row: sums and counts for
+    means, value counts for modes, and the categories to encode."""
+    op = params.get('operation')
+    method = params.get('method', 'mean')
+    columns = params.get('columns', [])
+    sums, counts, values = {}, {}, {}
+    rows = 0
+    for i, chunk in enumerate(read_chunks()):
+        if op == 'fill_missing' and method == 'mean':
+            numeric = chunk.select_dtypes(include=['number'])
+            for col in numeric.columns:
+                sums[col] = sums.get(col, 0.0) + float(numeric[col].sum())
+                counts[col] = counts.get(col, 0) + int(numeric[col].count())
+        elif op == 'fill_missing' and method == 'mode':
+            for col in chunk.columns:
+                seen = values.setdefault(col, {})
+                for value, n in chunk[col].value_counts().items():
+                    seen[value] = seen.get(value, 0) + int(n)
+        else:
+            for col in columns:
+                if col in chunk.columns:
+                    as_text = chunk[col].astype(str) if op == 'label_encode' else chunk[col].dropna()
+                    values.setdefault(col, set()).update(as_text.unique())
+        rows += len(chunk)
+        chunk_progress("scanning", i, rows, total_rows, 2, 0)
+    if op == 'fill_missing' and method == 'mean':
+        return {col: sums[col] / counts[col] for col in sums if counts[col]}
+    if op == 'fill_missing' and method == 'mode':
+        return {col: max(seen, key=seen.get) for col, seen in values.items() if seen}
+    return {col: sorted(found, key=str) for col, found in values.items()}
+
+def transform_chunk(chunk, params, stats):
+    op = params.get('operation')
+    if op == 'drop_missing':
+        return chunk.dropna()
+    if op == 'fill_missing':
+        if params.get('method', 'mean') == 'zero':
+            return chunk.fillna(0)
+        return chunk.fillna({col: value for col, value in stats.items() if col in chunk.columns})
+    if op == 'label_encode':
+        for col, categories in stats.items():
+            # The sorted order LabelEncoder uses, over the whole file.
+            codes = {value: i for i, value in enumerate(categories)}
+            chunk[col] = chunk[col].astype(str).map(codes)
+        return chunk
+    if op == 'one_hot_encode':
+        cols = [col for col in params.get('columns', []) if col in chunk.columns]
+        for col in cols:
+            # Every chunk gets a column per category, even ones it lacks.
+            chunk[col] = pd.Categorical(chunk[col], categories=stats.get(col, []))
+        return pd.get_dummies(chunk, columns=cols, drop_first=params.get('drop_first', False))
+    raise ValueError(f"Unknown operation {op}.")
+
+def process_chunked(args, params):
+    """Processes a CSV or TSV file chunk_rows rows at a time, appending each
+    chunk to the output so memory use does not grow with the file."""
+    op = params.get('operation')
+    if op == 'fill_missing' and params.get('method') == 'median':
+        raise ValueError("The median needs every row at once; use mean or mode when processing in chunks.")
+    sep = '\t' if detect_format(args.file) == 'tsv' else ','
+    save_path = args.out if args.out else args.file
+    out_sep = '\t' if save_path.lower().endswith('.tsv') else ','
+    read_chunks = lambda: pd.read_csv(args.file, sep=sep, chunksize=args.chunk_rows)
+
+    two_pass = op in ('label_encode', 'one_hot_encode') or (op == 'fill_missing' and params.get('method', 'mean') != 'zero')
+    stats = collect_stats(read_chunks, params, args.total_rows) if two_pass else {}
+    passes, current = (2, 1) if two_pass else (1, 0)
+
+    # Written next to the output and moved over it at the end, so processing in
+    # place never reads rows it has already written.
+    partial = save_path + '.partial'
+    rows_read = rows_written = 0
+    columns = []
+    missing = {}
+    dtypes = {}
+    try:
+        for i, chunk in enumerate(read_chunks()):
+            rows_read += len(chunk)
+            chunk = transform_chunk(chunk, params, stats)
+            chunk.to_csv(partial, sep=out_sep, index=False, mode='w' if i == 0 else 'a', header=(i == 0))
+            if i == 0:
+                columns = [str(c) for c in chunk.columns]
+                dtypes = {str(col): str(dtype) for col, dtype in chunk.dtypes.items()}
+            for col, n in chunk.isnull().sum().items():
+                missing[str(col)] = missing.get(str(col), 0) + int(n)
+            rows_written += len(chunk)
+            chunk_progress("writing", i, rows_read, args.total_rows, passes, current)
+        os.replace(partial, save_path)
+    finally:
+        if os.path.exists(partial):
+            os.remove(partial)
+
+    head = pd.read_csv(save_path, sep=out_sep, nrows=10).replace({float('nan'): None})
+    return {
+        "columns": columns,
+        "data": head.values.tolist(),
+        "shape": (rows_written, len(columns)),
+        "dtypes": dtypes,
+        "missing": missing,
+        "status": "success",
+        "message": f"Operation {op} completed on {rows_read} rows in chunks of {args.chunk_rows}.",
+        "file_path": save_path,
+    }
 
 def main():
     parser = argparse.ArgumentParser(description="Tabular Data Processor")
@@ -31,6 +220,8 @@ def main():
     parser.add_argument("--file", type=str, required=True, help="Path to input file")
     parser.add_argument("--out", type=str, help="Path to save processed file")
     parser.add_argument("--params", type=str, help="JSON string of parameters for processing")
+    parser.add_argument("--chunk_rows", type=int, default=None, help="Process CSV/TSV files this many rows at a time")
+    parser.add_argument("--total_rows", type=int, default=None, help="Row count of the input, counted by the app, for progress")
 
     args = parser.parse_args()
 
@@ -42,6 +233,9 @@ def main():
             result['loaded_path'] = args.file
             print(json.dumps(result))
 
+        elif args.action == 'process' and args.chunk_rows:
+            print(json.dumps(process_chunked(args, json.loads(args.params))))
+
         elif args.action == 'process':
             df = load_data(args.file)
             params = json.loads(args.params)
@@ -81,10 +275,7 @@ def main():
 
             # Save the result
             save_path = args.out if args.out else args.file
-            if save_path.endswith('.csv'):
-                df.to_csv(save_path, index=False)
-            else:
-                df.to_excel(save_path, index=False)
+            save_data(df, save_path)
 
             result = get_preview(df)
             result['status'] = 'success'
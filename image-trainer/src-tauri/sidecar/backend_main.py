@@ -0,0 +1,50 @@
+"""Entry point of the bundled backend binary.
+
+Frozen with PyInstaller together with the packages in
+python_backend/requirements.txt, it answers the subset of the python command
+line the app uses, so it can stand in for a system interpreter:
+
+    epoq-backend --version
+    epoq-backend -c "code" [args...]
+    epoq-backend -m module [args...]
+    epoq-backend script.py [args...]
+"""
+import os
+import runpy
+import sys
+
+# Frozen builds cannot make environments or install into themselves.
+UNSUPPORTED_MODULES = {"venv", "pip", "ensurepip"}
+
+
+def main(argv):
+    if not argv or argv[0] in ("-h", "--help"):
+        print(__doc__.strip())
+        return 0
+    if argv[0] in ("-V", "--version"):
+        print("Python %d.%d.%d" % sys.version_info[:3])
+        return 0
+    if argv[0] == "-c":
+        sys.argv = ["-c"] + argv[2:]
+        exec(compile(argv[1], "<string>", "exec"), {"__name__": "__main__"})
+        return 0
+    if argv[0] == "-m":
+        if argv[1] in UNSUPPORTED_MODULES:
+            print(
+                "The bundled backend cannot run '-m %s'. Install Python to use a virtual environment." % argv[1],
+                file=sys.stderr,
+            )
+            return 2
+        sys.argv = argv[1:]
+        runpy.run_module(argv[1], run_name="__main__", alter_sys=True)
+        return 0
+    script = os.path.abspath(argv[0])
+    sys.argv = [script] + argv[1:]
+    # Like the python command, so scripts can import their sibling modules.
+    sys.path.insert(0, os.path.dirname(script))
+    runpy.run_path(script, run_name="__main__")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main(sys.argv[1:]))
@@ -0,0 +1,54 @@
+"""Builds the bundled backend binary with PyInstaller.
+
+Run with the Python environment whose packages should be bundled:
+
+    pip install pyinstaller -r python_backend/requirements.txt
+    python sidecar/build.py
+
+The binary is written to binaries/epoq-backend-<target triple>, where
+`tauri build --config tauri.sidecar.conf.json` picks it up.
+"""
+import os
+import subprocess
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+ROOT = os.path.dirname(HERE)
+# Import names of the requirements whose distribution names differ.
+IMPORT_NAMES = {"scikit-learn": "sklearn"}
+
+
+def target_triple():
+    output = subprocess.run(["rustc", "-vV"], capture_output=True, text=True, check=True).stdout
+    for line in output.splitlines():
+        if line.startswith("host:"):
+            return line.split(":", 1)[1].strip()
+    raise SystemExit("rustc -vV did not report a host triple")
+
+
+def requirements():
+    with open(os.path.join(ROOT, "python_backend", "requirements.txt")) as f:
+        names = [line.split("#")[0].strip() for line in f]
+    return [IMPORT_NAMES.get(name, name) for name in names if name]
+
+
+def main():
+    args = [
+        sys.executable, "-m", "PyInstaller",
+        "--onefile",
+        "--noconfirm",
+        "--name", "epoq-backend-" + target_triple(),
+        "--distpath", os.path.join(ROOT, "binaries"),
+        "--workpath", os.path.join(HERE, "build"),
+        "--specpath", os.path.join(HERE, "build"),
+    ]
+    # The scripts are shipped as resources and loaded at run time, so their
+    # imports are invisible to PyInstaller's analysis.
+    for module in requirements():
+        args += ["--collect-submodules", module]
+    args.append(os.path.join(HERE, "backend_main.py"))
+    subprocess.run(args, check=True)
+
+
+if __name__ == "__main__":
+    main()
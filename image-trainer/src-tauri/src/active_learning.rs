@@ -0,0 +1,262 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::annotations::{AnnotationStore, ReviewItem, ReviewStatus};
+use crate::classes;
+use crate::dataset;
+use crate::jobs::{new_id, unix_now};
+use crate::paths;
+use crate::predictions::ClassScore;
+use crate::preprocessing;
+use crate::python::{backend_script, run_python};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionStrategy {
+    /// Images whose predicted class distribution has the highest entropy.
+    Uncertainty,
+    /// Greedy k-center over the model's embeddings: each pick is the image
+    /// farthest from everything picked so far, covering the folder evenly.
+    Diversity,
+}
+
+impl SelectionStrategy {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Uncertainty => "uncertainty",
+            Self::Diversity => "diversity",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LabelingCandidate {
+    pub file: String,
+    /// 1 is the most useful image to label.
+    pub rank: usize,
+    /// Normalized entropy for uncertainty; distance to the nearest earlier
+    /// pick for diversity, except for the first pick, which is the most
+    /// uncertain image and carries its entropy.
+    pub score: f64,
+    pub predicted_label: String,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LabelingSelection {
+    pub model_id: String,
+    pub strategy: SelectionStrategy,
+    /// Images scored by the model.
+    pub scanned: usize,
+    /// Images skipped because they were already in the review queue.
+    pub already_queued: usize,
+    pub candidates: Vec<LabelingCandidate>,
+    /// How many candidates were new to the review queue.
+    pub queued: usize,
+    /// Images that could not be scored, as `path: reason`.
+    pub failures: Vec<String>,
+}
+
+/// One image scored by predict.py with `--embeddings`.
+#[derive(Debug, Deserialize)]
+struct Scored {
+    file: String,
+    label: String,
+    confidence: f64,
+    top_k: Vec<ClassScore>,
+    #[serde(default)]
+    embedding: Vec<f32>,
+}
+
+/// Entropy of the class distribution divided by its maximum, so 1.0 means
+/// the model cannot tell the classes apart at all.
+fn normalized_entropy(scores: &[ClassScore], classes: usize) -> f64 {
+    if classes < 2 {
+        return 0.0;
+    }
+    let entropy: f64 = scores
+        .iter()
+        .filter(|s| s.probability > 0.0)
+        .map(|s| -s.probability * s.probability.ln())
+        .sum();
+    entropy / (classes as f64).ln()
+}
+
+fn normalize(v: &[f32]) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return v.to_vec();
+    }
+    v.iter().map(|x| x / norm).collect()
+}
+
+fn squared_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Greedy k-center: starts from the most uncertain image, then repeatedly
+/// takes the image farthest from all picks. Returns indices with the
+/// distance each was picked at.
+fn k_center(scored: &[Scored], uncertainty: &[f64], n: usize) -> Vec<(usize, f64)> {
+    let embeddings: Vec<Vec<f32>> = scored.iter().map(|s| normalize(&s.embedding)).collect();
+    let Some(first) = (0..scored.len()).max_by(|&a, &b| uncertainty[a].total_cmp(&uncertainty[b])) else {
+        return Vec::new();
+    };
+    let mut nearest: Vec<f32> = embeddings.iter().map(|e| squared_distance(e, &embeddings[first])).collect();
+    let mut picks = vec![(first, uncertainty[first])];
+    while picks.len() < n.min(scored.len()) {
+        let (next, distance) = nearest
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, d)| (i, *d))
+            .unwrap();
+        if distance <= 0.0 {
+            break;
+        }
+        picks.push((next, f64::from(distance.sqrt())));
+        for (i, e) in embeddings.iter().enumerate() {
+            nearest[i] = nearest[i].min(squared_distance(e, &embeddings[next]));
+        }
+    }
+    picks
+}
+
+async fn score(app: &tauri::AppHandle, model_id: &str, images: &[String]) -> Result<(Vec<Scored>, Vec<String>, usize), String> {
+    let model_dir = Path::new(model_id);
+    preprocessing::load(model_dir)?;
+    let classes = classes::model_classes(model_dir)?.len();
+    let script = backend_script(app, "predict.py")?;
+    let images_file = paths::cache_file(app, &format!("{}_images.json", new_id("select")))?;
+    let images_json = serde_json::to_string(images).map_err(|e| e.to_string())?;
+    std::fs::write(&images_file, images_json).map_err(|e| format!("Cannot write image list: {}", e))?;
+    let images_arg = images_file.to_string_lossy().to_string();
+    let top_k = classes.to_string();
+    let output = run_python(
+        app,
+        &[
+            script.as_str(),
+            "--model_dir",
+            model_id,
+            "--images_file",
+            images_arg.as_str(),
+            "--top_k",
+            top_k.as_str(),
+            "--embeddings",
+        ],
+    )
+    .await;
+    let _ = std::fs::remove_file(&images_file);
+    let stdout = output?;
+
+    let mut scored = Vec::new();
+    let mut failures = Vec::new();
+    for line in stdout.lines() {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        match value["status"].as_str() {
+            Some("error") => return Err(value["message"].as_str().unwrap_or("Scoring failed.").to_string()),
+            Some("prediction_error") => failures.push(format!(
+                "{}: {}",
+                value["file"].as_str().unwrap_or_default(),
+                value["message"].as_str().unwrap_or_default()
+            )),
+            Some("prediction") => {
+                if let Ok(s) = serde_json::from_value(value) {
+                    scored.push(s);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((scored, failures, classes))
+}
+
+/// Scores the images in `folder` that are not yet in the review queue with
+/// `model_id`, picks the `n` most useful to label and adds them to the queue
+/// in that order.
+pub async fn select(
+    app: &tauri::AppHandle,
+    store: &AnnotationStore,
+    model_id: &str,
+    folder: &str,
+    n: usize,
+    strategy: SelectionStrategy,
+) -> Result<LabelingSelection, String> {
+    if n == 0 {
+        return Err("Select at least one image.".to_string());
+    }
+    let mut images = Vec::new();
+    let mut already_queued = 0;
+    for path in dataset::list_images(Path::new(folder), true)? {
+        let path = path.to_string_lossy().to_string();
+        if store.is_queued(&path)? {
+            already_queued += 1;
+        } else {
+            images.push(path);
+        }
+    }
+    if images.is_empty() {
+        return Err(format!("{} has no images that are not already in the review queue.", folder));
+    }
+
+    let (scored, failures, classes) = score(app, model_id, &images).await?;
+    let uncertainty: Vec<f64> = scored.iter().map(|s| normalized_entropy(&s.top_k, classes)).collect();
+    let picks: Vec<(usize, f64)> = match strategy {
+        SelectionStrategy::Uncertainty => {
+            let mut order: Vec<usize> = (0..scored.len()).collect();
+            order.sort_by(|&a, &b| uncertainty[b].total_cmp(&uncertainty[a]));
+            order.into_iter().take(n).map(|i| (i, uncertainty[i])).collect()
+        }
+        SelectionStrategy::Diversity => {
+            if scored.iter().any(|s| s.embedding.is_empty()) {
+                return Err("The model returned no embeddings; diversity selection is unavailable.".to_string());
+            }
+            k_center(&scored, &uncertainty, n)
+        }
+    };
+
+    let candidates: Vec<LabelingCandidate> = picks
+        .iter()
+        .enumerate()
+        .map(|(rank, &(i, score))| LabelingCandidate {
+            file: scored[i].file.clone(),
+            rank: rank + 1,
+            score,
+            predicted_label: scored[i].label.clone(),
+            confidence: scored[i].confidence,
+        })
+        .collect();
+    // Rank order survives in the queue alongside items from earlier selections.
+    let added_at = unix_now();
+    let items: Vec<ReviewItem> = candidates
+        .iter()
+        .map(|c| ReviewItem {
+            file: c.file.clone(),
+            priority: 1.0 - (c.rank - 1) as f64 / candidates.len() as f64,
+            source: format!("active_learning:{}", strategy.name()),
+            model_id: Some(model_id.to_string()),
+            suggested_label: Some(c.predicted_label.clone()),
+            confidence: Some(c.confidence),
+            added_at,
+            status: ReviewStatus::Todo,
+            batch_id: None,
+            label: None,
+            started_at: None,
+            completed_at: None,
+        })
+        .collect();
+    let queued = store.enqueue(&items)?;
+
+    Ok(LabelingSelection {
+        model_id: model_id.to_string(),
+        strategy,
+        scanned: scored.len(),
+        already_queued,
+        candidates,
+        queued,
+        failures,
+    })
+}
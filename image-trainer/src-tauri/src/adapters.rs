@@ -0,0 +1,137 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::BackendError;
+use crate::hardware::GpuReport;
+
+/// A graphics adapter found through wgpu, independent of Python, PyTorch and
+/// vendor management libraries.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GpuAdapter {
+    pub name: String,
+    pub vendor: String,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    /// `discrete`, `integrated`, `virtual`, `cpu` or `other`.
+    pub device_type: String,
+    pub backend: String,
+    pub driver: String,
+    /// Device-local memory reported by Vulkan; `None` where Vulkan is unavailable
+    /// (e.g. macOS, whose GPUs share system memory).
+    pub vram_total_mb: Option<u64>,
+}
+
+fn vendor_name(id: u32) -> &'static str {
+    match id {
+        0x10de => "NVIDIA",
+        0x1002 | 0x1022 => "AMD",
+        0x8086 => "Intel",
+        0x106b => "Apple",
+        0x5143 => "Qualcomm",
+        0x13b5 => "ARM",
+        0x1414 => "Microsoft",
+        _ => "Unknown",
+    }
+}
+
+/// Device-local heap sizes per `(vendor_id, device_id)` from the Vulkan loader.
+#[cfg(feature = "gpu-fallback")]
+fn vulkan_vram() -> std::collections::HashMap<(u32, u32), u64> {
+    let mut sizes = std::collections::HashMap::new();
+    // SAFETY: loads the system Vulkan loader; failure just means no Vulkan.
+    let Ok(entry) = (unsafe { ash::Entry::load() }) else {
+        return sizes;
+    };
+    let app_info = ash::vk::ApplicationInfo::default().api_version(ash::vk::API_VERSION_1_0);
+    let create_info = ash::vk::InstanceCreateInfo::default().application_info(&app_info);
+    // SAFETY: the instance is destroyed below and no handle outlives it.
+    let Ok(instance) = (unsafe { entry.create_instance(&create_info, None) }) else {
+        return sizes;
+    };
+    unsafe {
+        for device in instance.enumerate_physical_devices().unwrap_or_default() {
+            let props = instance.get_physical_device_properties(device);
+            let memory = instance.get_physical_device_memory_properties(device);
+            let local: u64 = memory.memory_heaps[..memory.memory_heap_count as usize]
+                .iter()
+                .filter(|heap| heap.flags.contains(ash::vk::MemoryHeapFlags::DEVICE_LOCAL))
+                .map(|heap| heap.size)
+                .sum();
+            sizes.insert((props.vendor_id, props.device_id), local);
+        }
+        instance.destroy_instance(None);
+    }
+    sizes
+}
+
+/// All adapters wgpu can see, discrete GPUs first. Blocking; can take a few
+/// hundred milliseconds while drivers initialise.
+#[cfg(feature = "gpu-fallback")]
+pub fn enumerate() -> Vec<GpuAdapter> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    let vram = vulkan_vram();
+    let mut adapters: Vec<GpuAdapter> = Vec::new();
+    for adapter in instance.enumerate_adapters(wgpu::Backends::all()) {
+        let info = adapter.get_info();
+        // The same GPU shows up once per backend; keep the first.
+        if adapters.iter().any(|a| a.vendor_id == info.vendor && a.device_id == info.device && a.name == info.name) {
+            continue;
+        }
+        adapters.push(GpuAdapter {
+            vendor: vendor_name(info.vendor).to_string(),
+            vendor_id: info.vendor,
+            device_id: info.device,
+            device_type: match info.device_type {
+                wgpu::DeviceType::DiscreteGpu => "discrete",
+                wgpu::DeviceType::IntegratedGpu => "integrated",
+                wgpu::DeviceType::VirtualGpu => "virtual",
+                wgpu::DeviceType::Cpu => "cpu",
+                wgpu::DeviceType::Other => "other",
+            }
+            .to_string(),
+            backend: format!("{:?}", info.backend),
+            driver: [info.driver, info.driver_info]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            vram_total_mb: vram.get(&(info.vendor, info.device)).map(|b| b / (1024 * 1024)),
+            name: info.name,
+        });
+    }
+    adapters.sort_by_key(|a| match a.device_type.as_str() {
+        "discrete" => 0,
+        "integrated" => 1,
+        _ => 2,
+    });
+    adapters
+}
+
+#[cfg(not(feature = "gpu-fallback"))]
+pub fn enumerate() -> Vec<GpuAdapter> {
+    Vec::new()
+}
+
+/// Completes a check_gpu.py result with adapters found through wgpu when the
+/// probe failed (no Python or PyTorch yet) or saw no CUDA device. The probe's
+/// `vram_total_mb` stays empty in that case, since training only uses CUDA
+/// memory; adapter memory is reported per adapter instead.
+pub async fn with_fallback(probe: Result<GpuReport, BackendError>) -> Result<GpuReport, BackendError> {
+    let mut report = match &probe {
+        Ok(report) if report.cuda_available => return probe,
+        Ok(report) => report.clone(),
+        Err(e) => GpuReport { probe_error: Some(e.message().to_string()), ..GpuReport::default() },
+    };
+    let adapters = tauri::async_runtime::spawn_blocking(enumerate)
+        .await
+        .unwrap_or_default();
+    if adapters.is_empty() {
+        return probe;
+    }
+    if report.device_name.is_none() {
+        report.device_name = Some(adapters[0].name.clone());
+    }
+    report.adapters = Some(adapters);
+    report.adapter_source = Some("wgpu".to_string());
+    Ok(report)
+}
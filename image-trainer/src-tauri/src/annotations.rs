@@ -0,0 +1,492 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::database::Database;
+use crate::jobs::{new_id, unix_now};
+use crate::paths;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS review_queue (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file TEXT NOT NULL UNIQUE,
+    priority REAL NOT NULL,
+    source TEXT NOT NULL,
+    model_id TEXT,
+    suggested_label TEXT,
+    confidence REAL,
+    added_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS review_queue_priority ON review_queue (priority DESC);
+CREATE TABLE IF NOT EXISTS labeling_batches (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    assignee TEXT,
+    created_at INTEGER NOT NULL
+);
+";
+
+/// Columns added to `review_queue` by the labeling workflow, applied to
+/// stores created before it.
+const QUEUE_COLUMNS: &[(&str, &str)] = &[
+    ("status", "TEXT NOT NULL DEFAULT 'todo'"),
+    ("batch_id", "TEXT"),
+    ("label", "TEXT"),
+    ("started_at", "INTEGER"),
+    ("completed_at", "INTEGER"),
+];
+
+const ITEM_COLUMNS: &str =
+    "file, priority, source, model_id, suggested_label, confidence, added_at, status, batch_id, label, started_at, completed_at";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Todo,
+    /// Handed out by `next_item` and being labeled.
+    InReview,
+    Done,
+}
+
+impl ReviewStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Todo => "todo",
+            Self::InReview => "in_review",
+            Self::Done => "done",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "in_review" => Self::InReview,
+            "done" => Self::Done,
+            _ => Self::Todo,
+        }
+    }
+}
+
+/// An image waiting for a human label. Higher `priority` is reviewed first.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReviewItem {
+    pub file: String,
+    pub priority: f64,
+    /// What queued the item, e.g. `active_learning:uncertainty`.
+    pub source: String,
+    pub model_id: Option<String>,
+    /// The model's prediction, shown as a starting point.
+    pub suggested_label: Option<String>,
+    pub confidence: Option<f64>,
+    pub added_at: u64,
+    pub status: ReviewStatus,
+    pub batch_id: Option<String>,
+    /// The label given by the reviewer once done.
+    pub label: Option<String>,
+    /// When the item was last handed out for review.
+    pub started_at: Option<u64>,
+    pub completed_at: Option<u64>,
+}
+
+/// A set of queue items handed to one labeler.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LabelingBatch {
+    pub id: String,
+    pub name: String,
+    pub assignee: Option<String>,
+    pub created_at: u64,
+    pub todo: usize,
+    pub in_review: usize,
+    pub done: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct AssigneeStats {
+    pub done: usize,
+    pub done_last_24h: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct LabelingStats {
+    pub todo: usize,
+    pub in_review: usize,
+    pub done: usize,
+    pub done_last_hour: usize,
+    pub done_last_24h: usize,
+    /// Median time from hand-out to label over the last 24 hours.
+    pub median_seconds_per_item: Option<f64>,
+    /// Items labeled per hour over the last 24 hours, counting only hours
+    /// in which something was labeled.
+    pub items_per_active_hour: Option<f64>,
+    /// Done counts by the assignee of the item's batch; unbatched work is
+    /// listed under an empty name.
+    pub by_assignee: BTreeMap<String, AssigneeStats>,
+}
+
+/// Managed state: the annotation store (`annotations.sqlite`), opened on
+/// first use.
+pub struct AnnotationStore {
+    db: Database,
+}
+
+fn init(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)?;
+    migrate(conn)
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    let existing: Vec<String> = conn
+        .prepare("SELECT name FROM pragma_table_info('review_queue')")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (name, definition) in QUEUE_COLUMNS {
+        if !existing.iter().any(|c| c == name) {
+            conn.execute_batch(&format!("ALTER TABLE review_queue ADD COLUMN {} {};", name, definition))?;
+        }
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS review_queue_status ON review_queue (status, batch_id);")
+}
+
+fn item_from_row(row: &rusqlite::Row) -> rusqlite::Result<ReviewItem> {
+    Ok(ReviewItem {
+        file: row.get(0)?,
+        priority: row.get(1)?,
+        source: row.get(2)?,
+        model_id: row.get(3)?,
+        suggested_label: row.get(4)?,
+        confidence: row.get(5)?,
+        added_at: row.get::<_, i64>(6)? as u64,
+        status: ReviewStatus::parse(&row.get::<_, String>(7)?),
+        batch_id: row.get(8)?,
+        label: row.get(9)?,
+        started_at: row.get::<_, Option<i64>>(10)?.map(|t| t as u64),
+        completed_at: row.get::<_, Option<i64>>(11)?.map(|t| t as u64),
+    })
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+impl AnnotationStore {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        Ok(Self { db: Database::new(paths::data_file(app, "annotations.sqlite")?, "annotation store", init) })
+    }
+
+    pub fn close(&self) {
+        self.db.close()
+    }
+
+    pub fn is_queued(&self, file: &str) -> Result<bool, String> {
+        self.db.with_conn(|conn| {
+            conn.query_row("SELECT 1 FROM review_queue WHERE file = ?1", params![file], |_| Ok(()))
+                .optional()
+                .map(|row| row.is_some())
+        })
+    }
+
+    /// Adds items to the review queue as `todo`; files already queued keep
+    /// their entry. Returns how many were added.
+    pub fn enqueue(&self, items: &[ReviewItem]) -> Result<usize, String> {
+        self.db.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let mut added = 0;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT OR IGNORE INTO review_queue (file, priority, source, model_id, suggested_label, confidence, added_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?;
+                for item in items {
+                    added += stmt.execute(params![
+                        item.file,
+                        item.priority,
+                        item.source,
+                        item.model_id,
+                        item.suggested_label,
+                        item.confidence,
+                        item.added_at as i64,
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(added)
+        })
+    }
+
+    /// Queued items, highest priority first, optionally only those with
+    /// `status` or in `batch_id`.
+    pub fn review_queue(
+        &self,
+        status: Option<ReviewStatus>,
+        batch_id: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ReviewItem>, String> {
+        if !self.db.exists() {
+            return Ok(Vec::new());
+        }
+        let limit = limit.map(|l| l as i64).unwrap_or(-1);
+        self.db.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM review_queue
+                 WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR batch_id = ?2)
+                 ORDER BY priority DESC, id LIMIT ?3",
+                ITEM_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![status.map(ReviewStatus::as_str), batch_id, limit], item_from_row)?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })
+    }
+
+    /// Removes files from the review queue. Returns how many were removed.
+    pub fn dequeue(&self, files: &[String]) -> Result<usize, String> {
+        self.db.with_conn(|conn| {
+            let mut removed = 0;
+            for file in files {
+                removed += conn.execute("DELETE FROM review_queue WHERE file = ?1", params![file])?;
+            }
+            Ok(removed)
+        })
+    }
+
+    /// Groups the `size` highest-priority unbatched `todo` items into a new
+    /// batch.
+    pub fn create_batch(&self, name: &str, assignee: Option<&str>, size: usize) -> Result<LabelingBatch, String> {
+        if name.trim().is_empty() {
+            return Err("A batch needs a name.".to_string());
+        }
+        if size == 0 {
+            return Err("A batch needs at least one item.".to_string());
+        }
+        let id = new_id("batch");
+        let taken = self.db.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO labeling_batches (id, name, assignee, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![id, name.trim(), assignee, unix_now() as i64],
+            )?;
+            let taken = tx.execute(
+                "UPDATE review_queue SET batch_id = ?1 WHERE id IN (
+                     SELECT id FROM review_queue WHERE status = 'todo' AND batch_id IS NULL
+                     ORDER BY priority DESC, id LIMIT ?2)",
+                params![id, size as i64],
+            )?;
+            if taken > 0 {
+                tx.commit()?;
+            }
+            Ok(taken)
+        })?;
+        if taken == 0 {
+            return Err("No unbatched items are waiting in the review queue.".to_string());
+        }
+        self.batch(&id)
+    }
+
+    fn batch(&self, batch_id: &str) -> Result<LabelingBatch, String> {
+        self.list_batches()?
+            .into_iter()
+            .find(|b| b.id == batch_id)
+            .ok_or_else(|| format!("Unknown batch {}.", batch_id))
+    }
+
+    /// Batches with their progress, newest first.
+    pub fn list_batches(&self) -> Result<Vec<LabelingBatch>, String> {
+        if !self.db.exists() {
+            return Ok(Vec::new());
+        }
+        self.db.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT b.id, b.name, b.assignee, b.created_at,
+                        COUNT(CASE WHEN q.status = 'todo' THEN 1 END),
+                        COUNT(CASE WHEN q.status = 'in_review' THEN 1 END),
+                        COUNT(CASE WHEN q.status = 'done' THEN 1 END)
+                 FROM labeling_batches b LEFT JOIN review_queue q ON q.batch_id = b.id
+                 GROUP BY b.id ORDER BY b.created_at DESC, b.id DESC",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(LabelingBatch {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    assignee: row.get(2)?,
+                    created_at: row.get::<_, i64>(3)? as u64,
+                    todo: row.get::<_, i64>(4)? as usize,
+                    in_review: row.get::<_, i64>(5)? as usize,
+                    done: row.get::<_, i64>(6)? as usize,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })
+    }
+
+    /// Sets or clears who labels a batch.
+    pub fn assign_batch(&self, batch_id: &str, assignee: Option<&str>) -> Result<LabelingBatch, String> {
+        let updated = self.db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE labeling_batches SET assignee = ?2 WHERE id = ?1",
+                params![batch_id, assignee],
+            )
+        })?;
+        if updated == 0 {
+            return Err(format!("Unknown batch {}.", batch_id));
+        }
+        self.batch(batch_id)
+    }
+
+    /// Deletes a batch; its unfinished items return to the unbatched queue.
+    pub fn remove_batch(&self, batch_id: &str) -> Result<(), String> {
+        self.db.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "UPDATE review_queue SET batch_id = NULL, status = 'todo', started_at = NULL
+                 WHERE batch_id = ?1 AND status != 'done'",
+                params![batch_id],
+            )?;
+            tx.execute("DELETE FROM labeling_batches WHERE id = ?1", params![batch_id])?;
+            tx.commit()
+        })
+    }
+
+    /// Hands out the highest-priority `todo` item, from `batch_id` when given,
+    /// and marks it in review. `None` when nothing is left.
+    pub fn next_item(&self, batch_id: Option<&str>) -> Result<Option<ReviewItem>, String> {
+        let file: Option<String> = self.db.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let file: Option<String> = tx
+                .query_row(
+                    "SELECT file FROM review_queue WHERE status = 'todo' AND (?1 IS NULL OR batch_id = ?1)
+                     ORDER BY priority DESC, id LIMIT 1",
+                    params![batch_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(file) = &file {
+                tx.execute(
+                    "UPDATE review_queue SET status = 'in_review', started_at = ?2 WHERE file = ?1",
+                    params![file, unix_now() as i64],
+                )?;
+            }
+            tx.commit()?;
+            Ok(file)
+        })?;
+        match file {
+            Some(file) => self.item(&file).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn item(&self, file: &str) -> Result<ReviewItem, String> {
+        self.db.with_conn(|conn| {
+            conn.query_row(
+                &format!("SELECT {} FROM review_queue WHERE file = ?1", ITEM_COLUMNS),
+                params![file],
+                item_from_row,
+            )
+            .optional()
+        })?
+        .ok_or_else(|| format!("{} is not in the review queue.", file))
+    }
+
+    /// Records the reviewer's label and marks the item done.
+    pub fn submit_label(&self, file: &str, label: &str) -> Result<ReviewItem, String> {
+        if label.trim().is_empty() {
+            return Err("The label is empty.".to_string());
+        }
+        let now = unix_now() as i64;
+        self.db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE review_queue SET status = 'done', label = ?2, completed_at = ?3,
+                        started_at = COALESCE(started_at, ?3)
+                 WHERE file = ?1",
+                params![file, label.trim(), now],
+            )
+        })?;
+        self.item(file)
+    }
+
+    /// Moves an item back to `todo` (e.g. skipped) or to another status by hand.
+    /// Returning to `todo` clears the label.
+    pub fn set_status(&self, file: &str, status: ReviewStatus) -> Result<ReviewItem, String> {
+        let now = unix_now() as i64;
+        self.db.with_conn(|conn| match status {
+            ReviewStatus::Todo => conn.execute(
+                "UPDATE review_queue SET status = 'todo', label = NULL, started_at = NULL, completed_at = NULL WHERE file = ?1",
+                params![file],
+            ),
+            ReviewStatus::InReview => conn.execute(
+                "UPDATE review_queue SET status = 'in_review', started_at = ?2, completed_at = NULL WHERE file = ?1",
+                params![file, now],
+            ),
+            ReviewStatus::Done => conn.execute(
+                "UPDATE review_queue SET status = 'done', completed_at = ?2 WHERE file = ?1",
+                params![file, now],
+            ),
+        })?;
+        self.item(file)
+    }
+
+    /// Queue totals and labeling throughput.
+    pub fn stats(&self) -> Result<LabelingStats, String> {
+        if !self.db.exists() {
+            return Ok(LabelingStats::default());
+        }
+        let now = unix_now();
+        let day_ago = now.saturating_sub(86_400);
+        let hour_ago = now.saturating_sub(3600);
+        self.db.with_conn(|conn| {
+            let mut stats = LabelingStats::default();
+            let mut stmt = conn.prepare("SELECT status, COUNT(*) FROM review_queue GROUP BY status")?;
+            let counts = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))?;
+            for count in counts {
+                let (status, n) = count?;
+                match ReviewStatus::parse(&status) {
+                    ReviewStatus::Todo => stats.todo += n,
+                    ReviewStatus::InReview => stats.in_review += n,
+                    ReviewStatus::Done => stats.done += n,
+                }
+            }
+
+            let mut stmt = conn.prepare(
+                "SELECT q.completed_at, q.started_at, COALESCE(b.assignee, '')
+                 FROM review_queue q LEFT JOIN labeling_batches b ON q.batch_id = b.id
+                 WHERE q.status = 'done'",
+            )?;
+            let done = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, Option<i64>>(0)?.map(|t| t as u64),
+                    row.get::<_, Option<i64>>(1)?.map(|t| t as u64),
+                    row.get::<_, String>(2)?,
+                ))
+            })?;
+            let mut durations = Vec::new();
+            let mut active_hours = BTreeSet::new();
+            for row in done {
+                let (completed_at, started_at, assignee) = row?;
+                let by_assignee = stats.by_assignee.entry(assignee).or_default();
+                by_assignee.done += 1;
+                let Some(completed_at) = completed_at else {
+                    continue;
+                };
+                if completed_at >= hour_ago {
+                    stats.done_last_hour += 1;
+                }
+                if completed_at >= day_ago {
+                    stats.done_last_24h += 1;
+                    by_assignee.done_last_24h += 1;
+                    active_hours.insert(completed_at / 3600);
+                    if let Some(started_at) = started_at {
+                        durations.push(completed_at.saturating_sub(started_at) as f64);
+                    }
+                }
+            }
+            stats.median_seconds_per_item = median(durations);
+            if !active_hours.is_empty() {
+                stats.items_per_active_hour = Some(stats.done_last_24h as f64 / active_hours.len() as f64);
+            }
+            Ok(stats)
+        })
+    }
+}
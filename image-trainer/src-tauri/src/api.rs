@@ -0,0 +1,430 @@
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::active_learning::{LabelingSelection, SelectionStrategy};
+use crate::annotations::{LabelingBatch, LabelingStats, ReviewItem, ReviewStatus};
+use crate::api_server::{ApiServerStatus, StartedApiServer};
+use crate::app_settings::AppSettings;
+use crate::architectures::{ArchitectureDescriptor, ArchitectureInfo};
+use crate::audit::{AuditExportFormat, AuditSettings};
+use crate::augmentation::AugmentationPolicy;
+use crate::backups::{BackupInfo, BackupRun, BackupSettings};
+use crate::batch_report::BatchSummary;
+use crate::capture::{CameraInfo, CaptureInfo};
+use crate::checkpoints::{CheckpointList, CheckpointPolicy, PruneReport};
+use crate::classes::{ClassEntry, ClassSpec};
+use crate::clipboard::{ClipboardFormat, PayloadKind};
+use crate::confidence::ConfidenceReport;
+use crate::dashboard::DashboardSnapshot;
+use crate::dataset_scan::DatasetScan;
+use crate::dataset_split::{SplitMode, SplitRatios, SplitReport};
+use crate::dedupe::DedupeReport;
+use crate::dev_mode::DevModeStatus;
+use crate::downloads::DownloadedWeights;
+use crate::drift::{DriftReport, DriftSettings};
+use crate::error::BackendError;
+use crate::evaluation::EvaluationReport;
+use crate::events::Envelope;
+use crate::gallery::Misclassification;
+use crate::gpu_monitor::{GpuDevice, GpuStats};
+use crate::hardware::{GpuReport, SystemInfo};
+use crate::hf_hub::{HfDownload, HfKind, HfRepo, HfTokenStatus};
+use crate::history::{HistoryEntry, HistoryFilter};
+use crate::hooks::PostJobHook;
+use crate::hpo::{HpoConfig, HpoStudy};
+use crate::i18n::{LanguageSettings, LanguageStatus};
+use crate::image_meta::ImageMetadata;
+use crate::interpreters::{DependencyStatus, PythonInterpreter};
+use crate::job_env::JobEnvSettings;
+use crate::jobs::{JobControl, JobRecord};
+use crate::live_metrics::{JobMetrics, MetricsSettings};
+use crate::logging::LogEntry;
+use crate::metrics::{MetricRef, RunComparison};
+use crate::model_card::{CardFormat, ModelCard};
+use crate::models::RegisteredModel;
+use crate::onnx::OnnxExport;
+use crate::paths::StorageLocations;
+use crate::plugins::{PluginInfo, PluginRunOutput};
+use crate::ports::ServiceEndpoint;
+use crate::predictions::{BatchPredictionRequest, BatchPredictionResult, ExportFormat, PredictionRecord, PredictionRequest};
+use crate::preflight::{PreflightJob, PreflightReport};
+use crate::presets::{PackInfo, PresetLibrary};
+use crate::profiles::{Profile, ProfilesStatus};
+use crate::project_archive::{ProjectExport, ProjectImport};
+use crate::projects::{OpenProject, Project};
+use crate::python::{BackendStatus, PythonDiagnosis, PythonSettings};
+use crate::recents::{RecentItem, RecentKind};
+use crate::report::ReportFormat;
+use crate::resource_monitor::ResourceMonitorStatus;
+use crate::run_journal::UnfinishedRun;
+use crate::schedule::{ScheduledJob, Trigger};
+use crate::server_security::{
+    ApiKeyInfo, IssuedApiKey, ServerSecuritySettings, ServerSecurityStatus, TlsCertificateInfo,
+};
+use crate::settings_transfer::{SettingsFile, SettingsImportReport};
+use crate::shutdown::ActiveWork;
+use crate::startup_check::StartupReport;
+use crate::sweep::{SweepConfig, SweepProgress};
+use crate::synthetic::MaterializeReport;
+use crate::table_profile::TableProfile;
+use crate::tables::{DbTable, TablePage};
+use crate::tabular::{TabularRequest, TabularResponse};
+use crate::tabular_pipeline::Pipeline;
+use crate::thumbnails::Thumbnail;
+use crate::training::{BatchSizeProbeReport, ReproducibilityCheck, TrainingConfig, TrainingConfigReport};
+use crate::updater::UpdateInfo;
+use crate::venv::VenvStatus;
+use crate::verify::VerifyReport;
+use crate::wasm_plugins::{HookResult, WasmPluginInfo};
+use crate::watch::WatchInfo;
+use crate::webhooks::{Webhook, WebhookDelivery};
+
+/// Version of the command and event surface. Bump on any breaking change to a
+/// command's parameters or result, or to event payloads, and list the change
+/// in `BREAKING_CHANGES`.
+pub const API_VERSION: u32 = 4;
+/// Oldest client version served. There are no compatibility shims for the
+/// changes in `BREAKING_CHANGES`, so older clients are refused rather than
+/// sent results in a shape they do not expect.
+pub const MIN_API_VERSION: u32 = 4;
+
+/// What each version changed that clients of the previous one cannot handle,
+/// quoted when refusing an older client.
+const BREAKING_CHANGES: &[(u32, &str)] = &[
+    (3, "failing commands return an error object with a kind and a message instead of a string"),
+    (
+        4,
+        "run_tabular_processor, run_prediction, run_check_gpu, get_system_info and check_dependencies \
+take typed requests and return parsed results instead of JSON strings",
+    ),
+];
+
+/// Event names a frontend can listen to, besides per-plugin topics.
+pub const EVENT_TOPICS: &[&str] = &[
+    "job://output",
+    "job://finished",
+    "automl://progress",
+    "automl://complete",
+    "schedule://started",
+    "schedule://skipped",
+    "hook://finished",
+    "webhook://delivery",
+    "bus://dropped",
+    "dev://script_changed",
+    "monitor://drift",
+    "backup://completed",
+    "app://close_requested",
+    "app://startup_report",
+    "augment://progress",
+    "split://progress",
+    "dedupe://progress",
+    "verify://progress",
+    "gpu://stats",
+    "system://resources",
+    "hpo://trial",
+    "hpo://status",
+    "download://progress",
+    "tabular://progress",
+    "job://progress",
+    "training://metrics",
+    "job://cpu_fallback",
+    "python://stdout",
+    "python://stderr",
+    "onnx://progress",
+    "predict://result",
+    "watch://prediction",
+    "watch://failed",
+    "capture://frame",
+    "capture://stopped",
+    "settings://changed",
+    "update://progress",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamDescriptor {
+    /// Key in the invoke arguments (camelCase, as Tauri expects).
+    pub name: &'static str,
+    pub schema: Schema,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandDescriptor {
+    pub name: &'static str,
+    /// API version that introduced the command in its current shape.
+    pub since: u32,
+    pub description: &'static str,
+    pub params: Vec<ParamDescriptor>,
+    /// Schema of the success value; failures are described by `errors`.
+    pub returns: Schema,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiDescriptor {
+    pub version: u32,
+    pub min_supported_version: u32,
+    pub commands: Vec<CommandDescriptor>,
+    pub events: Vec<&'static str>,
+    /// Schema of the error every failing command returns.
+    pub errors: Schema,
+    /// Shared type definitions referenced by `$ref` from the schemas above.
+    pub definitions: BTreeMap<String, Schema>,
+}
+
+/// Result of `negotiate_api_version`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiHandshake {
+    pub server_version: u32,
+    /// Version the backend will speak to this client.
+    pub version: u32,
+}
+
+/// Checks a client's API version; anything older than `MIN_API_VERSION` or
+/// newer than this backend is refused.
+pub fn negotiate(client_version: u32) -> Result<ApiHandshake, String> {
+    if client_version > API_VERSION {
+        return Err(format!(
+            "Client speaks API version {} but this backend only supports up to {}. Update EPOQ.",
+            client_version, API_VERSION
+        ));
+    }
+    if client_version < MIN_API_VERSION {
+        let changes: Vec<&str> = BREAKING_CHANGES
+            .iter()
+            .filter(|(version, _)| *version > client_version)
+            .map(|(_, change)| *change)
+            .collect();
+        return Err(format!(
+            "API version {} is no longer supported; the oldest supported version is {}. Since then: {}.",
+            client_version,
+            MIN_API_VERSION,
+            changes.join("; ")
+        ));
+    }
+    Ok(ApiHandshake {
+        server_version: API_VERSION,
+        version: client_version,
+    })
+}
+
+macro_rules! command {
+    ($gen:expr, $name:literal, since $since:literal, $desc:literal, ($($param:literal: $ty:ty),* $(,)?) -> $ret:ty) => {
+        CommandDescriptor {
+            name: $name,
+            since: $since,
+            description: $desc,
+            params: vec![$(ParamDescriptor { name: $param, schema: $gen.subschema_for::<$ty>() }),*],
+            returns: $gen.subschema_for::<$ret>(),
+        }
+    };
+}
+
+/// Describes every command registered in `main`. Keep in sync with the
+/// `generate_handler!` list when adding or changing commands.
+pub fn descriptor() -> ApiDescriptor {
+    let mut gen = SchemaGenerator::new(SchemaSettings::draft07());
+    type Inputs = serde_json::Map<String, serde_json::Value>;
+
+    let commands = vec![
+        command!(gen, "get_api_descriptor", since 2, "This descriptor.", () -> serde_json::Value),
+        command!(gen, "negotiate_api_version", since 2, "Agrees on an API version; clients older than minSupportedVersion are refused.", ("clientVersion": u32) -> serde_json::Value),
+        command!(gen, "run_tabular_processor", since 4, "Loads or processes a table with tabular_processor.py and returns a preview of the result. With jobId, output lines are streamed as python://stdout and python://stderr; with request.chunk_rows, CSV/TSV files are processed in chunks with tabular://progress after each. With sessionId, process runs are recorded in that session's undoable pipeline.", ("request": TabularRequest, "jobId": Option<String>, "sessionId": Option<String>) -> TabularResponse),
+        command!(gen, "run_check_gpu", since 4, "GPU detection result, with wgpu adapters when PyTorch sees no CUDA device.", () -> GpuReport),
+        command!(gen, "get_system_info", since 4, "Hardware and Python details.", () -> SystemInfo),
+        command!(gen, "check_dependencies", since 4, "Which required Python packages are installed; python is false when no interpreter starts.", () -> DependencyStatus),
+        command!(gen, "validate_training_config", since 2, "Validates a config against the detected GPU.", ("config": TrainingConfig) -> TrainingConfigReport),
+        command!(gen, "verify_run_reproducibility", since 2, "Compares a run's recorded seed with the requested one.", ("config": TrainingConfig) -> ReproducibilityCheck),
+        command!(gen, "find_max_batch_size", since 2, "Probes the largest batch size that fits on the GPU.", ("config": TrainingConfig) -> BatchSizeProbeReport),
+        command!(gen, "prepare_training_config", since 2, "Resolves seed, class weights, custom models and metrics.", ("config": TrainingConfig) -> TrainingConfig),
+        command!(gen, "start_automl_sweep", since 2, "Starts a multi-GPU AutoML sweep; returns its id.", ("config": SweepConfig) -> String),
+        command!(gen, "cancel_automl_sweep", since 2, "Stops every worker of a sweep.", ("sweepId": String) -> ()),
+        command!(gen, "list_automl_sweeps", since 2, "Running sweeps.", () -> Vec<SweepProgress>),
+        command!(gen, "schedule_training", since 2, "Schedules a training run.", ("config": TrainingConfig, "trigger": Trigger) -> ScheduledJob),
+        command!(gen, "list_scheduled_jobs", since 2, "Pending scheduled runs.", () -> Vec<ScheduledJob>),
+        command!(gen, "cancel_scheduled_job", since 2, "Removes a pending scheduled run.", ("id": String) -> ()),
+        command!(gen, "list_plugins", since 2, "Installed script plugins, including invalid ones.", () -> Vec<PluginInfo>),
+        command!(gen, "run_plugin", since 2, "Runs a plugin as a job; returns the job id.", ("name": String, "inputs": Inputs) -> String),
+        command!(gen, "get_plugin_outputs", since 2, "Declared outputs of a plugin run.", ("name": String, "jobId": String) -> Vec<PluginRunOutput>),
+        command!(gen, "list_wasm_plugins", since 2, "Installed WASM plugins.", () -> Vec<WasmPluginInfo>),
+        command!(gen, "run_wasm_hook", since 2, "Passes a payload through every WASM plugin implementing a hook.", ("hook": String, "payload": serde_json::Value) -> HookResult),
+        command!(gen, "replay_events", since 2, "Buffered events newer than a sequence number.", ("sinceSeq": Option<u64>, "topics": Option<Vec<String>>, "jobId": Option<String>) -> Vec<Envelope>),
+        command!(gen, "list_architectures", since 2, "Built-in and registered model architectures.", () -> Vec<ArchitectureInfo>),
+        command!(gen, "register_architecture", since 2, "Registers a custom architecture.", ("descriptor": ArchitectureDescriptor) -> ()),
+        command!(gen, "remove_architecture", since 2, "Removes a custom architecture.", ("name": String) -> ()),
+        command!(gen, "list_custom_metrics", since 2, "Evaluation metrics provided by plugins.", () -> Vec<MetricRef>),
+        command!(gen, "compare_runs", since 2, "Built-in and custom metrics of finished runs side by side.", ("runDirs": Vec<String>) -> RunComparison),
+        command!(gen, "list_post_job_hooks", since 2, "Configured post-job hooks.", () -> Vec<PostJobHook>),
+        command!(gen, "save_post_job_hook", since 2, "Creates or updates a post-job hook.", ("hook": PostJobHook) -> PostJobHook),
+        command!(gen, "remove_post_job_hook", since 2, "Deletes a post-job hook.", ("id": String) -> ()),
+        command!(gen, "get_hook_log", since 2, "Log of the hooks that ran after a job.", ("jobId": String) -> String),
+        command!(gen, "list_webhooks", since 2, "Configured webhooks; secrets are write-only.", () -> Vec<Webhook>),
+        command!(gen, "save_webhook", since 2, "Creates or updates a webhook.", ("webhook": Webhook) -> Webhook),
+        command!(gen, "remove_webhook", since 2, "Deletes a webhook.", ("id": String) -> ()),
+        command!(gen, "test_webhook", since 2, "Sends a test event to a webhook.", ("id": String) -> WebhookDelivery),
+        command!(gen, "get_preset_library", since 2, "Saved training, augmentation and pipeline presets.", () -> PresetLibrary),
+        command!(gen, "save_preset_library", since 2, "Validates and stores the preset library.", ("library": PresetLibrary) -> PresetLibrary),
+        command!(gen, "export_preset_pack", since 2, "Writes presets into a signed pack.", ("dest": String, "name": String, "author": Option<String>, "training": Vec<String>, "augmentation": Vec<String>, "pipelines": Vec<String>) -> ()),
+        command!(gen, "inspect_preset_pack", since 2, "Checks a pack without importing it.", ("path": String) -> PackInfo),
+        command!(gen, "import_preset_pack", since 2, "Imports a checked pack.", ("path": String, "trustSigner": bool, "overwrite": bool) -> PackInfo),
+        command!(gen, "get_pack_signing_key", since 2, "This installation's public pack signing key.", () -> String),
+        command!(gen, "trust_pack_signer", since 2, "Adds a public key to the trusted pack signers.", ("publicKey": String) -> ()),
+        command!(gen, "get_dev_mode", since 2, "Whether backend scripts come from a developer override directory.", () -> DevModeStatus),
+        command!(gen, "set_script_dir_override", since 2, "Sets or clears the developer script directory.", ("dir": Option<String>) -> DevModeStatus),
+        command!(gen, "export_predictions", since 2, "Exports a job's stored predictions; returns the row count.", ("jobId": String, "format": ExportFormat, "dest": String) -> usize),
+        command!(gen, "copy_to_clipboard", since 2, "Copies predictions, metrics or a run config to the clipboard; returns the text.", ("payloadKind": PayloadKind, "id": String, "format": Option<ClipboardFormat>) -> String),
+        command!(gen, "generate_model_card", since 2, "Builds and stores a model card for a run directory.", ("modelId": String) -> ModelCard),
+        command!(gen, "export_model_card", since 2, "Writes a run's model card as Markdown or JSON.", ("modelId": String, "format": CardFormat, "dest": String) -> ()),
+        command!(gen, "get_audit_settings", since 2, "Whether predictions are written to the inference audit log.", () -> AuditSettings),
+        command!(gen, "set_audit_settings", since 2, "Enables the inference audit log and sets its retention.", ("settings": AuditSettings) -> AuditSettings),
+        command!(gen, "export_audit_log", since 2, "Exports logged predictions; returns the row count.", ("format": AuditExportFormat, "dest": String, "since": Option<u64>, "until": Option<u64>) -> usize),
+        command!(gen, "get_drift_report", since 2, "Recent prediction statistics of a model against its training distribution.", ("modelId": String) -> DriftReport),
+        command!(gen, "get_drift_settings", since 2, "Drift monitoring window and thresholds.", () -> DriftSettings),
+        command!(gen, "set_drift_settings", since 2, "Updates drift monitoring window and thresholds.", ("settings": DriftSettings) -> DriftSettings),
+        command!(gen, "run_prediction", since 4, "Classifies one image with a trained model, using its stored preprocessing.", ("request": PredictionRequest) -> PredictionRecord),
+        command!(gen, "check_class_spec", since 2, "Validates a classes JSON document against a model's class map.", ("modelId": String, "classes": String) -> Vec<ClassEntry>),
+        command!(gen, "confidence_report", since 2, "Max-confidence histograms per predicted class over a folder.", ("modelId": String, "folder": String) -> ConfidenceReport),
+        command!(gen, "get_misclassified", since 2, "Misclassified test images of a run.", ("runDir": String) -> Vec<Misclassification>),
+        command!(gen, "export_misclassified_gallery", since 2, "Writes misclassified images as a standalone HTML gallery; returns the image count.", ("runDir": String, "dest": String, "limit": Option<usize>) -> usize),
+        command!(gen, "export_batch_summary", since 2, "Writes an HTML summary of a batch prediction job.", ("jobId": String, "dest": String, "lowConfidenceThreshold": Option<f64>) -> BatchSummary),
+        command!(gen, "get_storage_locations", since 2, "Directories holding settings, data, models, caches and logs.", () -> StorageLocations),
+        command!(gen, "get_python_settings", since 2, "How the Python interpreter is located.", () -> PythonSettings),
+        command!(gen, "set_python_settings", since 2, "Sets how the Python interpreter is located, e.g. the py launcher version.", ("settings": PythonSettings) -> PythonSettings),
+        command!(gen, "get_language_settings", since 2, "Language setting, active language and available translations.", () -> LanguageStatus),
+        command!(gen, "set_language_settings", since 2, "Sets the language of backend messages, reports and notifications.", ("settings": LanguageSettings) -> LanguageStatus),
+        command!(gen, "get_profiles", since 2, "Profiles on this OS account, the active one and whether to show the picker.", () -> ProfilesStatus),
+        command!(gen, "save_profile", since 2, "Creates a profile (empty id) or updates its name and model sharing.", ("profile": Profile) -> Profile),
+        command!(gen, "remove_profile", since 2, "Forgets a profile; its files stay on disk.", ("id": String) -> ()),
+        command!(gen, "set_profile_prompt", since 2, "Whether to ask for a profile when the app opens.", ("promptAtStartup": bool) -> ()),
+        command!(gen, "switch_profile", since 2, "Opens a profile, restarting the app if it is not the active one.", ("id": String) -> ()),
+        command!(gen, "list_recents", since 2, "Recently opened datasets, runs and tables of the active profile.", () -> Vec<RecentItem>),
+        command!(gen, "add_recent", since 2, "Records an opened item at the top of the recents list.", ("kind": RecentKind, "path": String) -> Vec<RecentItem>),
+        command!(gen, "clear_recents", since 2, "Clears recent items of one kind, or all.", ("kind": Option<RecentKind>) -> ()),
+        command!(gen, "get_backup_settings", since 2, "Database backup interval and rotation.", () -> BackupSettings),
+        command!(gen, "set_backup_settings", since 2, "Sets the database backup interval and rotation.", ("settings": BackupSettings) -> BackupSettings),
+        command!(gen, "list_backups", since 2, "Database snapshots, newest first, optionally for one store.", ("store": Option<String>) -> Vec<BackupInfo>),
+        command!(gen, "backup_now", since 2, "Snapshots every database immediately.", () -> BackupRun),
+        command!(gen, "restore_from_backup", since 2, "Replaces a database with a verified snapshot, saving the current contents first.", ("backup": String) -> BackupInfo),
+        command!(gen, "export_settings", since 2, "Writes settings, without secrets, to a portable file.", ("dest": String) -> SettingsFile),
+        command!(gen, "import_settings", since 2, "Applies a settings file, validating each section and skipping invalid ones.", ("file": String) -> SettingsImportReport),
+        command!(gen, "get_active_work", since 2, "Running jobs and sweeps that closing the app would stop.", () -> ActiveWork),
+        command!(gen, "confirm_shutdown", since 2, "Checkpoints training jobs, stops all work and exits the app.", ("graceSecs": Option<u64>) -> ()),
+        command!(gen, "get_startup_report", since 2, "Issues found by the launch self-check of scripts, stores, directories and Python.", ("refresh": Option<bool>) -> StartupReport),
+        command!(gen, "materialize_augmentations", since 2, "Writes a dataset copy with augmented training images, tracked in a manifest that keeps them out of evaluation.", ("dataset": String, "policy": AugmentationPolicy, "multiplier": u32, "dest": String, "seed": Option<u64>) -> MaterializeReport),
+        command!(gen, "select_for_labeling", since 2, "Scores an unlabeled folder and queues the images most worth labeling for review.", ("modelId": String, "unlabeledFolder": String, "n": usize, "strategy": SelectionStrategy) -> LabelingSelection),
+        command!(gen, "get_review_queue", since 2, "Review queue items, highest priority first, optionally filtered by status or batch.", ("status": Option<ReviewStatus>, "batchId": Option<String>, "limit": Option<usize>) -> Vec<ReviewItem>),
+        command!(gen, "remove_from_review_queue", since 2, "Removes files from the review queue; returns how many were removed.", ("files": Vec<String>) -> usize),
+        command!(gen, "create_labeling_batch", since 2, "Groups the highest-priority unbatched review items into a batch.", ("name": String, "assignee": Option<String>, "size": usize) -> LabelingBatch),
+        command!(gen, "list_labeling_batches", since 2, "Labeling batches with their progress, newest first.", () -> Vec<LabelingBatch>),
+        command!(gen, "assign_labeling_batch", since 2, "Sets or clears who labels a batch.", ("batchId": String, "assignee": Option<String>) -> LabelingBatch),
+        command!(gen, "remove_labeling_batch", since 2, "Deletes a batch; unfinished items return to the queue.", ("batchId": String) -> ()),
+        command!(gen, "next_labeling_item", since 2, "Hands out the next unlabeled item and marks it in review; null when none are left.", ("batchId": Option<String>) -> Option<ReviewItem>),
+        command!(gen, "submit_label", since 2, "Records a label for a review item and marks it done.", ("file": String, "label": String) -> ReviewItem),
+        command!(gen, "set_review_status", since 2, "Changes a review item's status, e.g. back to todo.", ("file": String, "status": ReviewStatus) -> ReviewItem),
+        command!(gen, "get_labeling_stats", since 2, "Review queue totals and labeling throughput.", () -> LabelingStats),
+        command!(gen, "get_metrics", since 2, "Raw and EMA-smoothed metric series of a running or recent job, with its progress and ETA.", ("jobId": String, "smoothing": Option<f64>) -> JobMetrics),
+        command!(gen, "get_metrics_settings", since 2, "The smoothing factor applied to metric series.", () -> MetricsSettings),
+        command!(gen, "set_metrics_settings", since 2, "Sets the smoothing factor applied to metric series.", ("settings": MetricsSettings) -> MetricsSettings),
+        command!(gen, "get_server_security", since 2, "Local server bind mode, client allow-list, API keys and TLS certificate.", () -> ServerSecurityStatus),
+        command!(gen, "set_server_security", since 2, "Sets the local server bind mode, allow-list and TLS.", ("settings": ServerSecuritySettings) -> ServerSecurityStatus),
+        command!(gen, "list_api_keys", since 2, "Local server API keys, without their secrets.", () -> Vec<ApiKeyInfo>),
+        command!(gen, "create_api_key", since 2, "Generates a local server API key; the secret is returned only once.", ("label": String) -> IssuedApiKey),
+        command!(gen, "rotate_api_key", since 2, "Replaces an API key's secret.", ("id": String) -> IssuedApiKey),
+        command!(gen, "revoke_api_key", since 2, "Deletes an API key.", ("id": String) -> ()),
+        command!(gen, "regenerate_tls_certificate", since 2, "Creates a new self-signed certificate for the local server.", () -> TlsCertificateInfo),
+        command!(gen, "get_service_endpoints", since 2, "Local services with their ports, bound address and port conflicts.", () -> Vec<ServiceEndpoint>),
+        command!(gen, "set_preferred_port", since 2, "Sets or clears the port a local service tries first.", ("service": String, "port": Option<u16>) -> Vec<ServiceEndpoint>),
+        command!(gen, "get_job_env_settings", since 2, "Environment variables set on every job unless a run overrides them.", () -> JobEnvSettings),
+        command!(gen, "set_job_env_settings", since 2, "Sets the default environment variables for jobs.", ("settings": JobEnvSettings) -> JobEnvSettings),
+        command!(gen, "get_dashboard_snapshot", since 2, "Active jobs with progress, GPU and system load, queue depth, recent completions and storage use.", () -> DashboardSnapshot),
+        command!(gen, "cancel_job", since 2, "Kills a running job and the processes it started.", ("jobId": String) -> ()),
+        command!(gen, "run_training", since 2, "Starts a training job; returns its id. Completion arrives as job://finished.", ("config": TrainingConfig) -> String),
+        command!(gen, "list_jobs", since 2, "Every job of this session with kind, arguments, times and status, newest first.", () -> Vec<JobRecord>),
+        command!(gen, "get_job", since 2, "One job's record.", ("jobId": String) -> JobRecord),
+        command!(gen, "clear_jobs", since 2, "Removes finished jobs from the list; returns how many.", () -> usize),
+        command!(gen, "list_python_interpreters", since 2, "Python 3 interpreters found on PATH, via the py launcher, pyenv and conda.", () -> Vec<PythonInterpreter>),
+        command!(gen, "set_python_interpreter", since 2, "Sets the interpreter scripts run with first; null searches PATH again.", ("path": Option<String>) -> PythonSettings),
+        command!(gen, "create_venv", since 2, "Creates the app's venv if missing and runs scripts with it.", () -> VenvStatus),
+        command!(gen, "install_requirements", since 2, "Runs pip install -r requirements.txt in the app's venv, streaming output for the job.", ("jobId": Option<String>) -> VenvStatus),
+        command!(gen, "get_venv_status", since 2, "Whether the app's venv exists, is in use and has the requirements installed.", () -> VenvStatus),
+        command!(gen, "get_backend_mode", since 2, "Whether scripts run with the bundled backend or a Python on the machine.", () -> BackendStatus),
+        command!(gen, "set_conda_environment", since 2, "Runs scripts through conda run -n in the named environment; null stops using conda.", ("name": Option<String>) -> PythonSettings),
+        command!(gen, "query_history", since 4, "Finished jobs from every session, newest first; filter by kind, status, finish time or text in the arguments.", ("filter": Option<HistoryFilter>) -> Vec<HistoryEntry>),
+        command!(gen, "register_model", since 4, "Adds a training run to the model registry, or refreshes its entry.", ("runDir": String, "name": Option<String>) -> RegisteredModel),
+        command!(gen, "list_models", since 4, "Registered models, newest first, including runs found in the models folder.", () -> Vec<RegisteredModel>),
+        command!(gen, "get_model", since 4, "One registered model by id (its run directory).", ("id": String) -> RegisteredModel),
+        command!(gen, "delete_model", since 4, "Removes a model from the registry; deleteFiles also deletes its run directory.", ("id": String, "deleteFiles": Option<bool>) -> ()),
+        command!(gen, "export_model_onnx", since 4, "Exports a trained model to ONNX and records the path in the model registry; progress arrives as onnx://progress.", ("modelId": String, "outPath": String, "opset": Option<u32>, "jobId": Option<String>) -> OnnxExport),
+        command!(gen, "run_batch_prediction", since 4, "Classifies every image in a folder in batches; results arrive as predict://result and can be written to dest.", ("request": BatchPredictionRequest, "jobId": Option<String>) -> BatchPredictionResult),
+        command!(gen, "start_watch_folder", since 4, "Classifies images dropped into a folder; results arrive as watch://prediction and watch://failed.", ("path": String, "modelId": String, "classes": Option<ClassSpec>) -> WatchInfo),
+        command!(gen, "stop_watch_folder", since 4, "Stops watching a folder.", ("watchId": String) -> ()),
+        command!(gen, "list_watch_folders", since 4, "Folders being watched, oldest first.", () -> Vec<WatchInfo>),
+        command!(gen, "list_cameras", since 4, "Cameras that can be captured from; needs a build with the webcam feature.", () -> Vec<CameraInfo>),
+        command!(gen, "start_capture", since 4, "Classifies camera frames at up to fps per second; results arrive as capture://frame, then capture://stopped.", ("modelId": String, "cameraIndex": Option<u32>, "fps": Option<f64>, "classes": Option<ClassSpec>) -> CaptureInfo),
+        command!(gen, "stop_capture", since 4, "Stops a camera capture.", ("captureId": String) -> ()),
+        command!(gen, "list_captures", since 4, "Running camera captures, oldest first.", () -> Vec<CaptureInfo>),
+        command!(gen, "scan_dataset", since 4, "Checks a dataset's class folders without Python: images per class, empty classes, unsupported files, duplicate names and problems.", ("path": String) -> DatasetScan),
+        command!(gen, "split_dataset", since 4, "Splits a dataset into train, val and test by copying, linking or writing a manifest; stratified unless told otherwise. Progress arrives as split://progress.", ("path": String, "dest": String, "ratios": SplitRatios, "stratified": Option<bool>, "seed": Option<u64>, "mode": Option<SplitMode>) -> SplitReport),
+        command!(gen, "dedupe_dataset", since 4, "Near-duplicate image pairs by perceptual hash, closest first, flagging pairs across splits; progress arrives as dedupe://progress.", ("path": String, "threshold": Option<u32>) -> DedupeReport),
+        command!(gen, "get_thumbnail", since 4, "A cached JPEG thumbnail of an image, at most size pixels (default 256) on its longer side; load the path with convertFileSrc.", ("path": String, "size": Option<u32>) -> Thumbnail),
+        command!(gen, "clear_thumbnail_cache", since 4, "Deletes every cached thumbnail; returns the bytes freed.", () -> u64),
+        command!(gen, "read_image_metadata", since 4, "Dimensions, EXIF orientation, camera and capture date of an image; thumbnails and training apply the orientation.", ("path": String) -> ImageMetadata),
+        command!(gen, "verify_images", since 4, "Decodes every image in a folder and reports empty, truncated, corrupt and oversized files; progress arrives as verify://progress.", ("folder": String) -> VerifyReport),
+        command!(gen, "preview_table", since 4, "A page of a CSV, TSV, Parquet, Feather or Excel file read without Python: rows (default 100, at most 5000) from offset, with column types and missing counts for the whole file.", ("file": String, "rows": Option<usize>, "offset": Option<usize>) -> TablePage),
+        command!(gen, "list_db_tables", since 4, "Tables and views of an SQLite database, with the sqlite://file#table source to pass to the tabular commands.", ("path": String) -> Vec<DbTable>),
+        command!(gen, "profile_table", since 4, "Per-column type, missing count, cardinality, min/max, mean, histogram and top values of a table, computed without Python, with warnings about columns to fix before training.", ("file": String) -> TableProfile),
+        command!(gen, "get_pipeline", since 4, "The tabular steps applied in a session, oldest first, and the undone steps that can be redone.", ("sessionId": String) -> Pipeline),
+        command!(gen, "undo_tabular_step", since 4, "Restores the table changed by the session's last step from the snapshot taken before it.", ("sessionId": String) -> Pipeline),
+        command!(gen, "redo_tabular_step", since 4, "Runs the session's most recently undone step again.", ("sessionId": String) -> Pipeline),
+        command!(gen, "get_gpu_stats", since 4, "Utilization, VRAM, temperature and power of every NVIDIA GPU through NVML, or check_gpu.py without it; published every second as gpu://stats while training runs.", () -> GpuStats),
+        command!(gen, "list_gpu_devices", since 4, "GPUs in PCI bus order; pass an index (or several joined with commas, or cpu) as the device of a training config or prediction request.", () -> Vec<GpuDevice>),
+        command!(gen, "start_resource_monitor", since 4, "Publishes CPU load per core, RAM, swap and disk throughput as system://resources every intervalMs (250 to 60000, default 1000); starting again replaces the running monitor.", ("intervalMs": Option<u64>) -> ResourceMonitorStatus),
+        command!(gen, "stop_resource_monitor", since 4, "Stops the system://resources monitor.", () -> ResourceMonitorStatus),
+        command!(gen, "preflight_check", since 4, "Estimated disk space of a training run (weights, checkpoint, dataset zip) or ONNX export against the free space on its drive; run_training and export_model_onnx refuse to start when it does not fit.", ("job": PreflightJob) -> PreflightReport),
+        command!(gen, "diagnose_python", since 4, "Tries every Python interpreter, retrying failed starts per python settings, and reports the command, outcome, exit code and stderr of each attempt.", () -> PythonDiagnosis),
+        command!(gen, "get_logs", since 4, "The last tailN entries of the app log (200 by default), oldest first, at level or more severe.", ("level": Option<String>, "tailN": Option<usize>) -> Vec<LogEntry>),
+        command!(gen, "open_log_dir", since 4, "Opens the folder holding the daily log files, to attach them to a bug report; returns its path.", () -> String),
+        command!(gen, "list_unfinished_runs", since 4, "Training runs that crashed, were stopped or were cut off by the app closing, newest first, with the checkpoint each can resume from.", () -> Vec<UnfinishedRun>),
+        command!(gen, "resume_training", since 4, "Relaunches an unfinished run from its checkpoint.pth as a new job; returns the new job id.", ("jobId": String) -> String),
+        command!(gen, "list_checkpoints", since 4, "The latest (checkpoint.pth), best (best_model.pth) and per-epoch checkpoints of a run, with validation metrics and sizes.", ("runId": String) -> CheckpointList),
+        command!(gen, "delete_checkpoint", since 4, "Deletes a checkpoint by its name from list_checkpoints; best_model.pth cannot be deleted this way.", ("runId": String, "name": String) -> CheckpointList),
+        command!(gen, "prune_checkpoints", since 4, "Stores the keep-best/keep-last policy of a run, or uses the stored one, and deletes the per-epoch checkpoints it does not keep.", ("runId": String, "policy": Option<CheckpointPolicy>) -> PruneReport),
+        command!(gen, "promote_checkpoint_to_model", since 4, "Copies a checkpoint with the run's metadata into a new run directory and registers it as a model.", ("runId": String, "name": String) -> RegisteredModel),
+        command!(gen, "run_hpo", since 4, "Starts an Optuna study over learning rate, batch size and optimizer. Trials are hpo_trial jobs streamed as hpo://trial; the best trial is registered as a model with its hyperparameters when the study ends (hpo://status).", ("config": HpoConfig) -> HpoStudy),
+        command!(gen, "get_hpo_study", since 4, "A study with its trials and best parameters.", ("studyId": String) -> HpoStudy),
+        command!(gen, "list_hpo_studies", since 4, "Studies of this session, newest first.", () -> Vec<HpoStudy>),
+        command!(gen, "pause_hpo", since 4, "Holds the next trial until resume_hpo; the running trial finishes.", ("studyId": String) -> HpoStudy),
+        command!(gen, "resume_hpo", since 4, "Lets a paused study start trials again.", ("studyId": String) -> HpoStudy),
+        command!(gen, "stop_hpo", since 4, "Ends a study after its running trial; cancel_job with the study id stops it at once.", ("studyId": String) -> HpoStudy),
+        command!(gen, "send_job_control", since 4, "Stops a training job after its epoch, saves a checkpoint now, or extends it by some epochs.", ("jobId": String, "action": JobControl) -> ()),
+        command!(gen, "evaluate_model", since 4, "Evaluates a model on a labelled test folder: confusion matrix, per-class precision, recall and F1, and misclassified images, saved with the model.", ("modelId": String, "testDir": String) -> EvaluationReport),
+        command!(gen, "export_report", since 4, "Writes a standalone HTML or PDF report of a run: configuration, metric curves, confusion matrix and sample predictions; returns the path.", ("runId": String, "format": ReportFormat, "outPath": String) -> String),
+        command!(gen, "download_pretrained", since 4, "Downloads a built-in model's pretrained weights into the shared cache with progress (download://progress), resume and checksum verification.", ("modelName": String) -> DownloadedWeights),
+        command!(gen, "hf_search", since 4, "Searches Hugging Face Hub models or datasets, most downloaded first.", ("query": String, "kind": Option<HfKind>) -> Vec<HfRepo>),
+        command!(gen, "hf_download", since 4, "Downloads a Hugging Face repository revision into the model or dataset store; models trained with this app are flagged registrable, not registered.", ("repoId": String, "revision": Option<String>, "kind": Option<HfKind>) -> HfDownload),
+        command!(gen, "hf_token_status", since 4, "Whether a Hugging Face access token is configured.", () -> HfTokenStatus),
+        command!(gen, "set_hf_token", since 4, "Stores or removes the Hugging Face access token in the OS keyring.", ("token": Option<String>) -> HfTokenStatus),
+        command!(gen, "export_project", since 4, "Packs a training run and a manifest of its dataset into one .epoq archive.", ("runId": String, "outPath": String) -> ProjectExport),
+        command!(gen, "import_project", since 4, "Unpacks a .epoq archive into the models folder, verifying checksums, and registers the run.", ("path": String) -> ProjectImport),
+        command!(gen, "create_project", since 4, "Creates a project directory with a project.json manifest and opens it.", ("path": String, "name": String, "description": Option<String>) -> OpenProject),
+        command!(gen, "open_project", since 4, "Opens a project directory and makes it the active project.", ("path": String) -> OpenProject),
+        command!(gen, "save_project", since 4, "Writes the active project's manifest, optionally replacing its contents.", ("project": Option<Project>) -> OpenProject),
+        command!(gen, "get_active_project", since 4, "The active project, if any.", () -> Option<OpenProject>),
+        command!(gen, "get_settings", since 4, "General app settings: interpreter, default output folder, theme and concurrency limits.", () -> AppSettings),
+        command!(gen, "update_settings", since 4, "Changes the given app settings and publishes settings://changed.", ("patch": serde_json::Value) -> AppSettings),
+        command!(gen, "check_for_updates", since 4, "Checks the release channel chosen in settings for a newer version.", () -> UpdateInfo),
+        command!(gen, "install_update", since 4, "Downloads and installs the pending update (update://progress), then restarts the app.", () -> ()),
+        command!(gen, "start_api_server", since 4, "Starts the REST server (predict, train, models, job status) on a port, with a session token that is generated when omitted.", ("port": Option<u16>, "token": Option<String>) -> StartedApiServer),
+        command!(gen, "stop_api_server", since 4, "Stops the REST server.", () -> ()),
+        command!(gen, "get_api_server_status", since 4, "Whether the REST server runs, and its URL.", () -> ApiServerStatus),
+    ];
+
+    ApiDescriptor {
+        version: API_VERSION,
+        min_supported_version: MIN_API_VERSION,
+        commands,
+        events: EVENT_TOPICS.to_vec(),
+        errors: gen.subschema_for::<BackendError>(),
+        definitions: gen.take_definitions().into_iter().collect(),
+    }
+}
@@ -0,0 +1,348 @@
+use axum::extract::{ConnectInfo, Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rand::RngCore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::api::API_VERSION;
+use crate::classes::ClassSpec;
+use crate::error::BackendError;
+use crate::jobs::{self, JobManager, JobRecord};
+use crate::live_metrics::{MetricsCollector, TrainingProgress};
+use crate::models::{self, RegisteredModel};
+use crate::ports::PortManager;
+use crate::predictions::{self, PredictionRecord};
+use crate::server_security::{secrets_match, ServerSecurity};
+use crate::training::TrainingConfig;
+
+/// Name of the server in the port registry.
+const SERVICE: &str = "rest_server";
+/// Shorter tokens are too easy to guess for a server others can reach.
+const MIN_TOKEN_LEN: usize = 16;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ApiServerStatus {
+    pub running: bool,
+    /// Base URL while running, e.g. `http://127.0.0.1:8765/api/v1`.
+    pub url: Option<String>,
+    pub tls: bool,
+    pub started_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StartedApiServer {
+    pub status: ApiServerStatus,
+    /// The session token, when the app generated it; shown only here.
+    pub token: Option<String>,
+}
+
+struct Running {
+    /// Which `start` this is, so a stopped server's task leaves a newer one alone.
+    generation: u64,
+    handle: axum_server::Handle,
+    url: String,
+    tls: bool,
+    started_at: u64,
+}
+
+/// Managed state: the REST server, while it runs.
+#[derive(Default)]
+pub struct ApiServer {
+    running: Mutex<Option<Running>>,
+    generation: AtomicU64,
+}
+
+#[derive(Clone)]
+struct ServerState {
+    app: tauri::AppHandle,
+    /// Accepted besides the API keys, until the server stops.
+    session_token: Arc<String>,
+    security: Arc<ServerSecurity>,
+    jobs: Arc<JobManager>,
+}
+
+/// A failed request: the status code and the same error the commands return.
+struct ApiError(StatusCode, BackendError);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<BackendError> for ApiError {
+    fn from(error: BackendError) -> Self {
+        let status = match &error {
+            BackendError::InvalidInput { .. } => StatusCode::BAD_REQUEST,
+            BackendError::NotFound { .. } => StatusCode::NOT_FOUND,
+            BackendError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            BackendError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            BackendError::PythonNotFound { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, error)
+    }
+}
+
+/// Untyped failures are the server's fault unless classified at the source.
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        BackendError::from(message).into()
+    }
+}
+
+fn bad_request(message: impl Into<String>) -> ApiError {
+    BackendError::invalid_input(message).into()
+}
+
+fn not_found(message: impl Into<String>) -> ApiError {
+    BackendError::not_found(message).into()
+}
+
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer =
+        headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("Bearer "));
+    bearer.or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok())).map(str::trim)
+}
+
+/// Rejects clients outside the allow-list and requests without the session
+/// token or a valid API key.
+async fn authorize(
+    State(state): State<ServerState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.security.allows(client.ip()) {
+        tracing::warn!(client = %client, "REST request from a client that is not allowed");
+        return ApiError::from(BackendError::forbidden("This client is not allowed.")).into_response();
+    }
+    let Some(key) = presented_key(request.headers()).map(str::to_string) else {
+        return ApiError::from(BackendError::unauthorized("Send a token as 'Authorization: Bearer <token>'."))
+            .into_response();
+    };
+    let security = state.security.clone();
+    let session = state.session_token.clone();
+    let caller = tauri::async_runtime::spawn_blocking(move || {
+        if secrets_match(&session, &key) {
+            Some("session".to_string())
+        } else {
+            security.authenticate(&key).map(|info| info.label)
+        }
+    })
+    .await
+    .ok()
+    .flatten();
+    let Some(caller) = caller else {
+        tracing::warn!(client = %client, "REST request with an invalid token");
+        return ApiError::from(BackendError::unauthorized("Invalid token.")).into_response();
+    };
+    tracing::info!(client = %client, caller = %caller, method = %request.method(), path = %request.uri().path(), "REST request");
+    next.run(request).await
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION"), "api_version": API_VERSION }))
+}
+
+async fn list_models(State(state): State<ServerState>) -> Result<Json<Vec<RegisteredModel>>, ApiError> {
+    Ok(Json(models::list(&state.app)?))
+}
+
+#[derive(Deserialize)]
+struct PredictBody {
+    model_id: String,
+    images: Vec<String>,
+    #[serde(default)]
+    classes: Option<ClassSpec>,
+    #[serde(default)]
+    device: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PredictResponse {
+    prediction_id: String,
+    records: Vec<PredictionRecord>,
+    failures: Vec<String>,
+}
+
+async fn predict(
+    State(state): State<ServerState>,
+    Json(body): Json<PredictBody>,
+) -> Result<Json<PredictResponse>, ApiError> {
+    if !std::path::Path::new(&body.model_id).is_dir() {
+        return Err(not_found(format!("Model {} does not exist.", body.model_id)));
+    }
+    if body.images.is_empty() {
+        return Err(bad_request("Send at least one image path."));
+    }
+    if let Some(missing) = body.images.iter().find(|image| !std::path::Path::new(image).is_file()) {
+        return Err(bad_request(format!("{} does not exist or is not a file.", missing)));
+    }
+    let (prediction_id, records, failures) =
+        predictions::predict(&state.app, &body.model_id, &body.images, body.classes.as_ref(), body.device.as_deref())
+            .await?;
+    Ok(Json(PredictResponse { prediction_id, records, failures }))
+}
+
+async fn train(
+    State(state): State<ServerState>,
+    Json(config): Json<TrainingConfig>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let job_id = jobs::start_training(&state.app, &state.jobs, config).await?;
+    Ok(Json(serde_json::json!({ "job_id": job_id })))
+}
+
+async fn list_jobs(State(state): State<ServerState>) -> Json<Vec<JobRecord>> {
+    Json(state.jobs.list())
+}
+
+#[derive(Serialize)]
+struct JobStatusResponse {
+    job: JobRecord,
+    /// Epoch and metrics of a training job, once it reported any.
+    progress: Option<TrainingProgress>,
+}
+
+async fn job_status(
+    State(state): State<ServerState>,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobStatusResponse>, ApiError> {
+    let job = state.jobs.get(&job_id).map_err(not_found)?;
+    let progress = state.app.try_state::<Arc<MetricsCollector>>().and_then(|m| m.progress(&job_id));
+    Ok(Json(JobStatusResponse { job, progress }))
+}
+
+async fn cancel_job(State(state): State<ServerState>, Path(job_id): Path<String>) -> Result<StatusCode, ApiError> {
+    state.jobs.cancel(&job_id).map_err(not_found)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn router(state: ServerState) -> Router {
+    let protected = Router::new()
+        .route("/models", get(list_models))
+        .route("/predict", post(predict))
+        .route("/train", post(train))
+        .route("/jobs", get(list_jobs))
+        .route("/jobs/:id", get(job_status))
+        .route("/jobs/:id/cancel", post(cancel_job))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize));
+    Router::new().nest("/api/v1", protected.route("/health", get(health))).with_state(state)
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+impl ApiServer {
+    pub fn status(&self) -> ApiServerStatus {
+        match &*self.running.lock().unwrap() {
+            Some(running) => ApiServerStatus {
+                running: true,
+                url: Some(running.url.clone()),
+                tls: running.tls,
+                started_at: Some(running.started_at),
+            },
+            None => ApiServerStatus { running: false, url: None, tls: false, started_at: None },
+        }
+    }
+
+    /// Serves the REST API on `port` (the preferred or default port when
+    /// `None`), with the binding, allow-list and TLS chosen in the server
+    /// security settings. Requests need `token`, or one generated if `None`,
+    /// or one of the API keys.
+    pub async fn start(
+        self: &Arc<Self>,
+        app: &tauri::AppHandle,
+        port: Option<u16>,
+        token: Option<String>,
+    ) -> Result<StartedApiServer, String> {
+        if self.running.lock().unwrap().is_some() {
+            return Err("The REST server is already running.".to_string());
+        }
+        let generated = token.is_none();
+        let token = token.map(|t| t.trim().to_string()).unwrap_or_else(generate_token);
+        if token.len() < MIN_TOKEN_LEN {
+            return Err(format!("Use a token of at least {} characters.", MIN_TOKEN_LEN));
+        }
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let security = app.state::<Arc<ServerSecurity>>().inner().clone();
+        let ports = app.state::<Arc<PortManager>>().inner().clone();
+        if port.is_some() {
+            ports.set_preferred(SERVICE, port)?;
+        }
+        let tls = security.settings().tls.then(|| security.certificate()).flatten();
+        if security.settings().tls && tls.is_none() {
+            return Err("TLS is on but there is no certificate; regenerate it in the server settings.".to_string());
+        }
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        let listener = ports.allocate(SERVICE, security.bind_host(), scheme)?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let address = listener.local_addr().map_err(|e| e.to_string())?;
+
+        let state = ServerState {
+            app: app.clone(),
+            session_token: Arc::new(token.clone()),
+            security,
+            jobs: app.state::<Arc<JobManager>>().inner().clone(),
+        };
+        let service = router(state).into_make_service_with_connect_info::<SocketAddr>();
+        let handle = axum_server::Handle::new();
+        let server = match &tls {
+            Some(cert) => {
+                let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&cert.cert_path, &cert.key_path)
+                    .await
+                    .map_err(|e| {
+                        ports.release(SERVICE);
+                        format!("Cannot load the TLS certificate: {}", e)
+                    })?;
+                let server = axum_server::from_tcp_rustls(listener, config).handle(handle.clone());
+                tauri::async_runtime::spawn(async move { server.serve(service).await })
+            }
+            None => {
+                let server = axum_server::from_tcp(listener).handle(handle.clone());
+                tauri::async_runtime::spawn(async move { server.serve(service).await })
+            }
+        };
+
+        let host = if address.ip().is_unspecified() { "localhost".to_string() } else { address.ip().to_string() };
+        let url = format!("{}://{}:{}/api/v1", scheme, host, address.port());
+        *self.running.lock().unwrap() =
+            Some(Running { generation, handle, url: url.clone(), tls: tls.is_some(), started_at: jobs::unix_now() });
+        tracing::info!("REST server listening on {}", url);
+
+        let manager = self.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Ok(Err(e)) = server.await {
+                tracing::error!("REST server stopped: {}", e);
+            }
+            let mut running = manager.running.lock().unwrap();
+            // After stop() and a new start() the port and state belong to the new server.
+            if manager.generation.load(Ordering::SeqCst) == generation {
+                if running.as_ref().is_some_and(|r| r.generation == generation) {
+                    running.take();
+                }
+                ports.release(SERVICE);
+            }
+        });
+        Ok(StartedApiServer { status: self.status(), token: generated.then_some(token) })
+    }
+
+    /// Stops accepting requests; requests in flight get a few seconds to finish.
+    pub fn stop(&self) -> Result<(), String> {
+        let running = self.running.lock().unwrap().take().ok_or("The REST server is not running.")?;
+        running.handle.graceful_shutdown(Some(std::time::Duration::from_secs(5)));
+        tracing::info!("REST server stopped");
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::error::BackendError;
+use crate::events;
+use crate::interpreters;
+use crate::jobs::JobManager;
+use crate::paths;
+use crate::python;
+use crate::training::TrainingConfig;
+use crate::updater::UpdateChannel;
+
+const SETTINGS_FILE: &str = "app_settings.json";
+/// Job kinds that hold the GPU for minutes to hours.
+const TRAINING_KINDS: &[&str] = &["training", "hpo"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Follow the operating system.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// General app preferences, stored in `app_settings.json` in the settings
+/// directory. Feature settings with their own screens keep their own files.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Python interpreter scripts run with; the same setting as
+    /// `set_python_interpreter`, checked and stored the same way.
+    pub interpreter_path: Option<String>,
+    /// Training runs go to `<dir>/<experiment id>` when neither the config
+    /// nor the open project says where.
+    pub default_output_dir: Option<String>,
+    pub theme: Theme,
+    /// Background jobs of any kind running at once; 0 for no limit.
+    pub max_concurrent_jobs: u32,
+    /// Training runs and hyperparameter studies running at once; 0 for no limit.
+    pub max_concurrent_training: u32,
+    /// Releases `check_for_updates` looks at.
+    pub update_channel: UpdateChannel,
+}
+
+impl AppSettings {
+    fn validate(&self) -> Result<(), BackendError> {
+        if let Some(dir) = &self.default_output_dir {
+            if dir.trim().is_empty() {
+                return Err(BackendError::invalid_input("The default output folder cannot be blank; clear it instead."));
+            }
+        }
+        if self.max_concurrent_training > 0
+            && self.max_concurrent_jobs > 0
+            && self.max_concurrent_training > self.max_concurrent_jobs
+        {
+            return Err(BackendError::invalid_input("The training limit cannot be higher than the limit on all jobs."));
+        }
+        Ok(())
+    }
+}
+
+/// Published as `settings://changed` after `update_settings`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SettingsChanged {
+    pub settings: AppSettings,
+    /// Top-level keys whose value changed.
+    pub changed: Vec<String>,
+}
+
+/// Managed state: the app settings, loaded once and written on every change.
+pub struct AppSettingsStore {
+    path: PathBuf,
+    settings: Mutex<AppSettings>,
+}
+
+impl AppSettingsStore {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let path = paths::settings_file(app, SETTINGS_FILE)?;
+        let settings =
+            std::fs::read_to_string(&path).ok().and_then(|raw| serde_json::from_str(&raw).ok()).unwrap_or_default();
+        Ok(Self { path, settings: Mutex::new(settings) })
+    }
+
+    pub fn get(&self, app: &tauri::AppHandle) -> AppSettings {
+        AppSettings {
+            interpreter_path: python::load_settings(app).interpreter,
+            ..self.settings.lock().unwrap().clone()
+        }
+    }
+
+    /// Merges `patch`, an object of the fields to change, into the settings,
+    /// validates and saves them, and publishes `settings://changed`.
+    pub async fn update(&self, app: &tauri::AppHandle, patch: serde_json::Value) -> Result<AppSettings, String> {
+        let serde_json::Value::Object(patch) = patch else {
+            return Err("Settings updates are objects of the fields to change.".to_string());
+        };
+        let current = self.get(app);
+        let mut merged = serde_json::to_value(&current).map_err(|e| e.to_string())?;
+        let mut changed = Vec::new();
+        for (key, value) in patch {
+            let slot = merged.get_mut(&key).ok_or_else(|| format!("There is no setting named '{}'.", key))?;
+            if *slot != value {
+                changed.push(key);
+                *slot = value;
+            }
+        }
+        let settings: AppSettings = serde_json::from_value(merged).map_err(|e| format!("Invalid settings: {}", e))?;
+        if changed.is_empty() {
+            return Ok(settings);
+        }
+        settings.validate()?;
+
+        if settings.interpreter_path != current.interpreter_path {
+            interpreters::select(app, settings.interpreter_path.clone()).await?;
+        }
+        // The interpreter lives in python_settings.json only.
+        let stored = AppSettings { interpreter_path: None, ..settings.clone() };
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&stored).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| format!("Cannot write {}: {}", self.path.display(), e))?;
+        *self.settings.lock().unwrap() = stored;
+
+        tracing::info!(changed = ?changed, "App settings updated");
+        events::publish(app, "settings://changed", None, SettingsChanged { settings: settings.clone(), changed });
+        Ok(settings)
+    }
+
+    /// Refuses to start a job of `kind` when the concurrency limits are reached.
+    pub fn check_capacity(&self, manager: &JobManager, kind: &str) -> Result<(), String> {
+        let settings = self.settings.lock().unwrap().clone();
+        let running = manager.running_count(None);
+        if settings.max_concurrent_jobs > 0 && running >= settings.max_concurrent_jobs as usize {
+            return Err(format!(
+                "{} jobs are already running, the most allowed at once; wait for one to finish or raise the limit in Settings.",
+                running
+            ));
+        }
+        if TRAINING_KINDS.contains(&kind) && settings.max_concurrent_training > 0 {
+            let training: usize = TRAINING_KINDS.iter().map(|k| manager.running_count(Some(k))).sum();
+            if training >= settings.max_concurrent_training as usize {
+                return Err(format!(
+                    "{} training jobs are already running, the most allowed at once; wait for one to finish or raise the limit in Settings.",
+                    training
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Points a run without an output folder at the default output folder.
+    pub fn place_run(&self, config: &mut TrainingConfig) -> Result<(), String> {
+        let settings = self.settings.lock().unwrap();
+        let (Some(base), None, Some(experiment_id)) =
+            (&settings.default_output_dir, &config.save_path, &config.experiment_id)
+        else {
+            return Ok(());
+        };
+        let run_dir = PathBuf::from(base).join(experiment_id);
+        std::fs::create_dir_all(&run_dir).map_err(|e| format!("Cannot create {}: {}", run_dir.display(), e))?;
+        config.save_path = Some(run_dir.to_string_lossy().to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::BackendError;
+use crate::paths;
+use crate::plugins::is_python_identifier;
+use crate::training::{TrainingConfig, SUPPORTED_MODELS};
+
+/// Where a custom architecture comes from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArchitectureSource {
+    /// Any `timm.create_model` id, e.g. `convnext_small.fb_in22k`.
+    Timm {
+        model_id: String,
+        #[serde(default = "default_true")]
+        pretrained: bool,
+    },
+    /// A Python file exposing `factory(num_classes) -> nn.Module`.
+    Script { path: String, factory: String },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A user-registered architecture, trainable under `name` like the built-ins.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ArchitectureDescriptor {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub source: ArchitectureSource,
+    /// Square input resolution the network expects.
+    #[serde(default = "default_input_size")]
+    pub input_size: u32,
+}
+
+fn default_input_size() -> u32 {
+    224
+}
+
+/// Built-in or custom architecture as shown in the model picker.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ArchitectureInfo {
+    pub name: String,
+    pub builtin: bool,
+    pub input_size: u32,
+    pub descriptor: Option<ArchitectureDescriptor>,
+}
+
+impl ArchitectureDescriptor {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(BackendError::invalid_input(format!(
+                "Architecture name '{}' must be letters, digits, '-' or '_'.",
+                self.name
+            )));
+        }
+        if SUPPORTED_MODELS.contains(&self.name.as_str()) {
+            return Err(BackendError::invalid_input(format!("'{}' is a built-in model name.", self.name)));
+        }
+        if !(32..=1024).contains(&self.input_size) {
+            return Err(BackendError::invalid_input("Input size must be between 32 and 1024 pixels."));
+        }
+        match &self.source {
+            ArchitectureSource::Timm { model_id, .. } => {
+                let valid = !model_id.is_empty()
+                    && model_id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "_-./".contains(c));
+                if !valid {
+                    return Err(BackendError::invalid_input(format!("'{}' is not a valid timm model id.", model_id)));
+                }
+            }
+            ArchitectureSource::Script { path, factory } => {
+                let path = Path::new(path);
+                if !path.is_absolute() || !path.is_file() {
+                    return Err(BackendError::invalid_input(format!(
+                        "Script {} must be an existing absolute path.",
+                        path.display()
+                    )));
+                }
+                if path.extension().and_then(|e| e.to_str()) != Some("py") {
+                    return Err(BackendError::invalid_input("Architecture script must be a .py file."));
+                }
+                if !is_python_identifier(factory) {
+                    return Err(BackendError::invalid_input(format!(
+                        "Factory '{}' is not a valid Python function name.",
+                        factory
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn registry_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::data_file(app, "architectures.json")?)
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<Vec<ArchitectureDescriptor>, String> {
+    let path = registry_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid architecture registry: {}", e))
+}
+
+fn save(app: &tauri::AppHandle, archs: &[ArchitectureDescriptor]) -> Result<(), String> {
+    let path = registry_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(archs).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| e.to_string())
+}
+
+/// Adds or replaces a custom architecture.
+pub fn register(app: &tauri::AppHandle, descriptor: ArchitectureDescriptor) -> Result<(), String> {
+    descriptor.validate()?;
+    let mut archs = load(app)?;
+    archs.retain(|a| a.name != descriptor.name);
+    archs.push(descriptor);
+    archs.sort_by(|a, b| a.name.cmp(&b.name));
+    save(app, &archs)
+}
+
+pub fn remove(app: &tauri::AppHandle, name: &str) -> Result<(), String> {
+    let mut archs = load(app)?;
+    let before = archs.len();
+    archs.retain(|a| a.name != name);
+    if archs.len() == before {
+        return Err(format!("No custom architecture named '{}'.", name));
+    }
+    save(app, &archs)
+}
+
+pub fn list(app: &tauri::AppHandle) -> Result<Vec<ArchitectureInfo>, String> {
+    let mut all: Vec<ArchitectureInfo> = SUPPORTED_MODELS
+        .iter()
+        .map(|name| ArchitectureInfo {
+            name: name.to_string(),
+            builtin: true,
+            input_size: 224,
+            descriptor: None,
+        })
+        .collect();
+    all.extend(load(app)?.into_iter().map(|d| ArchitectureInfo {
+        name: d.name.clone(),
+        builtin: false,
+        input_size: d.input_size,
+        descriptor: Some(d),
+    }));
+    Ok(all)
+}
+
+/// Attaches the registered descriptor when `config.model` names a custom
+/// architecture, so the script knows how to build it.
+pub fn resolve(app: &tauri::AppHandle, config: &mut TrainingConfig) -> Result<(), String> {
+    if SUPPORTED_MODELS.contains(&config.model.as_str()) {
+        config.architecture = None;
+        return Ok(());
+    }
+    let descriptor = load(app)?
+        .into_iter()
+        .find(|a| a.name == config.model)
+        .ok_or_else(|| format!("Unknown model type: {}", config.model))?;
+    descriptor.validate()?;
+    config.architecture = Some(descriptor);
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use rusqlite::{params, Connection};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::database::Database;
+use crate::jobs::unix_now;
+use crate::paths;
+use crate::predictions::{csv_field, PredictionRecord};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS inferences (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    job_id TEXT NOT NULL,
+    input_path TEXT NOT NULL,
+    input_hash TEXT,
+    model_id TEXT,
+    model_version TEXT,
+    label TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    output TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS inferences_timestamp ON inferences (timestamp);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+pub struct AuditSettings {
+    /// Off by default: the log keeps input hashes and paths indefinitely
+    /// unless a retention period is set.
+    pub enabled: bool,
+    /// Entries older than this many days are deleted; `None` keeps everything.
+    pub retention_days: Option<u32>,
+}
+
+/// One logged prediction.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub job_id: String,
+    pub input_path: String,
+    /// SHA-256 of the input file at prediction time; `None` if it was unreadable.
+    pub input_hash: Option<String>,
+    pub model_id: Option<String>,
+    pub model_version: Option<String>,
+    pub label: String,
+    pub confidence: f64,
+    /// The full prediction record, including top-k scores.
+    pub output: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditExportFormat {
+    Csv,
+    Json,
+}
+
+/// Managed state: audit settings (`audit_settings.json`) and the SQLite log
+/// (`inference_audit.sqlite`), opened on first use.
+pub struct AuditLog {
+    settings: Mutex<AuditSettings>,
+    settings_path: PathBuf,
+    db: Database,
+}
+
+fn init(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+impl AuditLog {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let settings_path = paths::settings_file(app, "audit_settings.json")?;
+        let settings = std::fs::read_to_string(&settings_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            settings: Mutex::new(settings),
+            settings_path,
+            db: Database::new(paths::data_file(app, "inference_audit.sqlite")?, "inference audit log", init),
+        })
+    }
+
+    pub fn settings(&self) -> AuditSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: AuditSettings) -> Result<(), String> {
+        if settings.retention_days == Some(0) {
+            return Err("Retention must be at least one day.".to_string());
+        }
+        if let Some(dir) = self.settings_path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.settings_path, raw).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        self.prune().map(|_| ())
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        self.db.check()
+    }
+
+    pub fn close(&self) {
+        self.db.close()
+    }
+
+    /// Deletes entries past the retention period. Returns how many were removed.
+    pub fn prune(&self) -> Result<usize, String> {
+        let Some(days) = self.settings().retention_days else {
+            return Ok(0);
+        };
+        if !self.db.exists() {
+            return Ok(0);
+        }
+        let cutoff = unix_now().saturating_sub(u64::from(days) * 86_400) as i64;
+        self.db.with_conn(|conn| conn.execute("DELETE FROM inferences WHERE timestamp < ?1", params![cutoff]))
+    }
+
+    /// Logs one prediction when auditing is enabled. Returns whether it was logged.
+    pub fn record(&self, job_id: &str, record: &PredictionRecord) -> Result<bool, String> {
+        if !self.settings().enabled {
+            return Ok(false);
+        }
+        let output = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        self.db.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO inferences (timestamp, job_id, input_path, input_hash, model_id, model_version, label, confidence, output)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    unix_now() as i64,
+                    job_id,
+                    record.file,
+                    hash_file(Path::new(&record.file)),
+                    record.model_id,
+                    record.model_version,
+                    record.label,
+                    record.confidence,
+                    output,
+                ],
+            )
+        })
+        .map(|_| true)
+    }
+
+    /// Entries with `since <= timestamp < until`, oldest first.
+    pub fn query(&self, since: Option<u64>, until: Option<u64>) -> Result<Vec<AuditEntry>, String> {
+        if !self.db.exists() {
+            return Ok(Vec::new());
+        }
+        let since = since.unwrap_or(0) as i64;
+        let until = until.map(|u| u as i64).unwrap_or(i64::MAX);
+        self.db.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT timestamp, job_id, input_path, input_hash, model_id, model_version, label, confidence, output
+                 FROM inferences WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY id",
+            )?;
+            let rows = stmt.query_map(params![since, until], entry_from_row)?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })
+    }
+
+    /// The latest `limit` entries produced by `model_id`, oldest first.
+    pub fn recent(&self, model_id: &str, limit: usize) -> Result<Vec<AuditEntry>, String> {
+        if !self.db.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = self.db.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT timestamp, job_id, input_path, input_hash, model_id, model_version, label, confidence, output
+                 FROM inferences WHERE model_id = ?1 ORDER BY id DESC LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(params![model_id, limit as i64], entry_from_row)?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Writes entries in the time range to `dest`. Returns the number of rows.
+    pub fn export(
+        &self,
+        format: AuditExportFormat,
+        dest: &Path,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<usize, String> {
+        let entries = self.query(since, until)?;
+        let contents = match format {
+            AuditExportFormat::Json => serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?,
+            AuditExportFormat::Csv => {
+                let mut out = String::from(
+                    "timestamp,job_id,input_path,input_hash,model_id,model_version,label,confidence\n",
+                );
+                for e in &entries {
+                    out.push_str(&format!(
+                        "{},{},{},{},{},{},{},{:.6}\n",
+                        e.timestamp,
+                        csv_field(&e.job_id),
+                        csv_field(&e.input_path),
+                        e.input_hash.as_deref().unwrap_or(""),
+                        csv_field(e.model_id.as_deref().unwrap_or("")),
+                        csv_field(e.model_version.as_deref().unwrap_or("")),
+                        csv_field(&e.label),
+                        e.confidence
+                    ));
+                }
+                out
+            }
+        };
+        std::fs::write(dest, contents).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+        Ok(entries.len())
+    }
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<AuditEntry> {
+    let output: String = row.get(8)?;
+    Ok(AuditEntry {
+        timestamp: row.get::<_, i64>(0)? as u64,
+        job_id: row.get(1)?,
+        input_path: row.get(2)?,
+        input_hash: row.get(3)?,
+        model_id: row.get(4)?,
+        model_version: row.get(5)?,
+        label: row.get(6)?,
+        confidence: row.get(7)?,
+        output: serde_json::from_str(&output).unwrap_or(serde_json::Value::Null),
+    })
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+    Some(hex::encode(hasher.finalize()))
+}
@@ -0,0 +1,124 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::BackendError;
+
+/// Train-time augmentation applied by script.py. Only the training split is
+/// augmented; the policy is also written to the model's preprocessing.json so
+/// inference can check it is applying the same deterministic preprocessing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+pub struct AugmentationPolicy {
+    pub horizontal_flip: bool,
+    pub vertical_flip: bool,
+    /// Maximum absolute rotation in degrees; 0 disables rotation.
+    pub rotation_degrees: f64,
+    pub color_jitter: Option<ColorJitter>,
+    pub cutout: Option<Cutout>,
+    pub randaugment: Option<RandAugment>,
+}
+
+impl Default for AugmentationPolicy {
+    // Matches the transforms script.py used before policies were configurable.
+    fn default() -> Self {
+        Self {
+            horizontal_flip: true,
+            vertical_flip: false,
+            rotation_degrees: 0.0,
+            color_jitter: None,
+            cutout: None,
+            randaugment: None,
+        }
+    }
+}
+
+/// Strengths for torchvision's ColorJitter; 0 leaves a property untouched.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(default)]
+pub struct ColorJitter {
+    pub brightness: f64,
+    pub contrast: f64,
+    pub saturation: f64,
+    pub hue: f64,
+}
+
+/// Random erasing of a rectangle covering up to `max_area` of the image.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct Cutout {
+    pub probability: f64,
+    pub max_area: f64,
+}
+
+/// torchvision RandAugment: `num_ops` random ops per image at `magnitude` (0-30).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RandAugment {
+    pub num_ops: u32,
+    pub magnitude: u32,
+}
+
+const MAX_RANDAUGMENT_MAGNITUDE: u32 = 30;
+
+impl AugmentationPolicy {
+    /// Rejects values torchvision would refuse and warns about settings that
+    /// tend to hurt accuracy. Returns the warnings.
+    pub fn validate(&self) -> Result<Vec<String>, BackendError> {
+        let mut warnings = Vec::new();
+
+        if !(0.0..=180.0).contains(&self.rotation_degrees) {
+            return Err(BackendError::invalid_input("Rotation must be between 0 and 180 degrees."));
+        }
+        if self.rotation_degrees > 45.0 {
+            warnings.push(format!(
+                "Rotations up to {} degrees only suit orientation-free images (e.g. microscopy, aerial).",
+                self.rotation_degrees
+            ));
+        }
+
+        if let Some(jitter) = &self.color_jitter {
+            for (name, value) in [
+                ("brightness", jitter.brightness),
+                ("contrast", jitter.contrast),
+                ("saturation", jitter.saturation),
+            ] {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(BackendError::invalid_input(format!("Color jitter {} must be between 0 and 1.", name)));
+                }
+            }
+            if !(0.0..=0.5).contains(&jitter.hue) {
+                return Err(BackendError::invalid_input("Color jitter hue must be between 0 and 0.5."));
+            }
+        }
+
+        if let Some(cutout) = &self.cutout {
+            if !(0.0..=1.0).contains(&cutout.probability) {
+                return Err(BackendError::invalid_input("Cutout probability must be between 0 and 1."));
+            }
+            // RandomErasing samples areas from [0.02, max_area]
+            if !(cutout.max_area > 0.02 && cutout.max_area < 1.0) {
+                return Err(BackendError::invalid_input("Cutout max_area must be between 0.02 and 1."));
+            }
+            if cutout.max_area > 0.5 {
+                warnings.push("Cutout areas above 50% often erase the object being classified.".to_string());
+            }
+        }
+
+        if let Some(ra) = &self.randaugment {
+            if ra.num_ops == 0 {
+                return Err(BackendError::invalid_input("RandAugment needs at least one op."));
+            }
+            if ra.magnitude > MAX_RANDAUGMENT_MAGNITUDE {
+                return Err(BackendError::invalid_input(format!(
+                    "RandAugment magnitude must be between 0 and {}.",
+                    MAX_RANDAUGMENT_MAGNITUDE
+                )));
+            }
+            if self.color_jitter.is_some() || self.rotation_degrees > 0.0 {
+                warnings.push(
+                    "RandAugment already includes color and rotation ops; stacking explicit jitter/rotation strengthens them further.".to_string(),
+                );
+            }
+        }
+
+        Ok(warnings)
+    }
+}
@@ -0,0 +1,267 @@
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::events;
+use crate::jobs::unix_now;
+use crate::paths;
+
+/// How often the ticker checks whether a snapshot is due.
+const TICK: Duration = Duration::from_secs(600);
+/// Database file extensions backed up from the data directory.
+const STORE_EXTENSIONS: &[&str] = &["sqlite", "db"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    /// Snapshots kept per database; the oldest are deleted first.
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BackupInfo {
+    /// File name of the database in the data directory.
+    pub store: String,
+    pub path: String,
+    pub created_at: u64,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct BackupRun {
+    pub created: Vec<BackupInfo>,
+    /// Stores that failed the integrity check or could not be copied. Their
+    /// existing snapshots are kept untouched.
+    pub errors: Vec<String>,
+}
+
+/// Managed state: backup settings (`backup_settings.json`) and the data
+/// directory whose SQLite stores are snapshotted into `backups/<store>/`.
+pub struct BackupManager {
+    settings: Mutex<BackupSettings>,
+    settings_path: PathBuf,
+    data_dir: PathBuf,
+}
+
+/// Runs `PRAGMA integrity_check` and returns the problems it reports.
+pub fn integrity_problems(path: &Path) -> Result<Vec<String>, String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    let mut stmt = conn.prepare("PRAGMA integrity_check").map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Integrity check of {} failed: {}", path.display(), e))?;
+    let problems: Vec<String> = rows.filter_map(Result::ok).filter(|r| r != "ok").collect();
+    Ok(problems)
+}
+
+/// SQLite files directly in `data_dir`.
+pub fn store_files(data_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(data_dir) else {
+        return Vec::new();
+    };
+    let mut stores: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| STORE_EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    stores.sort();
+    stores
+}
+
+impl BackupManager {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let settings_path = paths::settings_file(app, "backup_settings.json")?;
+        let settings = std::fs::read_to_string(&settings_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            settings: Mutex::new(settings),
+            settings_path,
+            data_dir: paths::data_dir(app)?,
+        })
+    }
+
+    pub fn settings(&self) -> BackupSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: BackupSettings) -> Result<(), String> {
+        if settings.interval_hours == 0 || settings.keep == 0 {
+            return Err("Interval and number of backups kept must be at least 1.".to_string());
+        }
+        if let Some(dir) = self.settings_path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.settings_path, raw).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    fn backups_dir(&self) -> PathBuf {
+        self.data_dir.join("backups")
+    }
+
+    fn stores(&self) -> Vec<PathBuf> {
+        store_files(&self.data_dir)
+    }
+
+    /// Snapshots, newest first, of one store or all of them.
+    pub fn list(&self, store: Option<&str>) -> Vec<BackupInfo> {
+        let Ok(dirs) = std::fs::read_dir(self.backups_dir()) else {
+            return Vec::new();
+        };
+        let mut backups = Vec::new();
+        for dir in dirs.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+            let name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if store.is_some_and(|s| s != name) {
+                continue;
+            }
+            for file in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+                let path = file.path();
+                let Some(created_at) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse().ok()) else {
+                    continue;
+                };
+                backups.push(BackupInfo {
+                    store: name.clone(),
+                    path: path.to_string_lossy().to_string(),
+                    created_at,
+                    size_bytes: file.metadata().map(|m| m.len()).unwrap_or(0),
+                });
+            }
+        }
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        backups
+    }
+
+    fn due(&self) -> bool {
+        let settings = self.settings();
+        if !settings.enabled {
+            return false;
+        }
+        let newest = self.list(None).first().map(|b| b.created_at).unwrap_or(0);
+        unix_now().saturating_sub(newest) >= settings.interval_hours * 3600
+    }
+
+    /// Copies one store with SQLite's online backup API, so it is consistent
+    /// even while the app writes to it, then checks the copy.
+    fn snapshot(&self, store: &Path, created_at: u64) -> Result<BackupInfo, String> {
+        let name = store.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let problems = integrity_problems(store)?;
+        if !problems.is_empty() {
+            return Err(format!("{} failed the integrity check: {}", name, problems.join("; ")));
+        }
+        let dir = self.backups_dir().join(&name);
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let dest = dir.join(format!("{}.sqlite", created_at));
+        let conn = Connection::open_with_flags(store, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(|e| e.to_string())?;
+        conn.backup(DatabaseName::Main, &dest, None)
+            .map_err(|e| format!("Cannot back up {}: {}", name, e))?;
+        if let Ok(problems) = integrity_problems(&dest) {
+            if !problems.is_empty() {
+                let _ = std::fs::remove_file(&dest);
+                return Err(format!("The backup of {} is damaged: {}", name, problems.join("; ")));
+            }
+        }
+        Ok(BackupInfo {
+            store: name,
+            size_bytes: std::fs::metadata(&dest).map(|m| m.len()).unwrap_or(0),
+            path: dest.to_string_lossy().to_string(),
+            created_at,
+        })
+    }
+
+    fn rotate(&self, store: &str) {
+        let keep = self.settings().keep;
+        for old in self.list(Some(store)).into_iter().skip(keep) {
+            if let Err(e) = std::fs::remove_file(&old.path) {
+                tracing::warn!("Could not delete old backup {}: {}", old.path, e);
+            }
+        }
+    }
+
+    /// Snapshots every store and rotates old snapshots of the ones that succeeded.
+    pub fn run(&self) -> BackupRun {
+        let created_at = unix_now();
+        let mut run = BackupRun::default();
+        for store in self.stores() {
+            match self.snapshot(&store, created_at) {
+                Ok(info) => {
+                    self.rotate(&info.store);
+                    run.created.push(info);
+                }
+                Err(e) => run.errors.push(e),
+            }
+        }
+        run
+    }
+
+    /// Replaces a store's contents with a snapshot after checking it. The
+    /// current contents are snapshotted first, so a restore can be undone.
+    pub fn restore(&self, backup_path: &str) -> Result<BackupInfo, String> {
+        let backup = Path::new(backup_path);
+        let info = self
+            .list(None)
+            .into_iter()
+            .find(|b| Path::new(&b.path) == backup)
+            .ok_or_else(|| format!("{} is not one of this profile's backups.", backup_path))?;
+        let problems = integrity_problems(backup)?;
+        if !problems.is_empty() {
+            return Err(format!("The backup is damaged: {}", problems.join("; ")));
+        }
+
+        let target = self.data_dir.join(&info.store);
+        if target.exists() {
+            let dir = self.backups_dir().join(&info.store);
+            std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            // Plain copy: the current file may be too damaged to open.
+            let safety = dir.join(format!("{}.sqlite", unix_now()));
+            std::fs::copy(&target, &safety).map_err(|e| format!("Cannot save the current {}: {}", info.store, e))?;
+        }
+        // Restoring through SQLite keeps connections the app holds open valid.
+        let mut conn = Connection::open(&target).map_err(|e| format!("Cannot open {}: {}", target.display(), e))?;
+        conn.restore(DatabaseName::Main, backup, None::<fn(rusqlite::backup::Progress)>)
+            .map_err(|e| format!("Cannot restore {}: {}", info.store, e))?;
+        Ok(info)
+    }
+}
+
+/// Takes a snapshot whenever the newest one is older than the interval.
+pub fn spawn_ticker(app: tauri::AppHandle, manager: Arc<BackupManager>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if manager.due() {
+                let worker = manager.clone();
+                if let Ok(run) = tauri::async_runtime::spawn_blocking(move || worker.run()).await {
+                    for error in &run.errors {
+                        tracing::warn!("Backup: {}", error);
+                    }
+                    events::publish(&app, "backup://completed", None, run);
+                }
+            }
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
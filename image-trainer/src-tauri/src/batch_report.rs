@@ -0,0 +1,209 @@
+use minijinja::{context, Environment};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::html;
+use crate::i18n::{self, Localizer};
+use crate::predictions::{self, PredictionRecord};
+
+/// Predictions below this confidence go to the review queue by default.
+pub const DEFAULT_LOW_CONFIDENCE: f64 = 0.6;
+/// Rows shown in the HTML review queue; the CSV export has them all.
+const QUEUE_ROWS: usize = 200;
+const TEMPLATE: &str = include_str!("../templates/batch_summary.html");
+/// Messages the template looks up by id.
+const MESSAGES: &[&str] = &[
+    "batch-class-counts",
+    "batch-class",
+    "batch-images",
+    "batch-share",
+    "batch-mean-confidence",
+    "batch-review-queue",
+    "batch-file",
+    "batch-predicted",
+    "batch-confidence",
+    "batch-runner-up",
+    "batch-failures",
+];
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ClassCount {
+    pub label: String,
+    pub count: usize,
+    pub mean_confidence: f64,
+}
+
+#[derive(Serialize)]
+struct ClassRow {
+    label: String,
+    count: usize,
+    share: String,
+    mean_confidence: String,
+}
+
+#[derive(Serialize)]
+struct QueueRow {
+    file: String,
+    label: String,
+    confidence: String,
+    runner_up: String,
+}
+
+/// Summary of a batch prediction job.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BatchSummary {
+    pub job_id: String,
+    pub model_id: Option<String>,
+    pub scored: usize,
+    pub failed: usize,
+    pub duration_secs: Option<u64>,
+    pub classes: Vec<ClassCount>,
+    pub low_confidence_threshold: f64,
+    /// Predictions below the threshold, least confident first.
+    pub low_confidence: Vec<PredictionRecord>,
+    pub failures: Vec<String>,
+}
+
+pub fn summarize(app: &tauri::AppHandle, job_id: &str, threshold: f64) -> Result<BatchSummary, String> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(i18n::localizer(app).text("batch-threshold-range", &[]));
+    }
+    let records = predictions::load(app, job_id)?;
+    let stats = predictions::load_stats(app, job_id);
+
+    let mut per_class: BTreeMap<&str, (usize, f64)> = BTreeMap::new();
+    for r in &records {
+        let entry = per_class.entry(r.label.as_str()).or_insert((0, 0.0));
+        entry.0 += 1;
+        entry.1 += r.confidence;
+    }
+    let mut classes: Vec<ClassCount> = per_class
+        .into_iter()
+        .map(|(label, (count, total))| ClassCount {
+            label: label.to_string(),
+            count,
+            mean_confidence: total / count as f64,
+        })
+        .collect();
+    classes.sort_by(|a, b| b.count.cmp(&a.count));
+
+    let mut low_confidence: Vec<PredictionRecord> =
+        records.iter().filter(|r| r.confidence < threshold).cloned().collect();
+    low_confidence.sort_by(|a, b| a.confidence.total_cmp(&b.confidence));
+
+    let failures = stats.as_ref().map(|s| s.failures.clone()).unwrap_or_default();
+    Ok(BatchSummary {
+        job_id: job_id.to_string(),
+        model_id: stats
+            .as_ref()
+            .map(|s| s.model_id.clone())
+            .or_else(|| records.first().and_then(|r| r.model_id.clone())),
+        scored: records.len(),
+        failed: failures.len(),
+        duration_secs: stats.as_ref().map(|s| s.finished_at.saturating_sub(s.started_at)),
+        classes,
+        low_confidence_threshold: threshold,
+        low_confidence,
+        failures,
+    })
+}
+
+/// The summary as a standalone HTML page.
+fn render(summary: &BatchSummary, l10n: &Localizer) -> Result<String, String> {
+    let t: BTreeMap<&str, String> = MESSAGES.iter().map(|id| (*id, l10n.text(id, &[]))).collect();
+
+    let mut overview = vec![
+        (l10n.text("%s", &[]), summary.job_id.clone()),
+        (l10n.text("%s", &[]), summary.model_id.clone().unwrap_or_else(|| l10n.text("batch-unknown-model", &[]))),
+        (l10n.text("%s", &[]), summary.scored.to_string()),
+        (l10n.text("%s", &[]), summary.failed.to_string()),
+    ];
+    if let Some(secs) = summary.duration_secs {
+        let value = if secs > 0 {
+            let rate = format!("{:.1}", summary.scored as f64 / secs as f64);
+            l10n.text("batch-rate", &[("seconds", secs.to_string().into()), ("rate", rate.into())])
+        } else {
+            l10n.text("batch-duration-value", &[("seconds", secs.to_string().into())])
+        };
+        overview.push((l10n.text("batch-duration", &[]), value));
+    }
+    overview.push((
+        l10n.text("batch-low-confidence", &[]),
+        l10n.text(
+            "batch-low-confidence-value",
+            &[
+                ("count", summary.low_confidence.len().to_string().into()),
+                ("percent", format!("{:.0}", summary.low_confidence_threshold * 100.0).into()),
+            ],
+        ),
+    ));
+
+    let classes: Vec<ClassRow> = summary
+        .classes
+        .iter()
+        .map(|c| ClassRow {
+            label: c.label.clone(),
+            count: c.count,
+            share: format!("{:.1}%", c.count as f64 * 100.0 / summary.scored.max(1) as f64),
+            mean_confidence: format!("{:.1}%", c.mean_confidence * 100.0),
+        })
+        .collect();
+    let queue: Vec<QueueRow> = summary
+        .low_confidence
+        .iter()
+        .take(QUEUE_ROWS)
+        .map(|r| QueueRow {
+            file: r.file.clone(),
+            label: r.label.clone(),
+            confidence: format!("{:.1}%", r.confidence * 100.0),
+            runner_up: r
+                .top_k
+                .get(1)
+                .map(|s| format!("{} ({:.1}%)", s.label, s.probability * 100.0))
+                .unwrap_or_default(),
+        })
+        .collect();
+    let review_note = if summary.low_confidence.len() > QUEUE_ROWS {
+        l10n.text(
+            "batch-review-note-truncated",
+            &[
+                ("shown", QUEUE_ROWS.to_string().into()),
+                ("total", summary.low_confidence.len().to_string().into()),
+            ],
+        )
+    } else {
+        l10n.text("batch-review-note", &[])
+    };
+
+    let mut env = Environment::new();
+    env.add_template("batch_summary.html", TEMPLATE).map_err(|e| e.to_string())?;
+    let body = env
+        .get_template("batch_summary.html")
+        .and_then(|template| {
+            template.render(context! {
+                t => t,
+                overview => overview,
+                classes => classes,
+                review_note => review_note,
+                queue => queue,
+                failures => &summary.failures,
+            })
+        })
+        .map_err(|e| format!("Cannot render the summary: {}", e))?;
+    Ok(html::page(&l10n.language(), &l10n.text("batch-title", &[]), &body))
+}
+
+/// Writes the job's summary as a single HTML file and returns it.
+pub fn export(app: &tauri::AppHandle, job_id: &str, dest: &Path, threshold: f64) -> Result<BatchSummary, String> {
+    let summary = summarize(app, job_id, threshold)?;
+    let l10n = i18n::localizer(app);
+    std::fs::write(dest, render(&summary, &l10n)?).map_err(|e| {
+        l10n.text(
+            "error-write-file",
+            &[("path", dest.display().to_string().into()), ("error", e.to_string().into())],
+        )
+    })?;
+    Ok(summary)
+}
@@ -0,0 +1,410 @@
+use base64::Engine;
+use image::imageops::FilterType;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use crate::classes::{self, ClassSpec};
+use crate::events;
+use crate::jobs::{new_id, unix_now};
+use crate::paths;
+use crate::predictions::{self, PredictLine, PredictionRecord};
+use crate::preprocessing;
+use crate::python::{backend_script, resolve_launcher};
+
+const DEFAULT_FPS: f64 = 2.0;
+const MAX_FPS: f64 = 30.0;
+/// Frames are downscaled to this width before classifying and streaming;
+/// models take far smaller inputs anyway.
+const FRAME_WIDTH: u32 = 640;
+/// Frame files reused in turn. Only one frame is classified at a time, so two
+/// are enough to never overwrite the one Python is reading.
+const FRAME_SLOTS: u64 = 2;
+/// Lines of Python's stderr kept for the error when it exits unexpectedly.
+const STDERR_TAIL: usize = 10;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CameraInfo {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+}
+
+/// A running capture, as returned by `start_capture`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CaptureInfo {
+    pub capture_id: String,
+    pub camera_index: u32,
+    pub model_id: String,
+    /// Frames classified per second at most; slower models drop frames instead
+    /// of falling behind.
+    pub fps: f64,
+    pub started_at: u64,
+}
+
+/// Published as `capture://frame` for each classified frame.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureFrame {
+    pub frame: u64,
+    pub record: PredictionRecord,
+    /// The frame as a JPEG data URL.
+    pub image: String,
+    /// From grabbing the frame to its prediction.
+    pub latency_ms: u64,
+}
+
+/// Published as `capture://stopped` when a capture ends for any reason.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureStopped {
+    /// Why it ended, when not through `stop_capture`.
+    pub error: Option<String>,
+}
+
+struct ActiveCapture {
+    info: CaptureInfo,
+    shared: Arc<Shared>,
+}
+
+/// State shared by the camera thread, the prediction reader and `stop`.
+#[derive(Default)]
+struct Shared {
+    stop: AtomicBool,
+    /// predict.py has loaded the model.
+    ready: AtomicBool,
+    in_flight: Mutex<Option<InFlight>>,
+    child: Mutex<Option<CommandChild>>,
+    /// Why the camera thread gave up.
+    camera_error: Mutex<Option<String>>,
+}
+
+impl Shared {
+    fn kill(&self) {
+        if let Some(child) = self.child.lock().unwrap().take() {
+            let _ = child.kill();
+        }
+    }
+}
+
+/// Frame handed to predict.py and not yet answered.
+struct InFlight {
+    frame: u64,
+    jpeg: Vec<u8>,
+    grabbed: Instant,
+}
+
+/// Managed state holding the running camera captures.
+#[derive(Default)]
+pub struct CaptureManager {
+    captures: Mutex<HashMap<String, ActiveCapture>>,
+}
+
+pub fn list_cameras() -> Result<Vec<CameraInfo>, String> {
+    runtime::cameras()
+}
+
+impl CaptureManager {
+    /// Opens camera `camera_index` and classifies its frames with `model_id`
+    /// through one predict.py process that keeps the model loaded. Results are
+    /// published as `capture://frame` with the capture id as job id; they are
+    /// not stored, as a camera produces far more than is worth keeping.
+    pub async fn start(
+        &self,
+        app: &tauri::AppHandle,
+        model_id: &str,
+        camera_index: u32,
+        fps: Option<f64>,
+        classes: Option<ClassSpec>,
+    ) -> Result<CaptureInfo, String> {
+        let fps = fps.unwrap_or(DEFAULT_FPS);
+        if !(fps > 0.0 && fps <= MAX_FPS) {
+            return Err(format!("The frame rate must be above 0 and at most {}.", MAX_FPS));
+        }
+        if self.captures.lock().unwrap().values().any(|c| c.info.camera_index == camera_index) {
+            return Err(format!("Camera {} is already capturing.", camera_index));
+        }
+        let model_dir = Path::new(model_id);
+        preprocessing::load(model_dir)?;
+        let labels = match &classes {
+            Some(spec) => classes::resolve(model_dir, spec)?,
+            None => classes::model_classes(model_dir)?.into_iter().map(|e| e.name).collect(),
+        };
+        let labels_json = serde_json::to_string(&labels).map_err(|e| e.to_string())?;
+        let script = backend_script(app, "predict.py")?;
+        let launcher = resolve_launcher(app).await.map_err(|e| e.to_string())?;
+        let args = launcher.with_args([
+            script.as_str(),
+            "--model_dir",
+            model_id,
+            "--classes",
+            labels_json.as_str(),
+            "--stdin",
+            "--top_k",
+            "3",
+        ]);
+
+        let info = CaptureInfo {
+            capture_id: new_id("capture"),
+            camera_index,
+            model_id: model_id.to_string(),
+            fps,
+            started_at: unix_now(),
+        };
+        let (rx, child) = app
+            .shell()
+            .command(&launcher.program)
+            .args(args)
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", launcher.display(), e))?;
+
+        let shared = Arc::new(Shared::default());
+        *shared.child.lock().unwrap() = Some(child);
+        tauri::async_runtime::spawn(read_predictions(app.clone(), info.capture_id.clone(), rx, shared.clone()));
+        let grabber = Grabber {
+            app: app.clone(),
+            capture_id: info.capture_id.clone(),
+            interval: Duration::from_secs_f64(1.0 / fps),
+            shared: shared.clone(),
+        };
+        std::thread::spawn(move || grabber.run(camera_index));
+
+        self.captures.lock().unwrap().insert(info.capture_id.clone(), ActiveCapture { info: info.clone(), shared });
+        Ok(info)
+    }
+
+    pub fn stop(&self, capture_id: &str) -> Result<(), String> {
+        let capture = self
+            .captures
+            .lock()
+            .unwrap()
+            .remove(capture_id)
+            .ok_or_else(|| format!("No capture with id {}", capture_id))?;
+        capture.shared.stop.store(true, Ordering::SeqCst);
+        capture.shared.kill();
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<CaptureInfo> {
+        let mut captures: Vec<CaptureInfo> = self.captures.lock().unwrap().values().map(|c| c.info.clone()).collect();
+        captures.sort_by_key(|c| c.started_at);
+        captures
+    }
+
+    /// Drops a capture that ended on its own.
+    fn forget(&self, capture_id: &str) {
+        self.captures.lock().unwrap().remove(capture_id);
+    }
+}
+
+/// Reads frames on its own thread, as camera APIs block, and feeds them to
+/// predict.py at the requested rate.
+struct Grabber {
+    app: tauri::AppHandle,
+    capture_id: String,
+    interval: Duration,
+    shared: Arc<Shared>,
+}
+
+impl Grabber {
+    fn run(self, camera_index: u32) {
+        let mut frames = 0;
+        let mut last = None::<Instant>;
+        let result = runtime::stream(camera_index, |frame| {
+            if self.shared.stop.load(Ordering::SeqCst) {
+                return Ok(false);
+            }
+            // Drop frames while the model loads, is busy, or it is too early.
+            if !self.shared.ready.load(Ordering::SeqCst)
+                || self.shared.in_flight.lock().unwrap().is_some()
+                || last.is_some_and(|t| t.elapsed() < self.interval)
+            {
+                return Ok(true);
+            }
+            last = Some(Instant::now());
+            self.send(frames, frame)?;
+            frames += 1;
+            Ok(true)
+        });
+        if let Err(message) = result {
+            // Ending predict.py ends the reader, which reports the error.
+            *self.shared.camera_error.lock().unwrap() = Some(message);
+            self.shared.kill();
+        }
+    }
+
+    fn send(&self, n: u64, frame: image::RgbImage) -> Result<(), String> {
+        let frame = if frame.width() > FRAME_WIDTH {
+            let height = (frame.height() as u64 * FRAME_WIDTH as u64 / frame.width() as u64).max(1) as u32;
+            image::imageops::resize(&frame, FRAME_WIDTH, height, FilterType::Triangle)
+        } else {
+            frame
+        };
+        let mut jpeg = Vec::new();
+        image::DynamicImage::ImageRgb8(frame)
+            .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+            .map_err(|e| format!("Cannot encode frame: {}", e))?;
+        let path = frame_path(&self.app, &self.capture_id, n)?;
+        std::fs::write(&path, &jpeg).map_err(|e| format!("Cannot write frame: {}", e))?;
+
+        *self.shared.in_flight.lock().unwrap() = Some(InFlight { frame: n, jpeg, grabbed: Instant::now() });
+        let mut child = self.shared.child.lock().unwrap();
+        let Some(child) = child.as_mut() else {
+            return Ok(());
+        };
+        child
+            .write(format!("{}\n", path.to_string_lossy()).as_bytes())
+            .map_err(|e| format!("Cannot send frame to Python: {}", e))
+    }
+}
+
+fn frame_path(app: &tauri::AppHandle, capture_id: &str, n: u64) -> Result<PathBuf, String> {
+    Ok(paths::cache_file(app, &format!("{}_{}.jpg", capture_id, n % FRAME_SLOTS))?)
+}
+
+/// Publishes predict.py's answers until it exits, then cleans up the capture.
+async fn read_predictions(
+    app: tauri::AppHandle,
+    capture_id: String,
+    mut rx: tauri::async_runtime::Receiver<CommandEvent>,
+    shared: Arc<Shared>,
+) {
+    let mut error = None;
+    let mut stderr = Vec::new();
+    let mut code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(bytes) => {
+                let line = String::from_utf8_lossy(&bytes).trim_end().to_string();
+                if serde_json::from_str::<serde_json::Value>(&line).is_ok_and(|v| v["status"] == "ready") {
+                    shared.ready.store(true, Ordering::SeqCst);
+                    continue;
+                }
+                match predictions::parse_predict_line(&line) {
+                    Some(PredictLine::Record(record)) => {
+                        if let Some(sent) = shared.in_flight.lock().unwrap().take() {
+                            let frame = CaptureFrame {
+                                frame: sent.frame,
+                                record,
+                                image: format!(
+                                    "data:image/jpeg;base64,{}",
+                                    base64::engine::general_purpose::STANDARD.encode(&sent.jpeg)
+                                ),
+                                latency_ms: sent.grabbed.elapsed().as_millis() as u64,
+                            };
+                            events::publish(&app, "capture://frame", Some(&capture_id), frame);
+                        }
+                    }
+                    Some(PredictLine::Failure { .. }) => {
+                        // A bad frame is skipped; the next one is tried.
+                        shared.in_flight.lock().unwrap().take();
+                    }
+                    Some(PredictLine::Error(message)) => {
+                        error = Some(message);
+                    }
+                    None => {}
+                }
+            }
+            CommandEvent::Stderr(bytes) => {
+                if stderr.len() == STDERR_TAIL {
+                    stderr.remove(0);
+                }
+                stderr.push(String::from_utf8_lossy(&bytes).trim_end().to_string());
+            }
+            CommandEvent::Terminated(payload) => {
+                code = payload.code;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let camera_error = shared.camera_error.lock().unwrap().take();
+    let stopped = shared.stop.swap(true, Ordering::SeqCst) && camera_error.is_none();
+    error = camera_error.or(error);
+    if !stopped && error.is_none() {
+        error = Some(format!("Python exited with code {:?} while capturing: {}", code, stderr.join("\n")));
+    }
+    if let Some(captures) = app.try_state::<Arc<CaptureManager>>() {
+        captures.forget(&capture_id);
+    }
+    for n in 0..FRAME_SLOTS {
+        if let Ok(path) = frame_path(&app, &capture_id, n) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    events::publish(&app, "capture://stopped", Some(&capture_id), CaptureStopped { error });
+}
+
+#[cfg(feature = "webcam")]
+mod runtime {
+    use super::CameraInfo;
+    use nokhwa::pixel_format::RgbFormat;
+    use nokhwa::utils::{ApiBackend, CameraIndex, RequestedFormat, RequestedFormatType};
+    use nokhwa::Camera;
+
+    fn backend() -> Result<ApiBackend, String> {
+        nokhwa::native_api_backend().ok_or_else(|| "No camera API is available on this platform.".to_string())
+    }
+
+    pub fn cameras() -> Result<Vec<CameraInfo>, String> {
+        // Asks for camera access on macOS; a no-op elsewhere.
+        nokhwa::nokhwa_initialize(|_| {});
+        let found = nokhwa::query(backend()?).map_err(|e| format!("Cannot list cameras: {}", e))?;
+        Ok(found
+            .into_iter()
+            .filter_map(|camera| match camera.index() {
+                CameraIndex::Index(index) => Some(CameraInfo {
+                    index: *index,
+                    name: camera.human_name(),
+                    description: camera.description().to_string(),
+                }),
+                CameraIndex::String(_) => None,
+            })
+            .collect())
+    }
+
+    /// Hands frames to `on_frame` until it returns false or fails.
+    pub fn stream(index: u32, mut on_frame: impl FnMut(image::RgbImage) -> Result<bool, String>) -> Result<(), String> {
+        nokhwa::nokhwa_initialize(|_| {});
+        let format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+        let mut camera = Camera::new(CameraIndex::Index(index), format)
+            .map_err(|e| format!("Cannot open camera {}: {}", index, e))?;
+        camera.open_stream().map_err(|e| format!("Cannot start camera {}: {}", index, e))?;
+        let result = loop {
+            let frame = match camera.frame().and_then(|f| f.decode_image::<RgbFormat>()) {
+                Ok(frame) => frame,
+                Err(e) => break Err(format!("Camera {} stopped delivering frames: {}", index, e)),
+            };
+            let (width, height) = (frame.width(), frame.height());
+            let Some(frame) = image::RgbImage::from_raw(width, height, frame.into_raw()) else {
+                break Err("The camera returned a malformed frame.".to_string());
+            };
+            match on_frame(frame) {
+                Ok(true) => {}
+                Ok(false) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        let _ = camera.stop_stream();
+        result
+    }
+}
+
+#[cfg(not(feature = "webcam"))]
+mod runtime {
+    use super::CameraInfo;
+
+    pub fn cameras() -> Result<Vec<CameraInfo>, String> {
+        Err("This build was compiled without the webcam feature.".to_string())
+    }
+
+    pub fn stream(_index: u32, _on_frame: impl FnMut(image::RgbImage) -> Result<bool, String>) -> Result<(), String> {
+        Err("This build was compiled without the webcam feature.".to_string())
+    }
+}
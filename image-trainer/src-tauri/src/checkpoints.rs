@@ -0,0 +1,287 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::error::BackendError;
+use crate::models::{self, RegisteredModel};
+use crate::run_journal::CHECKPOINT_FILE;
+
+/// Subfolder of a run holding per-epoch checkpoints, written by script.py
+/// with `--epoch_checkpoints`.
+const EPOCH_DIR: &str = "checkpoints";
+const INDEX: &str = "index.json";
+const POLICY: &str = "policy.json";
+const WEIGHTS: &str = "best_model.pth";
+
+/// Which per-epoch checkpoints of a run are kept. With neither limit set,
+/// all are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CheckpointPolicy {
+    /// Save a checkpoint after every epoch, not only the latest.
+    pub every_epoch: bool,
+    /// Keep the N checkpoints with the best validation accuracy...
+    pub keep_best: Option<u32>,
+    /// ...and the N most recent ones.
+    pub keep_last: Option<u32>,
+}
+
+impl CheckpointPolicy {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if self.keep_best == Some(0) && self.keep_last == Some(0) {
+            return Err(BackendError::invalid_input("Keep at least one checkpoint."));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointKind {
+    /// `checkpoint.pth`, the one runs resume from.
+    Latest,
+    /// `best_model.pth`, the run's model.
+    Best,
+    Epoch,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CheckpointInfo {
+    /// File name, relative to the run directory for epoch checkpoints,
+    /// e.g. `checkpoints/epoch_0005.pth`.
+    pub name: String,
+    pub path: String,
+    pub kind: CheckpointKind,
+    pub epoch: Option<u32>,
+    pub val_accuracy: Option<f64>,
+    pub val_loss: Option<f64>,
+    pub size_bytes: u64,
+    pub modified_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CheckpointList {
+    pub run_id: String,
+    pub policy: CheckpointPolicy,
+    /// Latest and best first, then epochs in order.
+    pub checkpoints: Vec<CheckpointInfo>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PruneReport {
+    pub removed: Vec<String>,
+    pub freed_bytes: u64,
+}
+
+/// An entry of `checkpoints/index.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    file: String,
+    epoch: u32,
+    val_accuracy: Option<f64>,
+    val_loss: Option<f64>,
+    #[serde(default)]
+    saved_at: Option<u64>,
+}
+
+fn run_dir(run_id: &str) -> Result<PathBuf, BackendError> {
+    let dir = PathBuf::from(run_id);
+    if !dir.is_dir() {
+        return Err(BackendError::invalid_input(format!("Run directory {} does not exist.", run_id)));
+    }
+    Ok(dir)
+}
+
+fn read_index(dir: &Path) -> Vec<IndexEntry> {
+    std::fs::read_to_string(dir.join(EPOCH_DIR).join(INDEX))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(dir: &Path, entries: &[IndexEntry]) -> Result<(), String> {
+    let raw = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(EPOCH_DIR).join(INDEX), raw).map_err(|e| e.to_string())
+}
+
+pub fn load_policy(dir: &Path) -> CheckpointPolicy {
+    std::fs::read_to_string(dir.join(EPOCH_DIR).join(POLICY))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Stores the policy pruning applies to the run, during training and after.
+pub fn save_policy(dir: &Path, policy: &CheckpointPolicy) -> Result<(), String> {
+    policy.validate()?;
+    let folder = dir.join(EPOCH_DIR);
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let raw = serde_json::to_string_pretty(policy).map_err(|e| e.to_string())?;
+    std::fs::write(folder.join(POLICY), raw).map_err(|e| e.to_string())
+}
+
+fn info(path: PathBuf, name: String, kind: CheckpointKind, entry: Option<&IndexEntry>) -> Option<CheckpointInfo> {
+    let meta = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+    Some(CheckpointInfo {
+        name,
+        path: path.to_string_lossy().to_string(),
+        kind,
+        epoch: entry.map(|e| e.epoch),
+        val_accuracy: entry.and_then(|e| e.val_accuracy),
+        val_loss: entry.and_then(|e| e.val_loss),
+        size_bytes: meta.len(),
+        modified_at: meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs()),
+    })
+}
+
+pub fn list(run_id: &str) -> Result<CheckpointList, BackendError> {
+    let dir = run_dir(run_id)?;
+    let mut checkpoints: Vec<CheckpointInfo> =
+        [(CHECKPOINT_FILE, CheckpointKind::Latest), (WEIGHTS, CheckpointKind::Best)]
+            .into_iter()
+            .filter_map(|(file, kind)| info(dir.join(file), file.to_string(), kind, None))
+            .collect();
+    let mut entries = read_index(&dir);
+    entries.sort_by_key(|e| e.epoch);
+    checkpoints.extend(entries.iter().filter_map(|entry| {
+        let name = format!("{}/{}", EPOCH_DIR, entry.file);
+        info(dir.join(EPOCH_DIR).join(&entry.file), name, CheckpointKind::Epoch, Some(entry))
+    }));
+    let total_bytes = checkpoints.iter().map(|c| c.size_bytes).sum();
+    Ok(CheckpointList { run_id: run_id.to_string(), policy: load_policy(&dir), checkpoints, total_bytes })
+}
+
+/// Resolves `name` from `list` to its file, refusing anything outside the run.
+fn resolve(dir: &Path, name: &str) -> Result<(PathBuf, CheckpointKind), BackendError> {
+    if name == CHECKPOINT_FILE {
+        return Ok((dir.join(name), CheckpointKind::Latest));
+    }
+    if name == WEIGHTS {
+        return Ok((dir.join(name), CheckpointKind::Best));
+    }
+    let file = name.strip_prefix(&format!("{}/", EPOCH_DIR)).unwrap_or(name);
+    let valid = file.ends_with(".pth") && !file.contains(['/', '\\']) && !file.starts_with('.');
+    if !valid || !read_index(dir).iter().any(|e| e.file == file) {
+        return Err(BackendError::invalid_input(format!("{} is not a checkpoint of this run.", name)));
+    }
+    Ok((dir.join(EPOCH_DIR).join(file), CheckpointKind::Epoch))
+}
+
+/// Deletes a checkpoint. The run's model, `best_model.pth`, is not deleted
+/// this way.
+pub fn delete(run_id: &str, name: &str) -> Result<CheckpointList, BackendError> {
+    let dir = run_dir(run_id)?;
+    let (path, kind) = resolve(&dir, name)?;
+    if kind == CheckpointKind::Best {
+        return Err(BackendError::invalid_input("best_model.pth is the run's model; delete the model instead."));
+    }
+    std::fs::remove_file(&path).map_err(|e| BackendError::io(format!("Cannot delete {}: {}", path.display(), e)))?;
+    if kind == CheckpointKind::Epoch {
+        let file = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+        let entries: Vec<IndexEntry> = read_index(&dir).into_iter().filter(|e| e.file != file).collect();
+        write_index(&dir, &entries)?;
+    }
+    list(run_id)
+}
+
+/// Deletes the epoch checkpoints `policy` does not keep: the union of the best
+/// `keep_best` by validation accuracy and the last `keep_last`.
+pub fn prune(run_id: &str, policy: &CheckpointPolicy) -> Result<PruneReport, BackendError> {
+    policy.validate()?;
+    let dir = run_dir(run_id)?;
+    let mut entries = read_index(&dir);
+    if entries.is_empty() || (policy.keep_best.is_none() && policy.keep_last.is_none()) {
+        return Ok(PruneReport { removed: Vec::new(), freed_bytes: 0 });
+    }
+    let mut keep = HashSet::new();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.epoch));
+    keep.extend(entries.iter().take(policy.keep_last.unwrap_or(0) as usize).map(|e| e.file.clone()));
+    entries.sort_by(|a, b| {
+        let accuracy = |e: &IndexEntry| e.val_accuracy.unwrap_or(f64::NEG_INFINITY);
+        accuracy(b).total_cmp(&accuracy(a)).then(b.epoch.cmp(&a.epoch))
+    });
+    keep.extend(entries.iter().take(policy.keep_best.unwrap_or(0) as usize).map(|e| e.file.clone()));
+
+    let mut removed = Vec::new();
+    let mut freed_bytes = 0;
+    let mut kept = Vec::new();
+    for entry in entries {
+        if keep.contains(&entry.file) {
+            kept.push(entry);
+            continue;
+        }
+        let path = dir.join(EPOCH_DIR).join(&entry.file);
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                freed_bytes += size;
+                removed.push(format!("{}/{}", EPOCH_DIR, entry.file));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => removed.push(format!("{}/{}", EPOCH_DIR, entry.file)),
+            Err(e) => {
+                tracing::warn!("Could not prune checkpoint {}: {}", path.display(), e);
+                kept.push(entry);
+            }
+        }
+    }
+    kept.sort_by_key(|e| e.epoch);
+    write_index(&dir, &kept)?;
+    Ok(PruneReport { removed, freed_bytes })
+}
+
+/// Applies the run's stored policy after script.py reports a new epoch
+/// checkpoint on `line`.
+pub fn on_output_line(dir: &Path, line: &str) {
+    if !line.contains("\"epoch_checkpoint\"") {
+        return;
+    }
+    let policy = load_policy(dir);
+    if let Err(e) = prune(&dir.to_string_lossy(), &policy) {
+        tracing::warn!("Could not prune the checkpoints of {}: {}", dir.display(), e);
+    }
+}
+
+/// Makes a checkpoint a model of its own: a copy of the run's metadata next to
+/// the run, with the checkpoint's weights as `best_model.pth`, registered in
+/// the model registry.
+pub fn promote(app: &tauri::AppHandle, run_id: &str, name: &str) -> Result<RegisteredModel, BackendError> {
+    let dir = run_dir(run_id)?;
+    let (path, kind) = resolve(&dir, name)?;
+    if kind == CheckpointKind::Best {
+        return Err(BackendError::invalid_input("best_model.pth is already the run's model."));
+    }
+    if !path.is_file() {
+        return Err(BackendError::invalid_input(format!("{} no longer exists.", name)));
+    }
+    let suffix = match kind {
+        CheckpointKind::Epoch => path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        _ => "latest".to_string(),
+    };
+    let run_name = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "run".to_string());
+    let target = dir.with_file_name(format!("{}_{}", run_name, suffix));
+    if target.exists() {
+        return Err(BackendError::invalid_input(format!("{} already exists.", target.display())));
+    }
+    std::fs::create_dir_all(&target).map_err(BackendError::io)?;
+    let copy = || -> Result<(), String> {
+        // Manifests, class names and preprocessing; not other weights or archives.
+        for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+            let source = entry.path();
+            let skip = source.extension().is_some_and(|ext| ext == "pth" || ext == "zip");
+            if source.is_file() && !skip {
+                std::fs::copy(&source, target.join(entry.file_name())).map_err(|e| e.to_string())?;
+            }
+        }
+        std::fs::copy(&path, target.join(WEIGHTS)).map_err(|e| e.to_string())?;
+        Ok(())
+    };
+    if let Err(e) = copy() {
+        let _ = std::fs::remove_dir_all(&target);
+        return Err(BackendError::io(format!("Cannot copy {} to {}: {}", name, target.display(), e)));
+    }
+    let model_name = format!("{} ({})", run_name, suffix.replace('_', " "));
+    Ok(models::register(app, &target.to_string_lossy(), Some(model_name))?)
+}
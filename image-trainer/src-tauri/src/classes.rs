@@ -0,0 +1,193 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use crate::run_manifest;
+
+const CLASS_MAP: &str = "class_map.json";
+
+/// A model output index and the label shown for it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ClassEntry {
+    pub id: u32,
+    pub name: String,
+}
+
+/// Class labels supplied for a model: either names in output order, or
+/// explicit `{id, name}` entries in any order.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ClassSpec {
+    Names(Vec<String>),
+    Entries(Vec<ClassEntry>),
+}
+
+impl ClassSpec {
+    /// Parses a classes JSON document such as a user's labels file.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        serde_json::from_str(raw).map_err(|_| {
+            "Classes must be a JSON list of names or of {\"id\", \"name\"} objects.".to_string()
+        })
+    }
+
+    /// Entries sorted by id, checked for duplicates and gaps.
+    pub fn entries(&self) -> Result<Vec<ClassEntry>, String> {
+        let mut entries = match self {
+            ClassSpec::Names(names) => names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| ClassEntry { id: i as u32, name: name.clone() })
+                .collect(),
+            ClassSpec::Entries(entries) => entries.clone(),
+        };
+        entries.sort_by_key(|e| e.id);
+
+        let mut names = HashSet::new();
+        for (expected, entry) in entries.iter().enumerate() {
+            if entry.name.trim().is_empty() {
+                return Err(format!("Class {} has an empty name.", entry.id));
+            }
+            if entry.id as usize != expected {
+                return Err(if (entry.id as usize) < expected {
+                    format!("Class id {} appears more than once.", entry.id)
+                } else {
+                    format!("Class ids must be contiguous from 0; id {} is missing.", expected)
+                });
+            }
+            if !names.insert(entry.name.as_str()) {
+                return Err(format!("Class name '{}' appears more than once.", entry.name));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// The id-to-name mapping a model was trained with, stored as `class_map.json`
+/// in its directory. Created from the run manifest on first use and never
+/// reordered afterwards.
+pub fn model_classes(model_dir: &Path) -> Result<Vec<ClassEntry>, String> {
+    let map_path = model_dir.join(CLASS_MAP);
+    if let Ok(raw) = std::fs::read_to_string(&map_path) {
+        return serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", map_path.display(), e));
+    }
+    let manifest = run_manifest::load(model_dir)?;
+    let names: Vec<String> = serde_json::from_value(manifest["classes"].clone())
+        .map_err(|_| "The run manifest lists no classes.".to_string())?;
+    let entries = ClassSpec::Names(names).entries()?;
+    let raw = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+    std::fs::write(&map_path, raw).map_err(|e| format!("Cannot write {}: {}", map_path.display(), e))?;
+    Ok(entries)
+}
+
+/// Checks user-supplied labels against a model: one per output, and no known
+/// label moved to a different id. Renaming an id is allowed. Returns the
+/// labels in output order.
+pub fn resolve(model_dir: &Path, spec: &ClassSpec) -> Result<Vec<String>, String> {
+    let stored = model_classes(model_dir)?;
+    let entries = spec.entries()?;
+    if entries.len() != stored.len() {
+        return Err(format!(
+            "The model has {} outputs but {} classes were given.",
+            stored.len(),
+            entries.len()
+        ));
+    }
+    let stored_ids: BTreeMap<&str, u32> = stored.iter().map(|e| (e.name.as_str(), e.id)).collect();
+    for entry in &entries {
+        if let Some(id) = stored_ids.get(entry.name.as_str()).filter(|id| **id != entry.id) {
+            return Err(format!(
+                "Class '{}' is output {} of this model but was given as {}; reordering the list would shuffle labels.",
+                entry.name, id, entry.id
+            ));
+        }
+    }
+    Ok(entries.into_iter().map(|e| e.name).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A model directory whose stable class map holds `names` in output order.
+    struct Model(PathBuf);
+
+    impl Model {
+        fn new(test: &str, names: &[&str]) -> Self {
+            let dir = std::env::temp_dir().join(format!("epoq-classes-{}-{}", test, std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let entries = spec(names).entries().unwrap();
+            std::fs::write(dir.join(CLASS_MAP), serde_json::to_string(&entries).unwrap()).unwrap();
+            Model(dir)
+        }
+    }
+
+    impl Drop for Model {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn spec(names: &[&str]) -> ClassSpec {
+        ClassSpec::Names(names.iter().map(|n| n.to_string()).collect())
+    }
+
+    fn entry(id: u32, name: &str) -> ClassEntry {
+        ClassEntry { id, name: name.to_string() }
+    }
+
+    #[test]
+    fn entries_are_sorted_by_id() {
+        let spec = ClassSpec::Entries(vec![entry(2, "bird"), entry(0, "cat"), entry(1, "dog")]);
+        assert_eq!(spec.entries().unwrap(), vec![entry(0, "cat"), entry(1, "dog"), entry(2, "bird")]);
+    }
+
+    #[test]
+    fn entries_reject_duplicates_gaps_and_blank_names() {
+        assert!(spec(&["cat", "dog", "cat"]).entries().is_err());
+        assert!(ClassSpec::Entries(vec![entry(0, "cat"), entry(0, "dog")]).entries().is_err());
+        assert!(ClassSpec::Entries(vec![entry(0, "cat"), entry(2, "dog")]).entries().is_err());
+        assert!(ClassSpec::Entries(vec![entry(1, "cat")]).entries().is_err());
+        assert!(spec(&["cat", " "]).entries().is_err());
+    }
+
+    #[test]
+    fn parse_accepts_names_and_entries() {
+        assert!(matches!(ClassSpec::parse(r#"["cat", "dog"]"#).unwrap(), ClassSpec::Names(_)));
+        assert!(matches!(ClassSpec::parse(r#"[{"id": 0, "name": "cat"}]"#).unwrap(), ClassSpec::Entries(_)));
+        assert!(ClassSpec::parse(r#"{"cat": 0}"#).is_err());
+    }
+
+    #[test]
+    fn resolve_accepts_the_stored_order_and_renames() {
+        let model = Model::new("renames", &["cat", "dog", "bird"]);
+        assert_eq!(resolve(&model.0, &spec(&["cat", "dog", "bird"])).unwrap(), ["cat", "dog", "bird"]);
+        assert_eq!(resolve(&model.0, &spec(&["cat", "dog", "sparrow"])).unwrap(), ["cat", "dog", "sparrow"]);
+        let shuffled = ClassSpec::Entries(vec![entry(2, "bird"), entry(0, "cat"), entry(1, "dog")]);
+        assert_eq!(resolve(&model.0, &shuffled).unwrap(), ["cat", "dog", "bird"]);
+    }
+
+    #[test]
+    fn resolve_rejects_reordered_classes() {
+        let model = Model::new("reordered", &["cat", "dog", "bird"]);
+        assert!(resolve(&model.0, &spec(&["dog", "cat", "bird"])).is_err());
+        let moved = ClassSpec::Entries(vec![entry(0, "cat"), entry(1, "bird"), entry(2, "dog")]);
+        assert!(resolve(&model.0, &moved).is_err());
+    }
+
+    #[test]
+    fn resolve_rejects_missing_extra_and_duplicate_classes() {
+        let model = Model::new("counts", &["cat", "dog", "bird"]);
+        assert!(resolve(&model.0, &spec(&["cat", "dog"])).is_err());
+        assert!(resolve(&model.0, &spec(&["cat", "dog", "bird", "fish"])).is_err());
+        assert!(resolve(&model.0, &spec(&["cat", "dog", "dog"])).is_err());
+    }
+
+    #[test]
+    fn stored_class_map_wins_over_the_manifest() {
+        let model = Model::new("stored", &["cat", "dog"]);
+        std::fs::write(model.0.join(run_manifest::FILE_NAME), r#"{"classes": ["dog", "cat"]}"#).unwrap();
+        assert_eq!(model_classes(&model.0).unwrap(), vec![entry(0, "cat"), entry(1, "dog")]);
+    }
+}
@@ -0,0 +1,199 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{Listener, Manager};
+
+use crate::jobs::{self, JobManager, JobRecord, JobStatus};
+use crate::models::{self, RegisteredModel};
+use crate::predictions::{self, PredictionRecord};
+use crate::training::TrainingConfig;
+
+const USAGE: &str = "\
+Usage:
+  epoq train --config <run.yaml|run.json|run.toml>
+  epoq predict --model <model dir> --image <file> [--image <file>...] [--device <cpu|index>]
+  epoq models
+  epoq --help | --version
+
+Runs the same backend as the app without opening a window. Results are
+printed to stdout as JSON; training metrics stream to stderr as JSON lines.
+Exit status: 0 on success, 1 when the job fails, 2 for usage errors.";
+/// How often a training job is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub enum Command {
+    Train { config: PathBuf },
+    Predict { model: String, images: Vec<String>, device: Option<String> },
+    Models,
+    Help,
+    Version,
+}
+
+/// Reads the command line. `None` starts the app as usual; arguments that do
+/// not start with a subcommand, such as those the OS adds when launching an
+/// app bundle, are left to it.
+pub fn parse(args: &[String]) -> Option<Result<Command, String>> {
+    let (first, rest) = args.split_first()?;
+    let command = match first.as_str() {
+        "train" => parse_train(rest),
+        "predict" => parse_predict(rest),
+        "models" if rest.is_empty() => Ok(Command::Models),
+        "models" => Err(format!("Unexpected argument '{}'.", rest[0])),
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "--version" | "-V" => Ok(Command::Version),
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Pairs `--flag value` arguments; every flag takes a value.
+fn flags(args: &[String]) -> Result<Vec<(&str, &str)>, String> {
+    let mut pairs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        if !flag.starts_with("--") {
+            return Err(format!("Unexpected argument '{}'.", flag));
+        }
+        let value = iter.next().ok_or_else(|| format!("{} needs a value.", flag))?;
+        pairs.push((flag.as_str(), value.as_str()));
+    }
+    Ok(pairs)
+}
+
+fn parse_train(args: &[String]) -> Result<Command, String> {
+    let mut config = None;
+    for (flag, value) in flags(args)? {
+        match flag {
+            "--config" => config = Some(PathBuf::from(value)),
+            _ => return Err(format!("Unknown option {} for train.", flag)),
+        }
+    }
+    Ok(Command::Train { config: config.ok_or("train needs --config.")? })
+}
+
+fn parse_predict(args: &[String]) -> Result<Command, String> {
+    let (mut model, mut images, mut device) = (None, Vec::new(), None);
+    for (flag, value) in flags(args)? {
+        match flag {
+            "--model" => model = Some(value.to_string()),
+            "--image" => images.push(value.to_string()),
+            "--device" => device = Some(value.to_string()),
+            _ => return Err(format!("Unknown option {} for predict.", flag)),
+        }
+    }
+    if images.is_empty() {
+        return Err("predict needs at least one --image.".to_string());
+    }
+    Ok(Command::Predict { model: model.ok_or("predict needs --model.")?, images, device })
+}
+
+pub fn usage() -> &'static str {
+    USAGE
+}
+
+/// Reads a training config from YAML, TOML or JSON, by extension.
+fn load_config(path: &Path) -> Result<TrainingConfig, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "yaml" | "yml" => serde_yaml::from_str(&raw).map_err(|e| e.to_string()),
+        "toml" => toml::from_str(&raw).map_err(|e| e.to_string()),
+        _ => serde_json::from_str(&raw).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("Invalid training config {}: {}", path.display(), e))
+}
+
+#[derive(Serialize)]
+struct TrainResult {
+    job: JobRecord,
+    /// Registry entry of the finished run.
+    model: Option<RegisteredModel>,
+}
+
+#[derive(Serialize)]
+struct PredictResult {
+    prediction_id: String,
+    records: Vec<PredictionRecord>,
+    failures: Vec<String>,
+}
+
+/// Starts training and waits for it, streaming each epoch's metrics to stderr.
+async fn train(app: &tauri::AppHandle, config: &Path) -> Result<(serde_json::Value, bool), String> {
+    let config = load_config(config)?;
+    let manager = app.state::<Arc<JobManager>>().inner().clone();
+    let listener = app.listen_any("training://metrics", |event| eprintln!("{}", event.payload()));
+    let job_id = jobs::start_training(app, &manager, config).await;
+    let job_id = match job_id {
+        Ok(job_id) => job_id,
+        Err(e) => {
+            app.unlisten(listener);
+            return Err(e.to_string());
+        }
+    };
+    let job = loop {
+        let job = manager.get(&job_id)?;
+        if job.status.is_finished() {
+            break job;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    };
+    app.unlisten(listener);
+    let success = job.status == JobStatus::Succeeded;
+    let model = match (&job.output_dir, success) {
+        (Some(dir), true) => models::register(app, dir, None).ok(),
+        _ => None,
+    };
+    let result = serde_json::to_value(TrainResult { job, model }).map_err(|e| e.to_string())?;
+    Ok((result, success))
+}
+
+async fn execute(app: &tauri::AppHandle, command: Command) -> Result<(serde_json::Value, bool), String> {
+    match command {
+        Command::Train { config } => train(app, &config).await,
+        Command::Predict { model, images, device } => {
+            let (prediction_id, records, failures) =
+                predictions::predict(app, &model, &images, None, device.as_deref()).await?;
+            let success = failures.is_empty();
+            let result = PredictResult { prediction_id, records, failures };
+            Ok((serde_json::to_value(result).map_err(|e| e.to_string())?, success))
+        }
+        Command::Models => Ok((serde_json::to_value(models::list(app)?).map_err(|e| e.to_string())?, true)),
+        Command::Help | Command::Version => unreachable!("answered before the app starts"),
+    }
+}
+
+/// Runs `command` once the app is set up, prints its result and exits.
+pub fn spawn(app: tauri::AppHandle, command: Command) {
+    tauri::async_runtime::spawn(async move {
+        let code = match execute(&app, command).await {
+            Ok((result, success)) => {
+                println!("{}", result);
+                if success {
+                    0
+                } else {
+                    1
+                }
+            }
+            Err(e) => {
+                println!("{}", serde_json::json!({ "error": e }));
+                1
+            }
+        };
+        app.exit(code);
+    });
+}
+
+/// Windows release builds have no console of their own; output goes to the
+/// terminal the CLI was started from.
+#[cfg(windows)]
+pub fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // Fails harmlessly when started without a console, e.g. from Explorer.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_console() {}
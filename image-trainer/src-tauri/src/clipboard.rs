@@ -0,0 +1,114 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::{metrics, predictions, run_manifest};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadKind {
+    /// Stored predictions of a job; `id` is the job id.
+    Predictions,
+    /// Test metrics of a run; `id` is the run directory.
+    Metrics,
+    /// Hyperparameters a run was trained with; `id` is the run directory.
+    RunConfig,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardFormat {
+    /// Tab-separated; pastes into spreadsheet cells.
+    #[default]
+    Tsv,
+    Markdown,
+    Json,
+}
+
+struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    json: serde_json::Value,
+}
+
+impl Table {
+    fn render(&self, format: ClipboardFormat) -> String {
+        match format {
+            ClipboardFormat::Json => serde_json::to_string_pretty(&self.json).unwrap_or_default(),
+            ClipboardFormat::Tsv => {
+                // Tabs and newlines inside a cell would shift the pasted grid.
+                let clean = |cell: &String| cell.replace(['\t', '\n', '\r'], " ");
+                std::iter::once(&self.headers)
+                    .chain(&self.rows)
+                    .map(|row| row.iter().map(clean).collect::<Vec<_>>().join("\t"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            ClipboardFormat::Markdown => {
+                let clean = |cell: &String| cell.replace('|', "\\|").replace(['\n', '\r'], " ");
+                let line = |row: &Vec<String>| format!("| {} |", row.iter().map(clean).collect::<Vec<_>>().join(" | "));
+                let mut out = vec![line(&self.headers)];
+                out.push(format!("|{}", " --- |".repeat(self.headers.len())));
+                out.extend(self.rows.iter().map(line));
+                out.join("\n")
+            }
+        }
+    }
+}
+
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn build_table(app: &tauri::AppHandle, kind: PayloadKind, id: &str) -> Result<Table, String> {
+    match kind {
+        PayloadKind::Predictions => {
+            let records = predictions::load(app, id)?;
+            Ok(Table {
+                headers: vec!["File".into(), "Label".into(), "Confidence".into()],
+                rows: records
+                    .iter()
+                    .map(|r| vec![r.file.clone(), r.label.clone(), format!("{:.4}", r.confidence)])
+                    .collect(),
+                json: serde_json::to_value(&records).map_err(|e| e.to_string())?,
+            })
+        }
+        PayloadKind::Metrics => {
+            let comparison = metrics::compare(&[id.to_string()])?;
+            let run = comparison.runs.into_iter().next().ok_or("Run has no metrics.")?;
+            Ok(Table {
+                headers: vec!["Metric".into(), "Value".into()],
+                rows: run.values.iter().map(|(k, v)| vec![k.clone(), format!("{:.4}", v)]).collect(),
+                json: serde_json::to_value(&run.values).map_err(|e| e.to_string())?,
+            })
+        }
+        PayloadKind::RunConfig => {
+            let config = run_manifest::load(Path::new(id))?["config"].clone();
+            let entries = config.as_object().ok_or("Run manifest has no config.")?;
+            Ok(Table {
+                headers: vec!["Parameter".into(), "Value".into()],
+                rows: entries
+                    .iter()
+                    .filter(|(_, v)| !v.is_null())
+                    .map(|(k, v)| vec![k.clone(), value_text(v)])
+                    .collect(),
+                json: config.clone(),
+            })
+        }
+    }
+}
+
+/// Formats the payload and places it on the system clipboard. Returns the text
+/// so the UI can show what was copied.
+pub fn copy(app: &tauri::AppHandle, kind: PayloadKind, id: &str, format: ClipboardFormat) -> Result<String, String> {
+    let text = build_table(app, kind, id)?.render(format);
+    app.clipboard()
+        .write_text(text.clone())
+        .map_err(|e| format!("Failed to write to the clipboard: {}", e))?;
+    Ok(text)
+}
@@ -0,0 +1,108 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::dataset;
+use crate::predictions::{self, PredictionRecord};
+
+/// Equal-width confidence bins over [0, 1].
+const BINS: usize = 10;
+/// Candidate decision thresholds reported with their coverage.
+const THRESHOLDS: &[f64] = &[0.5, 0.6, 0.7, 0.8, 0.9, 0.95];
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfidenceHistogram {
+    pub count: usize,
+    pub mean: f64,
+    /// Predictions per bin; bin `i` covers `[i/10, (i+1)/10)`, the last one includes 1.0.
+    pub bins: Vec<usize>,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ThresholdCoverage {
+    pub threshold: f64,
+    /// Share of images whose top confidence reaches the threshold.
+    pub accepted: f64,
+}
+
+/// Max-confidence distribution of a model over an unlabelled folder, per
+/// predicted class and overall, to help pick a production threshold.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfidenceReport {
+    pub model_id: String,
+    pub folder: String,
+    pub images: usize,
+    pub overall: ConfidenceHistogram,
+    pub classes: BTreeMap<String, ConfidenceHistogram>,
+    pub coverage: Vec<ThresholdCoverage>,
+    /// Images that could not be scored, as `path: reason`.
+    pub failures: Vec<String>,
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn histogram(mut values: Vec<f64>) -> ConfidenceHistogram {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mut bins = vec![0; BINS];
+    for v in &values {
+        bins[((v * BINS as f64) as usize).min(BINS - 1)] += 1;
+    }
+    ConfidenceHistogram {
+        count: values.len(),
+        mean: if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 },
+        bins,
+        p10: percentile(&values, 0.1),
+        p50: percentile(&values, 0.5),
+        p90: percentile(&values, 0.9),
+    }
+}
+
+fn build(model_id: &str, folder: &str, records: &[PredictionRecord], failures: Vec<String>) -> ConfidenceReport {
+    let mut per_class: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for r in records {
+        per_class.entry(r.label.clone()).or_default().push(r.confidence);
+    }
+    let coverage = THRESHOLDS
+        .iter()
+        .map(|t| ThresholdCoverage {
+            threshold: *t,
+            accepted: if records.is_empty() {
+                0.0
+            } else {
+                records.iter().filter(|r| r.confidence >= *t).count() as f64 / records.len() as f64
+            },
+        })
+        .collect();
+    ConfidenceReport {
+        model_id: model_id.to_string(),
+        folder: folder.to_string(),
+        images: records.len(),
+        overall: histogram(records.iter().map(|r| r.confidence).collect()),
+        classes: per_class.into_iter().map(|(k, v)| (k, histogram(v))).collect(),
+        coverage,
+        failures,
+    }
+}
+
+/// Scores every image in `folder` (recursively) and summarises the confidences.
+pub async fn report(app: &tauri::AppHandle, model_id: &str, folder: &str) -> Result<ConfidenceReport, String> {
+    let images: Vec<String> = dataset::list_images(Path::new(folder), true)?
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    if images.is_empty() {
+        return Err(format!("No images found in {}.", folder));
+    }
+    let (_, records, failures) = predictions::predict(app, model_id, &images, None, None).await?;
+    Ok(build(model_id, folder, &records, failures))
+}
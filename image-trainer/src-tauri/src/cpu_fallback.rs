@@ -0,0 +1,235 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::{self, GpuAdapter};
+use crate::events;
+use crate::python::resolve_interpreter;
+
+/// Oldest NVIDIA driver (Linux, Windows) that runs each CUDA version, newest first.
+const DRIVER_CUDA: &[((u32, u32), (u32, u32), &str)] = &[
+    ((570, 26), (572, 16), "12.8"),
+    ((560, 28), (560, 76), "12.6"),
+    ((555, 42), (555, 85), "12.5"),
+    ((550, 54), (551, 61), "12.4"),
+    ((545, 23), (546, 12), "12.3"),
+    ((535, 54), (536, 25), "12.2"),
+    ((530, 30), (531, 14), "12.1"),
+    ((525, 60), (527, 41), "12.0"),
+    ((520, 61), (520, 6), "11.8"),
+];
+/// CUDA versions PyTorch publishes wheels for, newest first, with their index tag.
+const CUDA_WHEELS: &[(&str, &str)] = &[
+    ("12.8", "cu128"),
+    ("12.6", "cu126"),
+    ("12.4", "cu124"),
+    ("12.1", "cu121"),
+    ("11.8", "cu118"),
+];
+const ROCM_WHEEL: &str = "rocm6.2";
+const WHEEL_INDEX: &str = "https://download.pytorch.org/whl";
+
+/// Device details script.py sends with its first progress line.
+#[derive(Debug, Clone, Deserialize)]
+struct DeviceInfo {
+    #[serde(rename = "type")]
+    kind: String,
+    torch_version: String,
+    torch_cuda: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackCause {
+    /// The installed PyTorch has no CUDA support.
+    CpuOnlyBuild,
+    /// PyTorch needs a newer CUDA than the driver supports.
+    DriverTooOld,
+    /// Build and driver match, yet no CUDA device was usable.
+    CudaUnavailable,
+    /// The GPU has no PyTorch support on this platform.
+    UnsupportedPlatform,
+}
+
+/// Published as `job://cpu_fallback` when training runs on the CPU although a
+/// usable GPU is installed.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CpuFallbackWarning {
+    pub gpu: String,
+    pub cause: FallbackCause,
+    pub torch_version: String,
+    /// CUDA version PyTorch was built for; `None` for CPU-only builds.
+    pub torch_cuda: Option<String>,
+    pub driver_version: Option<String>,
+    /// Newest CUDA version the installed driver supports.
+    pub driver_cuda: Option<String>,
+    pub message: String,
+    pub remediation: String,
+    /// Shell command that applies the fix, when one does.
+    pub command: Option<String>,
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    Some((parts.next()?.trim().parse().ok()?, parts.next()?.trim().parse().ok()?))
+}
+
+/// NVIDIA driver version as `major.minor` from the adapter's driver string.
+/// Windows reports the WDDM form (`32.0.15.5186`), whose last five digits
+/// are the NVIDIA version (`551.86`).
+fn nvidia_driver_version(driver: &str) -> Option<(u32, u32)> {
+    for token in driver.split_whitespace() {
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() == 4 && parts.iter().all(|p| p.parse::<u32>().is_ok()) {
+            let digits = format!("{}{}", parts[2], parts[3]);
+            let tail = &digits[digits.len().saturating_sub(5)..];
+            if tail.len() == 5 {
+                return Some((tail[..3].parse().ok()?, tail[3..].parse().ok()?));
+            }
+        }
+        if let Some(version) = parse_version(token) {
+            return Some(version);
+        }
+    }
+    None
+}
+
+fn driver_cuda(driver: (u32, u32)) -> Option<&'static str> {
+    DRIVER_CUDA
+        .iter()
+        .find(|(linux, windows, _)| {
+            let minimum = if cfg!(windows) { windows } else { linux };
+            driver >= *minimum
+        })
+        .map(|(_, _, cuda)| *cuda)
+}
+
+/// Newest PyTorch CUDA wheel the driver can run; the oldest one when the
+/// driver version is unknown.
+fn cuda_wheel(driver_cuda: Option<&str>) -> &'static str {
+    let Some(supported) = driver_cuda.and_then(parse_version) else {
+        return CUDA_WHEELS[CUDA_WHEELS.len() - 1].1;
+    };
+    CUDA_WHEELS
+        .iter()
+        .find(|(cuda, _)| parse_version(cuda).is_some_and(|v| v <= supported))
+        .map(|(_, tag)| *tag)
+        .unwrap_or(CUDA_WHEELS[CUDA_WHEELS.len() - 1].1)
+}
+
+fn pip_command(interpreter: &str, wheel: &str) -> String {
+    format!(
+        "\"{}\" -m pip install --force-reinstall torch torchvision --index-url {}/{}",
+        interpreter, WHEEL_INDEX, wheel
+    )
+}
+
+fn diagnose(device: &DeviceInfo, adapters: &[GpuAdapter], interpreter: &str) -> Option<CpuFallbackWarning> {
+    let usable = |a: &&GpuAdapter| a.device_type == "discrete" || a.device_type == "integrated";
+    if let Some(gpu) = adapters.iter().filter(usable).find(|a| a.vendor == "NVIDIA") {
+        let driver = nvidia_driver_version(&gpu.driver);
+        let driver_cuda = driver.and_then(driver_cuda);
+        let wheel = cuda_wheel(driver_cuda);
+        let (cause, message, remediation, command) = match &device.torch_cuda {
+            None => (
+                FallbackCause::CpuOnlyBuild,
+                format!("PyTorch {} is a CPU-only build, so {} is not used.", device.torch_version, gpu.name),
+                format!("Reinstall PyTorch with CUDA support from the {} wheel index, which your driver supports.", wheel),
+                Some(pip_command(interpreter, wheel)),
+            ),
+            Some(torch_cuda)
+                if driver_cuda
+                    .and_then(parse_version)
+                    .zip(parse_version(torch_cuda))
+                    .is_some_and(|(supported, needed)| supported < needed) =>
+            {
+                (
+                    FallbackCause::DriverTooOld,
+                    format!(
+                        "PyTorch is built for CUDA {}, but the NVIDIA driver only supports CUDA {}.",
+                        torch_cuda,
+                        driver_cuda.unwrap_or_default()
+                    ),
+                    format!(
+                        "Update the NVIDIA driver, or install the PyTorch build for CUDA {} from the {} wheel index.",
+                        driver_cuda.unwrap_or_default(),
+                        wheel
+                    ),
+                    Some(pip_command(interpreter, wheel)),
+                )
+            }
+            Some(torch_cuda) => (
+                FallbackCause::CudaUnavailable,
+                format!("PyTorch is built for CUDA {} but found no usable CUDA device.", torch_cuda),
+                "Check that nvidia-smi lists the GPU, that CUDA_VISIBLE_DEVICES is not hiding it, and restart after driver updates.".to_string(),
+                None,
+            ),
+        };
+        return Some(CpuFallbackWarning {
+            gpu: gpu.name.clone(),
+            cause,
+            torch_version: device.torch_version.clone(),
+            torch_cuda: device.torch_cuda.clone(),
+            driver_version: driver.map(|(major, minor)| format!("{}.{:02}", major, minor)),
+            driver_cuda: driver_cuda.map(str::to_string),
+            message,
+            remediation,
+            command,
+        });
+    }
+
+    let gpu = adapters.iter().filter(|a| a.device_type == "discrete").find(|a| a.vendor == "AMD")?;
+    let (cause, remediation, command) = if cfg!(target_os = "linux") {
+        (
+            FallbackCause::CpuOnlyBuild,
+            format!("Install the ROCm build of PyTorch from the {} wheel index.", ROCM_WHEEL),
+            Some(pip_command(interpreter, ROCM_WHEEL)),
+        )
+    } else {
+        (
+            FallbackCause::UnsupportedPlatform,
+            "PyTorch supports AMD GPUs only on Linux through ROCm; training here runs on the CPU.".to_string(),
+            None,
+        )
+    };
+    Some(CpuFallbackWarning {
+        gpu: gpu.name.clone(),
+        cause,
+        torch_version: device.torch_version.clone(),
+        torch_cuda: device.torch_cuda.clone(),
+        driver_version: None,
+        driver_cuda: None,
+        message: format!("Training runs on the CPU although {} is installed.", gpu.name),
+        remediation,
+        command,
+    })
+}
+
+/// Checks the device info on a job's first progress line. When training runs
+/// on the CPU while a supported GPU is installed, publishes a warning with the
+/// likely cause and a fix.
+pub fn inspect(app: &tauri::AppHandle, job_id: &str, line: &str) {
+    if !line.contains("\"device\"") {
+        return;
+    }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+        return;
+    };
+    let Ok(device) = serde_json::from_value::<DeviceInfo>(value["device"].clone()) else {
+        return;
+    };
+    if value["status"] != "progress" || device.kind != "cpu" {
+        return;
+    }
+    let app = app.clone();
+    let job_id = job_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let adapters = tauri::async_runtime::spawn_blocking(adapters::enumerate)
+            .await
+            .unwrap_or_default();
+        let interpreter = resolve_interpreter(&app).await.unwrap_or_else(|_| "python".to_string());
+        if let Some(warning) = diagnose(&device, &adapters, &interpreter) {
+            tracing::warn!("Job {}: {} {}", job_id, warning.message, warning.remediation);
+            events::publish(&app, "job://cpu_fallback", Some(&job_id), warning);
+        }
+    });
+}
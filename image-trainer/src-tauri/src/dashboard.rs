@@ -0,0 +1,243 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::System;
+use tauri::Manager;
+use tauri_plugin_shell::ShellExt;
+
+use crate::jobs::{unix_now, ActiveJob, JobManager, JobRecord};
+use crate::live_metrics::{MetricsCollector, TrainingProgress};
+use crate::paths;
+use crate::preflight;
+use crate::schedule::Scheduler;
+use crate::sweep::SweepManager;
+
+/// nvidia-smi starts a process, so its readings are reused for this long.
+const GPU_TTL: Duration = Duration::from_secs(5);
+/// Walking the storage folders is the most expensive part of a snapshot.
+const STORAGE_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DashboardJob {
+    #[serde(flatten)]
+    pub job: ActiveJob,
+    /// Epoch, step and ETA for training jobs that reported progress.
+    pub progress: Option<TrainingProgress>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GpuUsage {
+    pub index: u32,
+    pub name: String,
+    pub utilization_percent: Option<f64>,
+    pub memory_used_mb: Option<u64>,
+    pub memory_total_mb: Option<u64>,
+    pub temperature_c: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SystemUsage {
+    /// Averaged over all cores since the previous snapshot.
+    pub cpu_percent: f64,
+    pub memory_used_mb: u64,
+    pub memory_total_mb: u64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QueueDepth {
+    pub scheduled_jobs: u32,
+    /// Trials of running sweeps that have not finished yet.
+    pub pending_trials: u32,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StorageUsage {
+    pub data_bytes: u64,
+    pub models_bytes: u64,
+    pub cache_bytes: u64,
+    pub logs_bytes: u64,
+    /// Free space on the drive holding the models folder.
+    pub free_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    /// When these sizes were measured.
+    pub measured_at: u64,
+}
+
+/// Everything the home screen shows, returned by `get_dashboard_snapshot`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DashboardSnapshot {
+    pub jobs: Vec<DashboardJob>,
+    /// Empty when nvidia-smi is not installed.
+    pub gpus: Vec<GpuUsage>,
+    pub system: SystemUsage,
+    pub queue: QueueDepth,
+    pub recent_completions: Vec<JobRecord>,
+    pub storage: Option<StorageUsage>,
+}
+
+/// Managed state: the system probe, which needs the previous reading for CPU
+/// load, and cached GPU and storage readings so polling stays cheap.
+pub struct Dashboard {
+    system: Mutex<System>,
+    gpus: Mutex<Option<(Instant, Vec<GpuUsage>)>>,
+    storage: Mutex<Option<(Instant, StorageUsage)>>,
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Self {
+            system: Mutex::new(System::new()),
+            gpus: Mutex::new(None),
+            storage: Mutex::new(None),
+        }
+    }
+}
+
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+fn measure_storage(app: &tauri::AppHandle) -> Result<StorageUsage, String> {
+    let models = paths::models_dir(app)?;
+    let disk = preflight::disk_space(&models);
+    Ok(StorageUsage {
+        data_bytes: dir_size(&paths::data_dir(app)?),
+        models_bytes: dir_size(&models),
+        cache_bytes: dir_size(&paths::cache_dir(app)?),
+        logs_bytes: dir_size(&paths::log_dir(app)?),
+        free_bytes: disk.map(|(free, _)| free),
+        total_bytes: disk.map(|(_, total)| total),
+        measured_at: unix_now(),
+    })
+}
+
+/// Parses `nvidia-smi --query-gpu` CSV output; `[N/A]` fields become `None`.
+fn parse_gpus(csv: &str) -> Vec<GpuUsage> {
+    csv.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            Some(GpuUsage {
+                index: fields[0].parse().ok()?,
+                name: fields[1].to_string(),
+                utilization_percent: fields[2].parse().ok(),
+                memory_used_mb: fields[3].parse().ok(),
+                memory_total_mb: fields[4].parse().ok(),
+                temperature_c: fields[5].parse().ok(),
+            })
+        })
+        .collect()
+}
+
+async fn query_gpus(app: &tauri::AppHandle) -> Vec<GpuUsage> {
+    let output = app
+        .shell()
+        .command("nvidia-smi")
+        .args([
+            "--query-gpu=index,name,utilization.gpu,memory.used,memory.total,temperature.gpu",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => parse_gpus(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+impl Dashboard {
+    fn system_usage(&self) -> SystemUsage {
+        let mut system = self.system.lock().unwrap();
+        system.refresh_cpu_usage();
+        system.refresh_memory();
+        SystemUsage {
+            cpu_percent: system.global_cpu_usage() as f64,
+            memory_used_mb: system.used_memory() / (1024 * 1024),
+            memory_total_mb: system.total_memory() / (1024 * 1024),
+        }
+    }
+
+    async fn gpus(&self, app: &tauri::AppHandle) -> Vec<GpuUsage> {
+        if let Some((at, gpus)) = self.gpus.lock().unwrap().as_ref() {
+            if at.elapsed() < GPU_TTL {
+                return gpus.clone();
+            }
+        }
+        let gpus = query_gpus(app).await;
+        *self.gpus.lock().unwrap() = Some((Instant::now(), gpus.clone()));
+        gpus
+    }
+
+    async fn storage(&self, app: &tauri::AppHandle) -> Option<StorageUsage> {
+        if let Some((at, storage)) = self.storage.lock().unwrap().as_ref() {
+            if at.elapsed() < STORAGE_TTL {
+                return Some(storage.clone());
+            }
+        }
+        let handle = app.clone();
+        let storage = tauri::async_runtime::spawn_blocking(move || measure_storage(&handle))
+            .await
+            .ok()?
+            .ok()?;
+        *self.storage.lock().unwrap() = Some((Instant::now(), storage.clone()));
+        Some(storage)
+    }
+}
+
+/// One payload with everything the dashboard shows. Managed states that are
+/// not loaded yet contribute empty sections.
+pub async fn snapshot(app: &tauri::AppHandle) -> DashboardSnapshot {
+    let dashboard = app.state::<Arc<Dashboard>>().inner().clone();
+    let (active, recent_completions) = app
+        .try_state::<Arc<JobManager>>()
+        .map(|jobs| (jobs.active(), jobs.recent_completions()))
+        .unwrap_or_default();
+    let collector = app.try_state::<Arc<MetricsCollector>>();
+    let jobs = active
+        .into_iter()
+        .map(|job| DashboardJob {
+            progress: collector.as_ref().and_then(|c| c.progress(&job.job_id)),
+            job,
+        })
+        .collect();
+    let pending_trials = app
+        .try_state::<Arc<SweepManager>>()
+        .map(|sweeps| {
+            sweeps
+                .list()
+                .iter()
+                .filter(|s| s.running_workers > 0 && !s.cancelled)
+                .map(|s| s.n_trials.saturating_sub(s.completed + s.failed))
+                .sum()
+        })
+        .unwrap_or(0);
+    let queue = QueueDepth {
+        scheduled_jobs: app
+            .try_state::<Arc<Scheduler>>()
+            .map_or(0, |s| s.list().len() as u32),
+        pending_trials,
+    };
+
+    DashboardSnapshot {
+        jobs,
+        gpus: dashboard.gpus(app).await,
+        system: dashboard.system_usage(),
+        queue,
+        recent_completions,
+        storage: dashboard.storage(app).await,
+    }
+}
@@ -0,0 +1,55 @@
+use rusqlite::Connection;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A SQLite database opened on first use and kept open until `close`, as the
+/// job history, inference audit log and annotation store use it.
+pub struct Database {
+    path: PathBuf,
+    /// What the database holds, for messages, e.g. `job history`.
+    name: &'static str,
+    /// Creates or migrates the schema when the database is opened.
+    init: fn(&Connection) -> rusqlite::Result<()>,
+    conn: Mutex<Option<Connection>>,
+}
+
+impl Database {
+    pub fn new(path: PathBuf, name: &'static str, init: fn(&Connection) -> rusqlite::Result<()>) -> Self {
+        Self { path, name, init, conn: Mutex::new(None) }
+    }
+
+    /// Whether the file exists; reads return nothing rather than create it.
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Opens an existing database and applies the schema, as the first write would.
+    pub fn check(&self) -> Result<(), String> {
+        if !self.exists() {
+            return Ok(());
+        }
+        self.with_conn(|_| Ok(()))
+    }
+
+    /// Closes the database so pending writes are on disk; the next use reopens it.
+    pub fn close(&self) {
+        if let Some(conn) = self.conn.lock().unwrap().take() {
+            if let Err((_, e)) = conn.close() {
+                tracing::error!("Failed to close the {}: {}", self.name, e);
+            }
+        }
+    }
+
+    pub fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+        let mut guard = self.conn.lock().unwrap();
+        if guard.is_none() {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            let conn = Connection::open(&self.path).map_err(|e| format!("Cannot open the {}: {}", self.name, e))?;
+            (self.init)(&conn).map_err(|e| format!("Cannot initialise the {}: {}", self.name, e))?;
+            *guard = Some(conn);
+        }
+        f(guard.as_mut().unwrap()).map_err(|e| format!("Error in the {}: {}", self.name, e))
+    }
+}
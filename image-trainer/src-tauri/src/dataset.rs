@@ -0,0 +1,130 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::BackendError;
+
+/// Extensions torchvision's ImageFolder accepts.
+pub const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "ppm", "bmp", "pgm", "tif", "tiff", "webp"];
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Whether `entry` is a real directory. Symlinked folders are not followed,
+/// so a link back up the tree cannot make a walk recurse forever.
+pub fn is_real_dir(entry: &fs::DirEntry) -> bool {
+    entry.file_type().map(|t| t.is_dir()).unwrap_or(false)
+}
+
+/// Image files under `dir`, sorted, descending into subfolders when `recursive`.
+pub fn list_images(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>, BackendError> {
+    let entries = fs::read_dir(dir).map_err(|e| BackendError::io(format!("Failed to read {}: {}", dir.display(), e)))?;
+    let mut images = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if is_real_dir(&entry) {
+            if recursive {
+                images.extend(list_images(&path, true)?);
+            }
+        } else if is_image(&path) {
+            images.push(path);
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+/// Per-class image counts of the training portion of a dataset.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassDistribution {
+    pub counts: BTreeMap<String, usize>,
+    pub total: usize,
+    /// Largest class count divided by the smallest non-empty one.
+    pub imbalance_ratio: f64,
+}
+
+/// Folder script.py trains from: `<root>/train` for pre-split datasets, otherwise
+/// the root itself (the auto-split is stratified, so proportions are the same).
+pub fn training_root(root: &Path) -> PathBuf {
+    let train = root.join("train");
+    if train.is_dir() {
+        train
+    } else {
+        root.to_path_buf()
+    }
+}
+
+fn count_images(dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if is_real_dir(&entry) {
+                // ImageFolder picks up images in nested folders too
+                count_images(&path)
+            } else if is_image(&path) {
+                1
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// Counts images per class folder under the dataset's training root.
+pub fn class_distribution(root: &Path) -> Result<ClassDistribution, BackendError> {
+    let train_root = training_root(root);
+    let entries = fs::read_dir(&train_root)
+        .map_err(|e| BackendError::io(format!("Failed to read dataset {}: {}", train_root.display(), e)))?;
+
+    let mut counts = BTreeMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        counts.insert(name, count_images(&path));
+    }
+    if counts.is_empty() {
+        return Err(BackendError::invalid_input("Invalid dataset structure. Expected folders for each class."));
+    }
+
+    let total = counts.values().sum();
+    let max = counts.values().copied().max().unwrap_or(0);
+    let min = counts.values().copied().filter(|&c| c > 0).min().unwrap_or(0);
+    let imbalance_ratio = if min > 0 { max as f64 / min as f64 } else { 0.0 };
+
+    Ok(ClassDistribution {
+        counts,
+        total,
+        imbalance_ratio,
+    })
+}
+
+impl ClassDistribution {
+    /// Inverse-frequency weights `total / (classes * count)` so the average weight is 1.
+    /// Empty classes get weight 1.0 since they never contribute to the loss.
+    pub fn balanced_weights(&self) -> BTreeMap<String, f64> {
+        let k = self.counts.len() as f64;
+        self.counts
+            .iter()
+            .map(|(name, &count)| {
+                let w = if count > 0 {
+                    self.total as f64 / (k * count as f64)
+                } else {
+                    1.0
+                };
+                (name.clone(), w)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,260 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dataset;
+
+/// Split folders script.py recognizes, in the order it looks for them.
+pub const SPLIT_NAMES: &[&str] = &["train", "val", "validation", "test"];
+/// Files every OS leaves around that are not worth reporting.
+const IGNORED_FILES: &[&str] = &["thumbs.db", "desktop.ini"];
+/// Unsupported files listed by path; the rest are only counted.
+const MAX_LISTED: usize = 200;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ClassScan {
+    pub name: String,
+    /// Split folder the class sits in, or null when classes are directly in the dataset.
+    pub split: Option<String>,
+    pub images: usize,
+}
+
+/// A file name found in more than one class, usually the same image filed twice.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DuplicateName {
+    pub name: String,
+    pub paths: Vec<String>,
+}
+
+/// What `scan_dataset` found, read without starting Python.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DatasetScan {
+    pub path: String,
+    /// Split folders present; empty when the dataset is split at training time.
+    pub splits: Vec<String>,
+    pub classes: Vec<ClassScan>,
+    pub total_images: usize,
+    /// `split/class` (or `class`) folders without a single image.
+    pub empty_classes: Vec<String>,
+    /// Files in class folders that are not images training can read.
+    pub unsupported_files: Vec<String>,
+    pub unsupported_count: usize,
+    /// Images outside any class folder, which training ignores.
+    pub loose_images: Vec<String>,
+    /// Class folder names differing only in case, e.g. `Cat` and `cat`.
+    pub duplicate_classes: Vec<Vec<String>>,
+    /// File names appearing in more than one class of the same split.
+    pub duplicate_names: Vec<DuplicateName>,
+    /// Classes missing from some split, per split.
+    pub missing_classes: BTreeMap<String, Vec<String>>,
+    /// Everything above that would make training fail or mislead, as sentences.
+    pub problems: Vec<String>,
+    /// No problem stops training.
+    pub valid: bool,
+}
+
+fn ignored(name: &str) -> bool {
+    name.starts_with('.') || IGNORED_FILES.contains(&name.to_ascii_lowercase().as_str())
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().to_string()
+}
+
+/// Images (recursively, as ImageFolder reads them) and other files in a class folder.
+fn walk_class(dir: &Path, images: &mut Vec<PathBuf>, other: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if ignored(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        if dataset::is_real_dir(&entry) {
+            walk_class(&path, images, other);
+        } else if dataset::is_image(&path) {
+            images.push(path);
+        } else {
+            other.push(path);
+        }
+    }
+}
+
+struct Folder {
+    dirs: Vec<(String, PathBuf)>,
+    files: Vec<PathBuf>,
+}
+
+fn read_folder(dir: &Path) -> Result<Folder, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read dataset {}: {}", dir.display(), e))?;
+    let mut folder = Folder { dirs: Vec::new(), files: Vec::new() };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if ignored(&name) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            folder.dirs.push((name, path));
+        } else {
+            folder.files.push(path);
+        }
+    }
+    folder.dirs.sort();
+    folder.files.sort();
+    Ok(folder)
+}
+
+/// Walks a class-per-folder dataset, pre-split into train/val/test or not,
+/// and reports its layout and anything that would trip up training.
+pub fn scan(path: &str) -> Result<DatasetScan, String> {
+    let root = Path::new(path);
+    if !root.is_dir() {
+        return Err(format!("{} is not a folder.", path));
+    }
+    let top = read_folder(root)?;
+    let split_dirs: Vec<(String, PathBuf)> =
+        top.dirs.iter().filter(|(name, _)| SPLIT_NAMES.contains(&name.as_str())).cloned().collect();
+    let pre_split = !split_dirs.is_empty();
+
+    let mut scan = DatasetScan {
+        path: path.to_string(),
+        splits: split_dirs.iter().map(|(name, _)| name.clone()).collect(),
+        classes: Vec::new(),
+        total_images: 0,
+        empty_classes: Vec::new(),
+        unsupported_files: Vec::new(),
+        unsupported_count: 0,
+        loose_images: Vec::new(),
+        duplicate_classes: Vec::new(),
+        duplicate_names: Vec::new(),
+        missing_classes: BTreeMap::new(),
+        problems: Vec::new(),
+        valid: true,
+    };
+    let note_loose = |files: &[PathBuf], scan: &mut DatasetScan| {
+        scan.loose_images.extend(files.iter().filter(|f| dataset::is_image(f)).map(|f| relative(root, f)));
+    };
+    note_loose(&top.files, &mut scan);
+
+    let groups: Vec<(Option<String>, Folder)> = if pre_split {
+        let mut groups = Vec::new();
+        for (name, dir) in &split_dirs {
+            let folder = read_folder(dir)?;
+            note_loose(&folder.files, &mut scan);
+            groups.push((Some(name.clone()), folder));
+        }
+        groups
+    } else {
+        vec![(None, top)]
+    };
+
+    let mut class_sets = BTreeMap::new();
+    for (split, folder) in &groups {
+        let mut by_lower: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut by_file: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, dir) in &folder.dirs {
+            let mut images = Vec::new();
+            let mut other = Vec::new();
+            walk_class(dir, &mut images, &mut other);
+            let label = match split {
+                Some(split) => format!("{}/{}", split, name),
+                None => name.clone(),
+            };
+            if images.is_empty() {
+                scan.empty_classes.push(label);
+            }
+            for image in &images {
+                if let Some(file) = image.file_name() {
+                    by_file.entry(file.to_string_lossy().to_string()).or_default().push(relative(root, image));
+                }
+            }
+            scan.unsupported_count += other.len();
+            let room = MAX_LISTED.saturating_sub(scan.unsupported_files.len());
+            scan.unsupported_files.extend(other.iter().take(room).map(|f| relative(root, f)));
+            by_lower.entry(name.to_lowercase()).or_default().push(name.clone());
+            scan.total_images += images.len();
+            scan.classes.push(ClassScan { name: name.clone(), split: split.clone(), images: images.len() });
+        }
+        scan.duplicate_classes.extend(by_lower.into_values().filter(|names| names.len() > 1));
+        // Only names in different class folders count; nested folders of one class may repeat names.
+        scan.duplicate_names.extend(by_file.into_iter().filter_map(|(name, paths)| {
+            let classes = paths.iter().map(|p| class_of(p, split.is_some())).collect::<BTreeSet<_>>();
+            (classes.len() > 1).then_some(DuplicateName { name, paths })
+        }));
+        class_sets.insert(split.clone(), folder.dirs.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>());
+    }
+
+    if pre_split {
+        let all: BTreeSet<&String> = class_sets.values().flatten().collect();
+        for (split, classes) in &class_sets {
+            let missing: Vec<String> = all.iter().filter(|c| !classes.contains(**c)).map(|c| c.to_string()).collect();
+            if !missing.is_empty() {
+                scan.missing_classes.insert(split.clone().unwrap_or_default(), missing);
+            }
+        }
+    }
+
+    check(&mut scan);
+    Ok(scan)
+}
+
+/// Class folder of a dataset-relative image path.
+fn class_of(relative: &str, pre_split: bool) -> String {
+    let mut parts = Path::new(relative).components().map(|c| c.as_os_str().to_string_lossy().to_string());
+    if pre_split {
+        parts.next();
+    }
+    parts.next().unwrap_or_default()
+}
+
+/// Turns the findings into `problems`; only some of them make the dataset invalid.
+fn check(scan: &mut DatasetScan) {
+    let mut fatal = Vec::new();
+    let mut warnings = Vec::new();
+    let training: Vec<&ClassScan> =
+        scan.classes.iter().filter(|c| c.split.is_none() || c.split.as_deref() == Some("train")).collect();
+    if scan.classes.is_empty() {
+        fatal.push("Invalid dataset structure. Expected folders for each class.".to_string());
+    } else if !scan.splits.is_empty() && !scan.splits.iter().any(|s| s == "train") {
+        fatal.push("The dataset has split folders but no train folder.".to_string());
+    } else if training.iter().filter(|c| c.images > 0).count() < 2 {
+        fatal.push("Training needs at least two classes with images.".to_string());
+    }
+    if scan.splits.iter().any(|s| s == "val") && scan.splits.iter().any(|s| s == "validation") {
+        warnings.push("Both val and validation folders exist; training uses val.".to_string());
+    }
+    if !scan.empty_classes.is_empty() {
+        warnings.push(format!(
+            "{} class folder(s) have no images: {}.",
+            scan.empty_classes.len(),
+            scan.empty_classes.join(", ")
+        ));
+    }
+    if scan.unsupported_count > 0 {
+        warnings.push(format!(
+            "{} file(s) in class folders are not supported images and will be skipped.",
+            scan.unsupported_count
+        ));
+    }
+    if !scan.loose_images.is_empty() {
+        warnings
+            .push(format!("{} image(s) are outside any class folder and will be ignored.", scan.loose_images.len()));
+    }
+    for names in &scan.duplicate_classes {
+        warnings
+            .push(format!("Class folders {} differ only in case and would become separate classes.", names.join(", ")));
+    }
+    if !scan.duplicate_names.is_empty() {
+        warnings.push(format!("{} file name(s) appear in more than one class.", scan.duplicate_names.len()));
+    }
+    for (split, missing) in &scan.missing_classes {
+        // ImageFolder numbers classes by folder, so a missing class shifts the labels of the others.
+        fatal.push(format!("The {} split is missing class(es): {}.", split, missing.join(", ")));
+    }
+    scan.valid = fatal.is_empty();
+    scan.problems = fatal.into_iter().chain(warnings).collect();
+}
@@ -0,0 +1,310 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::dataset;
+use crate::error::BackendError;
+use crate::events;
+use crate::jobs::unix_now;
+
+const SPLITS: [&str; 3] = ["train", "val", "test"];
+
+/// Fractions of each class (or of the whole dataset) per split; they must add up to 1.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct SplitRatios {
+    pub train: f64,
+    pub val: f64,
+    #[serde(default)]
+    pub test: f64,
+}
+
+impl SplitRatios {
+    fn validate(&self) -> Result<(), BackendError> {
+        if [self.train, self.val, self.test].iter().any(|r| !(0.0..=1.0).contains(r)) {
+            return Err(BackendError::invalid_input("Split ratios must be between 0 and 1."));
+        }
+        if self.train == 0.0 {
+            return Err(BackendError::invalid_input("The train ratio must be above 0."));
+        }
+        if (self.train + self.val + self.test - 1.0).abs() > 1e-6 {
+            return Err(BackendError::invalid_input("Split ratios must add up to 1."));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitMode {
+    /// Copies images into `dest/train|val|test/<class>`.
+    #[default]
+    Copy,
+    /// Links instead of copying; on Windows this needs Developer Mode or admin rights.
+    Symlink,
+    /// Writes `dest` as a JSON file listing the images of each split, leaving them in place.
+    Manifest,
+}
+
+/// Written by `SplitMode::Manifest`, with paths relative to the dataset.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SplitManifest {
+    pub created_at: u64,
+    pub dataset: String,
+    pub ratios: SplitRatios,
+    pub stratified: bool,
+    pub seed: u64,
+    pub splits: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SplitReport {
+    pub dest: String,
+    pub mode: SplitMode,
+    /// Images per class in each split.
+    pub counts: BTreeMap<String, BTreeMap<String, usize>>,
+    pub total: usize,
+    /// The seed used, generated when none was given, to repeat the split.
+    pub seed: u64,
+}
+
+/// Published as `split://progress` while images are copied or linked.
+#[derive(Debug, Clone, Serialize)]
+struct SplitProgress {
+    done: usize,
+    total: usize,
+}
+
+/// How many of `n` images go to val and test; every split with a non-zero
+/// ratio gets at least one image when there are enough to go round.
+fn allocate(n: usize, ratios: &SplitRatios) -> (usize, usize) {
+    let share = |ratio: f64| {
+        let count = (n as f64 * ratio).round() as usize;
+        if ratio > 0.0 && count == 0 && n >= 3 {
+            1
+        } else {
+            count
+        }
+    };
+    let val = share(ratios.val).min(n.saturating_sub(1));
+    let test = share(ratios.test).min(n.saturating_sub(1 + val));
+    (val, test)
+}
+
+fn assign(
+    images: &mut [(String, PathBuf)],
+    ratios: &SplitRatios,
+    rng: &mut StdRng,
+) -> Vec<(&'static str, String, PathBuf)> {
+    images.shuffle(rng);
+    let (val, test) = allocate(images.len(), ratios);
+    images
+        .iter()
+        .enumerate()
+        .map(|(i, (class, path))| {
+            let split = if i < val {
+                "val"
+            } else if i < val + test {
+                "test"
+            } else {
+                "train"
+            };
+            (split, class.clone(), path.clone())
+        })
+        .collect()
+}
+
+fn link(from: &Path, to: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(from, to);
+    #[cfg(windows)]
+    return std::os::windows::fs::symlink_file(from, to);
+}
+
+/// Splits a class-per-folder dataset into train, val and test. With
+/// `stratified`, each class is split by `ratios` on its own so every split keeps
+/// the class balance; otherwise the whole dataset is shuffled together. The
+/// same seed gives the same split.
+pub fn split(
+    app: &tauri::AppHandle,
+    path: &Path,
+    dest: &Path,
+    ratios: SplitRatios,
+    stratified: bool,
+    seed: Option<u64>,
+    mode: SplitMode,
+) -> Result<SplitReport, String> {
+    ratios.validate()?;
+    if !path.is_dir() {
+        return Err(format!("{} is not a folder.", path.display()));
+    }
+    if dataset::training_root(path) != path {
+        return Err("The dataset is already split into train, val and test folders.".to_string());
+    }
+    if mode == SplitMode::Manifest {
+        if !dest.to_string_lossy().to_lowercase().ends_with(".json") {
+            return Err("The manifest file must end in .json.".to_string());
+        }
+    } else {
+        if dest.exists() && std::fs::read_dir(dest).map(|mut d| d.next().is_some()).unwrap_or(true) {
+            return Err(format!("{} is not empty.", dest.display()));
+        }
+        if dest.starts_with(path) {
+            return Err("The destination cannot be inside the dataset.".to_string());
+        }
+    }
+
+    let mut classes = Vec::new();
+    let entries = std::fs::read_dir(path).map_err(|e| format!("Failed to read dataset {}: {}", path.display(), e))?;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if dir.is_dir() && !name.starts_with('.') {
+            let images = dataset::list_images(&dir, true)?;
+            classes.push((name.clone(), images.into_iter().map(|image| (name.clone(), image)).collect::<Vec<_>>()));
+        }
+    }
+    if classes.is_empty() {
+        return Err("Invalid dataset structure. Expected folders for each class.".to_string());
+    }
+    // read_dir order varies by filesystem; sort so the seed alone decides the split.
+    classes.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let assigned = if stratified {
+        let mut assigned = Vec::new();
+        for (_, mut images) in classes {
+            assigned.extend(assign(&mut images, &ratios, &mut rng));
+        }
+        assigned
+    } else {
+        let mut images: Vec<_> = classes.into_iter().flat_map(|(_, images)| images).collect();
+        assign(&mut images, &ratios, &mut rng)
+    };
+    if assigned.is_empty() {
+        return Err("The dataset has no images.".to_string());
+    }
+
+    let mut counts: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+    for (split, class, _) in &assigned {
+        *counts.entry(split.to_string()).or_default().entry(class.clone()).or_default() += 1;
+    }
+    let total = assigned.len();
+
+    if mode == SplitMode::Manifest {
+        let mut splits: BTreeMap<String, Vec<String>> = SPLITS.iter().map(|s| (s.to_string(), Vec::new())).collect();
+        for (split, _, image) in &assigned {
+            let relative = image.strip_prefix(path).unwrap_or(image).to_string_lossy().replace('\\', "/");
+            splits.entry(split.to_string()).or_default().push(relative);
+        }
+        splits.values_mut().for_each(|files| files.sort());
+        let manifest = SplitManifest {
+            created_at: unix_now(),
+            dataset: path.to_string_lossy().to_string(),
+            ratios,
+            stratified,
+            seed,
+            splits,
+        };
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        std::fs::write(dest, raw).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+    } else {
+        for (done, (split, class, image)) in assigned.iter().enumerate() {
+            // Keeps nested folders of a class apart, as list_images descends into them.
+            let within = image.strip_prefix(path.join(class)).unwrap_or(image);
+            let target = dest.join(split).join(class).join(within);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let result = match mode {
+                SplitMode::Symlink => {
+                    let source = image.canonicalize().unwrap_or_else(|_| image.clone());
+                    link(&source, &target)
+                }
+                _ => std::fs::copy(image, &target).map(|_| ()),
+            };
+            result.map_err(|e| format!("Cannot write {}: {}", target.display(), e))?;
+            if (done + 1) % 100 == 0 || done + 1 == total {
+                events::publish(app, "split://progress", None, SplitProgress { done: done + 1, total });
+            }
+        }
+    }
+
+    Ok(SplitReport { dest: dest.to_string_lossy().to_string(), mode, counts, total, seed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratios(train: f64, val: f64, test: f64) -> SplitRatios {
+        SplitRatios { train, val, test }
+    }
+
+    fn images(class: &str, n: usize) -> Vec<(String, PathBuf)> {
+        (0..n).map(|i| (class.to_string(), PathBuf::from(format!("{}/{}.jpg", class, i)))).collect()
+    }
+
+    fn count(assigned: &[(&'static str, String, PathBuf)], split: &str, class: &str) -> usize {
+        assigned.iter().filter(|(s, c, _)| *s == split && c == class).count()
+    }
+
+    #[test]
+    fn validate_rejects_bad_ratios() {
+        assert!(ratios(0.8, 0.1, 0.1).validate().is_ok());
+        assert!(ratios(1.0, 0.0, 0.0).validate().is_ok());
+        assert!(ratios(0.0, 0.5, 0.5).validate().is_err());
+        assert!(ratios(0.8, 0.3, 0.0).validate().is_err());
+        assert!(ratios(1.2, -0.2, 0.0).validate().is_err());
+    }
+
+    #[test]
+    fn allocate_gives_every_non_zero_split_an_image() {
+        for n in 3..50 {
+            let (val, test) = allocate(n, &ratios(0.9, 0.05, 0.05));
+            assert!(val >= 1 && test >= 1, "n = {}", n);
+            assert!(val + test < n, "n = {}", n);
+        }
+        assert_eq!(allocate(10, &ratios(0.9, 0.1, 0.0)), (1, 0));
+        assert_eq!(allocate(100, &ratios(0.7, 0.2, 0.1)), (20, 10));
+    }
+
+    #[test]
+    fn allocate_keeps_train_with_fewer_than_three_images() {
+        let even = ratios(0.34, 0.33, 0.33);
+        assert_eq!(allocate(0, &even), (0, 0));
+        assert_eq!(allocate(1, &even), (0, 0));
+        let (val, test) = allocate(2, &even);
+        assert!(val + test <= 1);
+        let (val, test) = allocate(2, &ratios(0.1, 0.9, 0.0));
+        assert_eq!((val, test), (1, 0));
+    }
+
+    #[test]
+    fn assign_is_repeatable_for_a_seed() {
+        let split = |seed| assign(&mut images("cat", 40), &ratios(0.7, 0.2, 0.1), &mut StdRng::seed_from_u64(seed));
+        assert_eq!(split(7), split(7));
+        assert_ne!(split(7), split(8));
+    }
+
+    #[test]
+    fn stratified_split_keeps_the_class_balance() {
+        let ratios = ratios(0.6, 0.2, 0.2);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut assigned = Vec::new();
+        for (class, n) in [("cat", 50), ("dog", 10)] {
+            assigned.extend(assign(&mut images(class, n), &ratios, &mut rng));
+        }
+        assert_eq!((count(&assigned, "val", "cat"), count(&assigned, "test", "cat")), (10, 10));
+        assert_eq!(count(&assigned, "train", "cat"), 30);
+        assert_eq!((count(&assigned, "val", "dog"), count(&assigned, "test", "dog")), (2, 2));
+        assert_eq!(count(&assigned, "train", "dog"), 6);
+    }
+}
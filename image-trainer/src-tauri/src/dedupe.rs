@@ -0,0 +1,138 @@
+use image_hasher::{HashAlg, HasherConfig, ImageHash};
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::dataset;
+use crate::dataset_scan::SPLIT_NAMES;
+use crate::events;
+use crate::image_meta;
+
+/// Hamming distance between 64-bit hashes at or below which images count as
+/// near-duplicates: 0 is identical after resizing, about 10 is a different photo.
+pub const DEFAULT_THRESHOLD: u32 = 5;
+const MAX_THRESHOLD: u32 = 32;
+/// Pairs reported, closest first; a folder of burst shots can produce millions.
+const MAX_PAIRS: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DuplicatePair {
+    /// Paths relative to the dataset.
+    pub a: String,
+    pub b: String,
+    pub distance: u32,
+    /// The images are in different split folders, e.g. train and test, so the
+    /// evaluation partly measures memorization.
+    pub cross_split: bool,
+    /// The images are filed under different classes.
+    pub cross_class: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DedupeReport {
+    pub path: String,
+    pub threshold: u32,
+    pub images: usize,
+    pub pairs: Vec<DuplicatePair>,
+    /// More pairs were found than reported.
+    pub truncated: bool,
+    pub cross_split_pairs: usize,
+    /// Images that could not be decoded, with the reason.
+    pub failures: Vec<String>,
+}
+
+/// Published as `dedupe://progress` while images are hashed.
+#[derive(Debug, Clone, Serialize)]
+struct DedupeProgress {
+    done: usize,
+    total: usize,
+}
+
+/// Split and class folder of a dataset-relative path.
+fn location(relative: &str) -> (Option<&str>, &str) {
+    let mut parts = relative.split('/');
+    let first = parts.next().unwrap_or_default();
+    if SPLIT_NAMES.contains(&first) {
+        (Some(first), parts.next().unwrap_or_default())
+    } else {
+        (None, first)
+    }
+}
+
+/// Finds near-duplicate images anywhere under `path` by perceptual hash,
+/// hashing on all cores. Pairs at Hamming distance `threshold` or less are
+/// reported closest first, cross-split pairs (train/test leakage) first among equals.
+pub fn find_duplicates(app: &tauri::AppHandle, path: &Path, threshold: Option<u32>) -> Result<DedupeReport, String> {
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD);
+    if threshold > MAX_THRESHOLD {
+        return Err(format!("The threshold must be at most {}.", MAX_THRESHOLD));
+    }
+    if !path.is_dir() {
+        return Err(format!("{} is not a folder.", path.display()));
+    }
+    let images = dataset::list_images(path, true)?;
+    let total = images.len();
+    let done = AtomicUsize::new(0);
+    // Gradient hashes hold up well to re-encoding and resizing, the usual
+    // ways one photo ends up in a dataset twice.
+    let hasher = HasherConfig::new().hash_alg(HashAlg::Gradient).hash_size(8, 8).to_hasher();
+
+    let hashed: Vec<(String, Result<ImageHash, String>)> = images
+        .par_iter()
+        .map(|image| {
+            let hash = image_meta::open_oriented(image).map(|img| hasher.hash_image(&img));
+            let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+            if finished % 100 == 0 || finished == total {
+                events::publish(app, "dedupe://progress", None, DedupeProgress { done: finished, total });
+            }
+            let relative = image.strip_prefix(path).unwrap_or(image).to_string_lossy().replace('\\', "/");
+            (relative, hash)
+        })
+        .collect();
+
+    let mut failures = Vec::new();
+    let mut hashes = Vec::with_capacity(hashed.len());
+    for (relative, hash) in hashed {
+        match hash {
+            Ok(hash) => hashes.push((relative, hash)),
+            Err(e) => failures.push(e),
+        }
+    }
+
+    let mut pairs: Vec<DuplicatePair> = (0..hashes.len())
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let (a, hash_a) = &hashes[i];
+            hashes[i + 1..].iter().filter_map(move |(b, hash_b)| {
+                let distance = hash_a.dist(hash_b);
+                if distance > threshold {
+                    return None;
+                }
+                let ((split_a, class_a), (split_b, class_b)) = (location(a), location(b));
+                Some(DuplicatePair {
+                    a: a.clone(),
+                    b: b.clone(),
+                    distance,
+                    cross_split: split_a.is_some() && split_a != split_b,
+                    cross_class: class_a != class_b,
+                })
+            })
+        })
+        .collect();
+    pairs.sort_by(|x, y| x.distance.cmp(&y.distance).then(y.cross_split.cmp(&x.cross_split)).then(x.a.cmp(&y.a)));
+    let cross_split_pairs = pairs.iter().filter(|p| p.cross_split).count();
+    let truncated = pairs.len() > MAX_PAIRS;
+    pairs.truncate(MAX_PAIRS);
+
+    Ok(DedupeReport {
+        path: path.to_string_lossy().to_string(),
+        threshold,
+        images: total,
+        pairs,
+        truncated,
+        cross_split_pairs,
+        failures,
+    })
+}
@@ -0,0 +1,166 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::Manager;
+
+use crate::events;
+use crate::paths;
+
+/// Scripts the app invokes directly; an override directory must provide them all.
+pub const BACKEND_SCRIPTS: &[&str] = &[
+    "script.py",
+    "model_factory.py",
+    "check_gpu.py",
+    "system_info.py",
+    "tabular_processor.py",
+    "automl_sweep.py",
+    "batch_probe.py",
+    "predict.py",
+    "export_onnx.py",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct DevSettings {
+    script_dir_override: Option<PathBuf>,
+}
+
+/// Returned by `get_dev_mode` so the UI can show a persistent banner while an
+/// override is active.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DevModeStatus {
+    pub enabled: bool,
+    pub script_dir: Option<String>,
+    /// Bundled scripts the override directory does not provide.
+    pub missing_scripts: Vec<String>,
+    /// Scripts are loaded from outside the signed bundle, so nothing vouches
+    /// for their contents; any future integrity check is skipped too.
+    pub integrity_checks_bypassed: bool,
+    pub warning: Option<String>,
+}
+
+/// Published as `dev://script_changed` when an override script was edited
+/// since the app last launched it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptChanged {
+    pub script: String,
+    pub path: String,
+}
+
+/// Managed state: the developer script directory, persisted to `dev_settings.json`.
+pub struct DevMode {
+    settings: Mutex<DevSettings>,
+    path: PathBuf,
+    /// Modification time of each override script when it was last launched.
+    seen: Mutex<HashMap<PathBuf, SystemTime>>,
+}
+
+impl DevMode {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            settings: Mutex::new(settings),
+            path,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn script_dir(&self) -> Option<PathBuf> {
+        self.settings.lock().unwrap().script_dir_override.clone()
+    }
+
+    pub fn set_script_dir(&self, dir: Option<PathBuf>) -> Result<(), String> {
+        if let Some(dir) = &dir {
+            if !dir.is_absolute() || !dir.is_dir() {
+                return Err(format!("{} is not an existing absolute directory.", dir.display()));
+            }
+        }
+        let mut settings = self.settings.lock().unwrap();
+        settings.script_dir_override = dir;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&*settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| e.to_string())?;
+        self.seen.lock().unwrap().clear();
+        Ok(())
+    }
+
+    pub fn status(&self) -> DevModeStatus {
+        let dir = self.script_dir();
+        let missing_scripts = dir
+            .as_deref()
+            .map(|dir| {
+                BACKEND_SCRIPTS
+                    .iter()
+                    .filter(|s| !dir.join(s).is_file())
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        DevModeStatus {
+            enabled: dir.is_some(),
+            integrity_checks_bypassed: dir.is_some(),
+            warning: dir.as_ref().map(|d| {
+                format!(
+                    "Developer mode: backend scripts are loaded from {} instead of the app bundle. Do not use for production training.",
+                    d.display()
+                )
+            }),
+            script_dir: dir.map(|d| d.to_string_lossy().to_string()),
+            missing_scripts,
+        }
+    }
+
+    /// Records the script's modification time and reports whether it changed
+    /// since the previous launch (the first launch does not count as a change).
+    fn note_launch(&self, script: &Path) -> bool {
+        let Ok(modified) = std::fs::metadata(script).and_then(|m| m.modified()) else {
+            return false;
+        };
+        let previous = self.seen.lock().unwrap().insert(script.to_path_buf(), modified);
+        previous.is_some_and(|p| p != modified)
+    }
+}
+
+pub fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::settings_file(app, "dev_settings.json")?)
+}
+
+/// The override location of `name`, when developer mode is on. Scripts are
+/// re-read by every new Python process, so edits apply on the next launch;
+/// this only checks the file exists and tells the UI it changed.
+pub fn override_script(app: &tauri::AppHandle, name: &str) -> Result<Option<PathBuf>, String> {
+    let Some(dev) = app.try_state::<std::sync::Arc<DevMode>>() else {
+        return Ok(None);
+    };
+    let Some(dir) = dev.script_dir() else {
+        return Ok(None);
+    };
+    let path = dir.join(name);
+    if !path.is_file() {
+        return Err(format!(
+            "Developer script directory {} has no {}. Add it or turn developer mode off.",
+            dir.display(),
+            name
+        ));
+    }
+    if dev.note_launch(&path) {
+        events::publish(
+            app,
+            "dev://script_changed",
+            None,
+            ScriptChanged {
+                script: name.to_string(),
+                path: path.to_string_lossy().to_string(),
+            },
+        );
+    }
+    Ok(Some(path))
+}
@@ -0,0 +1,355 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::events;
+use crate::paths;
+
+/// Attempts per file; each one resumes where the last stopped.
+const MAX_ATTEMPTS: u32 = 5;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// A stalled transfer is retried after this long without data.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const TORCHVISION_URL: &str = "https://download.pytorch.org/models";
+const HF_URL: &str = "https://huggingface.co";
+
+/// Built-in models and the torchvision file their `DEFAULT` weights load
+/// from. The part after the last `-` is the start of the file's SHA-256,
+/// which torch.hub checks too.
+const TORCHVISION_WEIGHTS: &[(&str, &str)] = &[
+    ("resnet18", "resnet18-f37072fd.pth"),
+    ("dcn", "resnet18-f37072fd.pth"),
+    ("resnet50", "resnet50-11ad3fa6.pth"),
+    ("efficientnet_b0", "efficientnet_b0_rwightman-7f5810bc.pth"),
+    ("mobilenet_v3", "mobilenet_v3_large-5c1a4163.pth"),
+    ("vit_b_16", "vit_b_16-c867db91.pth"),
+    ("convnext", "convnext_tiny-983f1562.pth"),
+];
+/// Built-in models loaded through timm from the Hugging Face Hub.
+const HF_WEIGHTS: &[(&str, &str, &str)] =
+    &[("eva02", "timm/eva02_base_patch14_224.mim_in22k_ft_in1k", "model.safetensors")];
+
+/// Published as `download://progress` while a file downloads.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DownloadProgress {
+    /// What is being downloaded, e.g. a model name or repository.
+    pub name: String,
+    pub file: String,
+    pub downloaded: u64,
+    /// Null when the server does not say.
+    pub total: Option<u64>,
+    pub attempt: u32,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DownloadedWeights {
+    pub model_name: String,
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+    /// The file was already in the cache and verified; nothing was downloaded.
+    pub cached: bool,
+}
+
+/// What a downloaded file must hash to.
+#[derive(Debug, Clone)]
+pub enum Checksum {
+    Sha256(String),
+    /// The first hex digits of the SHA-256, as torchvision names its files.
+    Sha256Prefix(String),
+    /// Nothing to verify against; the hash is still reported.
+    Unknown,
+}
+
+impl Checksum {
+    /// Hashes `path` and checks it. Returns the hex digest.
+    fn verify(&self, path: &Path) -> Result<String, String> {
+        let mut file = std::fs::File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0; 1 << 20];
+        loop {
+            let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        let digest = hex::encode(hasher.finalize());
+        let matches = match self {
+            Self::Sha256(expected) => digest.eq_ignore_ascii_case(expected),
+            Self::Sha256Prefix(prefix) => digest.starts_with(&prefix.to_ascii_lowercase()),
+            Self::Unknown => true,
+        };
+        if !matches {
+            return Err(format!("{} is corrupt: its checksum does not match; it was deleted.", path.display()));
+        }
+        Ok(digest)
+    }
+}
+
+enum Failure {
+    /// Network trouble or a server error; worth another attempt.
+    Retry(String),
+    Fatal(String),
+}
+
+/// Managed state: files being downloaded, so two requests for the same file
+/// do not write to it at once.
+#[derive(Default)]
+pub struct Downloads {
+    active: Mutex<HashSet<PathBuf>>,
+}
+
+/// Releases a file's slot when its download ends, however it ends.
+struct Slot<'a> {
+    downloads: &'a Downloads,
+    dest: PathBuf,
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.downloads.active.lock().unwrap().remove(&self.dest);
+    }
+}
+
+impl Downloads {
+    fn claim(&self, dest: &Path) -> Result<Slot<'_>, String> {
+        if !self.active.lock().unwrap().insert(dest.to_path_buf()) {
+            return Err(format!("{} is already being downloaded.", dest.display()));
+        }
+        Ok(Slot { downloads: self, dest: dest.to_path_buf() })
+    }
+}
+
+/// Shared by every profile; jobs see it as `TORCH_HOME` and `HF_HOME`.
+pub fn weights_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::shared_data_dir(app)?.join("weights"))
+}
+
+/// Cache variables for the backend, so torchvision and timm load what
+/// `download_pretrained` fetched instead of downloading again.
+pub fn cache_env(app: &tauri::AppHandle) -> Vec<(String, String)> {
+    let Ok(dir) = weights_dir(app) else {
+        return Vec::new();
+    };
+    vec![
+        ("TORCH_HOME".to_string(), dir.join("torch").to_string_lossy().to_string()),
+        ("HF_HOME".to_string(), dir.join("huggingface").to_string_lossy().to_string()),
+    ]
+}
+
+pub fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `download_pretrained` knows where the model's weights come from.
+pub fn is_known(model_name: &str) -> bool {
+    TORCHVISION_WEIGHTS.iter().any(|(name, _)| *name == model_name)
+        || HF_WEIGHTS.iter().any(|(name, _, _)| *name == model_name)
+}
+
+async fn fetch_once(
+    app: &tauri::AppHandle,
+    request: reqwest::RequestBuilder,
+    part: &Path,
+    progress: &mut DownloadProgress,
+) -> Result<(), Failure> {
+    let offset = std::fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    let request =
+        if offset > 0 { request.header(reqwest::header::RANGE, format!("bytes={}-", offset)) } else { request };
+    let mut response = request.send().await.map_err(|e| Failure::Retry(e.to_string()))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+        // The partial file is already whole; the checksum decides.
+        return Ok(());
+    }
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(Failure::Retry(format!("HTTP {}", status)));
+    }
+    if !status.is_success() {
+        return Err(Failure::Fatal(format!("HTTP {} for {}", status, response.url())));
+    }
+    // A server that ignores the range sends the whole file again.
+    let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(resumed)
+        .write(true)
+        .truncate(!resumed)
+        .open(part)
+        .map_err(|e| Failure::Fatal(format!("Cannot write {}: {}", part.display(), e)))?;
+    let start = if resumed { offset } else { 0 };
+    progress.downloaded = start;
+    progress.total = response.content_length().map(|len| len + start);
+
+    let mut last = Instant::now();
+    while let Some(chunk) = response.chunk().await.map_err(|e| Failure::Retry(e.to_string()))? {
+        file.write_all(&chunk).map_err(|e| Failure::Fatal(format!("Cannot write {}: {}", part.display(), e)))?;
+        progress.downloaded += chunk.len() as u64;
+        if last.elapsed() >= PROGRESS_INTERVAL {
+            last = Instant::now();
+            events::publish(app, "download://progress", None, progress.clone());
+        }
+    }
+    file.flush().map_err(|e| Failure::Fatal(e.to_string()))?;
+    if progress.total.is_some_and(|total| progress.downloaded < total) {
+        return Err(Failure::Retry("The connection closed before the file was complete.".to_string()));
+    }
+    events::publish(app, "download://progress", None, progress.clone());
+    Ok(())
+}
+
+/// Downloads `url` to `dest` through `dest.part`, resuming it after dropped
+/// connections and across app restarts, then checks it against `checksum`.
+/// `build` makes each attempt's request, e.g. to add an auth header. Returns
+/// the file's SHA-256.
+pub async fn fetch(
+    app: &tauri::AppHandle,
+    downloads: &Downloads,
+    name: &str,
+    build: impl Fn() -> reqwest::RequestBuilder,
+    dest: &Path,
+    checksum: &Checksum,
+) -> Result<String, String> {
+    let _slot = downloads.claim(dest)?;
+    let file_name = dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    let part = dest.with_file_name(format!("{}.part", file_name));
+    let mut progress =
+        DownloadProgress { name: name.to_string(), file: file_name.clone(), downloaded: 0, total: None, attempt: 0 };
+    let mut delay = RETRY_BASE_DELAY;
+    loop {
+        progress.attempt += 1;
+        match fetch_once(app, build(), &part, &mut progress).await {
+            Ok(()) => break,
+            Err(Failure::Fatal(e)) => return Err(format!("Cannot download {}: {}", file_name, e)),
+            Err(Failure::Retry(e)) if progress.attempt < MAX_ATTEMPTS => {
+                tracing::warn!(file = %file_name, attempt = progress.attempt, "Download interrupted, retrying: {}", e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(Failure::Retry(e)) => {
+                return Err(format!("Cannot download {} after {} attempts: {}", file_name, MAX_ATTEMPTS, e));
+            }
+        }
+    }
+    let digest = match checksum.verify(&part) {
+        Ok(digest) => digest,
+        Err(e) => {
+            let _ = std::fs::remove_file(&part);
+            return Err(e);
+        }
+    };
+    std::fs::rename(&part, dest).map_err(|e| format!("Cannot move {} into place: {}", dest.display(), e))?;
+    tracing::info!(file = %file_name, "Download complete");
+    Ok(digest)
+}
+
+/// Where a Hugging Face file lives in the hub cache layout
+/// (`hub/models--<org>--<name>/snapshots/<commit>/<file>`), which
+/// huggingface_hub reads under `HF_HOME`.
+fn hf_cache_repo(weights: &Path, repo_id: &str) -> PathBuf {
+    weights.join("huggingface").join("hub").join(format!("models--{}", repo_id.replace('/', "--")))
+}
+
+/// The commit `main` points to and the SHA-256 of `file` there, from the
+/// headers of the hub's resolve redirect.
+async fn hf_file_info(repo_id: &str, file: &str) -> Result<(String, Checksum), String> {
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())?;
+    let url = format!("{}/{}/resolve/main/{}", HF_URL, repo_id, file);
+    let response = client.head(&url).send().await.map_err(|e| format!("Cannot reach the Hugging Face Hub: {}", e))?;
+    let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.trim_matches('"'));
+    let commit =
+        header("x-repo-commit").ok_or_else(|| format!("The Hugging Face Hub has no {} in {}.", file, repo_id))?;
+    // Large files are stored with LFS, whose ETag is the content's SHA-256.
+    let checksum = match header("x-linked-etag").or_else(|| header("etag")) {
+        Some(etag) if etag.len() == 64 && etag.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Checksum::Sha256(etag.to_string())
+        }
+        _ => Checksum::Unknown,
+    };
+    Ok((commit.to_string(), checksum))
+}
+
+/// Makes sure the pretrained weights of a built-in model are in the shared
+/// cache, downloading them with progress events if not.
+pub async fn download_pretrained(
+    app: &tauri::AppHandle,
+    downloads: &Downloads,
+    model_name: &str,
+) -> Result<DownloadedWeights, String> {
+    let weights = weights_dir(app)?;
+    let finish = |dest: PathBuf, sha256: String, cached: bool| {
+        let bytes = std::fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+        DownloadedWeights {
+            model_name: model_name.to_string(),
+            path: dest.to_string_lossy().to_string(),
+            bytes,
+            sha256,
+            cached,
+        }
+    };
+
+    if let Some((_, file)) = TORCHVISION_WEIGHTS.iter().find(|(name, _)| *name == model_name) {
+        let dest = weights.join("torch").join("hub").join("checkpoints").join(file);
+        let prefix = file.rsplit_once('-').and_then(|(_, rest)| rest.split('.').next()).unwrap_or_default();
+        let checksum = Checksum::Sha256Prefix(prefix.to_string());
+        if dest.is_file() {
+            match checksum.verify(&dest) {
+                Ok(digest) => return Ok(finish(dest, digest, true)),
+                Err(e) => {
+                    tracing::warn!("{}", e);
+                    let _ = std::fs::remove_file(&dest);
+                }
+            }
+        }
+        let client = client()?;
+        let url = format!("{}/{}", TORCHVISION_URL, file);
+        let digest = fetch(app, downloads, model_name, || client.get(&url), &dest, &checksum).await?;
+        return Ok(finish(dest, digest, false));
+    }
+
+    if let Some((_, repo_id, file)) = HF_WEIGHTS.iter().find(|(name, _, _)| *name == model_name) {
+        let repo = hf_cache_repo(&weights, repo_id);
+        let main_ref = repo.join("refs").join("main");
+        // A cached snapshot is used without asking the hub, so training works offline.
+        if let Some(commit) = std::fs::read_to_string(&main_ref).ok().map(|c| c.trim().to_string()) {
+            let dest = repo.join("snapshots").join(&commit).join(file);
+            if dest.is_file() {
+                let digest = Checksum::Unknown.verify(&dest)?;
+                return Ok(finish(dest, digest, true));
+            }
+        }
+        let (commit, checksum) = hf_file_info(repo_id, file).await?;
+        let dest = repo.join("snapshots").join(&commit).join(file);
+        let client = client()?;
+        let url = format!("{}/{}/resolve/{}/{}", HF_URL, repo_id, commit, file);
+        let digest = fetch(app, downloads, model_name, || client.get(&url), &dest, &checksum).await?;
+        if let Some(dir) = main_ref.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&main_ref, &commit).map_err(|e| format!("Cannot write {}: {}", main_ref.display(), e))?;
+        return Ok(finish(dest, digest, false));
+    }
+
+    let known: Vec<&str> =
+        TORCHVISION_WEIGHTS.iter().map(|(n, _)| *n).chain(HF_WEIGHTS.iter().map(|(n, _, _)| *n)).collect();
+    Err(format!("No pretrained weights are known for {}; choose one of {}.", model_name, known.join(", ")))
+}
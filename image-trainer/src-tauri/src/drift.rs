@@ -0,0 +1,231 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::audit::AuditLog;
+use crate::events;
+use crate::paths;
+use crate::run_manifest;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DriftSettings {
+    /// Number of most recent logged predictions the statistics cover.
+    pub window: usize,
+    /// Below this many predictions no drift is reported.
+    pub min_samples: usize,
+    /// Re-check a model after this many new logged predictions.
+    pub check_every: usize,
+    /// Jensen-Shannon divergence (base 2, 0..1) between the training and the
+    /// observed class distribution above which drift is flagged.
+    pub max_divergence: f64,
+    /// Flag drift when mean confidence falls this far below the test-set mean.
+    pub max_confidence_drop: f64,
+}
+
+impl Default for DriftSettings {
+    fn default() -> Self {
+        Self {
+            window: 500,
+            min_samples: 100,
+            check_every: 50,
+            max_divergence: 0.1,
+            max_confidence_drop: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ClassShare {
+    pub label: String,
+    /// Share of the class in the training split.
+    pub training: f64,
+    /// Share of predictions with this label in the window.
+    pub observed: f64,
+}
+
+/// Rolling statistics of a model's logged predictions against its training
+/// run. Published as `monitor://drift` when `drifted` is set.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DriftReport {
+    pub model_id: String,
+    pub samples: usize,
+    /// Unix time range of the window.
+    pub window_start: Option<u64>,
+    pub window_end: Option<u64>,
+    pub classes: Vec<ClassShare>,
+    pub divergence: Option<f64>,
+    pub mean_confidence: Option<f64>,
+    /// Mean top-class confidence on the run's test set.
+    pub reference_confidence: Option<f64>,
+    pub drifted: bool,
+    pub reasons: Vec<String>,
+}
+
+/// Managed state: thresholds (persisted to `drift_settings.json`) and the
+/// number of predictions logged per model since its last check.
+pub struct DriftMonitor {
+    settings: Mutex<DriftSettings>,
+    path: PathBuf,
+    pending: Mutex<HashMap<String, usize>>,
+}
+
+impl DriftMonitor {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let path = paths::settings_file(app, "drift_settings.json")?;
+        let settings = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            settings: Mutex::new(settings),
+            path,
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn settings(&self) -> DriftSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: DriftSettings) -> Result<(), String> {
+        if settings.window == 0 || settings.check_every == 0 {
+            return Err("Window and check interval must be at least 1.".to_string());
+        }
+        if !(0.0..=1.0).contains(&settings.max_divergence) || !(0.0..=1.0).contains(&settings.max_confidence_drop) {
+            return Err("Thresholds must be between 0 and 1.".to_string());
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Counts a newly logged prediction and reports whether the model is due
+    /// for a check.
+    fn due(&self, model_id: &str) -> bool {
+        let every = self.settings().check_every;
+        let mut pending = self.pending.lock().unwrap();
+        let count = pending.entry(model_id.to_string()).or_insert(0);
+        *count += 1;
+        if *count >= every {
+            *count = 0;
+            return true;
+        }
+        false
+    }
+}
+
+/// Class counts of the training split and the test-set mean confidence,
+/// as recorded in the run manifest.
+fn reference(model_id: &str) -> Result<(BTreeMap<String, f64>, Option<f64>), String> {
+    let manifest = run_manifest::load(Path::new(model_id))?;
+    let counts: BTreeMap<String, f64> = serde_json::from_value(manifest["dataset"]["class_counts"].clone())
+        .map_err(|_| "The training run did not record its class distribution; retrain to enable drift monitoring.")?;
+    Ok((counts, manifest["evaluation"]["confidence"]["mean"].as_f64()))
+}
+
+fn normalize(counts: &BTreeMap<String, f64>) -> BTreeMap<String, f64> {
+    let total: f64 = counts.values().sum();
+    counts
+        .iter()
+        .map(|(k, v)| (k.clone(), if total > 0.0 { v / total } else { 0.0 }))
+        .collect()
+}
+
+/// Jensen-Shannon divergence in bits; 0 for identical distributions, 1 for disjoint ones.
+fn js_divergence(p: &[f64], q: &[f64]) -> f64 {
+    let kl = |a: &[f64], m: &[f64]| -> f64 {
+        a.iter()
+            .zip(m)
+            .filter(|(x, _)| **x > 0.0)
+            .map(|(x, y)| x * (x / y).log2())
+            .sum()
+    };
+    let m: Vec<f64> = p.iter().zip(q).map(|(a, b)| (a + b) / 2.0).collect();
+    0.5 * kl(p, &m) + 0.5 * kl(q, &m)
+}
+
+pub fn report(audit: &AuditLog, model_id: &str, settings: &DriftSettings) -> Result<DriftReport, String> {
+    let (training_counts, reference_confidence) = reference(model_id)?;
+    let entries = audit.recent(model_id, settings.window)?;
+
+    let mut observed_counts: BTreeMap<String, f64> = training_counts.keys().map(|k| (k.clone(), 0.0)).collect();
+    for entry in &entries {
+        *observed_counts.entry(entry.label.clone()).or_insert(0.0) += 1.0;
+    }
+    let training = normalize(&training_counts);
+    let observed = normalize(&observed_counts);
+    let classes: Vec<ClassShare> = observed
+        .iter()
+        .map(|(label, share)| ClassShare {
+            label: label.clone(),
+            training: training.get(label).copied().unwrap_or(0.0),
+            observed: *share,
+        })
+        .collect();
+
+    let samples = entries.len();
+    let mean_confidence = (samples > 0).then(|| entries.iter().map(|e| e.confidence).sum::<f64>() / samples as f64);
+    let divergence = (samples > 0).then(|| {
+        let p: Vec<f64> = classes.iter().map(|c| c.training).collect();
+        let q: Vec<f64> = classes.iter().map(|c| c.observed).collect();
+        js_divergence(&p, &q)
+    });
+
+    let mut reasons = Vec::new();
+    if samples >= settings.min_samples {
+        if let Some(d) = divergence.filter(|d| *d > settings.max_divergence) {
+            reasons.push(format!(
+                "Class distribution diverges from training (JS divergence {:.3} > {:.3}).",
+                d, settings.max_divergence
+            ));
+        }
+        if let (Some(mean), Some(reference)) = (mean_confidence, reference_confidence) {
+            if reference - mean > settings.max_confidence_drop {
+                reasons.push(format!(
+                    "Mean confidence {:.3} is {:.3} below the test-set mean {:.3}.",
+                    mean,
+                    reference - mean,
+                    reference
+                ));
+            }
+        }
+    }
+
+    Ok(DriftReport {
+        model_id: model_id.to_string(),
+        samples,
+        window_start: entries.first().map(|e| e.timestamp),
+        window_end: entries.last().map(|e| e.timestamp),
+        classes,
+        divergence,
+        mean_confidence,
+        reference_confidence,
+        drifted: !reasons.is_empty(),
+        reasons,
+    })
+}
+
+/// Called for every audited prediction. Every `check_every` predictions of a
+/// model, recomputes its statistics and publishes `monitor://drift` if they
+/// cross a threshold.
+pub fn note_prediction(app: &tauri::AppHandle, audit: &AuditLog, model_id: &str) {
+    let Some(monitor) = app.try_state::<Arc<DriftMonitor>>() else {
+        return;
+    };
+    if !monitor.due(model_id) {
+        return;
+    }
+    match report(audit, model_id, &monitor.settings()) {
+        Ok(report) if report.drifted => events::publish(app, "monitor://drift", None, report),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Drift check for {} skipped: {}", model_id, e),
+    }
+}
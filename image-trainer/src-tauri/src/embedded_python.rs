@@ -0,0 +1,162 @@
+use serde::Deserialize;
+use std::sync::Mutex;
+
+use crate::error::BackendError;
+use crate::python::run_python;
+
+/// Prints what the embedded interpreter needs to see the same packages as the
+/// configured one.
+const PROBE_SCRIPT: &str =
+    "import sys, json; print(json.dumps({'version': '%d.%d' % sys.version_info[:2], 'executable': sys.executable, 'path': sys.path}))";
+
+#[cfg_attr(not(feature = "embedded-python"), allow(dead_code))]
+#[derive(Debug, Clone, Deserialize)]
+struct InterpreterInfo {
+    version: String,
+    executable: String,
+    path: Vec<String>,
+}
+
+/// Outcome of setting up the embedded interpreter, decided once per session.
+static READY: Mutex<Option<Result<(), String>>> = Mutex::new(None);
+
+async fn ensure_ready(app: &tauri::AppHandle) -> Result<(), String> {
+    if let Some(ready) = READY.lock().unwrap().clone() {
+        return ready;
+    }
+    let ready = match run_python(app, &["-c", PROBE_SCRIPT]).await {
+        Ok(output) => match serde_json::from_str::<InterpreterInfo>(output.trim()) {
+            Ok(info) => tauri::async_runtime::spawn_blocking(move || runtime::init(&info))
+                .await
+                .unwrap_or_else(|e| Err(e.to_string())),
+            Err(e) => Err(format!("Unexpected interpreter probe output: {}", e)),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = &ready {
+        tracing::warn!("Embedded Python unavailable, using subprocesses: {}", e);
+    }
+    *READY.lock().unwrap() = Some(ready.clone());
+    ready
+}
+
+/// Whether quick scripts can run in the interpreter embedded in the app:
+/// this build has one and it can stand in for the configured interpreter.
+/// Set up on first use, then kept loaded for the session.
+pub async fn available(app: &tauri::AppHandle) -> bool {
+    cfg!(feature = "embedded-python") && ensure_ready(app).await.is_ok()
+}
+
+/// Makes the next call set up again, after the interpreter choice changed.
+/// The embedded interpreter itself stays loaded; only its paths are redone.
+pub fn reset() {
+    *READY.lock().unwrap() = None;
+}
+
+/// Runs `script.py args…` or `-c code` in the embedded interpreter. Modules
+/// stay imported between calls, so imports like pandas are paid once.
+pub async fn run(args: &[&str]) -> Result<String, BackendError> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    tauri::async_runtime::spawn_blocking(move || runtime::run(&args))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string().into()))
+}
+
+#[cfg(feature = "embedded-python")]
+mod runtime {
+    use super::InterpreterInfo;
+    use crate::error::BackendError;
+    use pyo3::prelude::*;
+    use pyo3::sync::GILOnceCell;
+    use pyo3::types::PyList;
+    use std::sync::Mutex;
+
+    /// Runs one call with argv, stdout and stderr swapped in, the way the
+    /// script would see them in its own process.
+    const RUNNER: &str = r#"
+import contextlib, io, runpy, sys, traceback
+
+def run(argv):
+    out, err = io.StringIO(), io.StringIO()
+    saved = sys.argv
+    code = 0
+    try:
+        with contextlib.redirect_stdout(out), contextlib.redirect_stderr(err):
+            try:
+                if argv[0] == '-c':
+                    sys.argv = ['-c'] + argv[2:]
+                    exec(compile(argv[1], '<string>', 'exec'), {'__name__': '__main__'})
+                else:
+                    sys.argv = argv
+                    runpy.run_path(argv[0], run_name='__main__')
+            except SystemExit as e:
+                code = e.code if isinstance(e.code, int) else (0 if e.code is None else 1)
+            except BaseException:
+                traceback.print_exc()
+                code = 1
+    finally:
+        sys.argv = saved
+    return code, out.getvalue(), err.getvalue()
+"#;
+
+    static RUN: GILOnceCell<Py<PyAny>> = GILOnceCell::new();
+    /// Redirecting stdout is process-wide, so calls run one at a time.
+    static CALL: Mutex<()> = Mutex::new(());
+
+    /// Points the embedded interpreter at the configured interpreter's
+    /// packages. Refused when the Python versions differ, since compiled
+    /// extensions would not load.
+    pub fn init(info: &InterpreterInfo) -> Result<(), String> {
+        Python::with_gil(|py| {
+            let sys = py.import_bound("sys")?;
+            let version = sys.getattr("version_info")?;
+            let embedded = format!(
+                "{}.{}",
+                version.getattr("major")?.extract::<u8>()?,
+                version.getattr("minor")?.extract::<u8>()?
+            );
+            if embedded != info.version {
+                return Ok(Err(format!(
+                    "the app embeds Python {} but the configured interpreter is {}",
+                    embedded, info.version
+                )));
+            }
+            sys.setattr("path", PyList::new_bound(py, &info.path))?;
+            sys.setattr("executable", &info.executable)?;
+            Ok(Ok(()))
+        })
+        .unwrap_or_else(|e: PyErr| Err(e.to_string()))
+    }
+
+    pub fn run(args: &[String]) -> Result<String, BackendError> {
+        let _guard = CALL.lock().unwrap();
+        let (code, stdout, stderr) = Python::with_gil(|py| {
+            let run = RUN.get_or_try_init(py, || {
+                PyModule::from_code_bound(py, RUNNER, "epoq_runner.py", "epoq_runner")?
+                    .getattr("run")
+                    .map(|f| f.unbind())
+            })?;
+            run.call1(py, (args.to_vec(),))?.extract::<(i32, String, String)>(py)
+        })
+        .map_err(|e| BackendError::from(e.to_string()))?;
+        if code == 0 {
+            Ok(stdout)
+        } else {
+            Err(BackendError::script_failed(Some(code), stderr, &stdout))
+        }
+    }
+}
+
+#[cfg(not(feature = "embedded-python"))]
+mod runtime {
+    use super::InterpreterInfo;
+    use crate::error::BackendError;
+
+    pub fn init(_info: &InterpreterInfo) -> Result<(), String> {
+        Err("This build was compiled without the embedded-python feature.".to_string())
+    }
+
+    pub fn run(_args: &[String]) -> Result<String, BackendError> {
+        Err("This build was compiled without the embedded-python feature.".into())
+    }
+}
@@ -0,0 +1,174 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt;
+
+use crate::i18n::Localizer;
+use crate::jobs::ResourceLimit;
+use crate::python::LaunchAttempt;
+
+/// Error returned by every command, so the frontend can tell a missing Python
+/// from a crashed script or a bad path. Serialized as an object with a `kind`
+/// tag and a `message` to show; some kinds add details. Before API version 3
+/// errors were plain strings; `api::negotiate` refuses clients that old, since
+/// nothing flattens the object back for them.
+///
+/// Errors are classified where they arise: the Python runner returns
+/// `PythonNotFound` and `ScriptFailed`, the path helpers `Io`, and config and
+/// settings validators `InvalidInput`. Code that still reports failures as
+/// `String` converts to `Other` through `?`, so a typed error only keeps its
+/// kind when every function it passes through returns `BackendError`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackendError {
+    /// No Python interpreter could be started.
+    PythonNotFound {
+        message: String,
+        /// Every interpreter tried and how it failed, when the search got that far.
+        attempts: Vec<LaunchAttempt>,
+    },
+    /// A script bundled with the app is missing from the install.
+    ScriptNotFound { message: String, path: String },
+    /// A script ran and exited with an error.
+    ScriptFailed {
+        message: String,
+        /// Exit code, if the script exited rather than being killed; also
+        /// `None` when the script exited normally but reported an error itself.
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// The request was rejected before anything ran.
+    InvalidInput { message: String },
+    /// A job, model or other named item does not exist.
+    NotFound { message: String },
+    /// A REST request without valid credentials.
+    Unauthorized { message: String },
+    /// A REST request from a client that is not allowed.
+    Forbidden { message: String },
+    /// Reading or writing a file failed.
+    Io { message: String },
+    /// A job was killed for running longer or using more memory than its limits allow.
+    ResourceLimitExceeded { message: String, limit: ResourceLimit },
+    /// Any other failure.
+    Other { message: String },
+}
+
+impl BackendError {
+    pub fn python_not_found(message: impl Into<String>) -> Self {
+        Self::PythonNotFound { message: message.into(), attempts: Vec::new() }
+    }
+
+    pub fn with_attempts(mut self, tried: Vec<LaunchAttempt>) -> Self {
+        if let Self::PythonNotFound { attempts, .. } = &mut self {
+            *attempts = tried;
+        }
+        self
+    }
+
+    pub fn script_not_found(path: impl Into<String>, l10n: &Localizer) -> Self {
+        let path = path.into();
+        Self::ScriptNotFound { message: l10n.text("error-script-missing", &[("path", path.clone().into())]), path }
+    }
+
+    /// The message is stderr, or stdout when the script wrote its error
+    /// there, or the exit code when it printed nothing.
+    pub fn script_failed(code: Option<i32>, stderr: impl Into<String>, stdout: &str) -> Self {
+        let stderr = stderr.into();
+        let message = if !stderr.trim().is_empty() {
+            stderr.clone()
+        } else if !stdout.trim().is_empty() {
+            stdout.to_string()
+        } else {
+            format!("Exited with code: {}", code.unwrap_or(-1))
+        };
+        Self::ScriptFailed { message, code, stderr }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::InvalidInput { message: message.into() }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound { message: message.into() }
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::Unauthorized { message: message.into() }
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::Forbidden { message: message.into() }
+    }
+
+    pub fn io(message: impl fmt::Display) -> Self {
+        Self::Io { message: message.to_string() }
+    }
+
+    pub fn resource_limit(limit: ResourceLimit) -> Self {
+        Self::ResourceLimitExceeded { message: limit.message(), limit }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::PythonNotFound { message, .. }
+            | Self::ScriptNotFound { message, .. }
+            | Self::ScriptFailed { message, .. }
+            | Self::InvalidInput { message }
+            | Self::NotFound { message }
+            | Self::Unauthorized { message }
+            | Self::Forbidden { message }
+            | Self::Io { message }
+            | Self::ResourceLimitExceeded { message, .. }
+            | Self::Other { message } => message,
+        }
+    }
+
+    /// Prefixes the message, e.g. with what was being attempted, keeping the kind.
+    pub fn context(mut self, prefix: &str) -> Self {
+        match &mut self {
+            Self::PythonNotFound { message, .. }
+            | Self::ScriptNotFound { message, .. }
+            | Self::ScriptFailed { message, .. }
+            | Self::InvalidInput { message }
+            | Self::NotFound { message }
+            | Self::Unauthorized { message }
+            | Self::Forbidden { message }
+            | Self::Io { message }
+            | Self::ResourceLimitExceeded { message, .. }
+            | Self::Other { message } => *message = format!("{}: {}", prefix, message),
+        }
+        self
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+impl From<String> for BackendError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+impl From<&str> for BackendError {
+    fn from(message: &str) -> Self {
+        Self::Other { message: message.to_string() }
+    }
+}
+
+impl From<std::io::Error> for BackendError {
+    fn from(e: std::io::Error) -> Self {
+        Self::io(e)
+    }
+}
+
+/// Lets functions that still return `Result<_, String>` call typed ones with `?`.
+impl From<BackendError> for String {
+    fn from(e: BackendError) -> Self {
+        e.to_string()
+    }
+}
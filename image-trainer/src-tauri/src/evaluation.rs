@@ -0,0 +1,197 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::classes;
+use crate::dataset;
+use crate::gallery::Misclassification;
+use crate::jobs::{new_id, unix_now};
+use crate::paths;
+use crate::predictions::{parse_predict_line, PredictLine};
+use crate::preprocessing;
+use crate::python::{backend_script, run_python};
+use crate::recents::{self, RecentKind};
+
+/// Written to the model's run directory; the latest evaluation replaces it.
+pub const REPORT_FILE: &str = "evaluation_report.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClassMetrics {
+    pub label: String,
+    /// Test images of this class.
+    pub support: usize,
+    /// Null when the model never predicted the class.
+    pub precision: Option<f64>,
+    /// Null when the test folder has no images of the class.
+    pub recall: Option<f64>,
+    pub f1: Option<f64>,
+}
+
+/// How a model does on a labelled test folder, stored as
+/// `evaluation_report.json` with the model.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EvaluationReport {
+    pub model_id: String,
+    pub test_dir: String,
+    pub evaluated_at: u64,
+    /// Model classes in output order; rows and columns of the matrix.
+    pub classes: Vec<String>,
+    /// `confusion_matrix[actual][predicted]` image counts.
+    pub confusion_matrix: Vec<Vec<usize>>,
+    pub per_class: Vec<ClassMetrics>,
+    pub accuracy: f64,
+    /// Unweighted mean over classes with a defined F1.
+    pub macro_f1: f64,
+    /// Mean F1 weighted by support.
+    pub weighted_f1: f64,
+    pub evaluated: usize,
+    /// Wrong predictions, most confident first.
+    pub misclassified: Vec<Misclassification>,
+    /// Subfolders of the test folder that are not classes of the model.
+    pub skipped_folders: Vec<String>,
+    /// Images that could not be scored, as `path: reason`.
+    pub failures: Vec<String>,
+}
+
+/// Test images as `(path, class index)`, from one subfolder per class named
+/// like the model's classes.
+fn labelled_images(test_dir: &Path, classes: &[String]) -> Result<(Vec<(String, usize)>, Vec<String>), String> {
+    let entries = std::fs::read_dir(test_dir)
+        .map_err(|e| format!("Cannot read the test folder {}: {}", test_dir.display(), e))?;
+    let mut folders: Vec<_> = entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    folders.sort();
+    let mut images = Vec::new();
+    let mut skipped = Vec::new();
+    for folder in folders {
+        let name = folder.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let Some(index) = classes.iter().position(|c| *c == name) else {
+            skipped.push(name);
+            continue;
+        };
+        for image in dataset::list_images(&folder, true)? {
+            images.push((image.to_string_lossy().to_string(), index));
+        }
+    }
+    if images.is_empty() {
+        return Err(format!(
+            "{} has no images in folders named after the model's classes ({}).",
+            test_dir.display(),
+            classes.join(", ")
+        ));
+    }
+    Ok((images, skipped))
+}
+
+fn ratio(numerator: usize, denominator: usize) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+fn class_metrics(classes: &[String], matrix: &[Vec<usize>]) -> Vec<ClassMetrics> {
+    (0..classes.len())
+        .map(|i| {
+            let correct = matrix[i][i];
+            let support: usize = matrix[i].iter().sum();
+            let predicted: usize = matrix.iter().map(|row| row[i]).sum();
+            let precision = ratio(correct, predicted);
+            let recall = ratio(correct, support);
+            let f1 = match (precision, recall) {
+                (Some(p), Some(r)) if p + r > 0.0 => Some(2.0 * p * r / (p + r)),
+                (Some(_), Some(_)) => Some(0.0),
+                _ => None,
+            };
+            ClassMetrics { label: classes[i].clone(), support, precision, recall, f1 }
+        })
+        .collect()
+}
+
+/// Classifies every image in `test_dir` with the model in `model_id` and
+/// compares the predictions with the folder each image is in. The report is
+/// saved with the model and returned.
+pub async fn evaluate(app: &tauri::AppHandle, model_id: &str, test_dir: &str) -> Result<EvaluationReport, String> {
+    let model_dir = Path::new(model_id);
+    preprocessing::load(model_dir)?;
+    let classes: Vec<String> = classes::model_classes(model_dir)?.into_iter().map(|e| e.name).collect();
+    let (images, skipped_folders) = labelled_images(Path::new(test_dir), &classes)?;
+    let actual: BTreeMap<&str, usize> = images.iter().map(|(path, index)| (path.as_str(), *index)).collect();
+
+    let script = backend_script(app, "predict.py")?;
+    let images_file = paths::cache_file(app, &format!("{}_images.json", new_id("evaluate")))?;
+    let paths: Vec<&str> = images.iter().map(|(path, _)| path.as_str()).collect();
+    let images_json = serde_json::to_string(&paths).map_err(|e| e.to_string())?;
+    std::fs::write(&images_file, images_json).map_err(|e| format!("Cannot write image list: {}", e))?;
+    let images_arg = images_file.to_string_lossy().to_string();
+    let output = run_python(
+        app,
+        &[script.as_str(), "--model_dir", model_id, "--images_file", images_arg.as_str(), "--top_k", "1"],
+    )
+    .await;
+    let _ = std::fs::remove_file(&images_file);
+    let stdout = output?;
+
+    let mut matrix = vec![vec![0; classes.len()]; classes.len()];
+    let mut misclassified = Vec::new();
+    let mut failures = Vec::new();
+    for line in stdout.lines() {
+        match parse_predict_line(line) {
+            Some(PredictLine::Error(message)) => return Err(message),
+            Some(PredictLine::Failure { file, message }) => failures.push(format!("{}: {}", file, message)),
+            Some(PredictLine::Record(record)) => {
+                let (Some(&truth), Some(predicted)) =
+                    (actual.get(record.file.as_str()), classes.iter().position(|c| *c == record.label))
+                else {
+                    continue;
+                };
+                matrix[truth][predicted] += 1;
+                if truth != predicted {
+                    misclassified.push(Misclassification {
+                        file: record.file,
+                        actual: classes[truth].clone(),
+                        predicted: record.label,
+                        confidence: record.confidence,
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+    let evaluated: usize = matrix.iter().flatten().sum();
+    if evaluated == 0 {
+        return Err(format!("None of the {} test images could be scored.", images.len()));
+    }
+    misclassified.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let per_class = class_metrics(&classes, &matrix);
+    let correct: usize = (0..classes.len()).map(|i| matrix[i][i]).sum();
+    let defined: Vec<f64> = per_class.iter().filter_map(|c| c.f1).collect();
+    let macro_f1 = if defined.is_empty() { 0.0 } else { defined.iter().sum::<f64>() / defined.len() as f64 };
+    let weighted_f1 = per_class.iter().map(|c| c.f1.unwrap_or(0.0) * c.support as f64).sum::<f64>() / evaluated as f64;
+    let report = EvaluationReport {
+        model_id: model_id.to_string(),
+        test_dir: test_dir.to_string(),
+        evaluated_at: unix_now(),
+        classes,
+        confusion_matrix: matrix,
+        per_class,
+        accuracy: correct as f64 / evaluated as f64,
+        macro_f1,
+        weighted_f1,
+        evaluated,
+        misclassified,
+        skipped_folders,
+        failures,
+    };
+    let path = model_dir.join(REPORT_FILE);
+    let raw = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    if let Err(e) = recents::add(app, RecentKind::Model, model_id) {
+        tracing::warn!("Cannot record the model in recents: {}", e);
+    }
+    Ok(report)
+}
+
+/// The last report saved with a model, if it was evaluated.
+pub fn load(model_dir: &Path) -> Option<EvaluationReport> {
+    let raw = std::fs::read_to_string(model_dir.join(REPORT_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
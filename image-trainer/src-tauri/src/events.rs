@@ -0,0 +1,140 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::jobs::unix_now;
+
+/// Envelopes kept for windows that attach after events were emitted.
+const REPLAY_CAPACITY: usize = 1000;
+/// Envelopes waiting for the dispatcher before lossy topics start being shed.
+const QUEUE_CAPACITY: usize = 2000;
+/// Hard limit on the queue: past it even lifecycle envelopes are shed, oldest
+/// first. They stay in the replay buffer for a while.
+const MAX_QUEUE: usize = 4 * QUEUE_CAPACITY;
+/// How often queued envelopes are flushed to the webview.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Every event the backend sends to the frontend. Emitted under `topic` as the
+/// Tauri event name, so listeners subscribe per topic and still get `seq`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Envelope {
+    pub topic: String,
+    pub job_id: Option<String>,
+    /// Monotonic across all topics; gaps mean envelopes were shed under load.
+    pub seq: u64,
+    pub timestamp: u64,
+    pub payload: serde_json::Value,
+}
+
+/// Emitted as `bus://dropped` after envelopes had to be shed.
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedReport {
+    pub dropped: BTreeMap<String, u64>,
+}
+
+/// High-volume or periodic topics where a newer envelope supersedes older
+/// ones. Everything else (job finished, sweep complete, schedule events) is
+/// only dropped when the queue reaches `MAX_QUEUE`.
+fn is_lossy(topic: &str) -> bool {
+    matches!(
+        topic,
+        "job://output"
+            | "automl://progress"
+            | "python://stdout"
+            | "python://stderr"
+            | "gpu://stats"
+            | "system://resources"
+            | "download://progress"
+    )
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    seq: AtomicU64,
+    recent: Mutex<VecDeque<Envelope>>,
+    queue: Mutex<VecDeque<Envelope>>,
+    dropped: Mutex<BTreeMap<String, u64>>,
+}
+
+impl EventBus {
+    pub fn publish<T: Serialize>(&self, topic: &str, job_id: Option<&str>, payload: T) {
+        let envelope = Envelope {
+            topic: topic.to_string(),
+            job_id: job_id.map(str::to_string),
+            seq: self.seq.fetch_add(1, Ordering::Relaxed) + 1,
+            timestamp: unix_now(),
+            payload: serde_json::to_value(payload).unwrap_or(serde_json::Value::Null),
+        };
+
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if recent.len() == REPLAY_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(envelope.clone());
+        }
+
+        let mut queue = self.queue.lock().unwrap();
+        if queue.len() >= QUEUE_CAPACITY {
+            // Shed the oldest lossy envelope; if there is none the queue may
+            // exceed capacity, up to `MAX_QUEUE`, rather than lose a lifecycle event.
+            if let Some(pos) = queue.iter().position(|e| is_lossy(&e.topic)) {
+                let shed = queue.remove(pos).expect("position is in range");
+                *self.dropped.lock().unwrap().entry(shed.topic).or_default() += 1;
+            } else if is_lossy(topic) {
+                *self.dropped.lock().unwrap().entry(envelope.topic).or_default() += 1;
+                return;
+            } else if queue.len() >= MAX_QUEUE {
+                let shed = queue.pop_front().expect("queue is full");
+                *self.dropped.lock().unwrap().entry(shed.topic).or_default() += 1;
+            }
+        }
+        queue.push_back(envelope);
+    }
+
+    /// Envelopes newer than `since_seq`, optionally limited to some topics or one job.
+    pub fn replay(&self, since_seq: u64, topics: Option<&[String]>, job_id: Option<&str>) -> Vec<Envelope> {
+        self.recent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.seq > since_seq)
+            .filter(|e| topics.is_none_or(|t| t.contains(&e.topic)))
+            .filter(|e| job_id.is_none_or(|id| e.job_id.as_deref() == Some(id)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Publishes through the managed bus. Safe to call before the bus is managed
+/// (e.g. during setup); such events are simply not delivered.
+pub fn publish<T: Serialize>(app: &tauri::AppHandle, topic: &str, job_id: Option<&str>, payload: T) {
+    if let Some(bus) = app.try_state::<Arc<EventBus>>() {
+        bus.publish(topic, job_id, payload);
+    }
+}
+
+/// Flushes queued envelopes to the webview on a fixed cadence. Each envelope is
+/// still emitted on its own under its topic; the queue only decouples
+/// publishers from the webview and lets lossy topics be shed under load.
+pub fn spawn_dispatcher(app: tauri::AppHandle, bus: Arc<EventBus>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+
+            let batch: Vec<Envelope> = bus.queue.lock().unwrap().drain(..).collect();
+            for envelope in batch {
+                let _ = app.emit(&envelope.topic, &envelope);
+            }
+
+            let dropped = std::mem::take(&mut *bus.dropped.lock().unwrap());
+            if !dropped.is_empty() {
+                let _ = app.emit("bus://dropped", DroppedReport { dropped });
+            }
+        }
+    });
+}
@@ -0,0 +1,96 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::html;
+use crate::i18n::Localizer;
+
+const THUMBNAIL_SIDE: u32 = 256;
+
+/// A test image the model got wrong, as recorded by script.py in
+/// `misclassified.json` in the run directory.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Misclassification {
+    pub file: String,
+    pub actual: String,
+    pub predicted: String,
+    pub confidence: f64,
+}
+
+/// Misclassified test images of a run, most confident mistakes first.
+pub fn load(run_dir: &Path) -> Result<Vec<Misclassification>, String> {
+    let path = run_dir.join("misclassified.json");
+    let raw = std::fs::read_to_string(&path)
+        .map_err(|_| "This run has no misclassification data; it needs a test split.".to_string())?;
+    let mut items: Vec<Misclassification> =
+        serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    items.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    Ok(items)
+}
+
+/// Writes the misclassifications as one self-contained HTML file with
+/// embedded thumbnails, viewable without the app. Returns the item count.
+pub fn export(run_dir: &Path, dest: &Path, limit: Option<usize>, l10n: &Localizer) -> Result<usize, String> {
+    let t = |id: &str| html::escape(&l10n.text(id, &[]));
+    let mut items = load(run_dir)?;
+    if let Some(limit) = limit {
+        items.truncate(limit);
+    }
+
+    let mut pairs: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for item in &items {
+        *pairs.entry((item.actual.as_str(), item.predicted.as_str())).or_insert(0) += 1;
+    }
+    let mut pair_counts: Vec<_> = pairs.into_iter().collect();
+    pair_counts.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let intro = l10n.text(
+        "gallery-intro",
+        &[
+            ("run", run_dir.to_string_lossy().to_string().into()),
+            ("count", items.len().to_string().into()),
+        ],
+    );
+    let mut body = format!("<p class=\"muted\">{}</p>\n", html::escape(&intro));
+    body.push_str(&format!(
+        "<table><tr><th>{}</th><th>{}</th><th>{}</th></tr>\n",
+        t("gallery-actual"),
+        t("gallery-predicted-as"),
+        t("gallery-count")
+    ));
+    for ((actual, predicted), count) in &pair_counts {
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            html::escape(actual),
+            html::escape(predicted),
+            count
+        ));
+    }
+    body.push_str("</table>\n<div class=\"grid\">\n");
+    for item in &items {
+        let image = match html::thumbnail_data_uri(Path::new(&item.file), THUMBNAIL_SIDE) {
+            Some(uri) => format!("<img src=\"{}\" alt=\"\">", uri),
+            None => format!("<p class=\"muted\">{}</p>", t("gallery-image-unavailable")),
+        };
+        body.push_str(&format!(
+            "<div class=\"card\">{}<div>{}: <b>{}</b></div><div class=\"bad\">{}: <b>{}</b> ({:.1}%)</div><div class=\"muted\">{}</div></div>\n",
+            image,
+            t("gallery-actual"),
+            html::escape(&item.actual),
+            t("gallery-predicted"),
+            html::escape(&item.predicted),
+            item.confidence * 100.0,
+            html::escape(&item.file)
+        ));
+    }
+    body.push_str("</div>\n");
+
+    std::fs::write(dest, html::page(&l10n.language(), &l10n.text("gallery-title", &[]), &body)).map_err(|e| {
+        l10n.text(
+            "error-write-file",
+            &[("path", dest.display().to_string().into()), ("error", e.to_string().into())],
+        )
+    })?;
+    Ok(items.len())
+}
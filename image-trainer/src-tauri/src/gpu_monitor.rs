@@ -0,0 +1,216 @@
+use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+use nvml_wrapper::Nvml;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::error::BackendError;
+use crate::events;
+use crate::jobs::unix_now;
+use crate::python::{backend_script, parse_output, run_python_unrecorded};
+
+/// How often `gpu://stats` is published while a training job runs.
+const INTERVAL: Duration = Duration::from_secs(1);
+/// Starting Python for every sample is slow; the fallback samples less often.
+const FALLBACK_INTERVAL: Duration = Duration::from_secs(5);
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GpuSample {
+    pub index: u32,
+    pub name: String,
+    pub utilization_percent: Option<u32>,
+    pub memory_used_mb: Option<u64>,
+    pub memory_total_mb: Option<u64>,
+    pub temperature_c: Option<u32>,
+    pub power_w: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsSource {
+    Nvml,
+    /// check_gpu.py through PyTorch, when the NVIDIA driver library cannot be loaded.
+    Python,
+}
+
+/// One reading of every NVIDIA GPU, published as `gpu://stats`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GpuStats {
+    pub timestamp: u64,
+    pub source: StatsSource,
+    pub devices: Vec<GpuSample>,
+}
+
+/// A GPU jobs can be pinned to with a `device` of its index.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GpuDevice {
+    /// Index in PCI bus order, as `CUDA_VISIBLE_DEVICES` takes it.
+    pub index: u32,
+    pub name: String,
+    pub uuid: Option<String>,
+    pub memory_total_mb: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ScriptStats {
+    devices: Vec<GpuSample>,
+}
+
+/// NVML, loaded on first use, and the training jobs being monitored.
+#[derive(Default)]
+pub struct GpuMonitor {
+    nvml: OnceLock<Option<Nvml>>,
+    watched: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl GpuMonitor {
+    fn nvml(&self) -> Option<&Nvml> {
+        self.nvml
+            .get_or_init(|| match Nvml::init() {
+                Ok(nvml) => Some(nvml),
+                Err(e) => {
+                    tracing::warn!("NVML is unavailable, GPU stats fall back to check_gpu.py: {}", e);
+                    None
+                }
+            })
+            .as_ref()
+    }
+
+    /// Reads every device through NVML; None when the driver library is missing.
+    /// Readings a device does not support are left out rather than failing.
+    fn sample_nvml(&self) -> Option<Vec<GpuSample>> {
+        let nvml = self.nvml()?;
+        let count = nvml.device_count().ok()?;
+        let devices = (0..count)
+            .filter_map(|index| {
+                let device = nvml.device_by_index(index).ok()?;
+                let memory = device.memory_info().ok();
+                Some(GpuSample {
+                    index,
+                    name: device.name().unwrap_or_else(|_| format!("GPU {}", index)),
+                    utilization_percent: device.utilization_rates().ok().map(|u| u.gpu),
+                    memory_used_mb: memory.as_ref().map(|m| m.used / MB),
+                    memory_total_mb: memory.as_ref().map(|m| m.total / MB),
+                    temperature_c: device.temperature(TemperatureSensor::Gpu).ok(),
+                    power_w: device.power_usage().ok().map(|mw| mw as f64 / 1000.0),
+                })
+            })
+            .collect();
+        Some(devices)
+    }
+}
+
+/// Environment that limits a process to `device`: `cpu`, a GPU index, a GPU
+/// UUID, or several of those joined with commas. CUDA numbers devices fastest
+/// first unless told otherwise, so the order is set to match NVML's.
+pub fn device_env(device: &str) -> Result<Vec<(String, String)>, BackendError> {
+    let device = device.trim();
+    if device.eq_ignore_ascii_case("cpu") {
+        // An empty list hides every GPU, so PyTorch falls back to the CPU.
+        return Ok(vec![("CUDA_VISIBLE_DEVICES".to_string(), String::new())]);
+    }
+    let ids: Vec<&str> = device.split(',').map(str::trim).collect();
+    let valid = |id: &&str| {
+        id.parse::<u32>().is_ok() || (id.starts_with("GPU-") && id[4..].chars().all(|c| c.is_ascii_hexdigit() || c == '-'))
+    };
+    if ids.is_empty() || !ids.iter().all(valid) {
+        return Err(BackendError::invalid_input(format!(
+            "Unknown device '{}'; use cpu, a GPU index such as 0, or indices like 0,1.",
+            device
+        )));
+    }
+    Ok(vec![
+        ("CUDA_DEVICE_ORDER".to_string(), "PCI_BUS_ID".to_string()),
+        ("CUDA_VISIBLE_DEVICES".to_string(), ids.join(",")),
+    ])
+}
+
+/// The GPUs of this machine, through NVML or, without it, check_gpu.py.
+pub async fn list_devices(app: &tauri::AppHandle) -> Result<Vec<GpuDevice>, BackendError> {
+    let monitor = app.state::<Arc<GpuMonitor>>().inner().clone();
+    let nvml = tauri::async_runtime::spawn_blocking(move || {
+        let nvml = monitor.nvml()?;
+        let count = nvml.device_count().ok()?;
+        let devices = (0..count)
+            .filter_map(|index| {
+                let device = nvml.device_by_index(index).ok()?;
+                Some(GpuDevice {
+                    index,
+                    name: device.name().unwrap_or_else(|_| format!("GPU {}", index)),
+                    uuid: device.uuid().ok(),
+                    memory_total_mb: device.memory_info().ok().map(|m| m.total / MB),
+                })
+            })
+            .collect::<Vec<_>>();
+        Some(devices)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Some(devices) = nvml {
+        return Ok(devices);
+    }
+    let stats = sample(app).await?;
+    Ok(stats
+        .devices
+        .into_iter()
+        .map(|d| GpuDevice { index: d.index, name: d.name, uuid: None, memory_total_mb: d.memory_total_mb })
+        .collect())
+}
+
+/// A reading of every GPU, through NVML or, without it, check_gpu.py.
+pub async fn sample(app: &tauri::AppHandle) -> Result<GpuStats, BackendError> {
+    let monitor = app.state::<Arc<GpuMonitor>>().inner().clone();
+    let nvml = tauri::async_runtime::spawn_blocking(move || monitor.sample_nvml()).await.map_err(|e| e.to_string())?;
+    if let Some(devices) = nvml {
+        return Ok(GpuStats { timestamp: unix_now(), source: StatsSource::Nvml, devices });
+    }
+    let script = backend_script(app, "check_gpu.py")?;
+    // Polled while training; not a job of its own.
+    let output = run_python_unrecorded(app, &[script.as_str(), "--stats"]).await?;
+    let stats: ScriptStats = parse_output("check_gpu.py", &output)?;
+    Ok(GpuStats { timestamp: unix_now(), source: StatsSource::Python, devices: stats.devices })
+}
+
+/// Publishes `gpu://stats` for `job_id` every second until `unwatch`.
+pub fn watch(app: &tauri::AppHandle, job_id: &str) {
+    let Some(monitor) = app.try_state::<Arc<GpuMonitor>>() else {
+        return;
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+    monitor.watched.lock().unwrap().insert(job_id.to_string(), stop.clone());
+    let app = app.clone();
+    let job_id = job_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        while !stop.load(Ordering::SeqCst) {
+            let interval = match sample(&app).await {
+                Ok(stats) => {
+                    let interval = if stats.source == StatsSource::Nvml { INTERVAL } else { FALLBACK_INTERVAL };
+                    // Machines without an NVIDIA GPU have nothing to graph.
+                    if stats.devices.is_empty() {
+                        break;
+                    }
+                    events::publish(&app, "gpu://stats", Some(&job_id), stats);
+                    interval
+                }
+                Err(e) => {
+                    tracing::warn!("GPU monitoring of job {} stopped: {}", job_id, e);
+                    break;
+                }
+            };
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+pub fn unwatch(app: &tauri::AppHandle, job_id: &str) {
+    if let Some(monitor) = app.try_state::<Arc<GpuMonitor>>() {
+        if let Some(stop) = monitor.watched.lock().unwrap().remove(job_id) {
+            stop.store(true, Ordering::SeqCst);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::{self, GpuAdapter};
+use crate::error::BackendError;
+use crate::python::{backend_script, parse_output, run_python};
+
+/// What check_gpu.py found through PyTorch, completed by `adapters::with_fallback`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GpuReport {
+    pub python_version: Option<String>,
+    pub torch_version: Option<String>,
+    pub cuda_available: bool,
+    pub cuda_version: Option<String>,
+    #[serde(default)]
+    pub device_count: u32,
+    pub device_name: Option<String>,
+    /// Memory of the first CUDA device.
+    pub vram_total_mb: Option<u64>,
+    /// Adapters found through wgpu when PyTorch saw no CUDA device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adapters: Option<Vec<GpuAdapter>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adapter_source: Option<String>,
+    /// Why the PyTorch probe failed, when the adapters come from the fallback.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PythonDetails {
+    pub version: String,
+    pub executable: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TorchDetails {
+    pub version: String,
+    pub cuda_available: bool,
+    pub cuda_version: Option<String>,
+    pub gpu_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HardwareDetails {
+    pub cpu: String,
+    pub architecture: String,
+    pub cores: Option<u32>,
+    pub cpu_usage_percent: f64,
+    pub cpu_freq_mhz: Option<f64>,
+    pub ram_total_gb: f64,
+    pub ram_available_gb: f64,
+    pub ram_used_gb: f64,
+    pub ram_used_percent: f64,
+    pub disk_total_gb: f64,
+    pub disk_free_gb: f64,
+    pub disk_used_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlatformDetails {
+    pub os: String,
+    pub release: String,
+    pub hostname: String,
+}
+
+/// Output of system_info.py.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SystemInfo {
+    pub python: PythonDetails,
+    pub torch: TorchDetails,
+    pub hardware: HardwareDetails,
+    pub platform: PlatformDetails,
+}
+
+/// Runs check_gpu.py. Adapters found through wgpu are added when the probe
+/// fails or sees no CUDA device.
+pub async fn check_gpu(app: &tauri::AppHandle) -> Result<GpuReport, BackendError> {
+    let script = backend_script(app, "check_gpu.py")?;
+    let probe = match run_python(app, &[script.as_str()]).await {
+        Ok(output) => parse_output("check_gpu.py", &output),
+        Err(e) => Err(e.context("GPU detection failed")),
+    };
+    adapters::with_fallback(probe).await
+}
+
+pub async fn system_info(app: &tauri::AppHandle) -> Result<SystemInfo, BackendError> {
+    let script = backend_script(app, "system_info.py")?;
+    let output = run_python(app, &[script.as_str()])
+        .await
+        .map_err(|e| e.context("System info failed"))?;
+    parse_output("system_info.py", &output)
+}
@@ -0,0 +1,308 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::downloads::{self, Checksum, Downloads};
+use crate::paths;
+use crate::run_manifest;
+
+const HF_URL: &str = "https://huggingface.co";
+/// Keyring entry holding the user's Hugging Face access token.
+const KEYRING_SERVICE: &str = "epoq-huggingface";
+const KEYRING_USER: &str = "token";
+const SEARCH_LIMIT: usize = 50;
+/// Written next to downloaded files so a later download of the same
+/// revision is skipped.
+const SNAPSHOT_FILE: &str = ".hf_snapshot.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HfKind {
+    #[default]
+    Model,
+    Dataset,
+}
+
+impl HfKind {
+    fn api_path(self) -> &'static str {
+        match self {
+            Self::Model => "models",
+            Self::Dataset => "datasets",
+        }
+    }
+
+    /// Prefix of the repository in resolve URLs; models have none.
+    fn url_prefix(self) -> &'static str {
+        match self {
+            Self::Model => "",
+            Self::Dataset => "datasets/",
+        }
+    }
+}
+
+/// A search hit, as the hub lists it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HfRepo {
+    pub id: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub downloads: u64,
+    #[serde(default)]
+    pub likes: u64,
+    #[serde(default, alias = "lastModified")]
+    pub last_modified: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Task of a model, e.g. `image-classification`.
+    #[serde(default)]
+    pub pipeline_tag: Option<String>,
+    #[serde(default)]
+    pub private: bool,
+    /// Needs the terms accepted on the hub and a token to download.
+    #[serde(default)]
+    pub gated: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HfDownload {
+    pub repo_id: String,
+    pub kind: HfKind,
+    /// The commit the revision resolved to.
+    pub commit: String,
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// The snapshot was already downloaded; nothing was fetched.
+    pub cached: bool,
+    /// The repository is a model trained with this app (it holds a run
+    /// manifest). It is not registered automatically: a snapshot from the
+    /// hub is untrusted until the user adds it with `register_model`.
+    pub registrable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HfTokenStatus {
+    pub configured: bool,
+    /// `keyring` or `environment` (`HF_TOKEN`).
+    pub source: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RepoInfo {
+    sha: String,
+    #[serde(default)]
+    siblings: Vec<Sibling>,
+}
+
+#[derive(Deserialize)]
+struct Sibling {
+    rfilename: String,
+    #[serde(default)]
+    lfs: Option<LfsInfo>,
+}
+
+#[derive(Deserialize)]
+struct LfsInfo {
+    sha256: String,
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| format!("Keyring unavailable: {}", e))
+}
+
+/// The stored token, else `HF_TOKEN` from the environment.
+fn token() -> Option<(String, &'static str)> {
+    let stored = keyring_entry().ok().and_then(|entry| entry.get_password().ok());
+    if let Some(token) = stored.filter(|t| !t.is_empty()) {
+        return Some((token, "keyring"));
+    }
+    std::env::var("HF_TOKEN").ok().filter(|t| !t.is_empty()).map(|t| (t, "environment"))
+}
+
+pub fn token_status() -> HfTokenStatus {
+    let source = token().map(|(_, source)| source.to_string());
+    HfTokenStatus { configured: source.is_some(), source }
+}
+
+/// Stores the access token in the OS keyring, or removes it when `None`.
+pub fn set_token(value: Option<&str>) -> Result<HfTokenStatus, String> {
+    let entry = keyring_entry()?;
+    match value.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(value) => {
+            entry.set_password(value).map_err(|e| format!("Cannot store the token in the keyring: {}", e))?
+        }
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(format!("Cannot remove the token from the keyring: {}", e)),
+        },
+    }
+    Ok(token_status())
+}
+
+fn authorize(request: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Turns a hub error status into a message that says what to do.
+fn check_status(response: &reqwest::Response, what: &str) -> Result<(), String> {
+    let status = response.status();
+    match status.as_u16() {
+        _ if status.is_success() => Ok(()),
+        401 | 403 => Err(format!(
+            "The Hugging Face Hub refused access to {}; it may be private or gated. Set an access token that can read it.",
+            what
+        )),
+        404 => Err(format!("{} was not found on the Hugging Face Hub.", what)),
+        _ => Err(format!("The Hugging Face Hub answered HTTP {} for {}.", status, what)),
+    }
+}
+
+fn validate_repo_id(repo_id: &str) -> Result<(), String> {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part != "."
+            && part != ".."
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    };
+    let parts: Vec<&str> = repo_id.split('/').collect();
+    if parts.is_empty() || parts.len() > 2 || !parts.iter().all(|p| valid_part(p)) {
+        return Err(format!("'{}' is not a Hugging Face repository id such as 'org/name'.", repo_id));
+    }
+    Ok(())
+}
+
+/// Searches the hub's models or datasets, most downloaded first.
+pub async fn search(query: &str, kind: HfKind) -> Result<Vec<HfRepo>, String> {
+    let token = token().map(|(token, _)| token);
+    let client = downloads::client()?;
+    let url = format!("{}/api/{}", HF_URL, kind.api_path());
+    let limit = SEARCH_LIMIT.to_string();
+    let params = [("search", query.trim()), ("sort", "downloads"), ("direction", "-1"), ("limit", limit.as_str())];
+    let response = authorize(client.get(&url).query(&params), token.as_deref())
+        .send()
+        .await
+        .map_err(|e| format!("Cannot reach the Hugging Face Hub: {}", e))?;
+    check_status(&response, "the search")?;
+    response.json().await.map_err(|e| format!("Unexpected search results from the Hugging Face Hub: {}", e))
+}
+
+/// Where a snapshot goes: models next to trained runs so the registry can
+/// hold them, datasets in the profile's dataset store.
+fn snapshot_dir(app: &tauri::AppHandle, kind: HfKind, repo_id: &str, commit: &str) -> Result<PathBuf, String> {
+    let base = match kind {
+        HfKind::Model => paths::models_dir(app)?.join("huggingface"),
+        HfKind::Dataset => paths::data_dir(app)?.join("datasets").join("huggingface"),
+    };
+    let short: String = commit.chars().take(12).collect();
+    Ok(base.join(format!("{}@{}", repo_id.replace('/', "--"), short)))
+}
+
+/// A commit as the hub reports it: a hex object id. Anything else is refused
+/// before it reaches a directory name or a URL.
+fn validate_commit(commit: &str) -> Result<(), String> {
+    if commit.is_empty() || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("The Hugging Face Hub returned an unexpected commit id '{}'.", commit));
+    }
+    Ok(())
+}
+
+/// Percent-encodes each segment of a repository file path for a resolve URL,
+/// keeping the `/` separators.
+fn encode_path(file: &str) -> String {
+    file.split('/')
+        .map(|segment| {
+            segment
+                .bytes()
+                .map(|b| match b {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+                    _ => format!("%{:02X}", b),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A repository file path as a relative path that stays inside the snapshot.
+fn safe_relative(file: &str) -> Option<PathBuf> {
+    let path = Path::new(file);
+    path.components().all(|c| matches!(c, Component::Normal(_))).then(|| path.to_path_buf())
+}
+
+/// Downloads every file of `repo_id` at `revision` (default `main`) into the
+/// model or dataset store, verifying LFS files against their SHA-256. A
+/// model repository holding a run manifest is reported as `registrable` but
+/// left out of the registry until the user registers it.
+pub async fn download(
+    app: &tauri::AppHandle,
+    downloads: &Downloads,
+    repo_id: &str,
+    revision: Option<&str>,
+    kind: HfKind,
+) -> Result<HfDownload, String> {
+    validate_repo_id(repo_id)?;
+    let revision = revision.map(str::trim).filter(|r| !r.is_empty()).unwrap_or("main");
+    let token = token().map(|(token, _)| token);
+    let client = downloads::client()?;
+
+    let info_url = format!("{}/api/{}/{}/revision/{}", HF_URL, kind.api_path(), repo_id, revision.replace('/', "%2F"));
+    let response = authorize(client.get(&info_url), token.as_deref())
+        .query(&[("blobs", "true")])
+        .send()
+        .await
+        .map_err(|e| format!("Cannot reach the Hugging Face Hub: {}", e))?;
+    check_status(&response, &format!("{}@{}", repo_id, revision))?;
+    let info: RepoInfo =
+        response.json().await.map_err(|e| format!("Unexpected repository info from the Hugging Face Hub: {}", e))?;
+    if info.siblings.is_empty() {
+        return Err(format!("{}@{} has no files.", repo_id, revision));
+    }
+
+    validate_commit(&info.sha)?;
+    let dir = snapshot_dir(app, kind, repo_id, &info.sha)?;
+    let marker = dir.join(SNAPSHOT_FILE);
+    let cached = marker.is_file();
+    if !cached {
+        for sibling in &info.siblings {
+            let Some(relative) = safe_relative(&sibling.rfilename) else {
+                tracing::warn!(repo = %repo_id, "Skipping unsafe file path {}", sibling.rfilename);
+                continue;
+            };
+            let dest = dir.join(&relative);
+            let checksum = match &sibling.lfs {
+                Some(lfs) => Checksum::Sha256(lfs.sha256.clone()),
+                None => Checksum::Unknown,
+            };
+            let url = format!("{}/{}{}/resolve/{}/{}", HF_URL, kind.url_prefix(), repo_id, info.sha, encode_path(&sibling.rfilename));
+            let build = || authorize(client.get(&url), token.as_deref());
+            downloads::fetch(app, downloads, repo_id, build, &dest, &checksum).await?;
+        }
+        let record = serde_json::json!({ "repo_id": repo_id, "kind": kind, "revision": revision, "commit": info.sha });
+        std::fs::write(&marker, record.to_string()).map_err(|e| format!("Cannot write {}: {}", marker.display(), e))?;
+    }
+    let bytes = info
+        .siblings
+        .iter()
+        .filter_map(|sibling| safe_relative(&sibling.rfilename))
+        .filter_map(|relative| std::fs::metadata(dir.join(relative)).ok())
+        .map(|m| m.len())
+        .sum();
+
+    let path = dir.to_string_lossy().to_string();
+    let registrable = kind == HfKind::Model && dir.join(run_manifest::FILE_NAME).is_file();
+    tracing::info!(repo = %repo_id, commit = %info.sha, cached, "Hugging Face snapshot ready at {}", path);
+    Ok(HfDownload {
+        repo_id: repo_id.to_string(),
+        kind,
+        commit: info.sha,
+        path,
+        files: info.siblings.len(),
+        bytes,
+        cached,
+        registrable,
+    })
+}
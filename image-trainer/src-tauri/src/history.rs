@@ -0,0 +1,191 @@
+use rusqlite::{params, Connection};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::database::Database;
+use crate::jobs::{unix_now, JobRecord, JobStatus};
+use crate::paths;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    args TEXT NOT NULL,
+    status TEXT NOT NULL,
+    queued_at INTEGER NOT NULL,
+    started_at INTEGER,
+    finished_at INTEGER,
+    duration_secs INTEGER,
+    code INTEGER,
+    output_dir TEXT,
+    error TEXT,
+    output_summary TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS jobs_finished_at ON jobs (finished_at);
+CREATE INDEX IF NOT EXISTS jobs_kind ON jobs (kind);
+";
+
+/// Results returned when the filter sets no limit.
+const DEFAULT_LIMIT: usize = 200;
+/// Jobs kept; older ones are deleted as new ones are stored.
+const MAX_ENTRIES: i64 = 10_000;
+/// Jobs finished longer ago than this are deleted as new ones are stored.
+const MAX_AGE_SECS: u64 = 365 * 86_400;
+
+/// A finished job as stored in the history.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HistoryEntry {
+    pub job_id: String,
+    pub kind: String,
+    pub args: Vec<String>,
+    pub status: JobStatus,
+    pub queued_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    /// From start to finish; `None` for jobs that never started.
+    pub duration_secs: Option<u64>,
+    pub code: Option<i32>,
+    pub output_dir: Option<String>,
+    pub error: Option<String>,
+    /// The last lines the job printed.
+    pub output_summary: String,
+}
+
+/// Narrows `query_history`. Every field is optional; an empty filter returns
+/// the latest jobs.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// e.g. `training`, `plugin` or a script name such as `check_gpu`.
+    pub kind: Option<String>,
+    pub status: Option<JobStatus>,
+    /// Jobs finished at or after this unix time.
+    pub since: Option<u64>,
+    /// Jobs finished before this unix time.
+    pub until: Option<u64>,
+    /// Matched case-insensitively against the job id, arguments and output
+    /// directory, e.g. a dataset path or a learning rate.
+    pub search: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Managed state: the job history database (`job_history.sqlite`), opened on
+/// first use. Jobs are added by `JobManager` as they finish; the latest
+/// `MAX_ENTRIES` from the past year are kept.
+pub struct JobHistory {
+    db: Database,
+}
+
+fn init(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+impl JobHistory {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        Ok(Self { db: Database::new(paths::data_file(app, "job_history.sqlite")?, "job history", init) })
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        self.db.check()
+    }
+
+    pub fn close(&self) {
+        self.db.close()
+    }
+
+    /// Stores a finished job.
+    pub fn record(&self, record: &JobRecord, output_summary: &str) -> Result<(), String> {
+        let args = serde_json::to_string(&record.args).map_err(|e| e.to_string())?;
+        let duration = match (record.started_at, record.finished_at) {
+            (Some(start), Some(end)) => Some(end.saturating_sub(start) as i64),
+            _ => None,
+        };
+        self.db.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO jobs (job_id, kind, args, status, queued_at, started_at, finished_at, duration_secs, code, output_dir, error, output_summary)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    record.job_id,
+                    record.kind,
+                    args,
+                    record.status.as_str(),
+                    record.queued_at as i64,
+                    record.started_at.map(|t| t as i64),
+                    record.finished_at.map(|t| t as i64),
+                    duration,
+                    record.code,
+                    record.output_dir,
+                    record.error,
+                    output_summary,
+                ],
+            )?;
+            // The newest jobs win; without this the history grows for ever.
+            let cutoff = unix_now().saturating_sub(MAX_AGE_SECS) as i64;
+            conn.execute(
+                "DELETE FROM jobs
+                 WHERE id <= (SELECT id FROM jobs ORDER BY id DESC LIMIT 1 OFFSET ?1)
+                    OR finished_at < ?2",
+                params![MAX_ENTRIES, cutoff],
+            )
+        })
+        .map(|_| ())
+    }
+
+    /// Jobs matching `filter`, most recently finished first.
+    pub fn query(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, String> {
+        if !self.db.exists() {
+            return Ok(Vec::new());
+        }
+        let search = filter
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("%{}%", s.to_lowercase()));
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT) as i64;
+        self.db.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT job_id, kind, args, status, queued_at, started_at, finished_at, duration_secs, code, output_dir, error, output_summary
+                 FROM jobs
+                 WHERE (?1 IS NULL OR kind = ?1)
+                   AND (?2 IS NULL OR status = ?2)
+                   AND (?3 IS NULL OR finished_at >= ?3)
+                   AND (?4 IS NULL OR finished_at < ?4)
+                   AND (?5 IS NULL OR lower(job_id || ' ' || args || ' ' || coalesce(output_dir, '')) LIKE ?5)
+                 ORDER BY id DESC LIMIT ?6",
+            )?;
+            let rows = stmt.query_map(
+                params![
+                    filter.kind,
+                    filter.status.map(JobStatus::as_str),
+                    filter.since.map(|t| t as i64),
+                    filter.until.map(|t| t as i64),
+                    search,
+                    limit,
+                ],
+                entry_from_row,
+            )?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        })
+    }
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let args: String = row.get(2)?;
+    let status: String = row.get(3)?;
+    Ok(HistoryEntry {
+        job_id: row.get(0)?,
+        kind: row.get(1)?,
+        args: serde_json::from_str(&args).unwrap_or_default(),
+        status: JobStatus::parse(&status).unwrap_or(JobStatus::Failed),
+        queued_at: row.get::<_, i64>(4)? as u64,
+        started_at: row.get::<_, Option<i64>>(5)?.map(|t| t as u64),
+        finished_at: row.get::<_, Option<i64>>(6)?.map(|t| t as u64),
+        duration_secs: row.get::<_, Option<i64>>(7)?.map(|t| t as u64),
+        code: row.get(8)?,
+        output_dir: row.get(9)?,
+        error: row.get(10)?,
+        output_summary: row.get(11)?,
+    })
+}
@@ -0,0 +1,385 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri_plugin_shell::process::CommandEvent;
+use tauri_plugin_shell::ShellExt;
+
+use crate::error::BackendError;
+use crate::events;
+use crate::jobs::{kill_process_tree, new_id, unix_now, JobManager};
+use crate::paths;
+use crate::plugins::{self, is_contained};
+
+/// How often a plugin hook's job is checked for completion.
+const PLUGIN_POLL: Duration = Duration::from_millis(500);
+
+/// Which job outcomes a hook runs for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookCondition {
+    #[default]
+    Success,
+    Failure,
+    Always,
+}
+
+/// What a hook does. Strings may contain `{job_id}`, `{output_dir}`,
+/// `{exit_code}` and `{status}`, which are filled in per job.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HookAction {
+    /// Runs a program directly (no shell), e.g. a deploy script.
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+    },
+    /// Copies a file or folder from the job's output directory into `dest`.
+    Copy { source: String, dest: String },
+    /// Runs an installed plugin as its own job and waits for it.
+    Plugin {
+        name: String,
+        #[serde(default)]
+        inputs: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PostJobHook {
+    /// Assigned on first save when empty.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub on: HookCondition,
+    pub action: HookAction,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    300
+}
+
+/// Published as `hook://finished` after each hook ran for a job.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookRun {
+    pub hook_id: String,
+    pub hook_name: String,
+    pub success: bool,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+/// Facts about the finished job that hook strings can refer to.
+struct JobContext<'a> {
+    job_id: &'a str,
+    output_dir: &'a Path,
+    code: Option<i32>,
+}
+
+impl JobContext<'_> {
+    fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    fn expand(&self, template: &str) -> String {
+        template
+            .replace("{job_id}", self.job_id)
+            .replace("{output_dir}", &self.output_dir.to_string_lossy())
+            .replace("{exit_code}", &self.code.map(|c| c.to_string()).unwrap_or_default())
+            .replace("{status}", if self.success() { "success" } else { "failure" })
+    }
+}
+
+impl PostJobHook {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if self.name.trim().is_empty() {
+            return Err(BackendError::invalid_input("Hook name is required."));
+        }
+        if !(1..=86_400).contains(&self.timeout_secs) {
+            return Err(BackendError::invalid_input("Hook timeout must be between 1 second and 24 hours."));
+        }
+        match &self.action {
+            HookAction::Command { program, .. } if program.trim().is_empty() => {
+                Err(BackendError::invalid_input("Command hooks need a program."))
+            }
+            HookAction::Copy { source, .. } if !is_contained(source) => Err(BackendError::invalid_input(format!(
+                "Copy source '{}' must be a relative path inside the job's output directory.",
+                source
+            ))),
+            HookAction::Copy { dest, .. } if !Path::new(dest).is_absolute() && !dest.starts_with("{output_dir}") => {
+                Err(BackendError::invalid_input(format!("Copy destination '{}' must be an absolute path.", dest)))
+            }
+            HookAction::Plugin { name, .. } if name.trim().is_empty() => {
+                Err(BackendError::invalid_input("Plugin hooks need a plugin name."))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn applies_to(&self, success: bool) -> bool {
+        self.enabled
+            && match self.on {
+                HookCondition::Success => success,
+                HookCondition::Failure => !success,
+                HookCondition::Always => true,
+            }
+    }
+}
+
+fn hooks_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::data_file(app, "post_job_hooks.json")?)
+}
+
+fn log_path(app: &tauri::AppHandle, job_id: &str) -> Result<PathBuf, String> {
+    if !job_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err("Invalid job id.".to_string());
+    }
+    Ok(paths::log_dir(app)?.join("hooks").join(format!("{}.log", job_id)))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<Vec<PostJobHook>, String> {
+    let path = hooks_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid hook configuration: {}", e))
+}
+
+fn save_all(app: &tauri::AppHandle, hooks: &[PostJobHook]) -> Result<(), String> {
+    let path = hooks_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(hooks).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| e.to_string())
+}
+
+/// Adds a hook, or replaces the one with the same id. Returns the stored hook.
+pub fn save(app: &tauri::AppHandle, mut hook: PostJobHook) -> Result<PostJobHook, String> {
+    hook.validate()?;
+    if hook.id.is_empty() {
+        hook.id = new_id("hook");
+    }
+    let mut hooks = load(app)?;
+    match hooks.iter_mut().find(|h| h.id == hook.id) {
+        Some(existing) => *existing = hook.clone(),
+        None => hooks.push(hook.clone()),
+    }
+    save_all(app, &hooks)?;
+    Ok(hook)
+}
+
+pub fn remove(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let mut hooks = load(app)?;
+    let before = hooks.len();
+    hooks.retain(|h| h.id != id);
+    if hooks.len() == before {
+        return Err(format!("No hook with id {}", id));
+    }
+    save_all(app, &hooks)
+}
+
+/// The hook log of a job; empty when no hook ran for it.
+pub fn read_log(app: &tauri::AppHandle, job_id: &str) -> Result<String, String> {
+    let path = log_path(app, job_id)?;
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    std::fs::read_to_string(&path).map_err(|e| e.to_string())
+}
+
+fn append_log(app: &tauri::AppHandle, job_id: &str, text: &str) {
+    let Ok(path) = log_path(app, job_id) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "{}", text);
+    }
+}
+
+async fn run_command(
+    app: &tauri::AppHandle,
+    program: &str,
+    args: Vec<String>,
+    cwd: Option<String>,
+    timeout: Duration,
+) -> Result<String, String> {
+    let mut command = app.shell().command(program).args(args);
+    if let Some(cwd) = cwd {
+        command = command.current_dir(cwd);
+    }
+    let (mut rx, child) = command
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+
+    let mut output = String::new();
+    let finished = tokio::time::timeout(timeout, async {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(bytes) | CommandEvent::Stderr(bytes) => {
+                    output.push_str(&String::from_utf8_lossy(&bytes));
+                }
+                CommandEvent::Terminated(payload) => return payload.code,
+                _ => {}
+            }
+        }
+        None
+    })
+    .await;
+
+    match finished {
+        Ok(Some(0)) => Ok(output),
+        Ok(code) => Err(format!("Exited with code {:?}\n{}", code, output)),
+        Err(_) => {
+            kill_process_tree(child.pid());
+            let _ = child.kill();
+            Err(format!("Timed out after {} seconds\n{}", timeout.as_secs(), output))
+        }
+    }
+}
+
+fn copy_recursive(source: &Path, dest: &Path) -> std::io::Result<u64> {
+    if source.is_dir() {
+        std::fs::create_dir_all(dest)?;
+        let mut total = 0;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            total += copy_recursive(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        Ok(total)
+    } else {
+        std::fs::copy(source, dest)
+    }
+}
+
+async fn run_copy(source: PathBuf, dest_dir: PathBuf, timeout: Duration) -> Result<String, String> {
+    let Some(name) = source.file_name().map(|n| n.to_owned()) else {
+        return Err("Copy source has no file name.".to_string());
+    };
+    if !source.exists() {
+        return Err(format!("{} does not exist.", source.display()));
+    }
+    let dest = dest_dir.join(name);
+    let task = tauri::async_runtime::spawn_blocking(move || {
+        std::fs::create_dir_all(&dest_dir)?;
+        copy_recursive(&source, &dest).map(|bytes| format!("Copied {} bytes to {}", bytes, dest.display()))
+    });
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(result)) => result.map_err(|e| e.to_string()),
+        Ok(Err(e)) => Err(e.to_string()),
+        // The blocking copy cannot be interrupted; it finishes in the background.
+        Err(_) => Err(format!("Timed out after {} seconds", timeout.as_secs())),
+    }
+}
+
+async fn run_plugin(
+    app: &tauri::AppHandle,
+    manager: &Arc<JobManager>,
+    name: &str,
+    inputs: serde_json::Map<String, serde_json::Value>,
+    timeout: Duration,
+) -> Result<String, String> {
+    let job_id = plugins::run(app, manager, name, inputs).await?;
+    let started = Instant::now();
+    while manager.is_running(&job_id) {
+        if started.elapsed() > timeout {
+            let _ = manager.kill(&job_id);
+            return Err(format!("Plugin job {} timed out after {} seconds", job_id, timeout.as_secs()));
+        }
+        tokio::time::sleep(PLUGIN_POLL).await;
+    }
+    Ok(format!("Plugin job {} finished", job_id))
+}
+
+async fn run_hook(app: &tauri::AppHandle, manager: &Arc<JobManager>, hook: &PostJobHook, ctx: &JobContext<'_>) -> Result<String, String> {
+    let timeout = Duration::from_secs(hook.timeout_secs);
+    match &hook.action {
+        HookAction::Command { program, args, cwd } => {
+            let args = args.iter().map(|a| ctx.expand(a)).collect();
+            let cwd = cwd
+                .as_deref()
+                .map(|c| ctx.expand(c))
+                .or_else(|| Some(ctx.output_dir.to_string_lossy().to_string()));
+            run_command(app, &ctx.expand(program), args, cwd, timeout).await
+        }
+        HookAction::Copy { source, dest } => {
+            run_copy(
+                ctx.output_dir.join(ctx.expand(source)),
+                PathBuf::from(ctx.expand(dest)),
+                timeout,
+            )
+            .await
+        }
+        HookAction::Plugin { name, inputs } => {
+            let inputs = inputs
+                .iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        serde_json::Value::String(s) => serde_json::Value::String(ctx.expand(s)),
+                        other => other.clone(),
+                    };
+                    (k.clone(), v)
+                })
+                .collect();
+            run_plugin(app, manager, name, inputs, timeout).await
+        }
+    }
+}
+
+/// Runs every enabled hook matching the job's outcome, in the order they were
+/// configured. A failing hook is logged and does not stop the others.
+pub async fn run_post_job(app: &tauri::AppHandle, manager: &Arc<JobManager>, job_id: &str, output_dir: &Path, code: Option<i32>) {
+    let ctx = JobContext { job_id, output_dir, code };
+    let hooks = match load(app) {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            append_log(app, job_id, &format!("[{}] Failed to load hooks: {}", unix_now(), e));
+            return;
+        }
+    };
+
+    for hook in hooks.iter().filter(|h| h.applies_to(ctx.success())) {
+        let started = Instant::now();
+        let result = run_hook(app, manager, hook, &ctx).await;
+        let run = HookRun {
+            hook_id: hook.id.clone(),
+            hook_name: hook.name.clone(),
+            success: result.is_ok(),
+            message: match &result {
+                Ok(output) | Err(output) => output.trim().to_string(),
+            },
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        append_log(
+            app,
+            job_id,
+            &format!(
+                "[{}] {} ({}) {} in {} ms\n{}",
+                unix_now(),
+                run.hook_name,
+                run.hook_id,
+                if run.success { "succeeded" } else { "failed" },
+                run.duration_ms,
+                run.message
+            ),
+        );
+        events::publish(app, "hook://finished", Some(job_id), run);
+    }
+}
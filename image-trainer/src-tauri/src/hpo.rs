@@ -0,0 +1,417 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::error::BackendError;
+use crate::events;
+use crate::gpu_monitor;
+use crate::jobs::{new_id, spawn_job, unix_now, JobManager, JobSpec};
+use crate::models;
+use crate::paths;
+use crate::python::{backend_script, resolve_launcher};
+
+/// A hyperparameter search: an Optuna study run by automl_sweep.py in one
+/// process, trying learning rate, batch size and optimizer.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct HpoConfig {
+    pub path: String,
+    pub model: String,
+    pub n_trials: u32,
+    pub epochs_per_trial: u32,
+    /// DataLoader workers; -1 picks a number for the platform.
+    pub num_workers: i32,
+    /// GPU to run every trial on, or `cpu`, as `list_gpu_devices` indexes them.
+    pub device: Option<String>,
+    /// Where the best trial is saved as a model; `hpo_<study id>` in the
+    /// models folder when empty.
+    pub output_dir: Option<String>,
+    /// Registry name of the best model.
+    pub name: Option<String>,
+}
+
+impl Default for HpoConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            model: "resnet18".to_string(),
+            n_trials: 20,
+            epochs_per_trial: 3,
+            num_workers: -1,
+            device: None,
+            output_dir: None,
+            name: None,
+        }
+    }
+}
+
+impl HpoConfig {
+    fn validate(&self) -> Result<(), BackendError> {
+        if !Path::new(&self.path).is_dir() {
+            return Err(BackendError::invalid_input(format!("Dataset folder {} does not exist.", self.path)));
+        }
+        if self.n_trials == 0 || self.epochs_per_trial == 0 {
+            return Err(BackendError::invalid_input("A study needs at least one trial of at least one epoch."));
+        }
+        if let Some(device) = &self.device {
+            gpu_monitor::device_env(device)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HpoState {
+    Running,
+    /// No new trial starts; the running one finishes.
+    Paused,
+    /// Ends after the running trial.
+    Stopping,
+    Completed,
+    Stopped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialState {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TrialEpoch {
+    pub epoch: u32,
+    pub train_loss: Option<f64>,
+    pub train_accuracy: Option<f64>,
+    pub val_loss: Option<f64>,
+    pub val_accuracy: Option<f64>,
+}
+
+/// One trial, published as `hpo://trial` whenever it changes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HpoTrial {
+    /// From 1.
+    pub number: u32,
+    /// The trial's entry in the job list, `<study id>_trial<number>`.
+    pub job_id: String,
+    pub params: serde_json::Value,
+    pub state: TrialState,
+    /// Best validation accuracy over the trial's epochs.
+    pub val_accuracy: Option<f64>,
+    pub epochs: Vec<TrialEpoch>,
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+/// A study and its trials, published as `hpo://status` when its state changes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HpoStudy {
+    /// Also the job id of the study's process.
+    pub study_id: String,
+    pub config: HpoConfig,
+    pub state: HpoState,
+    pub trials: Vec<HpoTrial>,
+    pub best_trial: Option<u32>,
+    pub best_params: Option<serde_json::Value>,
+    pub best_accuracy: Option<f64>,
+    pub output_dir: String,
+    /// The best trial's model in the registry, once the study has ended.
+    pub model_id: Option<String>,
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+struct StudyRun {
+    study: HpoStudy,
+    pause_file: PathBuf,
+    stop_file: PathBuf,
+}
+
+/// Managed state: studies of this session, running and ended.
+#[derive(Default)]
+pub struct HpoManager {
+    studies: Mutex<HashMap<String, StudyRun>>,
+}
+
+fn trial_job_id(study_id: &str, number: u32) -> String {
+    format!("{}_trial{}", study_id, number)
+}
+
+impl HpoManager {
+    pub fn get(&self, study_id: &str) -> Result<HpoStudy, String> {
+        self.studies
+            .lock()
+            .unwrap()
+            .get(study_id)
+            .map(|run| run.study.clone())
+            .ok_or_else(|| format!("No hyperparameter study with id {}", study_id))
+    }
+
+    /// Newest first.
+    pub fn list(&self) -> Vec<HpoStudy> {
+        let mut studies: Vec<HpoStudy> = self.studies.lock().unwrap().values().map(|run| run.study.clone()).collect();
+        studies.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        studies
+    }
+
+    /// Changes the state of a running study through its control files.
+    fn control(
+        &self,
+        study_id: &str,
+        apply: impl FnOnce(&mut StudyRun) -> Result<(), String>,
+    ) -> Result<HpoStudy, String> {
+        let mut studies = self.studies.lock().unwrap();
+        let run = studies.get_mut(study_id).ok_or_else(|| format!("No hyperparameter study with id {}", study_id))?;
+        if !matches!(run.study.state, HpoState::Running | HpoState::Paused | HpoState::Stopping) {
+            return Err(format!("Study {} has already ended.", study_id));
+        }
+        apply(run)?;
+        Ok(run.study.clone())
+    }
+
+    pub fn pause(&self, study_id: &str) -> Result<HpoStudy, String> {
+        self.control(study_id, |run| {
+            if run.study.state != HpoState::Running {
+                return Err("Only a running study can be paused.".to_string());
+            }
+            std::fs::write(&run.pause_file, b"pause").map_err(|e| e.to_string())?;
+            run.study.state = HpoState::Paused;
+            Ok(())
+        })
+    }
+
+    pub fn resume(&self, study_id: &str) -> Result<HpoStudy, String> {
+        self.control(study_id, |run| {
+            if run.study.state != HpoState::Paused {
+                return Err("The study is not paused.".to_string());
+            }
+            std::fs::remove_file(&run.pause_file).map_err(|e| e.to_string())?;
+            run.study.state = HpoState::Running;
+            Ok(())
+        })
+    }
+
+    /// Ends the study once the running trial finishes.
+    pub fn stop(&self, study_id: &str) -> Result<HpoStudy, String> {
+        self.control(study_id, |run| {
+            std::fs::write(&run.stop_file, b"stop").map_err(|e| e.to_string())?;
+            run.study.state = HpoState::Stopping;
+            Ok(())
+        })
+    }
+}
+
+fn metric(event: &serde_json::Value, key: &str) -> Option<f64> {
+    event[key].as_f64()
+}
+
+/// Folds a status line of automl_sweep.py into the study, tracking trials as
+/// sub-jobs in the job list. Called for every stdout line of `hpo` jobs.
+pub fn record_line(app: &tauri::AppHandle, study_id: &str, line: &str) {
+    let Some(hpo) = app.try_state::<Arc<HpoManager>>() else {
+        return;
+    };
+    let Ok(event) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+        return;
+    };
+    let Some(number) = event["trial"].as_u64().map(|n| n as u32) else {
+        return;
+    };
+    let jobs = app.state::<Arc<JobManager>>();
+    let job_id = trial_job_id(study_id, number);
+    let trial = {
+        let mut studies = hpo.studies.lock().unwrap();
+        let Some(run) = studies.get_mut(study_id) else {
+            return;
+        };
+        let study = &mut run.study;
+        if event["status"] == "automl_trial_started" {
+            jobs.queue(&job_id, "hpo_trial", &[], None);
+            jobs.start(&job_id);
+            study.trials.push(HpoTrial {
+                number,
+                job_id: job_id.clone(),
+                params: event["params"].clone(),
+                state: TrialState::Running,
+                val_accuracy: None,
+                epochs: Vec::new(),
+                error: None,
+                started_at: unix_now(),
+                finished_at: None,
+            });
+        }
+        let Some(trial) = study.trials.iter_mut().find(|t| t.number == number) else {
+            return;
+        };
+        match event["status"].as_str() {
+            Some("automl_trial_epoch") => trial.epochs.push(TrialEpoch {
+                epoch: event["epoch"].as_u64().unwrap_or(0) as u32,
+                train_loss: metric(&event, "train_loss"),
+                train_accuracy: metric(&event, "train_accuracy"),
+                val_loss: metric(&event, "val_loss"),
+                val_accuracy: metric(&event, "val_accuracy"),
+            }),
+            Some("automl_trial") => {
+                trial.state = TrialState::Completed;
+                trial.val_accuracy = metric(&event, "val_accuracy");
+                trial.finished_at = Some(unix_now());
+                jobs.finish(&job_id, Some(0), None);
+                if let Some(accuracy) = trial.val_accuracy.filter(|a| study.best_accuracy.is_none_or(|best| *a > best))
+                {
+                    study.best_accuracy = Some(accuracy);
+                    study.best_trial = Some(number);
+                    study.best_params = Some(trial.params.clone());
+                }
+            }
+            Some("automl_trial_error") => {
+                let message = event["message"].as_str().unwrap_or("The trial failed.").to_string();
+                trial.state = TrialState::Failed;
+                trial.finished_at = Some(unix_now());
+                jobs.finish(&job_id, Some(1), Some(message.clone()));
+                trial.error = Some(message);
+            }
+            _ => {}
+        }
+        // Published once the lock is released.
+        study.trials.iter().find(|t| t.number == number).cloned()
+    };
+    if let Some(trial) = trial {
+        events::publish(app, "hpo://trial", Some(study_id), trial);
+    }
+}
+
+/// Ends a study once its process exits: closes unfinished trials and
+/// registers the best trial's model with its parameters.
+pub fn finish(app: &tauri::AppHandle, study_id: &str, code: Option<i32>, cancelled: bool) {
+    let Some(hpo) = app.try_state::<Arc<HpoManager>>() else {
+        return;
+    };
+    let jobs = app.state::<Arc<JobManager>>();
+    let (study, output_dir) = {
+        let mut studies = hpo.studies.lock().unwrap();
+        let Some(run) = studies.get_mut(study_id) else {
+            return;
+        };
+        let _ = std::fs::remove_file(&run.pause_file);
+        let _ = std::fs::remove_file(&run.stop_file);
+        let study = &mut run.study;
+        for trial in study.trials.iter_mut().filter(|t| t.state == TrialState::Running) {
+            trial.state = TrialState::Failed;
+            trial.error = Some("The study ended during this trial.".to_string());
+            trial.finished_at = Some(unix_now());
+            jobs.finish(&trial.job_id, None, trial.error.clone());
+        }
+        study.state = if cancelled || study.state == HpoState::Stopping {
+            HpoState::Stopped
+        } else if code == Some(0) {
+            HpoState::Completed
+        } else {
+            study.error = Some(format!("automl_sweep.py exited with code {}", code.unwrap_or(-1)));
+            HpoState::Failed
+        };
+        study.finished_at = Some(unix_now());
+        (study.clone(), study.output_dir.clone())
+    };
+
+    let model_id = if Path::new(&output_dir).join("best_model.pth").is_file() {
+        match models::register(app, &output_dir, study.config.name.clone()) {
+            Ok(model) => Some(model.id),
+            Err(e) => {
+                tracing::warn!("Could not register the best model of study {}: {}", study_id, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let mut studies = hpo.studies.lock().unwrap();
+    let Some(run) = studies.get_mut(study_id) else {
+        return;
+    };
+    run.study.model_id = model_id;
+    let study = run.study.clone();
+    drop(studies);
+    events::publish(app, "hpo://status", Some(study_id), study);
+}
+
+/// Starts a study as a job of kind `hpo`; trials appear as `hpo_trial` jobs.
+pub async fn start(
+    app: &tauri::AppHandle,
+    jobs: &Arc<JobManager>,
+    hpo: &Arc<HpoManager>,
+    config: HpoConfig,
+) -> Result<HpoStudy, String> {
+    config.validate()?;
+    let study_id = new_id("hpo");
+    let output_dir = match &config.output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => paths::models_dir(app)?.join(&study_id),
+    };
+    let pause_file = paths::cache_file(app, &format!("{}.pause", study_id))?;
+    let stop_file = paths::cache_file(app, &format!("{}.stop", study_id))?;
+
+    let script = backend_script(app, "automl_sweep.py")?;
+    let args = vec![
+        script,
+        "--path".to_string(),
+        config.path.clone(),
+        "--model".to_string(),
+        config.model.clone(),
+        "--n_trials".to_string(),
+        config.n_trials.to_string(),
+        "--epochs_per_trial".to_string(),
+        config.epochs_per_trial.to_string(),
+        "--num_workers".to_string(),
+        config.num_workers.to_string(),
+        "--study_name".to_string(),
+        study_id.clone(),
+        "--output_dir".to_string(),
+        output_dir.to_string_lossy().to_string(),
+        "--pause_file".to_string(),
+        pause_file.to_string_lossy().to_string(),
+        "--stop_file".to_string(),
+        stop_file.to_string_lossy().to_string(),
+    ];
+    let env = match &config.device {
+        Some(device) => gpu_monitor::device_env(device)?,
+        None => Vec::new(),
+    };
+    let launcher = resolve_launcher(app).await?;
+    let spec = JobSpec {
+        kind: "hpo".to_string(),
+        program: launcher.program.clone(),
+        args: launcher.with_args(args),
+        env,
+        ..Default::default()
+    };
+
+    let study = HpoStudy {
+        study_id: study_id.clone(),
+        config,
+        state: HpoState::Running,
+        trials: Vec::new(),
+        best_trial: None,
+        best_params: None,
+        best_accuracy: None,
+        output_dir: output_dir.to_string_lossy().to_string(),
+        model_id: None,
+        error: None,
+        started_at: unix_now(),
+        finished_at: None,
+    };
+    // Registered before the process starts so no status line is missed.
+    hpo.studies.lock().unwrap().insert(study_id.clone(), StudyRun { study: study.clone(), pause_file, stop_file });
+    if let Err(e) = spawn_job(app, jobs, study_id.clone(), spec) {
+        hpo.studies.lock().unwrap().remove(&study_id);
+        return Err(e);
+    }
+    Ok(study)
+}
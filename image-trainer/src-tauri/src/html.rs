@@ -0,0 +1,71 @@
+use base64::Engine;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::image_meta;
+
+/// Escapes text for HTML element content and quoted attribute values.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A JPEG thumbnail of `path` no larger than `max_side`, as a data URI, so
+/// exported pages are self-contained. `None` if the image cannot be decoded.
+pub fn thumbnail_data_uri(path: &Path, max_side: u32) -> Option<String> {
+    let image = image_meta::open_oriented(path).ok()?.thumbnail(max_side, max_side).into_rgb8();
+    let mut jpeg = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .ok()?;
+    Some(format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(jpeg)
+    ))
+}
+
+/// Wraps `body` in a standalone page with the shared report styling. `lang`
+/// is the language the page is written in.
+pub fn page(lang: &str, title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; color: #1f2933; }}
+h1 {{ font-size: 1.5rem; }}
+table {{ border-collapse: collapse; margin: 1rem 0; }}
+th, td {{ border: 1px solid #d9e2ec; padding: 0.3rem 0.6rem; text-align: left; }}
+th {{ background: #f0f4f8; }}
+.grid {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 1rem; }}
+.card {{ border: 1px solid #d9e2ec; border-radius: 6px; padding: 0.5rem; font-size: 0.85rem; }}
+.card img {{ width: 100%; height: auto; display: block; margin-bottom: 0.4rem; }}
+.muted {{ color: #627d98; word-break: break-all; }}
+.bad {{ color: #c62828; }}
+.good {{ color: #2e7d32; font-weight: bold; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+"#,
+        lang = escape(lang),
+        title = escape(title),
+        body = body
+    )
+}
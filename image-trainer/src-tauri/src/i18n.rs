@@ -0,0 +1,198 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use unic_langid::LanguageIdentifier;
+
+use crate::paths;
+
+/// Every message exists in English, which fills the gaps of other languages.
+const FALLBACK: &str = "en";
+
+/// Translations shipped with the app. Deployments can add or override one by
+/// placing `<language>.ftl` in the `locales` folder of the data directory.
+const BUNDLED: &[(&str, &str)] = &[("en", include_str!("../locales/en.ftl"))];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct LanguageSettings {
+    /// BCP 47 tag such as `de` or `pt-BR`. `None` follows the system language.
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LanguageStatus {
+    pub settings: LanguageSettings,
+    /// Language messages are currently produced in.
+    pub active: String,
+    pub available: Vec<String>,
+}
+
+type Bundle = FluentBundle<FluentResource>;
+
+/// Managed state: the language setting (persisted to `language_settings.json`)
+/// and the Fluent bundles for the active language and the English fallback.
+/// Reports, notifications and the errors listed in `en.ftl` go through it.
+pub struct Localizer {
+    settings: Mutex<LanguageSettings>,
+    path: PathBuf,
+    locales_dir: Option<PathBuf>,
+    active: Mutex<(String, Option<Bundle>)>,
+    fallback: Bundle,
+}
+
+fn build(tag: &str, source: String) -> Result<Bundle, String> {
+    let lang: LanguageIdentifier = tag.parse().map_err(|_| format!("Invalid language tag: {}", tag))?;
+    let resource = FluentResource::try_new(source).map_err(|(_, errors)| {
+        format!("Invalid translation file for {}: {} syntax error(s)", tag, errors.len())
+    })?;
+    let mut bundle = FluentBundle::new_concurrent(vec![lang]);
+    // Unicode isolation marks would end up verbatim in HTML reports and webhooks.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .map_err(|errors| format!("Duplicate messages in translation for {}: {}", tag, errors.len()))?;
+    Ok(bundle)
+}
+
+fn format(bundle: &Bundle, id: &str, args: &FluentArgs) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    let text = bundle.format_pattern(pattern, Some(args), &mut errors);
+    errors.is_empty().then(|| text.into_owned())
+}
+
+impl Localizer {
+    /// English only; used before the app state exists.
+    pub fn english() -> Self {
+        let source = BUNDLED.iter().find(|(tag, _)| *tag == FALLBACK).map(|(_, s)| *s).unwrap_or_default();
+        Self {
+            settings: Mutex::new(LanguageSettings::default()),
+            path: PathBuf::new(),
+            locales_dir: None,
+            active: Mutex::new((FALLBACK.to_string(), None)),
+            fallback: build(FALLBACK, source.to_string()).expect("bundled English translation is valid"),
+        }
+    }
+
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let path = paths::settings_file(app, "language_settings.json")?;
+        let settings: LanguageSettings = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        let localizer = Self {
+            path,
+            locales_dir: Some(paths::data_dir(app)?.join("locales")),
+            ..Self::english()
+        };
+        if let Err(e) = localizer.activate(settings.language.as_deref()) {
+            tracing::warn!("{}; using English.", e);
+        }
+        *localizer.settings.lock().unwrap() = settings;
+        Ok(localizer)
+    }
+
+    /// Translation source for `tag`, preferring a user-provided file.
+    fn source(&self, tag: &str) -> Option<String> {
+        let custom = self
+            .locales_dir
+            .as_ref()
+            .and_then(|dir| std::fs::read_to_string(dir.join(format!("{}.ftl", tag))).ok());
+        custom.or_else(|| BUNDLED.iter().find(|(t, _)| t.eq_ignore_ascii_case(tag)).map(|(_, s)| s.to_string()))
+    }
+
+    pub fn available(&self) -> Vec<String> {
+        let mut tags: Vec<String> = BUNDLED.iter().map(|(tag, _)| tag.to_string()).collect();
+        if let Some(entries) = self.locales_dir.as_ref().and_then(|dir| std::fs::read_dir(dir).ok()) {
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.extension().is_some_and(|ext| ext == "ftl") {
+                    if let Some(stem) = path.file_stem() {
+                        tags.push(stem.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Switches to `language` (or the system language), trying the full tag
+    /// and then its primary language, e.g. `pt-BR` then `pt`.
+    fn activate(&self, language: Option<&str>) -> Result<(), String> {
+        let requested = match language {
+            Some(tag) => tag.to_string(),
+            None => sys_locale::get_locale().unwrap_or_else(|| FALLBACK.to_string()),
+        };
+        let primary = requested.split(['-', '_']).next().unwrap_or_default().to_string();
+        let mut chosen = None;
+        for tag in [requested.replace('_', "-"), primary] {
+            if tag == FALLBACK {
+                chosen = Some((tag, None));
+                break;
+            }
+            if let Some(source) = self.source(&tag) {
+                chosen = Some((tag.clone(), Some(build(&tag, source)?)));
+                break;
+            }
+        }
+        match chosen {
+            Some(chosen) => *self.active.lock().unwrap() = chosen,
+            None if language.is_some() => {
+                return Err(self.text("error-unknown-language", &[("language", requested.into())]));
+            }
+            None => *self.active.lock().unwrap() = (FALLBACK.to_string(), None),
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> LanguageStatus {
+        LanguageStatus {
+            settings: self.settings.lock().unwrap().clone(),
+            active: self.language(),
+            available: self.available(),
+        }
+    }
+
+    pub fn set_settings(&self, settings: LanguageSettings) -> Result<(), String> {
+        self.activate(settings.language.as_deref())?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Tag of the language messages are produced in, for `lang` attributes.
+    pub fn language(&self) -> String {
+        self.active.lock().unwrap().0.clone()
+    }
+
+    /// The message `id` in the active language, else in English, else the id itself.
+    pub fn text(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        let active = self.active.lock().unwrap();
+        active
+            .1
+            .as_ref()
+            .and_then(|bundle| format(bundle, id, &fluent_args))
+            .or_else(|| format(&self.fallback, id, &fluent_args))
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// The app's localizer, or an English one outside the app (e.g. during setup).
+pub fn localizer(app: &tauri::AppHandle) -> Arc<Localizer> {
+    app.try_state::<Arc<Localizer>>()
+        .map(|l| l.inner().clone())
+        .unwrap_or_else(|| Arc::new(Localizer::english()))
+}
@@ -0,0 +1,103 @@
+use exif::{In, Reader, Tag, Value};
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::io::BufReader;
+use std::path::Path;
+
+/// What `read_image_metadata` found in a file's header and EXIF data.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImageMetadata {
+    pub path: String,
+    /// Decoder format, e.g. `Jpeg`.
+    pub format: Option<String>,
+    pub file_size: u64,
+    /// As stored in the file.
+    pub width: u32,
+    pub height: u32,
+    /// As displayed, after the orientation is applied.
+    pub display_width: u32,
+    pub display_height: u32,
+    /// EXIF orientation 1 to 8; 1 (upright) when the file has none.
+    pub orientation: u32,
+    /// Whether the image is stored rotated or mirrored.
+    pub needs_rotation: bool,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    /// `DateTimeOriginal` as `YYYY-MM-DDTHH:MM:SS` in the camera's local time.
+    pub captured_at: Option<String>,
+    pub has_exif: bool,
+}
+
+fn ascii(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(parts) => {
+            let text = String::from_utf8_lossy(parts.first()?).trim().trim_end_matches('\0').to_string();
+            (!text.is_empty()).then_some(text)
+        }
+        _ => None,
+    }
+}
+
+fn captured_at(exif: &exif::Exif) -> Option<String> {
+    let field =
+        exif.get_field(Tag::DateTimeOriginal, In::PRIMARY).or_else(|| exif.get_field(Tag::DateTime, In::PRIMARY))?;
+    let Value::Ascii(parts) = &field.value else {
+        return None;
+    };
+    let at = exif::DateTime::from_ascii(parts.first()?).ok()?;
+    Some(format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", at.year, at.month, at.day, at.hour, at.minute, at.second))
+}
+
+/// Reads dimensions, orientation, camera and capture date without decoding the pixels.
+pub fn read(path: &Path) -> Result<ImageMetadata, String> {
+    let file_size = std::fs::metadata(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?.len();
+    let reader = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let format = reader.format().map(|f| format!("{:?}", f));
+    let (width, height) = reader.into_dimensions().map_err(|e| format!("Cannot decode {}: {}", path.display(), e))?;
+
+    let file = std::fs::File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    // Formats without EXIF, or files without it, are simply upright.
+    let exif = Reader::new().read_from_container(&mut BufReader::new(file)).ok();
+    let orientation = exif
+        .as_ref()
+        .and_then(|exif| exif.get_field(Tag::Orientation, In::PRIMARY))
+        .and_then(|field| field.value.get_uint(0))
+        .filter(|o| (1..=8).contains(o))
+        .unwrap_or(1);
+    // Orientations 5 to 8 turn the image by 90 degrees.
+    let (display_width, display_height) = if orientation >= 5 { (height, width) } else { (width, height) };
+
+    Ok(ImageMetadata {
+        path: path.to_string_lossy().to_string(),
+        format,
+        file_size,
+        width,
+        height,
+        display_width,
+        display_height,
+        orientation,
+        needs_rotation: orientation != 1,
+        camera_make: exif.as_ref().and_then(|e| ascii(e, Tag::Make)),
+        camera_model: exif.as_ref().and_then(|e| ascii(e, Tag::Model)),
+        lens_model: exif.as_ref().and_then(|e| ascii(e, Tag::LensModel)),
+        captured_at: exif.as_ref().and_then(captured_at),
+        has_exif: exif.is_some(),
+    })
+}
+
+/// Decodes an image and turns it upright by its EXIF orientation, as
+/// training does, so sideways phone photos look the way the model sees them.
+pub fn open_oriented(path: &Path) -> Result<DynamicImage, String> {
+    let decode = || -> image::ImageResult<DynamicImage> {
+        let mut decoder = ImageReader::open(path)?.with_guessed_format()?.into_decoder()?;
+        let orientation = decoder.orientation()?;
+        let mut image = DynamicImage::from_decoder(decoder)?;
+        image.apply_orientation(orientation);
+        Ok(image)
+    };
+    decode().map_err(|e| format!("Cannot decode {}: {}", path.display(), e))
+}
@@ -0,0 +1,320 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri_plugin_shell::ShellExt;
+
+use crate::embedded_python;
+use crate::python::{self, PythonSettings};
+use crate::venv;
+
+/// Prints the interpreter's real path, pointer width and version.
+const PROBE_SCRIPT: &str =
+    "import sys, struct; print(sys.executable); print(struct.calcsize('P') * 8); print('%d.%d.%d' % sys.version_info[:3])";
+/// Versioned names looked for on PATH besides `python` and `python3`.
+const MINOR_VERSIONS: std::ops::RangeInclusive<u32> = 8..=14;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InterpreterSource {
+    Path,
+    PyLauncher,
+    Pyenv,
+    Conda,
+    /// The app's own venv, made by `create_venv`.
+    Venv,
+    /// The configured interpreter, found by none of the scans.
+    Configured,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PythonInterpreter {
+    /// Path to pass to `set_python_interpreter`.
+    pub path: String,
+    /// What the interpreter reports as `sys.executable`.
+    pub executable: String,
+    pub version: String,
+    pub bits: u32,
+    pub source: InterpreterSource,
+    /// Name of the conda environment or pyenv version it belongs to.
+    pub environment: Option<String>,
+    /// PyTorch has no 32-bit builds.
+    pub usable: bool,
+    pub selected: bool,
+}
+
+struct Probe {
+    executable: String,
+    bits: u32,
+    version: String,
+}
+
+async fn probe(app: &tauri::AppHandle, path: &str) -> Result<Probe, String> {
+    let output = app
+        .shell()
+        .command(path)
+        .args(["-c", PROBE_SCRIPT])
+        .output()
+        .await
+        .map_err(|e| format!("Cannot run {}: {}", path, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} is not a working Python: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(str::trim);
+    let executable = lines.next().unwrap_or(path).to_string();
+    let bits = lines.next().and_then(|b| b.parse().ok()).unwrap_or(64);
+    let version = lines.next().unwrap_or_default().to_string();
+    if !version.starts_with("3.") {
+        return Err(format!("{} is Python {}, not Python 3.", path, version));
+    }
+    Ok(Probe { executable, bits, version })
+}
+
+fn executable_name(stem: &str) -> String {
+    format!("{}{}", stem, std::env::consts::EXE_SUFFIX)
+}
+
+fn on_path() -> Vec<PathBuf> {
+    let mut names = vec![executable_name("python"), executable_name("python3")];
+    names.extend(MINOR_VERSIONS.map(|minor| executable_name(&format!("python3.{}", minor))));
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        // The Microsoft Store aliases open the Store instead of running.
+        .filter(|dir| !dir.to_string_lossy().contains("WindowsApps"))
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Installs the Windows `py` launcher knows, from `py -0p` lines such as
+/// ` -V:3.11 *        C:\Python311\python.exe`.
+async fn from_py_launcher(app: &tauri::AppHandle) -> Vec<PathBuf> {
+    if !cfg!(windows) {
+        return Vec::new();
+    }
+    let Ok(output) = app.shell().command("py").arg("-0p").output().await else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.find(":\\").map(|i| line[i.saturating_sub(1)..].trim().to_string()))
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn home() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Interpreter inside an environment or install prefix.
+fn prefix_python(prefix: &Path) -> PathBuf {
+    if cfg!(windows) {
+        prefix.join("python.exe")
+    } else {
+        prefix.join("bin").join("python")
+    }
+}
+
+fn from_pyenv() -> Vec<(PathBuf, String)> {
+    let root = std::env::var_os("PYENV_ROOT")
+        .map(PathBuf::from)
+        .or_else(|| home().map(|h| h.join(".pyenv")));
+    let Some(root) = root else {
+        return Vec::new();
+    };
+    // pyenv-win keeps its versions one level deeper.
+    let versions = [root.join("versions"), root.join("pyenv-win").join("versions")];
+    versions
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| (prefix_python(&entry.path()), entry.file_name().to_string_lossy().to_string()))
+        .filter(|(path, _)| path.is_file())
+        .collect()
+}
+
+/// conda, mamba or micromamba, whichever answers first. The tool of the
+/// shell's activated installation is preferred, since conda is often not on
+/// the PATH an app is started with.
+pub async fn conda_tool(app: &tauri::AppHandle) -> Option<String> {
+    let mut tools: Vec<String> = ["CONDA_EXE", "MAMBA_EXE"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .filter(|tool| !tool.is_empty())
+        .collect();
+    tools.extend(["conda", "mamba", "micromamba"].map(String::from));
+    for tool in tools {
+        let answered = app.shell().command(&tool).arg("--version").output().await;
+        if answered.is_ok_and(|output| output.status.success()) {
+            return Some(tool);
+        }
+    }
+    None
+}
+
+/// Environments reported by `conda env list --json`, as (prefix, name).
+pub async fn conda_environments(app: &tauri::AppHandle) -> Vec<(PathBuf, String)> {
+    let Some(conda) = conda_tool(app).await else {
+        return Vec::new();
+    };
+    let Ok(output) = app.shell().command(&conda).args(["env", "list", "--json"]).output().await else {
+        return Vec::new();
+    };
+    let Ok(list) = serde_json::from_slice::<serde_json::Value>(&output.stdout) else {
+        return Vec::new();
+    };
+    list["envs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|env| env.as_str())
+        .map(|prefix| {
+            let prefix = PathBuf::from(prefix);
+            // The base environment is the install root, not under `envs/`.
+            let name = match prefix.parent().and_then(|p| p.file_name()) {
+                Some(parent) if parent == "envs" => prefix.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                _ => "base".to_string(),
+            };
+            (prefix, name)
+        })
+        .collect()
+}
+
+/// Every Python 3 interpreter found on PATH, through the `py` launcher, in
+/// pyenv and in conda environments, plus the app's venv and the configured
+/// one. Each is run once
+/// to read its version, so broken installs are left out.
+pub async fn discover(app: &tauri::AppHandle) -> Vec<PythonInterpreter> {
+    let settings = python::load_settings(app);
+    let configured = settings.interpreter;
+    let mut candidates: Vec<(PathBuf, InterpreterSource, Option<String>)> = Vec::new();
+    candidates.extend(on_path().into_iter().map(|p| (p, InterpreterSource::Path, None)));
+    candidates.extend(from_py_launcher(app).await.into_iter().map(|p| (p, InterpreterSource::PyLauncher, None)));
+    candidates.extend(from_pyenv().into_iter().map(|(p, v)| (p, InterpreterSource::Pyenv, Some(v))));
+    candidates.extend(
+        conda_environments(app)
+            .await
+            .into_iter()
+            .map(|(prefix, name)| (prefix_python(&prefix), InterpreterSource::Conda, Some(name)))
+            .filter(|(path, _, _)| path.is_file()),
+    );
+    if let Ok(dir) = venv::venv_dir(app) {
+        let python = venv::venv_python(&dir);
+        if python.is_file() {
+            candidates.push((python, InterpreterSource::Venv, None));
+        }
+    }
+    if let Some(path) = &configured {
+        candidates.push((PathBuf::from(path), InterpreterSource::Configured, None));
+    }
+
+    let mut seen = HashSet::new();
+    let mut found: Vec<PythonInterpreter> = Vec::new();
+    for (path, source, environment) in candidates {
+        let path = path.to_string_lossy().to_string();
+        let Ok(probe) = probe(app, &path).await else {
+            continue;
+        };
+        // Symlinks and shims often lead to the same install.
+        let key = std::fs::canonicalize(&probe.executable).unwrap_or_else(|_| PathBuf::from(&probe.executable));
+        let selected = configured.as_deref() == Some(path.as_str())
+            || (source == InterpreterSource::Conda && environment.is_some() && environment == settings.conda_env);
+        if !seen.insert(key) {
+            if let Some(existing) = found.iter_mut().find(|i| i.executable == probe.executable) {
+                existing.selected |= selected;
+                // An activated environment's python is also on PATH; keep the
+                // conda name so it can be picked as an environment.
+                if source == InterpreterSource::Conda {
+                    existing.source = source;
+                    existing.environment = environment;
+                }
+            }
+            continue;
+        }
+        found.push(PythonInterpreter {
+            path,
+            executable: probe.executable,
+            version: probe.version,
+            usable: probe.bits == 64,
+            bits: probe.bits,
+            source,
+            environment,
+            selected,
+        });
+    }
+    found
+}
+
+/// Checks that `path` runs a 64-bit Python 3 before it is stored.
+pub async fn check(app: &tauri::AppHandle, path: &str) -> Result<(), String> {
+    if !Path::new(path).is_file() {
+        return Err(format!("{} does not exist.", path));
+    }
+    let probe = probe(app, path).await?;
+    if probe.bits != 64 {
+        return Err(format!(
+            "{} is a 32-bit Python, which cannot load PyTorch. Choose a 64-bit install.",
+            probe.executable
+        ));
+    }
+    Ok(())
+}
+
+/// Makes `path` the interpreter scripts run with, after `check`; `None` goes
+/// back to searching PATH. Choosing an interpreter stops using conda.
+pub async fn select(app: &tauri::AppHandle, path: Option<String>) -> Result<PythonSettings, String> {
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(path) = &path {
+        check(app, path).await?;
+    }
+    let mut settings = python::load_settings(app);
+    if path.is_some() {
+        settings.conda_env = None;
+    }
+    settings.interpreter = path;
+    python::save_settings(app, &settings)?;
+    embedded_python::reset();
+    Ok(settings)
+}
+
+/// Packages the training scripts import, as found in the configured interpreter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DependencyStatus {
+    /// Whether any interpreter could be started; the rest is empty otherwise.
+    pub python: bool,
+    pub executable: Option<String>,
+    pub version: Option<String>,
+    pub pandas: bool,
+    pub sklearn: bool,
+    pub torch: bool,
+    pub timm: bool,
+    pub optuna: bool,
+    /// Why no interpreter could be started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+const DEPENDENCY_SCRIPT: &str = "import sys, json, importlib.util; p = lambda x: importlib.util.find_spec(x) is not None; print(json.dumps({'python': True, 'executable': sys.executable, 'version': sys.version.split()[0], 'pandas': p('pandas'), 'sklearn': p('sklearn'), 'torch': p('torch'), 'timm': p('timm'), 'optuna': p('optuna')}))";
+
+/// Never fails: a missing interpreter is reported as `python: false`, so the
+/// setup wizard can offer to install one.
+pub async fn dependencies(app: &tauri::AppHandle) -> DependencyStatus {
+    let result = match python::run_python_quick(app, &["-c", DEPENDENCY_SCRIPT]).await {
+        Ok(output) => python::parse_output("dependency check", &output),
+        Err(e) => Err(e),
+    };
+    result.unwrap_or_else(|e| DependencyStatus {
+        error: Some(e.message().to_string()),
+        ..DependencyStatus::default()
+    })
+}
@@ -0,0 +1,822 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::Manager;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use crate::app_settings::AppSettingsStore;
+use crate::architectures;
+use crate::checkpoints;
+use crate::cpu_fallback;
+use crate::downloads::{self, Downloads};
+use crate::error::BackendError;
+use crate::events;
+use crate::gpu_monitor;
+use crate::history::JobHistory;
+use crate::hpo;
+use crate::hooks;
+use crate::job_env;
+use crate::live_metrics;
+use crate::metrics;
+use crate::models;
+use crate::paths;
+use crate::predictions;
+use crate::preflight;
+use crate::projects::Projects;
+use crate::python::{backend_script, resolve_launcher};
+use crate::recents::{self, RecentKind};
+use crate::run_journal::RunJournal;
+use crate::training::TrainingConfig;
+use crate::webhooks::{self, JobLifecycle};
+
+/// One line of output from a job's process, published as `job://output`.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobOutput {
+    pub stream: &'static str,
+    pub line: String,
+}
+
+/// Published as `job://finished` when a job's process exits.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobFinished {
+    pub code: Option<i32>,
+    pub success: bool,
+    /// Stopped through `cancel_job`.
+    pub cancelled: bool,
+    /// A `resource_limit_exceeded` error when the supervisor killed the job.
+    pub error: Option<BackendError>,
+}
+
+/// Limits the supervisor enforces on a job by killing it; unset means none.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct JobLimits {
+    /// Wall-clock time from start.
+    pub timeout_secs: Option<u64>,
+    /// Resident memory of the job's process and everything it started, e.g.
+    /// DataLoader workers. Memory they share is counted once per process.
+    pub max_memory_mb: Option<u64>,
+}
+
+impl JobLimits {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if self.timeout_secs == Some(0) {
+            return Err(BackendError::invalid_input("The timeout must be at least 1 second."));
+        }
+        if self.max_memory_mb.is_some_and(|mb| mb < MIN_MEMORY_LIMIT_MB) {
+            return Err(BackendError::invalid_input(format!(
+                "The memory limit must be at least {} MB.",
+                MIN_MEMORY_LIMIT_MB
+            )));
+        }
+        Ok(())
+    }
+
+    fn is_set(&self) -> bool {
+        self.timeout_secs.is_some() || self.max_memory_mb.is_some()
+    }
+}
+
+/// The limit a job crossed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResourceLimit {
+    Timeout { limit_secs: u64 },
+    Memory { limit_mb: u64, used_mb: u64 },
+}
+
+impl ResourceLimit {
+    pub fn message(&self) -> String {
+        match self {
+            Self::Timeout { limit_secs } => format!("Stopped after reaching the {} s time limit.", limit_secs),
+            Self::Memory { limit_mb, used_mb } => {
+                format!("Stopped after using {} MB of memory, over the {} MB limit.", used_mb, limit_mb)
+            }
+        }
+    }
+}
+
+/// Most epochs a single `extend` can add.
+const MAX_EPOCH_EXTENSION: u32 = 1_000;
+
+/// An instruction for a running training job, appended as one JSON line to
+/// the control file script.py polls between batches.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum JobControl {
+    /// Finish the epoch in progress, then stop as if early stopping fired;
+    /// the test split is still evaluated.
+    StopAfterEpoch,
+    /// Write `checkpoint.pth` now and keep training.
+    SaveCheckpoint,
+    /// Raise the epoch count by `epochs`.
+    Extend { epochs: u32 },
+}
+
+impl JobControl {
+    fn validate(&self) -> Result<(), BackendError> {
+        match self {
+            Self::Extend { epochs } if !(1..=MAX_EPOCH_EXTENSION).contains(epochs) => {
+                Err(BackendError::invalid_input(format!(
+                    "A run can be extended by 1 to {} epochs at a time.",
+                    MAX_EPOCH_EXTENSION
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A running job as shown when the app is about to close.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ActiveJob {
+    pub job_id: String,
+    pub started_at: u64,
+    pub output_dir: Option<String>,
+    /// The job saves a checkpoint and exits when asked to stop, so it can be
+    /// resumed later; other jobs are killed.
+    pub checkpointable: bool,
+    /// The job accepts `send_job_control`.
+    pub controllable: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+
+    /// The serialized name, as stored in the job history.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [JobStatus::Queued, JobStatus::Running, JobStatus::Succeeded, JobStatus::Failed, JobStatus::Cancelled]
+            .into_iter()
+            .find(|status| status.as_str() == name)
+    }
+}
+
+/// One Python invocation: a training run, a plugin job, a streamed script or
+/// a one-shot call such as the GPU check.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct JobRecord {
+    pub job_id: String,
+    /// What the job runs: `training`, `plugin`, or the script name for
+    /// direct script calls, e.g. `check_gpu`.
+    pub kind: String,
+    /// Arguments the program was started with, including launcher arguments
+    /// such as `conda run -n <env> python`.
+    pub args: Vec<String>,
+    pub status: JobStatus,
+    pub queued_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub code: Option<i32>,
+    pub output_dir: Option<String>,
+    /// Why the job failed to start or run, when known.
+    pub error: Option<String>,
+}
+
+/// Job records kept in memory; the oldest finished ones are dropped first.
+const MAX_RECORDS: usize = 500;
+/// Finished jobs shown as recent completions.
+const RECENT_COMPLETIONS: usize = 20;
+/// Output lines kept per job for its history entry.
+const SUMMARY_LINES: usize = 20;
+/// How often the supervisor checks the limits of a running job.
+const LIMIT_POLL: Duration = Duration::from_secs(2);
+/// Python and PyTorch alone take about this much.
+const MIN_MEMORY_LIMIT_MB: u64 = 256;
+
+struct RunningJob {
+    child: CommandChild,
+    started_at: u64,
+    output_dir: Option<PathBuf>,
+    stop_file: Option<PathBuf>,
+    control_file: Option<PathBuf>,
+}
+
+/// Managed state holding the processes of running jobs.
+#[derive(Default)]
+pub struct JobManager {
+    children: Mutex<HashMap<String, RunningJob>>,
+    /// Every job of this session, oldest first.
+    records: Mutex<VecDeque<JobRecord>>,
+    /// Jobs cancelled by the user whose exit has not been handled yet.
+    cancelled: Mutex<HashSet<String>>,
+    /// Last output lines of unfinished jobs.
+    output: Mutex<HashMap<String, VecDeque<String>>>,
+    /// Jobs killed by the supervisor whose exit has not been handled yet.
+    exceeded: Mutex<HashMap<String, ResourceLimit>>,
+    /// Where finished jobs are persisted; set up once the data directory is known.
+    history: OnceLock<Arc<JobHistory>>,
+}
+
+/// Kills every process started by `pid`, deepest first; the caller kills
+/// `pid` itself. Killing only the direct child would orphan DataLoader
+/// workers and other helpers, which keep the GPU and files busy.
+pub fn kill_process_tree(pid: u32) {
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    let tree = process_tree(&system, pid);
+    for pid in tree.iter().skip(1).rev() {
+        if let Some(process) = system.process(*pid) {
+            process.kill();
+        }
+    }
+}
+
+/// `pid` followed by every process it started, parents before children.
+fn process_tree(system: &System, pid: u32) -> Vec<Pid> {
+    let mut tree = vec![Pid::from_u32(pid)];
+    let mut i = 0;
+    while i < tree.len() {
+        let parent = tree[i];
+        tree.extend(
+            system
+                .processes()
+                .iter()
+                .filter(|(_, p)| p.parent() == Some(parent))
+                .map(|(child, _)| *child),
+        );
+        i += 1;
+    }
+    tree
+}
+
+/// Resident memory of `pid` and its descendants, in MB.
+fn tree_memory_mb(pid: u32) -> u64 {
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::new().with_memory());
+    let bytes: u64 = process_tree(&system, pid).iter().filter_map(|pid| system.process(*pid)).map(|p| p.memory()).sum();
+    bytes / (1024 * 1024)
+}
+
+/// Kills the job once it crosses `limits`, checking every few seconds until it exits.
+fn supervise(manager: Arc<JobManager>, job_id: String, pid: u32, limits: JobLimits) {
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        loop {
+            tokio::time::sleep(LIMIT_POLL).await;
+            if !manager.is_running(&job_id) {
+                return;
+            }
+            let timed_out = limits.timeout_secs.filter(|limit| started.elapsed().as_secs() >= *limit);
+            let crossed = match (timed_out, limits.max_memory_mb) {
+                (Some(limit_secs), _) => Some(ResourceLimit::Timeout { limit_secs }),
+                (None, Some(limit_mb)) => {
+                    let used_mb = tauri::async_runtime::spawn_blocking(move || tree_memory_mb(pid)).await.unwrap_or(0);
+                    (used_mb > limit_mb).then_some(ResourceLimit::Memory { limit_mb, used_mb })
+                }
+                (None, None) => None,
+            };
+            if let Some(limit) = crossed {
+                tracing::warn!(job_id = %job_id, "{}", limit.message());
+                manager.exceeded.lock().unwrap().insert(job_id.clone(), limit);
+                if let Err(e) = manager.kill(&job_id) {
+                    manager.exceeded.lock().unwrap().remove(&job_id);
+                    tracing::warn!("Could not stop job {} over its limits: {}", job_id, e);
+                }
+                return;
+            }
+        }
+    });
+}
+
+impl JobManager {
+    /// Persists every job that finishes from now on.
+    pub fn attach_history(&self, history: Arc<JobHistory>) {
+        let _ = self.history.set(history);
+    }
+
+    /// Keeps a line of a job's output for its history summary.
+    pub fn note_output(&self, job_id: &str, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+        let mut output = self.output.lock().unwrap();
+        let lines = output.entry(job_id.to_string()).or_default();
+        if lines.len() == SUMMARY_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_string());
+    }
+
+    pub fn is_running(&self, job_id: &str) -> bool {
+        self.children.lock().unwrap().contains_key(job_id)
+    }
+
+    /// Jobs running now, of one kind or all.
+    pub fn running_count(&self, kind: Option<&str>) -> usize {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.status == JobStatus::Running && kind.is_none_or(|k| r.kind == k))
+            .count()
+    }
+
+    /// Kills a running job's process and everything it started;
+    /// `job://finished` follows as usual.
+    pub fn kill(&self, job_id: &str) -> Result<(), String> {
+        let job = self
+            .children
+            .lock()
+            .unwrap()
+            .remove(job_id)
+            .ok_or_else(|| format!("No running job with id {}", job_id))?;
+        kill_process_tree(job.child.pid());
+        job.child.kill().map_err(|e| e.to_string())
+    }
+
+    /// Kills a job at the user's request. Its `job://finished` event and
+    /// completion record are marked as cancelled.
+    pub fn cancel(&self, job_id: &str) -> Result<(), String> {
+        if !self.is_running(job_id) {
+            return Err(format!("No running job with id {}", job_id));
+        }
+        self.cancelled.lock().unwrap().insert(job_id.to_string());
+        self.kill(job_id).inspect_err(|_| {
+            self.cancelled.lock().unwrap().remove(job_id);
+        })
+    }
+
+    /// Records a job that is about to start.
+    pub fn queue(&self, job_id: &str, kind: &str, args: &[String], output_dir: Option<&Path>) {
+        let mut records = self.records.lock().unwrap();
+        Self::push_record(&mut records, job_id, kind, args, output_dir);
+    }
+
+    /// `queue` for ids chosen by the caller: refuses an id a queued or running
+    /// job already has, whose process could then no longer be cancelled.
+    pub fn queue_unique(&self, job_id: &str, kind: &str, args: &[String]) -> Result<(), BackendError> {
+        let mut records = self.records.lock().unwrap();
+        if records.iter().any(|r| r.job_id == job_id && !r.status.is_finished()) {
+            return Err(BackendError::invalid_input(format!("Job {} is already running; use a new job id.", job_id)));
+        }
+        Self::push_record(&mut records, job_id, kind, args, None);
+        Ok(())
+    }
+
+    fn push_record(
+        records: &mut VecDeque<JobRecord>,
+        job_id: &str,
+        kind: &str,
+        args: &[String],
+        output_dir: Option<&Path>,
+    ) {
+        records.push_back(JobRecord {
+            job_id: job_id.to_string(),
+            kind: kind.to_string(),
+            args: args.to_vec(),
+            status: JobStatus::Queued,
+            queued_at: unix_now(),
+            started_at: None,
+            finished_at: None,
+            code: None,
+            output_dir: output_dir.map(|d| d.to_string_lossy().to_string()),
+            error: None,
+        });
+        while records.len() > MAX_RECORDS {
+            match records.iter().position(|r| r.status.is_finished()) {
+                Some(oldest) => records.remove(oldest),
+                None => break,
+            };
+        }
+    }
+
+    fn update(&self, job_id: &str, change: impl FnOnce(&mut JobRecord)) {
+        if let Some(record) = self.records.lock().unwrap().iter_mut().rev().find(|r| r.job_id == job_id) {
+            change(record);
+        }
+    }
+
+    /// Marks a queued job as running.
+    pub fn start(&self, job_id: &str) {
+        self.update(job_id, |record| {
+            record.status = JobStatus::Running;
+            record.started_at = Some(unix_now());
+        });
+    }
+
+    /// Tracks the process of a job started outside `spawn_job`, e.g. a
+    /// streamed script, so it shows as active and can be cancelled.
+    pub fn track(&self, job_id: &str, child: CommandChild) {
+        self.children.lock().unwrap().insert(
+            job_id.to_string(),
+            RunningJob {
+                child,
+                started_at: unix_now(),
+                output_dir: None,
+                stop_file: None,
+                control_file: None,
+            },
+        );
+        self.start(job_id);
+    }
+
+    /// Records how a job ended and forgets its process. Returns whether it
+    /// was cancelled.
+    pub fn finish(&self, job_id: &str, code: Option<i32>, error: Option<String>) -> bool {
+        self.children.lock().unwrap().remove(job_id);
+        let cancelled = self.cancelled.lock().unwrap().remove(job_id);
+        let error = error.or_else(|| self.exceeded.lock().unwrap().get(job_id).map(ResourceLimit::message));
+        self.update(job_id, |record| {
+            record.status = if cancelled {
+                JobStatus::Cancelled
+            } else if code == Some(0) && error.is_none() {
+                JobStatus::Succeeded
+            } else {
+                JobStatus::Failed
+            };
+            record.finished_at = Some(unix_now());
+            record.code = code;
+            record.error = error;
+        });
+        let summary: Vec<String> = self.output.lock().unwrap().remove(job_id).unwrap_or_default().into();
+        if let (Some(history), Ok(record)) = (self.history.get(), self.get(job_id)) {
+            if let Err(e) = history.record(&record, &summary.join("\n")) {
+                tracing::error!("Failed to save job {} to the history: {}", job_id, e);
+            }
+        }
+        cancelled
+    }
+
+    /// The limit the supervisor killed a job for, once it has finished.
+    pub fn take_exceeded(&self, job_id: &str) -> Option<ResourceLimit> {
+        self.exceeded.lock().unwrap().remove(job_id)
+    }
+
+    /// Every job of this session, newest first.
+    pub fn list(&self) -> Vec<JobRecord> {
+        self.records.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    pub fn get(&self, job_id: &str) -> Result<JobRecord, String> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|r| r.job_id == job_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown job {}", job_id))
+    }
+
+    /// Forgets finished jobs; queued and running ones stay. Returns how many
+    /// were removed.
+    pub fn clear(&self) -> usize {
+        let mut records = self.records.lock().unwrap();
+        let before = records.len();
+        records.retain(|r| !r.status.is_finished());
+        before - records.len()
+    }
+
+    /// Oldest first.
+    pub fn active(&self) -> Vec<ActiveJob> {
+        let children = self.children.lock().unwrap();
+        let mut jobs: Vec<ActiveJob> = children
+            .iter()
+            .map(|(id, job)| ActiveJob {
+                job_id: id.clone(),
+                started_at: job.started_at,
+                output_dir: job.output_dir.as_ref().map(|d| d.to_string_lossy().to_string()),
+                checkpointable: job.stop_file.is_some(),
+                controllable: job.control_file.is_some(),
+            })
+            .collect();
+        jobs.sort_by_key(|j| j.started_at);
+        jobs
+    }
+
+    /// Jobs that finished during this session, newest first.
+    pub fn recent_completions(&self) -> Vec<JobRecord> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|r| r.status.is_finished())
+            .take(RECENT_COMPLETIONS)
+            .cloned()
+            .collect()
+    }
+
+    /// Asks a job that supports it to checkpoint and exit. Returns false for
+    /// jobs that can only be killed.
+    pub fn request_stop(&self, job_id: &str) -> Result<bool, String> {
+        let children = self.children.lock().unwrap();
+        let job = children
+            .get(job_id)
+            .ok_or_else(|| format!("No running job with id {}", job_id))?;
+        match &job.stop_file {
+            Some(path) => std::fs::write(path, b"stop").map(|_| true).map_err(|e| e.to_string()),
+            None => Ok(false),
+        }
+    }
+
+    /// Appends `control` to the job's control file. The file only grows, so
+    /// the job reads every line written since its last poll.
+    pub fn send_control(&self, job_id: &str, control: JobControl) -> Result<(), String> {
+        control.validate()?;
+        let children = self.children.lock().unwrap();
+        let job = children
+            .get(job_id)
+            .ok_or_else(|| format!("No running job with id {}", job_id))?;
+        let path = job
+            .control_file
+            .as_ref()
+            .ok_or_else(|| format!("Job {} does not accept controls; only training jobs do.", job_id))?;
+        let mut line = serde_json::to_string(&control).map_err(|e| e.to_string())?;
+        line.push('\n');
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Cannot open the control file {}: {}", path.display(), e))?;
+        file.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        tracing::info!(job_id = %job_id, ?control, "Job control sent");
+        Ok(())
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Unique id with a readable prefix, e.g. `job_1718000000123_0`.
+pub fn new_id(prefix: &str) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    format!("{}_{}_{}", prefix, millis, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// A process to run as a job.
+#[derive(Debug, Clone, Default)]
+pub struct JobSpec {
+    /// Shown in the job list, e.g. `training`.
+    pub kind: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// Variables for this job; they take precedence over the global defaults.
+    pub env: Vec<(String, String)>,
+    /// Where the job writes its artifacts. Post-job hooks only run for jobs
+    /// that set this, so hook-launched plugin jobs cannot trigger hooks again.
+    pub output_dir: Option<PathBuf>,
+    /// File the job polls for; once it exists the job checkpoints and exits.
+    pub stop_file: Option<PathBuf>,
+    /// File the job reads `JobControl` lines from.
+    pub control_file: Option<PathBuf>,
+    pub limits: JobLimits,
+}
+
+/// Spawns `spec` and forwards its output as `job://output` events until it
+/// exits, then emits `job://finished` and runs post-job hooks. Returns the job id.
+pub fn spawn_job(app: &tauri::AppHandle, manager: &Arc<JobManager>, job_id: String, spec: JobSpec) -> Result<String, String> {
+    let mut command = app.shell().command(&spec.program).args(&spec.args);
+    if let Some(cwd) = spec.cwd {
+        command = command.current_dir(cwd);
+    }
+    let env = job_env::merged(app, spec.env);
+    if !env.is_empty() {
+        command = command.envs(env);
+    }
+    if let Some(settings) = app.try_state::<Arc<AppSettingsStore>>() {
+        settings.check_capacity(manager, &spec.kind)?;
+    }
+
+    manager.queue(&job_id, &spec.kind, &spec.args, spec.output_dir.as_deref());
+    let (mut rx, child) = match command.spawn() {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            let error = format!("Failed to start {}: {}", spec.program, e);
+            manager.finish(&job_id, None, Some(error.clone()));
+            return Err(error);
+        }
+    };
+    let pid = child.pid();
+    manager.children.lock().unwrap().insert(
+        job_id.clone(),
+        RunningJob {
+            child,
+            started_at: unix_now(),
+            output_dir: spec.output_dir.clone(),
+            stop_file: spec.stop_file.clone(),
+            control_file: spec.control_file.clone(),
+        },
+    );
+    manager.start(&job_id);
+    tracing::info!(job_id = %job_id, kind = %spec.kind, pid, "Job started");
+    if spec.limits.is_set() {
+        supervise(manager.clone(), job_id.clone(), pid, spec.limits);
+    }
+    webhooks::notify(app, JobLifecycle::Started, &job_id, None);
+    if spec.kind == "training" {
+        gpu_monitor::watch(app, &job_id);
+    }
+
+    let app = app.clone();
+    let manager = manager.clone();
+    let id = job_id.clone();
+    let output_dir = spec.output_dir;
+    let stop_file = spec.stop_file;
+    let control_file = spec.control_file;
+    let kind = spec.kind;
+    tauri::async_runtime::spawn(async move {
+        let mut code = None;
+        while let Some(event) = rx.recv().await {
+            let (stream, bytes) = match event {
+                CommandEvent::Stdout(bytes) => ("stdout", bytes),
+                CommandEvent::Stderr(bytes) => ("stderr", bytes),
+                CommandEvent::Terminated(payload) => {
+                    code = payload.code;
+                    break;
+                }
+                _ => continue,
+            };
+            let line = String::from_utf8_lossy(&bytes).trim_end().to_string();
+            manager.note_output(&id, &line);
+            if stream == "stdout" {
+                if let Some(dir) = output_dir.as_ref().filter(|_| kind == "training") {
+                    checkpoints::on_output_line(dir, &line);
+                }
+                if kind == "hpo" {
+                    hpo::record_line(&app, &id, &line);
+                }
+                cpu_fallback::inspect(&app, &id, &line);
+                if !live_metrics::record_line(&app, &id, &line) {
+                    predictions::record_line(&app, &id, &line);
+                }
+            }
+            events::publish(&app, "job://output", Some(&id), JobOutput { stream, line });
+        }
+        let cancelled = manager.finish(&id, code, None);
+        let exceeded = manager.take_exceeded(&id);
+        tracing::info!(job_id = %id, ?code, cancelled, "Job finished");
+        gpu_monitor::unwatch(&app, &id);
+        if kind == "hpo" {
+            hpo::finish(&app, &id, code, cancelled);
+        }
+        if let Some(journal) = app.try_state::<Arc<RunJournal>>().filter(|_| kind == "training") {
+            if let Err(e) = journal.finish(&id, code, cancelled) {
+                tracing::warn!("Could not update the unfinished run journal for job {}: {}", id, e);
+            }
+        }
+        for path in stop_file.into_iter().chain(control_file) {
+            let _ = std::fs::remove_file(path);
+        }
+        events::publish(
+            &app,
+            "job://finished",
+            Some(&id),
+            JobFinished {
+                code,
+                success: code == Some(0) && exceeded.is_none(),
+                cancelled,
+                error: exceeded.map(BackendError::resource_limit),
+            },
+        );
+        let lifecycle = if cancelled {
+            JobLifecycle::Cancelled
+        } else if code == Some(0) {
+            JobLifecycle::Succeeded
+        } else {
+            JobLifecycle::Failed
+        };
+        webhooks::notify(&app, lifecycle, &id, code);
+        if let Some(dir) = output_dir.as_ref().filter(|_| kind == "training" && lifecycle == JobLifecycle::Succeeded) {
+            if let Err(e) = models::register(&app, &dir.to_string_lossy(), None) {
+                tracing::warn!("Could not register the model of job {}: {}", id, e);
+            }
+        }
+        // Hooks act on results; a cancelled job has none worth processing.
+        if let Some(dir) = output_dir.filter(|_| !cancelled) {
+            hooks::run_post_job(&app, &manager, &id, &dir, code).await;
+        }
+    });
+
+    Ok(job_id)
+}
+
+/// Prepares and validates `config`, then launches script.py in the background.
+/// Returns the job id; output arrives through `job://output` events.
+pub async fn start_training(
+    app: &tauri::AppHandle,
+    manager: &Arc<JobManager>,
+    mut config: TrainingConfig,
+) -> Result<String, BackendError> {
+    let job_id = new_id("job");
+    if config.experiment_id.is_none() {
+        config.experiment_id = Some(job_id.clone());
+    }
+    let projects = app.try_state::<Arc<Projects>>();
+    if let Some(projects) = &projects {
+        projects.place_run(&mut config)?;
+    }
+    if let Some(settings) = app.try_state::<Arc<AppSettingsStore>>() {
+        settings.place_run(&mut config)?;
+    }
+    architectures::resolve(app, &mut config)?;
+    metrics::resolve(app, &mut config)?;
+    config.prepare()?;
+    config.validate(None)?;
+    // Refuses runs that would fill the disk mid-epoch, before any output is written.
+    preflight::check_training(&config).into_result().map_err(|e| e.to_string())?;
+
+    if config.checkpoints.every_epoch {
+        checkpoints::save_policy(&config.output_dir(), &config.checkpoints)?;
+    }
+    // Fetched here, with progress and retries, rather than silently by torchvision.
+    if config.architecture.is_none() && config.resume.is_none() && downloads::is_known(&config.model) {
+        if let Some(downloads) = app.try_state::<Arc<Downloads>>() {
+            downloads::download_pretrained(app, downloads.inner(), &config.model).await?;
+        }
+    }
+
+    let script = backend_script(app, "script.py")?;
+    let stop_file = paths::cache_file(app, &format!("{}.stop", job_id))?;
+    let control_file = paths::cache_file(app, &format!("{}.control", job_id))?;
+    let mut args = vec![script];
+    args.extend(config.to_args());
+    args.push("--stop_file".to_string());
+    args.push(stop_file.to_string_lossy().to_string());
+    args.push("--control_file".to_string());
+    args.push(control_file.to_string_lossy().to_string());
+    args.push("--job_env".to_string());
+    args.push(job_env::manifest_entry(app, &config.env).to_string());
+
+    let launcher = resolve_launcher(app).await?;
+    let spec = JobSpec {
+        kind: "training".to_string(),
+        program: launcher.program.clone(),
+        args: launcher.with_args(args),
+        env: config.env.clone().into_iter().collect(),
+        output_dir: Some(config.output_dir()),
+        stop_file: Some(stop_file),
+        control_file: Some(control_file),
+        limits: config.limits,
+        ..Default::default()
+    };
+    let job_id = spawn_job(app, manager, job_id, spec)?;
+    if let Some(journal) = app.try_state::<Arc<RunJournal>>() {
+        if let Err(e) = journal.record(&job_id, &config) {
+            tracing::warn!("Job {} cannot be resumed after a crash: {}", job_id, e);
+        }
+    }
+    if let Some(projects) = &projects {
+        projects.record_run(&config);
+    }
+    if let Err(e) = recents::add(app, RecentKind::Dataset, &config.path) {
+        tracing::warn!("Cannot record the dataset in recents: {}", e);
+    }
+    Ok(job_id)
+}
+
+/// Starts an unfinished training run again from its last checkpoint, as a
+/// new job with the same config and experiment. Returns the new job's id.
+pub async fn resume_training(app: &tauri::AppHandle, manager: &Arc<JobManager>, job_id: &str) -> Result<String, String> {
+    if manager.is_running(job_id) {
+        return Err(format!("Job {} is still running.", job_id));
+    }
+    let journal = app.state::<Arc<RunJournal>>().inner().clone();
+    let run = journal.get(job_id).ok_or_else(|| format!("No unfinished training run with id {}", job_id))?;
+    let checkpoint = run.checkpoint.ok_or_else(|| {
+        format!("{} has no checkpoint to resume from; no epoch had completed. Start the run again.", run.output_dir)
+    })?;
+    let mut config = run.config;
+    config.resume = Some(checkpoint);
+    let resumed = start_training(app, manager, config).await?;
+    journal.remove(job_id)?;
+    Ok(resumed)
+}
@@ -0,0 +1,476 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::Manager;
+
+use crate::events;
+use crate::paths;
+
+/// Jobs whose series are kept in memory; the oldest are dropped first.
+const MAX_JOBS: usize = 50;
+/// Fields of a `training` status line that are not metrics.
+const NON_METRIC_FIELDS: &[&str] = &["status", "epoch", "total_epochs", "message"];
+/// Short names a script may print, and the metric each one stands for.
+const METRIC_ALIASES: &[(&str, &str)] = &[
+    ("loss", "train_loss"),
+    ("acc", "train_accuracy"),
+    ("accuracy", "train_accuracy"),
+    ("train_acc", "train_accuracy"),
+    ("val_acc", "val_accuracy"),
+];
+/// Relative spread of the ETA range before two epochs have been timed.
+const EARLY_SPREAD: f64 = 0.3;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MetricsSettings {
+    /// EMA weight of the previous smoothed value, in [0, 1). 0 turns
+    /// smoothing off; higher values smooth more.
+    pub smoothing: f64,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self { smoothing: 0.6 }
+    }
+}
+
+/// One metric of a job, with the raw values and their EMA at each epoch.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct MetricSeries {
+    pub epochs: Vec<u32>,
+    pub raw: Vec<f64>,
+    pub smoothed: Vec<f64>,
+}
+
+/// Estimated time left, assuming every remaining epoch runs (early stopping
+/// can only make it shorter).
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TrainingEta {
+    pub seconds: f64,
+    /// Range from the spread of epoch times so far.
+    pub low_seconds: f64,
+    pub high_seconds: f64,
+    /// Mean wall time of the epochs completed so far, or the projection of
+    /// the current one before any completed.
+    pub epoch_seconds: f64,
+}
+
+/// Where a training job is, published as `job://progress`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TrainingProgress {
+    pub epoch: u32,
+    pub total_epochs: Option<u32>,
+    /// Batches done in the current epoch, training and validation together.
+    pub step: u32,
+    pub steps: u32,
+    pub elapsed_seconds: f64,
+    /// Wall time of each completed epoch.
+    pub epoch_times: Vec<f64>,
+    /// Mean wall time per batch in the current epoch.
+    pub batch_seconds: Option<f64>,
+    pub eta: Option<TrainingEta>,
+}
+
+/// Metrics of one completed epoch, published as `training://metrics`.
+///
+/// Scripts report an epoch with one JSON line on stdout: either a `training`
+/// status line or a bare `{"epoch": 3, "loss": 0.41, "val_acc": 0.87}`.
+/// Values may be numbers or numeric strings; `loss`, `acc` and `val_acc` are
+/// read as `train_loss`, `train_accuracy` and `val_accuracy`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct EpochMetrics {
+    /// 1-based.
+    pub epoch: u32,
+    pub total_epochs: Option<u32>,
+    pub train_loss: Option<f64>,
+    pub train_accuracy: Option<f64>,
+    pub val_loss: Option<f64>,
+    pub val_accuracy: Option<f64>,
+    pub lr: Option<f64>,
+    /// Any other numeric field of the line.
+    pub extra: BTreeMap<String, f64>,
+}
+
+impl EpochMetrics {
+    fn from_values(epoch: u32, total_epochs: Option<u32>, mut values: BTreeMap<String, f64>) -> Self {
+        Self {
+            epoch,
+            total_epochs,
+            train_loss: values.remove("train_loss"),
+            train_accuracy: values.remove("train_accuracy"),
+            val_loss: values.remove("val_loss"),
+            val_accuracy: values.remove("val_accuracy"),
+            lr: values.remove("lr"),
+            extra: values,
+        }
+    }
+}
+
+/// What a metrics line changed for its job.
+pub struct Recorded {
+    pub progress: Option<TrainingProgress>,
+    /// Set for lines that completed an epoch.
+    pub metrics: Option<EpochMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct JobMetrics {
+    pub job_id: String,
+    /// Smoothing factor the `smoothed` values were computed with.
+    pub smoothing: f64,
+    pub total_epochs: Option<u32>,
+    pub series: BTreeMap<String, MetricSeries>,
+    pub progress: Option<TrainingProgress>,
+}
+
+struct Collected {
+    total_epochs: Option<u32>,
+    /// Metric name to `(epoch, value)` in arrival order.
+    series: BTreeMap<String, Vec<(u32, f64)>>,
+    started: Instant,
+    epoch_started: Instant,
+    epoch_times: Vec<f64>,
+    /// Epoch in progress (1-based) and its `(step, steps)`.
+    epoch: u32,
+    position: Option<(u32, u32)>,
+}
+
+impl Collected {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            total_epochs: None,
+            series: BTreeMap::new(),
+            started: now,
+            epoch_started: now,
+            epoch_times: Vec::new(),
+            epoch: 1,
+            position: None,
+        }
+    }
+
+    /// Share of the current epoch done and the mean time per batch.
+    fn current_epoch(&self) -> (f64, Option<f64>) {
+        match self.position {
+            Some((step, steps)) if step > 0 && steps > 0 => {
+                let elapsed = self.epoch_started.elapsed().as_secs_f64();
+                ((step as f64 / steps as f64).min(1.0), Some(elapsed / step as f64))
+            }
+            _ => (0.0, None),
+        }
+    }
+
+    fn eta(&self) -> Option<TrainingEta> {
+        let total = self.total_epochs?;
+        let (fraction, batch_seconds) = self.current_epoch();
+        let times = &self.epoch_times;
+        let mean = if times.is_empty() {
+            batch_seconds? * self.position?.1 as f64
+        } else {
+            times.iter().sum::<f64>() / times.len() as f64
+        };
+        // Epochs still to run, counting the rest of the current one.
+        let epochs_left = total.saturating_sub(self.epoch) as f64 + (1.0 - fraction);
+        let seconds = epochs_left * mean;
+        let margin = if times.len() < 2 {
+            EARLY_SPREAD * seconds
+        } else {
+            let variance = times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (times.len() - 1) as f64;
+            // Independent per-epoch deviations grow with the square root of the count.
+            2.0 * variance.sqrt() * epochs_left.sqrt()
+        };
+        Some(TrainingEta {
+            seconds,
+            low_seconds: (seconds - margin).max(0.0),
+            high_seconds: seconds + margin,
+            epoch_seconds: mean,
+        })
+    }
+
+    fn progress(&self) -> Option<TrainingProgress> {
+        let (step, steps) = self.position.unwrap_or((0, 0));
+        if self.position.is_none() && self.epoch_times.is_empty() {
+            return None;
+        }
+        Some(TrainingProgress {
+            epoch: self.epoch,
+            total_epochs: self.total_epochs,
+            step,
+            steps,
+            elapsed_seconds: self.started.elapsed().as_secs_f64(),
+            epoch_times: self.epoch_times.clone(),
+            batch_seconds: self.current_epoch().1,
+            eta: self.eta(),
+        })
+    }
+}
+
+/// Managed state: metric series and timing parsed from the `training` and
+/// `progress` status lines of running and recent jobs, and the smoothing
+/// setting (`metrics_settings.json`).
+pub struct MetricsCollector {
+    settings: Mutex<MetricsSettings>,
+    settings_path: PathBuf,
+    jobs: Mutex<HashMap<String, Collected>>,
+    order: Mutex<VecDeque<String>>,
+}
+
+/// Debiased exponential moving average, as TensorBoard draws it: early
+/// values are not pulled towards zero.
+fn ema(values: &[f64], smoothing: f64) -> Vec<f64> {
+    let mut smoothed = Vec::with_capacity(values.len());
+    let mut last = 0.0;
+    let mut weight = 1.0;
+    for value in values {
+        last = last * smoothing + (1.0 - smoothing) * value;
+        weight *= smoothing;
+        smoothed.push(if weight < 1.0 { last / (1.0 - weight) } else { *value });
+    }
+    smoothed
+}
+
+/// Metric values may be printed as numbers or as formatted strings.
+fn number(value: &serde_json::Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        .filter(|v: &f64| v.is_finite())
+}
+
+/// Numeric metrics of an epoch line under their canonical names.
+fn metric_values(line: &serde_json::Map<String, serde_json::Value>) -> BTreeMap<String, f64> {
+    let mut values = BTreeMap::new();
+    for (name, field) in line {
+        if NON_METRIC_FIELDS.contains(&name.as_str()) {
+            continue;
+        }
+        let Some(v) = number(field) else {
+            continue;
+        };
+        match METRIC_ALIASES.iter().find(|(alias, _)| alias == name) {
+            // An explicit canonical field wins over its alias.
+            Some((_, canonical)) => {
+                values.entry(canonical.to_string()).or_insert(v);
+            }
+            None => {
+                values.insert(name.clone(), v);
+            }
+        }
+    }
+    values
+}
+
+impl MetricsCollector {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let settings_path = paths::settings_file(app, "metrics_settings.json")?;
+        let settings = std::fs::read_to_string(&settings_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            settings: Mutex::new(settings),
+            settings_path,
+            jobs: Mutex::new(HashMap::new()),
+            order: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    pub fn settings(&self) -> MetricsSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, settings: MetricsSettings) -> Result<(), String> {
+        if !(0.0..1.0).contains(&settings.smoothing) {
+            return Err("Smoothing must be at least 0 and below 1.".to_string());
+        }
+        if let Some(dir) = self.settings_path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.settings_path, raw).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Stores the metrics and timing of `line` when it is a `training` or
+    /// `progress` status line, or a bare epoch metrics line. Returns `None`
+    /// for any other line.
+    pub fn record(&self, job_id: &str, line: &str) -> Option<Recorded> {
+        let value = serde_json::from_str::<serde_json::Value>(line.trim()).ok()?;
+        let status = match value.get("status") {
+            Some(status) => status.as_str()?,
+            None => "training",
+        };
+        if status != "training" && status != "progress" {
+            return None;
+        }
+        let epoch = value["epoch"].as_u64()? as u32;
+        let values = if status == "training" { metric_values(value.as_object()?) } else { BTreeMap::new() };
+        if value.get("status").is_none() && values.is_empty() {
+            return None;
+        }
+        let mut jobs = self.jobs.lock().unwrap();
+        if !jobs.contains_key(job_id) {
+            let mut order = self.order.lock().unwrap();
+            order.push_back(job_id.to_string());
+            while order.len() > MAX_JOBS {
+                if let Some(oldest) = order.pop_front() {
+                    jobs.remove(&oldest);
+                }
+            }
+        }
+        let collected = jobs.entry(job_id.to_string()).or_insert_with(Collected::new);
+        collected.total_epochs = value["total_epochs"].as_u64().map(|t| t as u32).or(collected.total_epochs);
+
+        if status == "progress" {
+            if epoch != collected.epoch {
+                // A resumed run starts past epoch 1.
+                collected.epoch = epoch;
+                collected.epoch_started = Instant::now();
+            }
+            if let (Some(step), Some(steps)) = (value["step"].as_u64(), value["steps"].as_u64()) {
+                collected.position = Some((step as u32, steps as u32));
+            }
+            return Some(Recorded { progress: collected.progress(), metrics: None });
+        }
+
+        let now = Instant::now();
+        collected.epoch_times.push(now.duration_since(collected.epoch_started).as_secs_f64());
+        collected.epoch_started = now;
+        collected.epoch = epoch + 1;
+        collected.position = None;
+        for (name, v) in &values {
+            collected.series.entry(name.clone()).or_default().push((epoch, *v));
+        }
+        Some(Recorded {
+            progress: collected.progress(),
+            metrics: Some(EpochMetrics::from_values(epoch, collected.total_epochs, values)),
+        })
+    }
+
+    /// Where a job is, if it reported progress.
+    pub fn progress(&self, job_id: &str) -> Option<TrainingProgress> {
+        self.jobs.lock().unwrap().get(job_id).and_then(Collected::progress)
+    }
+
+    /// Raw and smoothed series of a job, smoothed with `smoothing` or the
+    /// configured factor.
+    pub fn get(&self, job_id: &str, smoothing: Option<f64>) -> Result<JobMetrics, String> {
+        let smoothing = smoothing.unwrap_or_else(|| self.settings().smoothing);
+        if !(0.0..1.0).contains(&smoothing) {
+            return Err("Smoothing must be at least 0 and below 1.".to_string());
+        }
+        let jobs = self.jobs.lock().unwrap();
+        let collected = jobs
+            .get(job_id)
+            .ok_or_else(|| format!("No metrics recorded for job {}.", job_id))?;
+        let series = collected
+            .series
+            .iter()
+            .map(|(name, points)| {
+                let raw: Vec<f64> = points.iter().map(|(_, v)| *v).collect();
+                let series = MetricSeries {
+                    epochs: points.iter().map(|(e, _)| *e).collect(),
+                    smoothed: ema(&raw, smoothing),
+                    raw,
+                };
+                (name.clone(), series)
+            })
+            .collect();
+        Ok(JobMetrics {
+            job_id: job_id.to_string(),
+            smoothing,
+            total_epochs: collected.total_epochs,
+            series,
+            progress: collected.progress(),
+        })
+    }
+}
+
+/// Feeds a job's stdout line to the collector, if it is managed, and
+/// publishes the updated progress and, after an epoch, its metrics as
+/// `training://metrics`. Returns whether the line was a metrics line.
+pub fn record_line(app: &tauri::AppHandle, job_id: &str, line: &str) -> bool {
+    let Some(collector) = app.try_state::<Arc<MetricsCollector>>() else {
+        return false;
+    };
+    let Some(recorded) = collector.record(job_id, line) else {
+        return false;
+    };
+    if let Some(metrics) = recorded.metrics {
+        events::publish(app, "training://metrics", Some(job_id), metrics);
+    }
+    if let Some(progress) = recorded.progress {
+        events::publish(app, "job://progress", Some(job_id), progress);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collected(
+        total_epochs: Option<u32>,
+        epoch: u32,
+        epoch_times: Vec<f64>,
+        position: Option<(u32, u32)>,
+    ) -> Collected {
+        Collected { total_epochs, epoch, epoch_times, position, ..Collected::new() }
+    }
+
+    #[test]
+    fn ema_starts_at_the_first_sample() {
+        for smoothing in [0.0, 0.6, 0.99] {
+            let smoothed = ema(&[0.8], smoothing);
+            assert_eq!(smoothed.len(), 1);
+            assert!((smoothed[0] - 0.8).abs() < 1e-9, "{}", smoothed[0]);
+        }
+        assert!(ema(&[], 0.6).is_empty());
+    }
+
+    #[test]
+    fn ema_keeps_a_constant_series() {
+        for value in ema(&[2.5; 20], 0.9) {
+            assert!((value - 2.5).abs() < 1e-9, "{}", value);
+        }
+    }
+
+    #[test]
+    fn ema_without_smoothing_is_the_raw_series() {
+        let raw = [1.0, 3.0, 2.0, 5.0];
+        assert_eq!(ema(&raw, 0.0), raw.to_vec());
+        let smoothed = ema(&raw, 0.5);
+        assert!(smoothed[1] > 1.0 && smoothed[1] < 3.0);
+    }
+
+    #[test]
+    fn eta_needs_total_epochs_and_a_timing() {
+        assert!(collected(None, 2, vec![10.0], None).eta().is_none());
+        assert!(collected(Some(5), 1, Vec::new(), None).eta().is_none());
+        // No batch done yet, or a script reporting zero steps, gives no projection.
+        assert!(collected(Some(5), 1, Vec::new(), Some((0, 100))).eta().is_none());
+        assert!(collected(Some(5), 1, Vec::new(), Some((5, 0))).eta().is_none());
+    }
+
+    #[test]
+    fn eta_counts_the_remaining_epochs() {
+        let eta = collected(Some(5), 3, vec![10.0, 10.0], None).eta().unwrap();
+        assert_eq!(eta.epoch_seconds, 10.0);
+        assert_eq!(eta.seconds, 30.0);
+        assert_eq!((eta.low_seconds, eta.high_seconds), (30.0, 30.0));
+    }
+
+    #[test]
+    fn eta_handles_zero_elapsed_time() {
+        let eta = collected(Some(3), 2, vec![0.0, 0.0], None).eta().unwrap();
+        assert_eq!((eta.seconds, eta.low_seconds, eta.high_seconds), (0.0, 0.0, 0.0));
+        let eta = collected(Some(3), 1, Vec::new(), Some((1, 10))).eta().unwrap();
+        assert!(eta.seconds.is_finite() && eta.seconds >= 0.0);
+        assert!(eta.low_seconds <= eta.seconds && eta.seconds <= eta.high_seconds);
+    }
+}
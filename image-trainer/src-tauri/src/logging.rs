@@ -0,0 +1,152 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tauri::Manager;
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter};
+
+/// Log files are named `epoq.<date>.log`, one per day.
+const FILE_PREFIX: &str = "epoq";
+const FILE_SUFFIX: &str = "log";
+/// Days of logs kept; older files are deleted on rotation.
+const MAX_FILES: usize = 7;
+pub const DEFAULT_TAIL: usize = 200;
+const MAX_TAIL: usize = 5_000;
+/// Overrides the level, in `RUST_LOG` syntax, e.g. `debug` or `warn,app=trace`.
+const FILTER_VAR: &str = "EPOQ_LOG";
+
+/// One line of the log file.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LogEntry {
+    /// RFC 3339, UTC.
+    pub timestamp: String,
+    pub level: String,
+    /// Module that wrote the entry.
+    pub target: String,
+    pub message: String,
+    /// Structured values recorded with the message, e.g. `job_id`.
+    pub fields: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct RawEntry {
+    timestamp: String,
+    level: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    fields: BTreeMap<String, serde_json::Value>,
+}
+
+/// Managed state: the log folder, and the guard that flushes the file
+/// writer when the app exits.
+pub struct Logging {
+    dir: PathBuf,
+    _guard: WorkerGuard,
+}
+
+/// Sends `tracing` events to stderr and, as JSON lines, to a daily log file
+/// in the app's log folder. Call once, before anything logs.
+pub fn init(app: &tauri::AppHandle) -> Result<Logging, String> {
+    let dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create the log folder {}: {}", dir.display(), e))?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix(FILE_SUFFIX)
+        .max_log_files(MAX_FILES)
+        .build(&dir)
+        .map_err(|e| format!("Cannot open the log file in {}: {}", dir.display(), e))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    let filter = EnvFilter::try_from_env(FILTER_VAR).unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(fmt::layer().json().with_ansi(false).with_writer(writer))
+        .try_init()
+        .map_err(|e| e.to_string())?;
+    Ok(Logging { dir, _guard: guard })
+}
+
+/// Log files, oldest first; the date in their names sorts them.
+fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            name.starts_with(FILE_PREFIX) && name.ends_with(FILE_SUFFIX)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn parse(line: &str) -> Option<LogEntry> {
+    let mut raw: RawEntry = serde_json::from_str(line).ok()?;
+    let message = match raw.fields.remove("message") {
+        Some(serde_json::Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    Some(LogEntry { timestamp: raw.timestamp, level: raw.level, target: raw.target, message, fields: raw.fields })
+}
+
+impl Logging {
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The last `tail_n` entries at `level` or more severe, oldest first.
+    pub fn tail(&self, level: Option<&str>, tail_n: Option<usize>) -> Result<Vec<LogEntry>, String> {
+        let min = match level {
+            Some(level) => Level::from_str(level)
+                .map_err(|_| format!("Unknown log level '{}'; use error, warn, info, debug or trace.", level))?,
+            None => Level::TRACE,
+        };
+        let tail_n = tail_n.unwrap_or(DEFAULT_TAIL);
+        if !(1..=MAX_TAIL).contains(&tail_n) {
+            return Err(format!("Between 1 and {} entries can be read at a time.", MAX_TAIL));
+        }
+        let mut entries = Vec::new();
+        // Newest file first, until enough entries are found.
+        for file in log_files(&self.dir).iter().rev() {
+            let raw = std::fs::read_to_string(file).map_err(|e| format!("Cannot read {}: {}", file.display(), e))?;
+            let matching = raw
+                .lines()
+                .rev()
+                .filter_map(parse)
+                // Levels compare by verbosity: ERROR is the smallest.
+                .filter(|entry| Level::from_str(&entry.level).is_ok_and(|l| l <= min));
+            entries.extend(matching.take(tail_n - entries.len()));
+            if entries.len() == tail_n {
+                break;
+            }
+        }
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Shows the log folder in the system file manager.
+    pub fn open_dir(&self) -> Result<(), String> {
+        #[cfg(target_os = "windows")]
+        let program = "explorer";
+        #[cfg(target_os = "macos")]
+        let program = "open";
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let program = "xdg-open";
+        std::process::Command::new(program)
+            .arg(&self.dir)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Cannot open {}: {}", self.dir.display(), e))
+    }
+}
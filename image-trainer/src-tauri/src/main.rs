@@ -1,165 +1,2233 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod active_learning;
+mod adapters;
+mod annotations;
+mod api;
+mod api_server;
+mod app_settings;
+mod architectures;
+mod audit;
+mod augmentation;
+mod backups;
+mod batch_report;
+mod capture;
+mod checkpoints;
+mod classes;
+mod cli;
+mod clipboard;
+mod confidence;
+mod cpu_fallback;
+mod dashboard;
+mod database;
+mod dataset;
+mod dataset_scan;
+mod dataset_split;
+mod dedupe;
+mod dev_mode;
+mod downloads;
+mod drift;
+mod embedded_python;
+mod error;
+mod evaluation;
+mod events;
+mod gallery;
+mod gpu_monitor;
+mod hardware;
+mod hf_hub;
+mod history;
+mod hooks;
+mod hpo;
+mod html;
+mod i18n;
+mod image_meta;
+mod interpreters;
+mod job_env;
+mod jobs;
+mod live_metrics;
+mod logging;
+mod metrics;
+mod model_card;
+mod models;
+mod onnx;
+mod paths;
+mod plugins;
+mod ports;
+mod predictions;
+mod preflight;
+mod preprocessing;
+mod presets;
+mod profiles;
+mod project_archive;
+mod projects;
+mod python;
+mod recents;
+mod report;
+mod resource_monitor;
+mod run_journal;
+mod run_manifest;
+mod schedule;
+mod server_security;
+mod settings_transfer;
+mod shutdown;
+mod startup_check;
+mod sweep;
+mod synthetic;
+mod table_profile;
+mod tables;
+mod tabular;
+mod tabular_pipeline;
+mod thumbnails;
+mod training;
+mod updater;
+mod venv;
+mod verify;
+mod wasm_plugins;
+mod watch;
+mod webhooks;
+
+use active_learning::{LabelingSelection, SelectionStrategy};
+use annotations::{AnnotationStore, LabelingBatch, LabelingStats, ReviewItem, ReviewStatus};
+use api::{ApiDescriptor, ApiHandshake};
+use api_server::{ApiServer, ApiServerStatus, StartedApiServer};
+use app_settings::{AppSettings, AppSettingsStore};
+use architectures::{ArchitectureDescriptor, ArchitectureInfo};
+use audit::{AuditExportFormat, AuditLog, AuditSettings};
+use augmentation::AugmentationPolicy;
+use backups::{BackupInfo, BackupManager, BackupRun, BackupSettings};
+use batch_report::BatchSummary;
+use capture::{CameraInfo, CaptureInfo, CaptureManager};
+use checkpoints::{CheckpointList, CheckpointPolicy, PruneReport};
+use classes::{ClassEntry, ClassSpec};
+use clipboard::{ClipboardFormat, PayloadKind};
+use confidence::ConfidenceReport;
+use dashboard::{Dashboard, DashboardSnapshot};
+use dataset_scan::DatasetScan;
+use dataset_split::{SplitMode, SplitRatios, SplitReport};
+use dedupe::DedupeReport;
+use dev_mode::{DevMode, DevModeStatus};
+use downloads::{DownloadedWeights, Downloads};
+use error::BackendError;
+use drift::{DriftMonitor, DriftReport, DriftSettings};
+use evaluation::EvaluationReport;
+use events::{Envelope, EventBus};
+use gallery::Misclassification;
+use gpu_monitor::{GpuDevice, GpuMonitor, GpuStats};
+use hardware::{GpuReport, SystemInfo};
+use hf_hub::{HfDownload, HfKind, HfRepo, HfTokenStatus};
+use history::{HistoryEntry, HistoryFilter, JobHistory};
+use hooks::PostJobHook;
+use hpo::{HpoConfig, HpoManager, HpoStudy};
+use i18n::{LanguageSettings, LanguageStatus, Localizer};
+use image_meta::ImageMetadata;
+use job_env::JobEnvSettings;
+use interpreters::{DependencyStatus, PythonInterpreter};
+use jobs::{JobControl, JobManager, JobRecord};
+use live_metrics::{JobMetrics, MetricsCollector, MetricsSettings};
+use logging::{LogEntry, Logging};
+use metrics::{MetricRef, RunComparison};
+use model_card::{CardFormat, ModelCard};
+use models::RegisteredModel;
+use onnx::OnnxExport;
+use paths::StorageLocations;
+use plugins::{PluginInfo, PluginRunOutput};
+use ports::{PortManager, ServiceEndpoint};
+use predictions::{BatchPredictionRequest, BatchPredictionResult, ExportFormat, PredictionRecord, PredictionRequest};
+use preflight::{PreflightJob, PreflightReport};
+use presets::{PackInfo, PresetLibrary};
+use profiles::{Profile, Profiles, ProfilesStatus};
+use project_archive::{ProjectExport, ProjectImport};
+use projects::{OpenProject, Project, Projects};
+use python::{backend_script, run_python_resolved, BackendStatus, PythonDiagnosis, PythonSettings};
+use recents::{RecentItem, RecentKind};
+use report::ReportFormat;
+use resource_monitor::{ResourceMonitor, ResourceMonitorStatus};
+use run_journal::{RunJournal, UnfinishedRun};
+use schedule::{ScheduledJob, Scheduler, Trigger};
+use server_security::{
+    ApiKeyInfo, IssuedApiKey, ServerSecurity, ServerSecuritySettings, ServerSecurityStatus, TlsCertificateInfo,
+};
+use settings_transfer::{SettingsFile, SettingsImportReport};
+use shutdown::{ActiveWork, ShutdownState};
+use startup_check::{StartupCheck, StartupReport};
+use std::sync::Arc;
+use sweep::{SweepConfig, SweepManager, SweepProgress};
+use synthetic::MaterializeReport;
+use table_profile::TableProfile;
+use tables::{DbTable, TablePage};
+use tabular::{TabularRequest, TabularResponse};
+use tabular_pipeline::{Pipeline, TabularPipelines};
 use tauri::Manager;
-use tauri_plugin_shell::ShellExt;
+use thumbnails::{Thumbnail, ThumbnailCache};
+use wasm_plugins::{HookResult, WasmPluginInfo};
+use watch::{WatchInfo, WatchManager};
+use webhooks::{JobLifecycle, Webhook, WebhookDelivery};
+use training::{
+    BatchProbeAttempt, BatchSizeProbeReport, ReproducibilityCheck, TrainingConfig,
+    TrainingConfigReport, MAX_PROBE_BATCH_SIZE,
+};
+use updater::{UpdateInfo, Updates};
+use venv::VenvStatus;
+use verify::VerifyReport;
+
+/// Runs tabular_processor.py on a load or process request and returns the
+/// parsed result. With a `job_id`, output is also streamed line by line as
+/// `python://stdout` / `python://stderr`. With a `session_id`, process runs
+/// are recorded as steps of that session's pipeline so they can be undone.
+#[tauri::command]
+async fn run_tabular_processor(
+    app: tauri::AppHandle,
+    pipelines: tauri::State<'_, Arc<TabularPipelines>>,
+    request: TabularRequest,
+    job_id: Option<String>,
+    session_id: Option<String>,
+) -> Result<TabularResponse, BackendError> {
+    match session_id {
+        Some(session_id) => pipelines.run(&app, &session_id, &request, job_id.as_deref()).await,
+        None => tabular::run(&app, &request, job_id.as_deref()).await,
+    }
+}
+
+/// The steps applied in a tabular session and those that can be redone.
+#[tauri::command]
+fn get_pipeline(pipelines: tauri::State<'_, Arc<TabularPipelines>>, session_id: String) -> Pipeline {
+    pipelines.get(&session_id)
+}
+
+/// Restores the table the last step of the session changed to how it was before.
+#[tauri::command]
+fn undo_tabular_step(
+    pipelines: tauri::State<'_, Arc<TabularPipelines>>,
+    session_id: String,
+) -> Result<Pipeline, BackendError> {
+    pipelines.undo(&session_id)
+}
+
+/// Applies the most recently undone step of the session again.
+#[tauri::command]
+async fn redo_tabular_step(
+    app: tauri::AppHandle,
+    pipelines: tauri::State<'_, Arc<TabularPipelines>>,
+    session_id: String,
+) -> Result<Pipeline, BackendError> {
+    pipelines.redo(&app, &session_id).await
+}
+
+/// A page of a CSV, TSV, Parquet, Feather or Excel file read natively: `rows`
+/// rows (100 by default) from `offset`, with column types and missing counts
+/// for the whole file.
+#[tauri::command]
+async fn preview_table(file: String, rows: Option<usize>, offset: Option<usize>) -> Result<TablePage, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || tables::preview(&file, rows, offset))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+/// Tables and views of an SQLite database, each with the `sqlite://` source
+/// the tabular commands take.
+#[tauri::command]
+fn list_db_tables(path: String) -> Result<Vec<DbTable>, BackendError> {
+    Ok(tables::list_db_tables(std::path::Path::new(&path))?)
+}
+
+/// Column types, missing counts, cardinality, ranges, histograms and the most
+/// frequent values of a table, computed natively so the dataset health panel
+/// works without pandas.
+#[tauri::command]
+async fn profile_table(file: String) -> Result<TableProfile, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || table_profile::profile(&file))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+/// Runs check_gpu.py. Adapters found through wgpu are added when the probe
+/// fails or sees no CUDA device.
+#[tauri::command]
+async fn run_check_gpu(app: tauri::AppHandle) -> Result<GpuReport, BackendError> {
+    hardware::check_gpu(&app).await
+}
+
+/// Utilization, VRAM, temperature and power of every NVIDIA GPU right now,
+/// read through NVML or, when the driver library is missing, check_gpu.py.
+/// While training runs the same readings arrive every second as `gpu://stats`.
+#[tauri::command]
+async fn get_gpu_stats(app: tauri::AppHandle) -> Result<GpuStats, BackendError> {
+    gpu_monitor::sample(&app).await
+}
+
+/// The GPUs training and prediction can be pinned to with their `device`
+/// field, indexed in PCI bus order.
+#[tauri::command]
+async fn list_gpu_devices(app: tauri::AppHandle) -> Result<Vec<GpuDevice>, BackendError> {
+    gpu_monitor::list_devices(&app).await
+}
+
+/// Publishes CPU load, RAM and swap use and disk throughput as
+/// `system://resources` every `interval_ms` (1000 by default) until stopped,
+/// read natively instead of through system_info.py.
+#[tauri::command]
+fn start_resource_monitor(
+    app: tauri::AppHandle,
+    monitor: tauri::State<'_, Arc<ResourceMonitor>>,
+    interval_ms: Option<u64>,
+) -> Result<ResourceMonitorStatus, BackendError> {
+    Ok(monitor.start(&app, interval_ms)?)
+}
+
+#[tauri::command]
+fn stop_resource_monitor(monitor: tauri::State<'_, Arc<ResourceMonitor>>) -> ResourceMonitorStatus {
+    monitor.stop();
+    monitor.status()
+}
+
+/// Runs system_info.py and returns its hardware and Python details.
+#[tauri::command]
+async fn get_system_info(app: tauri::AppHandle) -> Result<SystemInfo, BackendError> {
+    hardware::system_info(&app).await
+}
+
+/// Validates a training config and reports the effective batch size along with
+/// warnings when the batch does not fit the detected GPU memory.
+#[tauri::command]
+async fn validate_training_config(
+    app: tauri::AppHandle,
+    mut config: TrainingConfig,
+) -> Result<TrainingConfigReport, BackendError> {
+    architectures::resolve(&app, &mut config)?;
+    metrics::resolve(&app, &mut config)?;
+    config.resolve_seed();
+    let imbalance_warning = config.imbalance_warning();
+    // VRAM is best-effort: validation still runs when the GPU probe fails.
+    let available_vram_mb = hardware::check_gpu(&app).await.ok().and_then(|gpu| gpu.vram_total_mb);
+    let mut report = config.validate(available_vram_mb)?;
+    report.warnings.extend(imbalance_warning);
+    Ok(report)
+}
+
+/// Estimates the disk space a training run or export needs and compares it
+/// with the free space on the target drive. `run_training` and
+/// `export_model_onnx` refuse to start when `ok` is false.
+#[tauri::command]
+async fn preflight_check(app: tauri::AppHandle, mut job: PreflightJob) -> Result<PreflightReport, BackendError> {
+    if let PreflightJob::Training { config } = &mut job {
+        architectures::resolve(&app, config)?;
+    }
+    // Sizing a dataset for its zip walks every file.
+    Ok(tauri::async_runtime::spawn_blocking(move || preflight::check(&job)).await.map_err(|e| e.to_string())?)
+}
+
+/// Resolves managed values in a training config (seed, `class_weights: "auto"`)
+/// and returns the config that should be passed to the script.
+#[tauri::command]
+fn prepare_training_config(app: tauri::AppHandle, mut config: TrainingConfig) -> Result<TrainingConfig, BackendError> {
+    architectures::resolve(&app, &mut config)?;
+    metrics::resolve(&app, &mut config)?;
+    config.prepare()?;
+    Ok(config)
+}
+
+/// Compares the seed recorded in a finished run's manifest with the requested one.
+#[tauri::command]
+fn verify_run_reproducibility(config: TrainingConfig) -> Result<ReproducibilityCheck, BackendError> {
+    Ok(config.verify_reproducibility()?)
+}
+
+/// Runs one batch_probe.py pass at `batch_size`. OOM is reported as `fits: false`;
+/// any other failure aborts the search since bigger batches would fail the same way.
+async fn probe_batch_size(
+    app: &tauri::AppHandle,
+    script: &str,
+    config: &TrainingConfig,
+    batch_size: u32,
+) -> Result<(BatchProbeAttempt, Option<String>), BackendError> {
+    let batch = batch_size.to_string();
+    let architecture = config
+        .architecture
+        .as_ref()
+        .map(|a| serde_json::to_string(a).unwrap_or_default());
+    let mut args = vec![
+        script,
+        "--model",
+        config.model.as_str(),
+        "--batch_size",
+        batch.as_str(),
+        "--freeze_mode",
+        config.freeze_policy.mode(),
+    ];
+    if let Some(architecture) = &architecture {
+        args.extend(["--architecture", architecture.as_str()]);
+    }
+
+    // Only the interpreter training would use: an OOM must not be retried with others.
+    match run_python_resolved(app, &args).await {
+        Ok(output) => {
+            let result = output
+                .lines()
+                .rev()
+                .find_map(|line| serde_json::from_str::<serde_json::Value>(line.trim()).ok())
+                .unwrap_or_default();
+            if result["status"] == "probe_skipped" {
+                return Err(BackendError::invalid_input(
+                    "No CUDA device available; batch size probing only applies to GPU training.",
+                ));
+            }
+            let attempt = BatchProbeAttempt {
+                batch_size,
+                fits: true,
+                peak_memory_mb: result["peak_memory_mb"].as_u64(),
+                error: None,
+            };
+            Ok((attempt, result["device"].as_str().map(str::to_string)))
+        }
+        Err(e) if training::is_out_of_memory(e.message()) => Ok((
+            BatchProbeAttempt {
+                batch_size,
+                fits: false,
+                peak_memory_mb: None,
+                error: Some("CUDA out of memory".to_string()),
+            },
+            None,
+        )),
+        Err(e) => Err(e.context(&format!("Batch size probe failed at batch size {}", batch_size))),
+    }
+}
+
+/// Finds the largest batch size that fits on the GPU by doubling until a probe
+/// runs out of memory, then bisecting between the last fit and the first failure.
+#[tauri::command]
+async fn find_max_batch_size(
+    app: tauri::AppHandle,
+    mut config: TrainingConfig,
+) -> Result<BatchSizeProbeReport, BackendError> {
+    architectures::resolve(&app, &mut config)?;
+    let script = backend_script(&app, "batch_probe.py")?;
+
+    let mut attempts = Vec::new();
+    let mut device = None;
+    let mut fits = 0u32;
+    let mut fails: Option<u32> = None;
+
+    // Exponential phase
+    let mut batch_size = 1u32;
+    while batch_size <= MAX_PROBE_BATCH_SIZE {
+        let (attempt, name) = probe_batch_size(&app, &script, &config, batch_size).await?;
+        device = device.or(name);
+        let ok = attempt.fits;
+        attempts.push(attempt);
+        if !ok {
+            fails = Some(batch_size);
+            break;
+        }
+        fits = batch_size;
+        batch_size *= 2;
+    }
+
+    if fits == 0 {
+        return Err(format!("{} does not fit in GPU memory even at batch size 1.", config.model).into());
+    }
+
+    // Binary search between the last fit and the first OOM
+    if let Some(mut hi) = fails {
+        let mut lo = fits;
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            let (attempt, name) = probe_batch_size(&app, &script, &config, mid).await?;
+            device = device.or(name);
+            if attempt.fits {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+            attempts.push(attempt);
+        }
+        fits = lo;
+    }
+
+    let effective = config.batch_size.saturating_mul(config.accumulation_steps.max(1));
+    Ok(BatchSizeProbeReport::new(device, fits, effective, attempts))
+}
+
+/// Starts an AutoML sweep, running one trial worker per free GPU. Progress is
+/// reported through `automl://progress` and `automl://complete` events.
+#[tauri::command]
+async fn start_automl_sweep(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, Arc<SweepManager>>,
+    config: SweepConfig,
+) -> Result<String, BackendError> {
+    let device_count = hardware::check_gpu(&app).await.map_or(0, |gpu| gpu.device_count);
+    let available_gpus: Vec<u32> = (0..device_count).collect();
+    Ok(sweep::start(app, manager.inner().clone(), config, available_gpus).await?)
+}
+
+/// Stops every worker of a running sweep.
+#[tauri::command]
+fn cancel_automl_sweep(manager: tauri::State<'_, Arc<SweepManager>>, sweep_id: String) -> Result<(), BackendError> {
+    Ok(manager.cancel(&sweep_id)?)
+}
+
+#[tauri::command]
+fn list_automl_sweeps(manager: tauri::State<'_, Arc<SweepManager>>) -> Vec<SweepProgress> {
+    manager.list()
+}
+
+/// Queues a training run to start at a given time or once the machine is idle.
+/// Schedules survive restarts and are reported via `schedule://started`.
+#[tauri::command]
+fn schedule_training(
+    app: tauri::AppHandle,
+    scheduler: tauri::State<'_, Arc<Scheduler>>,
+    mut config: TrainingConfig,
+    trigger: Trigger,
+) -> Result<ScheduledJob, BackendError> {
+    architectures::resolve(&app, &mut config)?;
+    metrics::resolve(&app, &mut config)?;
+    Ok(scheduler.add(config, trigger)?)
+}
+
+#[tauri::command]
+fn list_scheduled_jobs(scheduler: tauri::State<'_, Arc<Scheduler>>) -> Vec<ScheduledJob> {
+    scheduler.list()
+}
+
+#[tauri::command]
+fn cancel_scheduled_job(scheduler: tauri::State<'_, Arc<Scheduler>>, id: String) -> Result<(), BackendError> {
+    Ok(scheduler.remove(&id)?)
+}
+
+/// Lists user plugins from the plugins directory, including invalid ones with their errors.
+#[tauri::command]
+fn list_plugins(app: tauri::AppHandle) -> Result<Vec<PluginInfo>, BackendError> {
+    Ok(plugins::discover(&app)?)
+}
+
+/// Runs a plugin as a job in its own working directory and returns the job id.
+/// Plugins are trusted code; they are not sandboxed.
+#[tauri::command]
+async fn run_plugin(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, Arc<JobManager>>,
+    name: String,
+    inputs: serde_json::Map<String, serde_json::Value>,
+) -> Result<String, BackendError> {
+    Ok(plugins::run(&app, jobs.inner(), &name, inputs).await?)
+}
+
+#[tauri::command]
+fn get_plugin_outputs(app: tauri::AppHandle, name: String, job_id: String) -> Result<Vec<PluginRunOutput>, BackendError> {
+    Ok(plugins::run_outputs(&app, &name, &job_id)?)
+}
+
+#[tauri::command]
+fn list_wasm_plugins(app: tauri::AppHandle) -> Result<Vec<WasmPluginInfo>, BackendError> {
+    Ok(wasm_plugins::discover(&app)?)
+}
+
+/// Passes `payload` through every sandboxed WASM plugin that implements `hook`.
+#[tauri::command]
+async fn run_wasm_hook(app: tauri::AppHandle, hook: String, payload: serde_json::Value) -> Result<HookResult, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || wasm_plugins::run_hook(&app, &hook, payload))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+#[tauri::command]
+fn get_dev_mode(dev: tauri::State<'_, Arc<DevMode>>) -> DevModeStatus {
+    dev.status()
+}
+
+/// Points backend script resolution at a local checkout (`None` restores the
+/// bundled scripts), so script changes apply without rebuilding the app.
+#[tauri::command]
+fn set_script_dir_override(dev: tauri::State<'_, Arc<DevMode>>, dir: Option<String>) -> Result<DevModeStatus, BackendError> {
+    dev.set_script_dir(dir.map(std::path::PathBuf::from))?;
+    Ok(dev.status())
+}
+
+/// Writes a job's stored prediction results to `dest` as CSV, JSON or Excel.
+/// Returns the number of exported rows.
+#[tauri::command]
+fn export_predictions(app: tauri::AppHandle, job_id: String, format: ExportFormat, dest: String) -> Result<usize, BackendError> {
+    Ok(predictions::export(&app, &job_id, format, std::path::Path::new(&dest))?)
+}
+
+/// Copies predictions, a run's metrics or its config to the clipboard as TSV,
+/// Markdown or JSON, and returns the copied text.
+#[tauri::command]
+fn copy_to_clipboard(
+    app: tauri::AppHandle,
+    payload_kind: PayloadKind,
+    id: String,
+    format: Option<ClipboardFormat>,
+) -> Result<String, BackendError> {
+    Ok(clipboard::copy(&app, payload_kind, &id, format.unwrap_or_default())?)
+}
+
+/// Builds a model card for a run directory from its manifest and evaluation,
+/// and stores it next to the weights.
+#[tauri::command]
+fn generate_model_card(l10n: tauri::State<'_, Arc<Localizer>>, model_id: String) -> Result<ModelCard, BackendError> {
+    Ok(model_card::generate(&model_id, &l10n)?)
+}
+
+/// Writes a run's model card to `dest` as Markdown or JSON.
+#[tauri::command]
+fn export_model_card(
+    l10n: tauri::State<'_, Arc<Localizer>>,
+    model_id: String,
+    format: CardFormat,
+    dest: String,
+) -> Result<(), BackendError> {
+    Ok(model_card::export(&model_id, format, std::path::Path::new(&dest), &l10n)?)
+}
+
+#[tauri::command]
+fn get_audit_settings(audit: tauri::State<'_, Arc<AuditLog>>) -> AuditSettings {
+    audit.settings()
+}
+
+/// Turns the inference audit log on or off and applies the retention period.
+#[tauri::command]
+fn set_audit_settings(audit: tauri::State<'_, Arc<AuditLog>>, settings: AuditSettings) -> Result<AuditSettings, BackendError> {
+    audit.set_settings(settings)?;
+    Ok(audit.settings())
+}
+
+/// Writes logged predictions (optionally limited to a unix-time range) to
+/// `dest` as CSV or JSON. Returns the number of rows.
+#[tauri::command]
+fn export_audit_log(
+    audit: tauri::State<'_, Arc<AuditLog>>,
+    format: AuditExportFormat,
+    dest: String,
+    since: Option<u64>,
+    until: Option<u64>,
+) -> Result<usize, BackendError> {
+    Ok(audit.export(format, std::path::Path::new(&dest), since, until)?)
+}
+
+/// Class-distribution and confidence statistics of a model's recent logged
+/// predictions compared with its training run.
+#[tauri::command]
+fn get_drift_report(
+    audit: tauri::State<'_, Arc<AuditLog>>,
+    monitor: tauri::State<'_, Arc<DriftMonitor>>,
+    model_id: String,
+) -> Result<DriftReport, BackendError> {
+    Ok(drift::report(&audit, &model_id, &monitor.settings())?)
+}
+
+#[tauri::command]
+fn get_drift_settings(monitor: tauri::State<'_, Arc<DriftMonitor>>) -> DriftSettings {
+    monitor.settings()
+}
+
+#[tauri::command]
+fn set_drift_settings(
+    monitor: tauri::State<'_, Arc<DriftMonitor>>,
+    settings: DriftSettings,
+) -> Result<DriftSettings, BackendError> {
+    monitor.set_settings(settings)?;
+    Ok(monitor.settings())
+}
+
+/// Fetches the pretrained weights of a built-in model into the shared cache
+/// the backend reads, resuming interrupted downloads and verifying the
+/// checksum. Progress arrives as `download://progress`.
+#[tauri::command]
+async fn download_pretrained(
+    app: tauri::AppHandle,
+    downloads: tauri::State<'_, Arc<Downloads>>,
+    model_name: String,
+) -> Result<DownloadedWeights, BackendError> {
+    Ok(downloads::download_pretrained(&app, downloads.inner(), &model_name).await?)
+}
+
+/// Searches Hugging Face Hub models or datasets (`kind`, default models),
+/// using the stored access token if there is one.
+#[tauri::command]
+async fn hf_search(query: String, kind: Option<HfKind>) -> Result<Vec<HfRepo>, BackendError> {
+    Ok(hf_hub::search(&query, kind.unwrap_or_default()).await?)
+}
+
+/// Downloads a Hugging Face repository at `revision` (default `main`) into
+/// the model store, or the dataset store for datasets. Models trained with
+/// this app come back `registrable`; add them with `register_model` once the
+/// source is trusted. Progress arrives as `download://progress`.
+#[tauri::command]
+async fn hf_download(
+    app: tauri::AppHandle,
+    downloads: tauri::State<'_, Arc<Downloads>>,
+    repo_id: String,
+    revision: Option<String>,
+    kind: Option<HfKind>,
+) -> Result<HfDownload, BackendError> {
+    Ok(hf_hub::download(&app, downloads.inner(), &repo_id, revision.as_deref(), kind.unwrap_or_default()).await?)
+}
+
+/// Whether a Hugging Face access token is set, in the keyring or as `HF_TOKEN`.
+#[tauri::command]
+fn hf_token_status() -> HfTokenStatus {
+    hf_hub::token_status()
+}
+
+/// Stores a Hugging Face access token in the OS keyring; `None` removes it.
+#[tauri::command]
+fn set_hf_token(token: Option<String>) -> Result<HfTokenStatus, BackendError> {
+    Ok(hf_hub::set_token(token.as_deref())?)
+}
+
+/// Starts training `config` (dataset path, model, epochs, batch size, learning
+/// rate, output directory and the rest) with script.py. Returns the job id;
+/// output arrives as `job://output`, progress as `job://progress`, each
+/// epoch's metrics as `training://metrics`, and the exit as `job://finished`.
+#[tauri::command]
+async fn run_training(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, Arc<JobManager>>,
+    config: TrainingConfig,
+) -> Result<String, BackendError> {
+    Ok(jobs::start_training(&app, manager.inner(), config).await?)
+}
+
+/// Training runs that crashed, were stopped or were cut off by the app
+/// closing, with the checkpoint each can resume from.
+#[tauri::command]
+fn list_unfinished_runs(
+    journal: tauri::State<'_, Arc<RunJournal>>,
+    manager: tauri::State<'_, Arc<JobManager>>,
+) -> Vec<UnfinishedRun> {
+    journal.list(|job_id| manager.is_running(job_id))
+}
+
+/// Relaunches an unfinished run with `--resume <checkpoint>`; returns the new job id.
+#[tauri::command]
+async fn resume_training(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, Arc<JobManager>>,
+    job_id: String,
+) -> Result<String, BackendError> {
+    Ok(jobs::resume_training(&app, manager.inner(), &job_id).await?)
+}
+
+/// Starts an Optuna study over learning rate, batch size and optimizer. Trials
+/// appear in the job list and stream as `hpo://trial`; the best trial is saved
+/// and registered as a model with its parameters when the study ends.
+#[tauri::command]
+async fn run_hpo(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, Arc<JobManager>>,
+    hpo: tauri::State<'_, Arc<HpoManager>>,
+    config: HpoConfig,
+) -> Result<HpoStudy, BackendError> {
+    Ok(hpo::start(&app, jobs.inner(), hpo.inner(), config).await?)
+}
+
+#[tauri::command]
+fn get_hpo_study(hpo: tauri::State<'_, Arc<HpoManager>>, study_id: String) -> Result<HpoStudy, BackendError> {
+    Ok(hpo.get(&study_id)?)
+}
+
+#[tauri::command]
+fn list_hpo_studies(hpo: tauri::State<'_, Arc<HpoManager>>) -> Vec<HpoStudy> {
+    hpo.list()
+}
+
+/// Holds the next trial of a study until `resume_hpo`; the running one finishes.
+#[tauri::command]
+fn pause_hpo(
+    app: tauri::AppHandle,
+    hpo: tauri::State<'_, Arc<HpoManager>>,
+    study_id: String,
+) -> Result<HpoStudy, BackendError> {
+    let study = hpo.pause(&study_id)?;
+    events::publish(&app, "hpo://status", Some(&study_id), study.clone());
+    Ok(study)
+}
+
+#[tauri::command]
+fn resume_hpo(
+    app: tauri::AppHandle,
+    hpo: tauri::State<'_, Arc<HpoManager>>,
+    study_id: String,
+) -> Result<HpoStudy, BackendError> {
+    let study = hpo.resume(&study_id)?;
+    events::publish(&app, "hpo://status", Some(&study_id), study.clone());
+    Ok(study)
+}
+
+/// Ends a study after its running trial; `cancel_job` with the study id
+/// kills it at once.
+#[tauri::command]
+fn stop_hpo(
+    app: tauri::AppHandle,
+    hpo: tauri::State<'_, Arc<HpoManager>>,
+    study_id: String,
+) -> Result<HpoStudy, BackendError> {
+    let study = hpo.stop(&study_id)?;
+    events::publish(&app, "hpo://status", Some(&study_id), study.clone());
+    Ok(study)
+}
+
+/// The latest, best and per-epoch checkpoints of a run, with their sizes.
+#[tauri::command]
+async fn list_checkpoints(run_id: String) -> Result<CheckpointList, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || checkpoints::list(&run_id)).await.map_err(|e| e.to_string())?
+}
+
+/// Deletes one checkpoint of a run; returns what is left.
+#[tauri::command]
+async fn delete_checkpoint(run_id: String, name: String) -> Result<CheckpointList, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || checkpoints::delete(&run_id, &name))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Stores `policy` for the run (or uses the stored one) and deletes the
+/// epoch checkpoints it does not keep.
+#[tauri::command]
+async fn prune_checkpoints(run_id: String, policy: Option<CheckpointPolicy>) -> Result<PruneReport, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = std::path::Path::new(&run_id);
+        let policy = match policy {
+            Some(policy) => {
+                checkpoints::save_policy(dir, &policy)?;
+                policy
+            }
+            None => checkpoints::load_policy(dir),
+        };
+        checkpoints::prune(&run_id, &policy)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Copies a checkpoint into a new run directory next to the run and registers
+/// it as a model.
+#[tauri::command]
+async fn promote_checkpoint_to_model(
+    app: tauri::AppHandle,
+    run_id: String,
+    name: String,
+) -> Result<RegisteredModel, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || checkpoints::promote(&app, &run_id, &name))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Classifies one image with a trained model (its run directory), applying the
+/// preprocessing stored with it. `classes` optionally relabels the outputs and
+/// `device` picks the GPU.
+#[tauri::command]
+async fn run_prediction(app: tauri::AppHandle, request: PredictionRequest) -> Result<PredictionRecord, BackendError> {
+    request.validate()?;
+    let PredictionRequest { model_id, image, classes, device } = request;
+    let (_, records, failures) =
+        predictions::predict(&app, &model_id, &[image], classes.as_ref(), device.as_deref()).await?;
+    records
+        .into_iter()
+        .next()
+        .ok_or_else(|| BackendError::from(failures.into_iter().next().unwrap_or_else(|| "No prediction returned.".to_string())))
+}
+
+/// Classifies every image in a folder in batches, publishing each result as a
+/// `predict://result` event for `job_id`, and optionally writes all of them
+/// to `dest` as CSV, JSON or Excel.
+#[tauri::command]
+async fn run_batch_prediction(
+    app: tauri::AppHandle,
+    request: BatchPredictionRequest,
+    job_id: Option<String>,
+) -> Result<BatchPredictionResult, BackendError> {
+    if !std::path::Path::new(&request.folder).is_dir() {
+        return Err(BackendError::invalid_input(format!("{} is not a folder.", request.folder)));
+    }
+    let job_id = job_id.unwrap_or_else(|| jobs::new_id("predict"));
+    Ok(predictions::predict_folder(&app, &request, &job_id).await?)
+}
+
+/// Classifies every image dropped into `path` with `model_id` until stopped.
+/// Results arrive as `watch://prediction` and `watch://failed` events whose
+/// job id is the returned watch id.
+#[tauri::command]
+fn start_watch_folder(
+    app: tauri::AppHandle,
+    watches: tauri::State<'_, Arc<WatchManager>>,
+    path: String,
+    model_id: String,
+    classes: Option<ClassSpec>,
+) -> Result<WatchInfo, BackendError> {
+    Ok(watches.start(&app, &path, &model_id, classes)?)
+}
+
+#[tauri::command]
+fn stop_watch_folder(watches: tauri::State<'_, Arc<WatchManager>>, watch_id: String) -> Result<(), BackendError> {
+    Ok(watches.stop(&watch_id)?)
+}
+
+#[tauri::command]
+fn list_watch_folders(watches: tauri::State<'_, Arc<WatchManager>>) -> Vec<WatchInfo> {
+    watches.list()
+}
+
+#[tauri::command]
+fn list_cameras() -> Result<Vec<CameraInfo>, BackendError> {
+    Ok(capture::list_cameras()?)
+}
+
+/// Classifies frames from camera `camera_index` (the first one by default)
+/// with `model_id` at up to `fps` frames per second until stopped. Frames
+/// arrive as `capture://frame` events whose job id is the returned capture id,
+/// followed by one `capture://stopped`.
+#[tauri::command]
+async fn start_capture(
+    app: tauri::AppHandle,
+    captures: tauri::State<'_, Arc<CaptureManager>>,
+    model_id: String,
+    camera_index: Option<u32>,
+    fps: Option<f64>,
+    classes: Option<ClassSpec>,
+) -> Result<CaptureInfo, BackendError> {
+    Ok(captures.start(&app, &model_id, camera_index.unwrap_or(0), fps, classes).await?)
+}
+
+#[tauri::command]
+fn stop_capture(captures: tauri::State<'_, Arc<CaptureManager>>, capture_id: String) -> Result<(), BackendError> {
+    Ok(captures.stop(&capture_id)?)
+}
+
+#[tauri::command]
+fn list_captures(captures: tauri::State<'_, Arc<CaptureManager>>) -> Vec<CaptureInfo> {
+    captures.list()
+}
+
+/// Checks a dataset's folder layout without starting Python: images per
+/// class, empty classes, unsupported files and duplicate names.
+#[tauri::command]
+async fn scan_dataset(path: String) -> Result<DatasetScan, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || dataset_scan::scan(&path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+/// Splits a class-per-folder dataset into train, val and test, copying or
+/// linking images under `dest`, or listing them in a manifest file at `dest`.
+/// Stratified by default; progress arrives as `split://progress`.
+#[tauri::command]
+async fn split_dataset(
+    app: tauri::AppHandle,
+    path: String,
+    dest: String,
+    ratios: SplitRatios,
+    stratified: Option<bool>,
+    seed: Option<u64>,
+    mode: Option<SplitMode>,
+) -> Result<SplitReport, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        dataset_split::split(
+            &app,
+            std::path::Path::new(&path),
+            std::path::Path::new(&dest),
+            ratios,
+            stratified.unwrap_or(true),
+            seed,
+            mode.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(BackendError::from)
+}
+
+/// Finds near-duplicate images in a dataset by perceptual hash, flagging pairs
+/// that cross splits. `threshold` is the largest Hamming distance reported.
+#[tauri::command]
+async fn dedupe_dataset(app: tauri::AppHandle, path: String, threshold: Option<u32>) -> Result<DedupeReport, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || dedupe::find_duplicates(&app, std::path::Path::new(&path), threshold))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+/// A cached JPEG thumbnail of an image, at most `size` pixels on its longer side.
+#[tauri::command]
+async fn get_thumbnail(app: tauri::AppHandle, path: String, size: Option<u32>) -> Result<Thumbnail, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let cache = app.state::<Arc<ThumbnailCache>>();
+        cache.get(&app, std::path::Path::new(&path), size)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(BackendError::from)
+}
+
+/// Deletes every cached thumbnail; returns the bytes freed.
+#[tauri::command]
+fn clear_thumbnail_cache(app: tauri::AppHandle, cache: tauri::State<'_, Arc<ThumbnailCache>>) -> Result<u64, BackendError> {
+    Ok(cache.clear(&app)?)
+}
+
+/// Dimensions, EXIF orientation, camera and capture date of an image, read
+/// without decoding its pixels.
+#[tauri::command]
+fn read_image_metadata(path: String) -> Result<ImageMetadata, BackendError> {
+    Ok(image_meta::read(std::path::Path::new(&path))?)
+}
+
+/// Decodes every image in `folder` and reports empty, truncated and corrupt
+/// files before training trips over them. Progress arrives as `verify://progress`.
+#[tauri::command]
+async fn verify_images(app: tauri::AppHandle, folder: String) -> Result<VerifyReport, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || verify::verify(&app, std::path::Path::new(&folder)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+/// Parses a classes JSON document and checks it against a model's stored class
+/// map. Returns the entries in output order.
+#[tauri::command]
+fn check_class_spec(model_id: String, classes: String) -> Result<Vec<ClassEntry>, BackendError> {
+    let spec = ClassSpec::parse(&classes)?;
+    classes::resolve(std::path::Path::new(&model_id), &spec)?;
+    Ok(spec.entries()?)
+}
+
+/// Scores every image in `folder` and returns max-confidence histograms per
+/// predicted class, for choosing a decision threshold.
+#[tauri::command]
+async fn confidence_report(app: tauri::AppHandle, model_id: String, folder: String) -> Result<ConfidenceReport, BackendError> {
+    Ok(confidence::report(&app, &model_id, &folder).await?)
+}
+
+/// Misclassified test images of a run, most confident mistakes first.
+#[tauri::command]
+fn get_misclassified(run_dir: String) -> Result<Vec<Misclassification>, BackendError> {
+    Ok(gallery::load(std::path::Path::new(&run_dir))?)
+}
+
+/// Exports a run's misclassified images as a self-contained HTML gallery for
+/// reviewers without the app. Returns the number of images included.
+#[tauri::command]
+async fn export_misclassified_gallery(
+    app: tauri::AppHandle,
+    run_dir: String,
+    dest: String,
+    limit: Option<usize>,
+) -> Result<usize, BackendError> {
+    let l10n = i18n::localizer(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        gallery::export(std::path::Path::new(&run_dir), std::path::Path::new(&dest), limit, &l10n)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(BackendError::from)
+}
+
+/// Renders a run's configuration, loss and accuracy curves, test metrics,
+/// confusion matrix and misclassified samples into one standalone HTML or PDF
+/// file for people without the app. Returns the path written.
+#[tauri::command]
+async fn export_report(
+    app: tauri::AppHandle,
+    run_id: String,
+    format: ReportFormat,
+    out_path: String,
+) -> Result<String, BackendError> {
+    let l10n = i18n::localizer(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        report::export(std::path::Path::new(&run_id), format, std::path::Path::new(&out_path), &l10n)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(BackendError::from)
+}
+
+/// Packs a training run (weights, training config, class list, evaluation
+/// and reports) and a manifest of its dataset into one `.epoq` archive, to
+/// move the experiment to another machine.
+#[tauri::command]
+async fn export_project(run_id: String, out_path: String) -> Result<ProjectExport, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        project_archive::export(std::path::Path::new(&run_id), std::path::Path::new(&out_path))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(BackendError::from)
+}
+
+/// Unpacks a `.epoq` archive into the models folder after checking every
+/// file's checksum, and registers the run.
+#[tauri::command]
+async fn import_project(app: tauri::AppHandle, path: String) -> Result<ProjectImport, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || project_archive::import(&app, std::path::Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+/// Writes a one-file HTML summary of a batch prediction job (class counts,
+/// low-confidence review queue, processing stats) and returns the summary.
+#[tauri::command]
+fn export_batch_summary(
+    app: tauri::AppHandle,
+    job_id: String,
+    dest: String,
+    low_confidence_threshold: Option<f64>,
+) -> Result<BatchSummary, BackendError> {
+    let threshold = low_confidence_threshold.unwrap_or(batch_report::DEFAULT_LOW_CONFIDENCE);
+    Ok(batch_report::export(&app, &job_id, std::path::Path::new(&dest), threshold)?)
+}
+
+/// Where settings, data, models, caches and logs are stored on this machine.
+#[tauri::command]
+fn get_storage_locations(app: tauri::AppHandle) -> Result<StorageLocations, BackendError> {
+    Ok(paths::locations(&app)?)
+}
+
+#[tauri::command]
+fn get_python_settings(app: tauri::AppHandle) -> PythonSettings {
+    python::load_settings(&app)
+}
+
+/// Stores how Python is located, e.g. the version the `py` launcher starts.
+#[tauri::command]
+fn set_python_settings(app: tauri::AppHandle, settings: PythonSettings) -> Result<PythonSettings, BackendError> {
+    python::save_settings(&app, &settings)?;
+    embedded_python::reset();
+    Ok(settings)
+}
+
+/// Python 3 interpreters found on PATH, through the `py` launcher, in pyenv
+/// and in conda environments, with the selected one marked.
+#[tauri::command]
+async fn list_python_interpreters(app: tauri::AppHandle) -> Vec<PythonInterpreter> {
+    interpreters::discover(&app).await
+}
+
+/// Makes `path` the interpreter every script runs with first; `None` goes back
+/// to searching PATH.
+#[tauri::command]
+async fn set_python_interpreter(app: tauri::AppHandle, path: Option<String>) -> Result<PythonSettings, BackendError> {
+    Ok(interpreters::select(&app, path).await?)
+}
+
+/// Runs scripts in a conda environment through `conda run -n <name>`, so it
+/// is activated like in a terminal; `None` stops using conda.
+#[tauri::command]
+async fn set_conda_environment(app: tauri::AppHandle, name: Option<String>) -> Result<PythonSettings, BackendError> {
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if let Some(name) = &name {
+        let known = interpreters::conda_environments(&app).await;
+        if known.is_empty() {
+            return Err(BackendError::python_not_found("No conda, mamba or micromamba installation was found."));
+        }
+        if !known.iter().any(|(_, env)| env == name) {
+            return Err(BackendError::invalid_input(format!("There is no conda environment named '{}'.", name)));
+        }
+    }
+    let mut settings = python::load_settings(&app);
+    if name.is_some() {
+        settings.interpreter = None;
+    }
+    settings.conda_env = name;
+    python::save_settings(&app, &settings)?;
+    embedded_python::reset();
+    Ok(settings)
+}
+
+/// Creates the app's own venv under the app data folder, if missing, and
+/// makes scripts run with it.
+#[tauri::command]
+async fn create_venv(app: tauri::AppHandle) -> Result<VenvStatus, BackendError> {
+    Ok(venv::create(&app).await?)
+}
+
+/// Installs the backend's requirements into the app's venv. With a `job_id`,
+/// pip's output can be followed as `python://stdout` events for that job.
+#[tauri::command]
+async fn install_requirements(app: tauri::AppHandle, job_id: Option<String>) -> Result<VenvStatus, BackendError> {
+    Ok(venv::install_requirements(&app, job_id).await?)
+}
+
+#[tauri::command]
+fn get_venv_status(app: tauri::AppHandle) -> Result<VenvStatus, BackendError> {
+    Ok(venv::status(&app)?)
+}
+
+/// The last `tail_n` log entries (200 by default) at `level` or more severe.
+#[tauri::command]
+fn get_logs(
+    logs: tauri::State<'_, Logging>,
+    level: Option<String>,
+    tail_n: Option<usize>,
+) -> Result<Vec<LogEntry>, BackendError> {
+    Ok(logs.tail(level.as_deref(), tail_n)?)
+}
+
+/// Opens the log folder in the file manager and returns its path.
+#[tauri::command]
+fn open_log_dir(logs: tauri::State<'_, Logging>) -> Result<String, BackendError> {
+    logs.open_dir()?;
+    Ok(logs.dir().to_string_lossy().to_string())
+}
+
+/// Whether scripts run with the bundled backend or a Python on the machine.
+#[tauri::command]
+async fn get_backend_mode(app: tauri::AppHandle) -> BackendStatus {
+    python::backend_status(&app).await
+}
+
+/// Tries every Python interpreter and reports the command, exit code and
+/// stderr of each attempt.
+#[tauri::command]
+async fn diagnose_python(app: tauri::AppHandle) -> PythonDiagnosis {
+    python::diagnose(&app).await
+}
+
+/// The language setting, the language in use and the translations available.
+#[tauri::command]
+fn get_language_settings(l10n: tauri::State<'_, Arc<Localizer>>) -> LanguageStatus {
+    l10n.status()
+}
+
+/// Sets the language of messages, reports and notifications produced by the
+/// backend. `language: null` follows the system language.
+#[tauri::command]
+fn set_language_settings(
+    l10n: tauri::State<'_, Arc<Localizer>>,
+    settings: LanguageSettings,
+) -> Result<LanguageStatus, BackendError> {
+    l10n.set_settings(settings)?;
+    Ok(l10n.status())
+}
+
+/// Profiles on this OS account and the one in use.
+#[tauri::command]
+fn get_profiles(profiles: tauri::State<'_, Arc<Profiles>>) -> ProfilesStatus {
+    profiles.status()
+}
+
+/// Creates a profile (empty id) or renames one / changes its model sharing.
+#[tauri::command]
+fn save_profile(profiles: tauri::State<'_, Arc<Profiles>>, profile: Profile) -> Result<Profile, BackendError> {
+    Ok(profiles.save_profile(profile)?)
+}
+
+#[tauri::command]
+fn remove_profile(profiles: tauri::State<'_, Arc<Profiles>>, id: String) -> Result<(), BackendError> {
+    Ok(profiles.remove_profile(&id)?)
+}
+
+#[tauri::command]
+fn set_profile_prompt(profiles: tauri::State<'_, Arc<Profiles>>, prompt_at_startup: bool) -> Result<(), BackendError> {
+    Ok(profiles.set_prompt_at_startup(prompt_at_startup)?)
+}
+
+/// Opens a profile; the app restarts unless it is the one already in use.
+#[tauri::command]
+fn switch_profile(app: tauri::AppHandle, profiles: tauri::State<'_, Arc<Profiles>>, id: String) -> Result<(), BackendError> {
+    Ok(profiles::switch(&app, &profiles, &id)?)
+}
+
+#[tauri::command]
+fn list_recents(app: tauri::AppHandle) -> Result<Vec<RecentItem>, BackendError> {
+    Ok(recents::load(&app)?)
+}
+
+#[tauri::command]
+fn add_recent(app: tauri::AppHandle, kind: RecentKind, path: String) -> Result<Vec<RecentItem>, BackendError> {
+    Ok(recents::add(&app, kind, &path)?)
+}
+
+/// Clears one kind of recent item, or all of them.
+#[tauri::command]
+fn clear_recents(app: tauri::AppHandle, kind: Option<RecentKind>) -> Result<(), BackendError> {
+    Ok(recents::clear(&app, kind)?)
+}
+
+/// Makes `path` a project directory with a `project.json` manifest and opens
+/// it. Training runs started while it is open are recorded in it and, unless
+/// they name an output folder, saved under its `runs/` folder.
+#[tauri::command]
+fn create_project(
+    app: tauri::AppHandle,
+    projects: tauri::State<'_, Arc<Projects>>,
+    path: String,
+    name: String,
+    description: Option<String>,
+) -> Result<OpenProject, BackendError> {
+    Ok(projects.create(&app, std::path::Path::new(&path), &name, description)?)
+}
+
+#[tauri::command]
+fn open_project(
+    app: tauri::AppHandle,
+    projects: tauri::State<'_, Arc<Projects>>,
+    path: String,
+) -> Result<OpenProject, BackendError> {
+    Ok(projects.open(&app, std::path::Path::new(&path))?)
+}
+
+/// Writes the open project's manifest, replacing its datasets, runs, models
+/// and settings with `project` when given.
+#[tauri::command]
+fn save_project(projects: tauri::State<'_, Arc<Projects>>, project: Option<Project>) -> Result<OpenProject, BackendError> {
+    Ok(projects.save(project)?)
+}
+
+/// The open project; the last one opened is reopened at startup.
+#[tauri::command]
+fn get_active_project(projects: tauri::State<'_, Arc<Projects>>) -> Option<OpenProject> {
+    projects.active()
+}
+
+/// General preferences: interpreter, default output folder, theme and
+/// concurrency limits.
+#[tauri::command]
+fn get_settings(app: tauri::AppHandle, settings: tauri::State<'_, Arc<AppSettingsStore>>) -> AppSettings {
+    settings.get(&app)
+}
+
+/// Changes the fields present in `patch` and publishes `settings://changed`.
+#[tauri::command]
+async fn update_settings(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<AppSettingsStore>>,
+    patch: serde_json::Value,
+) -> Result<AppSettings, BackendError> {
+    Ok(settings.update(&app, patch).await?)
+}
+
+/// Checks the release channel chosen in settings (`update_channel`) for a
+/// newer version.
+#[tauri::command]
+async fn check_for_updates(app: tauri::AppHandle, updates: tauri::State<'_, Arc<Updates>>) -> Result<UpdateInfo, BackendError> {
+    Ok(updater::check(&app, updates.inner()).await?)
+}
+
+/// Installs the update found by `check_for_updates`, with `update://progress`
+/// events during the download, and restarts the app.
+#[tauri::command]
+async fn install_update(app: tauri::AppHandle, updates: tauri::State<'_, Arc<Updates>>) -> Result<(), BackendError> {
+    Ok(updater::install(&app, updates.inner()).await?)
+}
+
+/// Serves predict, train, model listing and job status over HTTP for scripts
+/// and LIMS systems, with the binding and TLS from the server security
+/// settings. Requests need `token` (generated and returned when omitted) or an
+/// API key.
+#[tauri::command]
+async fn start_api_server(
+    app: tauri::AppHandle,
+    server: tauri::State<'_, Arc<ApiServer>>,
+    port: Option<u16>,
+    token: Option<String>,
+) -> Result<StartedApiServer, BackendError> {
+    Ok(server.start(&app, port, token).await?)
+}
 
-async fn run_python(
-    app: &tauri::AppHandle,
-    args: &[&str],
-) -> Result<String, String> {
-    // Try `python` first, then alternatives including the Windows Python Launcher `py`
-    let cmds = ["python", "python3", "py"];
-    let mut last_err = String::new();
-
-    for cmd in cmds {
-        match app
-            .shell()
-            .command(cmd)
-            .args(args)
-            .output()
-            .await
-        {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                
-                // If it succeeds, immediately return the standard output
-                if output.status.success() {
-                    return Ok(stdout);
-                } else {
-                    // Record error to return if ALL commands fail
-                    let msg = if !stderr.trim().is_empty() {
-                        stderr
-                    } else if !stdout.trim().is_empty() {
-                        stdout
-                    } else {
-                        format!("Exited with code: {}", output.status.code().unwrap_or(-1))
-                    };
-                    last_err = msg;
-                    continue;
-                }
-            }
-            Err(e) => {
-                last_err = e.to_string();
-            }
-        }
+#[tauri::command]
+fn stop_api_server(server: tauri::State<'_, Arc<ApiServer>>) -> Result<(), BackendError> {
+    Ok(server.stop()?)
+}
+
+#[tauri::command]
+fn get_api_server_status(server: tauri::State<'_, Arc<ApiServer>>) -> ApiServerStatus {
+    server.status()
+}
+
+#[tauri::command]
+fn get_backup_settings(backups: tauri::State<'_, Arc<BackupManager>>) -> BackupSettings {
+    backups.settings()
+}
+
+#[tauri::command]
+fn set_backup_settings(
+    backups: tauri::State<'_, Arc<BackupManager>>,
+    settings: BackupSettings,
+) -> Result<BackupSettings, BackendError> {
+    backups.set_settings(settings)?;
+    Ok(backups.settings())
+}
+
+#[tauri::command]
+fn list_backups(backups: tauri::State<'_, Arc<BackupManager>>, store: Option<String>) -> Vec<BackupInfo> {
+    backups.list(store.as_deref())
+}
+
+/// Snapshots every SQLite store now, regardless of the interval.
+#[tauri::command]
+async fn backup_now(backups: tauri::State<'_, Arc<BackupManager>>) -> Result<BackupRun, BackendError> {
+    let manager = backups.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || manager.run())
+        .await
+        .map_err(|e| BackendError::from(e.to_string()))
+}
+
+/// Restores a store from one of its snapshots after an integrity check.
+#[tauri::command]
+async fn restore_from_backup(
+    backups: tauri::State<'_, Arc<BackupManager>>,
+    backup: String,
+) -> Result<BackupInfo, BackendError> {
+    let manager = backups.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || manager.restore(&backup))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(BackendError::from)
+}
+
+/// Writes the user's settings, without secrets, to a file that can be
+/// imported on another machine.
+#[tauri::command]
+fn export_settings(app: tauri::AppHandle, dest: String) -> Result<SettingsFile, BackendError> {
+    Ok(settings_transfer::export(&app, std::path::Path::new(&dest))?)
+}
+
+/// Applies a settings file section by section; invalid sections are reported
+/// and skipped.
+#[tauri::command]
+async fn import_settings(app: tauri::AppHandle, file: String) -> Result<SettingsImportReport, BackendError> {
+    Ok(settings_transfer::import(&app, std::path::Path::new(&file)).await?)
+}
+
+/// Running jobs and sweeps that closing the app would stop.
+#[tauri::command]
+fn get_active_work(app: tauri::AppHandle) -> ActiveWork {
+    shutdown::active_work(&app)
+}
+
+/// Called once the user confirmed closing despite active work. Training jobs
+/// get `grace_secs` (default 60) to checkpoint before everything is stopped.
+#[tauri::command]
+async fn confirm_shutdown(app: tauri::AppHandle, grace_secs: Option<u64>) -> Result<(), BackendError> {
+    let grace = grace_secs.map(std::time::Duration::from_secs).unwrap_or(shutdown::DEFAULT_GRACE);
+    shutdown::shutdown(app, grace).await;
+    Ok(())
+}
+
+/// The launch self-check report; `refresh` runs the checks again.
+#[tauri::command]
+async fn get_startup_report(
+    app: tauri::AppHandle,
+    check: tauri::State<'_, Arc<StartupCheck>>,
+    refresh: Option<bool>,
+) -> Result<StartupReport, BackendError> {
+    match check.last() {
+        Some(report) if !refresh.unwrap_or(false) => Ok(report),
+        _ => Ok(startup_check::run(&app).await),
     }
-    Err(last_err)
 }
 
-/// Runs tabular_processor.py with the given action, file, and optional params.
-/// Returns the JSON string printed by the script.
+/// Writes a copy of `dataset` to `dest` with `multiplier` augmented variants
+/// of every training image, listed in a manifest so they stay out of
+/// validation and test splits.
 #[tauri::command]
-async fn run_tabular_processor(
+async fn materialize_augmentations(
+    app: tauri::AppHandle,
+    dataset: String,
+    policy: AugmentationPolicy,
+    multiplier: u32,
+    dest: String,
+    seed: Option<u64>,
+) -> Result<MaterializeReport, BackendError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        synthetic::materialize(
+            &app,
+            std::path::Path::new(&dataset),
+            &policy,
+            multiplier,
+            std::path::Path::new(&dest),
+            seed,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(BackendError::from)
+}
+
+/// Scores the images in `unlabeled_folder` with a trained model and adds the
+/// `n` most useful to label to the annotation store's review queue.
+#[tauri::command]
+async fn select_for_labeling(
     app: tauri::AppHandle,
+    store: tauri::State<'_, Arc<AnnotationStore>>,
+    model_id: String,
+    unlabeled_folder: String,
+    n: usize,
+    strategy: SelectionStrategy,
+) -> Result<LabelingSelection, BackendError> {
+    Ok(active_learning::select(&app, &store, &model_id, &unlabeled_folder, n, strategy).await?)
+}
+
+/// Review queue items, highest priority first, optionally filtered by status
+/// or batch.
+#[tauri::command]
+fn get_review_queue(
+    store: tauri::State<'_, Arc<AnnotationStore>>,
+    status: Option<ReviewStatus>,
+    batch_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ReviewItem>, BackendError> {
+    Ok(store.review_queue(status, batch_id.as_deref(), limit)?)
+}
+
+#[tauri::command]
+fn remove_from_review_queue(store: tauri::State<'_, Arc<AnnotationStore>>, files: Vec<String>) -> Result<usize, BackendError> {
+    Ok(store.dequeue(&files)?)
+}
+
+/// Groups the highest-priority unbatched items into a batch for one labeler.
+#[tauri::command]
+fn create_labeling_batch(
+    store: tauri::State<'_, Arc<AnnotationStore>>,
+    name: String,
+    assignee: Option<String>,
+    size: usize,
+) -> Result<LabelingBatch, BackendError> {
+    Ok(store.create_batch(&name, assignee.as_deref(), size)?)
+}
+
+#[tauri::command]
+fn list_labeling_batches(store: tauri::State<'_, Arc<AnnotationStore>>) -> Result<Vec<LabelingBatch>, BackendError> {
+    Ok(store.list_batches()?)
+}
+
+#[tauri::command]
+fn assign_labeling_batch(
+    store: tauri::State<'_, Arc<AnnotationStore>>,
+    batch_id: String,
+    assignee: Option<String>,
+) -> Result<LabelingBatch, BackendError> {
+    Ok(store.assign_batch(&batch_id, assignee.as_deref())?)
+}
+
+/// Deletes a batch; its unfinished items go back to the unbatched queue.
+#[tauri::command]
+fn remove_labeling_batch(store: tauri::State<'_, Arc<AnnotationStore>>, batch_id: String) -> Result<(), BackendError> {
+    Ok(store.remove_batch(&batch_id)?)
+}
+
+/// Hands out the next unlabeled item, from one batch when given, and marks it
+/// in review. Returns null when the queue is empty.
+#[tauri::command]
+fn next_labeling_item(
+    store: tauri::State<'_, Arc<AnnotationStore>>,
+    batch_id: Option<String>,
+) -> Result<Option<ReviewItem>, BackendError> {
+    Ok(store.next_item(batch_id.as_deref())?)
+}
+
+#[tauri::command]
+fn submit_label(store: tauri::State<'_, Arc<AnnotationStore>>, file: String, label: String) -> Result<ReviewItem, BackendError> {
+    Ok(store.submit_label(&file, &label)?)
+}
+
+/// Changes an item's status by hand, e.g. back to todo when skipped.
+#[tauri::command]
+fn set_review_status(
+    store: tauri::State<'_, Arc<AnnotationStore>>,
     file: String,
-    action: String,
-    params: Option<String>,
-    out: Option<String>,
-) -> Result<String, String> {
-    let script_path = app
-        .path()
-        .resource_dir()
-        .map_err(|e| e.to_string())?
-        .join("python_backend")
-        .join("tabular_processor.py");
+    status: ReviewStatus,
+) -> Result<ReviewItem, BackendError> {
+    Ok(store.set_status(&file, status)?)
+}
 
-    let script = script_path.to_string_lossy().to_string().replace("\\\\?\\", "");
+#[tauri::command]
+fn get_labeling_stats(store: tauri::State<'_, Arc<AnnotationStore>>) -> Result<LabelingStats, BackendError> {
+    Ok(store.stats()?)
+}
 
-    // Build args list
-    let mut args: Vec<String> = vec![
-        script,
-        "--action".to_string(),
-        action,
-        "--file".to_string(),
-        file,
-    ];
-    if let Some(p) = params {
-        args.push("--params".to_string());
-        args.push(p);
-    }
-    if let Some(o) = out {
-        args.push("--out".to_string());
-        args.push(o);
-    }
+/// Metric series of a running or recent job, raw and EMA-smoothed, with its
+/// progress and ETA. `smoothing` overrides the configured factor for this call.
+#[tauri::command]
+fn get_metrics(
+    collector: tauri::State<'_, Arc<MetricsCollector>>,
+    job_id: String,
+    smoothing: Option<f64>,
+) -> Result<JobMetrics, BackendError> {
+    Ok(collector.get(&job_id, smoothing)?)
+}
 
-    let args_ref: Vec<&str> = args.iter().map(String::as_str).collect();
-    run_python(&app, &args_ref).await
+#[tauri::command]
+fn get_metrics_settings(collector: tauri::State<'_, Arc<MetricsCollector>>) -> MetricsSettings {
+    collector.settings()
 }
 
-/// Runs check_gpu.py and returns the stdout lines as a plain string.
 #[tauri::command]
-async fn run_check_gpu(app: tauri::AppHandle) -> Result<String, String> {
-    let script_path = app
-        .path()
-        .resource_dir()
-        .map_err(|e| e.to_string())?
-        .join("python_backend")
-        .join("check_gpu.py");
+fn set_metrics_settings(
+    collector: tauri::State<'_, Arc<MetricsCollector>>,
+    settings: MetricsSettings,
+) -> Result<MetricsSettings, BackendError> {
+    collector.set_settings(settings)?;
+    Ok(collector.settings())
+}
+
+/// Local server security: bind mode, client allow-list, API keys (without
+/// secrets) and the TLS certificate.
+#[tauri::command]
+fn get_server_security(security: tauri::State<'_, Arc<ServerSecurity>>) -> ServerSecurityStatus {
+    security.status()
+}
 
-    let script = script_path.to_string_lossy().to_string().replace("\\\\?\\", "");
+/// Turning TLS on creates the self-signed certificate if there is none.
+#[tauri::command]
+fn set_server_security(
+    security: tauri::State<'_, Arc<ServerSecurity>>,
+    settings: ServerSecuritySettings,
+) -> Result<ServerSecurityStatus, BackendError> {
+    security.set_settings(settings)?;
+    Ok(security.status())
+}
 
-    match run_python(&app, &[script.as_str()]).await {
-        Ok(output) => Ok(output.trim().to_string()), // remove extra newline
-        Err(e) => Err(format!("GPU detection failed: {}", e)),
-    }
+#[tauri::command]
+fn list_api_keys(security: tauri::State<'_, Arc<ServerSecurity>>) -> Vec<ApiKeyInfo> {
+    security.keys()
 }
-/// Runs system_info.py and returns structured JSON string.
+
+/// Generates an API key; the returned secret is not shown again.
 #[tauri::command]
-async fn get_system_info(app: tauri::AppHandle) -> Result<String, String> {
-    let script_path = app
-        .path()
-        .resource_dir()
-        .map_err(|e| e.to_string())?
-        .join("python_backend")
-        .join("system_info.py");
+fn create_api_key(security: tauri::State<'_, Arc<ServerSecurity>>, label: String) -> Result<IssuedApiKey, BackendError> {
+    Ok(security.create_key(&label)?)
+}
 
-    let script = script_path.to_string_lossy().to_string().replace("\\\\?\\", "");
+/// Replaces a key's secret, invalidating the old one.
+#[tauri::command]
+fn rotate_api_key(security: tauri::State<'_, Arc<ServerSecurity>>, id: String) -> Result<IssuedApiKey, BackendError> {
+    Ok(security.rotate_key(&id)?)
+}
 
-    match run_python(&app, &[script.as_str()]).await {
-        Ok(output) => Ok(output.trim().to_string()),
-        Err(e) => Err(format!("System info failed: {}", e)),
-    }
+#[tauri::command]
+fn revoke_api_key(security: tauri::State<'_, Arc<ServerSecurity>>, id: String) -> Result<(), BackendError> {
+    Ok(security.revoke_key(&id)?)
 }
 
 #[tauri::command]
-async fn check_dependencies(app: tauri::AppHandle) -> Result<String, String> {
-    println!("DEBUG: Running backend check_dependencies");
-    let script = "import sys, json, importlib.util; p = lambda x: importlib.util.find_spec(x) is not None; print(json.dumps({'python': True, 'executable': sys.executable, 'version': sys.version.split()[0], 'pandas': p('pandas'), 'sklearn': p('sklearn'), 'torch': p('torch'), 'timm': p('timm'), 'optuna': p('optuna')}))";
-    match run_python(&app, &["-c", script]).await {
-        Ok(output) => {
-            println!("DEBUG: Python stdout: {}", output);
-            Ok(output.trim().to_string())
-        },
-        Err(e) => {
-            println!("DEBUG: Python error: {}", e);
-            let error_json = format!(
-                "{{\"python\": false, \"version\": null, \"pandas\": false, \"sklearn\": false, \"torch\": false, \"timm\": false, \"optuna\": false, \"error\": \"{}\"}}",
-                e.replace("\"", "\\\"").replace("\n", " ")
-            );
-            Ok(error_json)
-        }
+fn regenerate_tls_certificate(security: tauri::State<'_, Arc<ServerSecurity>>) -> Result<TlsCertificateInfo, BackendError> {
+    Ok(security.regenerate_certificate()?)
+}
+
+/// Local services (TensorBoard, REST server) with their preferred ports, the
+/// address they are bound to and any port conflict.
+#[tauri::command]
+fn get_service_endpoints(ports: tauri::State<'_, Arc<PortManager>>) -> Vec<ServiceEndpoint> {
+    ports.endpoints()
+}
+
+/// Sets the port a service tries first; `None` returns it to the default.
+#[tauri::command]
+fn set_preferred_port(
+    ports: tauri::State<'_, Arc<PortManager>>,
+    service: String,
+    port: Option<u16>,
+) -> Result<Vec<ServiceEndpoint>, BackendError> {
+    ports.set_preferred(&service, port)?;
+    Ok(ports.endpoints())
+}
+
+/// Environment variables set on every job unless a run overrides them.
+#[tauri::command]
+fn get_job_env_settings(app: tauri::AppHandle) -> JobEnvSettings {
+    job_env::load_settings(&app)
+}
+
+/// Stores the default job environment, e.g. `OMP_NUM_THREADS` or `HTTPS_PROXY`.
+#[tauri::command]
+fn set_job_env_settings(app: tauri::AppHandle, settings: JobEnvSettings) -> Result<JobEnvSettings, BackendError> {
+    job_env::save_settings(&app, &settings)?;
+    Ok(settings)
+}
+
+/// Active jobs with progress, GPU and system load, queue depth, recent
+/// completions and storage use in one call, for the home-screen dashboard.
+#[tauri::command]
+async fn get_dashboard_snapshot(app: tauri::AppHandle) -> DashboardSnapshot {
+    dashboard::snapshot(&app).await
+}
+
+/// Stops a running job by killing its process and every process it started.
+/// Its `job://finished` event reports `cancelled: true`.
+#[tauri::command]
+fn cancel_job(jobs: tauri::State<'_, Arc<JobManager>>, job_id: String) -> Result<(), BackendError> {
+    Ok(jobs.cancel(&job_id)?)
+}
+
+/// Steers a running training job: stop after the current epoch, save a
+/// checkpoint now, or extend the run by some epochs. The job confirms with a
+/// `control` status line on `job://output`.
+#[tauri::command]
+fn send_job_control(
+    jobs: tauri::State<'_, Arc<JobManager>>,
+    job_id: String,
+    action: JobControl,
+) -> Result<(), BackendError> {
+    Ok(jobs.send_control(&job_id, action)?)
+}
+
+/// Every job of this session with its kind, arguments, times and status,
+/// newest first.
+#[tauri::command]
+fn list_jobs(manager: tauri::State<'_, Arc<JobManager>>) -> Vec<JobRecord> {
+    manager.list()
+}
+
+#[tauri::command]
+fn get_job(manager: tauri::State<'_, Arc<JobManager>>, job_id: String) -> Result<JobRecord, BackendError> {
+    Ok(manager.get(&job_id)?)
+}
+
+/// Removes finished jobs from the list. Returns how many were removed.
+#[tauri::command]
+fn clear_jobs(manager: tauri::State<'_, Arc<JobManager>>) -> usize {
+    manager.clear()
+}
+
+/// Adds a training run to the model registry under `name`, or refreshes its
+/// entry. Successful training jobs are registered automatically.
+#[tauri::command]
+fn register_model(app: tauri::AppHandle, run_dir: String, name: Option<String>) -> Result<RegisteredModel, BackendError> {
+    if !std::path::Path::new(&run_dir).is_dir() {
+        return Err(BackendError::invalid_input(format!("{} is not a directory.", run_dir)));
     }
+    Ok(models::register(&app, &run_dir, name)?)
+}
+
+/// Registered models, newest first, including runs found in the models folder.
+#[tauri::command]
+fn list_models(app: tauri::AppHandle) -> Result<Vec<RegisteredModel>, BackendError> {
+    Ok(models::list(&app)?)
+}
+
+#[tauri::command]
+fn get_model(app: tauri::AppHandle, id: String) -> Result<RegisteredModel, BackendError> {
+    Ok(models::get(&app, &id)?)
+}
+
+/// Classifies a labelled test folder (one subfolder per class) with a model
+/// and returns its confusion matrix, per-class precision, recall and F1, and
+/// the misclassified images. The report is saved with the model.
+#[tauri::command]
+async fn evaluate_model(
+    app: tauri::AppHandle,
+    model_id: String,
+    test_dir: String,
+) -> Result<EvaluationReport, BackendError> {
+    Ok(evaluation::evaluate(&app, &model_id, &test_dir).await?)
+}
+
+/// Removes a model from the registry; `delete_files` also deletes its run directory.
+#[tauri::command]
+fn delete_model(app: tauri::AppHandle, id: String, delete_files: Option<bool>) -> Result<(), BackendError> {
+    Ok(models::delete(&app, &id, delete_files.unwrap_or(false))?)
+}
+
+/// Exports a trained model (its run directory) to an ONNX file and records
+/// the path in the model registry. Progress arrives as `onnx://progress`
+/// events for `job_id`.
+#[tauri::command]
+async fn export_model_onnx(
+    app: tauri::AppHandle,
+    model_id: String,
+    out_path: String,
+    opset: Option<u32>,
+    job_id: Option<String>,
+) -> Result<OnnxExport, BackendError> {
+    onnx::export(&app, &model_id, &out_path, opset, job_id).await
+}
+
+/// Finished jobs from every session, newest first, with their arguments and
+/// the last lines they printed.
+#[tauri::command]
+fn query_history(
+    history: tauri::State<'_, Arc<JobHistory>>,
+    filter: Option<HistoryFilter>,
+) -> Result<Vec<HistoryEntry>, BackendError> {
+    Ok(history.query(&filter.unwrap_or_default())?)
+}
+
+/// Every command with JSON schemas for its parameters and result, for
+/// alternative frontends and automation built against the Rust core.
+#[tauri::command]
+fn get_api_descriptor() -> ApiDescriptor {
+    api::descriptor()
+}
+
+/// Agrees on the API version with a client.
+#[tauri::command]
+fn negotiate_api_version(client_version: u32) -> Result<ApiHandshake, BackendError> {
+    Ok(api::negotiate(client_version)?)
+}
+
+#[tauri::command]
+fn list_post_job_hooks(app: tauri::AppHandle) -> Result<Vec<PostJobHook>, BackendError> {
+    Ok(hooks::load(&app)?)
+}
+
+/// Creates or updates a post-job hook; hooks without an id get one assigned.
+#[tauri::command]
+fn save_post_job_hook(app: tauri::AppHandle, hook: PostJobHook) -> Result<PostJobHook, BackendError> {
+    Ok(hooks::save(&app, hook)?)
+}
+
+#[tauri::command]
+fn remove_post_job_hook(app: tauri::AppHandle, id: String) -> Result<(), BackendError> {
+    Ok(hooks::remove(&app, &id)?)
+}
+
+/// Output of every hook that ran after `job_id` finished.
+#[tauri::command]
+fn get_hook_log(app: tauri::AppHandle, job_id: String) -> Result<String, BackendError> {
+    Ok(hooks::read_log(&app, &job_id)?)
+}
+
+#[tauri::command]
+fn list_webhooks(app: tauri::AppHandle) -> Result<Vec<Webhook>, BackendError> {
+    Ok(webhooks::load(&app)?)
+}
+
+/// Creates or updates a webhook; webhooks without an id get one assigned.
+#[tauri::command]
+fn save_webhook(app: tauri::AppHandle, webhook: Webhook) -> Result<Webhook, BackendError> {
+    Ok(webhooks::save(&app, webhook)?)
+}
+
+#[tauri::command]
+fn remove_webhook(app: tauri::AppHandle, id: String) -> Result<(), BackendError> {
+    Ok(webhooks::remove(&app, &id)?)
+}
+
+/// Sends a `job.started` event for a fake job so the user can check the receiver.
+#[tauri::command]
+async fn test_webhook(app: tauri::AppHandle, id: String) -> Result<WebhookDelivery, BackendError> {
+    let webhook = webhooks::load(&app)?
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| format!("No webhook with id {}", id))?;
+    Ok(webhooks::deliver(&webhook, JobLifecycle::Started, "test", None, &i18n::localizer(&app)).await)
+}
+
+#[tauri::command]
+fn get_preset_library(app: tauri::AppHandle) -> Result<PresetLibrary, BackendError> {
+    Ok(presets::load_library(&app)?)
+}
+
+/// Validates and stores the whole preset library, stripping machine-specific paths.
+#[tauri::command]
+fn save_preset_library(app: tauri::AppHandle, library: PresetLibrary) -> Result<PresetLibrary, BackendError> {
+    Ok(presets::save_library(&app, library)?)
+}
+
+/// Exports the named presets into a signed pack archive.
+#[tauri::command]
+fn export_preset_pack(
+    app: tauri::AppHandle,
+    dest: String,
+    name: String,
+    author: Option<String>,
+    training: Vec<String>,
+    augmentation: Vec<String>,
+    pipelines: Vec<String>,
+) -> Result<(), BackendError> {
+    Ok(presets::export_pack(&app, std::path::Path::new(&dest), &name, author, &training, &augmentation, &pipelines)?)
+}
+
+/// Checks a pack's signature and contents without importing it.
+#[tauri::command]
+fn inspect_preset_pack(app: tauri::AppHandle, path: String) -> Result<PackInfo, BackendError> {
+    Ok(presets::inspect_pack(&app, std::path::Path::new(&path))?)
+}
+
+#[tauri::command]
+fn import_preset_pack(app: tauri::AppHandle, path: String, trust_signer: bool, overwrite: bool) -> Result<PackInfo, BackendError> {
+    Ok(presets::import_pack(&app, std::path::Path::new(&path), trust_signer, overwrite)?)
+}
+
+/// This installation's public signing key, to share with pack recipients.
+#[tauri::command]
+fn get_pack_signing_key(app: tauri::AppHandle) -> Result<String, BackendError> {
+    Ok(presets::public_key(&app)?)
+}
+
+#[tauri::command]
+fn trust_pack_signer(app: tauri::AppHandle, public_key: String) -> Result<(), BackendError> {
+    Ok(presets::trust_key(&app, &public_key)?)
+}
+
+/// Evaluation metrics contributed by installed plugins.
+#[tauri::command]
+fn list_custom_metrics(app: tauri::AppHandle) -> Result<Vec<MetricRef>, BackendError> {
+    Ok(metrics::available(&app)?)
+}
+
+/// Built-in and custom test metrics of finished runs side by side, read from
+/// each run directory's manifest.
+#[tauri::command]
+fn compare_runs(run_dirs: Vec<String>) -> Result<RunComparison, BackendError> {
+    Ok(metrics::compare(&run_dirs)?)
+}
+
+/// Built-in models followed by user-registered architectures.
+#[tauri::command]
+fn list_architectures(app: tauri::AppHandle) -> Result<Vec<ArchitectureInfo>, BackendError> {
+    Ok(architectures::list(&app)?)
+}
+
+/// Validates and stores a custom architecture so it can be picked like a built-in.
+#[tauri::command]
+fn register_architecture(app: tauri::AppHandle, descriptor: ArchitectureDescriptor) -> Result<(), BackendError> {
+    Ok(architectures::register(&app, descriptor)?)
+}
+
+#[tauri::command]
+fn remove_architecture(app: tauri::AppHandle, name: String) -> Result<(), BackendError> {
+    Ok(architectures::remove(&app, &name)?)
+}
+
+/// Returns buffered events newer than `since_seq` so a window that attaches late
+/// can catch up before listening for live events.
+#[tauri::command]
+fn replay_events(
+    bus: tauri::State<'_, Arc<EventBus>>,
+    since_seq: Option<u64>,
+    topics: Option<Vec<String>>,
+    job_id: Option<String>,
+) -> Vec<Envelope> {
+    bus.replay(since_seq.unwrap_or(0), topics.as_deref(), job_id.as_deref())
+}
+
+/// Which of the packages the scripts need are installed. Reports a missing
+/// interpreter as `python: false` instead of failing.
+#[tauri::command]
+async fn check_dependencies(app: tauri::AppHandle) -> DependencyStatus {
+    interpreters::dependencies(&app).await
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut cli = match cli::parse(&args) {
+        None => None,
+        Some(parsed) => {
+            cli::attach_console();
+            match parsed {
+                Ok(cli::Command::Help) => {
+                    println!("{}", cli::usage());
+                    return;
+                }
+                Ok(cli::Command::Version) => {
+                    println!("epoq {}", env!("CARGO_PKG_VERSION"));
+                    return;
+                }
+                Ok(command) => Some(command),
+                Err(e) => {
+                    eprintln!("{}\n\n{}", e, cli::usage());
+                    std::process::exit(2);
+                }
+            }
+        }
+    };
+    let headless = cli.is_some();
+    let mut context = tauri::generate_context!();
+    if headless {
+        // The CLI runs the same backend without opening a window.
+        context.config_mut().app.windows.clear();
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(Arc::new(EventBus::default()))
+        .manage(Arc::new(SweepManager::default()))
+        .manage(Arc::new(JobManager::default()))
+        .manage(Arc::new(ShutdownState::default()))
+        .manage(Arc::new(StartupCheck::default()))
+        .manage(Arc::new(Dashboard::default()))
+        .manage(Arc::new(WatchManager::default()))
+        .manage(Arc::new(CaptureManager::default()))
+        .manage(Arc::new(ThumbnailCache::default()))
+        .manage(Arc::new(GpuMonitor::default()))
+        .manage(Arc::new(ResourceMonitor::default()))
+        .manage(Arc::new(HpoManager::default()))
+        .manage(Arc::new(Downloads::default()))
+        .manage(Arc::new(Projects::default()))
+        .manage(Arc::new(Updates::default()))
+        .manage(Arc::new(ApiServer::default()))
         .invoke_handler(tauri::generate_handler![
             run_tabular_processor,
             run_check_gpu,
             get_system_info,
-            check_dependencies
+            check_dependencies,
+            validate_training_config,
+            verify_run_reproducibility,
+            find_max_batch_size,
+            prepare_training_config,
+            start_automl_sweep,
+            cancel_automl_sweep,
+            list_automl_sweeps,
+            schedule_training,
+            list_scheduled_jobs,
+            cancel_scheduled_job,
+            list_plugins,
+            run_plugin,
+            get_plugin_outputs,
+            list_wasm_plugins,
+            run_wasm_hook,
+            replay_events,
+            list_architectures,
+            register_architecture,
+            remove_architecture,
+            list_custom_metrics,
+            compare_runs,
+            list_post_job_hooks,
+            save_post_job_hook,
+            remove_post_job_hook,
+            get_hook_log,
+            list_webhooks,
+            save_webhook,
+            remove_webhook,
+            test_webhook,
+            get_preset_library,
+            save_preset_library,
+            export_preset_pack,
+            inspect_preset_pack,
+            import_preset_pack,
+            get_pack_signing_key,
+            trust_pack_signer,
+            get_api_descriptor,
+            negotiate_api_version,
+            get_dev_mode,
+            set_script_dir_override,
+            export_predictions,
+            copy_to_clipboard,
+            generate_model_card,
+            export_model_card,
+            get_audit_settings,
+            set_audit_settings,
+            export_audit_log,
+            get_drift_report,
+            get_drift_settings,
+            set_drift_settings,
+            run_prediction,
+            check_class_spec,
+            confidence_report,
+            get_misclassified,
+            export_misclassified_gallery,
+            export_batch_summary,
+            get_storage_locations,
+            get_python_settings,
+            set_python_settings,
+            get_language_settings,
+            set_language_settings,
+            get_profiles,
+            save_profile,
+            remove_profile,
+            set_profile_prompt,
+            switch_profile,
+            list_recents,
+            add_recent,
+            clear_recents,
+            get_backup_settings,
+            set_backup_settings,
+            list_backups,
+            backup_now,
+            restore_from_backup,
+            export_settings,
+            import_settings,
+            get_active_work,
+            confirm_shutdown,
+            get_startup_report,
+            materialize_augmentations,
+            select_for_labeling,
+            get_review_queue,
+            remove_from_review_queue,
+            create_labeling_batch,
+            list_labeling_batches,
+            assign_labeling_batch,
+            remove_labeling_batch,
+            next_labeling_item,
+            submit_label,
+            set_review_status,
+            get_labeling_stats,
+            get_metrics,
+            get_metrics_settings,
+            set_metrics_settings,
+            get_server_security,
+            set_server_security,
+            list_api_keys,
+            create_api_key,
+            rotate_api_key,
+            revoke_api_key,
+            regenerate_tls_certificate,
+            get_service_endpoints,
+            set_preferred_port,
+            get_job_env_settings,
+            set_job_env_settings,
+            get_dashboard_snapshot,
+            cancel_job,
+            run_training,
+            list_jobs,
+            get_job,
+            clear_jobs,
+            list_python_interpreters,
+            set_python_interpreter,
+            create_venv,
+            install_requirements,
+            get_venv_status,
+            get_backend_mode,
+            set_conda_environment,
+            query_history,
+            register_model,
+            list_models,
+            get_model,
+            delete_model,
+            export_model_onnx,
+            run_batch_prediction,
+            start_watch_folder,
+            stop_watch_folder,
+            list_watch_folders,
+            list_cameras,
+            start_capture,
+            stop_capture,
+            list_captures,
+            scan_dataset,
+            split_dataset,
+            dedupe_dataset,
+            get_thumbnail,
+            clear_thumbnail_cache,
+            read_image_metadata,
+            verify_images,
+            preview_table,
+            list_db_tables,
+            profile_table,
+            get_pipeline,
+            undo_tabular_step,
+            redo_tabular_step,
+            get_gpu_stats,
+            list_gpu_devices,
+            start_resource_monitor,
+            stop_resource_monitor,
+            preflight_check,
+            diagnose_python,
+            get_logs,
+            open_log_dir,
+            list_unfinished_runs,
+            resume_training,
+            list_checkpoints,
+            delete_checkpoint,
+            prune_checkpoints,
+            promote_checkpoint_to_model,
+            run_hpo,
+            get_hpo_study,
+            list_hpo_studies,
+            pause_hpo,
+            resume_hpo,
+            stop_hpo,
+            send_job_control,
+            evaluate_model,
+            export_report,
+            download_pretrained,
+            hf_search,
+            hf_download,
+            hf_token_status,
+            set_hf_token,
+            export_project,
+            import_project,
+            create_project,
+            open_project,
+            save_project,
+            get_active_project,
+            get_settings,
+            update_settings,
+            check_for_updates,
+            install_update,
+            start_api_server,
+            stop_api_server,
+            get_api_server_status
         ])
-        .setup(|app| {
-            let window = app.get_webview_window("main").unwrap();
-            let icon = tauri::include_image!("icons/icon.png");
-            window.set_icon(icon).unwrap();
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if shutdown::intercept(window.app_handle()) {
+                    api.prevent_close();
+                }
+            }
+        })
+        .setup(move |app| {
+            if let Some(window) = app.get_webview_window("main") {
+                let icon = tauri::include_image!("icons/icon.png");
+                window.set_icon(icon).unwrap();
+            }
+
+            app.manage(logging::init(app.handle())?);
+
+            let bus = app.state::<Arc<EventBus>>().inner().clone();
+            events::spawn_dispatcher(app.handle().clone(), bus);
+
+            // Before anything touches the data or settings directories.
+            app.manage(Arc::new(Profiles::load(app.handle())?));
+
+            for error in paths::migrate(app.handle()) {
+                tracing::warn!("{}", error);
+            }
+            app.state::<Arc<Projects>>().restore(app.handle());
+
+            app.manage(Arc::new(Localizer::load(app.handle())?));
+            app.manage(Arc::new(AppSettingsStore::load(app.handle())?));
+
+            let dev = Arc::new(DevMode::load(dev_mode::settings_path(app.handle())?));
+            if let Some(warning) = dev.status().warning {
+                tracing::warn!("{}", warning);
+            }
+            app.manage(dev);
+
+            let audit = Arc::new(AuditLog::load(app.handle())?);
+            if let Err(e) = audit.prune() {
+                tracing::error!("Failed to prune the inference audit log: {}", e);
+            }
+            app.manage(audit);
+            let history = Arc::new(JobHistory::load(app.handle())?);
+            app.state::<Arc<JobManager>>().attach_history(history.clone());
+            app.manage(history);
+            app.manage(Arc::new(DriftMonitor::load(app.handle())?));
+            app.manage(Arc::new(TabularPipelines::load(app.handle())?));
+            app.manage(Arc::new(RunJournal::load(app.handle())?));
+            app.manage(Arc::new(AnnotationStore::load(app.handle())?));
+            app.manage(Arc::new(MetricsCollector::load(app.handle())?));
+            app.manage(Arc::new(ServerSecurity::load(app.handle())?));
+            app.manage(Arc::new(PortManager::load(app.handle())?));
+
+            let scheduler = Arc::new(Scheduler::load(schedule::schedule_path(app.handle())?));
+            app.manage(scheduler.clone());
+            let backups = Arc::new(BackupManager::load(app.handle())?);
+            app.manage(backups.clone());
+
+            if let Some(command) = cli.take() {
+                // A scripted run does only what it was asked to.
+                cli::spawn(app.handle().clone(), command);
+                return Ok(());
+            }
+            let jobs = app.state::<Arc<JobManager>>().inner().clone();
+            schedule::spawn_ticker(app.handle().clone(), scheduler, jobs);
+            backups::spawn_ticker(app.handle().clone(), backups);
+
+            startup_check::spawn(app.handle().clone());
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Quit from the menu or dock; closes by the app itself carry an exit code.
+            if let tauri::RunEvent::ExitRequested { api, code: None, .. } = event {
+                if shutdown::intercept(app) {
+                    api.prevent_exit();
+                }
+            }
+        });
 }
@@ -0,0 +1,185 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::plugins::{self, BUILTIN_METRICS};
+use crate::run_manifest;
+use crate::training::TrainingConfig;
+
+/// A plugin metric resolved to a concrete file and function, as passed to
+/// script.py through `--custom_metrics`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct MetricRef {
+    pub plugin: String,
+    pub name: String,
+    pub path: String,
+    pub function: String,
+    pub higher_is_better: bool,
+}
+
+/// Every metric contributed by a valid plugin.
+pub fn available(app: &tauri::AppHandle) -> Result<Vec<MetricRef>, String> {
+    let mut metrics = Vec::new();
+    for plugin in plugins::discover(app)? {
+        let (Some(manifest), true) = (plugin.manifest, plugin.errors.is_empty()) else {
+            continue;
+        };
+        let dir = Path::new(&plugin.dir);
+        for metric in &manifest.metrics {
+            let module = metric.module.as_deref().unwrap_or(&manifest.entry);
+            metrics.push(MetricRef {
+                plugin: manifest.name.clone(),
+                name: metric.name.clone(),
+                path: dir.join(module).to_string_lossy().to_string(),
+                function: metric.function.clone(),
+                higher_is_better: metric.higher_is_better,
+            });
+        }
+    }
+    Ok(metrics)
+}
+
+/// Resolves `config.custom_metrics` (`metric` or `plugin/metric`) against the
+/// installed plugins so the run records exactly which code computed each value.
+pub fn resolve(app: &tauri::AppHandle, config: &mut TrainingConfig) -> Result<(), String> {
+    if config.custom_metrics.is_empty() {
+        config.metric_plugins.clear();
+        return Ok(());
+    }
+    let available = available(app)?;
+    let mut resolved: Vec<MetricRef> = Vec::new();
+    for requested in &config.custom_metrics {
+        let matches: Vec<&MetricRef> = match requested.split_once('/') {
+            Some((plugin, name)) => available.iter().filter(|m| m.plugin == plugin && m.name == name).collect(),
+            None => available.iter().filter(|m| &m.name == requested).collect(),
+        };
+        match matches.as_slice() {
+            [metric] if resolved.iter().any(|r| r.name == metric.name) => {
+                return Err(format!("Metric '{}' is selected twice.", metric.name));
+            }
+            [metric] => resolved.push((*metric).clone()),
+            [] => return Err(format!("No installed plugin provides metric '{}'.", requested)),
+            _ => {
+                return Err(format!(
+                    "Metric '{}' is provided by several plugins; use 'plugin/{}'.",
+                    requested, requested
+                ))
+            }
+        }
+    }
+    config.metric_plugins = resolved;
+    Ok(())
+}
+
+/// Evaluation results of one finished run, read from its `run_manifest.json`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RunMetrics {
+    pub run_dir: String,
+    pub experiment_id: Option<String>,
+    pub model: Option<String>,
+    /// Built-in and custom metric values by name.
+    pub values: BTreeMap<String, f64>,
+    /// Custom metrics that raised instead of returning a value.
+    pub errors: BTreeMap<String, String>,
+}
+
+/// One row of a run comparison.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MetricColumn {
+    pub name: String,
+    pub builtin: bool,
+    pub higher_is_better: bool,
+    /// Index into `runs` of the best value, when at least one run has it.
+    pub best_run: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RunComparison {
+    pub runs: Vec<RunMetrics>,
+    pub metrics: Vec<MetricColumn>,
+}
+
+fn read_run(run_dir: &str) -> Result<(RunMetrics, BTreeMap<String, bool>), String> {
+    let manifest = run_manifest::load(Path::new(run_dir))?;
+    let evaluation = &manifest["evaluation"];
+
+    let mut values = BTreeMap::new();
+    let mut errors = BTreeMap::new();
+    let mut directions = BTreeMap::new();
+    if let Some(builtin) = evaluation["metrics"].as_object() {
+        values.extend(builtin.iter().filter_map(|(k, v)| Some((k.clone(), v.as_f64()?))));
+    }
+    if let Some(custom) = evaluation["custom_metrics"].as_object() {
+        for (name, entry) in custom {
+            directions.insert(name.clone(), entry["higher_is_better"].as_bool().unwrap_or(true));
+            match (entry["value"].as_f64(), entry["error"].as_str()) {
+                (Some(value), _) => {
+                    values.insert(name.clone(), value);
+                }
+                (None, Some(error)) => {
+                    errors.insert(name.clone(), error.to_string());
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    let run = RunMetrics {
+        run_dir: run_dir.to_string(),
+        experiment_id: manifest["experiment_id"].as_str().map(str::to_string),
+        model: manifest["config"]["model"].as_str().map(str::to_string),
+        values,
+        errors,
+    };
+    Ok((run, directions))
+}
+
+/// Lines up built-in and custom metrics of several runs and marks the best run
+/// per metric. Runs evaluated before a metric existed simply have no value.
+pub fn compare(run_dirs: &[String]) -> Result<RunComparison, String> {
+    let mut runs = Vec::new();
+    let mut directions: BTreeMap<String, bool> = BTreeMap::new();
+    for dir in run_dirs {
+        let (run, run_directions) = read_run(dir)?;
+        directions.extend(run_directions);
+        runs.push(run);
+    }
+
+    let mut names: Vec<String> = BUILTIN_METRICS.iter().map(|s| s.to_string()).collect();
+    names.extend(
+        runs.iter()
+            .flat_map(|r| r.values.keys().chain(r.errors.keys()))
+            .filter(|name| !BUILTIN_METRICS.contains(&name.as_str()))
+            .cloned()
+            .collect::<std::collections::BTreeSet<_>>(),
+    );
+
+    let metrics = names
+        .into_iter()
+        .map(|name| {
+            let higher_is_better = directions.get(&name).copied().unwrap_or(true);
+            let best_run = runs
+                .iter()
+                .enumerate()
+                .filter_map(|(i, r)| Some((i, *r.values.get(&name)?)))
+                .reduce(|best, cur| {
+                    let better = if higher_is_better { cur.1 > best.1 } else { cur.1 < best.1 };
+                    if better {
+                        cur
+                    } else {
+                        best
+                    }
+                })
+                .map(|(i, _)| i);
+            MetricColumn {
+                builtin: BUILTIN_METRICS.contains(&name.as_str()),
+                name,
+                higher_is_better,
+                best_run,
+            }
+        })
+        .collect();
+
+    Ok(RunComparison { runs, metrics })
+}
@@ -0,0 +1,199 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::Localizer;
+use crate::jobs::unix_now;
+use crate::metrics;
+use crate::run_manifest;
+
+const CARD_JSON: &str = "model_card.json";
+const CARD_MARKDOWN: &str = "model_card.md";
+const PLACEHOLDER: &str = "_To be completed by the model owner._";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CardFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct DatasetSummary {
+    pub path: Option<String>,
+    pub classes: Vec<String>,
+    /// Image count per split (train/val/test).
+    pub splits: BTreeMap<String, u64>,
+}
+
+/// Model card for a trained model. `model_id` is the run directory holding the
+/// weights and run_manifest.json; the card is written next to them.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModelCard {
+    pub model_id: String,
+    pub name: String,
+    pub generated_at: u64,
+    pub architecture: Option<String>,
+    pub dataset: DatasetSummary,
+    /// Hyperparameters the run was trained with.
+    pub training: BTreeMap<String, serde_json::Value>,
+    /// Test-set metrics, built-in and custom.
+    pub metrics: BTreeMap<String, f64>,
+    /// Free-text sections. Regenerating keeps whatever the owner filled in.
+    pub intended_use: String,
+    pub limitations: String,
+    pub ethical_considerations: String,
+}
+
+/// Config keys worth showing on a card; paths and UI-only options are left out.
+const TRAINING_KEYS: &[&str] = &[
+    "model",
+    "epochs",
+    "batch_size",
+    "learning_rate",
+    "accumulation_steps",
+    "patience",
+    "freeze_mode",
+    "freeze_epochs",
+    "lr_schedule",
+    "seed",
+    "deterministic",
+];
+
+fn previous_card(run_dir: &Path) -> Option<ModelCard> {
+    let raw = std::fs::read_to_string(run_dir.join(CARD_JSON)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// Builds the card from the run manifest and writes `model_card.json` and
+/// `model_card.md` into the run directory.
+pub fn generate(model_id: &str, l10n: &Localizer) -> Result<ModelCard, String> {
+    let run_dir = PathBuf::from(model_id);
+    let manifest = run_manifest::load(&run_dir)?;
+    let config = &manifest["config"];
+
+    let dataset = DatasetSummary {
+        path: manifest["dataset"]["path"]
+            .as_str()
+            .or(config["path"].as_str())
+            .map(String::from),
+        classes: serde_json::from_value(manifest["classes"].clone()).unwrap_or_default(),
+        splits: serde_json::from_value(manifest["dataset"]["splits"].clone()).unwrap_or_default(),
+    };
+    let training = TRAINING_KEYS
+        .iter()
+        .filter_map(|k| config.get(*k).filter(|v| !v.is_null()).map(|v| (k.to_string(), v.clone())))
+        .collect();
+    // Runs without a test split have no evaluation; the card still documents them.
+    let metrics = metrics::compare(&[model_id.to_string()])
+        .ok()
+        .and_then(|c| c.runs.into_iter().next())
+        .map(|r| r.values)
+        .unwrap_or_default();
+    let architecture = manifest["architecture"]["name"]
+        .as_str()
+        .or(config["model"].as_str())
+        .map(String::from);
+    let name = manifest["experiment_id"]
+        .as_str()
+        .map(String::from)
+        .or_else(|| run_dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| model_id.to_string());
+
+    let previous = previous_card(&run_dir);
+    let keep = |f: fn(&ModelCard) -> &String| {
+        previous.as_ref().map(f).cloned().unwrap_or_else(|| PLACEHOLDER.to_string())
+    };
+    let card = ModelCard {
+        model_id: model_id.to_string(),
+        name,
+        generated_at: unix_now(),
+        architecture,
+        dataset,
+        training,
+        metrics,
+        intended_use: keep(|c| &c.intended_use),
+        limitations: keep(|c| &c.limitations),
+        ethical_considerations: keep(|c| &c.ethical_considerations),
+    };
+
+    let raw = serde_json::to_string_pretty(&card).map_err(|e| e.to_string())?;
+    std::fs::write(run_dir.join(CARD_JSON), raw).map_err(|e| format!("Cannot write model card: {}", e))?;
+    std::fs::write(run_dir.join(CARD_MARKDOWN), to_markdown(&card, l10n))
+        .map_err(|e| format!("Cannot write model card: {}", e))?;
+    Ok(card)
+}
+
+fn to_markdown(card: &ModelCard, l10n: &Localizer) -> String {
+    let t = |id: &str| l10n.text(id, &[]);
+    let mut out = format!("# {}\n\n", l10n.text("card-title", &[("name", card.name.as_str().into())]));
+    out.push_str(&format!("- **{}:** `{}`\n", t("card-model-id"), card.model_id));
+    out.push_str(&format!(
+        "- **{}:** {}\n",
+        t("card-architecture"),
+        card.architecture.clone().unwrap_or_else(|| t("card-unknown"))
+    ));
+    out.push_str(&format!(
+        "- **{}:** {}\n\n",
+        t("card-generated"),
+        l10n.text("card-generated-value", &[("time", card.generated_at.to_string().into())])
+    ));
+
+    out.push_str(&format!("## {}\n\n{}\n\n", t("card-intended-use"), card.intended_use));
+
+    out.push_str(&format!("## {}\n\n", t("card-dataset")));
+    if let Some(path) = &card.dataset.path {
+        out.push_str(&format!("- **{}:** `{}`\n", t("card-source"), path));
+    }
+    out.push_str(&format!(
+        "- **{}:** {}\n",
+        l10n.text("card-classes", &[("count", card.dataset.classes.len().to_string().into())]),
+        card.dataset.classes.join(", ")
+    ));
+    for (split, count) in &card.dataset.splits {
+        out.push_str(&format!(
+            "- **{}:** {}\n",
+            l10n.text("card-split-images", &[("split", split.as_str().into())]),
+            count
+        ));
+    }
+
+    out.push_str(&format!(
+        "\n## {}\n\n| {} | {} |\n| --- | --- |\n",
+        t("card-training"),
+        t("card-parameter"),
+        t("card-value")
+    ));
+    for (key, value) in &card.training {
+        let value = value.as_str().map(String::from).unwrap_or_else(|| value.to_string());
+        out.push_str(&format!("| {} | {} |\n", key, value.replace('|', "\\|")));
+    }
+
+    out.push_str(&format!("\n## {}\n\n", t("card-evaluation")));
+    if card.metrics.is_empty() {
+        out.push_str(&format!("{}\n", t("card-no-evaluation")));
+    } else {
+        out.push_str(&format!("| {} | {} |\n| --- | --- |\n", t("card-metric"), t("card-value")));
+        for (name, value) in &card.metrics {
+            out.push_str(&format!("| {} | {:.4} |\n", name, value));
+        }
+    }
+
+    out.push_str(&format!("\n## {}\n\n{}\n", t("card-limitations"), card.limitations));
+    out.push_str(&format!("\n## {}\n\n{}\n", t("card-ethical-considerations"), card.ethical_considerations));
+    out
+}
+
+/// Writes the run's card to `dest`, generating it first if the run has none.
+pub fn export(model_id: &str, format: CardFormat, dest: &Path, l10n: &Localizer) -> Result<(), String> {
+    let card = match previous_card(Path::new(model_id)) {
+        Some(card) => card,
+        None => generate(model_id, l10n)?,
+    };
+    let contents = match format {
+        CardFormat::Json => serde_json::to_string_pretty(&card).map_err(|e| e.to_string())?,
+        CardFormat::Markdown => to_markdown(&card, l10n),
+    };
+    std::fs::write(dest, contents).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))
+}
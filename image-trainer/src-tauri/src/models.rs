@@ -0,0 +1,241 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use crate::jobs::unix_now;
+use crate::metrics;
+use crate::paths;
+use crate::run_manifest;
+
+const REGISTRY_FILE: &str = "registry.json";
+const WEIGHTS: &str = "best_model.pth";
+
+/// Serializes read-modify-write cycles on the registry file.
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+/// A trained model in the registry. `id` is its run directory, the same
+/// `model_id` prediction and model card commands take.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RegisteredModel {
+    pub id: String,
+    pub name: String,
+    pub architecture: Option<String>,
+    /// Class names in output order.
+    pub classes: Vec<String>,
+    /// Dataset the model was trained on.
+    pub dataset: Option<String>,
+    /// Test-set metrics, built-in and custom; empty for runs without a test split.
+    pub metrics: BTreeMap<String, f64>,
+    /// When the weights were written.
+    pub created_at: u64,
+    pub registered_at: u64,
+    /// The run directory or its weights are gone; the entry stays until deleted.
+    #[serde(default)]
+    pub missing: bool,
+    /// Last ONNX export made with `export_model_onnx`.
+    #[serde(default)]
+    pub onnx_path: Option<String>,
+    /// Best parameters found by the hyperparameter study that produced the model.
+    #[serde(default)]
+    pub hyperparameters: Option<serde_json::Value>,
+}
+
+/// Stored as `registry.json` in the models folder.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Registry {
+    models: Vec<RegisteredModel>,
+    /// Runs in the models folder removed from the registry but not deleted,
+    /// which the scan must not add back.
+    ignored: Vec<String>,
+}
+
+/// Shared by every profile that shares the models folder.
+fn registry_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::models_dir(app)?.join(REGISTRY_FILE))
+}
+
+fn load(app: &tauri::AppHandle) -> Result<Registry, String> {
+    let path = registry_path(app)?;
+    if !path.exists() {
+        return Ok(Registry::default());
+    }
+    let raw = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid model registry: {}", e))
+}
+
+fn save(app: &tauri::AppHandle, registry: &Registry) -> Result<(), String> {
+    let path = registry_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(registry).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| e.to_string())
+}
+
+fn is_run_dir(dir: &Path) -> bool {
+    dir.join(run_manifest::FILE_NAME).is_file()
+}
+
+fn modified_at(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+/// Reads a run directory's manifest and metrics into a registry entry.
+fn describe(run_dir: &Path, name: Option<String>) -> Result<RegisteredModel, String> {
+    let manifest = run_manifest::load(run_dir)
+        .map_err(|e| format!("{} is not a training run: {}", run_dir.display(), e))?;
+    let config = &manifest["config"];
+    let id = run_dir.to_string_lossy().to_string();
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| manifest["experiment_id"].as_str().map(String::from))
+        .or_else(|| run_dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| id.clone());
+    let metrics = metrics::compare(std::slice::from_ref(&id))
+        .ok()
+        .and_then(|c| c.runs.into_iter().next())
+        .map(|r| r.values)
+        .unwrap_or_default();
+    let weights = run_dir.join(WEIGHTS);
+    Ok(RegisteredModel {
+        name,
+        architecture: manifest["architecture"]["name"]
+            .as_str()
+            .or(config["model"].as_str())
+            .map(String::from),
+        classes: serde_json::from_value(manifest["classes"].clone()).unwrap_or_default(),
+        dataset: manifest["dataset"]["path"]
+            .as_str()
+            .or(config["path"].as_str())
+            .map(String::from),
+        metrics,
+        created_at: modified_at(&weights).or_else(|| modified_at(&manifest_path)).unwrap_or_else(unix_now),
+        registered_at: unix_now(),
+        missing: !weights.is_file(),
+        onnx_path: None,
+        hyperparameters: Some(manifest["hpo"]["best_params"].clone()).filter(|params| params.is_object()),
+        id,
+    })
+}
+
+/// Adds a run directory to the registry, or refreshes its entry after it was
+/// evaluated again. A previously given name is kept unless a new one is passed.
+pub fn register(app: &tauri::AppHandle, run_dir: &str, name: Option<String>) -> Result<RegisteredModel, String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = load(app)?;
+    let existing = registry.models.iter().position(|m| m.id == run_dir);
+    let name = name.or_else(|| existing.map(|i| registry.models[i].name.clone()));
+    let mut model = describe(Path::new(run_dir), name)?;
+    match existing {
+        Some(i) => {
+            model.registered_at = registry.models[i].registered_at;
+            model.onnx_path = registry.models[i].onnx_path.take();
+            registry.models[i] = model.clone();
+        }
+        None => registry.models.push(model.clone()),
+    }
+    registry.ignored.retain(|id| id != run_dir);
+    save(app, &registry)?;
+    Ok(model)
+}
+
+/// Records where a model was exported to ONNX, registering it first if needed.
+pub fn set_onnx_path(app: &tauri::AppHandle, id: &str, path: &str) -> Result<RegisteredModel, String> {
+    register(app, id, None)?;
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = load(app)?;
+    let model = registry
+        .models
+        .iter_mut()
+        .find(|m| m.id == id)
+        .ok_or_else(|| format!("No registered model {}", id))?;
+    model.onnx_path = Some(path.to_string());
+    let model = model.clone();
+    save(app, &registry)?;
+    Ok(model)
+}
+
+/// Run directories directly inside the models folder or one level below.
+fn scan_models_dir(app: &tauri::AppHandle) -> Vec<PathBuf> {
+    let Ok(root) = paths::models_dir(app) else {
+        return Vec::new();
+    };
+    let children = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect())
+            .unwrap_or_default()
+    };
+    let mut runs = Vec::new();
+    for dir in children(&root) {
+        if is_run_dir(&dir) {
+            runs.push(dir);
+        } else {
+            runs.extend(children(&dir).into_iter().filter(|d| is_run_dir(d)));
+        }
+    }
+    runs
+}
+
+/// Every registered model, newest first. Runs saved into the models folder
+/// are registered on the way, and entries whose files are gone are marked.
+pub fn list(app: &tauri::AppHandle) -> Result<Vec<RegisteredModel>, String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = load(app)?;
+    let mut changed = false;
+    for dir in scan_models_dir(app) {
+        let id = dir.to_string_lossy().to_string();
+        if registry.models.iter().any(|m| m.id == id) || registry.ignored.contains(&id) {
+            continue;
+        }
+        if let Ok(model) = describe(&dir, None) {
+            registry.models.push(model);
+            changed = true;
+        }
+    }
+    for model in &mut registry.models {
+        let missing = !Path::new(&model.id).join(WEIGHTS).is_file();
+        changed |= model.missing != missing;
+        model.missing = missing;
+    }
+    if changed {
+        save(app, &registry)?;
+    }
+    let mut models = registry.models;
+    models.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(models)
+}
+
+pub fn get(app: &tauri::AppHandle, id: &str) -> Result<RegisteredModel, String> {
+    list(app)?
+        .into_iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| format!("No registered model {}", id))
+}
+
+/// Removes a model from the registry. With `delete_files`, its run directory
+/// goes too; otherwise the files stay and the scan skips them from now on.
+pub fn delete(app: &tauri::AppHandle, id: &str, delete_files: bool) -> Result<(), String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = load(app)?;
+    let before = registry.models.len();
+    registry.models.retain(|m| m.id != id);
+    if registry.models.len() == before {
+        return Err(format!("No registered model {}", id));
+    }
+    let dir = Path::new(id);
+    if delete_files && dir.exists() {
+        // Only ever remove something that looks like a training run.
+        if !is_run_dir(dir) {
+            return Err(format!("{} is not a training run; not deleting it.", dir.display()));
+        }
+        std::fs::remove_dir_all(dir).map_err(|e| format!("Cannot delete {}: {}", dir.display(), e))?;
+    } else if is_run_dir(dir) {
+        registry.ignored.push(id.to_string());
+    }
+    save(app, &registry)
+}
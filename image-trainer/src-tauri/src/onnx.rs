@@ -0,0 +1,115 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::BackendError;
+use crate::events;
+use crate::jobs::new_id;
+use crate::models;
+use crate::preflight;
+use crate::python::{backend_script, parse_output, run_python_observed};
+
+const SCRIPT: &str = "export_onnx.py";
+/// Opset used when none is given; supported by current ONNX Runtime releases.
+const DEFAULT_OPSET: u32 = 17;
+const OPSETS: std::ops::RangeInclusive<u32> = 9..=21;
+
+/// Published as `onnx://progress` while a model is exported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnnxProgress {
+    /// `loading`, `exporting` or `verifying`.
+    pub stage: String,
+    /// 0 to 1.
+    pub progress: f64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OnnxExport {
+    pub model_id: String,
+    pub path: String,
+    pub opset: u32,
+    /// Side of the square image the model takes, in pixels.
+    pub input_size: u32,
+    /// Hash of the exported weights, as in prediction records.
+    pub model_version: String,
+    /// Checked with the `onnx` package, which also stored the class names and
+    /// preprocessing in the file; false when the package is not installed.
+    pub verified: bool,
+    pub size_bytes: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ScriptResult {
+    Success {
+        path: String,
+        opset: u32,
+        input_size: u32,
+        model_version: String,
+        verified: bool,
+        size_bytes: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Exports the model in run directory `model_id` to `out_path` as job
+/// `job_id`, then stores the path in the model registry.
+pub async fn export(
+    app: &tauri::AppHandle,
+    model_id: &str,
+    out_path: &str,
+    opset: Option<u32>,
+    job_id: Option<String>,
+) -> Result<OnnxExport, BackendError> {
+    if !Path::new(model_id).join("best_model.pth").is_file() {
+        return Err(BackendError::invalid_input(format!("{} has no trained weights (best_model.pth).", model_id)));
+    }
+    if !out_path.to_lowercase().ends_with(".onnx") {
+        return Err(BackendError::invalid_input("The output file must end in .onnx."));
+    }
+    preflight::check_onnx_export(model_id, out_path).into_result()?;
+    let opset = opset.unwrap_or(DEFAULT_OPSET);
+    if !OPSETS.contains(&opset) {
+        return Err(BackendError::invalid_input(format!(
+            "Opset {} is not supported; use {} to {}.",
+            opset,
+            OPSETS.start(),
+            OPSETS.end()
+        )));
+    }
+
+    let script = backend_script(app, SCRIPT)?;
+    let opset_arg = opset.to_string();
+    let job_id = job_id.unwrap_or_else(|| new_id("onnx"));
+    let args = [script.as_str(), "--model_dir", model_id, "--out", out_path, "--opset", opset_arg.as_str()];
+    let output = run_python_observed(app, &job_id, &args, |line| {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            return;
+        };
+        if value["status"] == "progress" {
+            if let Ok(progress) = serde_json::from_value::<OnnxProgress>(value) {
+                events::publish(app, "onnx://progress", Some(&job_id), progress);
+            }
+        }
+    })
+    .await?;
+
+    match parse_output(SCRIPT, &output)? {
+        ScriptResult::Success { path, opset, input_size, model_version, verified, size_bytes } => {
+            models::set_onnx_path(app, model_id, &path)?;
+            Ok(OnnxExport {
+                model_id: model_id.to_string(),
+                path,
+                opset,
+                input_size,
+                model_version,
+                verified,
+                size_bytes,
+            })
+        }
+        ScriptResult::Error { message } => Err(BackendError::ScriptFailed { message, code: None, stderr: String::new() }),
+    }
+}
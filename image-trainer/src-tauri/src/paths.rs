@@ -0,0 +1,152 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::Manager;
+
+use crate::error::BackendError;
+use crate::profiles::Profiles;
+
+/// Settings files, which belong in the platform's config location rather
+/// than next to data. They lived in the data directory before.
+const SETTINGS_FILES: &[&str] = &["dev_settings.json", "audit_settings.json", "drift_settings.json"];
+
+/// Where each kind of app file lives. Tauri maps these to the platform
+/// conventions: XDG base directories on Linux, `AppData\Roaming` (data,
+/// settings) and `AppData\Local` (cache, logs) on Windows, and
+/// `~/Library/{Application Support,Caches,Logs}` on macOS.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StorageLocations {
+    /// User preferences (`$XDG_CONFIG_HOME`).
+    pub settings: String,
+    /// Registries, stored predictions and logs of record (`$XDG_DATA_HOME`).
+    pub data: String,
+    /// Default location for trained models.
+    pub models: String,
+    pub plugins: String,
+    /// Disposable files; safe to delete while the app is closed.
+    pub cache: String,
+    pub logs: String,
+}
+
+/// Subdirectory of the active profile; `None` for the default profile and
+/// before profiles are loaded.
+fn profile_scope(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.try_state::<Arc<Profiles>>().and_then(|p| p.scope())
+}
+
+/// App data shared by every profile, such as installed plugins.
+pub fn shared_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, BackendError> {
+    app.path().app_data_dir().map_err(BackendError::io)
+}
+
+/// Persistent app data of the active profile: registries, stores and databases.
+pub fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, BackendError> {
+    let root = shared_data_dir(app)?;
+    Ok(match profile_scope(app) {
+        Some(scope) => root.join(scope),
+        None => root,
+    })
+}
+
+pub fn data_file(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, BackendError> {
+    Ok(data_dir(app)?.join(name))
+}
+
+pub fn settings_dir(app: &tauri::AppHandle) -> Result<PathBuf, BackendError> {
+    let root = app.path().app_config_dir().map_err(BackendError::io)?;
+    Ok(match profile_scope(app) {
+        Some(scope) => root.join(scope),
+        None => root,
+    })
+}
+
+pub fn settings_file(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, BackendError> {
+    Ok(settings_dir(app)?.join(name))
+}
+
+/// The shared models folder, or the profile's own if it opted out of sharing.
+pub fn models_dir(app: &tauri::AppHandle) -> Result<PathBuf, BackendError> {
+    let shared = app
+        .try_state::<Arc<Profiles>>()
+        .is_none_or(|p| p.active().share_models);
+    let base = if shared { shared_data_dir(app)? } else { data_dir(app)? };
+    Ok(base.join("models"))
+}
+
+pub fn cache_dir(app: &tauri::AppHandle) -> Result<PathBuf, BackendError> {
+    app.path().app_cache_dir().map_err(BackendError::io)
+}
+
+/// A scratch file in the cache directory, creating the directory if needed.
+pub fn cache_file(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, BackendError> {
+    let dir = cache_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| BackendError::io(format!("Cannot create {}: {}", dir.display(), e)))?;
+    Ok(dir.join(name))
+}
+
+pub fn log_dir(app: &tauri::AppHandle) -> Result<PathBuf, BackendError> {
+    app.path().app_log_dir().map_err(BackendError::io)
+}
+
+pub fn locations(app: &tauri::AppHandle) -> Result<StorageLocations, BackendError> {
+    let text = |p: PathBuf| p.to_string_lossy().to_string();
+    Ok(StorageLocations {
+        settings: text(settings_dir(app)?),
+        data: text(data_dir(app)?),
+        models: text(models_dir(app)?),
+        plugins: text(crate::plugins::plugins_dir(app)?),
+        cache: text(cache_dir(app)?),
+        logs: text(log_dir(app)?),
+    })
+}
+
+/// Moves `from` to `to` unless `to` already exists. Falls back to copying
+/// when a rename is impossible (e.g. across drives).
+fn move_path(from: &Path, to: &Path) -> Result<(), BackendError> {
+    if !from.exists() || to.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(BackendError::io)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        std::fs::create_dir_all(to).map_err(BackendError::io)?;
+        for entry in std::fs::read_dir(from).map_err(BackendError::io)?.flatten() {
+            move_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        std::fs::remove_dir_all(from).map_err(BackendError::io)
+    } else {
+        std::fs::copy(from, to).map_err(BackendError::io)?;
+        std::fs::remove_file(from).map_err(BackendError::io)
+    }
+}
+
+/// Moves files written by earlier versions, which kept everything in the data
+/// directory, to their proper locations. Runs at startup before any state is
+/// loaded; failures are reported and the old file is left in place.
+pub fn migrate(app: &tauri::AppHandle) -> Vec<String> {
+    if profile_scope(app).is_some() {
+        return Vec::new();
+    }
+    let Ok(data) = data_dir(app) else {
+        return Vec::new();
+    };
+    let mut moves: Vec<(PathBuf, Result<PathBuf, BackendError>)> = SETTINGS_FILES
+        .iter()
+        .map(|name| (data.join(name), settings_file(app, name)))
+        .collect();
+    moves.push((data.join("hook_logs"), log_dir(app).map(|d| d.join("hooks"))));
+
+    let mut errors = Vec::new();
+    for (from, to) in moves {
+        let result = to.and_then(|to| if from == to { Ok(()) } else { move_path(&from, &to) });
+        if let Err(e) = result {
+            errors.push(format!("Could not migrate {}: {}", from.display(), e));
+        }
+    }
+    errors
+}
@@ -0,0 +1,377 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::jobs::{new_id, spawn_job, JobManager, JobSpec};
+use crate::paths;
+use crate::python::{resolve_launcher, run_python};
+
+pub const MANIFEST_FILE: &str = "plugin.toml";
+
+/// Metrics script.py always computes; plugin metrics may not reuse these names.
+pub const BUILTIN_METRICS: &[&str] = &["accuracy", "macro_f1", "weighted_f1", "macro_precision", "macro_recall"];
+
+/// Contents of a plugin's `plugin.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Python file inside the plugin folder that is executed.
+    pub entry: String,
+    /// pip distribution names the plugin imports.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    #[serde(default)]
+    pub inputs: Vec<PluginInput>,
+    #[serde(default)]
+    pub outputs: Vec<PluginOutput>,
+    /// Evaluation metrics the plugin contributes (see `metrics.rs`).
+    #[serde(default)]
+    pub metrics: Vec<MetricSpec>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum InputKind {
+    String,
+    Number,
+    Bool,
+    /// Existing file or folder on disk.
+    Path,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginInput {
+    pub name: String,
+    pub kind: InputKind,
+    #[serde(default = "default_true")]
+    pub required: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A file the plugin writes into its working directory.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginOutput {
+    pub name: String,
+    pub file: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A custom evaluation metric: `function(y_true, y_pred, y_prob, class_names) -> float`
+/// defined in `module` (the plugin's entry file when omitted).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MetricSpec {
+    pub name: String,
+    pub function: String,
+    #[serde(default)]
+    pub module: Option<String>,
+    #[serde(default = "default_true")]
+    pub higher_is_better: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A plugin folder as discovered on disk. Invalid plugins are still listed
+/// so the UI can show why they were rejected.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PluginInfo {
+    pub dir: String,
+    pub manifest: Option<PluginManifest>,
+    pub errors: Vec<String>,
+}
+
+pub fn plugins_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::shared_data_dir(app)?.join("plugins"))
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn is_python_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// True when `relative` stays inside its base folder once joined (no absolute
+/// paths, no `..` components).
+pub fn is_contained(relative: &str) -> bool {
+    let path = Path::new(relative);
+    !relative.is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir))
+}
+
+impl PluginManifest {
+    /// Checks the manifest against its plugin folder and returns all problems found.
+    pub fn validate(&self, dir: &Path) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if !is_valid_name(&self.name) {
+            errors.push(format!(
+                "Plugin name '{}' must be 1-64 characters of letters, digits, '-' or '_'.",
+                self.name
+            ));
+        }
+        if !is_contained(&self.entry) {
+            errors.push(format!("Entry '{}' must be a relative path inside the plugin folder.", self.entry));
+        } else if !self.entry.ends_with(".py") {
+            errors.push(format!("Entry '{}' must be a Python file.", self.entry));
+        } else if !dir.join(&self.entry).is_file() {
+            errors.push(format!("Entry '{}' does not exist.", self.entry));
+        }
+
+        let mut seen = HashSet::new();
+        for input in &self.inputs {
+            if !is_valid_name(&input.name) {
+                errors.push(format!("Input name '{}' is not a valid identifier.", input.name));
+            }
+            if !seen.insert(input.name.as_str()) {
+                errors.push(format!("Input '{}' is declared twice.", input.name));
+            }
+        }
+        for output in &self.outputs {
+            if !is_contained(&output.file) {
+                errors.push(format!(
+                    "Output '{}' must be a relative path inside the working directory.",
+                    output.name
+                ));
+            }
+        }
+        let mut metric_names = HashSet::new();
+        for metric in &self.metrics {
+            if !is_valid_name(&metric.name) || BUILTIN_METRICS.contains(&metric.name.as_str()) {
+                errors.push(format!("Metric name '{}' is invalid or shadows a built-in metric.", metric.name));
+            }
+            if !metric_names.insert(metric.name.as_str()) {
+                errors.push(format!("Metric '{}' is declared twice.", metric.name));
+            }
+            if !is_python_identifier(&metric.function) {
+                errors.push(format!("Metric function '{}' is not a valid Python name.", metric.function));
+            }
+            let module = metric.module.as_deref().unwrap_or(&self.entry);
+            if !is_contained(module) || !module.ends_with(".py") || !dir.join(module).is_file() {
+                errors.push(format!("Metric module '{}' must be an existing Python file inside the plugin folder.", module));
+            }
+        }
+        for package in &self.requires {
+            if package.trim().is_empty() || package.contains(char::is_whitespace) {
+                errors.push(format!("Required package '{}' is not a valid name.", package));
+            }
+        }
+
+        errors
+    }
+
+    /// Checks supplied input values against the declared inputs and returns the
+    /// JSON object handed to the plugin.
+    pub fn check_inputs(
+        &self,
+        values: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        if let Some(unknown) = values.keys().find(|k| !self.inputs.iter().any(|i| &i.name == *k)) {
+            return Err(format!("Plugin {} has no input named '{}'.", self.name, unknown));
+        }
+        for input in &self.inputs {
+            let Some(value) = values.get(&input.name).filter(|v| !v.is_null()) else {
+                if input.required {
+                    return Err(format!("Missing required input '{}'.", input.name));
+                }
+                continue;
+            };
+            let ok = match input.kind {
+                InputKind::String => value.is_string(),
+                InputKind::Number => value.is_number(),
+                InputKind::Bool => value.is_boolean(),
+                InputKind::Path => value.as_str().is_some_and(|p| Path::new(p).exists()),
+            };
+            if !ok {
+                return Err(format!(
+                    "Input '{}' must be a {:?}{}.",
+                    input.name,
+                    input.kind,
+                    if input.kind == InputKind::Path { " that exists" } else { "" }
+                ));
+            }
+        }
+        Ok(serde_json::Value::Object(values.clone()))
+    }
+}
+
+fn load_plugin(dir: &Path) -> PluginInfo {
+    let mut info = PluginInfo {
+        dir: dir.to_string_lossy().to_string(),
+        manifest: None,
+        errors: Vec::new(),
+    };
+    let raw = match std::fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Ok(raw) => raw,
+        Err(e) => {
+            info.errors.push(format!("Cannot read {}: {}", MANIFEST_FILE, e));
+            return info;
+        }
+    };
+    match toml::from_str::<PluginManifest>(&raw) {
+        Ok(manifest) => {
+            info.errors = manifest.validate(dir);
+            info.manifest = Some(manifest);
+        }
+        Err(e) => info.errors.push(format!("Invalid {}: {}", MANIFEST_FILE, e)),
+    }
+    info
+}
+
+/// Lists every folder in the plugins directory that contains a manifest.
+pub fn discover(app: &tauri::AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let dir = plugins_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut plugins: Vec<PluginInfo> = std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .map(|path| load_plugin(&path))
+        .collect();
+
+    // Duplicate names would make `run_plugin` ambiguous
+    let mut names = HashSet::new();
+    for plugin in &mut plugins {
+        if let Some(manifest) = &plugin.manifest {
+            if !names.insert(manifest.name.clone()) {
+                plugin.errors.push(format!("Another plugin is already named '{}'.", manifest.name));
+            }
+        }
+    }
+    Ok(plugins)
+}
+
+/// Returns the distributions from `requires` that are not installed.
+async fn missing_packages(app: &tauri::AppHandle, requires: &[String]) -> Result<Vec<String>, String> {
+    if requires.is_empty() {
+        return Ok(Vec::new());
+    }
+    let script = "import sys, json, importlib.metadata as m\n\
+                  missing = []\n\
+                  for p in sys.argv[1:]:\n    \
+                      try: m.version(p)\n    \
+                      except m.PackageNotFoundError: missing.append(p)\n\
+                  print(json.dumps(missing))";
+    let mut args = vec!["-c", script];
+    args.extend(requires.iter().map(String::as_str));
+    let output = run_python(app, &args).await?;
+    serde_json::from_str(output.trim()).map_err(|e| format!("Unexpected package check output: {}", e))
+}
+
+/// Runs a plugin as a job. Each run gets its own working directory under
+/// `plugin_runs/<plugin>/<job_id>`, also used as its temp directory; the plugin
+/// receives its inputs as JSON via `--inputs` and the directory via
+/// `EPOQ_PLUGIN_WORKDIR`. This only isolates the working directory and
+/// environment, it is not a sandbox: the script runs with the user's
+/// permissions and can read and write anything they can.
+pub async fn run(
+    app: &tauri::AppHandle,
+    manager: &Arc<JobManager>,
+    name: &str,
+    inputs: serde_json::Map<String, serde_json::Value>,
+) -> Result<String, String> {
+    let plugin = discover(app)?
+        .into_iter()
+        .find(|p| p.manifest.as_ref().is_some_and(|m| m.name == name))
+        .ok_or_else(|| format!("No plugin named '{}'.", name))?;
+    if !plugin.errors.is_empty() {
+        return Err(format!("Plugin '{}' is invalid: {}", name, plugin.errors.join(" ")));
+    }
+    let manifest = plugin.manifest.expect("checked above");
+    let inputs = manifest.check_inputs(&inputs)?;
+
+    let missing = missing_packages(app, &manifest.requires).await?;
+    if !missing.is_empty() {
+        return Err(format!(
+            "Plugin '{}' requires packages that are not installed: {}",
+            name,
+            missing.join(", ")
+        ));
+    }
+
+    let job_id = new_id("plugin");
+    let workdir = paths::data_file(app, "plugin_runs")?
+        .join(&manifest.name)
+        .join(&job_id);
+    std::fs::create_dir_all(&workdir).map_err(|e| e.to_string())?;
+
+    let entry = Path::new(&plugin.dir).join(&manifest.entry);
+    let launcher = resolve_launcher(app).await?;
+    let spec = JobSpec {
+        kind: "plugin".to_string(),
+        program: launcher.program.clone(),
+        args: launcher.with_args([
+            entry.to_string_lossy().to_string(),
+            "--inputs".to_string(),
+            inputs.to_string(),
+        ]),
+        cwd: Some(workdir.clone()),
+        env: vec![
+            ("EPOQ_PLUGIN_WORKDIR".to_string(), workdir.to_string_lossy().to_string()),
+            ("EPOQ_PLUGIN_NAME".to_string(), manifest.name.clone()),
+            ("TMPDIR".to_string(), workdir.to_string_lossy().to_string()),
+            ("TEMP".to_string(), workdir.to_string_lossy().to_string()),
+            ("TMP".to_string(), workdir.to_string_lossy().to_string()),
+        ],
+        ..Default::default()
+    };
+    spawn_job(app, manager, job_id, spec)
+}
+
+/// A declared output resolved against one run's working directory.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PluginRunOutput {
+    pub name: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Resolves a plugin's declared outputs for a finished run.
+pub fn run_outputs(app: &tauri::AppHandle, name: &str, job_id: &str) -> Result<Vec<PluginRunOutput>, String> {
+    if !is_valid_name(name) || !is_valid_name(job_id) {
+        return Err("Invalid plugin or job id.".to_string());
+    }
+    let manifest = discover(app)?
+        .into_iter()
+        .find_map(|p| p.manifest.filter(|m| m.name == name))
+        .ok_or_else(|| format!("No plugin named '{}'.", name))?;
+    let workdir = paths::data_file(app, "plugin_runs")?
+        .join(name)
+        .join(job_id);
+
+    Ok(manifest
+        .outputs
+        .iter()
+        .filter(|o| is_contained(&o.file))
+        .map(|o| {
+            let path = workdir.join(&o.file);
+            PluginRunOutput {
+                name: o.name.clone(),
+                exists: path.exists(),
+                path: path.to_string_lossy().to_string(),
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,191 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::paths;
+
+/// Services that listen on a port, with the port tried when none is preferred.
+pub const SERVICES: &[(&str, u16)] = &[("tensorboard", 6006), ("rest_server", 8765)];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PortSettings {
+    /// Port to try first per service; the default port otherwise.
+    pub preferred: BTreeMap<String, u16>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ServiceEndpoint {
+    pub service: String,
+    pub default_port: u16,
+    pub preferred_port: Option<u16>,
+    /// Where the service listens while running, e.g. `127.0.0.1:8765`.
+    pub bound: Option<String>,
+    pub url: Option<String>,
+    /// Why the preferred (or default) port cannot be used right now.
+    pub conflict: Option<String>,
+}
+
+struct BoundService {
+    address: SocketAddr,
+    scheme: &'static str,
+}
+
+fn default_port(service: &str) -> Result<u16, String> {
+    SERVICES
+        .iter()
+        .find(|(name, _)| *name == service)
+        .map(|(_, port)| *port)
+        .ok_or_else(|| format!("Unknown service '{}'.", service))
+}
+
+/// Managed state: preferred ports (`ports.json`) and the addresses services
+/// are bound to while they run.
+pub struct PortManager {
+    settings: Mutex<PortSettings>,
+    settings_path: PathBuf,
+    bound: Mutex<HashMap<String, BoundService>>,
+}
+
+impl PortManager {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let settings_path = paths::settings_file(app, "ports.json")?;
+        let settings = std::fs::read_to_string(&settings_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            settings: Mutex::new(settings),
+            settings_path,
+            bound: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn settings(&self) -> PortSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Sets or clears a service's preferred port. Ports below 1024 need
+    /// elevated rights on most systems and are refused.
+    pub fn set_preferred(&self, service: &str, port: Option<u16>) -> Result<(), String> {
+        default_port(service)?;
+        let mut settings = self.settings();
+        match port {
+            Some(port) if port < 1024 => return Err("Choose a port from 1024 upwards.".to_string()),
+            Some(port) => {
+                if let Some((other, _)) = settings.preferred.iter().find(|(s, p)| **p == port && *s != service) {
+                    return Err(format!("Port {} is already preferred by {}.", port, other));
+                }
+                settings.preferred.insert(service.to_string(), port);
+            }
+            None => {
+                settings.preferred.remove(service);
+            }
+        }
+        if let Some(dir) = self.settings_path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.settings_path, raw).map_err(|e| e.to_string())?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    fn wanted_port(&self, service: &str) -> Result<u16, String> {
+        let default = default_port(service)?;
+        Ok(self.settings().preferred.get(service).copied().unwrap_or(default))
+    }
+
+    /// Which other of our running services holds `port`, if any.
+    fn held_by_other(&self, service: &str, port: u16) -> Option<String> {
+        let bound = self.bound.lock().unwrap();
+        bound
+            .iter()
+            .find(|(s, b)| b.address.port() == port && *s != service)
+            .map(|(other, _)| format!("Port {} is used by {}.", port, other))
+    }
+
+    /// Why `port` cannot be bound on `host`, or `None` when it is free. Ports
+    /// held by one of our own running services are reported by name. Only
+    /// for display: the answer can be stale by the time anything binds.
+    fn conflict(&self, service: &str, host: IpAddr, port: u16) -> Option<String> {
+        if let Some(reason) = self.held_by_other(service, port) {
+            return Some(reason);
+        }
+        if self.bound.lock().unwrap().get(service).is_some_and(|b| b.address.port() == port) {
+            return None;
+        }
+        TcpListener::bind((host, port))
+            .err()
+            .map(|e| format!("Port {} is not available: {}", port, e))
+    }
+
+    /// Binds a listener for `service` on its preferred port, falling back to
+    /// a free port chosen by the OS, and records the address. The port is
+    /// bound directly rather than checked first, and the caller serves on the
+    /// returned listener, so no other process can take it in between.
+    pub fn allocate(&self, service: &str, host: IpAddr, scheme: &'static str) -> Result<TcpListener, String> {
+        let wanted = self.wanted_port(service)?;
+        let preferred = match self.held_by_other(service, wanted) {
+            Some(reason) => Err(reason),
+            None => TcpListener::bind((host, wanted)).map_err(|e| format!("Port {} is not available: {}", wanted, e)),
+        };
+        let listener = match preferred {
+            Ok(listener) => listener,
+            Err(reason) => {
+                tracing::warn!("{} Using a free port for {} instead.", reason, service);
+                TcpListener::bind((host, 0)).map_err(|e| format!("Cannot open a port for {}: {}", service, e))?
+            }
+        };
+        let address = listener.local_addr().map_err(|e| e.to_string())?;
+        self.bound
+            .lock()
+            .unwrap()
+            .insert(service.to_string(), BoundService { address, scheme });
+        Ok(listener)
+    }
+
+    /// Forgets a service's address once it stopped listening.
+    pub fn release(&self, service: &str) {
+        self.bound.lock().unwrap().remove(service);
+    }
+
+    /// Every known service with its ports, the address it is bound to, and
+    /// whether its port is currently taken by something else.
+    pub fn endpoints(&self) -> Vec<ServiceEndpoint> {
+        let preferred = self.settings().preferred;
+        SERVICES
+            .iter()
+            .map(|(service, default)| {
+                let bound = self
+                    .bound
+                    .lock()
+                    .unwrap()
+                    .get(*service)
+                    .map(|b| (b.address, b.scheme));
+                let wanted = preferred.get(*service).copied().unwrap_or(*default);
+                let conflict = match bound {
+                    Some(_) => None,
+                    None => self.conflict(service, IpAddr::V4(Ipv4Addr::LOCALHOST), wanted),
+                };
+                ServiceEndpoint {
+                    service: service.to_string(),
+                    default_port: *default,
+                    preferred_port: preferred.get(*service).copied(),
+                    bound: bound.map(|(address, _)| address.to_string()),
+                    url: bound.map(|(address, scheme)| {
+                        if address.ip().is_unspecified() {
+                            format!("{}://localhost:{}", scheme, address.port())
+                        } else {
+                            format!("{}://{}", scheme, address)
+                        }
+                    }),
+                    conflict,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,507 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tauri::Manager;
+
+use crate::audit::AuditLog;
+use crate::classes::{self, ClassSpec};
+use crate::dataset;
+use crate::drift;
+use crate::error::BackendError;
+use crate::events;
+use crate::gpu_monitor;
+use crate::jobs::{new_id, unix_now};
+use crate::paths;
+use crate::preprocessing;
+use crate::python::{backend_script, run_python_env, run_python_observed_env};
+
+/// One classified file. Prediction scripts print these as stdout lines with
+/// `"status": "prediction"`; `spawn_job` stores them per job so results outlive
+/// the Python process.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PredictionRecord {
+    pub file: String,
+    pub label: String,
+    pub confidence: f64,
+    /// Best classes with their probabilities, most likely first.
+    #[serde(default)]
+    pub top_k: Vec<ClassScore>,
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// Model (run directory) that produced the prediction.
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub model_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClassScore {
+    pub label: String,
+    pub probability: f64,
+}
+
+/// One image to classify with a trained model.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PredictionRequest {
+    /// Run directory of the model.
+    pub model_id: String,
+    pub image: String,
+    /// Relabels the model's outputs.
+    #[serde(default)]
+    pub classes: Option<ClassSpec>,
+    /// GPU(s) to predict on, as `list_gpu_devices` indexes them, or `cpu`.
+    #[serde(default)]
+    pub device: Option<String>,
+}
+
+impl PredictionRequest {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if !Path::new(&self.model_id).is_dir() {
+            return Err(BackendError::invalid_input(format!("Model {} does not exist.", self.model_id)));
+        }
+        if !Path::new(&self.image).is_file() {
+            return Err(BackendError::invalid_input(format!("{} does not exist or is not a file.", self.image)));
+        }
+        if let Some(device) = &self.device {
+            gpu_monitor::device_env(device)?;
+        }
+        Ok(())
+    }
+}
+
+/// Processing statistics of one prediction run, stored next to its records.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PredictionStats {
+    pub model_id: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+    /// Images submitted, including failed ones.
+    pub images: usize,
+    /// Images that could not be scored, as `path: reason`.
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Xlsx,
+}
+
+fn store_path(app: &tauri::AppHandle, job_id: &str) -> Result<PathBuf, String> {
+    if job_id.is_empty() || !job_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err("Invalid job id.".to_string());
+    }
+    Ok(paths::data_dir(app)?.join("predictions").join(format!("{}.jsonl", job_id)))
+}
+
+/// Stores `line` when it is a prediction record. Returns whether it was one;
+/// a record that cannot be stored is logged.
+pub fn record_line(app: &tauri::AppHandle, job_id: &str, line: &str) -> bool {
+    let Some(PredictLine::Record(record)) = parse_predict_line(line) else {
+        return false;
+    };
+    if let Err(e) = store(app, job_id, &record) {
+        tracing::error!("Failed to store the prediction for {}: {}", record.file, e);
+    }
+    true
+}
+
+/// Appends `record` to the job's prediction store and the inference audit log.
+fn store(app: &tauri::AppHandle, job_id: &str, record: &PredictionRecord) -> Result<(), String> {
+    let path = store_path(app, job_id)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    let raw = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", raw).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    if let Some(audit) = app.try_state::<Arc<AuditLog>>() {
+        match audit.record(job_id, record) {
+            Ok(true) => {
+                if let Some(model_id) = &record.model_id {
+                    drift::note_prediction(app, &audit, model_id);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => tracing::error!("Failed to write inference audit entry: {}", e),
+        }
+    }
+    Ok(())
+}
+
+pub fn save_stats(app: &tauri::AppHandle, job_id: &str, stats: &PredictionStats) -> Result<(), String> {
+    let path = store_path(app, job_id)?.with_extension("stats.json");
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(stats).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| e.to_string())
+}
+
+pub fn load_stats(app: &tauri::AppHandle, job_id: &str) -> Option<PredictionStats> {
+    let raw = std::fs::read_to_string(store_path(app, job_id).ok()?.with_extension("stats.json")).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+pub fn load(app: &tauri::AppHandle, job_id: &str) -> Result<Vec<PredictionRecord>, String> {
+    let path = store_path(app, job_id)?;
+    let file = std::fs::File::open(&path).map_err(|_| format!("No stored predictions for job {}.", job_id))?;
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(&l).map_err(|e| format!("Corrupt prediction store: {}", e)))
+        .collect()
+}
+
+/// Classifies `images` with the model in `model_id` (a run directory) through
+/// predict.py, labelling outputs with `classes` when given (checked against the
+/// model's stored class map). Results are stored and audited under a fresh prediction id like
+/// job output. Returns that id, the records, and the per-image failures.
+pub async fn predict(
+    app: &tauri::AppHandle,
+    model_id: &str,
+    images: &[String],
+    classes: Option<&ClassSpec>,
+    device: Option<&str>,
+) -> Result<(String, Vec<PredictionRecord>, Vec<String>), String> {
+    let env = device.map(gpu_monitor::device_env).transpose()?.unwrap_or_default();
+    // Fail before starting Python when the bundle cannot be predicted with faithfully.
+    let model_dir = Path::new(model_id);
+    preprocessing::load(model_dir)?;
+    let labels = match classes {
+        Some(spec) => classes::resolve(model_dir, spec)?,
+        None => classes::model_classes(model_dir)?.into_iter().map(|e| e.name).collect(),
+    };
+    let script = backend_script(app, "predict.py")?;
+    let labels_json = serde_json::to_string(&labels).map_err(|e| e.to_string())?;
+    let prediction_id = new_id("predict");
+    let started_at = unix_now();
+    // Folders can hold more paths than fit on a command line.
+    let images_file = paths::cache_file(app, &format!("{}_images.json", prediction_id))?;
+    let images_json = serde_json::to_string(images).map_err(|e| e.to_string())?;
+    std::fs::write(&images_file, images_json).map_err(|e| format!("Cannot write image list: {}", e))?;
+    let images_arg = images_file.to_string_lossy().to_string();
+    let output = run_python_env(
+        app,
+        &[
+            script.as_str(),
+            "--model_dir",
+            model_id,
+            "--images_file",
+            images_arg.as_str(),
+            "--classes",
+            labels_json.as_str(),
+        ],
+        &env,
+    )
+    .await;
+    let _ = std::fs::remove_file(&images_file);
+    let stdout = output?;
+
+    let mut records = Vec::new();
+    let mut failures = Vec::new();
+    for line in stdout.lines() {
+        match parse_predict_line(line) {
+            Some(PredictLine::Error(message)) => return Err(message),
+            Some(PredictLine::Failure { file, message }) => failures.push(format!("{}: {}", file, message)),
+            Some(PredictLine::Record(record)) => match store(app, &prediction_id, &record) {
+                Ok(()) => records.push(record),
+                Err(e) => failures.push(format!("{}: the prediction could not be stored: {}", record.file, e)),
+            },
+            None => {}
+        }
+    }
+    let stats = PredictionStats {
+        model_id: model_id.to_string(),
+        started_at,
+        finished_at: unix_now(),
+        images: images.len(),
+        failures: failures.clone(),
+    };
+    if let Err(e) = save_stats(app, &prediction_id, &stats) {
+        tracing::error!("Failed to store prediction stats: {}", e);
+    }
+    Ok((prediction_id, records, failures))
+}
+
+/// Images per predict.py run in a batch prediction; each run loads the model
+/// once.
+const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// Every image in a folder, classified with one model.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchPredictionRequest {
+    pub folder: String,
+    /// Run directory of the model.
+    pub model_id: String,
+    #[serde(default)]
+    pub classes: Option<ClassSpec>,
+    /// Also classify images in subfolders.
+    #[serde(default)]
+    pub recursive: bool,
+    /// GPU(s) to predict on, as `list_gpu_devices` indexes them, or `cpu`.
+    #[serde(default)]
+    pub device: Option<String>,
+    pub batch_size: Option<usize>,
+    /// Where to write all predictions once done, in `format`.
+    pub dest: Option<String>,
+    #[serde(default = "default_format")]
+    pub format: ExportFormat,
+}
+
+fn default_format() -> ExportFormat {
+    ExportFormat::Csv
+}
+
+/// Published as `predict://result` for every image of a batch prediction.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchPredictionEvent {
+    pub file: String,
+    pub record: Option<PredictionRecord>,
+    pub error: Option<String>,
+    /// Images handled so far, including this one.
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BatchPredictionResult {
+    /// Id the predictions are stored under, for `export_predictions` and
+    /// `export_batch_summary`.
+    pub job_id: String,
+    pub predicted: usize,
+    pub stats: PredictionStats,
+    /// The consolidated file, when `dest` was given.
+    pub output: Option<String>,
+}
+
+/// One line of predict.py output.
+pub enum PredictLine {
+    Record(PredictionRecord),
+    Failure { file: String, message: String },
+    Error(String),
+}
+
+pub fn parse_predict_line(line: &str) -> Option<PredictLine> {
+    let value = serde_json::from_str::<serde_json::Value>(line.trim()).ok()?;
+    match value["status"].as_str()? {
+        "error" => Some(PredictLine::Error(
+            value["message"].as_str().unwrap_or("Prediction failed.").to_string(),
+        )),
+        "prediction_error" => Some(PredictLine::Failure {
+            file: value["file"].as_str().unwrap_or_default().to_string(),
+            message: value["message"].as_str().unwrap_or_default().to_string(),
+        }),
+        "prediction" => serde_json::from_value(value).ok().map(PredictLine::Record),
+        _ => None,
+    }
+}
+
+/// Classifies every image in a folder in batches of predict.py runs, as job
+/// `job_id`. Each result is stored like job output and published as
+/// `predict://result`; with a `dest`, all of them are written there at the end.
+pub async fn predict_folder(
+    app: &tauri::AppHandle,
+    request: &BatchPredictionRequest,
+    job_id: &str,
+) -> Result<BatchPredictionResult, String> {
+    let model_dir = Path::new(&request.model_id);
+    preprocessing::load(model_dir)?;
+    let labels = match &request.classes {
+        Some(spec) => classes::resolve(model_dir, spec)?,
+        None => classes::model_classes(model_dir)?.into_iter().map(|e| e.name).collect(),
+    };
+    let labels_json = serde_json::to_string(&labels).map_err(|e| e.to_string())?;
+    // Fails early on ids the store cannot hold.
+    store_path(app, job_id)?;
+    let images: Vec<String> = dataset::list_images(Path::new(&request.folder), request.recursive)?
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    if images.is_empty() {
+        return Err(format!("{} contains no images.", request.folder));
+    }
+    let script = backend_script(app, "predict.py")?;
+    let env = request.device.as_deref().map(gpu_monitor::device_env).transpose()?.unwrap_or_default();
+    let batch_size = request.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let started_at = unix_now();
+    let total = images.len();
+    let mut done = 0;
+    let mut predicted = 0;
+    let mut failures = Vec::new();
+
+    for (index, batch) in images.chunks(batch_size).enumerate() {
+        let batch_id = format!("{}_batch{}", job_id, index);
+        let images_file = paths::cache_file(app, &format!("{}_images.json", batch_id))?;
+        let images_json = serde_json::to_string(batch).map_err(|e| e.to_string())?;
+        std::fs::write(&images_file, images_json).map_err(|e| format!("Cannot write image list: {}", e))?;
+        let images_arg = images_file.to_string_lossy().to_string();
+        let mut error = None;
+        let output = run_python_observed_env(
+            app,
+            &batch_id,
+            &[
+                script.as_str(),
+                "--model_dir",
+                request.model_id.as_str(),
+                "--images_file",
+                images_arg.as_str(),
+                "--classes",
+                labels_json.as_str(),
+            ],
+            &env,
+            |line| {
+                let (file, record, failure) = match parse_predict_line(line) {
+                    Some(PredictLine::Record(record)) => match store(app, job_id, &record) {
+                        Ok(()) => {
+                            predicted += 1;
+                            (record.file.clone(), Some(record), None)
+                        }
+                        Err(e) => {
+                            let message = format!("The prediction could not be stored: {}", e);
+                            failures.push(format!("{}: {}", record.file, message));
+                            (record.file, None, Some(message))
+                        }
+                    },
+                    Some(PredictLine::Failure { file, message }) => {
+                        failures.push(format!("{}: {}", file, message));
+                        (file, None, Some(message))
+                    }
+                    Some(PredictLine::Error(message)) => {
+                        error = Some(message);
+                        return;
+                    }
+                    None => return,
+                };
+                done += 1;
+                let event = BatchPredictionEvent { file, record, error: failure, done, total };
+                events::publish(app, "predict://result", Some(job_id), event);
+            },
+        )
+        .await;
+        let _ = std::fs::remove_file(&images_file);
+        output?;
+        // The model could not be loaded; later batches would fail the same way.
+        if let Some(message) = error {
+            return Err(message);
+        }
+    }
+
+    let stats = PredictionStats {
+        model_id: request.model_id.clone(),
+        started_at,
+        finished_at: unix_now(),
+        images: total,
+        failures,
+    };
+    save_stats(app, job_id, &stats)?;
+    let output = match &request.dest {
+        Some(dest) if predicted > 0 => {
+            export(app, job_id, request.format, Path::new(dest))?;
+            Some(dest.clone())
+        }
+        _ => None,
+    };
+    Ok(BatchPredictionResult {
+        job_id: job_id.to_string(),
+        predicted,
+        stats,
+        output,
+    })
+}
+
+/// `path` relative to `base` when it lies inside it, so an export folder can be
+/// moved together with its thumbnails. Otherwise the path is kept as is.
+fn relative_to(path: &str, base: &Path) -> String {
+    let path = Path::new(path);
+    match path.strip_prefix(base) {
+        Ok(rel) if rel.components().all(|c| matches!(c, Component::Normal(_))) => {
+            rel.to_string_lossy().replace('\\', "/")
+        }
+        _ => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Quotes a CSV field when it holds a separator, quote or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn top_k_text(record: &PredictionRecord) -> String {
+    record
+        .top_k
+        .iter()
+        .map(|s| format!("{}:{:.4}", s.label, s.probability))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Writes a job's stored predictions to `dest`. Returns the number of rows.
+pub fn export(app: &tauri::AppHandle, job_id: &str, format: ExportFormat, dest: &Path) -> Result<usize, String> {
+    let mut records = load(app, job_id)?;
+    let base = dest.parent().unwrap_or(Path::new(""));
+    for record in &mut records {
+        record.thumbnail = record.thumbnail.as_deref().map(|t| relative_to(t, base));
+    }
+
+    match format {
+        ExportFormat::Json => {
+            let raw = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+            std::fs::write(dest, raw).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+        }
+        ExportFormat::Csv => {
+            let mut out = String::from("file,label,confidence,top_k,thumbnail\n");
+            for r in &records {
+                out.push_str(&format!(
+                    "{},{},{:.6},{},{}\n",
+                    csv_field(&r.file),
+                    csv_field(&r.label),
+                    r.confidence,
+                    csv_field(&top_k_text(r)),
+                    csv_field(r.thumbnail.as_deref().unwrap_or(""))
+                ));
+            }
+            std::fs::write(dest, out).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+        }
+        ExportFormat::Xlsx => {
+            let mut workbook = rust_xlsxwriter::Workbook::new();
+            let sheet = workbook.add_worksheet();
+            sheet.set_name("Predictions").map_err(|e| e.to_string())?;
+            let bold = rust_xlsxwriter::Format::new().set_bold();
+            for (col, header) in ["File", "Label", "Confidence", "Top-k", "Thumbnail"].iter().enumerate() {
+                sheet
+                    .write_string_with_format(0, col as u16, *header, &bold)
+                    .map_err(|e| e.to_string())?;
+            }
+            for (i, r) in records.iter().enumerate() {
+                let row = i as u32 + 1;
+                sheet.write_string(row, 0, &r.file).map_err(|e| e.to_string())?;
+                sheet.write_string(row, 1, &r.label).map_err(|e| e.to_string())?;
+                sheet.write_number(row, 2, r.confidence).map_err(|e| e.to_string())?;
+                sheet.write_string(row, 3, top_k_text(r)).map_err(|e| e.to_string())?;
+                sheet
+                    .write_string(row, 4, r.thumbnail.as_deref().unwrap_or(""))
+                    .map_err(|e| e.to_string())?;
+            }
+            sheet.autofit();
+            workbook
+                .save(dest)
+                .map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+        }
+    }
+    Ok(records.len())
+}
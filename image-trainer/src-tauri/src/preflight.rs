@@ -0,0 +1,133 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+use crate::dashboard::dir_size;
+use crate::error::BackendError;
+use crate::training::{estimate_weights_mb, TrainingConfig};
+
+const MB: u64 = 1024 * 1024;
+/// Left free on top of the estimate, for logs, the OS and other programs.
+const HEADROOM: u64 = 512 * MB;
+/// Metrics, manifests and reports written next to the weights.
+const RUN_FILES: u64 = 20 * MB;
+
+/// A job to check before it starts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PreflightJob {
+    Training { config: TrainingConfig },
+    OnnxExport { model_id: String, out_path: String },
+}
+
+/// Something the job writes, with its estimated size.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SpaceItem {
+    pub name: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PreflightReport {
+    /// Folder the job writes to.
+    pub target: String,
+    pub items: Vec<SpaceItem>,
+    /// Sum of `items`, less the files the job overwrites.
+    pub required_bytes: u64,
+    /// Free space on the drive holding `target`; null when it cannot be read.
+    pub free_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    /// Enough space with headroom to spare, or the free space is unknown.
+    pub ok: bool,
+    pub message: Option<String>,
+}
+
+impl PreflightReport {
+    /// The report as an error when the job would run out of space.
+    pub fn into_result(self) -> Result<Self, BackendError> {
+        match (&self.message, self.ok) {
+            (Some(message), false) => Err(BackendError::invalid_input(message.clone())),
+            _ => Ok(self),
+        }
+    }
+}
+
+/// Free and total bytes of the drive holding `path`, which need not exist yet.
+pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let path = existing.canonicalize().unwrap_or_else(|_| existing.to_path_buf());
+    let disks = Disks::new_with_refreshed_list();
+    // The disk with the longest mount point containing the folder holds it.
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())?;
+    Some((disk.available_space(), disk.total_space()))
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn report(target: PathBuf, items: Vec<SpaceItem>, overwritten: u64) -> PreflightReport {
+    let required_bytes = items.iter().map(|i| i.bytes).sum::<u64>().saturating_sub(overwritten);
+    let space = disk_space(&target);
+    let ok = space.is_none_or(|(free, _)| free >= required_bytes + HEADROOM);
+    let message = space.filter(|_| !ok).map(|(free, _)| {
+        format!(
+            "Not enough disk space on the drive holding {}: the job needs about {} MB and {} MB are free.",
+            target.display(),
+            (required_bytes + HEADROOM) / MB,
+            free / MB
+        )
+    });
+    PreflightReport {
+        target: target.to_string_lossy().to_string(),
+        items,
+        required_bytes,
+        free_bytes: space.map(|(free, _)| free),
+        total_bytes: space.map(|(_, total)| total),
+        ok,
+        message,
+    }
+}
+
+/// Space a training run needs: the best weights, the resumable checkpoint
+/// (weights plus optimizer state) and, when asked for, the dataset zip.
+pub fn check_training(config: &TrainingConfig) -> PreflightReport {
+    let dir = config.output_dir();
+    let weights = estimate_weights_mb(&config.model) * MB;
+    let mut items = vec![
+        SpaceItem { name: "best_model.pth".to_string(), bytes: weights },
+        // Adam keeps two moments per weight.
+        SpaceItem { name: "checkpoint.pth".to_string(), bytes: weights * 3 },
+        SpaceItem { name: "metrics and reports".to_string(), bytes: RUN_FILES },
+    ];
+    if config.zip_dataset || config.only_zip {
+        // Images are already compressed; the archive is about as large as the dataset.
+        items.push(SpaceItem { name: "dataset.zip".to_string(), bytes: dir_size(Path::new(&config.path)) });
+    }
+    if config.only_zip {
+        items.retain(|item| item.name == "dataset.zip");
+    }
+    // Files from an earlier run in the same folder are replaced, not added to.
+    let overwritten = ["best_model.pth", "checkpoint.pth", "dataset.zip"].iter().map(|f| file_size(&dir.join(f))).sum();
+    report(dir, items, overwritten)
+}
+
+/// Space an ONNX export needs, about the size of the trained weights.
+pub fn check_onnx_export(model_id: &str, out_path: &str) -> PreflightReport {
+    let out = Path::new(out_path);
+    let weights = file_size(&Path::new(model_id).join("best_model.pth"));
+    let items = vec![SpaceItem { name: "ONNX model".to_string(), bytes: weights }];
+    report(out.parent().unwrap_or(out).to_path_buf(), items, file_size(out))
+}
+
+pub fn check(job: &PreflightJob) -> PreflightReport {
+    match job {
+        PreflightJob::Training { config } => check_training(config),
+        PreflightJob::OnnxExport { model_id, out_path } => check_onnx_export(model_id, out_path),
+    }
+}
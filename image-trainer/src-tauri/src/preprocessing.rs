@@ -0,0 +1,81 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::BackendError;
+use crate::run_manifest;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub enum ChannelOrder {
+    #[serde(rename = "RGB")]
+    Rgb,
+    #[serde(rename = "BGR")]
+    Bgr,
+}
+
+/// Deterministic preprocessing a model was trained with, read from the
+/// `preprocessing.json` script.py writes next to the weights. Every inference
+/// path must apply exactly this; there are no defaults to fall back on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct Preprocessing {
+    /// Shorter image side after resizing, before the center crop.
+    pub resize: u32,
+    /// Side of the square center crop fed to the model.
+    pub input_size: u32,
+    pub mean: [f64; 3],
+    pub std: [f64; 3],
+    pub channel_order: ChannelOrder,
+    /// Train-time augmentation, for reference only; inference never applies it.
+    #[serde(default)]
+    pub augmentation: Option<serde_json::Value>,
+}
+
+impl Preprocessing {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if self.input_size == 0 || self.resize < self.input_size {
+            return Err(BackendError::invalid_input(format!(
+                "Invalid preprocessing: resize {} must be at least the crop size {}.",
+                self.resize, self.input_size
+            )));
+        }
+        if self.std.iter().any(|s| *s <= 0.0) {
+            return Err(BackendError::invalid_input(
+                "Invalid preprocessing: normalization std values must be positive.",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Errors when an input of `width` x `height` pixels (e.g. the fixed input
+    /// of an exported graph) does not match the crop size.
+    pub fn ensure_input_shape(&self, width: u32, height: u32) -> Result<(), String> {
+        if width != self.input_size || height != self.input_size {
+            return Err(format!(
+                "Preprocessing mismatch: the model expects {}x{} inputs but was trained on {}x{} crops.",
+                width, height, self.input_size, self.input_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Reads and checks the preprocessing stored in a model directory, including
+/// that it agrees with the input size of a custom architecture.
+pub fn load(model_dir: &Path) -> Result<Preprocessing, String> {
+    let path = model_dir.join("preprocessing.json");
+    let raw = std::fs::read_to_string(&path).map_err(|_| {
+        format!(
+            "{} is missing; the model cannot be used without the preprocessing it was trained with.",
+            path.display()
+        )
+    })?;
+    let preprocessing: Preprocessing =
+        serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    preprocessing.validate()?;
+
+    let manifest = run_manifest::load(model_dir).unwrap_or_default();
+    if let Some(size) = manifest["architecture"]["input_size"].as_u64() {
+        preprocessing.ensure_input_shape(size as u32, size as u32)?;
+    }
+    Ok(preprocessing)
+}
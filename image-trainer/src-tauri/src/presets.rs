@@ -0,0 +1,471 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::augmentation::AugmentationPolicy;
+use crate::jobs::unix_now;
+use crate::paths;
+use crate::plugins;
+use crate::training::{TrainingConfig, SUPPORTED_MODELS};
+
+/// Version of the pack layout written by `export_pack`.
+const PACK_FORMAT_VERSION: u32 = 1;
+const PACK_MANIFEST: &str = "pack.json";
+const PACK_SIGNATURE: &str = "pack.sig";
+/// Packs are small JSON documents; anything bigger is not a preset pack.
+const MAX_PACK_ENTRY_BYTES: u64 = 8 * 1024 * 1024;
+
+/// A named training configuration without machine-specific paths.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TrainingPreset {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub config: TrainingConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AugmentationPreset {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub policy: AugmentationPolicy,
+}
+
+/// One plugin invocation in a pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PipelineStep {
+    pub plugin: String,
+    #[serde(default)]
+    pub inputs: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An ordered sequence of plugin runs, e.g. clean dataset -> export -> report.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Pipeline {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<PipelineStep>,
+}
+
+/// Everything the user saved as reusable configuration, stored in `presets.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+pub struct PresetLibrary {
+    pub training: Vec<TrainingPreset>,
+    pub augmentation: Vec<AugmentationPreset>,
+    pub pipelines: Vec<Pipeline>,
+}
+
+/// `pack.json` inside a pack archive.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PackManifest {
+    pub format_version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub created_at: u64,
+    pub library: PresetLibrary,
+}
+
+/// `pack.sig`: an Ed25519 signature over the exact bytes of `pack.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PackSignature {
+    public_key: String,
+    signature: String,
+}
+
+/// What `inspect_pack` found, shown to the user before importing.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PackInfo {
+    pub manifest: Option<PackManifest>,
+    /// Hex public key of the signer.
+    pub signer: Option<String>,
+    pub signature_valid: bool,
+    /// True when the signer is this installation or in the trusted key list.
+    pub trusted: bool,
+    /// Names that already exist locally and would be replaced.
+    pub conflicts: Vec<String>,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::data_dir(app)?)
+}
+
+fn read_json<T: for<'de> Deserialize<'de> + Default>(path: &Path) -> Result<T, String> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    std::fs::write(path, raw).map_err(|e| e.to_string())
+}
+
+pub fn load_library(app: &tauri::AppHandle) -> Result<PresetLibrary, String> {
+    read_json(&data_dir(app)?.join("presets.json"))
+}
+
+pub fn save_library(app: &tauri::AppHandle, mut library: PresetLibrary) -> Result<PresetLibrary, String> {
+    library.validate()?;
+    for preset in &mut library.training {
+        preset.config = portable_config(&preset.config);
+    }
+    write_json(&data_dir(app)?.join("presets.json"), &library)?;
+    Ok(library)
+}
+
+/// Strips fields that only make sense on the machine that created a config.
+fn portable_config(config: &TrainingConfig) -> TrainingConfig {
+    TrainingConfig {
+        path: String::new(),
+        save_path: None,
+        resume: None,
+        experiment_id: None,
+        architecture: None,
+        metric_plugins: Vec::new(),
+        // Paths and proxy credentials are local to the machine.
+        env: BTreeMap::new(),
+        seed: if config.reproducible { config.seed } else { None },
+        ..config.clone()
+    }
+}
+
+fn check_names<'a>(kind: &str, names: impl Iterator<Item = &'a str>) -> Result<(), String> {
+    let mut seen = BTreeSet::new();
+    for name in names {
+        if name.trim().is_empty() {
+            return Err(format!("Every {} preset needs a name.", kind));
+        }
+        if !seen.insert(name) {
+            return Err(format!("{} preset '{}' is defined twice.", kind, name));
+        }
+    }
+    Ok(())
+}
+
+impl PresetLibrary {
+    /// Hard errors only; see `warnings` for things that depend on this machine.
+    pub fn validate(&self) -> Result<(), String> {
+        check_names("Training", self.training.iter().map(|p| p.name.as_str()))?;
+        check_names("Augmentation", self.augmentation.iter().map(|p| p.name.as_str()))?;
+        check_names("Pipeline", self.pipelines.iter().map(|p| p.name.as_str()))?;
+
+        for preset in &self.training {
+            // Validate the hyperparameters alone; dataset, custom model and
+            // metrics are resolved when the preset is used.
+            let mut config = portable_config(&preset.config);
+            config.path = "preset".to_string();
+            config.custom_metrics.clear();
+            if !SUPPORTED_MODELS.contains(&config.model.as_str()) {
+                config.model = SUPPORTED_MODELS[0].to_string();
+            }
+            config
+                .validate(None)
+                .map_err(|e| format!("Training preset '{}': {}", preset.name, e))?;
+        }
+        for preset in &self.augmentation {
+            preset
+                .policy
+                .validate()
+                .map_err(|e| format!("Augmentation preset '{}': {}", preset.name, e))?;
+        }
+        for pipeline in &self.pipelines {
+            if pipeline.steps.is_empty() {
+                return Err(format!("Pipeline '{}' has no steps.", pipeline.name));
+            }
+            if pipeline.steps.iter().any(|s| s.plugin.trim().is_empty()) {
+                return Err(format!("Pipeline '{}' has a step without a plugin.", pipeline.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Things that will not work on this installation as-is.
+    fn warnings(&self, app: &tauri::AppHandle) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let custom_models: BTreeSet<String> = crate::architectures::load(app)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        for preset in &self.training {
+            let model = &preset.config.model;
+            if !SUPPORTED_MODELS.contains(&model.as_str()) && !custom_models.contains(model) {
+                warnings.push(format!(
+                    "Training preset '{}' uses custom architecture '{}', which is not registered here.",
+                    preset.name, model
+                ));
+            }
+        }
+
+        let installed: BTreeSet<String> = plugins::discover(app)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|p| p.manifest.map(|m| m.name))
+            .collect();
+        for pipeline in &self.pipelines {
+            for step in pipeline.steps.iter().filter(|s| !installed.contains(&s.plugin)) {
+                warnings.push(format!(
+                    "Pipeline '{}' needs plugin '{}', which is not installed.",
+                    pipeline.name, step.plugin
+                ));
+            }
+        }
+        warnings
+    }
+
+    fn conflicts(&self, existing: &PresetLibrary) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for p in self.training.iter().filter(|p| existing.training.iter().any(|e| e.name == p.name)) {
+            conflicts.push(format!("training/{}", p.name));
+        }
+        for p in self.augmentation.iter().filter(|p| existing.augmentation.iter().any(|e| e.name == p.name)) {
+            conflicts.push(format!("augmentation/{}", p.name));
+        }
+        for p in self.pipelines.iter().filter(|p| existing.pipelines.iter().any(|e| e.name == p.name)) {
+            conflicts.push(format!("pipeline/{}", p.name));
+        }
+        conflicts
+    }
+}
+
+fn signing_key_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir(app)?.join("pack_signing.key"))
+}
+
+/// This installation's signing key, if one has been created.
+fn existing_signing_key(app: &tauri::AppHandle) -> Result<Option<SigningKey>, String> {
+    let Ok(raw) = std::fs::read_to_string(signing_key_path(app)?) else {
+        return Ok(None);
+    };
+    let bytes: [u8; 32] = hex::decode(raw.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Pack signing key is corrupt.")?;
+    Ok(Some(SigningKey::from_bytes(&bytes)))
+}
+
+/// This installation's signing key, created on first use.
+fn signing_key(app: &tauri::AppHandle) -> Result<SigningKey, String> {
+    if let Some(key) = existing_signing_key(app)? {
+        return Ok(key);
+    }
+    let path = signing_key_path(app)?;
+    let key = SigningKey::generate(&mut rand::rngs::OsRng);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, hex::encode(key.to_bytes())).map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// Hex public key others add to their trusted list to accept this user's packs.
+pub fn public_key(app: &tauri::AppHandle) -> Result<String, String> {
+    Ok(hex::encode(signing_key(app)?.verifying_key().to_bytes()))
+}
+
+fn trusted_keys_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir(app)?.join("trusted_pack_keys.json"))
+}
+
+pub fn trusted_keys(app: &tauri::AppHandle) -> Result<Vec<String>, String> {
+    read_json(&trusted_keys_path(app)?)
+}
+
+pub fn trust_key(app: &tauri::AppHandle, key: &str) -> Result<(), String> {
+    let key = key.trim().to_ascii_lowercase();
+    parse_public_key(&key)?;
+    let mut keys = trusted_keys(app)?;
+    if !keys.contains(&key) {
+        keys.push(key);
+    }
+    write_json(&trusted_keys_path(app)?, &keys)
+}
+
+fn parse_public_key(hex_key: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = hex::decode(hex_key)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Public keys are 64 hex characters.")?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| e.to_string())
+}
+
+/// Writes the named presets into a signed `.epoqpack` (zip) archive at `dest`.
+pub fn export_pack(
+    app: &tauri::AppHandle,
+    dest: &Path,
+    name: &str,
+    author: Option<String>,
+    training: &[String],
+    augmentation: &[String],
+    pipelines: &[String],
+) -> Result<(), String> {
+    let all = load_library(app)?;
+    let pick = |wanted: &[String], have: Vec<String>| -> Result<(), String> {
+        match wanted.iter().find(|w| !have.contains(w)) {
+            Some(missing) => Err(format!("No preset named '{}'.", missing)),
+            None => Ok(()),
+        }
+    };
+    pick(training, all.training.iter().map(|p| p.name.clone()).collect())?;
+    pick(augmentation, all.augmentation.iter().map(|p| p.name.clone()).collect())?;
+    pick(pipelines, all.pipelines.iter().map(|p| p.name.clone()).collect())?;
+
+    let library = PresetLibrary {
+        training: all.training.into_iter().filter(|p| training.contains(&p.name)).collect(),
+        augmentation: all.augmentation.into_iter().filter(|p| augmentation.contains(&p.name)).collect(),
+        pipelines: all.pipelines.into_iter().filter(|p| pipelines.contains(&p.name)).collect(),
+    };
+    if library.training.is_empty() && library.augmentation.is_empty() && library.pipelines.is_empty() {
+        return Err("A pack needs at least one preset.".to_string());
+    }
+    library.validate()?;
+
+    let manifest = PackManifest {
+        format_version: PACK_FORMAT_VERSION,
+        name: name.to_string(),
+        author,
+        created_at: unix_now(),
+        library,
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    let key = signing_key(app)?;
+    let signature = PackSignature {
+        public_key: hex::encode(key.verifying_key().to_bytes()),
+        signature: hex::encode(key.sign(&manifest_bytes).to_bytes()),
+    };
+    let signature_bytes = serde_json::to_vec_pretty(&signature).map_err(|e| e.to_string())?;
+
+    let file = std::fs::File::create(dest).map_err(|e| format!("Cannot create {}: {}", dest.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+    for (entry, bytes) in [(PACK_MANIFEST, &manifest_bytes), (PACK_SIGNATURE, &signature_bytes)] {
+        zip.start_file(entry, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<Vec<u8>, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| format!("Pack is missing {}.", name))?;
+    if entry.size() > MAX_PACK_ENTRY_BYTES {
+        return Err(format!("{} is too large for a preset pack.", name));
+    }
+    let mut bytes = Vec::new();
+    entry
+        .take(MAX_PACK_ENTRY_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Opens and checks a pack without changing anything.
+pub fn inspect_pack(app: &tauri::AppHandle, path: &Path) -> Result<PackInfo, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Not a preset pack: {}", e))?;
+    let manifest_bytes = read_entry(&mut archive, PACK_MANIFEST)?;
+    let signature_bytes = read_entry(&mut archive, PACK_SIGNATURE)?;
+
+    let mut info = PackInfo {
+        manifest: None,
+        signer: None,
+        signature_valid: false,
+        trusted: false,
+        conflicts: Vec::new(),
+        warnings: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    match serde_json::from_slice::<PackSignature>(&signature_bytes) {
+        Ok(sig) => {
+            // Keys are compared in the lowercase hex `trust_key` stores.
+            let signer = sig.public_key.trim().to_ascii_lowercase();
+            let verified = parse_public_key(&signer).and_then(|key| {
+                let bytes: [u8; 64] = hex::decode(&sig.signature)
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or("Malformed signature.")?;
+                key.verify(&manifest_bytes, &Signature::from_bytes(&bytes))
+                    .map_err(|_| "Signature does not match the pack contents.".to_string())
+            });
+            match verified {
+                Ok(()) => info.signature_valid = true,
+                Err(e) => info.errors.push(e),
+            }
+            // Reading the own key must not create one: inspecting has no side effects.
+            let own = existing_signing_key(app)?.map(|key| hex::encode(key.verifying_key().to_bytes()));
+            info.trusted = info.signature_valid
+                && (own.as_ref() == Some(&signer)
+                    || trusted_keys(app)?.iter().any(|key| key.eq_ignore_ascii_case(&signer)));
+            info.signer = Some(signer);
+        }
+        Err(e) => info.errors.push(format!("Invalid {}: {}", PACK_SIGNATURE, e)),
+    }
+
+    match serde_json::from_slice::<PackManifest>(&manifest_bytes) {
+        Ok(manifest) => {
+            if manifest.format_version > PACK_FORMAT_VERSION {
+                info.errors.push(format!(
+                    "Pack format {} is newer than this version of EPOQ supports ({}).",
+                    manifest.format_version, PACK_FORMAT_VERSION
+                ));
+            }
+            if let Err(e) = manifest.library.validate() {
+                info.errors.push(e);
+            }
+            info.warnings = manifest.library.warnings(app);
+            info.conflicts = manifest.library.conflicts(&load_library(app)?);
+            info.manifest = Some(manifest);
+        }
+        Err(e) => info.errors.push(format!("Invalid {}: {}", PACK_MANIFEST, e)),
+    }
+    Ok(info)
+}
+
+/// Imports a pack after checking it. Packs from untrusted signers are only
+/// accepted with `trust_signer`, which also adds the signer to the trusted list;
+/// existing presets with the same name are only replaced with `overwrite`.
+pub fn import_pack(app: &tauri::AppHandle, path: &Path, trust_signer: bool, overwrite: bool) -> Result<PackInfo, String> {
+    let info = inspect_pack(app, path)?;
+    if !info.errors.is_empty() {
+        return Err(format!("Pack rejected: {}", info.errors.join(" ")));
+    }
+    if !info.trusted {
+        if !trust_signer {
+            return Err("Pack is signed by an untrusted key. Confirm the signer to import it.".to_string());
+        }
+        if let Some(signer) = &info.signer {
+            trust_key(app, signer)?;
+        }
+    }
+    if !info.conflicts.is_empty() && !overwrite {
+        return Err(format!("Presets already exist: {}", info.conflicts.join(", ")));
+    }
+
+    let incoming = info.manifest.clone().ok_or("Pack has no manifest.")?.library;
+    let mut library = load_library(app)?;
+    library.training.retain(|p| !incoming.training.iter().any(|i| i.name == p.name));
+    library.augmentation.retain(|p| !incoming.augmentation.iter().any(|i| i.name == p.name));
+    library.pipelines.retain(|p| !incoming.pipelines.iter().any(|i| i.name == p.name));
+    library.training.extend(incoming.training);
+    library.augmentation.extend(incoming.augmentation);
+    library.pipelines.extend(incoming.pipelines);
+    save_library(app, library)?;
+    Ok(info)
+}
@@ -0,0 +1,221 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::jobs::{new_id, unix_now};
+
+/// The profile every install starts with. It uses the top-level directories,
+/// so data from before profiles existed stays where it is.
+pub const DEFAULT_PROFILE: &str = "default";
+/// Environment variable naming the profile to open; also set when the app
+/// restarts itself after a profile switch.
+const PROFILE_ENV: &str = "EPOQ_PROFILE";
+
+/// A person's settings, history and credentials on a shared OS account.
+/// Everything under the data and settings directories is per profile; plugins
+/// and caches are shared.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    /// Keep trained models in the shared models folder rather than a private one.
+    #[serde(default = "default_true")]
+    pub share_models: bool,
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ProfileRegistry {
+    /// Profiles besides the default one.
+    pub profiles: Vec<Profile>,
+    pub last_used: Option<String>,
+    /// Ask which profile to use when the app opens.
+    pub prompt_at_startup: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProfilesStatus {
+    pub active: Profile,
+    /// Including the default profile.
+    pub profiles: Vec<Profile>,
+    pub prompt_at_startup: bool,
+    /// The frontend should show the profile picker before anything else.
+    pub show_picker: bool,
+}
+
+fn default_profile() -> Profile {
+    Profile {
+        id: DEFAULT_PROFILE.to_string(),
+        name: "Default".to_string(),
+        share_models: true,
+        created_at: 0,
+    }
+}
+
+/// Managed state: the profile registry (`profiles.json` in the top-level
+/// settings directory) and the profile this session runs as. The active
+/// profile is fixed at startup; switching restarts the app.
+pub struct Profiles {
+    registry: Mutex<ProfileRegistry>,
+    path: PathBuf,
+    active: Profile,
+    chosen_explicitly: bool,
+}
+
+impl Profiles {
+    /// Picks the session's profile from `--profile <id>`, `EPOQ_PROFILE` or
+    /// the last one used, falling back to the default profile.
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let path = app.path().app_config_dir().map_err(|e| e.to_string())?.join("profiles.json");
+        let registry: ProfileRegistry = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+
+        let args: Vec<String> = std::env::args().collect();
+        let requested = args
+            .iter()
+            .position(|a| a == "--profile")
+            .and_then(|i| args.get(i + 1).cloned())
+            .or_else(|| std::env::var(PROFILE_ENV).ok());
+        let chosen_explicitly = requested.is_some();
+        let wanted = requested.or_else(|| registry.last_used.clone());
+        let active = match wanted.as_deref() {
+            None | Some(DEFAULT_PROFILE) => default_profile(),
+            Some(id) => match registry.profiles.iter().find(|p| p.id == id || p.name == id) {
+                Some(profile) => profile.clone(),
+                None => {
+                    tracing::warn!("Unknown profile {}; using the default profile.", id);
+                    default_profile()
+                }
+            },
+        };
+        Ok(Self {
+            registry: Mutex::new(registry),
+            path,
+            active,
+            chosen_explicitly,
+        })
+    }
+
+    /// Subdirectory for the active profile's data and settings, `None` for
+    /// the default profile.
+    pub fn scope(&self) -> Option<PathBuf> {
+        (self.active.id != DEFAULT_PROFILE).then(|| PathBuf::from("profiles").join(&self.active.id))
+    }
+
+    pub fn active(&self) -> &Profile {
+        &self.active
+    }
+
+    pub fn status(&self) -> ProfilesStatus {
+        let registry = self.registry.lock().unwrap();
+        let mut profiles = vec![default_profile()];
+        profiles.extend(registry.profiles.iter().cloned());
+        ProfilesStatus {
+            active: self.active.clone(),
+            show_picker: registry.prompt_at_startup && !self.chosen_explicitly && profiles.len() > 1,
+            prompt_at_startup: registry.prompt_at_startup,
+            profiles,
+        }
+    }
+
+    fn save(&self, registry: &ProfileRegistry) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(registry).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| e.to_string())
+    }
+
+    /// Creates a profile, or updates the name and model sharing of an existing one.
+    pub fn save_profile(&self, mut profile: Profile) -> Result<Profile, String> {
+        let name = profile.name.trim().to_string();
+        if name.is_empty() {
+            return Err("Profile name is required.".to_string());
+        }
+        if profile.id == DEFAULT_PROFILE {
+            return Err("The default profile cannot be changed.".to_string());
+        }
+        let mut registry = self.registry.lock().unwrap();
+        if registry
+            .profiles
+            .iter()
+            .any(|p| p.id != profile.id && p.name.eq_ignore_ascii_case(&name))
+        {
+            return Err(format!("A profile named {} already exists.", name));
+        }
+        profile.name = name;
+        match registry.profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => {
+                existing.name = profile.name.clone();
+                existing.share_models = profile.share_models;
+                profile = existing.clone();
+            }
+            None => {
+                profile.id = new_id("profile");
+                profile.created_at = unix_now();
+                registry.profiles.push(profile.clone());
+            }
+        }
+        self.save(&registry)?;
+        Ok(profile)
+    }
+
+    /// Forgets a profile. Its files are left on disk.
+    pub fn remove_profile(&self, id: &str) -> Result<(), String> {
+        if id == self.active.id {
+            return Err("The profile in use cannot be removed.".to_string());
+        }
+        let mut registry = self.registry.lock().unwrap();
+        let before = registry.profiles.len();
+        registry.profiles.retain(|p| p.id != id);
+        if registry.profiles.len() == before {
+            return Err(format!("No profile with id {}", id));
+        }
+        if registry.last_used.as_deref() == Some(id) {
+            registry.last_used = None;
+        }
+        self.save(&registry)
+    }
+
+    pub fn set_prompt_at_startup(&self, prompt: bool) -> Result<(), String> {
+        let mut registry = self.registry.lock().unwrap();
+        registry.prompt_at_startup = prompt;
+        self.save(&registry)
+    }
+
+    /// Remembers `id` as the profile to open next time.
+    fn set_last_used(&self, id: &str) -> Result<(), String> {
+        let mut registry = self.registry.lock().unwrap();
+        if id != DEFAULT_PROFILE && !registry.profiles.iter().any(|p| p.id == id) {
+            return Err(format!("No profile with id {}", id));
+        }
+        registry.last_used = Some(id.to_string());
+        self.save(&registry)
+    }
+}
+
+/// Opens `id`. Continuing with the active profile just records the choice;
+/// any other profile relaunches the app, since every store is loaded at startup.
+pub fn switch(app: &tauri::AppHandle, profiles: &Profiles, id: &str) -> Result<(), String> {
+    profiles.set_last_used(id)?;
+    if id == profiles.active.id {
+        return Ok(());
+    }
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    std::process::Command::new(exe)
+        .env(PROFILE_ENV, id)
+        .spawn()
+        .map_err(|e| format!("Could not restart with the new profile: {}", e))?;
+    app.exit(0);
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::classes;
+use crate::dataset;
+use crate::dataset_scan;
+use crate::jobs::unix_now;
+use crate::models::{self, RegisteredModel};
+use crate::paths;
+use crate::run_manifest;
+
+/// Version of the archive layout written by `export`.
+const FORMAT_VERSION: u32 = 1;
+const PROJECT_FILE: &str = "project.json";
+const DATASET_FILE: &str = "dataset_manifest.json";
+/// Run directory files live under this folder in the archive.
+const RUN_PREFIX: &str = "run/";
+/// Run subfolders left out: per-epoch checkpoints are large and only matter
+/// for resuming on the machine that trained.
+const SKIPPED_DIRS: &[&str] = &["checkpoints"];
+/// The JSON entries are small; anything bigger is not a project archive.
+const MAX_JSON_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArchivedFile {
+    /// Relative to the run directory, with `/` separators.
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+/// `project.json`: what the archive holds and the checksum of every file.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProjectManifest {
+    pub format_version: u32,
+    pub name: String,
+    pub created_at: u64,
+    pub architecture: Option<String>,
+    /// Class names in output order.
+    pub classes: Vec<String>,
+    /// Where the dataset was on the exporting machine.
+    pub dataset_path: Option<String>,
+    pub files: Vec<ArchivedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DatasetFile {
+    /// Relative to the dataset folder, with `/` separators.
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// `dataset_manifest.json`: the dataset's layout without its images, so it
+/// can be found or rebuilt on the other machine.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DatasetManifest {
+    pub path: String,
+    /// The run manifest's dataset section: splits and class counts.
+    pub summary: serde_json::Value,
+    /// Result of `scan_dataset`; null when the folder was gone at export.
+    pub scan: Option<serde_json::Value>,
+    pub files: Vec<DatasetFile>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProjectExport {
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// The dataset folder was missing, so only the run manifest's summary of
+    /// it was included.
+    pub dataset_missing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProjectImport {
+    pub manifest: ProjectManifest,
+    pub dataset: Option<DatasetManifest>,
+    /// Registry entry of the unpacked run.
+    pub model: RegisteredModel,
+    pub warnings: Vec<String>,
+}
+
+/// Files of a run directory to archive, relative to it, sorted.
+fn run_files(run_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let dir = run_dir.join(&relative);
+        let entries = std::fs::read_dir(&dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = relative.join(&name);
+            let Ok(kind) = entry.file_type() else { continue };
+            if kind.is_dir() {
+                if !(relative.as_os_str().is_empty() && SKIPPED_DIRS.contains(&name.as_str())) {
+                    pending.push(path);
+                }
+            } else if kind.is_file() && !name.ends_with(".part") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn archive_path(relative: &Path) -> String {
+    relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+fn dataset_manifest(manifest: &serde_json::Value) -> Option<(DatasetManifest, bool)> {
+    let path = manifest["dataset"]["path"].as_str().or(manifest["config"]["path"].as_str())?.to_string();
+    let root = Path::new(&path);
+    let scan = dataset_scan::scan(&path).ok().and_then(|scan| serde_json::to_value(scan).ok());
+    let files = dataset::list_images(root, true)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|image| {
+            let bytes = std::fs::metadata(&image).ok()?.len();
+            let relative = image.strip_prefix(root).ok()?;
+            Some(DatasetFile { path: archive_path(relative), bytes })
+        })
+        .collect();
+    let missing = !root.is_dir();
+    Some((DatasetManifest { path, summary: manifest["dataset"].clone(), scan, files }, missing))
+}
+
+/// Copies `source` into the open zip entry, returning its SHA-256.
+fn copy_hashed(source: &Path, zip: &mut impl Write) -> Result<String, String> {
+    let mut file = std::fs::File::open(source).map_err(|e| format!("Cannot read {}: {}", source.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        zip.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Packs a training run — weights, run manifest with its training config,
+/// class list, preprocessing, evaluation and reports — and a manifest of its
+/// dataset into one `.epoq` (zip) archive at `dest`.
+pub fn export(run_dir: &Path, dest: &Path) -> Result<ProjectExport, String> {
+    let run_manifest = run_manifest::load(run_dir)
+        .map_err(|e| format!("{} is not a training run: {}", run_dir.display(), e))?;
+    // Writes classes.json from the manifest if the run predates it.
+    let classes: Vec<String> = classes::model_classes(run_dir)?.into_iter().map(|e| e.name).collect();
+    let name = run_manifest["experiment_id"]
+        .as_str()
+        .map(String::from)
+        .or_else(|| run_dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "project".to_string());
+
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    let file = std::fs::File::create(dest).map_err(|e| format!("Cannot create {}: {}", dest.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+
+    let mut files = Vec::new();
+    for relative in run_files(run_dir)? {
+        let source = run_dir.join(&relative);
+        let bytes = std::fs::metadata(&source).map(|m| m.len()).unwrap_or(0);
+        let path = archive_path(&relative);
+        zip.start_file(format!("{}{}", RUN_PREFIX, path), options.large_file(bytes > u32::MAX as u64))
+            .map_err(|e| e.to_string())?;
+        let sha256 = copy_hashed(&source, &mut zip)?;
+        files.push(ArchivedFile { path, bytes, sha256 });
+    }
+
+    let dataset = dataset_manifest(&run_manifest);
+    let dataset_missing = dataset.as_ref().is_some_and(|(_, missing)| *missing);
+    if let Some((dataset, _)) = &dataset {
+        let bytes = serde_json::to_vec_pretty(dataset).map_err(|e| e.to_string())?;
+        zip.start_file(DATASET_FILE, options).map_err(|e| e.to_string())?;
+        zip.write_all(&bytes).map_err(|e| e.to_string())?;
+    }
+
+    let manifest = ProjectManifest {
+        format_version: FORMAT_VERSION,
+        name,
+        created_at: unix_now(),
+        architecture: run_manifest["architecture"]["name"]
+            .as_str()
+            .or(run_manifest["config"]["model"].as_str())
+            .map(String::from),
+        classes,
+        dataset_path: dataset.as_ref().map(|(d, _)| d.path.clone()),
+        files,
+    };
+    let bytes = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.start_file(PROJECT_FILE, options).map_err(|e| e.to_string())?;
+    zip.write_all(&bytes).map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+
+    Ok(ProjectExport {
+        path: dest.to_string_lossy().to_string(),
+        files: manifest.files.len(),
+        bytes: std::fs::metadata(dest).map(|m| m.len()).unwrap_or(0),
+        dataset_missing,
+    })
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(
+    archive: &mut zip::ZipArchive<std::fs::File>,
+    name: &str,
+) -> Result<Option<T>, String> {
+    let entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    if entry.size() > MAX_JSON_ENTRY_BYTES {
+        return Err(format!("{} is too large for a project archive.", name));
+    }
+    let mut bytes = Vec::new();
+    entry.take(MAX_JSON_ENTRY_BYTES).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&bytes).map(Some).map_err(|e| format!("Invalid {}: {}", name, e))
+}
+
+/// A folder under `imported/` in the models folder not in use yet.
+fn import_dir(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, String> {
+    let base = paths::models_dir(app)?.join("imported");
+    let stem: String =
+        name.chars().map(|c| if c.is_ascii_alphanumeric() || "-_.".contains(c) { c } else { '_' }).collect();
+    let stem = if stem.trim_matches('.').is_empty() { "project".to_string() } else { stem };
+    let mut dir = base.join(&stem);
+    let mut n = 2;
+    while dir.exists() {
+        dir = base.join(format!("{}_{}", stem, n));
+        n += 1;
+    }
+    Ok(dir)
+}
+
+fn unpack(archive: &mut zip::ZipArchive<std::fs::File>, manifest: &ProjectManifest, dir: &Path) -> Result<(), String> {
+    for expected in &manifest.files {
+        let relative = Path::new(&expected.path);
+        if !relative.components().all(|c| matches!(c, std::path::Component::Normal(_))) {
+            return Err(format!("The archive lists an unsafe path: {}.", expected.path));
+        }
+        let entry = archive
+            .by_name(&format!("{}{}", RUN_PREFIX, expected.path))
+            .map_err(|_| format!("The archive is missing {}.", expected.path))?;
+        let dest = dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+        }
+        let mut out = std::fs::File::create(&dest).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+        let mut hasher = Sha256::new();
+        let mut reader = entry.take(expected.bytes + 1);
+        let mut buffer = vec![0; 1 << 20];
+        let mut written = 0;
+        loop {
+            let read = reader.read(&mut buffer).map_err(|e| format!("Cannot read {}: {}", expected.path, e))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            out.write_all(&buffer[..read]).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+            written += read as u64;
+        }
+        if written != expected.bytes || !hex::encode(hasher.finalize()).eq_ignore_ascii_case(&expected.sha256) {
+            return Err(format!("{} in the archive is corrupt: its checksum does not match.", expected.path));
+        }
+    }
+    Ok(())
+}
+
+/// Unpacks an archive made by `export` into the models folder, checking
+/// every file against its checksum, and adds the run to the registry.
+pub fn import(app: &tauri::AppHandle, archive_path: &Path) -> Result<ProjectImport, String> {
+    let file =
+        std::fs::File::open(archive_path).map_err(|e| format!("Cannot open {}: {}", archive_path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Not a project archive: {}", e))?;
+    let manifest: ProjectManifest =
+        read_json(&mut archive, PROJECT_FILE)?.ok_or_else(|| format!("Not a project archive: no {}.", PROJECT_FILE))?;
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "Project archive format {} is newer than this version of EPOQ supports ({}).",
+            manifest.format_version, FORMAT_VERSION
+        ));
+    }
+    if !manifest.files.iter().any(|f| f.path == run_manifest::FILE_NAME) {
+        return Err("The project archive holds no run manifest.".to_string());
+    }
+    let dataset: Option<DatasetManifest> = read_json(&mut archive, DATASET_FILE)?;
+
+    let dir = import_dir(app, &manifest.name)?;
+    if let Err(e) = unpack(&mut archive, &manifest, &dir) {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+
+    let mut warnings = Vec::new();
+    if !manifest.files.iter().any(|f| f.path == "best_model.pth") {
+        warnings
+            .push("The archive has no trained weights; the model cannot predict until it is retrained.".to_string());
+    }
+    if let Some(dataset) = &dataset {
+        if !Path::new(&dataset.path).is_dir() {
+            warnings.push(format!(
+                "The dataset is not at {} on this machine; point training at a copy to retrain or evaluate.",
+                dataset.path
+            ));
+        }
+    }
+    let model = models::register(app, &dir.to_string_lossy(), Some(manifest.name.clone()))?;
+    tracing::info!(archive = %archive_path.display(), "Imported project into {}", dir.display());
+    Ok(ProjectImport { manifest, dataset, model, warnings })
+}
@@ -0,0 +1,242 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::jobs::unix_now;
+use crate::recents::{self, RecentKind};
+use crate::training::TrainingConfig;
+
+/// Manifest at the root of a project directory.
+pub const MANIFEST_FILE: &str = "project.json";
+/// Version of the manifest layout written by `save`.
+const FORMAT_VERSION: u32 = 1;
+/// Runs started while a project is open go here unless they name a folder.
+const RUNS_DIR: &str = "runs";
+
+/// A project: its datasets, training runs and models, and whatever state the
+/// UI keeps per project. Paths inside the project directory are stored
+/// relative to it so the directory can be moved; the API always sees them
+/// absolute.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Project {
+    pub format_version: u32,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub datasets: Vec<String>,
+    /// Run directories, oldest first.
+    pub runs: Vec<String>,
+    /// Model ids (run directories) in the registry.
+    pub models: Vec<String>,
+    /// Per-project UI and training state, e.g. the last training config.
+    pub settings: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            name: String::new(),
+            description: None,
+            created_at: 0,
+            updated_at: 0,
+            datasets: Vec::new(),
+            runs: Vec::new(),
+            models: Vec::new(),
+            settings: serde_json::Map::new(),
+        }
+    }
+}
+
+impl Project {
+    /// Rewrites every listed path with `map`.
+    fn map_paths(mut self, map: impl Fn(&str) -> String) -> Self {
+        for list in [&mut self.datasets, &mut self.runs, &mut self.models] {
+            *list = list.iter().map(|p| map(p)).collect();
+        }
+        self
+    }
+}
+
+/// The open project and where it lives.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct OpenProject {
+    pub path: String,
+    pub project: Project,
+    /// Listed datasets, runs and models that are no longer on disk.
+    pub missing: Vec<String>,
+}
+
+fn absolute(root: &Path, stored: &str) -> String {
+    let path = Path::new(stored);
+    if path.is_absolute() {
+        stored.to_string()
+    } else {
+        root.join(path).to_string_lossy().to_string()
+    }
+}
+
+fn relative(root: &Path, path: &str) -> String {
+    match Path::new(path).strip_prefix(root) {
+        Ok(inner) => inner.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"),
+        Err(_) => path.to_string(),
+    }
+}
+
+fn read_manifest(dir: &Path) -> Result<Project, String> {
+    let path = dir.join(MANIFEST_FILE);
+    let raw = std::fs::read_to_string(&path)
+        .map_err(|e| format!("{} is not a project: cannot read {}: {}", dir.display(), MANIFEST_FILE, e))?;
+    let project: Project = serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    if project.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "The project was saved by a newer version of EPOQ (format {}; this version reads {}).",
+            project.format_version, FORMAT_VERSION
+        ));
+    }
+    Ok(project.map_paths(|p| absolute(dir, p)))
+}
+
+fn write_manifest(dir: &Path, project: &Project) -> Result<(), String> {
+    let stored = project.clone().map_paths(|p| relative(dir, p));
+    let path = dir.join(MANIFEST_FILE);
+    let raw = serde_json::to_string_pretty(&stored).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+fn describe(dir: &Path, project: &Project) -> OpenProject {
+    let missing = project
+        .datasets
+        .iter()
+        .chain(&project.runs)
+        .chain(&project.models)
+        .filter(|p| !Path::new(p).exists())
+        .cloned()
+        .collect();
+    OpenProject { path: dir.to_string_lossy().to_string(), project: project.clone(), missing }
+}
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+/// Managed state: the project open in this session, if any.
+#[derive(Default)]
+pub struct Projects {
+    active: Mutex<Option<(PathBuf, Project)>>,
+}
+
+impl Projects {
+    pub fn active(&self) -> Option<OpenProject> {
+        self.active.lock().unwrap().as_ref().map(|(dir, project)| describe(dir, project))
+    }
+
+    fn activate(&self, app: &tauri::AppHandle, dir: PathBuf, project: Project) -> OpenProject {
+        let open = describe(&dir, &project);
+        if let Err(e) = recents::add(app, RecentKind::Project, &open.path) {
+            tracing::warn!("Cannot record the project in recents: {}", e);
+        }
+        *self.active.lock().unwrap() = Some((dir, project));
+        open
+    }
+
+    /// Makes `dir` a new project and opens it. The directory may exist but
+    /// must not already hold a project.
+    pub fn create(
+        &self,
+        app: &tauri::AppHandle,
+        dir: &Path,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<OpenProject, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("A project needs a name.".to_string());
+        }
+        if dir.join(MANIFEST_FILE).exists() {
+            return Err(format!("{} already holds a project; open it instead.", dir.display()));
+        }
+        std::fs::create_dir_all(dir.join(RUNS_DIR)).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        let now = unix_now();
+        let project = Project {
+            name: name.to_string(),
+            description: description.filter(|d| !d.trim().is_empty()),
+            created_at: now,
+            updated_at: now,
+            ..Default::default()
+        };
+        write_manifest(dir, &project)?;
+        tracing::info!(project = %name, "Created project at {}", dir.display());
+        Ok(self.activate(app, dir.to_path_buf(), project))
+    }
+
+    pub fn open(&self, app: &tauri::AppHandle, dir: &Path) -> Result<OpenProject, String> {
+        let project = read_manifest(dir)?;
+        Ok(self.activate(app, dir.to_path_buf(), project))
+    }
+
+    /// Writes the open project's manifest, first replacing its contents with
+    /// `project` when given. The creation time and format are kept.
+    pub fn save(&self, project: Option<Project>) -> Result<OpenProject, String> {
+        let mut active = self.active.lock().unwrap();
+        let (dir, current) = active.as_mut().ok_or("No project is open.")?;
+        if let Some(mut project) = project {
+            if project.name.trim().is_empty() {
+                return Err("A project needs a name.".to_string());
+            }
+            project.format_version = FORMAT_VERSION;
+            project.created_at = current.created_at;
+            *current = project;
+        }
+        current.updated_at = unix_now();
+        write_manifest(dir, current)?;
+        Ok(describe(dir, current))
+    }
+
+    /// Points a run without an explicit output folder at the open project's
+    /// `runs/` folder.
+    pub fn place_run(&self, config: &mut TrainingConfig) -> Result<(), String> {
+        let active = self.active.lock().unwrap();
+        let (Some((dir, _)), None, Some(experiment_id)) = (active.as_ref(), &config.save_path, &config.experiment_id)
+        else {
+            return Ok(());
+        };
+        let run_dir = dir.join(RUNS_DIR).join(experiment_id);
+        std::fs::create_dir_all(&run_dir).map_err(|e| format!("Cannot create {}: {}", run_dir.display(), e))?;
+        config.save_path = Some(run_dir.to_string_lossy().to_string());
+        Ok(())
+    }
+
+    /// Adds a started run and its dataset to the open project and saves it.
+    pub fn record_run(&self, config: &TrainingConfig) {
+        let mut active = self.active.lock().unwrap();
+        let Some((dir, project)) = active.as_mut() else {
+            return;
+        };
+        push_unique(&mut project.datasets, config.path.clone());
+        push_unique(&mut project.runs, config.output_dir().to_string_lossy().to_string());
+        project.updated_at = unix_now();
+        if let Err(e) = write_manifest(dir, project) {
+            tracing::warn!("Cannot record the run in the project: {}", e);
+        }
+    }
+
+    /// Reopens the most recently opened project, if it is still there.
+    pub fn restore(&self, app: &tauri::AppHandle) {
+        let Ok(items) = recents::load(app) else {
+            return;
+        };
+        let Some(item) = items.into_iter().find(|item| item.kind == RecentKind::Project) else {
+            return;
+        };
+        match read_manifest(Path::new(&item.path)) {
+            Ok(project) => *self.active.lock().unwrap() = Some((PathBuf::from(item.path), project)),
+            Err(e) => tracing::info!("Last project not reopened: {}", e),
+        }
+    }
+}
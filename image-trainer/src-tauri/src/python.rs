@@ -0,0 +1,840 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Manager;
+use tauri_plugin_shell::process::{CommandEvent, Output};
+use tauri_plugin_shell::ShellExt;
+
+use crate::dev_mode;
+use crate::embedded_python;
+use crate::error::BackendError;
+use crate::events;
+use crate::i18n;
+use crate::interpreters;
+use crate::job_env;
+use crate::jobs::{new_id, JobManager};
+use crate::paths;
+
+/// Interpreters tried in order after the configured one.
+const PYTHON_CANDIDATES: [&str; 3] = ["python", "python3", "py"];
+
+/// Checked before PATH on macOS: apps launched from Finder get a minimal PATH
+/// that misses Homebrew and python.org installs and reaches only /usr/bin.
+#[cfg(target_os = "macos")]
+const MACOS_PYTHON_LOCATIONS: &[&str] = &[
+    "/opt/homebrew/bin/python3",
+    "/usr/local/bin/python3",
+    "/Library/Frameworks/Python.framework/Versions/Current/bin/python3",
+];
+
+/// Prints the interpreter's real path, pointer width and version.
+const PROBE_SCRIPT: &str = "import sys, struct; print(sys.executable); print(struct.calcsize('P') * 8); \
+print('%d.%d.%d' % sys.version_info[:3])";
+
+/// Name of the bundled backend binary built from `sidecar/`. Tauri installs
+/// it next to the app's executable, without the target triple.
+const SIDECAR_NAME: &str = "epoq-backend";
+
+/// Lines of stderr kept for the error message of a failed streamed run.
+const STDERR_TAIL: usize = 20;
+/// Characters of stderr kept per launch attempt.
+const ATTEMPT_STDERR: usize = 500;
+const MAX_LAUNCH_ATTEMPTS: u32 = 10;
+const MAX_BACKOFF_MS: u64 = 10_000;
+
+/// One line of a streamed script's output, published as `python://stdout`
+/// or `python://stderr` with the caller's job id.
+#[derive(Debug, Clone, Serialize)]
+pub struct PythonOutput {
+    pub line: String,
+}
+
+/// How scripts are run, reported by `get_backend_mode`.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendMode {
+    /// The self-contained backend shipped with the app.
+    Sidecar,
+    /// A Python installed on the machine, or the one the user picked.
+    SystemPython,
+    /// Neither works; scripts cannot run.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BackendStatus {
+    pub mode: BackendMode,
+    /// Program scripts are started with.
+    pub interpreter: Option<String>,
+    /// Whether this build ships the bundled backend, even if not in use.
+    pub sidecar_available: bool,
+    pub error: Option<String>,
+}
+
+/// User choices for locating Python, stored in `python_settings.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+pub struct PythonSettings {
+    /// Version passed to the Windows `py` launcher, e.g. `3.11` for `py -3.11`.
+    pub py_version: Option<String>,
+    /// Interpreter picked with `set_python_interpreter`, tried before any
+    /// other. Falls back to the usual search when it stops working.
+    pub interpreter: Option<String>,
+    /// Conda environment picked with `set_conda_environment`; scripts then run
+    /// through `conda run -n <env>`, which activates it first.
+    pub conda_env: Option<String>,
+    pub retry: LaunchRetry,
+}
+
+/// How often an interpreter that fails to start is tried again, e.g. while
+/// antivirus software holds the executable. Missing programs and scripts that
+/// ran and failed are not retried.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct LaunchRetry {
+    /// Tries per interpreter, including the first.
+    pub max_attempts: u32,
+    /// Wait before the second try, doubled for each try after it.
+    pub backoff_ms: u64,
+}
+
+impl Default for LaunchRetry {
+    fn default() -> Self {
+        Self { max_attempts: 2, backoff_ms: 500 }
+    }
+}
+
+impl LaunchRetry {
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor).min(MAX_BACKOFF_MS))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchOutcome {
+    /// Not run, e.g. the macOS placeholder python3.
+    Skipped,
+    /// No such program.
+    NotFound,
+    /// The program exists but could not be started or resolved.
+    StartFailed,
+    /// Started and exited with an error: installed, but mis-configured or
+    /// missing packages. Also a 32-bit Python or one other than the pinned version.
+    Failed,
+    Succeeded,
+}
+
+/// One try at starting an interpreter, for telling a missing Python from a
+/// broken one.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LaunchAttempt {
+    /// As typed in a shell.
+    pub command: String,
+    /// 1 for the first try of this command.
+    pub attempt: u32,
+    pub outcome: LaunchOutcome,
+    pub exit_code: Option<i32>,
+    /// End of stderr, or why the program could not start.
+    pub detail: Option<String>,
+}
+
+/// Result of `diagnose_python`: every interpreter tried, not only up to the
+/// first that works.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PythonDiagnosis {
+    /// The interpreter scripts would run with.
+    pub interpreter: Option<String>,
+    pub attempts: Vec<LaunchAttempt>,
+}
+
+impl PythonSettings {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if let Some(version) = &self.py_version {
+            let mut parts = version.split('.');
+            let valid = parts.next() == Some("3")
+                && parts.all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+                && version.split('.').count() <= 2;
+            if !valid {
+                return Err(BackendError::invalid_input(format!("'{}' is not a Python 3 version like 3.11.", version)));
+            }
+        }
+        if self.interpreter.as_deref().is_some_and(|path| path.trim().is_empty()) {
+            return Err(BackendError::invalid_input("The interpreter path is empty."));
+        }
+        if self.conda_env.as_deref().is_some_and(|env| env.trim().is_empty()) {
+            return Err(BackendError::invalid_input("The conda environment name is empty."));
+        }
+        if self.interpreter.is_some() && self.conda_env.is_some() {
+            return Err(BackendError::invalid_input("Choose either an interpreter or a conda environment, not both."));
+        }
+        if !(1..=MAX_LAUNCH_ATTEMPTS).contains(&self.retry.max_attempts) {
+            return Err(BackendError::invalid_input(format!(
+                "Launch attempts must be between 1 and {}.",
+                MAX_LAUNCH_ATTEMPTS
+            )));
+        }
+        if self.retry.backoff_ms > MAX_BACKOFF_MS {
+            return Err(BackendError::invalid_input(format!(
+                "The launch backoff must be at most {} ms.",
+                MAX_BACKOFF_MS
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub fn load_settings(app: &tauri::AppHandle) -> PythonSettings {
+    paths::settings_file(app, "python_settings.json")
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_settings(app: &tauri::AppHandle, settings: &PythonSettings) -> Result<(), String> {
+    settings.validate()?;
+    let path = paths::settings_file(app, "python_settings.json")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| e.to_string())
+}
+
+/// The bundled backend, when this build ships one.
+pub fn sidecar_path() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    let path = exe
+        .parent()?
+        .join(format!("{}{}", SIDECAR_NAME, std::env::consts::EXE_SUFFIX));
+    path.is_file().then(|| path.to_string_lossy().to_string())
+}
+
+/// Interpreters to try, in order: the one the user picked, the bundled
+/// backend, then on macOS pyenv shims, Homebrew and python.org framework
+/// builds, then the bare names. The bundled backend takes `python`'s command
+/// line, so it is tried like any other interpreter.
+fn interpreter_candidates(app: &tauri::AppHandle) -> Vec<String> {
+    let mut candidates: Vec<String> = load_settings(app).interpreter.into_iter().collect();
+    candidates.extend(sidecar_path());
+    #[cfg(target_os = "macos")]
+    {
+        use std::path::PathBuf;
+        let pyenv_root = std::env::var_os("PYENV_ROOT")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".pyenv")));
+        let found = pyenv_root
+            .map(|root| root.join("shims").join("python3"))
+            .into_iter()
+            .chain(MACOS_PYTHON_LOCATIONS.iter().map(PathBuf::from))
+            .filter(|path| path.is_file())
+            .map(|path| path.to_string_lossy().to_string());
+        candidates.extend(found);
+    }
+    candidates.extend(PYTHON_CANDIDATES.iter().map(|c| c.to_string()));
+    candidates
+}
+
+/// Whether `cmd` is the Xcode Command Line Tools placeholder in /usr/bin, which
+/// pops up an install dialog instead of running while the tools are missing.
+/// Detected without running it, so probing never triggers the dialog.
+#[cfg(target_os = "macos")]
+fn is_xcode_stub(cmd: &str) -> bool {
+    let in_usr_bin = match cmd {
+        "python" | "python3" => !std::path::Path::new("/opt/homebrew/bin").join(cmd).is_file()
+            && !std::path::Path::new("/usr/local/bin").join(cmd).is_file(),
+        other => other.starts_with("/usr/bin/"),
+    };
+    in_usr_bin
+        && !std::process::Command::new("xcode-select")
+            .arg("-p")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_xcode_stub(_cmd: &str) -> bool {
+    false
+}
+
+/// Absolute path of a script bundled under `python_backend/` (or in the
+/// developer override directory), with the Windows verbatim prefix stripped so
+/// Python accepts it. `ScriptNotFound` when the file is missing.
+pub fn backend_script(app: &tauri::AppHandle, name: &str) -> Result<String, BackendError> {
+    let script_path = match dev_mode::override_script(app, name)? {
+        Some(path) => path,
+        None => app
+            .path()
+            .resource_dir()
+            .map_err(|e| e.to_string())?
+            .join("python_backend")
+            .join(name),
+    };
+
+    let script = script_path.to_string_lossy().to_string().replace("\\\\?\\", "");
+    if !script_path.is_file() {
+        return Err(BackendError::script_not_found(script, &i18n::localizer(app)));
+    }
+    Ok(script)
+}
+
+/// Program and leading arguments that start Python: an interpreter on its
+/// own, or a conda tool running `python` in an environment.
+#[derive(Debug, Clone)]
+pub struct PythonLauncher {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl PythonLauncher {
+    fn interpreter(program: String) -> Self {
+        Self { program, args: Vec::new() }
+    }
+
+    /// Arguments for running `args`, e.g. a script and its options.
+    pub fn with_args<S: Into<String>>(&self, args: impl IntoIterator<Item = S>) -> Vec<String> {
+        self.args.iter().cloned().chain(args.into_iter().map(Into::into)).collect()
+    }
+
+    /// As typed in a shell, e.g. `conda run -n torch --no-capture-output python`.
+    pub fn display(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|part| if part.contains(' ') { format!("\"{}\"", part) } else { part.clone() })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// `conda run` in the selected environment, when one is selected and conda,
+/// mamba or micromamba is installed. `--no-capture-output` makes output
+/// stream instead of arriving at exit; micromamba streams without it.
+async fn conda_launcher(app: &tauri::AppHandle) -> Option<PythonLauncher> {
+    let env = load_settings(app).conda_env?;
+    let tool = interpreters::conda_tool(app).await?;
+    let mut args = vec!["run".to_string(), "-n".to_string(), env];
+    let micromamba = Path::new(&tool)
+        .file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().starts_with("micromamba"));
+    if !micromamba {
+        args.push("--no-capture-output".to_string());
+    }
+    args.push("python".to_string());
+    Some(PythonLauncher { program: tool, args })
+}
+
+/// How to start Python for a long-running process: through the selected conda
+/// environment if it works, otherwise the first interpreter that answers.
+pub async fn resolve_launcher(app: &tauri::AppHandle) -> Result<PythonLauncher, BackendError> {
+    if let Some(launcher) = conda_launcher(app).await {
+        let output = app
+            .shell()
+            .command(&launcher.program)
+            .args(launcher.with_args(["--version"]))
+            .output()
+            .await;
+        match output {
+            Ok(output) if output.status.success() => return Ok(launcher),
+            Ok(output) => tracing::warn!(
+                "Conda environment unusable, searching for Python instead: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => tracing::warn!("Conda environment unusable, searching for Python instead: {}", e),
+        }
+    }
+    find_interpreter(app).await.map(PythonLauncher::interpreter)
+}
+
+/// The command line that starts Python, for messages and diagnostics.
+/// Callers that spawn processes use `resolve_launcher`.
+pub async fn resolve_interpreter(app: &tauri::AppHandle) -> Result<String, BackendError> {
+    resolve_launcher(app).await.map(|launcher| launcher.display())
+}
+
+/// The end of `text`, trimmed; None when empty.
+fn snippet(text: &str) -> Option<String> {
+    let text = text.trim();
+    let skip = text.chars().count().saturating_sub(ATTEMPT_STDERR);
+    (!text.is_empty()).then(|| text.chars().skip(skip).collect())
+}
+
+fn attempt(command: &str, attempt: u32, outcome: LaunchOutcome, detail: Option<String>) -> LaunchAttempt {
+    LaunchAttempt { command: command.to_string(), attempt, outcome, exit_code: None, detail }
+}
+
+/// Runs `launcher` with `args`, retrying starts that fail for a reason other
+/// than a missing program, and records every try in `attempts`. None when it
+/// never started; otherwise its output, whatever the exit code.
+async fn launch(
+    app: &tauri::AppHandle,
+    launcher: &PythonLauncher,
+    args: Vec<String>,
+    env: &[(String, String)],
+    attempts: &mut Vec<LaunchAttempt>,
+) -> Option<Output> {
+    let retry = load_settings(app).retry;
+    let command = launcher.display();
+    for n in 1..=retry.max_attempts.max(1) {
+        if n > 1 {
+            tokio::time::sleep(retry.delay(n - 1)).await;
+        }
+        let result = app
+            .shell()
+            .command(&launcher.program)
+            .args(launcher.with_args(args.iter().cloned()))
+            .envs(job_env::merged(app, env.to_vec()))
+            .output()
+            .await;
+        match result {
+            Ok(output) => {
+                let outcome = if output.status.success() { LaunchOutcome::Succeeded } else { LaunchOutcome::Failed };
+                let detail = snippet(&String::from_utf8_lossy(&output.stderr)).filter(|_| !output.status.success());
+                attempts.push(LaunchAttempt { exit_code: output.status.code(), ..attempt(&command, n, outcome, detail) });
+                return Some(output);
+            }
+            Err(tauri_plugin_shell::Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                attempts.push(attempt(&command, n, LaunchOutcome::NotFound, Some(e.to_string())));
+                return None;
+            }
+            Err(e) => attempts.push(attempt(&command, n, LaunchOutcome::StartFailed, Some(e.to_string()))),
+        }
+    }
+    None
+}
+
+/// Resolves an interpreter candidate to the executable it starts, refusing it
+/// unless it is a 64-bit Python (PyTorch has no 32-bit builds) of the pinned
+/// version, if one is set; the `py` launcher is asked for that version itself.
+/// Scripts then run the resolved executable directly. Tries are recorded in
+/// `attempts`, a refusal as a failure of the last one.
+async fn resolve_candidate(app: &tauri::AppHandle, cmd: &str, attempts: &mut Vec<LaunchAttempt>) -> Option<String> {
+    let pinned = load_settings(app).py_version;
+    let mut args = Vec::new();
+    if let (Some(version), "py") = (&pinned, cmd) {
+        args.push(format!("-{}", version));
+    }
+    args.extend(["-c".to_string(), PROBE_SCRIPT.to_string()]);
+    let output = launch(app, &PythonLauncher::interpreter(cmd.to_string()), args, &[], attempts).await?;
+    let l10n = i18n::localizer(app);
+    let mut refuse = |reason: String| {
+        if let Some(last) = attempts.last_mut() {
+            last.outcome = LaunchOutcome::Failed;
+            last.detail = Some(reason);
+        }
+    };
+    if !output.status.success() {
+        if let (Some(version), "py") = (&pinned, cmd) {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            refuse(l10n.text(
+                "error-py-launcher-version",
+                &[("version", version.clone().into()), ("error", stderr.into())],
+            ));
+        }
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(str::trim);
+    let executable = lines.next().filter(|e| !e.is_empty()).unwrap_or(cmd).to_string();
+    let bits = lines.next();
+    let version = lines.next().unwrap_or_default();
+    if bits == Some("32") {
+        refuse(match &pinned {
+            Some(version) => l10n.text(
+                "error-python-32-bit-pinned",
+                &[("python", executable.clone().into()), ("version", version.clone().into())],
+            ),
+            None => l10n.text("error-python-32-bit", &[("python", executable.clone().into())]),
+        });
+        return None;
+    }
+    if let Some(pinned) = &pinned {
+        if version != pinned && !version.starts_with(&format!("{}.", pinned)) {
+            refuse(l10n.text(
+                "error-python-version",
+                &[
+                    ("python", executable.clone().into()),
+                    ("found", version.into()),
+                    ("version", pinned.clone().into()),
+                ],
+            ));
+            return None;
+        }
+    }
+    Some(executable)
+}
+
+/// Tries each interpreter, stopping at the first usable one unless `all`.
+/// Returns it and every attempt made.
+async fn probe_interpreters(app: &tauri::AppHandle, all: bool) -> (Option<String>, Vec<LaunchAttempt>) {
+    let mut attempts = Vec::new();
+    let mut found = None;
+    let sidecar = sidecar_path();
+    for cmd in interpreter_candidates(app) {
+        if is_xcode_stub(&cmd) {
+            attempts.push(attempt(&cmd, 1, LaunchOutcome::Skipped, Some(xcode_stub_error(app))));
+            continue;
+        }
+        // The bundled backend is built 64-bit with the app; it only needs to start.
+        let working = if sidecar.as_deref() == Some(cmd.as_str()) {
+            let launcher = PythonLauncher::interpreter(cmd.clone());
+            let output = launch(app, &launcher, vec!["--version".to_string()], &[], &mut attempts).await;
+            output.filter(|output| output.status.success()).map(|_| cmd)
+        } else {
+            resolve_candidate(app, &cmd, &mut attempts).await
+        };
+        if found.is_none() {
+            found = working;
+        }
+        if found.is_some() && !all {
+            break;
+        }
+    }
+    (found, attempts)
+}
+
+/// Why the macOS placeholder `python3` cannot be used.
+fn xcode_stub_error(app: &tauri::AppHandle) -> String {
+    i18n::localizer(app).text("error-xcode-stub", &[])
+}
+
+/// `PythonNotFound` with the attempts that led to it, its message from the
+/// last one that says why.
+fn not_found(app: &tauri::AppHandle, attempts: Vec<LaunchAttempt>) -> BackendError {
+    let message = if attempts.iter().all(|a| a.outcome == LaunchOutcome::Skipped) && !attempts.is_empty() {
+        xcode_stub_error(app)
+    } else {
+        attempts
+            .iter()
+            .rev()
+            .filter(|a| a.outcome != LaunchOutcome::Skipped)
+            .find_map(|a| a.detail.clone())
+            .unwrap_or_else(|| i18n::localizer(app).text("error-python-not-found", &[]))
+    };
+    BackendError::python_not_found(message).with_attempts(attempts)
+}
+
+/// Returns the first interpreter that answers `--version`.
+async fn find_interpreter(app: &tauri::AppHandle) -> Result<String, BackendError> {
+    match probe_interpreters(app, false).await {
+        (Some(interpreter), _) => Ok(interpreter),
+        (None, attempts) => Err(not_found(app, attempts)),
+    }
+}
+
+/// Tries every interpreter and reports how each one fared.
+pub async fn diagnose(app: &tauri::AppHandle) -> PythonDiagnosis {
+    let (interpreter, attempts) = probe_interpreters(app, true).await;
+    PythonDiagnosis { interpreter, attempts }
+}
+
+/// Which backend scripts run with: the bundled one unless the user picked an
+/// interpreter, then Python on the machine.
+pub async fn backend_status(app: &tauri::AppHandle) -> BackendStatus {
+    let sidecar = sidecar_path();
+    // Compared by program: the display form quotes paths with spaces.
+    let (mode, interpreter, error) = match resolve_launcher(app).await {
+        Ok(launcher) if sidecar.as_deref() == Some(launcher.program.as_str()) => {
+            (BackendMode::Sidecar, Some(launcher.display()), None)
+        }
+        Ok(launcher) => (BackendMode::SystemPython, Some(launcher.display()), None),
+        Err(e) => (BackendMode::Unavailable, None, Some(e.to_string())),
+    };
+    BackendStatus {
+        mode,
+        interpreter,
+        sidecar_available: sidecar.is_some(),
+        error,
+    }
+}
+
+/// Runs `run` as a job in the job list, recorded under the script's name.
+async fn recorded(
+    app: &tauri::AppHandle,
+    args: &[&str],
+    run: impl Future<Output = Result<String, BackendError>>,
+) -> Result<String, BackendError> {
+    let jobs = app.try_state::<Arc<JobManager>>().map(|jobs| jobs.inner().clone());
+    let job_id = new_id("py");
+    if let Some(jobs) = &jobs {
+        jobs.queue(&job_id, &script_kind(args), &owned(args), None);
+        jobs.start(&job_id);
+    }
+    let result = run.await;
+    if let Some(jobs) = &jobs {
+        if let Ok(stdout) = &result {
+            stdout.lines().for_each(|line| jobs.note_output(&job_id, line));
+        }
+        let code = match &result {
+            Ok(_) => Some(0),
+            Err(BackendError::ScriptFailed { code, .. }) => *code,
+            Err(_) => None,
+        };
+        jobs.finish(&job_id, code, result.as_ref().err().map(BackendError::to_string));
+    }
+    result
+}
+
+/// Runs a script to completion and returns its stdout. The call is recorded
+/// in the job list like any other job.
+pub async fn run_python(app: &tauri::AppHandle, args: &[&str]) -> Result<String, BackendError> {
+    recorded(app, args, run_first_interpreter(app, args, &[])).await
+}
+
+/// `run_python` in the one interpreter `resolve_launcher` picks. A script
+/// error is returned as it is rather than retried with the next interpreter,
+/// for probes expected to fail, such as running out of GPU memory.
+pub async fn run_python_resolved(app: &tauri::AppHandle, args: &[&str]) -> Result<String, BackendError> {
+    let run = async {
+        let launcher = resolve_launcher(app).await?;
+        let mut attempts = Vec::new();
+        let Some(output) = launch(app, &launcher, owned(args), &[], &mut attempts).await else {
+            return Err(not_found(app, attempts));
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(BackendError::script_failed(output.status.code(), stderr, &stdout));
+        }
+        Ok(stdout)
+    };
+    recorded(app, args, run).await
+}
+
+/// `run_python` with extra environment variables on the process, e.g. the
+/// `CUDA_VISIBLE_DEVICES` of a chosen GPU.
+pub async fn run_python_env(
+    app: &tauri::AppHandle,
+    args: &[&str],
+    env: &[(String, String)],
+) -> Result<String, BackendError> {
+    recorded(app, args, run_first_interpreter(app, args, env)).await
+}
+
+/// `run_python` for short calls such as dependency checks and previews: runs
+/// in the embedded interpreter when this build has one, saving the start-up
+/// of a new process, and in a subprocess otherwise.
+pub async fn run_python_quick(app: &tauri::AppHandle, args: &[&str]) -> Result<String, BackendError> {
+    if embedded_python::available(app).await {
+        return recorded(app, args, embedded_python::run(args)).await;
+    }
+    run_python(app, args).await
+}
+
+/// `run_python_quick` without a job record, for probes polled every few
+/// seconds such as GPU monitoring, which would otherwise crowd real jobs out
+/// of the job list and history.
+pub async fn run_python_unrecorded(app: &tauri::AppHandle, args: &[&str]) -> Result<String, BackendError> {
+    if embedded_python::available(app).await {
+        return embedded_python::run(args).await;
+    }
+    run_first_interpreter(app, args, &[]).await
+}
+
+/// Parses the JSON a script printed as its result into `T`. Scripts print the
+/// result last, so with streamed output any log lines before it are skipped.
+/// A result that does not match `T` is reported here rather than in the UI.
+pub fn parse_output<T: serde::de::DeserializeOwned>(script: &str, output: &str) -> Result<T, BackendError> {
+    let line = output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('{'))
+        .ok_or_else(|| format!("{} printed no result.", script))?;
+    serde_json::from_str(line).map_err(|e| format!("Unexpected {} output: {}", script, e).into())
+}
+
+async fn run_first_interpreter(
+    app: &tauri::AppHandle,
+    args: &[&str],
+    env: &[(String, String)],
+) -> Result<String, BackendError> {
+    // Try the selected conda environment, known install locations (macOS), then `python` and alternatives including the Windows Python Launcher `py`
+    let mut attempts = Vec::new();
+    // A script error from an interpreter that ran beats "not found" from later ones.
+    let mut failed: Option<BackendError> = None;
+
+    let mut launchers: Vec<PythonLauncher> = conda_launcher(app).await.into_iter().collect();
+    launchers.extend(interpreter_candidates(app).into_iter().map(PythonLauncher::interpreter));
+    let sidecar = sidecar_path();
+    for launcher in launchers {
+        if is_xcode_stub(&launcher.program) {
+            attempts.push(attempt(&launcher.program, 1, LaunchOutcome::Skipped, Some(xcode_stub_error(app))));
+            continue;
+        }
+        // Conda environments and the bundled backend run as they are; every
+        // other interpreter gets the version and 64-bit check first.
+        let checked = launcher.args.is_empty() && sidecar.as_deref() != Some(launcher.program.as_str());
+        let launcher = if checked {
+            match resolve_candidate(app, &launcher.program, &mut attempts).await {
+                Some(executable) => PythonLauncher::interpreter(executable),
+                None => continue,
+            }
+        } else {
+            launcher
+        };
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        if let Some(output) = launch(app, &launcher, args, env, &mut attempts).await {
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            if output.status.success() {
+                return Ok(stdout);
+            }
+            // Record error to return if ALL commands fail
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            failed = Some(BackendError::script_failed(output.status.code(), stderr, &stdout));
+        }
+    }
+    Err(failed.unwrap_or_else(|| not_found(app, attempts)))
+}
+
+/// Like `run_python`, but publishes every output line as it arrives instead of
+/// only returning at exit, so long-running scripts show live logs. The process
+/// is tracked as job `job_id`, which no queued or running job may already
+/// have, and can be cancelled. Returns the collected stdout on success and the
+/// last stderr lines on failure.
+pub async fn run_python_streaming(app: &tauri::AppHandle, job_id: &str, args: &[&str]) -> Result<String, BackendError> {
+    run_python_observed(app, job_id, args, |_| {}).await
+}
+
+/// `run_python_streaming` that also hands each stdout line to `on_line` as it
+/// arrives, e.g. to turn a script's progress lines into events.
+pub async fn run_python_observed(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    args: &[&str],
+    mut on_line: impl FnMut(&str) + Send,
+) -> Result<String, BackendError> {
+    let launcher = resolve_launcher(app).await;
+    stream_output(app, launcher, job_id, args, &[], &mut on_line).await
+}
+
+/// `run_python_observed` with extra environment variables on the process.
+pub async fn run_python_observed_env(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    args: &[&str],
+    env: &[(String, String)],
+    mut on_line: impl FnMut(&str) + Send,
+) -> Result<String, BackendError> {
+    let launcher = resolve_launcher(app).await;
+    stream_output(app, launcher, job_id, args, env, &mut on_line).await
+}
+
+/// `run_python_streaming` with a given interpreter instead of the configured
+/// one, e.g. to install packages into the managed venv.
+pub async fn run_interpreter_streaming(
+    app: &tauri::AppHandle,
+    interpreter: &str,
+    job_id: &str,
+    args: &[&str],
+) -> Result<String, BackendError> {
+    stream_output(app, Ok(PythonLauncher::interpreter(interpreter.to_string())), job_id, args, &[], &mut |_| {}).await
+}
+
+async fn stream_output(
+    app: &tauri::AppHandle,
+    launcher: Result<PythonLauncher, BackendError>,
+    job_id: &str,
+    args: &[&str],
+    env: &[(String, String)],
+    on_line: &mut (dyn FnMut(&str) + Send),
+) -> Result<String, BackendError> {
+    let jobs = app.try_state::<Arc<JobManager>>().map(|jobs| jobs.inner().clone());
+    if let Some(jobs) = &jobs {
+        jobs.queue_unique(job_id, &script_kind(args), &owned(args))?;
+    }
+    let spawned = match launcher {
+        Ok(launcher) => app
+            .shell()
+            .command(&launcher.program)
+            .args(launcher.with_args(args.iter().copied()))
+            .envs(job_env::merged(app, env.to_vec()))
+            .spawn()
+            .map_err(|e| {
+                BackendError::python_not_found(i18n::localizer(app).text(
+                    "error-python-start",
+                    &[("python", launcher.display().into()), ("error", e.to_string().into())],
+                ))
+            }),
+        Err(e) => Err(e),
+    };
+    let (mut rx, child) = match spawned {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            if let Some(jobs) = &jobs {
+                jobs.finish(job_id, None, Some(e.to_string()));
+            }
+            return Err(e);
+        }
+    };
+    if let Some(jobs) = &jobs {
+        jobs.track(job_id, child);
+    }
+
+    let mut stdout = String::new();
+    let mut stderr = std::collections::VecDeque::new();
+    let mut code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(bytes) => {
+                let line = String::from_utf8_lossy(&bytes).trim_end().to_string();
+                stdout.push_str(&line);
+                stdout.push('\n');
+                if let Some(jobs) = &jobs {
+                    jobs.note_output(job_id, &line);
+                }
+                on_line(&line);
+                events::publish(app, "python://stdout", Some(job_id), PythonOutput { line });
+            }
+            CommandEvent::Stderr(bytes) => {
+                let line = String::from_utf8_lossy(&bytes).trim_end().to_string();
+                if stderr.len() == STDERR_TAIL {
+                    stderr.pop_front();
+                }
+                stderr.push_back(line.clone());
+                if let Some(jobs) = &jobs {
+                    jobs.note_output(job_id, &line);
+                }
+                events::publish(app, "python://stderr", Some(job_id), PythonOutput { line });
+            }
+            CommandEvent::Terminated(payload) => {
+                code = payload.code;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let result = if code == Some(0) {
+        Ok(stdout)
+    } else {
+        let tail: Vec<String> = stderr.into_iter().collect();
+        Err(BackendError::script_failed(code, tail.join("\n"), ""))
+    };
+    if jobs.is_some_and(|jobs| jobs.finish(job_id, code, result.as_ref().err().map(BackendError::to_string))) {
+        return Err(format!("Job {} was cancelled.", job_id).into());
+    }
+    result
+}
+
+/// Job kind of a script call: the script's name without `.py`, or the module
+/// run with `-m`.
+fn script_kind(args: &[&str]) -> String {
+    if let ["-m", module, ..] = args {
+        return module.to_string();
+    }
+    args.iter()
+        .map(Path::new)
+        .find(|path| path.extension().is_some_and(|ext| ext == "py"))
+        .and_then(|path| path.file_stem())
+        .map_or_else(|| "python".to_string(), |stem| stem.to_string_lossy().to_string())
+}
+
+fn owned(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
@@ -0,0 +1,81 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::jobs::unix_now;
+use crate::paths;
+
+/// Entries kept per kind; older ones drop off.
+const MAX_PER_KIND: usize = 15;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentKind {
+    Dataset,
+    Run,
+    Table,
+    Project,
+    Model,
+}
+
+/// A recently opened dataset, run directory, table, project or model, kept
+/// per profile.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecentItem {
+    pub kind: RecentKind,
+    pub path: String,
+    pub opened_at: u64,
+}
+
+fn recents_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::data_file(app, "recents.json")?)
+}
+
+/// Most recent first.
+pub fn load(app: &tauri::AppHandle) -> Result<Vec<RecentItem>, String> {
+    let path = recents_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid recents file: {}", e))
+}
+
+fn save_all(app: &tauri::AppHandle, items: &[RecentItem]) -> Result<(), String> {
+    let path = recents_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(items).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| e.to_string())
+}
+
+/// Moves `path` to the top of its kind's list.
+pub fn add(app: &tauri::AppHandle, kind: RecentKind, path: &str) -> Result<Vec<RecentItem>, String> {
+    let mut items = load(app)?;
+    items.retain(|item| !(item.kind == kind && item.path == path));
+    items.insert(
+        0,
+        RecentItem {
+            kind,
+            path: path.to_string(),
+            opened_at: unix_now(),
+        },
+    );
+    let mut seen = 0;
+    items.retain(|item| {
+        if item.kind != kind {
+            return true;
+        }
+        seen += 1;
+        seen <= MAX_PER_KIND
+    });
+    save_all(app, &items)?;
+    Ok(items)
+}
+
+pub fn clear(app: &tauri::AppHandle, kind: Option<RecentKind>) -> Result<(), String> {
+    let mut items = load(app)?;
+    items.retain(|item| kind.is_some_and(|k| item.kind != k));
+    save_all(app, &items)
+}
@@ -0,0 +1,332 @@
+use minijinja::{context, Environment, Value};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::evaluation;
+use crate::gallery::{self, Misclassification};
+use crate::html;
+use crate::i18n::Localizer;
+use crate::jobs::{new_id, unix_now};
+use crate::run_manifest;
+
+const TEMPLATE: &str = include_str!("../templates/training_report.html");
+/// Misclassified images shown with thumbnails.
+const SAMPLE_COUNT: usize = 12;
+const THUMBNAIL_SIDE: u32 = 256;
+const CHART_WIDTH: f64 = 560.0;
+const CHART_HEIGHT: f64 = 240.0;
+const CHART_MARGIN: f64 = 40.0;
+/// Messages the template looks up by id.
+const MESSAGES: &[&str] = &[
+    "report-run",
+    "report-generated",
+    "report-configuration",
+    "report-classes",
+    "report-curves",
+    "report-no-curves",
+    "report-confusion-matrix",
+    "report-matrix-note",
+    "report-samples",
+    "report-samples-note",
+    "card-parameter",
+    "card-value",
+    "card-dataset",
+    "card-evaluation",
+    "card-no-evaluation",
+    "card-metric",
+    "gallery-actual",
+    "gallery-predicted",
+    "gallery-image-unavailable",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Html,
+    /// Printed from the HTML by a locally installed Chromium-based browser.
+    Pdf,
+}
+
+#[derive(Serialize)]
+struct Chart {
+    title: String,
+    svg: Value,
+}
+
+#[derive(Serialize)]
+struct MatrixRow {
+    label: String,
+    index: usize,
+    counts: Vec<usize>,
+}
+
+#[derive(Serialize)]
+struct Sample {
+    file: String,
+    actual: String,
+    predicted: String,
+    confidence: String,
+    image: Option<String>,
+}
+
+fn display(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => "—".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// An inline SVG line chart of `series`, each `(label, colour, points)`.
+fn line_chart(series: &[(String, &str, Vec<(f64, f64)>)]) -> String {
+    let points = series.iter().flat_map(|(_, _, p)| p.iter());
+    let (mut x_min, mut x_max, mut y_min, mut y_max) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for (x, y) in points {
+        x_min = x_min.min(*x);
+        x_max = x_max.max(*x);
+        y_min = y_min.min(*y);
+        y_max = y_max.max(*y);
+    }
+    if x_max <= x_min {
+        x_max = x_min + 1.0;
+    }
+    if y_max <= y_min {
+        y_max = y_min + 1.0;
+    }
+    let plot_width = CHART_WIDTH - 2.0 * CHART_MARGIN;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_MARGIN;
+    let sx = |x: f64| CHART_MARGIN + (x - x_min) / (x_max - x_min) * plot_width;
+    let sy = |y: f64| CHART_HEIGHT - CHART_MARGIN - (y - y_min) / (y_max - y_min) * plot_height;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-size=\"11\">",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    );
+    svg.push_str(&format!(
+        "<rect x=\"{m}\" y=\"{m}\" width=\"{pw}\" height=\"{ph}\" fill=\"none\" stroke=\"#d9e2ec\"/>",
+        m = CHART_MARGIN,
+        pw = plot_width,
+        ph = plot_height
+    ));
+    for (value, y) in [(y_max, sy(y_max)), (y_min, sy(y_min))] {
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"#627d98\">{:.3}</text>",
+            CHART_MARGIN - 4.0,
+            y + 4.0,
+            value
+        ));
+    }
+    for value in [x_min, x_max] {
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\" fill=\"#627d98\">{}</text>",
+            sx(value),
+            CHART_HEIGHT - CHART_MARGIN + 14.0,
+            value
+        ));
+    }
+    for (i, (label, colour, points)) in series.iter().enumerate() {
+        let path: Vec<String> = points.iter().map(|(x, y)| format!("{:.1},{:.1}", sx(*x), sy(*y))).collect();
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+            path.join(" "),
+            colour
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{}\" fill=\"{}\">{}</text>",
+            CHART_MARGIN + i as f64 * 140.0,
+            CHART_MARGIN - 10.0,
+            colour,
+            html::escape(label)
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Loss and accuracy charts from the per-epoch `metric_history` script.py
+/// keeps in the manifest.
+fn curves(manifest: &serde_json::Value, l10n: &Localizer) -> Vec<Chart> {
+    let Some(history) = manifest["metric_history"].as_array().filter(|h| !h.is_empty()) else {
+        return Vec::new();
+    };
+    let column = |key: &str| -> Vec<(f64, f64)> {
+        history.iter().filter_map(|entry| Some((entry["epoch"].as_f64()?, entry[key].as_f64()?))).collect()
+    };
+    let train = l10n.text("report-train", &[]);
+    let val = l10n.text("report-val", &[]);
+    [("report-loss", "train_loss", "val_loss"), ("report-accuracy", "train_accuracy", "val_accuracy")]
+        .into_iter()
+        .filter_map(|(title, train_key, val_key)| {
+            let series: Vec<_> =
+                [(train.clone(), "#1565c0", column(train_key)), (val.clone(), "#ef6c00", column(val_key))]
+                    .into_iter()
+                    .filter(|(_, _, points)| !points.is_empty())
+                    .collect();
+            (!series.is_empty())
+                .then(|| Chart { title: l10n.text(title, &[]), svg: Value::from_safe_string(line_chart(&series)) })
+        })
+        .collect()
+}
+
+fn samples(items: Vec<Misclassification>) -> Vec<Sample> {
+    items
+        .into_iter()
+        .take(SAMPLE_COUNT)
+        .map(|item| Sample {
+            image: html::thumbnail_data_uri(Path::new(&item.file), THUMBNAIL_SIDE),
+            confidence: format!("{:.1}%", item.confidence * 100.0),
+            file: item.file,
+            actual: item.actual,
+            predicted: item.predicted,
+        })
+        .collect()
+}
+
+/// The report as a standalone HTML page: configuration, training curves, test
+/// metrics, confusion matrix and misclassified samples of the run.
+pub fn render(run_dir: &Path, l10n: &Localizer) -> Result<String, String> {
+    let manifest = run_manifest::load(run_dir)?;
+    let name = manifest["experiment_id"]
+        .as_str()
+        .map(String::from)
+        .or_else(|| run_dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+
+    let config: Vec<(String, String)> = manifest["config"]
+        .as_object()
+        .map(|c| c.iter().map(|(k, v)| (k.clone(), display(v))).collect())
+        .unwrap_or_default();
+    let classes: Vec<String> = serde_json::from_value(manifest["classes"].clone()).unwrap_or_default();
+
+    // A later evaluate_model run supersedes the test split evaluated during training.
+    let evaluated = evaluation::load(run_dir);
+    let metrics: Vec<(String, String)> = match &evaluated {
+        Some(report) => vec![
+            ("accuracy".to_string(), format!("{:.4}", report.accuracy)),
+            ("macro_f1".to_string(), format!("{:.4}", report.macro_f1)),
+            ("weighted_f1".to_string(), format!("{:.4}", report.weighted_f1)),
+        ],
+        None => ["metrics", "custom_metrics"]
+            .iter()
+            .filter_map(|key| manifest["evaluation"][key].as_object())
+            .flatten()
+            .filter_map(|(k, v)| v.as_f64().map(|v| (k.clone(), format!("{:.4}", v))))
+            .collect(),
+    };
+    let matrix = evaluated.as_ref().map(|report| {
+        let rows: Vec<MatrixRow> = report
+            .classes
+            .iter()
+            .zip(&report.confusion_matrix)
+            .enumerate()
+            .map(|(index, (label, counts))| MatrixRow { label: label.clone(), index, counts: counts.clone() })
+            .collect();
+        context! { classes => &report.classes, rows => rows }
+    });
+    // Runs only evaluated during training have the heatmap script.py plotted.
+    let matrix_image = match matrix {
+        Some(_) => None,
+        None => html::thumbnail_data_uri(&run_dir.join("confusion_matrix.png"), 900),
+    };
+    let misclassified = match evaluated {
+        Some(report) => report.misclassified,
+        None => gallery::load(run_dir).unwrap_or_default(),
+    };
+
+    let t: BTreeMap<&str, String> = MESSAGES.iter().map(|id| (*id, l10n.text(id, &[]))).collect();
+    let mut env = Environment::new();
+    env.add_template("training_report.html", TEMPLATE).map_err(|e| e.to_string())?;
+    let body = env
+        .get_template("training_report.html")
+        .and_then(|template| {
+            template.render(context! {
+                t => t,
+                run_dir => run_dir.to_string_lossy(),
+                generated_at => l10n.text("card-generated-value", &[("time", unix_now().to_string().into())]),
+                config => config,
+                dataset => manifest["dataset"]["path"].as_str(),
+                classes => classes,
+                curves => curves(&manifest, l10n),
+                metrics => metrics,
+                matrix => matrix,
+                matrix_image => matrix_image,
+                samples => samples(misclassified),
+            })
+        })
+        .map_err(|e| format!("Cannot render the report: {}", e))?;
+    let title = l10n.text("report-title", &[("name", name.into())]);
+    Ok(html::page(&l10n.language(), &title, &body))
+}
+
+/// Chromium-based browsers that can print a page to PDF headlessly, by the
+/// names or paths they are usually installed under.
+fn pdf_browsers() -> Vec<PathBuf> {
+    if cfg!(target_os = "windows") {
+        vec![
+            PathBuf::from(r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe"),
+            PathBuf::from(r"C:\Program Files\Microsoft\Edge\Application\msedge.exe"),
+            PathBuf::from(r"C:\Program Files\Google\Chrome\Application\chrome.exe"),
+        ]
+    } else if cfg!(target_os = "macos") {
+        vec![
+            PathBuf::from("/Applications/Google Chrome.app/Contents/MacOS/Google Chrome"),
+            PathBuf::from("/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge"),
+            PathBuf::from("/Applications/Chromium.app/Contents/MacOS/Chromium"),
+        ]
+    } else {
+        let dirs: Vec<PathBuf> = std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()).collect();
+        ["google-chrome", "chromium", "chromium-browser", "microsoft-edge"]
+            .iter()
+            .flat_map(|name| dirs.iter().map(move |dir| dir.join(name)))
+            .collect()
+    }
+}
+
+fn print_pdf(html: &str, out_path: &Path, l10n: &Localizer) -> Result<(), String> {
+    let browser =
+        pdf_browsers().into_iter().find(|p| p.is_file()).ok_or_else(|| l10n.text("report-pdf-browser", &[]))?;
+    let page = std::env::temp_dir().join(format!("{}.html", new_id("report")));
+    std::fs::write(&page, html).map_err(|e| format!("Cannot write {}: {}", page.display(), e))?;
+    let output = std::process::Command::new(&browser)
+        .arg("--headless")
+        .arg("--disable-gpu")
+        .arg("--no-pdf-header-footer")
+        .arg(format!("--print-to-pdf={}", out_path.display()))
+        .arg(&page)
+        .output();
+    let _ = std::fs::remove_file(&page);
+    let output = output.map_err(|e| format!("Cannot start {}: {}", browser.display(), e))?;
+    if !output.status.success() || !out_path.is_file() {
+        return Err(format!(
+            "{} could not print the report: {}",
+            browser.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the report of `run_dir` to `out_path` as HTML or PDF. Returns the
+/// path written.
+pub fn export(run_dir: &Path, format: ReportFormat, out_path: &Path, l10n: &Localizer) -> Result<String, String> {
+    if !run_dir.join(run_manifest::FILE_NAME).is_file() {
+        return Err(format!("{} is not a training run.", run_dir.display()));
+    }
+    let html = render(run_dir, l10n)?;
+    if let Some(dir) = out_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    match format {
+        ReportFormat::Html => std::fs::write(out_path, html).map_err(|e| {
+            l10n.text(
+                "error-write-file",
+                &[("path", out_path.display().to_string().into()), ("error", e.to_string().into())],
+            )
+        })?,
+        ReportFormat::Pdf => print_pdf(&html, out_path, l10n)?,
+    }
+    Ok(out_path.to_string_lossy().to_string())
+}
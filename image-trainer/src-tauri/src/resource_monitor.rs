@@ -0,0 +1,122 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+
+use crate::events;
+use crate::jobs::unix_now;
+
+pub const DEFAULT_INTERVAL_MS: u64 = 1_000;
+const MIN_INTERVAL_MS: u64 = 250;
+const MAX_INTERVAL_MS: u64 = 60_000;
+const MB: u64 = 1024 * 1024;
+
+/// One reading, published as `system://resources`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ResourceSample {
+    pub timestamp: u64,
+    /// Averaged over all cores since the previous reading.
+    pub cpu_percent: f64,
+    /// Per core; a few cores pinned at 100% while the rest idle points at too few dataloader workers.
+    pub cpu_per_core: Vec<f64>,
+    pub memory_used_mb: u64,
+    pub memory_total_mb: u64,
+    pub swap_used_mb: u64,
+    pub swap_total_mb: u64,
+    /// Bytes per second read and written by all processes since the previous reading.
+    pub disk_read_bytes_per_sec: u64,
+    pub disk_write_bytes_per_sec: u64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ResourceMonitorStatus {
+    pub running: bool,
+    pub interval_ms: u64,
+}
+
+/// Managed state: the stop flag and interval of the running monitor, if any.
+#[derive(Default)]
+pub struct ResourceMonitor {
+    running: Mutex<Option<(Arc<AtomicBool>, u64)>>,
+}
+
+fn sample(system: &mut System, elapsed: Duration) -> ResourceSample {
+    system.refresh_cpu_usage();
+    system.refresh_memory();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::new().with_disk_usage());
+    let (read, written) = system
+        .processes()
+        .values()
+        .map(|p| p.disk_usage())
+        .fold((0u64, 0u64), |(r, w), usage| (r + usage.read_bytes, w + usage.written_bytes));
+    let per_second = |bytes: u64| (bytes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+    ResourceSample {
+        timestamp: unix_now(),
+        cpu_percent: system.global_cpu_usage() as f64,
+        cpu_per_core: system.cpus().iter().map(|cpu| cpu.cpu_usage() as f64).collect(),
+        memory_used_mb: system.used_memory() / MB,
+        memory_total_mb: system.total_memory() / MB,
+        swap_used_mb: system.used_swap() / MB,
+        swap_total_mb: system.total_swap() / MB,
+        disk_read_bytes_per_sec: per_second(read),
+        disk_write_bytes_per_sec: per_second(written),
+    }
+}
+
+impl ResourceMonitor {
+    pub fn status(&self) -> ResourceMonitorStatus {
+        match self.running.lock().unwrap().as_ref() {
+            Some((_, interval_ms)) => ResourceMonitorStatus { running: true, interval_ms: *interval_ms },
+            None => ResourceMonitorStatus { running: false, interval_ms: DEFAULT_INTERVAL_MS },
+        }
+    }
+
+    /// Publishes a `system://resources` reading every `interval_ms` until
+    /// stopped. Starting again replaces the running monitor.
+    pub fn start(&self, app: &tauri::AppHandle, interval_ms: Option<u64>) -> Result<ResourceMonitorStatus, String> {
+        let interval_ms = interval_ms.unwrap_or(DEFAULT_INTERVAL_MS);
+        if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&interval_ms) {
+            return Err(format!("The interval must be between {} and {} ms.", MIN_INTERVAL_MS, MAX_INTERVAL_MS));
+        }
+        self.stop();
+        let stop = Arc::new(AtomicBool::new(false));
+        *self.running.lock().unwrap() = Some((stop.clone(), interval_ms));
+
+        let app = app.clone();
+        std::thread::Builder::new()
+            .name("resource-monitor".to_string())
+            .spawn(move || {
+                let mut system = System::new_with_specifics(
+                    RefreshKind::new()
+                        .with_cpu(CpuRefreshKind::new().with_cpu_usage())
+                        .with_memory(MemoryRefreshKind::everything()),
+                );
+                // CPU usage and disk counters are deltas, so the first reading only sets the baseline.
+                system.refresh_processes_specifics(
+                    ProcessesToUpdate::All,
+                    true,
+                    ProcessRefreshKind::new().with_disk_usage(),
+                );
+                let mut last = Instant::now();
+                loop {
+                    std::thread::sleep(Duration::from_millis(interval_ms));
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let reading = sample(&mut system, last.elapsed());
+                    last = Instant::now();
+                    events::publish(&app, "system://resources", None, reading);
+                }
+            })
+            .map_err(|e| format!("Cannot start the resource monitor: {}", e))?;
+        Ok(ResourceMonitorStatus { running: true, interval_ms })
+    }
+
+    pub fn stop(&self) {
+        if let Some((stop, _)) = self.running.lock().unwrap().take() {
+            stop.store(true, Ordering::SeqCst);
+        }
+    }
+}
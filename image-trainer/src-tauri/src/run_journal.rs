@@ -0,0 +1,127 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::jobs::unix_now;
+use crate::paths;
+use crate::training::TrainingConfig;
+
+/// Saved by script.py after every epoch, and when asked to stop.
+pub const CHECKPOINT_FILE: &str = "checkpoint.pth";
+
+/// A training run that has not finished successfully, with the config it was
+/// started with so it can be resumed.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UnfinishedRun {
+    pub job_id: String,
+    /// The prepared config, with its seed and custom model resolved.
+    pub config: TrainingConfig,
+    /// Run directory holding the checkpoint.
+    pub output_dir: String,
+    pub started_at: u64,
+    /// Null while running, and when the app closed before the job exited.
+    pub exit_code: Option<i32>,
+    pub ended_at: Option<u64>,
+    /// The checkpoint to resume from; null when no epoch completed.
+    #[serde(skip_deserializing)]
+    pub checkpoint: Option<String>,
+}
+
+impl UnfinishedRun {
+    fn with_checkpoint(mut self) -> Self {
+        let checkpoint = Path::new(&self.output_dir).join(CHECKPOINT_FILE);
+        self.checkpoint = checkpoint.is_file().then(|| checkpoint.to_string_lossy().to_string());
+        self
+    }
+}
+
+/// Training runs in progress, journaled to `unfinished_runs.json` in the data
+/// folder. Entries are dropped when a run succeeds or is cancelled, so what
+/// is left after a restart was cut short by a crash, a stop or the app closing.
+pub struct RunJournal {
+    path: PathBuf,
+    runs: Mutex<BTreeMap<String, UnfinishedRun>>,
+}
+
+impl RunJournal {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let path = paths::data_file(app, "unfinished_runs.json")?;
+        let runs: BTreeMap<String, UnfinishedRun> =
+            std::fs::read_to_string(&path).ok().and_then(|raw| serde_json::from_str(&raw).ok()).unwrap_or_default();
+        for run in runs.values().filter(|run| run.exit_code.is_none()) {
+            tracing::info!(job_id = %run.job_id, output_dir = %run.output_dir, "Training run was interrupted");
+        }
+        Ok(Self { path, runs: Mutex::new(runs) })
+    }
+
+    fn save(&self, runs: &BTreeMap<String, UnfinishedRun>) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(runs).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| e.to_string())
+    }
+
+    pub fn record(&self, job_id: &str, config: &TrainingConfig) -> Result<(), String> {
+        let mut runs = self.runs.lock().unwrap();
+        runs.insert(
+            job_id.to_string(),
+            UnfinishedRun {
+                job_id: job_id.to_string(),
+                config: config.clone(),
+                output_dir: config.output_dir().to_string_lossy().to_string(),
+                started_at: unix_now(),
+                exit_code: None,
+                ended_at: None,
+                checkpoint: None,
+            },
+        );
+        self.save(&runs)
+    }
+
+    /// Forgets runs that succeeded or were cancelled; keeps the others resumable.
+    pub fn finish(&self, job_id: &str, code: Option<i32>, cancelled: bool) -> Result<(), String> {
+        let mut runs = self.runs.lock().unwrap();
+        if code == Some(0) || cancelled {
+            if runs.remove(job_id).is_none() {
+                return Ok(());
+            }
+        } else if let Some(run) = runs.get_mut(job_id) {
+            // Killed processes have no exit code; -1 still marks the run as ended.
+            run.exit_code = Some(code.unwrap_or(-1));
+            run.ended_at = Some(unix_now());
+        } else {
+            return Ok(());
+        }
+        self.save(&runs)
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<UnfinishedRun> {
+        self.runs.lock().unwrap().get(job_id).cloned().map(UnfinishedRun::with_checkpoint)
+    }
+
+    /// Runs that did not finish, newest first, skipping `running` jobs.
+    pub fn list(&self, running: impl Fn(&str) -> bool) -> Vec<UnfinishedRun> {
+        let mut runs: Vec<UnfinishedRun> = self
+            .runs
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|run| !running(&run.job_id))
+            .cloned()
+            .map(UnfinishedRun::with_checkpoint)
+            .collect();
+        runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        runs
+    }
+
+    pub fn remove(&self, job_id: &str) -> Result<(), String> {
+        let mut runs = self.runs.lock().unwrap();
+        if runs.remove(job_id).is_some() {
+            self.save(&runs)?;
+        }
+        Ok(())
+    }
+}
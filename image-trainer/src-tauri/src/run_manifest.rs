@@ -0,0 +1,15 @@
+use std::path::Path;
+
+use crate::error::BackendError;
+
+/// File a training run writes its config, dataset, architecture and
+/// evaluation into, next to its weights.
+pub const FILE_NAME: &str = "run_manifest.json";
+
+/// Reads and parses the manifest of the run in `run_dir`.
+pub fn load(run_dir: &Path) -> Result<serde_json::Value, BackendError> {
+    let path = run_dir.join(FILE_NAME);
+    let raw = std::fs::read_to_string(&path)
+        .map_err(|e| BackendError::io(format!("Cannot read {}: {}", path.display(), e)))?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid run manifest {}: {}", path.display(), e).into())
+}
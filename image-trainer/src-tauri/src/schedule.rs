@@ -0,0 +1,204 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::events;
+use crate::jobs::{self, new_id, unix_now, JobManager};
+use crate::paths;
+use crate::training::TrainingConfig;
+
+/// How often pending schedules are checked.
+const TICK: Duration = Duration::from_secs(30);
+
+/// When a scheduled job should start.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Trigger {
+    /// Start at a wall-clock time (unix seconds). If the app was closed or the
+    /// machine asleep at that time, the job starts as soon as it is noticed,
+    /// unless it is later than `max_delay_secs`.
+    At {
+        at: u64,
+        #[serde(default)]
+        max_delay_secs: Option<u64>,
+    },
+    /// Start once there has been no keyboard/mouse input for `idle_minutes`.
+    Idle { idle_minutes: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScheduledJob {
+    pub id: String,
+    pub config: TrainingConfig,
+    pub trigger: Trigger,
+    pub created_at: u64,
+}
+
+/// Published as `schedule://started` (with `job_id`) or `schedule://skipped`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleEvent {
+    pub schedule_id: String,
+    pub job_id: Option<String>,
+    /// Seconds between the requested time and the actual start (catch-up).
+    pub late_by_secs: u64,
+    pub message: String,
+}
+
+/// Pending scheduled jobs, persisted to `scheduled_jobs.json` in the app data dir.
+pub struct Scheduler {
+    pending: Mutex<Vec<ScheduledJob>>,
+    path: PathBuf,
+}
+
+impl Scheduler {
+    /// Loads pending schedules left over from a previous session.
+    pub fn load(path: PathBuf) -> Self {
+        let pending = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            pending: Mutex::new(pending),
+            path,
+        }
+    }
+
+    fn save(&self, pending: &[ScheduledJob]) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_string_pretty(pending).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| format!("Failed to save schedules: {}", e))
+    }
+
+    pub fn add(&self, mut config: TrainingConfig, trigger: Trigger) -> Result<ScheduledJob, String> {
+        if let Trigger::Idle { idle_minutes: 0 } = trigger {
+            return Err("Idle trigger needs at least one minute.".to_string());
+        }
+        // Validate now so a bad config fails while the user is still at the machine.
+        config.prepare()?;
+        config.validate(None)?;
+
+        let job = ScheduledJob {
+            id: new_id("sched"),
+            config,
+            trigger,
+            created_at: unix_now(),
+        };
+        let mut pending = self.pending.lock().unwrap();
+        pending.push(job.clone());
+        self.save(&pending)?;
+        Ok(job)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        let mut pending = self.pending.lock().unwrap();
+        let before = pending.len();
+        pending.retain(|job| job.id != id);
+        if pending.len() == before {
+            return Err(format!("No scheduled job with id {}", id));
+        }
+        self.save(&pending)
+    }
+
+    pub fn list(&self) -> Vec<ScheduledJob> {
+        self.pending.lock().unwrap().clone()
+    }
+
+    /// Removes and returns jobs whose trigger fired, split into (start, skip).
+    fn take_due(&self, now: u64, idle_secs: Option<u64>) -> (Vec<(ScheduledJob, u64)>, Vec<(ScheduledJob, u64)>) {
+        let mut pending = self.pending.lock().unwrap();
+        let mut start = Vec::new();
+        let mut skip = Vec::new();
+        let mut keep = Vec::new();
+
+        for job in pending.drain(..) {
+            match job.trigger {
+                Trigger::At { at, max_delay_secs } if now >= at => {
+                    let late = now - at;
+                    if max_delay_secs.is_some_and(|max| late > max) {
+                        skip.push((job, late));
+                    } else {
+                        start.push((job, late));
+                    }
+                }
+                Trigger::Idle { idle_minutes } if idle_secs.is_some_and(|idle| idle >= idle_minutes as u64 * 60) => {
+                    start.push((job, 0));
+                }
+                _ => keep.push(job),
+            }
+        }
+
+        *pending = keep;
+        if !start.is_empty() || !skip.is_empty() {
+            let _ = self.save(&pending);
+        }
+        (start, skip)
+    }
+}
+
+pub fn schedule_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::data_file(app, "scheduled_jobs.json")?)
+}
+
+/// Seconds since the last user input, when the platform can report it.
+fn user_idle_secs() -> Option<u64> {
+    user_idle::UserIdle::get_time().ok().map(|idle| idle.as_seconds())
+}
+
+/// Background loop that starts due jobs. Because it compares against wall-clock
+/// time on every tick, a schedule missed during sleep fires on the first tick
+/// after wake-up.
+pub fn spawn_ticker(app: tauri::AppHandle, scheduler: Arc<Scheduler>, jobs: Arc<JobManager>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (start, skip) = scheduler.take_due(unix_now(), user_idle_secs());
+
+            for (job, late) in skip {
+                events::publish(
+                    &app,
+                    "schedule://skipped",
+                    None,
+                    ScheduleEvent {
+                        schedule_id: job.id,
+                        job_id: None,
+                        late_by_secs: late,
+                        message: format!("Skipped: start time was missed by {} seconds.", late),
+                    },
+                );
+            }
+
+            for (job, late) in start {
+                let event = match jobs::start_training(&app, &jobs, job.config).await {
+                    Ok(job_id) => ScheduleEvent {
+                        schedule_id: job.id,
+                        job_id: Some(job_id),
+                        late_by_secs: late,
+                        message: if late > TICK.as_secs() {
+                            format!("Started {} seconds late (catch-up after a missed start time).", late)
+                        } else {
+                            "Started on schedule.".to_string()
+                        },
+                    },
+                    Err(e) => ScheduleEvent {
+                        schedule_id: job.id,
+                        job_id: None,
+                        late_by_secs: late,
+                        message: format!("Failed to start scheduled job: {}", e),
+                    },
+                };
+                let name = if event.job_id.is_some() {
+                    "schedule://started"
+                } else {
+                    "schedule://skipped"
+                };
+                let job_id = event.job_id.clone();
+                events::publish(&app, name, job_id.as_deref(), event);
+            }
+
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
@@ -0,0 +1,404 @@
+use rand::RngCore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::jobs::{new_id, unix_now};
+use crate::paths;
+
+/// Keyring service under which API keys are stored, one entry per key id.
+const KEYRING_SERVICE: &str = "epoq-server";
+/// Characters of a key shown in listings so users can tell keys apart.
+const VISIBLE_PREFIX: usize = 12;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerBinding {
+    /// Only this machine can connect.
+    #[default]
+    Localhost,
+    /// Other machines on the network can connect, limited by `allowed_clients`.
+    Lan,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ServerSecuritySettings {
+    pub binding: ServerBinding,
+    /// Client addresses or CIDR ranges (`192.168.1.0/24`) allowed in LAN
+    /// mode; empty allows any client that presents a valid key.
+    pub allowed_clients: Vec<String>,
+    /// Serve HTTPS with the self-signed certificate.
+    pub tls: bool,
+}
+
+/// An API key without its secret.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub label: String,
+    /// Start of the key, for telling keys apart.
+    pub prefix: String,
+    pub created_at: u64,
+    pub rotated_at: Option<u64>,
+}
+
+/// A new or rotated key. The secret is only ever returned here.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IssuedApiKey {
+    pub info: ApiKeyInfo,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TlsCertificateInfo {
+    pub cert_path: String,
+    pub key_path: String,
+    /// SHA-256 of the DER certificate, for pinning in clients.
+    pub fingerprint_sha256: String,
+    pub subject_alt_names: Vec<String>,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ServerSecurityStatus {
+    pub settings: ServerSecuritySettings,
+    pub keys: Vec<ApiKeyInfo>,
+    pub certificate: Option<TlsCertificateInfo>,
+}
+
+/// Parses an address or CIDR range into network address and prefix length.
+pub fn parse_network(value: &str) -> Result<(IpAddr, u8), String> {
+    let (address, prefix) = match value.trim().split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (value.trim(), None),
+    };
+    let address: IpAddr = address
+        .parse()
+        .map_err(|_| format!("'{}' is not an IP address or CIDR range.", value))?;
+    let max = if address.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(p) => p
+            .parse::<u8>()
+            .ok()
+            .filter(|p| *p <= max)
+            .ok_or_else(|| format!("'{}' has an invalid prefix length.", value))?,
+        None => max,
+    };
+    // Clients are compared in canonical form, so IPv4-mapped ranges become IPv4 ones.
+    match address {
+        IpAddr::V6(v6) if prefix >= 96 => match v6.to_ipv4_mapped() {
+            Some(v4) => Ok((IpAddr::V4(v4), prefix - 96)),
+            None => Ok((address, prefix)),
+        },
+        _ => Ok((address, prefix)),
+    }
+}
+
+/// Whether `address` lies in the network `(network, prefix)`.
+fn in_network(address: IpAddr, (network, prefix): (IpAddr, u8)) -> bool {
+    let bits = |ip: IpAddr| match ip {
+        IpAddr::V4(v4) => (u128::from(u32::from(v4)) << 96, 32u8),
+        IpAddr::V6(v6) => (u128::from(v6), 128u8),
+    };
+    let ((a, a_len), (n, n_len)) = (bits(address.to_canonical()), bits(network));
+    if a_len != n_len {
+        return false;
+    }
+    // IPv4 addresses sit in the top 32 bits, so the mask works for both.
+    let mask = if prefix == 0 { 0 } else { u128::MAX << (128 - u32::from(prefix)) };
+    a & mask == n & mask
+}
+
+/// Compares secrets in time independent of where they differ.
+pub fn secrets_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn keyring_entry(id: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, id).map_err(|e| format!("Keyring unavailable: {}", e))
+}
+
+fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("epoq_{}", hex::encode(bytes))
+}
+
+/// Managed state: server security settings (`server_security.json`), the
+/// list of API keys (`server_keys.json`, secrets in the OS keyring) and the
+/// self-signed certificate in `server_tls/`.
+pub struct ServerSecurity {
+    settings: Mutex<ServerSecuritySettings>,
+    settings_path: PathBuf,
+    keys_path: PathBuf,
+    tls_dir: PathBuf,
+}
+
+impl ServerSecurity {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let settings_path = paths::settings_file(app, "server_security.json")?;
+        let settings = std::fs::read_to_string(&settings_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            settings: Mutex::new(settings),
+            settings_path,
+            keys_path: paths::settings_file(app, "server_keys.json")?,
+            tls_dir: paths::settings_file(app, "server_tls")?,
+        })
+    }
+
+    pub fn settings(&self) -> ServerSecuritySettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set_settings(&self, mut settings: ServerSecuritySettings) -> Result<(), String> {
+        for client in &settings.allowed_clients {
+            parse_network(client)?;
+        }
+        settings.allowed_clients = settings.allowed_clients.iter().map(|c| c.trim().to_string()).collect();
+        if settings.tls && self.certificate().is_none() {
+            self.regenerate_certificate()?;
+        }
+        write_json(&self.settings_path, &settings)?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Address servers bind to for the configured binding.
+    pub fn bind_host(&self) -> IpAddr {
+        match self.settings().binding {
+            ServerBinding::Localhost => IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+            ServerBinding::Lan => IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+        }
+    }
+
+    /// Whether a client at `address` may connect: this machine always, others
+    /// only in LAN mode and within `allowed_clients` when it is set.
+    pub fn allows(&self, address: IpAddr) -> bool {
+        if address.to_canonical().is_loopback() {
+            return true;
+        }
+        let settings = self.settings();
+        if settings.binding != ServerBinding::Lan {
+            return false;
+        }
+        settings.allowed_clients.is_empty()
+            || settings.allowed_clients.iter().filter_map(|c| parse_network(c).ok()).any(|net| in_network(address, net))
+    }
+
+    /// The API key `presented` belongs to, checked against the keyring.
+    pub fn authenticate(&self, presented: &str) -> Option<ApiKeyInfo> {
+        self.keys().into_iter().filter(|k| presented.starts_with(&k.prefix)).find(|k| {
+            keyring_entry(&k.id)
+                .and_then(|entry| entry.get_password().map_err(|e| e.to_string()))
+                .is_ok_and(|secret| secrets_match(&secret, presented))
+        })
+    }
+
+    pub fn status(&self) -> ServerSecurityStatus {
+        ServerSecurityStatus {
+            settings: self.settings(),
+            keys: self.keys(),
+            certificate: self.certificate(),
+        }
+    }
+
+    pub fn keys(&self) -> Vec<ApiKeyInfo> {
+        std::fs::read_to_string(&self.keys_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_keys(&self, keys: &[ApiKeyInfo]) -> Result<(), String> {
+        write_json(&self.keys_path, &keys)
+    }
+
+    /// Generates a key and stores it in the keyring. The secret is returned
+    /// once and cannot be read back through the app.
+    pub fn create_key(&self, label: &str) -> Result<IssuedApiKey, String> {
+        if label.trim().is_empty() {
+            return Err("Give the key a label, e.g. the client that uses it.".to_string());
+        }
+        let key = generate_key();
+        let info = ApiKeyInfo {
+            id: new_id("key"),
+            label: label.trim().to_string(),
+            prefix: key[..VISIBLE_PREFIX].to_string(),
+            created_at: unix_now(),
+            rotated_at: None,
+        };
+        keyring_entry(&info.id)?
+            .set_password(&key)
+            .map_err(|e| format!("Cannot store the key in the keyring: {}", e))?;
+        let mut keys = self.keys();
+        keys.push(info.clone());
+        self.save_keys(&keys)?;
+        Ok(IssuedApiKey { info, key })
+    }
+
+    /// Replaces a key's secret; clients using the old one are rejected from now on.
+    pub fn rotate_key(&self, id: &str) -> Result<IssuedApiKey, String> {
+        let mut keys = self.keys();
+        let info = keys
+            .iter_mut()
+            .find(|k| k.id == id)
+            .ok_or_else(|| format!("Unknown API key {}.", id))?;
+        let key = generate_key();
+        keyring_entry(id)?
+            .set_password(&key)
+            .map_err(|e| format!("Cannot store the key in the keyring: {}", e))?;
+        info.prefix = key[..VISIBLE_PREFIX].to_string();
+        info.rotated_at = Some(unix_now());
+        let info = info.clone();
+        self.save_keys(&keys)?;
+        Ok(IssuedApiKey { info, key })
+    }
+
+    pub fn revoke_key(&self, id: &str) -> Result<(), String> {
+        let mut keys = self.keys();
+        let before = keys.len();
+        keys.retain(|k| k.id != id);
+        if keys.len() == before {
+            return Err(format!("Unknown API key {}.", id));
+        }
+        match keyring_entry(id)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(format!("Cannot remove the key from the keyring: {}", e)),
+        }
+        self.save_keys(&keys)
+    }
+
+    fn cert_paths(&self) -> (PathBuf, PathBuf, PathBuf) {
+        (
+            self.tls_dir.join("cert.pem"),
+            self.tls_dir.join("key.pem"),
+            self.tls_dir.join("certificate.json"),
+        )
+    }
+
+    pub fn certificate(&self) -> Option<TlsCertificateInfo> {
+        let (cert, key, info) = self.cert_paths();
+        if !cert.is_file() || !key.is_file() {
+            return None;
+        }
+        serde_json::from_str(&std::fs::read_to_string(info).ok()?).ok()
+    }
+
+    /// Creates a new self-signed certificate for localhost and this machine's
+    /// host name. Clients that pinned the old fingerprint must update it.
+    pub fn regenerate_certificate(&self) -> Result<TlsCertificateInfo, String> {
+        let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string(), "::1".to_string()];
+        if let Some(host) = std::env::var("COMPUTERNAME").or_else(|_| std::env::var("HOSTNAME")).ok() {
+            if !host.is_empty() && !names.contains(&host) {
+                names.push(host);
+            }
+        }
+        let certified = rcgen::generate_simple_self_signed(names.clone())
+            .map_err(|e| format!("Cannot create the certificate: {}", e))?;
+        let (cert_path, key_path, info_path) = self.cert_paths();
+        std::fs::create_dir_all(&self.tls_dir).map_err(|e| e.to_string())?;
+        std::fs::write(&cert_path, certified.cert.pem()).map_err(|e| e.to_string())?;
+        write_private(&key_path, certified.key_pair.serialize_pem().as_bytes())?;
+        let info = TlsCertificateInfo {
+            cert_path: cert_path.to_string_lossy().to_string(),
+            key_path: key_path.to_string_lossy().to_string(),
+            fingerprint_sha256: hex::encode(Sha256::digest(certified.cert.der())),
+            subject_alt_names: names,
+            created_at: unix_now(),
+        };
+        write_json(&info_path, &info)?;
+        Ok(info)
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    std::fs::write(path, raw).map_err(|e| e.to_string())
+}
+
+/// Writes a file only the current user can read where the OS supports it. The
+/// file is created with those permissions, so the contents are never readable
+/// by others, and an existing file is tightened before it is overwritten.
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+    }
+    file.write_all(contents).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn allows(network: &str, address: &str) -> bool {
+        in_network(ip(address), parse_network(network).unwrap())
+    }
+
+    #[test]
+    fn single_addresses_are_full_prefixes() {
+        assert_eq!(parse_network("10.1.2.3").unwrap(), (ip("10.1.2.3"), 32));
+        assert_eq!(parse_network(" fe80::1 ").unwrap(), (ip("fe80::1"), 128));
+        assert!(allows("10.1.2.3/32", "10.1.2.3"));
+        assert!(!allows("10.1.2.3/32", "10.1.2.4"));
+        assert!(allows("fe80::1/128", "fe80::1"));
+        assert!(!allows("fe80::1/128", "fe80::2"));
+    }
+
+    #[test]
+    fn zero_prefix_matches_its_whole_family() {
+        assert!(allows("0.0.0.0/0", "203.0.113.9"));
+        assert!(allows("::/0", "2001:db8::1"));
+        assert!(!allows("0.0.0.0/0", "2001:db8::1"));
+        assert!(!allows("::/0", "203.0.113.9"));
+    }
+
+    #[test]
+    fn ranges_match_on_the_prefix_bits() {
+        assert!(allows("192.168.1.0/24", "192.168.1.200"));
+        assert!(!allows("192.168.1.0/24", "192.168.2.1"));
+        assert!(allows("10.0.0.0/8", "10.255.0.1"));
+        assert!(allows("2001:db8::/32", "2001:db8:ffff::1"));
+        assert!(!allows("2001:db8::/32", "2001:db9::1"));
+    }
+
+    #[test]
+    fn ipv4_mapped_addresses_match_ipv4_ranges() {
+        assert!(allows("192.168.1.0/24", "::ffff:192.168.1.7"));
+        assert!(!allows("192.168.1.0/24", "::ffff:192.168.2.7"));
+        assert_eq!(parse_network("::ffff:192.168.1.0/120").unwrap(), (ip("192.168.1.0"), 24));
+        assert!(allows("::ffff:192.168.1.0/120", "192.168.1.7"));
+        assert!(allows("::ffff:192.168.1.0/120", "::ffff:192.168.1.7"));
+    }
+
+    #[test]
+    fn malformed_networks_are_rejected() {
+        for value in ["", "abc", "10.0.0/8", "10.0.0.0/", "10.0.0.0/33", "10.0.0.0/-1", "10.0.0.0/8/8", "::1/129"] {
+            assert!(parse_network(value).is_err(), "{}", value);
+        }
+    }
+}
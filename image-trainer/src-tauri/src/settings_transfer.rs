@@ -0,0 +1,172 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::Manager;
+
+use crate::app_settings::{AppSettings, AppSettingsStore};
+use crate::audit::{AuditLog, AuditSettings};
+use crate::backups::{BackupManager, BackupSettings};
+use crate::drift::{DriftMonitor, DriftSettings};
+use crate::i18n::{LanguageSettings, Localizer};
+use crate::job_env::{self, JobEnvSettings};
+use crate::jobs::unix_now;
+use crate::live_metrics::{MetricsCollector, MetricsSettings};
+use crate::ports::{PortManager, PortSettings};
+use crate::python::{self, PythonSettings};
+use crate::server_security::{ServerSecurity, ServerSecuritySettings};
+use crate::webhooks::{self, Webhook, WebhookFormat};
+
+const FORMAT: &str = "epoq-settings";
+const VERSION: u32 = 1;
+
+/// A portable copy of the user's configuration. Secrets never leave the
+/// machine: webhook signing secrets are dropped, and Slack and Teams webhooks
+/// are left out since their URLs are credentials themselves. Neither do
+/// machine-local paths: the interpreter, conda environment and developer
+/// script directory are never exported or imported.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SettingsFile {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: u64,
+    /// General preferences, without the interpreter.
+    #[serde(default)]
+    pub app: Option<AppSettings>,
+    #[serde(default)]
+    pub audit: Option<AuditSettings>,
+    #[serde(default)]
+    pub drift: Option<DriftSettings>,
+    #[serde(default)]
+    pub backups: Option<BackupSettings>,
+    /// Default job environment, without variables that hold secrets.
+    #[serde(default)]
+    pub job_env: Option<JobEnvSettings>,
+    #[serde(default)]
+    pub language: Option<LanguageSettings>,
+    #[serde(default)]
+    pub metrics: Option<MetricsSettings>,
+    #[serde(default)]
+    pub ports: Option<PortSettings>,
+    /// Launcher version and retry policy, without the interpreter or conda environment.
+    #[serde(default)]
+    pub python: Option<PythonSettings>,
+    /// Bind mode and allow-list; API keys and the certificate stay behind.
+    #[serde(default)]
+    pub server: Option<ServerSecuritySettings>,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct SettingsImportReport {
+    pub applied: Vec<String>,
+    /// Sections left unchanged, with the reason.
+    pub skipped: Vec<String>,
+}
+
+pub fn export(app: &tauri::AppHandle, dest: &Path) -> Result<SettingsFile, String> {
+    let webhooks = webhooks::load(app)?
+        .into_iter()
+        .filter(|w| w.format == WebhookFormat::Json)
+        .map(|w| Webhook { secret: None, secret_set: false, ..w })
+        .collect();
+    let file = SettingsFile {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        exported_at: unix_now(),
+        app: app.try_state::<Arc<AppSettingsStore>>().map(|s| AppSettings { interpreter_path: None, ..s.get(app) }),
+        audit: app.try_state::<Arc<AuditLog>>().map(|a| a.settings()),
+        drift: app.try_state::<Arc<DriftMonitor>>().map(|d| d.settings()),
+        backups: app.try_state::<Arc<BackupManager>>().map(|b| b.settings()),
+        job_env: Some(job_env::without_secrets(job_env::load_settings(app))),
+        language: app.try_state::<Arc<Localizer>>().map(|l| l.status().settings),
+        metrics: app.try_state::<Arc<MetricsCollector>>().map(|m| m.settings()),
+        ports: app.try_state::<Arc<PortManager>>().map(|p| p.settings()),
+        python: Some(PythonSettings { interpreter: None, conda_env: None, ..python::load_settings(app) }),
+        server: app.try_state::<Arc<ServerSecurity>>().map(|s| s.settings()),
+        webhooks,
+    };
+    let raw = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(dest, raw).map_err(|e| format!("Cannot write {}: {}", dest.display(), e))?;
+    Ok(file)
+}
+
+fn apply(report: &mut SettingsImportReport, section: &str, result: Result<(), String>) {
+    match result {
+        Ok(()) => report.applied.push(section.to_string()),
+        Err(e) => report.skipped.push(format!("{}: {}", section, e)),
+    }
+}
+
+/// Applies each section of a settings file through the same validation as
+/// the settings screens. A section that fails is skipped; the rest still apply.
+pub async fn import(app: &tauri::AppHandle, file: &Path) -> Result<SettingsImportReport, String> {
+    let raw = std::fs::read_to_string(file).map_err(|e| format!("Cannot read {}: {}", file.display(), e))?;
+    let settings: SettingsFile = serde_json::from_str(&raw).map_err(|e| format!("Not a settings file: {}", e))?;
+    if settings.format != FORMAT {
+        return Err("Not an EPOQ settings file.".to_string());
+    }
+    if settings.version > VERSION {
+        return Err(format!(
+            "The settings file is from a newer version of the app (format {}).",
+            settings.version
+        ));
+    }
+
+    let mut report = SettingsImportReport::default();
+    if let (Some(s), Some(store)) = (settings.app, app.try_state::<Arc<AppSettingsStore>>()) {
+        let patch = serde_json::json!({
+            "default_output_dir": s.default_output_dir,
+            "theme": s.theme,
+            "max_concurrent_jobs": s.max_concurrent_jobs,
+            "max_concurrent_training": s.max_concurrent_training,
+            "update_channel": s.update_channel,
+        });
+        apply(&mut report, "General", store.update(app, patch).await.map(|_| ()));
+    }
+    if let (Some(s), Some(audit)) = (settings.audit, app.try_state::<Arc<AuditLog>>()) {
+        apply(&mut report, "Inference audit", audit.set_settings(s));
+    }
+    if let (Some(s), Some(drift)) = (settings.drift, app.try_state::<Arc<DriftMonitor>>()) {
+        apply(&mut report, "Drift monitoring", drift.set_settings(s));
+    }
+    if let (Some(s), Some(backups)) = (settings.backups, app.try_state::<Arc<BackupManager>>()) {
+        apply(&mut report, "Backups", backups.set_settings(s));
+    }
+    if let Some(s) = settings.job_env {
+        apply(&mut report, "Job environment", job_env::save_settings(app, &s));
+    }
+    if let (Some(s), Some(l10n)) = (settings.language, app.try_state::<Arc<Localizer>>()) {
+        apply(&mut report, "Language", l10n.set_settings(s));
+    }
+    if let (Some(s), Some(metrics)) = (settings.metrics, app.try_state::<Arc<MetricsCollector>>()) {
+        apply(&mut report, "Metric smoothing", metrics.set_settings(s));
+    }
+    if let (Some(s), Some(ports)) = (settings.ports, app.try_state::<Arc<PortManager>>()) {
+        for (service, port) in s.preferred {
+            apply(&mut report, &format!("Port for {}", service), ports.set_preferred(&service, Some(port)));
+        }
+    }
+    if let Some(s) = settings.python {
+        // The interpreter and conda environment stay as they are on this machine.
+        let current = python::load_settings(app);
+        let merged = PythonSettings { interpreter: current.interpreter, conda_env: current.conda_env, ..s };
+        apply(&mut report, "Python", python::save_settings(app, &merged));
+    }
+    if let (Some(s), Some(server)) = (settings.server, app.try_state::<Arc<ServerSecurity>>()) {
+        apply(&mut report, "Local server security", server.set_settings(s));
+    }
+    for webhook in settings.webhooks {
+        let section = format!("Webhook {}", webhook.name);
+        if webhook.format != WebhookFormat::Json {
+            report.skipped.push(format!("{}: Slack and Teams webhooks are not imported.", section));
+            continue;
+        }
+        // Imported webhooks get fresh ids so they never replace existing ones.
+        let webhook = Webhook { id: String::new(), secret: None, secret_set: false, ..webhook };
+        let result = webhooks::save(app, webhook).map(|_| ());
+        apply(&mut report, &section, result);
+    }
+    Ok(report)
+}
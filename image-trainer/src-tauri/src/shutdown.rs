@@ -0,0 +1,122 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use crate::annotations::AnnotationStore;
+use crate::audit::AuditLog;
+use crate::capture::CaptureManager;
+use crate::events;
+use crate::history::JobHistory;
+use crate::jobs::{ActiveJob, JobManager};
+use crate::sweep::{SweepManager, SweepProgress};
+
+/// How long training jobs get to write their checkpoint before being killed.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(60);
+const POLL: Duration = Duration::from_millis(500);
+
+/// Work that would be lost by closing the app, for the confirm dialog.
+/// Published as `app://close_requested` when a close is held back.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ActiveWork {
+    pub jobs: Vec<ActiveJob>,
+    pub sweeps: Vec<SweepProgress>,
+}
+
+impl ActiveWork {
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty() && self.sweeps.is_empty()
+    }
+}
+
+/// Managed state: set once the user confirmed closing, so the final exit is
+/// not intercepted again.
+#[derive(Default)]
+pub struct ShutdownState {
+    confirmed: AtomicBool,
+}
+
+pub fn active_work(app: &tauri::AppHandle) -> ActiveWork {
+    ActiveWork {
+        jobs: app
+            .try_state::<Arc<JobManager>>()
+            .map(|jobs| jobs.active())
+            .unwrap_or_default(),
+        sweeps: app
+            .try_state::<Arc<SweepManager>>()
+            .map(|sweeps| sweeps.list().into_iter().filter(|s| s.running_workers > 0).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// Whether a close or exit request should be held back for confirmation.
+/// Publishes the active work so the frontend can ask.
+pub fn intercept(app: &tauri::AppHandle) -> bool {
+    if app
+        .try_state::<Arc<ShutdownState>>()
+        .is_some_and(|s| s.confirmed.load(Ordering::SeqCst))
+    {
+        return false;
+    }
+    let work = active_work(app);
+    if work.is_empty() {
+        return false;
+    }
+    events::publish(app, "app://close_requested", None, work);
+    true
+}
+
+/// Orderly shutdown after the user confirmed: training jobs are asked to
+/// checkpoint and given `grace` to exit, sweeps are cancelled (finished trials
+/// stay in their study), stores are flushed, anything left is killed, and the
+/// app exits.
+pub async fn shutdown(app: tauri::AppHandle, grace: Duration) {
+    if let Some(state) = app.try_state::<Arc<ShutdownState>>() {
+        state.confirmed.store(true, Ordering::SeqCst);
+    }
+    let jobs = app.state::<Arc<JobManager>>().inner().clone();
+
+    let mut waiting = Vec::new();
+    for job in jobs.active() {
+        match jobs.request_stop(&job.job_id) {
+            Ok(true) => waiting.push(job.job_id),
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Could not ask job {} to stop: {}", job.job_id, e),
+        }
+    }
+    if let Some(captures) = app.try_state::<Arc<CaptureManager>>() {
+        for capture in captures.list() {
+            let _ = captures.stop(&capture.capture_id);
+        }
+    }
+    if let Some(sweeps) = app.try_state::<Arc<SweepManager>>() {
+        for sweep in sweeps.list().into_iter().filter(|s| s.running_workers > 0) {
+            if let Err(e) = sweeps.cancel(&sweep.sweep_id) {
+                tracing::warn!("{}", e);
+            }
+        }
+    }
+
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline && waiting.iter().any(|id| jobs.is_running(id)) {
+        tokio::time::sleep(POLL).await;
+    }
+
+    for job in jobs.active() {
+        if let Err(e) = jobs.kill(&job.job_id) {
+            tracing::warn!("Could not stop job {}: {}", job.job_id, e);
+        }
+    }
+    if let Some(audit) = app.try_state::<Arc<AuditLog>>() {
+        audit.close();
+    }
+    if let Some(store) = app.try_state::<Arc<AnnotationStore>>() {
+        store.close();
+    }
+    if let Some(history) = app.try_state::<Arc<JobHistory>>() {
+        history.close();
+    }
+    app.exit(0);
+}
@@ -0,0 +1,214 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::audit::AuditLog;
+use crate::backups;
+use crate::dev_mode::BACKEND_SCRIPTS;
+use crate::error::BackendError;
+use crate::events;
+use crate::history::JobHistory;
+use crate::jobs::unix_now;
+use crate::paths;
+use crate::python::{backend_script, resolve_interpreter};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A feature will fail until this is fixed.
+    Error,
+    /// Works, but something was reset or is degraded.
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StartupIssue {
+    pub severity: Severity,
+    /// `scripts`, `stores`, `directories`, `settings` or `python`.
+    pub area: String,
+    pub message: String,
+    pub path: Option<String>,
+}
+
+/// Result of the checks run at launch, published as `app://startup_report`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StartupReport {
+    pub checked_at: u64,
+    /// No issue of error severity.
+    pub ok: bool,
+    /// The resolved Python interpreter, if one was found.
+    pub interpreter: Option<String>,
+    pub issues: Vec<StartupIssue>,
+}
+
+/// Managed state: the most recent report, so a window opened after the
+/// check finished can still show it.
+#[derive(Default)]
+pub struct StartupCheck {
+    last: Mutex<Option<StartupReport>>,
+}
+
+impl StartupCheck {
+    pub fn last(&self) -> Option<StartupReport> {
+        self.last.lock().unwrap().clone()
+    }
+}
+
+fn issue(severity: Severity, area: &str, message: String, path: Option<&Path>) -> StartupIssue {
+    StartupIssue {
+        severity,
+        area: area.to_string(),
+        message,
+        path: path.map(|p| p.to_string_lossy().to_string()),
+    }
+}
+
+fn check_scripts(app: &tauri::AppHandle, issues: &mut Vec<StartupIssue>) {
+    for name in BACKEND_SCRIPTS {
+        match backend_script(app, name) {
+            Ok(_) => {}
+            Err(BackendError::ScriptNotFound { path, .. }) => issues.push(issue(
+                Severity::Error,
+                "scripts",
+                format!("Backend script {} is missing; reinstall the app.", name),
+                Some(Path::new(&path)),
+            )),
+            Err(e) => issues.push(issue(Severity::Error, "scripts", e.to_string(), None)),
+        }
+    }
+}
+
+/// Creates the directory if needed and writes and removes a probe file.
+fn check_writable(area_name: &str, dir: Result<PathBuf, BackendError>, issues: &mut Vec<StartupIssue>) {
+    let dir = match dir {
+        Ok(dir) => dir,
+        Err(e) => {
+            issues.push(issue(
+                Severity::Error,
+                "directories",
+                format!("The {} directory cannot be determined: {}", area_name, e),
+                None,
+            ));
+            return;
+        }
+    };
+    let probe = dir.join(".write_test");
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe));
+    if let Err(e) = result {
+        issues.push(issue(
+            Severity::Error,
+            "directories",
+            format!("The {} directory is not writable: {}", area_name, e),
+            Some(&dir),
+        ));
+    }
+}
+
+fn check_stores(app: &tauri::AppHandle, issues: &mut Vec<StartupIssue>) {
+    if let Some(audit) = app.try_state::<Arc<AuditLog>>() {
+        if let Err(e) = audit.check() {
+            issues.push(issue(Severity::Error, "stores", e, None));
+        }
+    }
+    if let Some(history) = app.try_state::<Arc<JobHistory>>() {
+        if let Err(e) = history.check() {
+            issues.push(issue(Severity::Error, "stores", e, None));
+        }
+    }
+    let Ok(data_dir) = paths::data_dir(app) else {
+        return;
+    };
+    for store in backups::store_files(&data_dir) {
+        match backups::integrity_problems(&store) {
+            Ok(problems) if problems.is_empty() => {}
+            Ok(problems) => issues.push(issue(
+                Severity::Error,
+                "stores",
+                format!(
+                    "The database is damaged ({}); restore it from a backup.",
+                    problems.join("; ")
+                ),
+                Some(&store),
+            )),
+            Err(e) => issues.push(issue(Severity::Error, "stores", e, Some(&store))),
+        }
+    }
+}
+
+/// Settings files that no longer parse are silently replaced by defaults
+/// when loaded; say so instead.
+fn check_settings(app: &tauri::AppHandle, issues: &mut Vec<StartupIssue>) {
+    let Ok(dir) = paths::settings_dir(app) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if !path.extension().is_some_and(|ext| ext == "json") {
+            continue;
+        }
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).map_err(|e| e.to_string()));
+        if let Err(e) = parsed {
+            issues.push(issue(
+                Severity::Warning,
+                "settings",
+                format!("Settings could not be read and defaults are in use: {}", e),
+                Some(&path),
+            ));
+        }
+    }
+}
+
+pub async fn run(app: &tauri::AppHandle) -> StartupReport {
+    let mut issues = Vec::new();
+    check_scripts(app, &mut issues);
+    check_writable("data", paths::data_dir(app), &mut issues);
+    check_writable("settings", paths::settings_dir(app), &mut issues);
+    check_writable("cache", paths::cache_dir(app), &mut issues);
+    check_writable("log", paths::log_dir(app), &mut issues);
+    check_writable("models", paths::models_dir(app), &mut issues);
+    check_settings(app, &mut issues);
+    check_stores(app, &mut issues);
+
+    let interpreter = match resolve_interpreter(app).await {
+        Ok(interpreter) => Some(interpreter),
+        Err(e) => {
+            issues.push(issue(
+                Severity::Error,
+                "python",
+                format!("No usable Python interpreter: {}", e),
+                None,
+            ));
+            None
+        }
+    };
+
+    let report = StartupReport {
+        checked_at: unix_now(),
+        ok: !issues.iter().any(|i| i.severity == Severity::Error),
+        interpreter,
+        issues,
+    };
+    if let Some(state) = app.try_state::<Arc<StartupCheck>>() {
+        *state.last.lock().unwrap() = Some(report.clone());
+    }
+    report
+}
+
+/// Runs the checks in the background once all state is loaded.
+pub fn spawn(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let report = run(&app).await;
+        for issue in &report.issues {
+            tracing::warn!("Startup check ({}): {}", issue.area, issue.message);
+        }
+        events::publish(&app, "app://startup_report", None, report);
+    });
+}
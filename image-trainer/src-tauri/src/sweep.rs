@@ -0,0 +1,330 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use crate::events;
+use crate::jobs::JobManager;
+use crate::paths;
+use crate::python::{backend_script, resolve_launcher};
+
+/// AutoML sweep request. Trials are spread across `gpus` (or every detected GPU
+/// when omitted), one worker process per leased GPU.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SweepConfig {
+    pub path: String,
+    #[serde(default = "default_model")]
+    pub model: String,
+    #[serde(default = "default_n_trials")]
+    pub n_trials: u32,
+    #[serde(default = "default_epochs_per_trial")]
+    pub epochs_per_trial: u32,
+    #[serde(default = "default_num_workers")]
+    pub num_workers: i32,
+    #[serde(default)]
+    pub gpus: Option<Vec<u32>>,
+}
+
+fn default_model() -> String {
+    "resnet18".to_string()
+}
+
+fn default_n_trials() -> u32 {
+    10
+}
+
+fn default_epochs_per_trial() -> u32 {
+    3
+}
+
+fn default_num_workers() -> i32 {
+    -1
+}
+
+/// Aggregate view of a sweep, published as `automl://progress` after every worker
+/// line and as `automl://complete` once all workers have exited.
+#[derive(Debug, Clone, Serialize, Default, JsonSchema)]
+pub struct SweepProgress {
+    pub sweep_id: String,
+    pub n_trials: u32,
+    pub completed: u32,
+    pub failed: u32,
+    pub running_workers: u32,
+    pub best_accuracy: Option<f64>,
+    pub best_params: Option<serde_json::Value>,
+    pub cancelled: bool,
+    /// GPU index of the worker that produced `event`, if any.
+    pub gpu: Option<u32>,
+    /// The raw status line from the worker that triggered this update.
+    pub event: Option<serde_json::Value>,
+}
+
+struct Sweep {
+    children: Vec<CommandChild>,
+    gpus: Vec<u32>,
+    progress: SweepProgress,
+}
+
+/// Managed state: running sweeps and the GPUs they currently hold.
+#[derive(Default)]
+pub struct SweepManager {
+    sweeps: Mutex<HashMap<String, Sweep>>,
+    leased_gpus: Mutex<BTreeSet<u32>>,
+}
+
+impl SweepManager {
+    /// Leases up to `want` GPUs from `candidates` that no other sweep holds.
+    fn lease(&self, candidates: &[u32], want: usize) -> Vec<u32> {
+        let mut leased = self.leased_gpus.lock().unwrap();
+        let granted: Vec<u32> = candidates
+            .iter()
+            .copied()
+            .filter(|gpu| !leased.contains(gpu))
+            .take(want)
+            .collect();
+        leased.extend(granted.iter().copied());
+        granted
+    }
+
+    fn release(&self, gpus: &[u32]) {
+        let mut leased = self.leased_gpus.lock().unwrap();
+        for gpu in gpus {
+            leased.remove(gpu);
+        }
+    }
+
+    /// Kills every worker of a sweep. All workers are stopped together so no
+    /// GPU keeps burning on trials of a study the user abandoned.
+    pub fn cancel(&self, sweep_id: &str) -> Result<(), String> {
+        let mut sweeps = self.sweeps.lock().unwrap();
+        let sweep = sweeps
+            .get_mut(sweep_id)
+            .ok_or_else(|| format!("No running sweep with id {}", sweep_id))?;
+        sweep.progress.cancelled = true;
+        let mut errors = Vec::new();
+        for child in sweep.children.drain(..) {
+            if let Err(e) = child.kill() {
+                errors.push(e.to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Failed to stop some sweep workers: {}", errors.join("; ")))
+        }
+    }
+
+    pub fn list(&self) -> Vec<SweepProgress> {
+        let sweeps = self.sweeps.lock().unwrap();
+        sweeps.values().map(|s| s.progress.clone()).collect()
+    }
+}
+
+/// Folds one worker status line into the sweep's aggregate progress.
+fn record_event(progress: &mut SweepProgress, event: &serde_json::Value) {
+    match event["status"].as_str() {
+        Some("automl_trial") => {
+            progress.completed += 1;
+            if let Some(acc) = event["val_accuracy"].as_f64() {
+                if progress.best_accuracy.is_none_or(|best| acc > best) {
+                    progress.best_accuracy = Some(acc);
+                    progress.best_params = Some(event["params"].clone());
+                }
+            }
+        }
+        Some("automl_trial_error") => progress.failed += 1,
+        _ => {}
+    }
+}
+
+fn new_sweep_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    format!("sweep_{}", millis)
+}
+
+/// Starts a sweep with one automl_sweep.py worker per leased GPU. Workers share
+/// an Optuna SQLite study so the trial budget is split between them.
+pub async fn start(
+    app: tauri::AppHandle,
+    manager: Arc<SweepManager>,
+    config: SweepConfig,
+    available_gpus: Vec<u32>,
+) -> Result<String, String> {
+    if config.n_trials == 0 {
+        return Err("A sweep needs at least one trial.".to_string());
+    }
+
+    let candidates = config.gpus.clone().unwrap_or(available_gpus);
+    let want = (config.n_trials as usize).min(candidates.len()).max(1);
+    let gpus = manager.lease(&candidates, want);
+    if !candidates.is_empty() && gpus.is_empty() {
+        return Err("All GPUs are leased by other sweeps.".to_string());
+    }
+
+    let result = spawn_workers(&app, &manager, &config, &gpus).await;
+    if result.is_err() {
+        manager.release(&gpus);
+    }
+    result
+}
+
+async fn spawn_workers(
+    app: &tauri::AppHandle,
+    manager: &Arc<SweepManager>,
+    config: &SweepConfig,
+    gpus: &[u32],
+) -> Result<String, String> {
+    let sweep_id = new_sweep_id();
+    let script = backend_script(app, "automl_sweep.py")?;
+    let launcher = resolve_launcher(app).await?;
+
+    let storage_path = paths::cache_file(app, &format!("{}.db", sweep_id))?;
+    let storage = format!("sqlite:///{}", storage_path.to_string_lossy().replace('\\', "/"));
+
+    // No GPUs means a single CPU worker without a device pin.
+    let slots: Vec<Option<u32>> = if gpus.is_empty() {
+        vec![None]
+    } else {
+        gpus.iter().copied().map(Some).collect()
+    };
+
+    let progress = SweepProgress {
+        sweep_id: sweep_id.clone(),
+        n_trials: config.n_trials,
+        running_workers: slots.len() as u32,
+        ..Default::default()
+    };
+    manager.sweeps.lock().unwrap().insert(
+        sweep_id.clone(),
+        Sweep {
+            children: Vec::new(),
+            gpus: gpus.to_vec(),
+            progress,
+        },
+    );
+
+    for (worker_id, gpu) in slots.into_iter().enumerate() {
+        let args = vec![
+            script.clone(),
+            "--path".to_string(),
+            config.path.clone(),
+            "--model".to_string(),
+            config.model.clone(),
+            "--n_trials".to_string(),
+            config.n_trials.to_string(),
+            "--epochs_per_trial".to_string(),
+            config.epochs_per_trial.to_string(),
+            "--num_workers".to_string(),
+            config.num_workers.to_string(),
+            "--storage".to_string(),
+            storage.clone(),
+            "--study_name".to_string(),
+            sweep_id.clone(),
+            "--worker_id".to_string(),
+            worker_id.to_string(),
+        ];
+
+        let mut command = app.shell().command(&launcher.program).args(launcher.with_args(args.iter().cloned()));
+        if let Some(gpu) = gpu {
+            command = command.env("CUDA_VISIBLE_DEVICES", gpu.to_string());
+        }
+
+        // Each worker shows up in the job list of its own.
+        let jobs = app.try_state::<Arc<JobManager>>().map(|jobs| jobs.inner().clone());
+        let job_id = format!("{}_worker{}", sweep_id, worker_id);
+        if let Some(jobs) = &jobs {
+            jobs.queue(&job_id, "automl_sweep", &args, None);
+        }
+        let (mut rx, child) = match command.spawn() {
+            Ok(spawned) => {
+                if let Some(jobs) = &jobs {
+                    jobs.start(&job_id);
+                }
+                spawned
+            }
+            Err(e) => {
+                if let Some(jobs) = &jobs {
+                    jobs.finish(&job_id, None, Some(e.to_string()));
+                }
+                // Tear down workers that already started
+                let _ = manager.cancel(&sweep_id);
+                manager.sweeps.lock().unwrap().remove(&sweep_id);
+                return Err(format!("Failed to start sweep worker: {}", e));
+            }
+        };
+        if let Some(sweep) = manager.sweeps.lock().unwrap().get_mut(&sweep_id) {
+            sweep.children.push(child);
+        }
+
+        let app = app.clone();
+        let manager = manager.clone();
+        let sweep_id = sweep_id.clone();
+        let storage_path = storage_path.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut code = None;
+            while let Some(event) = rx.recv().await {
+                let line = match event {
+                    CommandEvent::Stdout(bytes) => bytes,
+                    CommandEvent::Terminated(payload) => {
+                        code = payload.code;
+                        break;
+                    }
+                    _ => continue,
+                };
+                if let Some(jobs) = &jobs {
+                    jobs.note_output(&job_id, &String::from_utf8_lossy(&line));
+                }
+                let Ok(value) = serde_json::from_slice::<serde_json::Value>(line.trim_ascii()) else {
+                    continue;
+                };
+                let snapshot = {
+                    let mut sweeps = manager.sweeps.lock().unwrap();
+                    let Some(sweep) = sweeps.get_mut(&sweep_id) else {
+                        break;
+                    };
+                    record_event(&mut sweep.progress, &value);
+                    let mut snapshot = sweep.progress.clone();
+                    snapshot.gpu = gpu;
+                    snapshot.event = Some(value);
+                    snapshot
+                };
+                events::publish(&app, "automl://progress", Some(&sweep_id), snapshot);
+            }
+
+            if let Some(jobs) = &jobs {
+                jobs.finish(&job_id, code, None);
+            }
+
+            // Last worker out releases the GPUs and reports the final result.
+            let finished = {
+                let mut sweeps = manager.sweeps.lock().unwrap();
+                let done = match sweeps.get_mut(&sweep_id) {
+                    Some(sweep) => {
+                        sweep.progress.running_workers = sweep.progress.running_workers.saturating_sub(1);
+                        sweep.progress.running_workers == 0
+                    }
+                    None => false,
+                };
+                if done {
+                    sweeps.remove(&sweep_id)
+                } else {
+                    None
+                }
+            };
+            if let Some(sweep) = finished {
+                manager.release(&sweep.gpus);
+                let _ = std::fs::remove_file(&storage_path);
+                events::publish(&app, "automl://complete", Some(&sweep_id), sweep.progress);
+            }
+        });
+    }
+
+    Ok(sweep_id)
+}
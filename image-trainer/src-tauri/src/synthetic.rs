@@ -0,0 +1,275 @@
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::augmentation::AugmentationPolicy;
+use crate::dataset;
+use crate::events;
+use crate::image_meta;
+use crate::jobs::unix_now;
+
+/// Written to the root of a materialized dataset. script.py reads it to keep
+/// synthetic images out of the validation and test splits.
+pub const MANIFEST: &str = "synthetic_manifest.json";
+pub const MAX_MULTIPLIER: u32 = 20;
+/// Extensions the enabled `image` encoders write; anything else is saved as PNG.
+const WRITABLE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "bmp", "tif", "tiff", "webp"];
+
+/// One generated image, with paths relative to the dataset root.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SyntheticImage {
+    pub file: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SyntheticManifest {
+    pub created_at: u64,
+    /// Dataset the originals were copied from.
+    pub source_dataset: String,
+    pub policy: AugmentationPolicy,
+    pub multiplier: u32,
+    pub seed: Option<u64>,
+    pub synthetic: Vec<SyntheticImage>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MaterializeReport {
+    pub dest: String,
+    pub originals: usize,
+    pub synthetic: usize,
+    pub failures: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Published as `augment://progress` while images are written.
+#[derive(Debug, Clone, Serialize)]
+struct MaterializeProgress {
+    done: usize,
+    total: usize,
+}
+
+fn apply_color_jitter(img: &mut RgbaImage, policy: &AugmentationPolicy, rng: &mut StdRng) {
+    let Some(jitter) = &policy.color_jitter else {
+        return;
+    };
+    let factor = |rng: &mut StdRng, strength: f64| 1.0 + rng.gen_range(-strength..=strength);
+    if jitter.brightness > 0.0 {
+        let f = factor(rng, jitter.brightness) as f32;
+        for p in img.pixels_mut() {
+            for c in &mut p.0[..3] {
+                *c = (*c as f32 * f).clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+    if jitter.contrast > 0.0 {
+        let f = factor(rng, jitter.contrast) as f32;
+        *img = imageops::contrast(&*img, (f - 1.0) * 100.0);
+    }
+    if jitter.saturation > 0.0 {
+        let f = factor(rng, jitter.saturation) as f32;
+        for p in img.pixels_mut() {
+            let [r, g, b, _] = p.0;
+            let gray = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            for c in &mut p.0[..3] {
+                *c = (gray + (*c as f32 - gray) * f).clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+    if jitter.hue > 0.0 {
+        let degrees = rng.gen_range(-jitter.hue..=jitter.hue) * 360.0;
+        *img = imageops::huerotate(&*img, degrees as i32);
+    }
+}
+
+/// Applies the policy's geometric and color ops with the same ranges
+/// torchvision uses at train time. RandAugment has no equivalent here.
+fn augment(source: &DynamicImage, policy: &AugmentationPolicy, rng: &mut StdRng) -> RgbaImage {
+    let mut img = source.to_rgba8();
+    if policy.horizontal_flip && rng.gen_bool(0.5) {
+        imageops::flip_horizontal_in_place(&mut img);
+    }
+    if policy.vertical_flip && rng.gen_bool(0.5) {
+        imageops::flip_vertical_in_place(&mut img);
+    }
+    if policy.rotation_degrees > 0.0 {
+        let degrees = rng.gen_range(-policy.rotation_degrees..=policy.rotation_degrees);
+        img = rotate_about_center(
+            &img,
+            degrees.to_radians() as f32,
+            Interpolation::Bilinear,
+            Rgba([0, 0, 0, 255]),
+        );
+    }
+    apply_color_jitter(&mut img, policy, rng);
+    if let Some(cutout) = &policy.cutout {
+        if rng.gen_bool(cutout.probability) {
+            let (w, h) = img.dimensions();
+            let area = rng.gen_range(0.02..=cutout.max_area) * (w * h) as f64;
+            let ratio = rng.gen_range(0.3..=3.3f64);
+            let cw = ((area * ratio).sqrt() as u32).clamp(1, w);
+            let ch = ((area / ratio).sqrt() as u32).clamp(1, h);
+            let x0 = rng.gen_range(0..=w - cw);
+            let y0 = rng.gen_range(0..=h - ch);
+            for y in y0..y0 + ch {
+                for x in x0..x0 + cw {
+                    img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+                }
+            }
+        }
+    }
+    img
+}
+
+fn relative(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn copy_tree(from: &Path, to: &Path) -> Result<usize, String> {
+    let mut copied = 0;
+    for image in dataset::list_images(from, true)? {
+        let target = to.join(image.strip_prefix(from).unwrap_or(&image));
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::copy(&image, &target).map_err(|e| format!("Cannot copy {}: {}", image.display(), e))?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// Writes the `source` dataset to `dest` with `multiplier` augmented copies of every
+/// training image next to its original, using all CPU cores. Validation and
+/// test folders of pre-split datasets are copied unchanged. The manifest lists
+/// every synthetic image and its original so training keeps them out of
+/// evaluation.
+pub fn materialize(
+    app: &tauri::AppHandle,
+    source: &Path,
+    policy: &AugmentationPolicy,
+    multiplier: u32,
+    dest: &Path,
+    seed: Option<u64>,
+) -> Result<MaterializeReport, String> {
+    let mut warnings = policy.validate()?;
+    if multiplier == 0 || multiplier > MAX_MULTIPLIER {
+        return Err(format!("The multiplier must be between 1 and {}.", MAX_MULTIPLIER));
+    }
+    if dest.exists() && std::fs::read_dir(dest).map(|mut d| d.next().is_some()).unwrap_or(true) {
+        return Err(format!("{} is not empty.", dest.display()));
+    }
+    if source.join(MANIFEST).exists() {
+        return Err("This dataset already contains synthetic images; materialize from the original dataset.".to_string());
+    }
+    if policy.randaugment.is_some() {
+        warnings.push("RandAugment is not materialized; it still applies at train time.".to_string());
+    }
+
+    let train_root = dataset::training_root(source);
+    let pre_split = train_root != source;
+    let dest_train = if pre_split { dest.join("train") } else { dest.to_path_buf() };
+    let originals = dataset::list_images(&train_root, true)?;
+    if originals.is_empty() {
+        return Err("The dataset has no training images.".to_string());
+    }
+
+    let mut copied = copy_tree(&train_root, &dest_train)?;
+    if pre_split {
+        for split in ["val", "validation", "test"] {
+            let dir = source.join(split);
+            if dir.is_dir() {
+                copied += copy_tree(&dir, &dest.join(split))?;
+            }
+        }
+    }
+
+    let total = originals.len() * multiplier as usize;
+    let done = AtomicUsize::new(0);
+    let synthetic = Mutex::new(Vec::with_capacity(total));
+    let failures = Mutex::new(Vec::new());
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let chunk = originals.len().div_ceil(workers);
+    let base_seed = seed.unwrap_or_else(rand::random);
+
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk_images) in originals.chunks(chunk).enumerate() {
+            let (done, synthetic, failures) = (&done, &synthetic, &failures);
+            let (dest_train, train_root) = (&dest_train, &train_root);
+            scope.spawn(move || {
+                for (offset, original) in chunk_images.iter().enumerate() {
+                    let index = (chunk_index * chunk + offset) as u64;
+                    // Per-image seed, so results do not depend on the worker count.
+                    let mut rng = StdRng::seed_from_u64(base_seed ^ index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                    // Augmented copies are saved without EXIF, so they are written upright.
+                    let image = match image_meta::open_oriented(original) {
+                        Ok(image) => image,
+                        Err(e) => {
+                            failures.lock().unwrap().push(e);
+                            done.fetch_add(multiplier as usize, Ordering::Relaxed);
+                            continue;
+                        }
+                    };
+                    let relative_original = original.strip_prefix(train_root).unwrap_or(original);
+                    let target_original = dest_train.join(relative_original);
+                    let stem = original.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                    let ext = original
+                        .extension()
+                        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+                        .filter(|e| WRITABLE_EXTENSIONS.contains(&e.as_str()))
+                        .unwrap_or_else(|| "png".to_string());
+                    for k in 1..=multiplier {
+                        let out: PathBuf = target_original.with_file_name(format!("{}__aug{}.{}", stem, k, ext));
+                        let augmented = DynamicImage::ImageRgba8(augment(&image, policy, &mut rng));
+                        // JPEG has no alpha channel.
+                        let result = if matches!(ext.as_str(), "jpg" | "jpeg") {
+                            DynamicImage::ImageRgb8(augmented.to_rgb8()).save(&out)
+                        } else {
+                            augmented.save(&out)
+                        };
+                        match result {
+                            Ok(()) => synthetic.lock().unwrap().push(SyntheticImage {
+                                file: relative(&out, dest),
+                                source: relative(&target_original, dest),
+                            }),
+                            Err(e) => failures.lock().unwrap().push(format!("{}: {}", out.display(), e)),
+                        }
+                        let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        if finished % 100 == 0 || finished == total {
+                            events::publish(app, "augment://progress", None, MaterializeProgress { done: finished, total });
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let mut synthetic = synthetic.into_inner().unwrap();
+    synthetic.sort_by(|a, b| a.file.cmp(&b.file));
+    let manifest = SyntheticManifest {
+        created_at: unix_now(),
+        source_dataset: source.to_string_lossy().to_string(),
+        policy: policy.clone(),
+        multiplier,
+        seed,
+        synthetic,
+    };
+    let raw = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(dest.join(MANIFEST), raw).map_err(|e| format!("Cannot write the manifest: {}", e))?;
+
+    Ok(MaterializeReport {
+        dest: dest.to_string_lossy().to_string(),
+        originals: copied,
+        synthetic: manifest.synthetic.len(),
+        failures: failures.into_inner().unwrap(),
+        warnings,
+    })
+}
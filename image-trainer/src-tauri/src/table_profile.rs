@@ -0,0 +1,259 @@
+use polars::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::tables::{self, json_value, polars_error, TableFormat};
+
+/// Bins in each numeric histogram.
+const HISTOGRAM_BINS: usize = 20;
+/// Most frequent values listed for boolean and categorical columns.
+const TOP_VALUES: u32 = 10;
+/// Text columns with at most this many distinct values are categorical.
+const CATEGORICAL_MAX: u64 = 50;
+/// Columns that are unique in fewer rows than this are not called identifiers.
+const IDENTIFIER_MIN_ROWS: u64 = 20;
+/// Columns missing more than this fraction of their values are reported.
+const MOSTLY_MISSING: f64 = 0.5;
+
+/// What a column holds, going by its type and values rather than its type alone.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    /// Every value is missing.
+    Empty,
+    Boolean,
+    Integer,
+    Float,
+    Datetime,
+    /// Text with few distinct values, worth encoding.
+    Categorical,
+    /// Free text with many distinct values.
+    Text,
+    /// Integers or text unique in every row, e.g. ids, which carry no signal for training.
+    Identifier,
+}
+
+/// Equal-width bins from `edges[i]` to `edges[i + 1]`; the last bin includes its upper edge.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Histogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ValueCount {
+    pub value: Value,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ColumnProfile {
+    pub name: String,
+    /// Polars type, e.g. `i64`, `f64` or `str`.
+    pub dtype: String,
+    pub kind: ColumnKind,
+    pub nulls: u64,
+    pub null_fraction: f64,
+    /// Distinct values, not counting missing ones.
+    pub unique: u64,
+    /// Smallest and largest values of numeric, date and text columns; text compares alphabetically.
+    pub min: Value,
+    pub max: Value,
+    pub mean: Option<f64>,
+    pub std: Option<f64>,
+    /// Numeric columns only.
+    pub histogram: Option<Histogram>,
+    /// Boolean and categorical columns only, most frequent first.
+    pub top_values: Vec<ValueCount>,
+}
+
+/// What `profile_table` found, computed natively without pandas.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TableProfile {
+    pub file: String,
+    pub format: TableFormat,
+    pub rows: u64,
+    pub columns: Vec<ColumnProfile>,
+    /// Columns likely to need attention before training, as sentences.
+    pub warnings: Vec<String>,
+}
+
+fn extract_u64(frame: &DataFrame, name: &str) -> u64 {
+    frame.column(name).ok().and_then(|c| c.get(0).ok()).and_then(|v| v.extract::<u64>()).unwrap_or(0)
+}
+
+fn extract_f64(frame: &DataFrame, name: &str) -> Option<f64> {
+    frame.column(name).ok().and_then(|c| c.get(0).ok()).and_then(|v| v.extract::<f64>()).filter(|f| f.is_finite())
+}
+
+fn extract_value(frame: &DataFrame, name: &str) -> Value {
+    frame.column(name).ok().and_then(|c| c.get(0).ok()).map(json_value).unwrap_or(Value::Null)
+}
+
+fn infer_kind(dtype: &DataType, count: u64, unique: u64) -> ColumnKind {
+    if count == 0 {
+        ColumnKind::Empty
+    } else if *dtype == DataType::Boolean {
+        ColumnKind::Boolean
+    } else if dtype.is_temporal() {
+        ColumnKind::Datetime
+    } else if (dtype.is_integer() || *dtype == DataType::String) && unique == count && count >= IDENTIFIER_MIN_ROWS {
+        ColumnKind::Identifier
+    } else if dtype.is_integer() {
+        ColumnKind::Integer
+    } else if dtype.is_float() {
+        ColumnKind::Float
+    } else if unique <= CATEGORICAL_MAX || unique * 20 <= count {
+        ColumnKind::Categorical
+    } else {
+        ColumnKind::Text
+    }
+}
+
+/// Edges of the histogram of a column from `min` to `max`; integer columns
+/// spanning fewer values than there are bins get one bin per value.
+fn histogram_edges(min: f64, max: f64, integer: bool) -> Vec<f64> {
+    if min == max {
+        return vec![min, max];
+    }
+    let bins = if integer { HISTOGRAM_BINS.min((max - min) as usize + 1) } else { HISTOGRAM_BINS };
+    let width = (max - min) / bins as f64;
+    (0..=bins).map(|i| if i == bins { max } else { min + width * i as f64 }).collect()
+}
+
+/// Types, missing counts, cardinality, ranges and distributions of every
+/// column of a table file or `sqlite://` source. Statistics and histograms are
+/// each computed in one streaming pass, so files larger than memory work.
+pub fn profile(file: &str) -> Result<TableProfile, String> {
+    let (mut frame, format) = tables::scan(file)?;
+    let path = Path::new(file);
+    let schema = frame.collect_schema().map_err(|e| polars_error(path, e))?;
+    let fields: Vec<(String, DataType)> =
+        schema.iter().map(|(name, dtype)| (name.to_string(), dtype.clone())).collect();
+
+    // Aliased by column index, as names may hold anything.
+    let mut stats = vec![len().alias("rows")];
+    for (i, (name, dtype)) in fields.iter().enumerate() {
+        stats.push(col(name.as_str()).null_count().alias(format!("{}_nulls", i)));
+        stats.push(col(name.as_str()).n_unique().alias(format!("{}_unique", i)));
+        if dtype.is_numeric() || dtype.is_temporal() || *dtype == DataType::String {
+            stats.push(col(name.as_str()).min().alias(format!("{}_min", i)));
+            stats.push(col(name.as_str()).max().alias(format!("{}_max", i)));
+        }
+        if dtype.is_numeric() {
+            stats.push(col(name.as_str()).cast(DataType::Float64).mean().alias(format!("{}_mean", i)));
+            stats.push(col(name.as_str()).cast(DataType::Float64).std(1).alias(format!("{}_std", i)));
+        }
+    }
+    let stats = frame.clone().select(stats).with_streaming(true).collect().map_err(|e| polars_error(path, e))?;
+    let rows = extract_u64(&stats, "rows");
+
+    let mut columns = Vec::with_capacity(fields.len());
+    for (i, (name, dtype)) in fields.iter().enumerate() {
+        let nulls = extract_u64(&stats, &format!("{}_nulls", i));
+        let count = rows.saturating_sub(nulls);
+        // n_unique counts null as a value of its own.
+        let unique = extract_u64(&stats, &format!("{}_unique", i)).saturating_sub((nulls > 0) as u64);
+        columns.push(ColumnProfile {
+            name: name.clone(),
+            dtype: dtype.to_string(),
+            kind: infer_kind(dtype, count, unique),
+            nulls,
+            null_fraction: if rows == 0 { 0.0 } else { nulls as f64 / rows as f64 },
+            unique,
+            min: extract_value(&stats, &format!("{}_min", i)),
+            max: extract_value(&stats, &format!("{}_max", i)),
+            mean: extract_f64(&stats, &format!("{}_mean", i)),
+            std: extract_f64(&stats, &format!("{}_std", i)),
+            histogram: None,
+            top_values: Vec::new(),
+        });
+    }
+
+    // One more pass counts every bin of every numeric column at once.
+    let mut bins = Vec::new();
+    let mut edges_of = Vec::new();
+    for (i, column) in columns.iter().enumerate() {
+        if !matches!(column.kind, ColumnKind::Integer | ColumnKind::Float) {
+            continue;
+        }
+        let (Some(min), Some(max)) = (column.min.as_f64(), column.max.as_f64()) else {
+            continue;
+        };
+        let edges = histogram_edges(min, max, column.kind == ColumnKind::Integer);
+        let last = edges.len() as i64 - 2;
+        let width = (max - min) / (last + 1) as f64;
+        let value = col(column.name.as_str()).cast(DataType::Float64);
+        let bin = if width > 0.0 { ((value - lit(min)) / lit(width)).cast(DataType::Int64) } else { lit(0i64) };
+        let bin = when(bin.clone().gt(lit(last))).then(lit(last)).otherwise(bin);
+        for b in 0..=last {
+            let counted = col(column.name.as_str()).is_not_null().and(bin.clone().eq(lit(b)));
+            bins.push(counted.sum().alias(format!("{}_bin_{}", i, b)));
+        }
+        edges_of.push((i, edges));
+    }
+    if !bins.is_empty() {
+        let counts = frame.clone().select(bins).with_streaming(true).collect().map_err(|e| polars_error(path, e))?;
+        for (i, edges) in edges_of {
+            let counts = (0..edges.len() - 1).map(|b| extract_u64(&counts, &format!("{}_bin_{}", i, b))).collect();
+            columns[i].histogram = Some(Histogram { edges, counts });
+        }
+    }
+
+    for column in columns.iter_mut() {
+        if !matches!(column.kind, ColumnKind::Boolean | ColumnKind::Categorical) {
+            continue;
+        }
+        let name = column.name.as_str();
+        let top = frame
+            .clone()
+            .filter(col(name).is_not_null())
+            .group_by([col(name)])
+            .agg([len().alias("count")])
+            .sort(
+                [PlSmallStr::from("count"), PlSmallStr::from(name)],
+                SortMultipleOptions::default().with_order_descending_multi([true, false]),
+            )
+            .limit(TOP_VALUES)
+            .collect()
+            .map_err(|e| polars_error(path, e))?;
+        let (Ok(values), Ok(counts)) = (top.column(name), top.column("count")) else {
+            continue;
+        };
+        column.top_values = (0..top.height())
+            .map(|row| ValueCount {
+                value: values.get(row).map(json_value).unwrap_or(Value::Null),
+                count: counts.get(row).ok().and_then(|v| v.extract::<u64>()).unwrap_or(0),
+            })
+            .collect();
+    }
+
+    let warnings = warnings(&columns);
+    Ok(TableProfile { file: file.to_string(), format, rows, columns, warnings })
+}
+
+fn warnings(columns: &[ColumnProfile]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for column in columns {
+        match column.kind {
+            ColumnKind::Empty => warnings.push(format!("{} has no values.", column.name)),
+            ColumnKind::Identifier => {
+                warnings.push(format!("{} is unique in every row, likely an id; drop it before training.", column.name))
+            }
+            ColumnKind::Text => warnings.push(format!(
+                "{} is free text with {} distinct values; encoding it would add as many columns.",
+                column.name, column.unique
+            )),
+            _ if column.unique == 1 => {
+                warnings.push(format!("{} holds a single value and carries no information.", column.name))
+            }
+            _ => {}
+        }
+        if column.kind != ColumnKind::Empty && column.null_fraction > MOSTLY_MISSING {
+            warnings.push(format!("{} is missing {:.0}% of its values.", column.name, column.null_fraction * 100.0));
+        }
+    }
+    warnings
+}
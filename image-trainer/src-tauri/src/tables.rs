@@ -0,0 +1,403 @@
+use calamine::{open_workbook_auto, Data, DataType, Reader};
+use polars::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::BackendError;
+
+pub const DEFAULT_PAGE_ROWS: usize = 100;
+pub const MAX_PAGE_ROWS: usize = 5_000;
+/// Rows read to guess column types.
+const INFER_ROWS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    Csv,
+    Tsv,
+    Parquet,
+    /// Feather v2, i.e. the Arrow IPC file format.
+    Feather,
+    /// `.xlsx` or legacy `.xls`; the first sheet is read.
+    Excel,
+    /// A table or view of an SQLite database, given as `sqlite://<file>#<table>`.
+    Sqlite,
+}
+
+impl TableFormat {
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" | "txt" => Some(Self::Csv),
+            "tsv" => Some(Self::Tsv),
+            "parquet" | "pq" => Some(Self::Parquet),
+            "feather" | "arrow" | "ipc" => Some(Self::Feather),
+            "xlsx" | "xlsm" | "xls" => Some(Self::Excel),
+            _ => None,
+        }
+    }
+
+    /// By the file's magic bytes, falling back to the extension for text
+    /// formats, which have none.
+    pub fn detect(path: &Path) -> Result<Self, String> {
+        let mut head = [0u8; 8];
+        let read = std::fs::File::open(path)
+            .and_then(|mut f| f.read(&mut head))
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let head = &head[..read];
+        if head.starts_with(b"PAR1") {
+            return Ok(Self::Parquet);
+        }
+        if head.starts_with(b"ARROW1") {
+            return Ok(Self::Feather);
+        }
+        if head.starts_with(b"SQLite f") {
+            return Err(format!(
+                "{} is an SQLite database; pick a table with sqlite://{}#<table>.",
+                path.display(),
+                path.display()
+            ));
+        }
+        if head.starts_with(b"FEA1") {
+            return Err(
+                "Feather v1 files are not supported; save them again with a current pandas or pyarrow.".to_string()
+            );
+        }
+        // xlsx is a zip archive, xls an OLE compound document.
+        if head.starts_with(b"PK\x03\x04") || head.starts_with(&[0xD0, 0xCF, 0x11, 0xE0]) {
+            return match Self::from_extension(path) {
+                Some(Self::Excel) | None => Ok(Self::Excel),
+                Some(_) => Err(format!("{} is not a table file.", path.display())),
+            };
+        }
+        Self::from_extension(path).ok_or_else(|| {
+            format!("{} is not a supported table file; use CSV, TSV, Parquet, Feather or Excel.", path.display())
+        })
+    }
+}
+
+/// One page of a table read natively, without starting Python.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TablePage {
+    pub columns: Vec<String>,
+    /// Rows `offset` onwards; missing cells are `null`.
+    pub data: Vec<Vec<Value>>,
+    pub offset: usize,
+    pub format: TableFormat,
+    /// Rows and columns of the whole table.
+    pub shape: (u64, u64),
+    /// Polars type per column, e.g. `i64`, `f64` or `str`.
+    pub dtypes: BTreeMap<String, String>,
+    pub missing: BTreeMap<String, u64>,
+}
+
+pub fn polars_error(path: &Path, e: PolarsError) -> String {
+    format!("Cannot read {}: {}", path.display(), e)
+}
+
+/// A table in an SQLite database, written `sqlite://<file>#<table>`.
+#[derive(Debug, Clone)]
+pub struct DbSource {
+    pub path: PathBuf,
+    pub table: String,
+}
+
+impl DbSource {
+    pub const SCHEME: &'static str = "sqlite://";
+
+    /// `None` when `source` is a plain file path.
+    pub fn parse(source: &str) -> Option<Result<Self, String>> {
+        let rest = source.strip_prefix(Self::SCHEME)?;
+        Some(match rest.rsplit_once('#') {
+            Some((path, table)) if !path.is_empty() && !table.is_empty() => {
+                Ok(Self { path: PathBuf::from(path), table: table.to_string() })
+            }
+            _ => Err(format!("{} is not a database source; write sqlite://<file>#<table>.", source)),
+        })
+    }
+
+    /// Checks the database opens and has the table.
+    pub fn validate(&self) -> Result<(), BackendError> {
+        let tables = list_db_tables(&self.path).map_err(BackendError::invalid_input)?;
+        if !tables.iter().any(|t| t.name == self.table) {
+            return Err(BackendError::invalid_input(format!(
+                "{} has no table or view named {}.",
+                self.path.display(), self.table
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A table or view of an SQLite database, as listed by `list_db_tables`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DbTable {
+    pub name: String,
+    /// `table` or `view`.
+    pub kind: String,
+    pub columns: Vec<String>,
+    /// Null for views, which would have to be run to count.
+    pub rows: Option<u64>,
+    /// The source to pass to the tabular commands.
+    pub source: String,
+}
+
+fn open_db(path: &Path) -> Result<rusqlite::Connection, String> {
+    if !path.is_file() {
+        return Err(format!("{} does not exist or is not a file.", path.display()));
+    }
+    rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cannot open {}: {}", path.display(), e))
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// User tables and views of an SQLite database, by name.
+pub fn list_db_tables(path: &Path) -> Result<Vec<DbTable>, String> {
+    let conn = open_db(path)?;
+    let db_error = |e: rusqlite::Error| format!("Cannot read {}: {}", path.display(), e);
+    let mut statement = conn
+        .prepare("SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .map_err(db_error)?;
+    let entries: Vec<(String, String)> = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(db_error)?
+        .collect::<Result<_, _>>()
+        .map_err(db_error)?;
+
+    let mut tables = Vec::with_capacity(entries.len());
+    for (name, kind) in entries {
+        let quoted = quote_identifier(&name);
+        let columns = conn
+            .prepare(&format!("SELECT * FROM {} LIMIT 0", quoted))
+            .map(|s| s.column_names().into_iter().map(String::from).collect())
+            .map_err(db_error)?;
+        let rows = if kind == "table" {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", quoted), [], |row| row.get::<_, i64>(0))
+                .ok()
+                .map(|n| n as u64)
+        } else {
+            None
+        };
+        tables.push(DbTable {
+            source: format!("{}{}#{}", DbSource::SCHEME, path.display(), name),
+            name,
+            kind,
+            columns,
+            rows,
+        });
+    }
+    Ok(tables)
+}
+
+/// Loads a whole table; SQLite has no columnar scan Polars could use.
+fn read_db_table(source: &DbSource) -> Result<DataFrame, String> {
+    use rusqlite::types::ValueRef;
+    source.validate()?;
+    let conn = open_db(&source.path)?;
+    let db_error = |e: rusqlite::Error| format!("Cannot read {}: {}", source.path.display(), e);
+    let mut statement =
+        conn.prepare(&format!("SELECT * FROM {}", quote_identifier(&source.table))).map_err(db_error)?;
+    let header: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+    let width = header.len();
+    let body: Vec<Vec<Data>> = statement
+        .query_map([], |row| {
+            (0..width)
+                .map(|i| {
+                    Ok(match row.get_ref(i)? {
+                        ValueRef::Null => Data::Empty,
+                        ValueRef::Integer(n) => Data::Int(n),
+                        ValueRef::Real(f) => Data::Float(f),
+                        ValueRef::Text(text) => Data::String(String::from_utf8_lossy(text).to_string()),
+                        ValueRef::Blob(blob) => Data::String(format!("<{} bytes>", blob.len())),
+                    })
+                })
+                .collect()
+        })
+        .map_err(db_error)?
+        .collect::<Result<_, _>>()
+        .map_err(db_error)?;
+    frame_from_cells(&header, &body).map_err(|e| polars_error(&source.path, e))
+}
+
+/// A lazy scan of a table file or `sqlite://` source; nothing is read until
+/// it is collected, except for Excel sheets and database tables, which are
+/// loaded whole.
+pub fn scan(file: &str) -> Result<(LazyFrame, TableFormat), String> {
+    if let Some(source) = DbSource::parse(file) {
+        return Ok((read_db_table(&source?)?.lazy(), TableFormat::Sqlite));
+    }
+    let path = Path::new(file);
+    if !path.is_file() {
+        return Err(format!("{} does not exist or is not a file.", path.display()));
+    }
+    let format = TableFormat::detect(path)?;
+    let frame = match format {
+        TableFormat::Csv | TableFormat::Tsv => LazyCsvReader::new(path)
+            .with_has_header(true)
+            .with_separator(if format == TableFormat::Tsv { b'\t' } else { b',' })
+            .with_infer_schema_length(Some(INFER_ROWS))
+            .finish(),
+        TableFormat::Parquet => LazyFrame::scan_parquet(path, ScanArgsParquet::default()),
+        TableFormat::Feather => LazyFrame::scan_ipc(path, ScanArgsIpc::default()),
+        TableFormat::Excel => return Ok((read_excel(path)?.lazy(), format)),
+        TableFormat::Sqlite => unreachable!("detect never returns Sqlite"),
+    };
+    Ok((frame.map_err(|e| polars_error(path, e))?, format))
+}
+
+fn count(frame: LazyFrame, path: &Path) -> Result<u64, String> {
+    let counts =
+        frame.select([len().alias("rows")]).with_streaming(true).collect().map_err(|e| polars_error(path, e))?;
+    Ok(counts.column("rows").ok().and_then(|c| c.get(0).ok()).and_then(|v| v.extract::<u64>()).unwrap_or(0))
+}
+
+/// Rows in a table, counted in a streaming pass for files larger than memory.
+pub fn count_rows(file: &str) -> Result<u64, String> {
+    let (frame, _) = scan(file)?;
+    count(frame, Path::new(file))
+}
+
+/// Column type of Excel or database cells: the narrowest that holds every non-empty one.
+#[derive(PartialEq, PartialOrd)]
+enum CellKind {
+    Bool,
+    Int,
+    Float,
+    Text,
+}
+
+fn cell_kind(cell: &Data) -> Option<CellKind> {
+    match cell {
+        Data::Empty => None,
+        Data::Bool(_) => Some(CellKind::Bool),
+        Data::Int(_) => Some(CellKind::Int),
+        Data::Float(f) if f.fract() == 0.0 && f.abs() < 9e15 => Some(CellKind::Int),
+        Data::Float(_) => Some(CellKind::Float),
+        _ => Some(CellKind::Text),
+    }
+}
+
+/// The first sheet, with its first row as the header.
+fn read_excel(path: &Path) -> Result<DataFrame, String> {
+    let mut workbook = open_workbook_auto(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let sheet = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| format!("{} has no sheets.", path.display()))?
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut rows = sheet.rows();
+    let header: Vec<String> = rows.next().map(|row| row.iter().map(|c| c.to_string()).collect()).unwrap_or_default();
+    let body: Vec<&[Data]> = rows.collect();
+    frame_from_cells(&header, &body).map_err(|e| polars_error(path, e))
+}
+
+/// Builds typed columns from rows of cells, as pandas would infer them.
+fn frame_from_cells<R: AsRef<[Data]>>(header: &[String], body: &[R]) -> PolarsResult<DataFrame> {
+    let mut columns = Vec::with_capacity(header.len());
+    for (index, name) in header.iter().enumerate() {
+        let name = if name.trim().is_empty() { format!("column_{}", index + 1) } else { name.clone() };
+        let cells: Vec<&Data> = body.iter().map(|row| row.as_ref().get(index).unwrap_or(&Data::Empty)).collect();
+        // Booleans mixed with numbers are text, as in pandas.
+        let kinds: Vec<CellKind> = cells.iter().filter_map(|c| cell_kind(c)).collect();
+        let kind = if kinds.iter().all(|k| *k == CellKind::Bool) && !kinds.is_empty() {
+            CellKind::Bool
+        } else if kinds.iter().any(|k| *k == CellKind::Bool) {
+            CellKind::Text
+        } else {
+            kinds.into_iter().fold(CellKind::Int, |a, b| if b > a { b } else { a })
+        };
+        let series = match kind {
+            CellKind::Bool => Series::new(
+                name.as_str().into(),
+                cells.iter().map(|c| if let Data::Bool(b) = c { Some(*b) } else { None }).collect::<Vec<_>>(),
+            ),
+            // Integers stay i64: going through f64 corrupts values above 2^53, such as IDs.
+            CellKind::Int => Series::new(
+                name.as_str().into(),
+                cells
+                    .iter()
+                    .map(|c| match c {
+                        Data::Int(i) => Some(*i),
+                        Data::Float(f) => Some(*f as i64),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            CellKind::Float => Series::new(name.as_str().into(), cells.iter().map(|c| c.as_f64()).collect::<Vec<_>>()),
+            CellKind::Text => Series::new(
+                name.as_str().into(),
+                cells.iter().map(|c| (**c != Data::Empty).then(|| c.to_string())).collect::<Vec<_>>(),
+            ),
+        };
+        columns.push(series);
+    }
+    DataFrame::new(columns)
+}
+
+fn finite(value: f64) -> Value {
+    serde_json::Number::from_f64(value).map(Value::Number).unwrap_or(Value::Null)
+}
+
+/// A cell as JSON; types JSON lacks (dates, times) are written as text.
+pub fn json_value(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(b) => Value::Bool(b),
+        AnyValue::String(s) => Value::String(s.to_string()),
+        AnyValue::StringOwned(s) => Value::String(s.to_string()),
+        AnyValue::Float32(f) => finite(f as f64),
+        AnyValue::Float64(f) => finite(f),
+        v if v.dtype().is_unsigned_integer() => v.extract::<u64>().map(Value::from).unwrap_or(Value::Null),
+        v if v.dtype().is_integer() => v.extract::<i64>().map(Value::from).unwrap_or(Value::Null),
+        v => Value::String(v.to_string()),
+    }
+}
+
+/// Rows `offset..offset + rows` of `file` with column types and missing
+/// counts for the whole table. Only the page is materialized; the counts
+/// come from a streaming pass, so multi-gigabyte files stay out of memory.
+pub fn preview(file: &str, rows: Option<usize>, offset: Option<usize>) -> Result<TablePage, String> {
+    let rows = rows.unwrap_or(DEFAULT_PAGE_ROWS);
+    if rows == 0 || rows > MAX_PAGE_ROWS {
+        return Err(format!("Pages hold 1 to {} rows.", MAX_PAGE_ROWS));
+    }
+    let offset = offset.unwrap_or(0);
+    let (mut frame, format) = scan(file)?;
+    let path = Path::new(file);
+    let schema = frame.collect_schema().map_err(|e| polars_error(path, e))?;
+
+    let total = count(frame.clone(), path)?;
+    let nulls =
+        frame.clone().select([all().null_count()]).with_streaming(true).collect().map_err(|e| polars_error(path, e))?;
+    let page = frame.slice(offset as i64, rows as IdxSize).collect().map_err(|e| polars_error(path, e))?;
+
+    let columns: Vec<String> = schema.iter_names().map(|name| name.to_string()).collect();
+    let data = (0..page.height())
+        .map(|row| {
+            page.get_columns().iter().map(|column| column.get(row).map(json_value).unwrap_or(Value::Null)).collect()
+        })
+        .collect();
+    let missing = nulls
+        .get_columns()
+        .iter()
+        .map(|column| {
+            let count = column.get(0).ok().and_then(|v| v.extract::<u64>()).unwrap_or(0);
+            (column.name().to_string(), count)
+        })
+        .collect();
+    Ok(TablePage {
+        dtypes: schema.iter().map(|(name, dtype)| (name.to_string(), dtype.to_string())).collect(),
+        shape: (total, columns.len() as u64),
+        columns,
+        data,
+        offset,
+        format,
+        missing,
+    })
+}
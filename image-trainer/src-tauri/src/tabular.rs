@@ -0,0 +1,263 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::BackendError;
+use crate::events;
+use crate::jobs::new_id;
+use crate::python::{
+    backend_script, parse_output, run_python, run_python_observed, run_python_quick, run_python_streaming,
+};
+use crate::tables::{self, DbSource, TableFormat};
+
+const SCRIPT: &str = "tabular_processor.py";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FillMethod {
+    /// Numeric columns only.
+    Mean,
+    /// Numeric columns only.
+    Median,
+    Mode,
+    Zero,
+}
+
+/// A cleaning step applied by tabular_processor.py, passed to it as `--params`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum TabularOperation {
+    DropMissing,
+    FillMissing { method: FillMethod },
+    LabelEncode { columns: Vec<String> },
+    OneHotEncode {
+        columns: Vec<String>,
+        #[serde(default)]
+        drop_first: bool,
+    },
+}
+
+/// `load` previews a file; `process` applies `operation` and saves the result
+/// to `out`, or over `file` when no output is given. Either may also be an
+/// SQLite table written `sqlite://<file>#<table>`.
+///
+/// With `chunk_rows`, a CSV or TSV file is processed that many rows at a time
+/// and written out chunk by chunk, so files larger than memory can be cleaned.
+/// Means, modes and encodings still cover the whole file, at the cost of a
+/// second pass; the median cannot be computed this way.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum TabularRequest {
+    Load {
+        file: String,
+    },
+    Process {
+        file: String,
+        operation: TabularOperation,
+        out: Option<String>,
+        #[serde(default)]
+        chunk_rows: Option<usize>,
+    },
+}
+
+impl TabularRequest {
+    pub fn file(&self) -> &str {
+        match self {
+            Self::Load { file } | Self::Process { file, .. } => file,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), BackendError> {
+        let file = self.file();
+        match DbSource::parse(file) {
+            Some(source) => source.map_err(BackendError::invalid_input)?.validate()?,
+            None if !std::path::Path::new(file).is_file() => {
+                return Err(BackendError::invalid_input(format!("{} does not exist or is not a file.", file)));
+            }
+            None => {
+                TableFormat::detect(std::path::Path::new(file)).map_err(BackendError::invalid_input)?;
+            }
+        }
+        if let Self::Process { operation, out, chunk_rows, .. } = self {
+            match operation {
+                TabularOperation::LabelEncode { columns } | TabularOperation::OneHotEncode { columns, .. }
+                    if columns.is_empty() =>
+                {
+                    return Err(BackendError::invalid_input("Choose at least one column to encode."));
+                }
+                _ => {}
+            }
+            if out.as_deref().is_some_and(|o| o.trim().is_empty()) {
+                return Err(BackendError::invalid_input("The output path is empty."));
+            }
+            if let Some(chunk_rows) = chunk_rows {
+                validate_chunked(file, out.as_deref(), operation, *chunk_rows)?;
+            }
+            // The output format follows its extension, so the input can be converted on the way.
+            // A database target replaces the table, creating it if needed.
+            let target = out.as_deref().unwrap_or(file);
+            if let Some(source) = DbSource::parse(target) {
+                source.map_err(BackendError::invalid_input)?;
+                return Ok(());
+            }
+            let target = std::path::Path::new(target);
+            match TableFormat::from_extension(target) {
+                None => {
+                    return Err(BackendError::invalid_input(
+                        "The output must end in .csv, .tsv, .parquet, .feather or .xlsx.",
+                    ))
+                }
+                Some(_) if target.extension().is_some_and(|e| e.eq_ignore_ascii_case("xls")) => {
+                    return Err(BackendError::invalid_input("Saving as .xls is not supported; use .xlsx."))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn chunked(&self) -> bool {
+        matches!(self, Self::Process { chunk_rows: Some(_), .. })
+    }
+
+    fn args(&self, script: String) -> Result<Vec<String>, BackendError> {
+        let mut args = vec![script];
+        match self {
+            Self::Load { file } => args.extend(["--action", "load", "--file", file.as_str()].map(String::from)),
+            Self::Process { file, operation, out, chunk_rows } => {
+                let params = serde_json::to_string(operation).map_err(|e| e.to_string())?;
+                args.extend(["--action", "process", "--file", file.as_str(), "--params", params.as_str()].map(String::from));
+                if let Some(out) = out {
+                    args.extend(["--out", out.as_str()].map(String::from));
+                }
+                if let Some(chunk_rows) = chunk_rows {
+                    args.extend(["--chunk_rows".to_string(), chunk_rows.to_string()]);
+                }
+            }
+        }
+        Ok(args)
+    }
+}
+
+fn validate_chunked(
+    file: &str,
+    out: Option<&str>,
+    operation: &TabularOperation,
+    chunk_rows: usize,
+) -> Result<(), BackendError> {
+    if chunk_rows == 0 {
+        return Err(BackendError::invalid_input("Chunks must hold at least one row."));
+    }
+    let text = |format: Option<TableFormat>| matches!(format, Some(TableFormat::Csv | TableFormat::Tsv));
+    if DbSource::parse(file).is_some() || !text(TableFormat::detect(std::path::Path::new(file)).ok()) {
+        return Err(BackendError::invalid_input("Only CSV and TSV files can be processed in chunks."));
+    }
+    let target = out.unwrap_or(file);
+    if DbSource::parse(target).is_some() || !text(TableFormat::from_extension(std::path::Path::new(target))) {
+        return Err(BackendError::invalid_input("Chunked output must be written to a .csv or .tsv file."));
+    }
+    if matches!(operation, TabularOperation::FillMissing { method: FillMethod::Median }) {
+        return Err(BackendError::invalid_input(
+            "The median needs every row at once; use mean or mode when processing in chunks.",
+        ));
+    }
+    Ok(())
+}
+
+/// Published as `tabular://progress` after each chunk of a chunked run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TabularProgress {
+    /// `scanning` while whole-file statistics are gathered, then `writing`.
+    stage: String,
+    chunk: u64,
+    rows_done: u64,
+    rows_total: Option<u64>,
+    /// 0 to 1 over both passes, when the row count is known.
+    progress: Option<f64>,
+}
+
+/// The first rows of a table, with per-column types and missing counts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TablePreview {
+    pub columns: Vec<String>,
+    /// Up to 10 rows; missing cells are `null`.
+    pub data: Vec<Vec<serde_json::Value>>,
+    /// Rows and columns of the whole table.
+    pub shape: (u64, u64),
+    /// pandas dtype per column.
+    pub dtypes: BTreeMap<String, String>,
+    pub missing: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TabularResponse {
+    #[serde(flatten)]
+    pub preview: TablePreview,
+    /// The file the preview was read from or the processed table saved to.
+    pub file_path: String,
+    /// What `process` did.
+    pub message: Option<String>,
+}
+
+/// tabular_processor.py reports its own failures on stdout with exit code 0.
+#[derive(Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ScriptResult {
+    Success {
+        #[serde(flatten)]
+        preview: TablePreview,
+        loaded_path: Option<String>,
+        file_path: Option<String>,
+        message: Option<String>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Runs the request through tabular_processor.py. With a `job_id`, output is
+/// also streamed line by line as `python://stdout` / `python://stderr`.
+pub async fn run(
+    app: &tauri::AppHandle,
+    request: &TabularRequest,
+    job_id: Option<&str>,
+) -> Result<TabularResponse, BackendError> {
+    request.validate()?;
+    let mut args = request.args(backend_script(app, SCRIPT)?)?;
+    if request.chunked() {
+        // Counted natively so progress can be a fraction; the script cannot know the total up front.
+        let file = request.file().to_string();
+        if let Ok(Ok(total)) = tauri::async_runtime::spawn_blocking(move || tables::count_rows(&file)).await {
+            args.extend(["--total_rows".to_string(), total.to_string()]);
+        }
+    }
+    let args_ref: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = match job_id {
+        _ if request.chunked() => {
+            let job_id = job_id.map(str::to_string).unwrap_or_else(|| new_id("tabular"));
+            run_python_observed(app, &job_id, &args_ref, |line| {
+                let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+                    return;
+                };
+                if value["status"] == "progress" {
+                    if let Ok(progress) = serde_json::from_value::<TabularProgress>(value) {
+                        events::publish(app, "tabular://progress", Some(&job_id), progress);
+                    }
+                }
+            })
+            .await?
+        }
+        Some(job_id) => run_python_streaming(app, job_id, &args_ref).await?,
+        // Loading a preview is quick enough for the embedded interpreter.
+        None if matches!(request, TabularRequest::Load { .. }) => run_python_quick(app, &args_ref).await?,
+        None => run_python(app, &args_ref).await?,
+    };
+    match parse_output(SCRIPT, &output)? {
+        ScriptResult::Success { preview, loaded_path, file_path, message } => Ok(TabularResponse {
+            preview,
+            file_path: file_path.or(loaded_path).unwrap_or_else(|| request.file().to_string()),
+            message,
+        }),
+        ScriptResult::Error { message } => Err(BackendError::ScriptFailed { message, code: None, stderr: String::new() }),
+    }
+}
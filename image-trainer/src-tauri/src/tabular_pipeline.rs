@@ -0,0 +1,256 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::error::BackendError;
+use crate::jobs::unix_now;
+use crate::paths;
+use crate::tables::DbSource;
+use crate::tabular::{self, TabularOperation, TabularRequest, TabularResponse};
+
+/// Steps kept per session; undoing further back than this is not possible.
+const MAX_STEPS: usize = 50;
+const JOURNAL: &str = "pipeline.json";
+
+/// One `process` run of a session, with what is needed to undo and redo it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PipelineStep {
+    pub id: u64,
+    pub operation: TabularOperation,
+    pub file: String,
+    /// Where the result was saved; null when `file` was overwritten.
+    pub out: Option<String>,
+    pub chunk_rows: Option<usize>,
+    pub applied_at: u64,
+    pub message: Option<String>,
+    /// Copy of the target taken before the step, restored by undo; null when
+    /// the step created the target. For `sqlite://` targets it is the whole database.
+    pub snapshot: Option<String>,
+}
+
+impl PipelineStep {
+    fn target(&self) -> &str {
+        self.out.as_deref().unwrap_or(&self.file)
+    }
+
+    fn request(&self) -> TabularRequest {
+        TabularRequest::Process {
+            file: self.file.clone(),
+            operation: self.operation.clone(),
+            out: self.out.clone(),
+            chunk_rows: self.chunk_rows,
+        }
+    }
+}
+
+/// The transformations applied in one tabular session, oldest first, and
+/// those undone since, most recently undone last.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Pipeline {
+    pub session_id: String,
+    /// The table the session was started on by `load`.
+    pub source: Option<String>,
+    pub steps: Vec<PipelineStep>,
+    pub undone: Vec<PipelineStep>,
+    pub updated_at: u64,
+}
+
+impl Pipeline {
+    fn new(session_id: &str) -> Self {
+        Self { session_id: session_id.to_string(), ..Default::default() }
+    }
+
+    fn next_id(&self) -> u64 {
+        self.steps.iter().chain(&self.undone).map(|s| s.id + 1).max().unwrap_or(1)
+    }
+}
+
+/// The file holding a target's data: the database for `sqlite://` tables.
+fn target_file(target: &str) -> PathBuf {
+    match DbSource::parse(target) {
+        Some(Ok(source)) => source.path,
+        _ => PathBuf::from(target),
+    }
+}
+
+fn remove_snapshot(step: &PipelineStep) {
+    if let Some(snapshot) = &step.snapshot {
+        let _ = std::fs::remove_file(snapshot);
+    }
+}
+
+fn validate_session(session_id: &str) -> Result<(), BackendError> {
+    let valid = !session_id.is_empty()
+        && session_id.len() <= 64
+        && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(BackendError::invalid_input("Session ids use letters, digits, '-' and '_' only."))
+    }
+}
+
+/// Pipelines by session, each journaled with its snapshots under
+/// `tabular_sessions/<session>` in the data folder so they survive a restart.
+pub struct TabularPipelines {
+    dir: PathBuf,
+    sessions: Mutex<HashMap<String, Pipeline>>,
+}
+
+impl TabularPipelines {
+    pub fn load(app: &tauri::AppHandle) -> Result<Self, String> {
+        let dir = paths::data_file(app, "tabular_sessions")?;
+        let mut sessions = HashMap::new();
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let journal = entry.path().join(JOURNAL);
+            let pipeline =
+                std::fs::read_to_string(&journal).ok().and_then(|raw| serde_json::from_str::<Pipeline>(&raw).ok());
+            if let Some(pipeline) = pipeline {
+                sessions.insert(pipeline.session_id.clone(), pipeline);
+            }
+        }
+        Ok(Self { dir, sessions: Mutex::new(sessions) })
+    }
+
+    fn session_dir(&self, session_id: &str) -> PathBuf {
+        self.dir.join(session_id)
+    }
+
+    fn save(&self, pipeline: &mut Pipeline) -> Result<(), String> {
+        pipeline.updated_at = unix_now();
+        let dir = self.session_dir(&pipeline.session_id);
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let raw = serde_json::to_string_pretty(pipeline).map_err(|e| e.to_string())?;
+        std::fs::write(dir.join(JOURNAL), raw).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, session_id: &str) -> Pipeline {
+        self.sessions.lock().unwrap().get(session_id).cloned().unwrap_or_else(|| Pipeline::new(session_id))
+    }
+
+    /// Runs `request` through tabular_processor.py, recording `process` runs as
+    /// steps of the session. Loading another table starts the session over.
+    pub async fn run(
+        &self,
+        app: &tauri::AppHandle,
+        session_id: &str,
+        request: &TabularRequest,
+        job_id: Option<&str>,
+    ) -> Result<TabularResponse, BackendError> {
+        validate_session(session_id)?;
+        let TabularRequest::Process { file, operation, out, chunk_rows } = request else {
+            let response = tabular::run(app, request, job_id).await?;
+            let mut sessions = self.sessions.lock().unwrap();
+            let pipeline = sessions.entry(session_id.to_string()).or_insert_with(|| Pipeline::new(session_id));
+            if pipeline.source.as_deref() != Some(request.file()) {
+                pipeline.steps.iter().chain(&pipeline.undone).for_each(remove_snapshot);
+                *pipeline = Pipeline { source: Some(request.file().to_string()), ..Pipeline::new(session_id) };
+                self.save(pipeline)?;
+            }
+            return Ok(response);
+        };
+        request.validate()?;
+
+        let id = self.get(session_id).next_id();
+        let mut step = PipelineStep {
+            id,
+            operation: operation.clone(),
+            file: file.clone(),
+            out: out.clone(),
+            chunk_rows: *chunk_rows,
+            applied_at: unix_now(),
+            message: None,
+            snapshot: None,
+        };
+        step.snapshot = self.take_snapshot(session_id, &step)?;
+        let response = match tabular::run(app, request, job_id).await {
+            Ok(response) => response,
+            Err(e) => {
+                remove_snapshot(&step);
+                return Err(e);
+            }
+        };
+        step.message = response.message.clone();
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let pipeline = sessions.entry(session_id.to_string()).or_insert_with(|| Pipeline::new(session_id));
+        // A new step replaces whatever could have been redone.
+        pipeline.undone.drain(..).for_each(|s| remove_snapshot(&s));
+        pipeline.steps.push(step);
+        if pipeline.steps.len() > MAX_STEPS {
+            let dropped: Vec<_> = pipeline.steps.drain(..pipeline.steps.len() - MAX_STEPS).collect();
+            dropped.iter().for_each(remove_snapshot);
+        }
+        self.save(pipeline)?;
+        Ok(response)
+    }
+
+    fn take_snapshot(&self, session_id: &str, step: &PipelineStep) -> Result<Option<String>, BackendError> {
+        let target = target_file(step.target());
+        if !target.is_file() {
+            return Ok(None);
+        }
+        let dir = self.session_dir(session_id);
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let snapshot = dir.join(format!("step_{}.bak", step.id));
+        std::fs::copy(&target, &snapshot)
+            .map_err(|e| format!("Cannot back up {} before changing it: {}", target.display(), e))?;
+        Ok(Some(snapshot.to_string_lossy().to_string()))
+    }
+
+    /// Puts the target of the last step back as it was before the step.
+    pub fn undo(&self, session_id: &str) -> Result<Pipeline, BackendError> {
+        validate_session(session_id)?;
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(pipeline) = sessions.get_mut(session_id) else {
+            return Err(BackendError::invalid_input("There is nothing to undo."));
+        };
+        let Some(step) = pipeline.steps.last().cloned() else {
+            return Err(BackendError::invalid_input("There is nothing to undo."));
+        };
+        let target = target_file(step.target());
+        match &step.snapshot {
+            Some(snapshot) => std::fs::copy(snapshot, &target)
+                .map(|_| ())
+                .map_err(|e| format!("Cannot restore {}: {}", target.display(), e))?,
+            None if target.exists() => {
+                std::fs::remove_file(&target).map_err(|e| format!("Cannot remove {}: {}", target.display(), e))?
+            }
+            None => {}
+        }
+        pipeline.steps.pop();
+        pipeline.undone.push(step);
+        self.save(pipeline)?;
+        Ok(pipeline.clone())
+    }
+
+    /// Runs the most recently undone step again.
+    pub async fn redo(&self, app: &tauri::AppHandle, session_id: &str) -> Result<Pipeline, BackendError> {
+        validate_session(session_id)?;
+        let step = self
+            .get(session_id)
+            .undone
+            .last()
+            .cloned()
+            .ok_or_else(|| BackendError::invalid_input("There is nothing to redo."))?;
+        // Snapshot the target again, in case it changed since the undo.
+        let snapshot = self.take_snapshot(session_id, &step)?;
+        if let Err(e) = tabular::run(app, &step.request(), None).await {
+            if let Some(snapshot) = &snapshot {
+                let _ = std::fs::copy(snapshot, target_file(step.target()));
+            }
+            return Err(e);
+        }
+        let mut sessions = self.sessions.lock().unwrap();
+        let pipeline = sessions.entry(session_id.to_string()).or_insert_with(|| Pipeline::new(session_id));
+        if let Some(mut step) = pipeline.undone.pop() {
+            step.snapshot = snapshot;
+            step.applied_at = unix_now();
+            pipeline.steps.push(step);
+        }
+        self.save(pipeline)?;
+        Ok(pipeline.clone())
+    }
+}
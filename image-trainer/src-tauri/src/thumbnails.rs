@@ -0,0 +1,147 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::image_meta;
+use crate::paths;
+
+pub const DEFAULT_SIZE: u32 = 256;
+const SIZES: std::ops::RangeInclusive<u32> = 16..=1024;
+/// Least recently used thumbnails are deleted beyond this.
+const MAX_CACHE_BYTES: u64 = 512 * 1024 * 1024;
+/// New thumbnails written between checks of the cache size.
+const EVICT_EVERY: usize = 64;
+
+/// A cached thumbnail; load `path` with `convertFileSrc`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Thumbnail {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// Served from the cache rather than generated now.
+    pub cached: bool,
+}
+
+/// Content hashes of source files, so a cache hit costs a `stat` instead of
+/// reading the file. Keyed by path, size and modification time.
+type HashMemo = HashMap<(PathBuf, u64, u128), String>;
+
+/// Managed state for the on-disk thumbnail cache. Thumbnails are keyed by the
+/// source's content hash and size, so renamed or copied files share one.
+#[derive(Default)]
+pub struct ThumbnailCache {
+    hashes: Mutex<HashMemo>,
+    written: AtomicUsize,
+}
+
+fn cache_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::cache_dir(app)?.join("thumbnails"))
+}
+
+fn content_hash(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+impl ThumbnailCache {
+    fn hash(&self, path: &Path) -> Result<String, String> {
+        let meta = std::fs::metadata(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let modified = meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_nanos());
+        let key = (path.to_path_buf(), meta.len(), modified.unwrap_or_default());
+        if let Some(hash) = self.hashes.lock().unwrap().get(&key) {
+            return Ok(hash.clone());
+        }
+        let hash = content_hash(path)?;
+        self.hashes.lock().unwrap().insert(key, hash.clone());
+        Ok(hash)
+    }
+
+    /// A JPEG of `path` whose longer side is at most `size` pixels, from the
+    /// cache when it was made before.
+    pub fn get(&self, app: &tauri::AppHandle, path: &Path, size: Option<u32>) -> Result<Thumbnail, String> {
+        let size = size.unwrap_or(DEFAULT_SIZE);
+        if !SIZES.contains(&size) {
+            return Err(format!("The thumbnail size must be between {} and {}.", SIZES.start(), SIZES.end()));
+        }
+        let dir = cache_dir(app)?;
+        let target = dir.join(format!("{}_{}.jpg", self.hash(path)?, size));
+        if let Ok((width, height)) = image::image_dimensions(&target) {
+            // Marks it as recently used for eviction.
+            if let Ok(file) = std::fs::File::options().write(true).open(&target) {
+                let _ = file.set_modified(SystemTime::now());
+            }
+            return Ok(Thumbnail { path: target.to_string_lossy().to_string(), width, height, cached: true });
+        }
+
+        let image = image_meta::open_oriented(path)?;
+        let thumbnail = image.thumbnail(size, size).into_rgb8();
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        // Written under a temporary name so a concurrent request never reads half a file.
+        let partial = target.with_extension("jpg.part");
+        thumbnail
+            .save_with_format(&partial, image::ImageFormat::Jpeg)
+            .and_then(|_| std::fs::rename(&partial, &target).map_err(image::ImageError::IoError))
+            .map_err(|e| format!("Cannot write thumbnail: {}", e))?;
+
+        if (self.written.fetch_add(1, Ordering::Relaxed) + 1) % EVICT_EVERY == 0 {
+            evict(&dir, MAX_CACHE_BYTES);
+        }
+        Ok(Thumbnail {
+            path: target.to_string_lossy().to_string(),
+            width: thumbnail.width(),
+            height: thumbnail.height(),
+            cached: false,
+        })
+    }
+
+    /// Deletes every cached thumbnail; returns the bytes freed.
+    pub fn clear(&self, app: &tauri::AppHandle) -> Result<u64, String> {
+        let dir = cache_dir(app)?;
+        let freed = evict(&dir, 0);
+        self.hashes.lock().unwrap().clear();
+        Ok(freed)
+    }
+}
+
+/// Deletes the least recently used thumbnails until the cache holds at most
+/// `limit` bytes; returns the bytes freed.
+fn evict(dir: &Path, limit: u64) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file().then(|| (meta.modified().unwrap_or(UNIX_EPOCH), meta.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort();
+    let mut freed = 0;
+    for (_, len, path) in files {
+        if total <= limit {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= len;
+            freed += len;
+        }
+    }
+    freed
+}
@@ -0,0 +1,675 @@
+use crate::architectures::ArchitectureDescriptor;
+use crate::augmentation::AugmentationPolicy;
+use crate::checkpoints::CheckpointPolicy;
+use crate::dataset;
+use crate::error::BackendError;
+use crate::gpu_monitor;
+use crate::job_env;
+use crate::jobs::JobLimits;
+use crate::metrics::MetricRef;
+use crate::run_manifest;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Training configuration passed to script.py.
+/// Defaults mirror the argparse defaults in the script.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TrainingConfig {
+    pub path: String,
+    pub save_path: Option<String>,
+    pub model: String,
+    /// Descriptor of a registered custom architecture; filled in by
+    /// `architectures::resolve` when `model` is not a built-in.
+    pub architecture: Option<ArchitectureDescriptor>,
+    pub epochs: u32,
+    pub batch_size: u32,
+    pub learning_rate: f64,
+    /// Number of mini-batches whose gradients are summed before each optimizer step.
+    pub accumulation_steps: u32,
+    pub num_workers: i32,
+    pub patience: u32,
+    pub freeze_policy: FreezePolicy,
+    pub lr_schedule: LrSchedule,
+    pub augmentation: AugmentationPolicy,
+    /// Per-class loss weights, or `"auto"` to derive them from the dataset's class balance.
+    pub class_weights: Option<ClassWeights>,
+    /// Plugin metrics to compute on the test split, as `metric` or `plugin/metric`.
+    pub custom_metrics: Vec<String>,
+    /// `custom_metrics` resolved by `metrics::resolve`.
+    pub metric_plugins: Vec<MetricRef>,
+    /// Seeds every RNG and forces deterministic cuDNN kernels.
+    pub reproducible: bool,
+    /// Seed for reproducible runs, below 2^32; generated by `resolve_seed` when left empty.
+    pub seed: Option<u64>,
+    pub experiment_id: Option<String>,
+    pub resume: Option<String>,
+    pub zip_dataset: bool,
+    pub only_zip: bool,
+    /// Environment variables for this run, on top of the global defaults
+    /// from `job_env.json`.
+    pub env: BTreeMap<String, String>,
+    /// GPU(s) to train on, as `list_gpu_devices` indexes them, or `cpu`;
+    /// turned into `CUDA_VISIBLE_DEVICES` in `env` by `prepare`.
+    pub device: Option<String>,
+    /// Time and memory the run may use before the supervisor stops it.
+    pub limits: JobLimits,
+    /// Per-epoch checkpoints and how many of them are kept.
+    pub checkpoints: CheckpointPolicy,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            save_path: None,
+            model: "resnet18".to_string(),
+            architecture: None,
+            epochs: 5,
+            batch_size: 32,
+            learning_rate: 0.001,
+            accumulation_steps: 1,
+            num_workers: -1,
+            patience: 5,
+            freeze_policy: FreezePolicy::default(),
+            lr_schedule: LrSchedule::default(),
+            augmentation: AugmentationPolicy::default(),
+            class_weights: None,
+            custom_metrics: Vec::new(),
+            metric_plugins: Vec::new(),
+            reproducible: false,
+            seed: None,
+            experiment_id: None,
+            resume: None,
+            zip_dataset: false,
+            only_zip: false,
+            env: BTreeMap::new(),
+            device: None,
+            limits: JobLimits::default(),
+            checkpoints: CheckpointPolicy::default(),
+        }
+    }
+}
+
+/// Which layers train and when. The model factory freezes everything except the
+/// classification head by default, which corresponds to `Frozen`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FreezePolicy {
+    /// Backbone stays frozen for the whole run; only the head is trained.
+    #[default]
+    Frozen,
+    /// All layers are trainable from the first epoch.
+    Full,
+    /// Backbone frozen for `freeze_epochs`, then unfrozen and trained at
+    /// `learning_rate * unfreeze_lr_factor` while the head keeps the base rate.
+    Staged {
+        freeze_epochs: u32,
+        #[serde(default = "default_unfreeze_lr_factor")]
+        unfreeze_lr_factor: f64,
+    },
+}
+
+fn default_unfreeze_lr_factor() -> f64 {
+    0.1
+}
+
+impl FreezePolicy {
+    /// Value for script.py's `--freeze_mode`.
+    pub fn mode(&self) -> &'static str {
+        match self {
+            FreezePolicy::Frozen => "frozen",
+            FreezePolicy::Full => "full",
+            FreezePolicy::Staged { .. } => "staged",
+        }
+    }
+}
+
+/// Learning-rate schedule applied on top of `learning_rate`. The script computes
+/// the multiplier per optimizer step and reports the resulting LR with each epoch.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LrSchedule {
+    /// Flat LR after an optional linear warmup.
+    Constant {
+        #[serde(default)]
+        warmup_epochs: u32,
+    },
+    /// Cosine decay from `learning_rate` down to `learning_rate * min_lr_ratio`.
+    Cosine {
+        #[serde(default)]
+        warmup_epochs: u32,
+        #[serde(default)]
+        min_lr_ratio: f64,
+    },
+    /// Multiplies the LR by `gamma` every `step_size` epochs.
+    Step { step_size: u32, gamma: f64 },
+    /// Ramps up to `learning_rate` over `pct_start` of training, then anneals.
+    OneCycle {
+        #[serde(default = "default_pct_start")]
+        pct_start: f64,
+        #[serde(default = "default_div_factor")]
+        div_factor: f64,
+        #[serde(default = "default_final_div_factor")]
+        final_div_factor: f64,
+    },
+}
+
+impl Default for LrSchedule {
+    fn default() -> Self {
+        LrSchedule::Constant { warmup_epochs: 0 }
+    }
+}
+
+fn default_pct_start() -> f64 {
+    0.3
+}
+
+fn default_div_factor() -> f64 {
+    25.0
+}
+
+fn default_final_div_factor() -> f64 {
+    1e4
+}
+
+impl LrSchedule {
+    fn validate(&self, epochs: u32) -> Result<(), BackendError> {
+        match *self {
+            LrSchedule::Constant { warmup_epochs } | LrSchedule::Cosine { warmup_epochs, .. }
+                if warmup_epochs >= epochs =>
+            {
+                Err(BackendError::invalid_input(format!(
+                    "Warmup ({} epochs) must be shorter than training ({} epochs).",
+                    warmup_epochs, epochs
+                )))
+            }
+            LrSchedule::Cosine { min_lr_ratio, .. } if !(0.0..1.0).contains(&min_lr_ratio) => {
+                Err(BackendError::invalid_input("Cosine min_lr_ratio must be in [0, 1)."))
+            }
+            LrSchedule::Step { step_size, .. } if step_size == 0 => {
+                Err(BackendError::invalid_input("Step schedule step_size must be at least 1."))
+            }
+            LrSchedule::Step { gamma, .. } if !(gamma > 0.0 && gamma <= 1.0) => {
+                Err(BackendError::invalid_input("Step schedule gamma must be in (0, 1]."))
+            }
+            LrSchedule::OneCycle { pct_start, .. } if !(pct_start > 0.0 && pct_start < 1.0) => {
+                Err(BackendError::invalid_input("One-cycle pct_start must be in (0, 1)."))
+            }
+            LrSchedule::OneCycle { div_factor, final_div_factor, .. }
+                if div_factor < 1.0 || final_div_factor < 1.0 =>
+            {
+                Err(BackendError::invalid_input("One-cycle div factors must be at least 1."))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassWeightMode {
+    Auto,
+}
+
+/// Either `"auto"` or an explicit `{class_name: weight}` map.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum ClassWeights {
+    Mode(ClassWeightMode),
+    Explicit(BTreeMap<String, f64>),
+}
+
+/// Imbalance ratio above which validation suggests `class_weights: "auto"`.
+const IMBALANCE_WARNING_RATIO: f64 = 3.0;
+
+/// Result of validating a config: the derived values plus any non-fatal warnings.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TrainingConfigReport {
+    pub effective_batch_size: u32,
+    pub estimated_vram_mb: u64,
+    pub available_vram_mb: Option<u64>,
+    /// Seed the run will use, after `resolve_seed`.
+    pub seed: Option<u64>,
+    pub warnings: Vec<String>,
+}
+
+/// Outcome of comparing the seed recorded by the script against the requested one.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReproducibilityCheck {
+    pub requested_seed: Option<u64>,
+    pub recorded_seed: Option<u64>,
+    pub deterministic: bool,
+    pub matches: bool,
+    pub message: String,
+}
+
+pub const SUPPORTED_MODELS: &[&str] = &[
+    "resnet18",
+    "resnet50",
+    "efficientnet_b0",
+    "dcn",
+    "eva02",
+    "mobilenet_v3",
+    "vit_b_16",
+    "convnext",
+];
+
+// Effective batch sizes above this rarely help the small datasets this app targets
+// and usually need a learning-rate rescale to converge.
+const MAX_EFFECTIVE_BATCH_SIZE: u32 = 4096;
+
+/// Rough training footprint at 224x224 fp32: (fixed MB for weights, grads and
+/// optimizer state, MB of activations per sample in the batch).
+fn model_memory_profile(model: &str) -> (u64, u64) {
+    match model {
+        "resnet18" | "dcn" => (180, 40),
+        "resnet50" => (400, 110),
+        "efficientnet_b0" => (120, 90),
+        "mobilenet_v3" => (100, 45),
+        "convnext" => (450, 120),
+        "vit_b_16" => (1100, 150),
+        "eva02" => (1100, 170),
+        _ => (400, 110),
+    }
+}
+
+/// Estimated size of the saved weights; the fixed footprint holds them four
+/// times over (weights, gradients and Adam's two moments).
+pub fn estimate_weights_mb(model: &str) -> u64 {
+    model_memory_profile(model).0 / 4
+}
+
+/// Estimated VRAM needed to run one forward/backward pass at `batch_size`.
+/// Accumulation does not change this, only the per-step micro-batch does.
+pub fn estimate_vram_mb(model: &str, batch_size: u32) -> u64 {
+    let (fixed, per_sample) = model_memory_profile(model);
+    // CUDA context and allocator overhead
+    let overhead = 500;
+    overhead + fixed + per_sample * batch_size as u64
+}
+
+impl TrainingConfig {
+    /// Picks a seed for reproducible runs that did not specify one, so the value
+    /// is known up front and can be recorded before the script starts.
+    pub fn resolve_seed(&mut self) {
+        if self.reproducible && self.seed.is_none() {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0);
+            // Keep it within numpy's 32-bit seed range.
+            self.seed = Some(nanos % (u32::MAX as u64));
+        }
+    }
+
+    /// Replaces `class_weights: "auto"` with explicit inverse-frequency weights
+    /// computed from the dataset on disk.
+    pub fn resolve_class_weights(&mut self) -> Result<(), BackendError> {
+        if self.class_weights == Some(ClassWeights::Mode(ClassWeightMode::Auto)) {
+            let distribution = dataset::class_distribution(Path::new(&self.path))?;
+            self.class_weights = Some(ClassWeights::Explicit(distribution.balanced_weights()));
+        }
+        Ok(())
+    }
+
+    /// Resolves everything the script cannot decide on its own (seed, automatic
+    /// class weights) so the config recorded for a run is the one actually used.
+    pub fn prepare(&mut self) -> Result<(), BackendError> {
+        self.resolve_seed();
+        if let Some(device) = &self.device {
+            self.env.extend(gpu_monitor::device_env(device)?);
+        }
+        self.resolve_class_weights()
+    }
+
+    /// Warns when the dataset is imbalanced and the config does not compensate.
+    pub fn imbalance_warning(&self) -> Option<String> {
+        if self.class_weights.is_some() {
+            return None;
+        }
+        let distribution = dataset::class_distribution(Path::new(&self.path)).ok()?;
+        (distribution.imbalance_ratio > IMBALANCE_WARNING_RATIO).then(|| {
+            format!(
+                "The largest class has {:.1}x the images of the smallest; consider class_weights \"auto\".",
+                distribution.imbalance_ratio
+            )
+        })
+    }
+
+    /// Directory the script writes its outputs and run_manifest.json into.
+    pub fn output_dir(&self) -> PathBuf {
+        PathBuf::from(self.save_path.as_deref().unwrap_or(&self.path))
+    }
+
+    /// Reads run_manifest.json after a run and checks the recorded seed
+    /// against the one this config requested.
+    pub fn verify_reproducibility(&self) -> Result<ReproducibilityCheck, String> {
+        let manifest = run_manifest::load(&self.output_dir())?;
+
+        let repro = &manifest["reproducibility"];
+        let recorded_seed = repro["seed"].as_u64();
+        let deterministic = repro["deterministic"].as_bool().unwrap_or(false);
+
+        let (matches, message) = if !self.reproducible {
+            (true, "Run was not requested as reproducible.".to_string())
+        } else if !deterministic {
+            (false, "Run was requested as reproducible but the script did not enable deterministic mode.".to_string())
+        } else if recorded_seed != self.seed {
+            (
+                false,
+                format!(
+                    "Seed mismatch: requested {:?}, recorded {:?}. Results are not comparable.",
+                    self.seed, recorded_seed
+                ),
+            )
+        } else {
+            (true, format!("Run used seed {} in deterministic mode.", recorded_seed.unwrap_or_default()))
+        };
+
+        Ok(ReproducibilityCheck {
+            requested_seed: self.seed,
+            recorded_seed,
+            deterministic,
+            matches,
+            message,
+        })
+    }
+
+    /// Checks the config for hard errors and returns the derived report.
+    /// `available_vram_mb` is the total memory of the training GPU when known.
+    pub fn validate(&self, available_vram_mb: Option<u64>) -> Result<TrainingConfigReport, BackendError> {
+        if self.path.trim().is_empty() {
+            return Err(BackendError::invalid_input("Dataset path is required."));
+        }
+        let custom = self.architecture.as_ref().is_some_and(|a| a.name == self.model);
+        if !custom && !SUPPORTED_MODELS.contains(&self.model.as_str()) {
+            return Err(BackendError::invalid_input(format!("Unknown model type: {}", self.model)));
+        }
+        if self.epochs == 0 {
+            return Err(BackendError::invalid_input("Epochs must be at least 1."));
+        }
+        if self.batch_size == 0 {
+            return Err(BackendError::invalid_input("Batch size must be at least 1."));
+        }
+        if self.accumulation_steps == 0 {
+            return Err(BackendError::invalid_input("Accumulation steps must be at least 1."));
+        }
+        if !(self.learning_rate > 0.0 && self.learning_rate.is_finite()) {
+            return Err(BackendError::invalid_input("Learning rate must be a positive number."));
+        }
+        // numpy only accepts 32-bit seeds.
+        if let Some(seed) = self.seed.filter(|seed| *seed > u32::MAX as u64) {
+            return Err(BackendError::invalid_input(format!("Seed must be below 2^32, got {}.", seed)));
+        }
+
+        if let FreezePolicy::Staged { unfreeze_lr_factor, .. } = self.freeze_policy {
+            if !(unfreeze_lr_factor > 0.0 && unfreeze_lr_factor <= 1.0) {
+                return Err(BackendError::invalid_input("Unfreeze learning-rate factor must be in (0, 1]."));
+            }
+        }
+
+        self.lr_schedule.validate(self.epochs)?;
+        job_env::validate(&self.env)?;
+        self.limits.validate()?;
+        self.checkpoints.validate()?;
+        if let Some(device) = &self.device {
+            gpu_monitor::device_env(device)?;
+        }
+        if let Some(ClassWeights::Explicit(weights)) = &self.class_weights {
+            if let Some((name, w)) = weights.iter().find(|(_, w)| !(**w >= 0.0 && w.is_finite())) {
+                return Err(BackendError::invalid_input(format!(
+                    "Class weight for '{}' must be a non-negative number, got {}.",
+                    name, w
+                )));
+            }
+            // All-zero weights make the loss zero and the run learn nothing.
+            if !weights.is_empty() && !weights.values().any(|w| *w > 0.0) {
+                return Err(BackendError::invalid_input(
+                    "Class weights cannot all be zero; at least one must be positive.",
+                ));
+            }
+        }
+
+        if self.metric_plugins.len() != self.custom_metrics.len() {
+            return Err(BackendError::invalid_input("Custom metrics have not been resolved against installed plugins."));
+        }
+
+        let effective_batch_size = self
+            .batch_size
+            .checked_mul(self.accumulation_steps)
+            .ok_or_else(|| BackendError::invalid_input("Effective batch size overflows."))?;
+        if effective_batch_size > MAX_EFFECTIVE_BATCH_SIZE {
+            return Err(BackendError::invalid_input(format!(
+                "Effective batch size {} (batch size {} x {} accumulation steps) exceeds the maximum of {}.",
+                effective_batch_size, self.batch_size, self.accumulation_steps, MAX_EFFECTIVE_BATCH_SIZE
+            )));
+        }
+
+        let mut warnings = self.augmentation.validate()?;
+        let estimated_vram_mb = estimate_vram_mb(&self.model, self.batch_size);
+
+        if let Some(available) = available_vram_mb {
+            if estimated_vram_mb > available {
+                // Suggest the largest micro-batch that fits while keeping the same effective batch.
+                let (fixed, per_sample) = model_memory_profile(&self.model);
+                let fitting = available.saturating_sub(500 + fixed) / per_sample.max(1);
+                if fitting == 0 {
+                    warnings.push(format!(
+                        "{} needs about {} MB of VRAM even at batch size 1; only {} MB is available.",
+                        self.model, estimate_vram_mb(&self.model, 1), available
+                    ));
+                } else {
+                    let fitting = fitting as u32;
+                    let steps = effective_batch_size.div_ceil(fitting);
+                    warnings.push(format!(
+                        "Batch size {} needs about {} MB of VRAM but only {} MB is available. Use batch size {} with {} accumulation steps to keep an effective batch of ~{}.",
+                        self.batch_size, estimated_vram_mb, available, fitting, steps, fitting * steps
+                    ));
+                }
+            } else if self.accumulation_steps > 1 {
+                let full = estimate_vram_mb(&self.model, effective_batch_size);
+                if full <= available {
+                    warnings.push(format!(
+                        "An effective batch of {} fits in VRAM directly (~{} MB); accumulation only slows training down here.",
+                        effective_batch_size, full
+                    ));
+                }
+            }
+        }
+
+        if let FreezePolicy::Staged { freeze_epochs, .. } = self.freeze_policy {
+            if freeze_epochs == 0 {
+                warnings.push("Staged fine-tuning with 0 frozen epochs is the same as training all layers.".to_string());
+            } else if freeze_epochs >= self.epochs {
+                warnings.push(format!(
+                    "The backbone is frozen for {} epochs but training only runs {}; it will never be unfrozen.",
+                    freeze_epochs, self.epochs
+                ));
+            }
+        }
+        if self.freeze_policy != FreezePolicy::Frozen && self.model == "dcn" {
+            warnings.push("Unfreezing the DCN backbone also retrains the pretrained ResNet layers around the deformable blocks.".to_string());
+        }
+
+        if let LrSchedule::Step { step_size, .. } = self.lr_schedule {
+            if step_size >= self.epochs {
+                warnings.push(format!(
+                    "Step size {} is not shorter than the run ({} epochs); the LR never decays.",
+                    step_size, self.epochs
+                ));
+            }
+        }
+        if matches!(self.lr_schedule, LrSchedule::OneCycle { .. }) && self.patience < self.epochs {
+            warnings.push(
+                "Early stopping can end a one-cycle run before the annealing phase; consider raising patience.".to_string(),
+            );
+        }
+
+        if self.reproducible && self.num_workers != 0 {
+            warnings.push(
+                "Deterministic mode seeds each dataloader worker, but results can still differ across machines with different worker counts.".to_string(),
+            );
+        }
+
+        if self.accumulation_steps > 1 && self.batch_size < 8 {
+            warnings.push(
+                "Micro-batches below 8 make BatchNorm statistics noisy even with accumulation.".to_string(),
+            );
+        }
+
+        Ok(TrainingConfigReport {
+            effective_batch_size,
+            estimated_vram_mb,
+            available_vram_mb,
+            seed: self.seed,
+            warnings,
+        })
+    }
+
+    /// Builds the script.py argument list (without the script path itself).
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--path".to_string(),
+            self.path.clone(),
+            "--epochs".to_string(),
+            self.epochs.to_string(),
+            "--batch_size".to_string(),
+            self.batch_size.to_string(),
+            "--model".to_string(),
+            self.model.clone(),
+            "--learning_rate".to_string(),
+            self.learning_rate.to_string(),
+            "--accumulation_steps".to_string(),
+            self.accumulation_steps.to_string(),
+            "--num_workers".to_string(),
+            self.num_workers.to_string(),
+            "--patience".to_string(),
+            self.patience.to_string(),
+            "--freeze_mode".to_string(),
+            self.freeze_policy.mode().to_string(),
+        ];
+        if let Some(architecture) = &self.architecture {
+            args.push("--architecture".to_string());
+            args.push(serde_json::to_string(architecture).unwrap_or_default());
+        }
+        if let FreezePolicy::Staged { freeze_epochs, unfreeze_lr_factor } = self.freeze_policy {
+            args.push("--freeze_epochs".to_string());
+            args.push(freeze_epochs.to_string());
+            args.push("--unfreeze_lr_factor".to_string());
+            args.push(unfreeze_lr_factor.to_string());
+        }
+        if self.lr_schedule != LrSchedule::default() {
+            args.push("--lr_schedule".to_string());
+            args.push(serde_json::to_string(&self.lr_schedule).unwrap_or_default());
+        }
+        if self.augmentation != AugmentationPolicy::default() {
+            args.push("--augmentation".to_string());
+            args.push(serde_json::to_string(&self.augmentation).unwrap_or_default());
+        }
+        if let Some(ClassWeights::Explicit(weights)) = &self.class_weights {
+            args.push("--class_weights".to_string());
+            args.push(serde_json::to_string(weights).unwrap_or_default());
+        }
+        if !self.metric_plugins.is_empty() {
+            args.push("--custom_metrics".to_string());
+            args.push(serde_json::to_string(&self.metric_plugins).unwrap_or_default());
+        }
+        if self.reproducible {
+            args.push("--deterministic".to_string());
+        }
+        if let Some(seed) = self.seed {
+            args.push("--seed".to_string());
+            args.push(seed.to_string());
+        }
+        if let Some(id) = &self.experiment_id {
+            args.push("--experiment_id".to_string());
+            args.push(id.clone());
+        }
+        if let Some(save) = &self.save_path {
+            args.push("--save_path".to_string());
+            args.push(save.clone());
+        }
+        if let Some(resume) = &self.resume {
+            args.push("--resume".to_string());
+            args.push(resume.clone());
+        }
+        if self.checkpoints.every_epoch {
+            args.push("--epoch_checkpoints".to_string());
+        }
+        if self.zip_dataset {
+            args.push("--zip_dataset".to_string());
+        }
+        if self.only_zip {
+            args.push("--only_zip".to_string());
+        }
+        args
+    }
+}
+
+/// One probe run of batch_probe.py.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BatchProbeAttempt {
+    pub batch_size: u32,
+    pub fits: bool,
+    pub peak_memory_mb: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Outcome of `find_max_batch_size`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BatchSizeProbeReport {
+    pub device: Option<String>,
+    /// Largest batch size that completed a forward/backward pass.
+    pub max_batch_size: u32,
+    /// `max_batch_size` with headroom for dataloader and allocator fragmentation.
+    pub recommended_batch_size: u32,
+    /// Accumulation steps that keep the config's effective batch at the recommended size.
+    pub suggested_accumulation_steps: u32,
+    pub attempts: Vec<BatchProbeAttempt>,
+}
+
+/// Upper bound for probing; beyond this the dataset is usually the limit, not VRAM.
+pub const MAX_PROBE_BATCH_SIZE: u32 = 1024;
+
+const OOM_PATTERNS: &[&str] = &[
+    "CUDA out of memory",
+    "OutOfMemoryError",
+    "CUBLAS_STATUS_ALLOC_FAILED",
+    "CUDNN_STATUS_ALLOC_FAILED",
+    "HIP out of memory",
+    "MPS backend out of memory",
+];
+
+/// True when a failed probe's stderr looks like an allocation failure rather than a crash.
+pub fn is_out_of_memory(stderr: &str) -> bool {
+    OOM_PATTERNS.iter().any(|p| stderr.contains(p))
+}
+
+impl BatchSizeProbeReport {
+    pub fn new(
+        device: Option<String>,
+        max_batch_size: u32,
+        effective_batch_size: u32,
+        attempts: Vec<BatchProbeAttempt>,
+    ) -> Self {
+        // Keep ~10% headroom and prefer multiples of 8 for tensor-core friendly shapes.
+        let headroom = max_batch_size * 9 / 10;
+        let recommended_batch_size = if headroom >= 8 {
+            headroom / 8 * 8
+        } else {
+            headroom.max(1)
+        };
+        let suggested_accumulation_steps = effective_batch_size.div_ceil(recommended_batch_size).max(1);
+        Self {
+            device,
+            max_batch_size,
+            recommended_batch_size,
+            suggested_accumulation_steps,
+            attempts,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::app_settings::AppSettingsStore;
+use crate::events;
+use crate::shutdown;
+
+/// Update manifest of each channel; `{channel}` is `stable` or `beta`.
+/// Builds for a private mirror set `EPOQ_UPDATE_URL` at compile time.
+const DEFAULT_ENDPOINT: &str = "https://github.com/gitit24x7/EPOQ/releases/download/updater/{channel}.json";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Pre-releases, for trying fixes before they ship to everyone.
+    Beta,
+}
+
+impl UpdateChannel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UpdateInfo {
+    pub channel: UpdateChannel,
+    pub current_version: String,
+    pub available: bool,
+    /// Version offered, when one is available.
+    pub version: Option<String>,
+    /// Release notes.
+    pub notes: Option<String>,
+    pub published_at: Option<String>,
+}
+
+/// Published as `update://progress` while an update downloads.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UpdateProgress {
+    pub version: String,
+    pub downloaded: u64,
+    /// Null when the server does not say.
+    pub total: Option<u64>,
+}
+
+/// Managed state: the update found by the last check, installed by
+/// `install_update`.
+#[derive(Default)]
+pub struct Updates {
+    pending: Mutex<Option<Update>>,
+}
+
+/// The key update bundles are signed with, baked in at build time. Builds
+/// without one cannot verify updates and so never install them.
+fn public_key(app: &tauri::AppHandle) -> Option<String> {
+    option_env!("EPOQ_UPDATER_PUBKEY")
+        .map(String::from)
+        .or_else(|| {
+            let config = app.config().plugins.0.get("updater")?;
+            config["pubkey"].as_str().map(String::from)
+        })
+        .filter(|key| !key.trim().is_empty())
+}
+
+fn endpoint(channel: UpdateChannel) -> Result<tauri::Url, String> {
+    let template = option_env!("EPOQ_UPDATE_URL").unwrap_or(DEFAULT_ENDPOINT);
+    template.replace("{channel}", channel.as_str()).parse().map_err(|e| format!("Invalid update URL: {}", e))
+}
+
+fn channel(app: &tauri::AppHandle) -> UpdateChannel {
+    app.try_state::<Arc<AppSettingsStore>>().map(|s| s.get(app).update_channel).unwrap_or_default()
+}
+
+/// Asks the channel chosen in settings whether a newer version exists and
+/// remembers it for `install`.
+pub async fn check(app: &tauri::AppHandle, updates: &Updates) -> Result<UpdateInfo, String> {
+    let channel = channel(app);
+    let current_version = app.package_info().version.to_string();
+    let key = public_key(app).ok_or("This build has no update signing key, so it cannot install updates.")?;
+    let updater = app
+        .updater_builder()
+        .pubkey(key)
+        .endpoints(vec![endpoint(channel)?])
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Cannot set up the updater: {}", e))?;
+    let update = updater.check().await.map_err(|e| format!("Cannot check for updates: {}", e))?;
+    let info = UpdateInfo {
+        channel,
+        current_version,
+        available: update.is_some(),
+        version: update.as_ref().map(|u| u.version.clone()),
+        notes: update.as_ref().and_then(|u| u.body.clone()),
+        published_at: update.as_ref().and_then(|u| u.date).map(|date| date.to_string()),
+    };
+    if let Some(version) = &info.version {
+        tracing::info!(channel = channel.as_str(), "Update {} is available", version);
+    }
+    *updates.pending.lock().unwrap() = update;
+    Ok(info)
+}
+
+/// Downloads, verifies and installs the update found by the last check,
+/// publishing `update://progress`, then restarts the app. Refuses while jobs
+/// or sweeps run, since installing closes the app.
+pub async fn install(app: &tauri::AppHandle, updates: &Updates) -> Result<(), String> {
+    if !shutdown::active_work(app).is_empty() {
+        return Err("Jobs are still running; install the update once they finish.".to_string());
+    }
+    let update = updates.pending.lock().unwrap().take().ok_or("No update to install; check for updates first.")?;
+    let version = update.version.clone();
+    let mut progress = UpdateProgress { version: version.clone(), downloaded: 0, total: None };
+    let mut last = Instant::now();
+    let result = update
+        .download_and_install(
+            |chunk, total| {
+                progress.downloaded += chunk as u64;
+                progress.total = total;
+                if last.elapsed() >= PROGRESS_INTERVAL || total.is_some_and(|t| progress.downloaded >= t) {
+                    last = Instant::now();
+                    events::publish(app, "update://progress", None, progress.clone());
+                }
+            },
+            || tracing::info!("Update {} downloaded", version),
+        )
+        .await;
+    if let Err(e) = result {
+        // Checked again before the next attempt rather than reused.
+        return Err(format!("Cannot install the update: {}", e));
+    }
+    tracing::info!("Update installed; restarting");
+    app.restart()
+}
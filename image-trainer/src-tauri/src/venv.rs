@@ -0,0 +1,137 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::embedded_python;
+use crate::jobs::new_id;
+use crate::paths;
+use crate::python::{self, backend_script, run_interpreter_streaming, run_python};
+
+/// Hash of the requirements last installed, kept inside the venv so it goes
+/// away with it.
+const STAMP_FILE: &str = "epoq-requirements.sha256";
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct VenvStatus {
+    pub path: String,
+    pub exists: bool,
+    /// Python version the venv was created with.
+    pub version: Option<String>,
+    /// Whether the bundled `requirements.txt` was installed as it is now.
+    pub requirements_installed: bool,
+    /// Whether scripts run with the venv's interpreter.
+    pub active: bool,
+}
+
+/// The venv is shared by every profile, like installed plugins.
+pub fn venv_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::shared_data_dir(app)?.join("venv"))
+}
+
+pub fn venv_python(dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        dir.join("Scripts").join("python.exe")
+    } else {
+        dir.join("bin").join("python")
+    }
+}
+
+/// Reads the version from `pyvenv.cfg` instead of starting the interpreter.
+fn venv_version(dir: &Path) -> Option<String> {
+    let cfg = std::fs::read_to_string(dir.join("pyvenv.cfg")).ok()?;
+    cfg.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        matches!(key.trim(), "version" | "version_info").then(|| value.trim().to_string())
+    })
+}
+
+fn requirements_hash(app: &tauri::AppHandle) -> Result<(String, String), String> {
+    let path = backend_script(app, "requirements.txt")?;
+    let raw = std::fs::read(&path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    Ok((path, hex::encode(Sha256::digest(&raw))))
+}
+
+pub fn status(app: &tauri::AppHandle) -> Result<VenvStatus, String> {
+    let dir = venv_dir(app)?;
+    let interpreter = venv_python(&dir);
+    let exists = interpreter.is_file();
+    let interpreter = interpreter.to_string_lossy().to_string();
+    let requirements_installed = exists
+        && requirements_hash(app).is_ok_and(|(_, hash)| {
+            std::fs::read_to_string(dir.join(STAMP_FILE)).is_ok_and(|stamp| stamp.trim() == hash)
+        });
+    Ok(VenvStatus {
+        path: dir.to_string_lossy().to_string(),
+        exists,
+        version: exists.then(|| venv_version(&dir)).flatten(),
+        requirements_installed,
+        active: exists && python::load_settings(app).interpreter.as_deref() == Some(interpreter.as_str()),
+    })
+}
+
+/// Makes the venv's interpreter the one every script runs with.
+fn select(app: &tauri::AppHandle, interpreter: &Path) -> Result<(), String> {
+    let mut settings = python::load_settings(app);
+    settings.interpreter = Some(interpreter.to_string_lossy().to_string());
+    settings.conda_env = None;
+    python::save_settings(app, &settings)?;
+    embedded_python::reset();
+    Ok(())
+}
+
+/// Creates the venv with the current interpreter and switches scripts to it.
+/// An existing venv is kept and only selected again; a half-created one from
+/// an interrupted run is replaced.
+pub async fn create(app: &tauri::AppHandle) -> Result<VenvStatus, String> {
+    let dir = venv_dir(app)?;
+    let interpreter = venv_python(&dir);
+    if !interpreter.is_file() {
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).map_err(|e| format!("Cannot remove the incomplete venv: {}", e))?;
+        }
+        if let Some(parent) = dir.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let target = dir.to_string_lossy().to_string();
+        run_python(app, &["-m", "venv", &target])
+            .await
+            .map_err(|e| format!("Could not create the virtual environment: {}", e.message().trim()))?;
+        if !interpreter.is_file() {
+            return Err(format!("Python did not create {}.", interpreter.display()));
+        }
+    }
+    select(app, &interpreter)?;
+    status(app)
+}
+
+/// Runs `pip install -r requirements.txt` in the venv as job `job_id`, with
+/// pip's output streamed as `python://stdout` and `python://stderr` events.
+pub async fn install_requirements(app: &tauri::AppHandle, job_id: Option<String>) -> Result<VenvStatus, String> {
+    let dir = venv_dir(app)?;
+    let interpreter = venv_python(&dir);
+    if !interpreter.is_file() {
+        return Err("Create the virtual environment before installing requirements.".to_string());
+    }
+    let (requirements, hash) = requirements_hash(app)?;
+    let job_id = job_id.unwrap_or_else(|| new_id("pip"));
+    run_interpreter_streaming(
+        app,
+        &interpreter.to_string_lossy(),
+        &job_id,
+        &[
+            "-m",
+            "pip",
+            "install",
+            "--disable-pip-version-check",
+            // The bar redraws one line, which arrives as a single event at the end.
+            "--progress-bar",
+            "off",
+            "-r",
+            &requirements,
+        ],
+    )
+    .await?;
+    std::fs::write(dir.join(STAMP_FILE), hash).map_err(|e| e.to_string())?;
+    status(app)
+}
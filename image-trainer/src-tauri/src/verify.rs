@@ -0,0 +1,152 @@
+use image::error::ImageError;
+use image::{ImageFormat, ImageReader};
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::dataset;
+use crate::events;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    /// Zero bytes.
+    Empty,
+    /// Could not be opened or read.
+    Unreadable,
+    /// The data stops before the end of the image.
+    Truncated,
+    /// The data is not a valid image of its format.
+    Corrupt,
+    /// Decoding would exceed the memory limit, e.g. a decompression bomb.
+    TooLarge,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImageProblem {
+    pub path: String,
+    pub kind: ProblemKind,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct VerifyReport {
+    pub folder: String,
+    pub checked: usize,
+    pub ok: usize,
+    pub problems: Vec<ImageProblem>,
+    /// Files in formats this build cannot decode (PPM/PGM), left to Python.
+    pub unchecked: Vec<String>,
+}
+
+/// Published as `verify://progress` while images are decoded.
+#[derive(Debug, Clone, Serialize)]
+struct VerifyProgress {
+    done: usize,
+    total: usize,
+}
+
+enum Outcome {
+    Ok,
+    Unchecked,
+    Problem(ProblemKind, String),
+}
+
+/// Whether a JPEG or PNG ends with its end marker. Decoders tolerate a
+/// missing tail and fill the rest of the image with gray, PIL does not.
+fn has_end_marker(path: &Path, format: ImageFormat) -> std::io::Result<bool> {
+    let (marker, slack): (&[u8], usize) = match format {
+        ImageFormat::Jpeg => (&[0xFF, 0xD9], 64),
+        // IEND chunk type and CRC.
+        ImageFormat::Png => (&[0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82], 0),
+        _ => return Ok(true),
+    };
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let tail = (marker.len() + slack).min(len as usize);
+    file.seek(SeekFrom::End(-(tail as i64)))?;
+    let mut buffer = Vec::with_capacity(tail);
+    file.read_to_end(&mut buffer)?;
+    // Some cameras pad JPEGs after the end marker.
+    Ok(buffer.windows(marker.len()).any(|w| w == marker))
+}
+
+fn check(path: &Path) -> Outcome {
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.len() == 0 => return Outcome::Problem(ProblemKind::Empty, "The file is empty.".to_string()),
+        Ok(_) => {}
+        Err(e) => return Outcome::Problem(ProblemKind::Unreadable, e.to_string()),
+    }
+    let reader = match ImageReader::open(path).and_then(|r| r.with_guessed_format()) {
+        Ok(reader) => reader,
+        Err(e) => return Outcome::Problem(ProblemKind::Unreadable, e.to_string()),
+    };
+    let Some(format) = reader.format() else {
+        return Outcome::Problem(ProblemKind::Corrupt, "The content is not a recognized image format.".to_string());
+    };
+    match reader.decode() {
+        Ok(_) => {}
+        Err(ImageError::Unsupported(_)) => return Outcome::Unchecked,
+        Err(ImageError::Limits(e)) => return Outcome::Problem(ProblemKind::TooLarge, e.to_string()),
+        Err(ImageError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Outcome::Problem(ProblemKind::Truncated, e.to_string())
+        }
+        Err(ImageError::IoError(e)) => return Outcome::Problem(ProblemKind::Unreadable, e.to_string()),
+        Err(e) => return Outcome::Problem(ProblemKind::Corrupt, e.to_string()),
+    }
+    match has_end_marker(path, format) {
+        Ok(true) => Outcome::Ok,
+        Ok(false) => Outcome::Problem(
+            ProblemKind::Truncated,
+            format!("The {:?} data has no end marker; the file was cut short.", format),
+        ),
+        Err(e) => Outcome::Problem(ProblemKind::Unreadable, e.to_string()),
+    }
+}
+
+/// Decodes every image under `folder` on all cores and reports the ones
+/// training would fail on, with the reason.
+pub fn verify(app: &tauri::AppHandle, folder: &Path) -> Result<VerifyReport, String> {
+    if !folder.is_dir() {
+        return Err(format!("{} is not a folder.", folder.display()));
+    }
+    let images = dataset::list_images(folder, true)?;
+    let total = images.len();
+    let done = AtomicUsize::new(0);
+    let outcomes: Vec<(String, Outcome)> = images
+        .par_iter()
+        .map(|image| {
+            let outcome = check(image);
+            let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+            if finished % 100 == 0 || finished == total {
+                events::publish(app, "verify://progress", None, VerifyProgress { done: finished, total });
+            }
+            (image.to_string_lossy().to_string(), outcome)
+        })
+        .collect();
+
+    let mut report = VerifyReport {
+        folder: folder.to_string_lossy().to_string(),
+        checked: 0,
+        ok: 0,
+        problems: Vec::new(),
+        unchecked: Vec::new(),
+    };
+    for (path, outcome) in outcomes {
+        match outcome {
+            Outcome::Ok => {
+                report.checked += 1;
+                report.ok += 1;
+            }
+            Outcome::Unchecked => report.unchecked.push(path),
+            Outcome::Problem(kind, reason) => {
+                report.checked += 1;
+                report.problems.push(ImageProblem { path, kind, reason });
+            }
+        }
+    }
+    Ok(report)
+}
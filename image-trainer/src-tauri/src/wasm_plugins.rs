@@ -0,0 +1,256 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+use crate::plugins::MANIFEST_FILE;
+
+/// Hooks a WASM plugin may export. Each receives a JSON document and returns one.
+pub const HOOKS: &[&str] = &["transform_predictions", "report_section"];
+
+/// Host functions a plugin may import. Anything not declared is not linked,
+/// so a module importing it fails to instantiate.
+pub const CAPABILITIES: &[&str] = &["log"];
+
+/// Linear memory cap per plugin instance.
+#[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+/// Instruction budget per hook call; exhausting it traps instead of hanging the app.
+#[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+const FUEL_PER_CALL: u64 = 500_000_000;
+
+/// `plugin.toml` of a WASM plugin folder under `wasm_plugins/`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WasmPluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `.wasm` file inside the plugin folder.
+    pub module: String,
+    pub hooks: Vec<String>,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WasmPluginInfo {
+    pub dir: String,
+    pub manifest: Option<WasmPluginManifest>,
+    pub errors: Vec<String>,
+}
+
+/// Output of running a hook through every plugin that implements it.
+#[derive(Debug, Clone, Serialize, Default, JsonSchema)]
+pub struct HookResult {
+    /// For `transform_predictions`, the payload after every plugin ran in order.
+    pub payload: serde_json::Value,
+    /// For `report_section`, one section per plugin.
+    pub sections: Vec<serde_json::Value>,
+    /// Messages plugins wrote through the `log` capability.
+    pub logs: Vec<String>,
+    /// Plugins that failed; their output is skipped and the chain continues.
+    pub errors: Vec<String>,
+}
+
+pub fn wasm_plugins_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::shared_data_dir(app)?.join("wasm_plugins"))
+}
+
+impl WasmPluginManifest {
+    pub fn validate(&self, dir: &Path) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.name.is_empty() || !self.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            errors.push(format!("Plugin name '{}' must be letters, digits, '-' or '_'.", self.name));
+        }
+        let module = Path::new(&self.module);
+        if module.components().count() != 1 || module.extension().and_then(|e| e.to_str()) != Some("wasm") {
+            errors.push(format!("Module '{}' must be a .wasm file directly inside the plugin folder.", self.module));
+        } else if !dir.join(module).is_file() {
+            errors.push(format!("Module '{}' does not exist.", self.module));
+        }
+        if self.hooks.is_empty() {
+            errors.push("Plugin declares no hooks.".to_string());
+        }
+        for hook in &self.hooks {
+            if !HOOKS.contains(&hook.as_str()) {
+                errors.push(format!("Unknown hook '{}'. Supported: {}.", hook, HOOKS.join(", ")));
+            }
+        }
+        for cap in &self.capabilities {
+            if !CAPABILITIES.contains(&cap.as_str()) {
+                errors.push(format!(
+                    "Capability '{}' is not available to WASM plugins. Supported: {}.",
+                    cap,
+                    CAPABILITIES.join(", ")
+                ));
+            }
+        }
+        errors
+    }
+}
+
+pub fn discover(app: &tauri::AppHandle) -> Result<Vec<WasmPluginInfo>, String> {
+    let dir = wasm_plugins_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let plugins = std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .map(|path| {
+            let mut info = WasmPluginInfo {
+                dir: path.to_string_lossy().to_string(),
+                manifest: None,
+                errors: Vec::new(),
+            };
+            match std::fs::read_to_string(path.join(MANIFEST_FILE))
+                .map_err(|e| e.to_string())
+                .and_then(|raw| toml::from_str::<WasmPluginManifest>(&raw).map_err(|e| e.to_string()))
+            {
+                Ok(manifest) => {
+                    info.errors = manifest.validate(&path);
+                    info.manifest = Some(manifest);
+                }
+                Err(e) => info.errors.push(format!("Invalid {}: {}", MANIFEST_FILE, e)),
+            }
+            info
+        })
+        .collect();
+    Ok(plugins)
+}
+
+/// Runs `hook` through every valid plugin that declares it, in name order.
+pub fn run_hook(app: &tauri::AppHandle, hook: &str, payload: serde_json::Value) -> Result<HookResult, String> {
+    if !HOOKS.contains(&hook) {
+        return Err(format!("Unknown hook '{}'.", hook));
+    }
+    let mut plugins: Vec<(PathBuf, WasmPluginManifest)> = discover(app)?
+        .into_iter()
+        .filter(|p| p.errors.is_empty())
+        .filter_map(|p| p.manifest.map(|m| (PathBuf::from(p.dir), m)))
+        .filter(|(_, m)| m.hooks.iter().any(|h| h == hook))
+        .collect();
+    plugins.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+    let mut result = HookResult {
+        payload,
+        ..Default::default()
+    };
+    for (dir, manifest) in plugins {
+        let input = serde_json::to_vec(&result.payload).map_err(|e| e.to_string())?;
+        match runtime::call(&dir.join(&manifest.module), &manifest.capabilities, hook, &input) {
+            Ok((output, logs)) => {
+                result.logs.extend(logs.into_iter().map(|l| format!("[{}] {}", manifest.name, l)));
+                match serde_json::from_slice::<serde_json::Value>(&output) {
+                    Ok(value) if hook == "report_section" => result.sections.push(value),
+                    Ok(value) => result.payload = value,
+                    Err(e) => result.errors.push(format!("{}: returned invalid JSON: {}", manifest.name, e)),
+                }
+            }
+            Err(e) => result.errors.push(format!("{}: {}", manifest.name, e)),
+        }
+    }
+    Ok(result)
+}
+
+/// ABI: the module exports `memory`, `alloc(len) -> ptr`, and one function per
+/// hook taking `(ptr, len)` of the input JSON and returning `(ptr << 32) | len`
+/// of the output JSON. With the `log` capability it may import
+/// `epoq.log(ptr, len)`.
+#[cfg(feature = "wasm-plugins")]
+mod runtime {
+    use super::{FUEL_PER_CALL, MAX_MEMORY_BYTES};
+    use std::path::Path;
+    use wasmtime::{Caller, Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+    struct HostState {
+        limits: StoreLimits,
+        logs: Vec<String>,
+    }
+
+    pub fn call(module_path: &Path, capabilities: &[String], hook: &str, input: &[u8]) -> Result<(Vec<u8>, Vec<String>), String> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| e.to_string())?;
+        let module = Module::from_file(&engine, module_path).map_err(|e| e.to_string())?;
+
+        let mut linker: Linker<HostState> = Linker::new(&engine);
+        if capabilities.iter().any(|c| c == "log") {
+            linker
+                .func_wrap("epoq", "log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) else {
+                        return;
+                    };
+                    let data = memory.data(&caller);
+                    // Guest pointers are unsigned 32-bit offsets; a negative i32 is a large offset.
+                    let start = ptr as u32 as usize;
+                    let Some(end) = start.checked_add(len as u32 as usize).filter(|&end| end <= data.len()) else {
+                        return;
+                    };
+                    if let Some(bytes) = data.get(start..end) {
+                        let line = String::from_utf8_lossy(bytes).to_string();
+                        caller.data_mut().logs.push(line);
+                    }
+                })
+                .map_err(|e| e.to_string())?;
+        }
+
+        let mut store = Store::new(
+            &engine,
+            HostState {
+                limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).build(),
+                logs: Vec::new(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| format!("Failed to instantiate (undeclared import?): {}", e))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("Module does not export `memory`.")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| e.to_string())?;
+        let func = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, hook)
+            .map_err(|e| format!("Module does not export `{}`: {}", hook, e))?;
+
+        let len = i32::try_from(input.len()).map_err(|_| "Payload too large.")?;
+        let ptr = alloc.call(&mut store, len).map_err(|e| e.to_string())?;
+        memory
+            .write(&mut store, ptr as usize, input)
+            .map_err(|e| e.to_string())?;
+        let packed = func
+            .call(&mut store, (ptr, len))
+            .map_err(|e| format!("Hook trapped: {}", e))?;
+
+        let (out_ptr, out_len) = ((packed as u64 >> 32) as usize, (packed as u64 & 0xffff_ffff) as usize);
+        // Check the range against the guest memory before allocating for it.
+        let in_bounds = out_ptr.checked_add(out_len).is_some_and(|end| end <= memory.data_size(&store));
+        if !in_bounds {
+            return Err(format!("Hook returned an output range outside its memory ({} bytes at {}).", out_len, out_ptr));
+        }
+        let mut output = vec![0u8; out_len];
+        memory
+            .read(&store, out_ptr, &mut output)
+            .map_err(|e| e.to_string())?;
+
+        let logs = std::mem::take(&mut store.data_mut().logs);
+        Ok((output, logs))
+    }
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+mod runtime {
+    use std::path::Path;
+
+    pub fn call(_module_path: &Path, _capabilities: &[String], _hook: &str, _input: &[u8]) -> Result<(Vec<u8>, Vec<String>), String> {
+        Err("This build was compiled without the wasm-plugins feature.".to_string())
+    }
+}
@@ -0,0 +1,163 @@
+use notify::event::ModifyKind;
+use notify::{EventKind, RecursiveMode, Watcher};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::classes::ClassSpec;
+use crate::dataset;
+use crate::events;
+use crate::jobs::{new_id, unix_now};
+use crate::predictions;
+use crate::preprocessing;
+
+/// How long to wait after a file appears before classifying it, so copies
+/// in progress can finish; files arriving meanwhile join the same batch.
+const SETTLE: Duration = Duration::from_millis(750);
+
+/// A watched folder, as returned by `start_watch_folder`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WatchInfo {
+    pub watch_id: String,
+    pub path: String,
+    pub model_id: String,
+    pub started_at: u64,
+}
+
+/// Published as `watch://failed` when a new image could not be classified.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchFailure {
+    pub file: Option<String>,
+    pub message: String,
+}
+
+struct ActiveWatch {
+    info: WatchInfo,
+    /// Stops delivering events when dropped, which also ends the worker.
+    _watcher: notify::RecommendedWatcher,
+}
+
+/// Managed state holding the folders being watched.
+#[derive(Default)]
+pub struct WatchManager {
+    watches: Mutex<HashMap<String, ActiveWatch>>,
+}
+
+impl WatchManager {
+    /// Watches `path` for new images and classifies them with `model_id`.
+    /// Predictions are published as `watch://prediction` with the watch id
+    /// as job id, and stored and audited like any other prediction.
+    pub fn start(
+        &self,
+        app: &tauri::AppHandle,
+        path: &str,
+        model_id: &str,
+        classes: Option<ClassSpec>,
+    ) -> Result<WatchInfo, String> {
+        let folder = PathBuf::from(path);
+        if !folder.is_dir() {
+            return Err(format!("{} is not a folder.", path));
+        }
+        if self.watches.lock().unwrap().values().any(|w| w.info.path == path) {
+            return Err(format!("{} is already being watched.", path));
+        }
+        // Fail now rather than on the first image.
+        preprocessing::load(Path::new(model_id))?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            // Copies and saves show up as creations; moves into the folder as renames.
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) {
+                for path in event.paths.into_iter().filter(|p| dataset::is_image(p)) {
+                    let _ = tx.send(path);
+                }
+            }
+        })
+        .map_err(|e| format!("Cannot watch {}: {}", path, e))?;
+        watcher
+            .watch(&folder, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Cannot watch {}: {}", path, e))?;
+
+        let info = WatchInfo {
+            watch_id: new_id("watch"),
+            path: path.to_string(),
+            model_id: model_id.to_string(),
+            started_at: unix_now(),
+        };
+        tauri::async_runtime::spawn(classify_arrivals(app.clone(), info.clone(), classes, rx));
+        self.watches.lock().unwrap().insert(
+            info.watch_id.clone(),
+            ActiveWatch {
+                info: info.clone(),
+                _watcher: watcher,
+            },
+        );
+        Ok(info)
+    }
+
+    pub fn stop(&self, watch_id: &str) -> Result<(), String> {
+        self.watches
+            .lock()
+            .unwrap()
+            .remove(watch_id)
+            .map(|_| ())
+            .ok_or_else(|| format!("No watched folder with id {}", watch_id))
+    }
+
+    pub fn list(&self) -> Vec<WatchInfo> {
+        let mut watches: Vec<WatchInfo> = self.watches.lock().unwrap().values().map(|w| w.info.clone()).collect();
+        watches.sort_by_key(|w| w.started_at);
+        watches
+    }
+}
+
+/// Classifies images as they arrive until the watcher is dropped.
+async fn classify_arrivals(
+    app: tauri::AppHandle,
+    info: WatchInfo,
+    classes: Option<ClassSpec>,
+    mut rx: mpsc::UnboundedReceiver<PathBuf>,
+) {
+    // A file is reported again when it is renamed or rewritten in place.
+    let mut seen = HashSet::new();
+    while let Some(first) = rx.recv().await {
+        tokio::time::sleep(SETTLE).await;
+        let mut arrived = vec![first];
+        while let Ok(path) = rx.try_recv() {
+            arrived.push(path);
+        }
+        let images: Vec<String> = arrived
+            .into_iter()
+            .filter(|p| p.is_file() && seen.insert(p.clone()))
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        if images.is_empty() {
+            continue;
+        }
+        match predictions::predict(&app, &info.model_id, &images, classes.as_ref(), None).await {
+            Ok((_, records, failures)) => {
+                for record in records {
+                    events::publish(&app, "watch://prediction", Some(&info.watch_id), record);
+                }
+                for failure in failures {
+                    let (file, message) = failure.split_once(": ").unwrap_or(("", &failure));
+                    let failure = WatchFailure {
+                        file: Some(file.to_string()).filter(|f| !f.is_empty()),
+                        message: message.to_string(),
+                    };
+                    events::publish(&app, "watch://failed", Some(&info.watch_id), failure);
+                }
+            }
+            Err(message) => {
+                events::publish(&app, "watch://failed", Some(&info.watch_id), WatchFailure { file: None, message });
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use hmac::{Hmac, Mac};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::BackendError;
+use crate::events;
+use crate::i18n::{self, Localizer};
+use crate::jobs::{new_id, unix_now};
+use crate::paths;
+use crate::server_security;
+
+/// Delivery attempts per event, including the first one.
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before the first retry; doubled for each further retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const KEYRING_SERVICE: &str = "epoq-webhooks";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobLifecycle {
+    Started,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobLifecycle {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobLifecycle::Started => "job.started",
+            JobLifecycle::Succeeded => "job.succeeded",
+            JobLifecycle::Failed => "job.failed",
+            JobLifecycle::Cancelled => "job.cancelled",
+        }
+    }
+}
+
+/// Body shape expected by the receiving service.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// Plain JSON event, for custom receivers.
+    #[default]
+    Json,
+    /// Slack incoming webhook (`{"text": ...}`).
+    Slack,
+    /// Microsoft Teams incoming webhook (MessageCard).
+    Teams,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Webhook {
+    /// Assigned on first save when empty.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    pub events: Vec<JobLifecycle>,
+    /// When set, requests carry `X-EPOQ-Signature: sha256=<hex>`, an HMAC of
+    /// `"<timestamp>.<body>"` with this secret, and `X-EPOQ-Timestamp`.
+    /// Write-only: kept in the OS keyring and never returned. On save, `None`
+    /// keeps the current secret and an empty string removes it.
+    #[serde(default, skip_serializing)]
+    pub secret: Option<String>,
+    /// Whether a signing secret is stored for this webhook.
+    #[serde(default)]
+    pub secret_set: bool,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Published as `webhook://delivery` once a delivery succeeded or gave up.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WebhookDelivery {
+    pub webhook_id: String,
+    pub event: JobLifecycle,
+    pub attempts: u32,
+    pub success: bool,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// One-line description of a job event in the user's language.
+fn notification(l10n: &Localizer, event: JobLifecycle, job_id: &str, code: Option<i32>) -> String {
+    let job = ("job", job_id.into());
+    match event {
+        JobLifecycle::Started => l10n.text("job-started", &[job]),
+        JobLifecycle::Succeeded => l10n.text("job-succeeded", &[job]),
+        JobLifecycle::Failed => l10n.text(
+            "job-failed",
+            &[job, ("code", code.map_or_else(|| "none".to_string(), |c| c.to_string()).into())],
+        ),
+        JobLifecycle::Cancelled => l10n.text("job-cancelled", &[job]),
+    }
+}
+
+impl Webhook {
+    pub fn validate(&self) -> Result<(), BackendError> {
+        if self.name.trim().is_empty() {
+            return Err(BackendError::invalid_input("Webhook name is required."));
+        }
+        let url = reqwest::Url::parse(&self.url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        let local = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        if url.scheme() != "https" && !(url.scheme() == "http" && local) {
+            return Err(BackendError::invalid_input(
+                "Webhook URLs must use https (plain http is only allowed for localhost).",
+            ));
+        }
+        if self.events.is_empty() {
+            return Err(BackendError::invalid_input("Select at least one job event."));
+        }
+        if self.secret.as_deref().is_some_and(|s| !s.is_empty() && s.len() < 16) {
+            return Err(BackendError::invalid_input("Signing secrets must be at least 16 characters."));
+        }
+        Ok(())
+    }
+
+    fn body(&self, event: JobLifecycle, job_id: &str, code: Option<i32>, l10n: &Localizer) -> serde_json::Value {
+        let summary = notification(l10n, event, job_id, code);
+        match self.format {
+            WebhookFormat::Json => serde_json::json!({
+                "event": event.as_str(),
+                "job_id": job_id,
+                "exit_code": code,
+                "timestamp": unix_now(),
+                "message": summary,
+            }),
+            WebhookFormat::Slack => serde_json::json!({ "text": summary }),
+            WebhookFormat::Teams => serde_json::json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "summary": summary,
+                "themeColor": match event {
+                    JobLifecycle::Started => "0078D7",
+                    JobLifecycle::Succeeded => "2EB886",
+                    JobLifecycle::Failed => "D00000",
+                    JobLifecycle::Cancelled => "8A8886",
+                },
+                "title": summary,
+                "text": l10n.text("job-event-detail", &[("event", event.as_str().into()), ("job", job_id.into())]),
+            }),
+        }
+    }
+}
+
+fn webhooks_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::data_file(app, "webhooks.json")?)
+}
+
+fn keyring_entry(id: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, id).map_err(|e| format!("Keyring unavailable: {}", e))
+}
+
+/// Stores `secret` for webhook `id`, or removes the stored one when `None`.
+fn store_secret(id: &str, secret: Option<&str>) -> Result<(), String> {
+    let entry = keyring_entry(id)?;
+    match secret {
+        Some(secret) => {
+            entry.set_password(secret).map_err(|e| format!("Cannot store the secret in the keyring: {}", e))
+        }
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Cannot remove the secret from the keyring: {}", e)),
+        },
+    }
+}
+
+fn signing_secret(webhook: &Webhook) -> Option<String> {
+    if !webhook.secret_set {
+        return None;
+    }
+    match keyring_entry(&webhook.id).and_then(|entry| entry.get_password().map_err(|e| e.to_string())) {
+        Ok(secret) => Some(secret),
+        Err(e) => {
+            tracing::warn!("Signing secret of webhook {} unavailable: {}", webhook.id, e);
+            None
+        }
+    }
+}
+
+/// Configured webhooks. Secrets are never included; files written by older
+/// versions with plaintext secrets are migrated to the keyring here.
+pub fn load(app: &tauri::AppHandle) -> Result<Vec<Webhook>, String> {
+    let path = webhooks_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mut webhooks: Vec<Webhook> =
+        serde_json::from_str(&raw).map_err(|e| format!("Invalid webhook configuration: {}", e))?;
+    if webhooks.iter().any(|w| w.secret.is_some()) {
+        for webhook in &mut webhooks {
+            if let Some(secret) = webhook.secret.take().filter(|s| !s.is_empty()) {
+                store_secret(&webhook.id, Some(&secret))?;
+                webhook.secret_set = true;
+            }
+        }
+        save_all(app, &webhooks)?;
+    }
+    Ok(webhooks)
+}
+
+fn save_all(app: &tauri::AppHandle, webhooks: &[Webhook]) -> Result<(), String> {
+    let path = webhooks_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let raw = serde_json::to_string_pretty(webhooks).map_err(|e| e.to_string())?;
+    server_security::write_private(&path, raw.as_bytes())
+}
+
+/// Adds a webhook, or replaces the one with the same id. Returns the stored
+/// webhook, without its secret.
+pub fn save(app: &tauri::AppHandle, mut webhook: Webhook) -> Result<Webhook, String> {
+    webhook.validate()?;
+    if webhook.id.is_empty() {
+        webhook.id = new_id("webhook");
+    }
+    let mut webhooks = load(app)?;
+    let existing = webhooks.iter().position(|w| w.id == webhook.id);
+    webhook.secret_set = match webhook.secret.take() {
+        Some(secret) if secret.is_empty() => {
+            store_secret(&webhook.id, None)?;
+            false
+        }
+        Some(secret) => {
+            store_secret(&webhook.id, Some(&secret))?;
+            true
+        }
+        None => existing.is_some_and(|i| webhooks[i].secret_set),
+    };
+    match existing {
+        Some(i) => webhooks[i] = webhook.clone(),
+        None => webhooks.push(webhook.clone()),
+    }
+    save_all(app, &webhooks)?;
+    Ok(webhook)
+}
+
+pub fn remove(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let mut webhooks = load(app)?;
+    let before = webhooks.len();
+    webhooks.retain(|w| w.id != id);
+    if webhooks.len() == before {
+        return Err(format!("No webhook with id {}", id));
+    }
+    store_secret(id, None)?;
+    save_all(app, &webhooks)
+}
+
+fn sign(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Posts one event, retrying network errors, 429 and 5xx with exponential backoff.
+/// Other 4xx responses are not retried since resending would not change them.
+pub async fn deliver(
+    webhook: &Webhook,
+    event: JobLifecycle,
+    job_id: &str,
+    code: Option<i32>,
+    l10n: &Localizer,
+) -> WebhookDelivery {
+    let body = serde_json::to_vec(&webhook.body(event, job_id, code, l10n)).unwrap_or_default();
+    let secret = signing_secret(webhook);
+    let mut delivery = WebhookDelivery {
+        webhook_id: webhook.id.clone(),
+        event,
+        attempts: 0,
+        success: false,
+        status: None,
+        error: None,
+    };
+    let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            delivery.error = Some(e.to_string());
+            return delivery;
+        }
+    };
+
+    let mut delay = RETRY_BASE_DELAY;
+    while delivery.attempts < MAX_ATTEMPTS {
+        if delivery.attempts > 0 {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+        delivery.attempts += 1;
+
+        let timestamp = unix_now();
+        let mut request = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-EPOQ-Event", event.as_str())
+            .header("X-EPOQ-Timestamp", timestamp.to_string())
+            .body(body.clone());
+        if let Some(secret) = &secret {
+            request = request.header("X-EPOQ-Signature", sign(secret, timestamp, &body));
+        }
+
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                delivery.status = Some(status.as_u16());
+                if status.is_success() {
+                    delivery.success = true;
+                    delivery.error = None;
+                    break;
+                }
+                delivery.error = Some(format!("HTTP {}", status));
+                if !(status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                    break;
+                }
+            }
+            Err(e) => delivery.error = Some(e.to_string()),
+        }
+    }
+    delivery
+}
+
+/// Fires every enabled webhook subscribed to `event` in the background.
+pub fn notify(app: &tauri::AppHandle, event: JobLifecycle, job_id: &str, code: Option<i32>) {
+    let webhooks = match load(app) {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            tracing::warn!("Skipping webhooks: {}", e);
+            return;
+        }
+    };
+    let l10n = i18n::localizer(app);
+    for webhook in webhooks.into_iter().filter(|w| w.enabled && w.events.contains(&event)) {
+        let app = app.clone();
+        let job_id = job_id.to_string();
+        let l10n = l10n.clone();
+        tauri::async_runtime::spawn(async move {
+            let delivery = deliver(&webhook, event, &job_id, code, &l10n).await;
+            events::publish(&app, "webhook://delivery", Some(&job_id), delivery);
+        });
+    }
+}